@@ -1,19 +1,24 @@
-use crate::{BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, Opcode, Prefix, WitnessProgram};
+use crate::{
+    tweak_public_key, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, Opcode, Prefix,
+    WitnessProgram,
+};
 use anychain_core::libsecp256k1;
 use anychain_core::{
-    crypto::{checksum, hash160},
+    crypto::{hash160, sha256},
+    encoding::{base58check_decode, base58check_encode, segwit_decode, segwit_encode},
     Address, AddressError,
 };
-use anychain_core::{no_std::*, PublicKey};
+use anychain_core::{hex, no_std::*, PublicKey};
 
-use base58::{FromBase58, ToBase58};
-use bech32::{self, u5, FromBase32, ToBase32, Variant};
+use bech32::{self, u5, FromBase32, ToBase32};
 use core::hash::Hash;
 use core::{fmt, marker::PhantomData, str::FromStr};
-use sha2::{Digest, Sha256};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
-/// Represents a Bitcoin address
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Represents a Bitcoin address. Addresses compare by their encoding only: a
+/// base58 P2SH address may be P2SH or P2SH_P2WPKH depending on how it was
+/// built, while its string and scriptPubKey are the same.
+#[derive(Debug, Clone)]
 pub struct BitcoinAddress<N: BitcoinNetwork> {
     /// The Bitcoin address
     address: String,
@@ -23,10 +28,7 @@ pub struct BitcoinAddress<N: BitcoinNetwork> {
     _network: PhantomData<N>,
 }
 
-pub static BASE32_ENCODE_TABLE: [u8; 32] = [
-    b'q', b'p', b'z', b'r', b'y', b'9', b'x', b'8', b'g', b'f', b'2', b't', b'v', b'd', b'w', b'0',
-    b's', b'3', b'j', b'n', b'5', b'4', b'k', b'h', b'c', b'e', b'6', b'm', b'u', b'a', b'7', b'l',
-];
+pub static BASE32_ENCODE_TABLE: [u8; 32] = *b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
 
 pub static BASE32_DECODE_TABLE: [i8; 128] = [
     -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
@@ -116,6 +118,7 @@ impl<N: BitcoinNetwork> Address for BitcoinAddress<N> {
     type SecretKey = libsecp256k1::SecretKey;
     type Format = BitcoinFormat;
     type PublicKey = BitcoinPublicKey<N>;
+    type Network = N;
 
     /// Returns the address corresponding to the given Bitcoin private key.
     fn from_secret_key(
@@ -136,45 +139,39 @@ impl<N: BitcoinNetwork> Address for BitcoinAddress<N> {
                 String::from("non-script"),
                 String::from("p2wsh address"),
             )),
+            BitcoinFormat::P2SH => Err(AddressError::IncompatibleFormats(
+                String::from("non-script"),
+                String::from("p2sh address"),
+            )),
             BitcoinFormat::P2SH_P2WPKH => Self::p2sh_p2wpkh(public_key),
             BitcoinFormat::Bech32 => Self::bech32(public_key),
             BitcoinFormat::CashAddr => Self::cash_addr(public_key),
         }
     }
+
+    /// Returns the network of the address.
+    fn network(&self) -> N {
+        N::default()
+    }
+
+    /// Returns the format of the address.
+    fn format(&self) -> BitcoinFormat {
+        BitcoinAddress::format(self)
+    }
 }
 
 impl<N: BitcoinNetwork> BitcoinAddress<N> {
     /// Returns a P2PKH address from a given Bitcoin public key.
     pub fn p2pkh(public_key: &<Self as Address>::PublicKey) -> Result<Self, AddressError> {
-        let mut data = [0u8; 25];
-        data[0] = N::to_address_prefix(BitcoinFormat::P2PKH)?.version();
-        data[1..21].copy_from_slice(&hash160(&public_key.serialize()));
-
-        let checksum = &checksum(&data[..21])[..4];
-        data[21..].copy_from_slice(checksum);
-
-        Ok(Self {
-            address: data.to_base58(),
-            format: BitcoinFormat::P2PKH,
-            _network: PhantomData,
-        })
+        Self::from_hash160(&hash160(&public_key.serialize()), BitcoinFormat::P2PKH)
     }
 
     // Returns a P2WSH address in Bech32 format from a given Bitcoin script
     pub fn p2wsh(original_script: &[u8]) -> Result<Self, AddressError> {
-        let script = Sha256::digest(original_script).to_vec();
-
-        // Organize as a hash
-        let v = N::to_address_prefix(BitcoinFormat::P2WSH)?.version();
-        let version = u5::try_from_u8(v)?;
-
-        let mut data = vec![version];
-
-        // Get the SHA256 hash of the script
-        data.extend_from_slice(&script.to_vec().to_base32());
-
-        let prefix = N::to_address_prefix(BitcoinFormat::Bech32)?.prefix();
-        let bech32 = bech32::encode(&prefix, data, Variant::Bech32)?;
+        let script = sha256(original_script);
+        let version = N::to_address_prefix(BitcoinFormat::P2WSH)?.version()?;
+        let prefix = N::to_address_prefix(BitcoinFormat::Bech32)?.prefix()?;
+        let bech32 = segwit_encode(&prefix, version, &script)?;
 
         Ok(Self {
             address: bech32,
@@ -183,32 +180,25 @@ impl<N: BitcoinNetwork> BitcoinAddress<N> {
         })
     }
 
+    /// Returns a legacy P2SH address from a given redeem script.
+    pub fn p2sh(redeem_script: &[u8]) -> Result<Self, AddressError> {
+        Self::from_hash160(&hash160(redeem_script), BitcoinFormat::P2SH)
+    }
+
     /// Returns a P2SH_P2WPKH address from a given Bitcoin public key.
     pub fn p2sh_p2wpkh(public_key: &<Self as Address>::PublicKey) -> Result<Self, AddressError> {
-        let mut data = [0u8; 25];
-        data[0] = N::to_address_prefix(BitcoinFormat::P2SH_P2WPKH)?.version();
-        data[1..21].copy_from_slice(&hash160(&Self::create_redeem_script(public_key)));
-
-        let checksum = &checksum(&data[..21])[..4];
-        data[21..].copy_from_slice(checksum);
-
-        Ok(Self {
-            address: data.to_base58(),
-            format: BitcoinFormat::P2SH_P2WPKH,
-            _network: PhantomData,
-        })
+        public_key.check_segwit(&BitcoinFormat::P2SH_P2WPKH)?;
+        Self::from_hash160(
+            &hash160(&Self::create_redeem_script(public_key)),
+            BitcoinFormat::P2SH_P2WPKH,
+        )
     }
 
     /// Returns a Bech32 address from a given Bitcoin public key.
     pub fn bech32(public_key: &<Self as Address>::PublicKey) -> Result<Self, AddressError> {
-        let data = [
-            vec![u5::try_from_u8(0)?], // version byte: 0
-            hash160(&public_key.serialize()).to_base32(),
-        ]
-        .concat();
-
-        let prefix = N::to_address_prefix(BitcoinFormat::Bech32)?.prefix();
-        let bech32 = bech32::encode(&prefix, data, Variant::Bech32)?;
+        public_key.check_segwit(&BitcoinFormat::Bech32)?;
+        let prefix = N::to_address_prefix(BitcoinFormat::Bech32)?.prefix()?;
+        let bech32 = segwit_encode(&prefix, 0, &hash160(&public_key.serialize()))?;
 
         Ok(Self {
             address: bech32,
@@ -217,9 +207,40 @@ impl<N: BitcoinNetwork> BitcoinAddress<N> {
         })
     }
 
+    /// Returns a P2TR address in Bech32m format, paying to the taproot output
+    /// key of a given Bitcoin public key and an optional script tree
+    /// 'merkle_root'. Without a merkle root this is the BIP86 key path address.
+    pub fn p2tr(
+        public_key: &<Self as Address>::PublicKey,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<Self, AddressError> {
+        let internal_key: [u8; 32] = public_key.to_secp256k1_public_key().serialize_compressed()
+            [1..]
+            .try_into()
+            .unwrap();
+        let output_key = tweak_public_key(&internal_key, merkle_root)
+            .ok_or_else(|| AddressError::Message("invalid taproot tweak".into()))?;
+
+        Self::from_witness_program(&WitnessProgram {
+            version: 1,
+            program: output_key.to_vec(),
+        })
+    }
+
+    /// Returns a P2PKH address in CashAddr format from a given Bitcoin public key.
     pub fn cash_addr(public_key: &<Self as Address>::PublicKey) -> Result<Self, AddressError> {
-        let mut payload = vec![0u8]; // payload starts with version byte: 0
-        payload.extend(&hash160(&public_key.serialize()));
+        Self::cash_addr_from_hash(CASH_ADDR_P2PKH, &hash160(&public_key.serialize()))
+    }
+
+    /// Returns a P2SH address in CashAddr format from a given redeem script.
+    pub fn cash_addr_p2sh(redeem_script: &[u8]) -> Result<Self, AddressError> {
+        Self::cash_addr_from_hash(CASH_ADDR_P2SH, &hash160(redeem_script))
+    }
+
+    /// Returns a CashAddr address of the given type byte from a 20-byte hash.
+    fn cash_addr_from_hash(version: u8, hash: &[u8]) -> Result<Self, AddressError> {
+        let mut payload = vec![version];
+        payload.extend(hash);
 
         let payload: Vec<u8> = payload
             .to_base32()
@@ -228,7 +249,7 @@ impl<N: BitcoinNetwork> BitcoinAddress<N> {
             .collect();
 
         let payload = String::from_utf8(payload)?;
-        let prefix = N::to_address_prefix(BitcoinFormat::CashAddr)?.prefix();
+        let prefix = N::to_address_prefix(BitcoinFormat::CashAddr)?.prefix()?;
         let checksum = compute_checksum_bch(&payload, &prefix)?;
 
         Ok(Self {
@@ -238,6 +259,105 @@ impl<N: BitcoinNetwork> BitcoinAddress<N> {
         })
     }
 
+    /// Returns the type byte and the hash encoded in a CashAddr address.
+    pub fn cash_addr_payload(&self) -> Result<(u8, Vec<u8>), AddressError> {
+        if self.format != BitcoinFormat::CashAddr {
+            return Err(AddressError::IncompatibleFormats(
+                self.format.to_string(),
+                BitcoinFormat::CashAddr.to_string(),
+            ));
+        }
+        cash_addr_payload(&self.address)
+    }
+
+    /// Returns the address paid by the given scriptPubKey. This is the inverse
+    /// of `create_script_pub_key`, supporting P2PKH, P2SH, P2WPKH, P2WSH and P2TR.
+    /// The redeem script of a P2SH output is unknown, so its format is P2SH
+    /// rather than P2SH_P2WPKH.
+    pub fn from_script_pub_key(script_pub_key: &[u8]) -> Result<Self, AddressError> {
+        match script_pub_key {
+            // OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+            [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+                Self::from_hash160(hash, BitcoinFormat::P2PKH)
+            }
+            // OP_HASH160 <20 bytes> OP_EQUAL
+            [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => {
+                Self::from_hash160(hash, BitcoinFormat::P2SH)
+            }
+            // OP_n <2 to 40 bytes>
+            [version @ (0x00 | 0x51..=0x60), size, program @ ..]
+                if *size as usize == program.len() =>
+            {
+                let version = match version {
+                    0x00 => 0,
+                    v => v - 0x50,
+                };
+                let program = WitnessProgram::new(&[&[version, *size], program].concat())?;
+                Self::from_witness_program(&program)
+            }
+            _ => Err(AddressError::InvalidAddress(format!(
+                "unrecognized script pub key {}",
+                hex::encode(script_pub_key)
+            ))),
+        }
+    }
+
+    /// Returns a base58 encoded address of the given format from a 20-byte hash.
+    fn from_hash160(hash: &[u8], format: BitcoinFormat) -> Result<Self, AddressError> {
+        let version = N::to_address_prefix(format.clone())?.version()?;
+
+        Ok(Self {
+            address: base58check_encode(version, hash),
+            format,
+            _network: PhantomData,
+        })
+    }
+
+    /// Returns a native segwit address from a witness program, encoded in
+    /// Bech32 for witness version 0 and in Bech32m for versions 1 to 16 (BIP350).
+    pub fn from_witness_program(program: &WitnessProgram) -> Result<Self, AddressError> {
+        program.validate()?;
+
+        let format = match (program.version, program.program.len()) {
+            (0, 32) => BitcoinFormat::P2WSH,
+            _ => BitcoinFormat::Bech32,
+        };
+
+        let prefix = N::to_address_prefix(BitcoinFormat::Bech32)?.prefix()?;
+        let bech32 = segwit_encode(&prefix, program.version, &program.program)?;
+
+        Ok(Self {
+            address: bech32,
+            format,
+            _network: PhantomData,
+        })
+    }
+
+    /// Returns the P2SH address re-encoded with the current version byte of
+    /// network N if it is encoded with the deprecated one, e.g. a Litecoin
+    /// address starting with '3' as the matching address starting with 'M'.
+    /// Other addresses are returned unchanged.
+    pub fn upgrade_p2sh(&self) -> Result<Self, AddressError> {
+        if matches!(
+            self.format,
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH
+        ) {
+            let (version, hash) = base58check_decode(&self.address)?;
+            if N::DEPRECATED_P2SH_VERSION == Some(version) {
+                return Self::from_hash160(&hash, self.format.clone());
+            }
+        }
+        Ok(self.clone())
+    }
+
+    /// Returns the script hash of the address used by Electrum servers to
+    /// subscribe to its history.
+    pub fn electrum_script_hash(&self) -> Result<String, AddressError> {
+        let script_pub_key = crate::create_script_pub_key(self)
+            .map_err(|error| AddressError::Message(error.to_string()))?;
+        Ok(crate::electrum_script_hash(&script_pub_key))
+    }
+
     /// Returns the format of the Bitcoin address.
     pub fn format(&self) -> BitcoinFormat {
         self.format.clone()
@@ -252,6 +372,61 @@ impl<N: BitcoinNetwork> BitcoinAddress<N> {
     }
 }
 
+/// The CashAddr type byte of an address paying to a public key hash
+pub const CASH_ADDR_P2PKH: u8 = 0x00;
+
+/// The CashAddr type byte of an address paying to a script hash
+pub const CASH_ADDR_P2SH: u8 = 0x08;
+
+/// Returns the type byte and the hash of a CashAddr 'address', with or
+/// without its prefix. The checksum is not verified.
+fn cash_addr_payload(address: &str) -> Result<(u8, Vec<u8>), AddressError> {
+    let payload = address.rsplit(':').next().unwrap_or_default();
+    if payload.len() < 8 {
+        return Err(AddressError::InvalidAddress(address.to_owned()));
+    }
+
+    // trim the checksum
+    let payload = payload.as_bytes()[..payload.len() - 8]
+        .iter()
+        .map(|byte| match BASE32_DECODE_TABLE.get(*byte as usize) {
+            Some(val) if *val != -1 => Ok(u5::try_from_u8(*val as u8)?),
+            _ => Err(AddressError::InvalidAddress(address.to_owned())),
+        })
+        .collect::<Result<Vec<u5>, AddressError>>()?;
+    let payload = Vec::<u8>::from_base32(&payload)?;
+
+    match payload.split_first() {
+        Some((&version, hash))
+            if (version == CASH_ADDR_P2PKH || version == CASH_ADDR_P2SH) && hash.len() == 20 =>
+        {
+            Ok((version, hash.to_vec()))
+        }
+        _ => Err(AddressError::InvalidAddress(address.to_owned())),
+    }
+}
+
+/// Returns true if 'address' starts with the bech32 prefix of network N
+fn is_network_bech32<N: BitcoinNetwork>(address: &str) -> bool {
+    match N::to_address_prefix(BitcoinFormat::Bech32) {
+        Ok(Prefix::AddressPrefix(hrp)) => address.starts_with(&format!("{}1", hrp)),
+        _ => false,
+    }
+}
+
+/// Returns the format of the base58 version byte 'version', looking it up in
+/// the prefixes of network N before the well known ones
+fn base58_format<N: BitcoinNetwork>(version: Prefix) -> Result<BitcoinFormat, AddressError> {
+    for format in [BitcoinFormat::P2PKH, BitcoinFormat::P2SH_P2WPKH] {
+        if let Ok(Prefix::Version(v)) = N::to_address_prefix(format.clone()) {
+            if matches!(version, Prefix::Version(version) if version == v) {
+                return Ok(format);
+            }
+        }
+    }
+    BitcoinFormat::from_address_prefix(version)
+}
+
 impl<N: BitcoinNetwork> FromStr for BitcoinAddress<N> {
     type Err = AddressError;
 
@@ -280,6 +455,9 @@ impl<N: BitcoinNetwork> FromStr for BitcoinAddress<N> {
                 ));
             }
 
+            // check if the payload holds a supported type and hash.
+            let _ = cash_addr_payload(address)?;
+
             Ok(BitcoinAddress {
                 address: address.to_string(),
                 format: BitcoinFormat::CashAddr,
@@ -289,20 +467,16 @@ impl<N: BitcoinNetwork> FromStr for BitcoinAddress<N> {
             || address.starts_with("tb1")
             || address.starts_with("ltc1")
             || address.starts_with("tltc1")
+            || is_network_bech32::<N>(address)
         {
             // we are processing an address in Bech32 format
-            let (hrp, data, _) = bech32::decode(address)?;
-
-            if data.is_empty() {
-                return Err(AddressError::InvalidAddress(address.to_owned()));
-            }
+            // BIP350: witness version 0 takes the bech32 checksum, versions 1 to 16
+            // the bech32m checksum. The other combination is rejected.
+            let (hrp, version, mut program) = segwit_decode(address)?;
 
             // check if the address prefix corresponds to the correct network.
             let _ = N::from_address_prefix(Prefix::from_prefix(&hrp))?;
 
-            let version = data[0].to_u8();
-            let mut program = Vec::from_base32(&data[1..])?;
-
             let mut data = vec![version, program.len() as u8];
             data.append(&mut program);
 
@@ -326,37 +500,25 @@ impl<N: BitcoinNetwork> FromStr for BitcoinAddress<N> {
 
             if has_uppercase(address) {
                 // we are processing an address in p2pkh or p2sh_p2wpkh format
-                let data = address.from_base58()?;
+                let (version_byte, hash) = base58check_decode(address)?;
 
-                if data.len() != 25 {
-                    return Err(AddressError::InvalidByteLength(data.len()));
+                if hash.len() != 20 {
+                    return Err(AddressError::InvalidByteLength(hash.len() + 5));
                 }
 
-                let version = Prefix::from_version(data[0]);
+                let version = Prefix::from_version(version_byte);
 
                 // check if the address prefix corresponds to the correct network
                 let _ = N::from_address_prefix(version.clone())?;
 
-                let format = BitcoinFormat::from_address_prefix(version)?;
+                let format = base58_format::<N>(version)?;
 
-                // check if the payload produces the provided checksum
                 match format {
-                    BitcoinFormat::P2PKH | BitcoinFormat::P2SH_P2WPKH => {
-                        let checksum_gen = &checksum(&data[..21])[..4];
-                        let checksum_provided = &data[21..];
-                        if *checksum_gen != *checksum_provided {
-                            return Err(AddressError::InvalidChecksum(
-                                [data[..21].to_vec(), checksum_gen.to_vec()]
-                                    .concat()
-                                    .to_base58(),
-                                address.to_string(),
-                            ));
-                        }
-                    }
+                    BitcoinFormat::P2PKH | BitcoinFormat::P2SH_P2WPKH => {}
                     _ => {
                         return Err(AddressError::Message(format!(
                             "Unrecognized version byte {}",
-                            data[0],
+                            version_byte,
                         )))
                     }
                 }
@@ -368,7 +530,7 @@ impl<N: BitcoinNetwork> FromStr for BitcoinAddress<N> {
                 })
             } else {
                 // we are processing a bitcoin cash address in CashAddr format without an explicit prefix
-                let prefix = N::to_address_prefix(BitcoinFormat::CashAddr)?.prefix();
+                let prefix = N::to_address_prefix(BitcoinFormat::CashAddr)?.prefix()?;
 
                 if address.len() != 42 {
                     return Err(AddressError::InvalidCharacterLength(address.len()));
@@ -402,6 +564,45 @@ impl<N: BitcoinNetwork> fmt::Display for BitcoinAddress<N> {
     }
 }
 
+impl<N: BitcoinNetwork> PartialEq for BitcoinAddress<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<N: BitcoinNetwork> Eq for BitcoinAddress<N> {}
+
+impl<N: BitcoinNetwork> PartialOrd for BitcoinAddress<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: BitcoinNetwork> Ord for BitcoinAddress<N> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.address.cmp(&other.address)
+    }
+}
+
+impl<N: BitcoinNetwork> Hash for BitcoinAddress<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.address.hash(state)
+    }
+}
+
+impl<N: BitcoinNetwork> Serialize for BitcoinAddress<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.address)
+    }
+}
+
+impl<'de, N: BitcoinNetwork> Deserialize<'de> for BitcoinAddress<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        Self::from_str(&address).map_err(D::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -743,6 +944,74 @@ mod tests {
         }
     }
 
+    mod bech32m_mainnet {
+        use super::*;
+
+        type N = Bitcoin;
+
+        // witness version 1 to 16 addresses from BIP350
+        const VALID: [&str; 3] = [
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+            "bc1sw50qgdz25j",
+        ];
+
+        const INVALID: [&str; 3] = [
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd", // bech32 checksum for witness version 1
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL", // bech32 checksum for witness version 16
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh", // bech32m checksum for witness version 0
+        ];
+
+        #[test]
+        fn from_invalid_address() {
+            INVALID.iter().for_each(|invalid_bech32m| {
+                assert!(BitcoinAddress::<N>::from_str(invalid_bech32m).is_err());
+            });
+        }
+
+        #[test]
+        fn from_str() {
+            VALID.iter().for_each(|address| {
+                test_from_str::<N>(address, &BitcoinFormat::Bech32);
+            });
+        }
+
+        #[test]
+        fn round_trip() {
+            VALID.iter().for_each(|address| {
+                let address = BitcoinAddress::<N>::from_str(address).unwrap();
+                let script_pub_key = crate::create_script_pub_key(&address).unwrap();
+                assert_eq!(
+                    address.to_string(),
+                    BitcoinAddress::<N>::from_script_pub_key(&script_pub_key)
+                        .unwrap()
+                        .to_string()
+                );
+            });
+        }
+
+        #[test]
+        fn p2tr() {
+            // the first receiving address of the BIP86 test vectors
+            let internal_key = libsecp256k1::PublicKey::parse_slice(
+                &hex::decode("02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                    .unwrap(),
+                None,
+            )
+            .unwrap();
+            let public_key = BitcoinPublicKey::<N>::from_secp256k1_public_key(internal_key, true);
+            let address = BitcoinAddress::<N>::p2tr(&public_key, None).unwrap();
+            assert_eq!(
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                address.to_string()
+            );
+            assert_eq!(
+                "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+                hex::encode(crate::create_script_pub_key(&address).unwrap())
+            );
+        }
+    }
+
     mod bech32_testnet {
         use super::*;
 
@@ -849,6 +1118,169 @@ mod tests {
         }
     }
 
+    mod cash_addr_mainnet {
+        use super::*;
+
+        type N = BitcoinCash;
+
+        // the same hash as P2PKH and P2SH addresses, from the CashAddr specification
+        const P2PKH: &str = "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a";
+        const P2SH: &str = "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq";
+        const HASH: &str = "76a04053bda0a88bda5177b86a15c3b29f559873";
+
+        #[test]
+        fn from_str() {
+            test_from_str::<N>(P2PKH, &BitcoinFormat::CashAddr);
+            test_from_str::<N>(P2SH, &BitcoinFormat::CashAddr);
+        }
+
+        #[test]
+        fn from_invalid_address() {
+            // a valid checksum over an unsupported type byte
+            let invalid = "bitcoincash:q9m2qsznhks23z7629mms6s4cwef74vcwvtn0d2s8a";
+            assert!(BitcoinAddress::<N>::from_str(invalid).is_err());
+        }
+
+        #[test]
+        fn script_pub_key() {
+            let address = BitcoinAddress::<N>::from_str(P2PKH).unwrap();
+            assert_eq!(
+                (CASH_ADDR_P2PKH, hex::decode(HASH).unwrap()),
+                address.cash_addr_payload().unwrap()
+            );
+            assert_eq!(
+                format!("76a914{}88ac", HASH),
+                hex::encode(crate::create_script_pub_key(&address).unwrap())
+            );
+
+            let address = BitcoinAddress::<N>::from_str(P2SH).unwrap();
+            assert_eq!(
+                format!("a914{}87", HASH),
+                hex::encode(crate::create_script_pub_key(&address).unwrap())
+            );
+        }
+
+        #[test]
+        fn p2sh() {
+            let redeem_script = hex::decode("51").unwrap();
+            let address = BitcoinAddress::<N>::cash_addr_p2sh(&redeem_script).unwrap();
+            let (version, hash) = address.cash_addr_payload().unwrap();
+            assert_eq!(CASH_ADDR_P2SH, version);
+            assert_eq!(hash160(&redeem_script), hash);
+            test_from_str::<BitcoinCashTestnet>(
+                "bchtest:ppm2qsznhks23z7629mms6s4cwef74vcwvhanqgjxu",
+                &BitcoinFormat::CashAddr,
+            );
+        }
+    }
+
+    mod litecoin {
+        use super::*;
+
+        #[test]
+        fn deprecated_p2sh() {
+            let deprecated =
+                BitcoinAddress::<Litecoin>::from_str("3CWFddi6m4ndiGyKqzYvsFYagqDLPVMTzC").unwrap();
+            let current = deprecated.upgrade_p2sh().unwrap();
+            assert_eq!("MJiPwX84iBe4WnFDwsYGgtnz1XonPhUqhf", current.to_string());
+            assert_eq!(BitcoinFormat::P2SH_P2WPKH, current.format());
+            assert_eq!(current, current.upgrade_p2sh().unwrap());
+            assert_eq!(
+                crate::create_script_pub_key(&deprecated).unwrap(),
+                crate::create_script_pub_key(&current).unwrap()
+            );
+            assert!(BitcoinAddress::<Bitcoin>::from_str(&current.to_string()).is_err());
+
+            let deprecated =
+                BitcoinAddress::<LitecoinTestnet>::from_str("2N44ThNe8NXHyv4bsX8AoVCXquBRW94Ls7W")
+                    .unwrap();
+            assert_eq!(
+                "QXRDpPWNPdM54FMv9ECpZtyH3ZsL5zGe29",
+                deprecated.upgrade_p2sh().unwrap().to_string()
+            );
+        }
+
+        #[test]
+        fn bech32() {
+            let secret_key = libsecp256k1::SecretKey::parse(&[0x11; 32]).unwrap();
+            let address =
+                BitcoinAddress::<Litecoin>::from_secret_key(&secret_key, &BitcoinFormat::Bech32)
+                    .unwrap();
+            assert!(address.to_string().starts_with("ltc1q"));
+            assert_eq!(
+                address,
+                BitcoinAddress::<Litecoin>::from_str(&address.to_string()).unwrap()
+            );
+            assert!(BitcoinAddress::<Bitcoin>::from_str(&address.to_string()).is_err());
+        }
+    }
+
+    mod from_script_pub_key {
+        use super::*;
+
+        const ADDRESSES: [&str; 5] = [
+            "1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS",
+            "38EMCierP738rgYVHjj1qJANHKgx1166TN",
+            "bc1qztqceddvavsxdgju4cz6z42tawu444m8uttmxg",
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        ];
+
+        #[test]
+        fn round_trip() {
+            ADDRESSES.iter().for_each(|expected| {
+                let address = BitcoinAddress::<Bitcoin>::from_str(expected).unwrap();
+                let script = crate::create_script_pub_key(&address).unwrap();
+                let address = BitcoinAddress::<Bitcoin>::from_script_pub_key(&script).unwrap();
+                assert_eq!(*expected, address.to_string());
+            });
+        }
+
+        #[test]
+        fn p2sh_multisig() {
+            // a 2-of-3 multisig redeem script, which is not a P2WPKH program
+            let public_keys: Vec<u8> = (1..=3u8)
+                .flat_map(|i| {
+                    let secret_key = libsecp256k1::SecretKey::parse(&[i; 32]).unwrap();
+                    let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+                    [&[0x21][..], &public_key.serialize_compressed()].concat()
+                })
+                .collect();
+            let redeem_script = [&[0x52][..], &public_keys, &[0x53, 0xae]].concat();
+            let expected = BitcoinAddress::<Bitcoin>::p2sh(&redeem_script).unwrap();
+            assert_eq!(BitcoinFormat::P2SH, expected.format());
+
+            let script = crate::create_script_pub_key(&expected).unwrap();
+            let address = BitcoinAddress::<Bitcoin>::from_script_pub_key(&script).unwrap();
+            assert_eq!(expected, address);
+            assert!(address.to_string().starts_with('3'));
+
+            let parsed = BitcoinAddress::<Bitcoin>::from_str(&address.to_string()).unwrap();
+            assert_eq!(script, crate::create_script_pub_key(&parsed).unwrap());
+        }
+
+        #[test]
+        fn p2sh_equality() {
+            let address =
+                BitcoinAddress::<Bitcoin>::from_str("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy").unwrap();
+            let script = crate::create_script_pub_key(&address).unwrap();
+            let decoded = BitcoinAddress::<Bitcoin>::from_script_pub_key(&script).unwrap();
+            assert_eq!(BitcoinFormat::P2SH, decoded.format());
+            assert_eq!(address, decoded);
+
+            let json = serde_json::to_string(&decoded).unwrap();
+            let parsed: BitcoinAddress<Bitcoin> = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, parsed);
+        }
+
+        #[test]
+        fn invalid_script() {
+            let op_return = hex::decode("6a146f6d6e69000000000000001f000000003b9aca00").unwrap();
+            assert!(BitcoinAddress::<Bitcoin>::from_script_pub_key(&op_return).is_err());
+            assert!(BitcoinAddress::<Bitcoin>::from_script_pub_key(&[]).is_err());
+        }
+    }
+
     #[test]
     fn f() {
         let secret_key = [
@@ -939,4 +1371,74 @@ mod tests {
             addr1, addr2, addr3, addr4, addr5,
         );
     }
+
+    #[test]
+    fn test_signet() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[1; 32]).unwrap();
+        for format in [
+            BitcoinFormat::P2PKH,
+            BitcoinFormat::P2SH_P2WPKH,
+            BitcoinFormat::Bech32,
+        ] {
+            let signet = BitcoinAddress::<Signet>::from_secret_key(&secret_key, &format).unwrap();
+            let testnet =
+                BitcoinAddress::<BitcoinTestnet>::from_secret_key(&secret_key, &format).unwrap();
+            assert_eq!(testnet.to_string(), signet.to_string());
+            assert_eq!(
+                signet,
+                BitcoinAddress::<Signet>::from_str(&signet.to_string()).unwrap()
+            );
+        }
+        assert!(BitcoinAddress::<Signet>::from_str("1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS").is_err());
+        assert_eq!("bitcoin signet", Signet.to_string());
+        assert_eq!(Signet, Signet::from_str("bitcoin signet").unwrap());
+    }
+
+    #[test]
+    fn test_testnet4() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[1; 32]).unwrap();
+        let address =
+            BitcoinAddress::<Testnet4>::from_secret_key(&secret_key, &BitcoinFormat::Bech32)
+                .unwrap();
+        let testnet =
+            BitcoinAddress::<BitcoinTestnet>::from_secret_key(&secret_key, &BitcoinFormat::Bech32)
+                .unwrap();
+        assert_eq!(testnet.to_string(), address.to_string());
+        assert_eq!(
+            address,
+            BitcoinAddress::<Testnet4>::from_str(&address.to_string()).unwrap()
+        );
+        assert_eq!(Testnet4, Testnet4::from_str("bitcoin testnet4").unwrap());
+        assert!(Testnet4::from_str("bitcoin testnet").is_err());
+    }
+
+    #[test]
+    fn test_introspection() {
+        fn describe<A: Address>(address: &str) -> Option<(A::Network, A::Format)> {
+            let address = A::from_str(address).ok()?;
+            Some((address.network(), Address::format(&address)))
+        }
+        let address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        assert!(BitcoinAddress::<Bitcoin>::is_valid(address));
+        assert!(!BitcoinAddress::<BitcoinTestnet>::is_valid(address));
+        assert_eq!(
+            Some((Bitcoin, BitcoinFormat::Bech32)),
+            describe::<BitcoinAddress<Bitcoin>>(address)
+        );
+        assert_eq!(
+            Some((Litecoin, BitcoinFormat::P2SH_P2WPKH)),
+            describe::<BitcoinAddress<Litecoin>>("MJiPwX84iBe4WnFDwsYGgtnz1XonPhUqhf")
+        );
+        assert_eq!(
+            Some((BitcoinTestnet, BitcoinFormat::P2PKH)),
+            describe::<BitcoinAddress<BitcoinTestnet>>("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn")
+        );
+        assert_eq!(
+            "dogecoin",
+            BitcoinAddress::<Dogecoin>::from_str("DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L")
+                .unwrap()
+                .network()
+                .to_string()
+        );
+    }
 }
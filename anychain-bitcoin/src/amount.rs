@@ -2,8 +2,10 @@ use anychain_core::no_std::*;
 use anychain_core::{Amount, AmountError};
 
 use core::fmt;
-use serde::Serialize;
-use std::ops::{Add, Sub};
+use core::iter::Sum;
+use core::ops::{Add, Sub};
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
 
 // Number of satoshis (base unit) per BTC
 const COIN: i64 = 1_0000_0000;
@@ -12,9 +14,11 @@ const COIN: i64 = 1_0000_0000;
 const MAX_COINS: i64 = 21_000_000 * COIN;
 
 /// Represents the amount of Bitcoin in satoshis
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct BitcoinAmount(pub i64);
 
+/// The units an amount of Bitcoin is expressed in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Denomination {
     // sat
     Satoshi,
@@ -61,7 +65,45 @@ impl fmt::Display for Denomination {
     }
 }
 
-impl Amount for BitcoinAmount {}
+impl FromStr for Denomination {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sat" | "sats" | "satoshi" | "satoshis" => Ok(Denomination::Satoshi),
+            "uBTC" | "bit" | "bits" => Ok(Denomination::MicroBit),
+            "mBTC" => Ok(Denomination::MilliBit),
+            "cBTC" => Ok(Denomination::CentiBit),
+            "dBTC" => Ok(Denomination::DeciBit),
+            "BTC" => Ok(Denomination::Bitcoin),
+            _ => Err(AmountError::InvalidAmount(format!(
+                "unknown denomination {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl Amount for BitcoinAmount {
+    const DECIMALS: u32 = 8;
+
+    fn from_smallest_unit(value: u128) -> Result<Self, AmountError> {
+        let satoshis = i64::try_from(value).map_err(|_| out_of_bounds(value.to_string()))?;
+        Self::from_satoshi(satoshis)
+    }
+
+    fn to_smallest_unit(&self) -> Result<u128, AmountError> {
+        Ok(self.non_negative()?.0 as u128)
+    }
+
+    fn checked_add(self, rhs: Self) -> Result<Self, AmountError> {
+        BitcoinAmount::checked_add(self, rhs)
+    }
+
+    fn checked_sub(self, rhs: Self) -> Result<Self, AmountError> {
+        BitcoinAmount::checked_sub(self, rhs)
+    }
+}
 
 impl BitcoinAmount {
     /// The zero amount.
@@ -113,17 +155,139 @@ impl BitcoinAmount {
     }
 }
 
+impl BitcoinAmount {
+    /// Parses a decimal amount expressed in 'denomination', e.g. `0.0005` in
+    /// BTC, without rounding. Amounts finer than a satoshi are rejected.
+    pub fn from_str_in(s: &str, denomination: Denomination) -> Result<Self, AmountError> {
+        let invalid = || AmountError::InvalidAmount(s.to_string());
+
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let precision = denomination.precision() as usize;
+        if integer.is_empty() && fraction.is_empty()
+            || fraction.len() > precision
+            || !integer
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let integer: i64 = match integer {
+            "" => 0,
+            integer => integer.parse().map_err(|_| out_of_bounds(s.into()))?,
+        };
+        let fraction: i64 = match fraction {
+            "" => 0,
+            fraction => format!("{:0<1$}", fraction, precision)
+                .parse()
+                .map_err(|_| invalid())?,
+        };
+        let satoshis = integer
+            .checked_mul(10_i64.pow(precision as u32))
+            .and_then(|satoshis| satoshis.checked_add(fraction))
+            .ok_or_else(|| out_of_bounds(s.into()))?;
+        Self::from_satoshi(if negative { -satoshis } else { satoshis })
+    }
+
+    /// Returns the amount as a decimal in 'denomination', without trailing
+    /// zeros in the fraction
+    pub fn to_string_in(&self, denomination: Denomination) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let satoshis = self.0.unsigned_abs();
+        let precision = denomination.precision() as usize;
+        let unit = 10_u64.pow(precision as u32);
+
+        let fraction = format!("{:01$}", satoshis % unit, precision);
+        match fraction.trim_end_matches('0') {
+            "" => format!("{}{}", sign, satoshis / unit),
+            fraction => format!("{}{}.{}", sign, satoshis / unit, fraction),
+        }
+    }
+
+    /// Returns the amount if it is within the supply bounds, for amounts
+    /// constructed from the raw number of satoshis.
+    pub fn validate(self) -> Result<Self, AmountError> {
+        Self::from_satoshi(self.0)
+    }
+
+    /// Returns the sum of two non-negative amounts, failing on overflow or a
+    /// result out of the supply bounds.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, AmountError> {
+        self.non_negative()?;
+        rhs.non_negative()?;
+        self + rhs
+    }
+
+    /// Returns the difference of two non-negative amounts, failing if the
+    /// result is negative.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, AmountError> {
+        self.non_negative()?;
+        rhs.non_negative()?;
+        (self - rhs)?.non_negative()
+    }
+
+    /// Returns the non-negative amount multiplied by 'rhs', failing on
+    /// overflow or a result out of the supply bounds.
+    pub fn checked_mul(self, rhs: u64) -> Result<Self, AmountError> {
+        self.non_negative()?;
+        let satoshis = i64::try_from(rhs)
+            .ok()
+            .and_then(|rhs| self.0.checked_mul(rhs))
+            .ok_or_else(|| out_of_bounds(format!("{} * {}", self, rhs)))?;
+        Self::from_satoshi(satoshis)
+    }
+
+    fn non_negative(self) -> Result<Self, AmountError> {
+        match self.0 < 0 {
+            true => Err(AmountError::InvalidAmount(format!(
+                "negative amount {}",
+                self.0
+            ))),
+            false => self.validate(),
+        }
+    }
+}
+
+fn out_of_bounds(expression: String) -> AmountError {
+    AmountError::AmountOutOfBounds(expression, MAX_COINS.to_string())
+}
+
 impl Add for BitcoinAmount {
     type Output = Result<Self, AmountError>;
     fn add(self, rhs: Self) -> Self::Output {
-        Self::from_satoshi(self.0 + rhs.0)
+        self.0
+            .checked_add(rhs.0)
+            .ok_or_else(|| out_of_bounds(format!("{} + {}", self, rhs)))
+            .and_then(Self::from_satoshi)
     }
 }
 
 impl Sub for BitcoinAmount {
     type Output = Result<Self, AmountError>;
     fn sub(self, rhs: Self) -> Self::Output {
-        Self::from_satoshi(self.0 - rhs.0)
+        self.0
+            .checked_sub(rhs.0)
+            .ok_or_else(|| out_of_bounds(format!("{} - {}", self, rhs)))
+            .and_then(Self::from_satoshi)
+    }
+}
+
+/// Sums non-negative amounts, failing on overflow or a total out of the
+/// supply bounds.
+impl Sum<BitcoinAmount> for Result<BitcoinAmount, AmountError> {
+    fn sum<I: Iterator<Item = BitcoinAmount>>(mut iter: I) -> Self {
+        iter.try_fold(BitcoinAmount::ZERO, BitcoinAmount::checked_add)
+    }
+}
+
+impl<'a> Sum<&'a BitcoinAmount> for Result<BitcoinAmount, AmountError> {
+    fn sum<I: Iterator<Item = &'a BitcoinAmount>>(iter: I) -> Self {
+        iter.copied().sum()
     }
 }
 
@@ -309,6 +473,104 @@ mod tests {
         }
     }
 
+    mod checked_arithmetic {
+        use super::*;
+
+        #[test]
+        fn test_checked() {
+            let a = BitcoinAmount(30_000);
+            let b = BitcoinAmount(20_000);
+            assert_eq!(BitcoinAmount(50_000), a.checked_add(b).unwrap());
+            assert_eq!(BitcoinAmount(10_000), a.checked_sub(b).unwrap());
+            assert_eq!(BitcoinAmount(90_000), a.checked_mul(3).unwrap());
+
+            assert!(b.checked_sub(a).is_err());
+            assert!(BitcoinAmount(-1).checked_add(a).is_err());
+            assert!(BitcoinAmount::ONE_BTC.checked_mul(21_000_001).is_err());
+            assert!(a.checked_mul(u64::MAX).is_err());
+            assert!(BitcoinAmount(i64::MAX).validate().is_err());
+            assert!((BitcoinAmount(i64::MAX) + BitcoinAmount(1)).is_err());
+            assert!((BitcoinAmount(i64::MIN) - BitcoinAmount(1)).is_err());
+        }
+
+        #[test]
+        fn test_amount_trait() {
+            let amount = <BitcoinAmount as Amount>::from_decimal_str("0.0005").unwrap();
+            assert_eq!(BitcoinAmount(50_000), amount);
+            assert_eq!("0.0005", amount.to_decimal_string().unwrap());
+            assert_eq!(50_000, amount.to_smallest_unit().unwrap());
+            assert_eq!(
+                BitcoinAmount(70_000),
+                Amount::checked_add(amount, BitcoinAmount(20_000)).unwrap()
+            );
+
+            assert!(BitcoinAmount::from_smallest_unit(i64::MAX as u128 + 1).is_err());
+            assert!(BitcoinAmount::from_decimal_str("21000000.00000001").is_err());
+            assert!(BitcoinAmount(-1).to_smallest_unit().is_err());
+        }
+
+        #[test]
+        fn test_sum() {
+            let amounts = [BitcoinAmount(1), BitcoinAmount(2), BitcoinAmount(3)];
+            let sum: Result<BitcoinAmount, AmountError> = amounts.iter().sum();
+            assert_eq!(BitcoinAmount(6), sum.unwrap());
+
+            let sum: Result<BitcoinAmount, AmountError> =
+                [BitcoinAmount(MAX_COINS); 2].into_iter().sum();
+            assert!(sum.is_err());
+        }
+    }
+
+    mod denominations {
+        use super::*;
+
+        #[test]
+        fn test_from_str_in() {
+            for (s, denomination, satoshis) in [
+                ("0.0005", Denomination::Bitcoin, 50_000),
+                ("21000000", Denomination::Bitcoin, MAX_COINS),
+                (".1", Denomination::Bitcoin, 10_000_000),
+                ("-1.5", Denomination::MilliBit, -150_000),
+                ("12.34", "bits".parse().unwrap(), 1234),
+                ("546", "sat".parse().unwrap(), 546),
+            ] {
+                let amount = BitcoinAmount::from_str_in(s, denomination).unwrap();
+                assert_eq!(BitcoinAmount(satoshis), amount);
+            }
+
+            for (s, denomination) in [
+                ("0.000000001", Denomination::Bitcoin),
+                ("1.5", Denomination::Satoshi),
+                ("21000000.00000001", Denomination::Bitcoin),
+                ("99999999999999999999", Denomination::Satoshi),
+                ("1e3", Denomination::Satoshi),
+                ("+1", Denomination::Bitcoin),
+                ("", Denomination::Bitcoin),
+                (".", Denomination::Bitcoin),
+            ] {
+                assert!(
+                    BitcoinAmount::from_str_in(s, denomination).is_err(),
+                    "{}",
+                    s
+                );
+            }
+        }
+
+        #[test]
+        fn test_to_string_in() {
+            let amount = BitcoinAmount(123_456_789);
+            assert_eq!("1.23456789", amount.to_string_in(Denomination::Bitcoin));
+            assert_eq!("1234.56789", amount.to_string_in(Denomination::MilliBit));
+            assert_eq!("1234567.89", amount.to_string_in(Denomination::MicroBit));
+            assert_eq!("123456789", amount.to_string_in(Denomination::Satoshi));
+            assert_eq!(
+                "-0.5",
+                BitcoinAmount(-50_000_000).to_string_in(Denomination::Bitcoin)
+            );
+            assert_eq!("0", BitcoinAmount::ZERO.to_string_in(Denomination::Bitcoin));
+        }
+    }
+
     mod test_invalid {
         use super::*;
 
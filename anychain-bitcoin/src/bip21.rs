@@ -0,0 +1,261 @@
+//!
+//! BIP21
+//!
+//! This module encodes and decodes `bitcoin:` payment URIs as specified in BIP21.
+//!
+
+use crate::{BitcoinAddress, BitcoinAmount, BitcoinNetwork, Denomination};
+use anychain_core::{no_std::*, AddressError};
+use core::{fmt, str::FromStr};
+
+/// Represents a BIP21 payment URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bip21Uri<N: BitcoinNetwork> {
+    /// The address to pay to
+    pub address: BitcoinAddress<N>,
+    /// The requested amount
+    pub amount: Option<BitcoinAmount>,
+    /// A label for the address
+    pub label: Option<String>,
+    /// A message describing the payment
+    pub message: Option<String>,
+    /// The other optional parameters, decoded, in order of appearance
+    pub extras: Vec<(String, String)>,
+}
+
+impl<N: BitcoinNetwork> Bip21Uri<N> {
+    /// The URI scheme
+    pub const SCHEME: &'static str = "bitcoin";
+
+    /// Returns a payment URI for the given address
+    pub fn new(address: BitcoinAddress<N>) -> Self {
+        Self {
+            address,
+            amount: None,
+            label: None,
+            message: None,
+            extras: vec![],
+        }
+    }
+
+    /// Sets the requested amount
+    pub fn with_amount(mut self, amount: BitcoinAmount) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Sets the label of the address
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the message describing the payment
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for Bip21Uri<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", Self::SCHEME, self.address)?;
+
+        let mut params = vec![];
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", format_btc(amount.0)));
+        }
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", percent_encode(label)));
+        }
+        if let Some(message) = &self.message {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+        for (key, value) in &self.extras {
+            params.push(format!("{}={}", percent_encode(key), percent_encode(value)));
+        }
+
+        if !params.is_empty() {
+            write!(f, "?{}", params.join("&"))?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for Bip21Uri<N> {
+    type Err = AddressError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| AddressError::InvalidUri(reason.into());
+
+        let (scheme, rest) = uri
+            .split_once(':')
+            .ok_or_else(|| invalid("missing scheme"))?;
+        if !scheme.eq_ignore_ascii_case(Self::SCHEME) {
+            return Err(AddressError::InvalidUri(format!(
+                "unknown scheme {}",
+                scheme
+            )));
+        }
+
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+        // checks that the address belongs to the network 'N'
+        let mut result = Self::new(BitcoinAddress::<N>::from_str(address)?);
+
+        for param in query.into_iter().flat_map(|query| query.split('&')) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| AddressError::InvalidUri(format!("invalid parameter {}", param)))?;
+            let key = percent_decode(key)?;
+            let duplicate = AddressError::InvalidUri(format!("duplicate parameter {}", key));
+
+            match key.as_str() {
+                "amount" => {
+                    let amount = parse_btc(value).ok_or_else(|| {
+                        AddressError::InvalidUri(format!("invalid amount {}", value))
+                    })?;
+                    if result.amount.replace(BitcoinAmount(amount)).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                "label" => {
+                    if result.label.replace(percent_decode(value)?).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                "message" => {
+                    if result.message.replace(percent_decode(value)?).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                // a required parameter that is not understood invalidates the URI
+                key if key.starts_with("req-") => {
+                    return Err(AddressError::InvalidUri(format!(
+                        "unsupported required parameter {}",
+                        key
+                    )))
+                }
+                _ => result.extras.push((key, percent_decode(value)?)),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Parses a decimal amount of bitcoins into satoshis
+fn parse_btc(amount: &str) -> Option<i64> {
+    match amount.starts_with('-') {
+        true => None,
+        false => BitcoinAmount::from_str_in(amount, Denomination::Bitcoin)
+            .ok()
+            .map(|amount| amount.0),
+    }
+}
+
+/// Formats an amount of satoshis as a decimal amount of bitcoins
+fn format_btc(satoshis: i64) -> String {
+    BitcoinAmount(satoshis).to_string_in(Denomination::Bitcoin)
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Result<String, AddressError> {
+    let invalid = || AddressError::InvalidUri(format!("invalid percent encoding {}", value));
+
+    let mut decoded = vec![];
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    bytes.next().ok_or_else(invalid)?,
+                    bytes.next().ok_or_else(invalid)?,
+                ];
+                let hex = core::str::from_utf8(&hex).map_err(|_| invalid())?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            byte => decoded.push(byte),
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinTestnet};
+
+    const ADDRESS: &str = "1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS";
+
+    #[test]
+    fn test_parse() {
+        let uri = Bip21Uri::<Bitcoin>::from_str(
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?amount=20.3&label=Luke-Jr&message=Donation%20for%20project%20xyz&somethingyoudontunderstand=50",
+        )
+        .unwrap();
+        assert_eq!(ADDRESS, uri.address.to_string());
+        assert_eq!(Some(BitcoinAmount(2_030_000_000)), uri.amount);
+        assert_eq!(Some("Luke-Jr".into()), uri.label);
+        assert_eq!(Some("Donation for project xyz".into()), uri.message);
+        assert_eq!(
+            vec![("somethingyoudontunderstand".into(), "50".into())],
+            uri.extras
+        );
+
+        let uri =
+            Bip21Uri::<Bitcoin>::from_str("BITCOIN:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS").unwrap();
+        assert_eq!(None, uri.amount);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for uri in [
+            "litecoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS",
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?req-somethingyoudontunderstand=50",
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?amount=1&amount=2",
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?amount=1.000000001",
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?amount=1e3",
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?label=%zz",
+        ] {
+            assert!(Bip21Uri::<Bitcoin>::from_str(uri).is_err(), "{}", uri);
+        }
+        // a mainnet address is not valid on testnet
+        assert!(
+            Bip21Uri::<BitcoinTestnet>::from_str("bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let address = BitcoinAddress::<Bitcoin>::from_str(ADDRESS).unwrap();
+        let mut uri = Bip21Uri::new(address)
+            .with_amount(BitcoinAmount(50_000))
+            .with_label("Luke-Jr")
+            .with_message("Donation for project xyz");
+        uri.extras.push(("lightning".into(), "lnbc1".into()));
+
+        let formatted = uri.to_string();
+        assert_eq!(
+            "bitcoin:1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS?amount=0.0005&label=Luke-Jr&message=Donation%20for%20project%20xyz&lightning=lnbc1",
+            formatted
+        );
+        assert_eq!(uri, Bip21Uri::from_str(&formatted).unwrap());
+        assert_eq!("1", format_btc(100_000_000));
+    }
+}
@@ -0,0 +1,539 @@
+//!
+//! BIP322
+//!
+//! This module implements the generic signed message format of BIP322. The
+//! message is committed to by a virtual `to_spend` transaction paying to the
+//! address, and the signature is the spend of its output by a virtual
+//! `to_sign` transaction, so that segwit and taproot addresses can prove
+//! ownership with their regular spending conditions.
+//!
+//! The simple format encodes the witness stack of `to_sign` and covers P2WPKH
+//! and P2TR key path addresses; the full format encodes the whole `to_sign`
+//! transaction and also covers P2PKH and P2SH-P2WPKH addresses.
+//!
+
+use crate::{
+    create_script_pub_key,
+    message::{base64_decode, base64_encode},
+    taproot::{
+        schnorr_sign, schnorr_verify, tagged_hash, tweak_public_key, tweak_secret_key,
+        x_only_public_key,
+    },
+    variable_length_integer, BitcoinAddress, BitcoinNetwork, BitcoinTransactionRef, DecodeLimits,
+};
+use anychain_core::{
+    crypto::{checksum as double_sha2, hash160, sha256},
+    hex,
+    libsecp256k1::{self, Message, PublicKey, SecretKey, Signature},
+    no_std::*,
+    AddressError,
+};
+
+/// The scriptPubKey of the only output of `to_sign`: OP_RETURN
+const TO_SIGN_SCRIPT_PUB_KEY: [u8; 1] = [0x6a];
+
+/// The sighash flag of the ECDSA signatures
+const SIGHASH_ALL: u8 = 0x01;
+
+/// Returns the BIP322 message hash: the tagged hash of 'message' with the tag
+/// "BIP0322-signed-message"
+pub fn bip322_message_hash(message: &[u8]) -> [u8; 32] {
+    tagged_hash("BIP0322-signed-message", message)
+}
+
+/// Returns the serialized virtual `to_spend` transaction committing to
+/// 'message' with a single output paying to 'script_pub_key'
+pub fn bip322_to_spend(script_pub_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut transaction = vec![0, 0, 0, 0, 1];
+    // the input spends the null outpoint with the scriptSig OP_0 PUSH32[message_hash]
+    transaction.extend([0u8; 32]);
+    transaction.extend([0xff; 4]);
+    transaction.extend([34, 0x00, 0x20]);
+    transaction.extend(bip322_message_hash(message));
+    transaction.extend([0u8; 4]);
+    transaction.push(1);
+    transaction.extend([0u8; 8]);
+    transaction.extend(variable_length_integer(script_pub_key.len() as u64).unwrap());
+    transaction.extend(script_pub_key);
+    transaction.extend([0u8; 4]);
+    transaction
+}
+
+/// Returns the serialized virtual `to_sign` transaction spending the output of
+/// the `to_spend` transaction with id 'to_spend_txid' (in internal byte order)
+/// with the given scriptSig and witness stack
+pub fn bip322_to_sign(to_spend_txid: &[u8; 32], script_sig: &[u8], witness: &[Vec<u8>]) -> Vec<u8> {
+    let mut transaction = vec![0, 0, 0, 0];
+    if !witness.is_empty() {
+        transaction.extend([0x00, 0x01]);
+    }
+    transaction.push(1);
+    transaction.extend(to_spend_txid);
+    transaction.extend([0u8; 4]);
+    transaction.extend(variable_length_integer(script_sig.len() as u64).unwrap());
+    transaction.extend(script_sig);
+    transaction.extend([0u8; 4]);
+    transaction.push(1);
+    transaction.extend([0u8; 8]);
+    transaction.push(TO_SIGN_SCRIPT_PUB_KEY.len() as u8);
+    transaction.extend(TO_SIGN_SCRIPT_PUB_KEY);
+    if !witness.is_empty() {
+        transaction.extend(serialize_witness(witness));
+    }
+    transaction.extend([0u8; 4]);
+    transaction
+}
+
+/// Signs 'message' for 'address' with 'secret_key' and returns the base64
+/// encoded witness stack of `to_sign`. Only P2WPKH and P2TR addresses can be
+/// signed for in the simple format.
+pub fn bip322_sign_simple<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    secret_key: &SecretKey,
+) -> Result<String, AddressError> {
+    let (script_sig, witness) = sign_to_sign(address, message, secret_key)?;
+    if !script_sig.is_empty() {
+        return Err(AddressError::Message(format!(
+            "the address {} requires the full signature format",
+            address
+        )));
+    }
+    Ok(base64_encode(&serialize_witness(&witness)))
+}
+
+/// Signs 'message' for 'address' with 'secret_key' and returns the base64
+/// encoded `to_sign` transaction
+pub fn bip322_sign_full<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    secret_key: &SecretKey,
+) -> Result<String, AddressError> {
+    let script_pub_key = script_pub_key(address)?;
+    let (script_sig, witness) = sign_to_sign(address, message, secret_key)?;
+    let txid = to_spend_txid(&script_pub_key, message);
+    Ok(base64_encode(&bip322_to_sign(&txid, &script_sig, &witness)))
+}
+
+/// Returns true if 'signature' is a valid simple BIP322 signature of 'message'
+/// for 'address'
+pub fn bip322_verify_simple<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    signature: &str,
+) -> Result<bool, AddressError> {
+    let witness = base64_decode(signature).ok_or_else(invalid_signature)?;
+    let script_pub_key = script_pub_key(address)?;
+    let txid = to_spend_txid(&script_pub_key, message);
+
+    // splice the witness stack into a to_sign transaction and let the strict
+    // parser validate it
+    let mut to_sign = bip322_to_sign(&txid, &[], &[vec![]]);
+    let witness_start = to_sign.len() - 6;
+    to_sign.splice(witness_start..to_sign.len() - 4, witness);
+    verify_to_sign(&script_pub_key, &txid, &to_sign)
+}
+
+/// Returns true if 'signature' is a valid full BIP322 signature of 'message'
+/// for 'address'
+pub fn bip322_verify_full<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    signature: &str,
+) -> Result<bool, AddressError> {
+    let to_sign = base64_decode(signature).ok_or_else(invalid_signature)?;
+    let script_pub_key = script_pub_key(address)?;
+    let txid = to_spend_txid(&script_pub_key, message);
+    verify_to_sign(&script_pub_key, &txid, &to_sign)
+}
+
+fn invalid_signature() -> AddressError {
+    AddressError::Message("invalid BIP322 signature".into())
+}
+
+fn unsupported_address(script_pub_key: &[u8]) -> AddressError {
+    AddressError::Message(format!(
+        "BIP322 is not supported for the script pub key {}",
+        hex::encode(script_pub_key)
+    ))
+}
+
+fn script_pub_key<N: BitcoinNetwork>(address: &BitcoinAddress<N>) -> Result<Vec<u8>, AddressError> {
+    create_script_pub_key(address).map_err(|error| AddressError::Message(error.to_string()))
+}
+
+/// Returns the id of the `to_spend` transaction in internal byte order
+fn to_spend_txid(script_pub_key: &[u8], message: &[u8]) -> [u8; 32] {
+    double_sha2(&bip322_to_spend(script_pub_key, message))
+        .try_into()
+        .unwrap()
+}
+
+fn serialize_witness(witness: &[Vec<u8>]) -> Vec<u8> {
+    let mut serialized = variable_length_integer(witness.len() as u64).unwrap();
+    for item in witness {
+        serialized.extend(variable_length_integer(item.len() as u64).unwrap());
+        serialized.extend(item);
+    }
+    serialized
+}
+
+/// Returns the P2PKH script code of a 20 byte public key hash
+fn p2pkh_script_code(hash: &[u8]) -> Vec<u8> {
+    [&[0x76, 0xa9, 0x14], hash, &[0x88, 0xac]].concat()
+}
+
+/// Returns the scriptSig and the witness stack of `to_sign` for 'address'
+fn sign_to_sign<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    secret_key: &SecretKey,
+) -> Result<(Vec<u8>, Vec<Vec<u8>>), AddressError> {
+    let script_pub_key = script_pub_key(address)?;
+    let txid = to_spend_txid(&script_pub_key, message);
+    let to_sign = bip322_to_sign(&txid, &[], &[]);
+    let to_sign = BitcoinTransactionRef::parse(&to_sign).unwrap();
+
+    let public_key = PublicKey::from_secret_key(secret_key);
+    let compressed = public_key.serialize_compressed().to_vec();
+    let ecdsa_sign = |hash: [u8; 32]| {
+        let (signature, _) = libsecp256k1::sign(&Message::parse(&hash), secret_key);
+        [signature.serialize_der().as_ref(), &[SIGHASH_ALL]].concat()
+    };
+    let mismatch = || AddressError::Message(format!("the secret key does not match {}", address));
+
+    match script_pub_key.as_slice() {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            let public_key = [compressed, public_key.serialize().to_vec()]
+                .into_iter()
+                .find(|public_key| hash160(public_key) == hash)
+                .ok_or_else(mismatch)?;
+            let hash = legacy_sighash(&to_sign, &script_pub_key);
+            let signature = ecdsa_sign(hash);
+            let mut script_sig = vec![signature.len() as u8];
+            script_sig.extend(signature);
+            script_sig.push(public_key.len() as u8);
+            script_sig.extend(public_key);
+            Ok((script_sig, vec![]))
+        }
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => {
+            let key_hash = hash160(&compressed);
+            let redeem_script = [&[0x00, 0x14], &key_hash[..]].concat();
+            if hash160(&redeem_script) != hash {
+                return Err(mismatch());
+            }
+            let hash = segwit_v0_sighash(&to_sign, &p2pkh_script_code(&key_hash));
+            let script_sig = [&[redeem_script.len() as u8], &redeem_script[..]].concat();
+            Ok((script_sig, vec![ecdsa_sign(hash), compressed]))
+        }
+        [0x00, 0x14, hash @ ..] if hash.len() == 20 => {
+            if hash160(&compressed) != hash {
+                return Err(mismatch());
+            }
+            let hash = segwit_v0_sighash(&to_sign, &p2pkh_script_code(hash));
+            Ok((vec![], vec![ecdsa_sign(hash), compressed]))
+        }
+        [0x51, 0x20, output_key @ ..] if output_key.len() == 32 => {
+            let internal_key = x_only_public_key(secret_key);
+            if tweak_public_key(&internal_key, None) != Some(output_key.try_into().unwrap()) {
+                return Err(mismatch());
+            }
+            let secret_key = tweak_secret_key(secret_key, None).ok_or_else(mismatch)?;
+            let hash = taproot_sighash(&to_sign, &script_pub_key, 0x00);
+            let signature = schnorr_sign(&hash, &secret_key, &[0u8; 32]).ok_or_else(|| {
+                AddressError::Message("the schnorr signature nonce is invalid".to_string())
+            })?;
+            Ok((vec![], vec![signature.to_vec()]))
+        }
+        _ => Err(unsupported_address(&script_pub_key)),
+    }
+}
+
+/// Returns true if the serialized 'to_sign' transaction is a valid spend of the
+/// `to_spend` transaction with id 'txid' paying to 'script_pub_key'
+fn verify_to_sign(
+    script_pub_key: &[u8],
+    txid: &[u8; 32],
+    to_sign: &[u8],
+) -> Result<bool, AddressError> {
+    let limits = DecodeLimits {
+        strict: true,
+        ..Default::default()
+    };
+    let to_sign = BitcoinTransactionRef::parse_with_limits(to_sign, &limits)
+        .map_err(|_| invalid_signature())?;
+    match (to_sign.inputs.as_slice(), to_sign.outputs.as_slice()) {
+        ([input], [output])
+            if input.reverse_transaction_id == txid
+                && input.index == 0
+                && output.amount == 0
+                && output.script_pub_key == TO_SIGN_SCRIPT_PUB_KEY => {}
+        _ => return Ok(false),
+    }
+    let input = &to_sign.inputs[0];
+    let witness: Vec<&[u8]> = input.witness.iter().collect();
+
+    let verified = match script_pub_key {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            match (
+                split_pushes(input.script_sig).as_deref(),
+                witness.as_slice(),
+            ) {
+                (Some([signature, public_key]), []) if hash160(public_key) == hash => {
+                    let sighash = legacy_sighash(&to_sign, script_pub_key);
+                    ecdsa_verify(sighash, signature, public_key)
+                }
+                _ => false,
+            }
+        }
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => match input.script_sig {
+            [0x16, redeem_script @ ..] if hash160(redeem_script) == hash => match redeem_script {
+                [0x00, 0x14, key_hash @ ..] => p2wpkh_verify(&to_sign, key_hash, &witness),
+                _ => return Err(unsupported_address(script_pub_key)),
+            },
+            _ => false,
+        },
+        [0x00, 0x14, key_hash @ ..] if key_hash.len() == 20 => {
+            input.script_sig.is_empty() && p2wpkh_verify(&to_sign, key_hash, &witness)
+        }
+        [0x51, 0x20, output_key @ ..] if output_key.len() == 32 => {
+            let (signature, sighash_type) = match witness.as_slice() {
+                [signature] if signature.len() == 64 => (&signature[..], 0x00),
+                [signature] if signature.len() == 65 && signature[64] == SIGHASH_ALL => {
+                    (&signature[..64], SIGHASH_ALL)
+                }
+                _ => return Ok(false),
+            };
+            input.script_sig.is_empty()
+                && schnorr_verify(
+                    &taproot_sighash(&to_sign, script_pub_key, sighash_type),
+                    signature.try_into().unwrap(),
+                    output_key.try_into().unwrap(),
+                )
+        }
+        _ => return Err(unsupported_address(script_pub_key)),
+    };
+    Ok(verified)
+}
+
+/// Returns the data of a scriptSig made of direct pushes only
+fn split_pushes(mut script: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut pushes = vec![];
+    while let [size @ 1..=75, rest @ ..] = script {
+        pushes.push(rest.get(..*size as usize)?);
+        script = &rest[*size as usize..];
+    }
+    script.is_empty().then_some(pushes)
+}
+
+fn p2wpkh_verify(to_sign: &BitcoinTransactionRef, key_hash: &[u8], witness: &[&[u8]]) -> bool {
+    match witness {
+        [signature, public_key] if public_key.len() == 33 && hash160(public_key) == key_hash => {
+            let sighash = segwit_v0_sighash(to_sign, &p2pkh_script_code(key_hash));
+            ecdsa_verify(sighash, signature, public_key)
+        }
+        _ => false,
+    }
+}
+
+/// Verifies a DER encoded low S signature followed by the SIGHASH_ALL flag
+fn ecdsa_verify(hash: [u8; 32], signature: &[u8], public_key: &[u8]) -> bool {
+    let (signature, public_key) = match (
+        signature.split_last(),
+        PublicKey::parse_slice(public_key, None),
+    ) {
+        (Some((&SIGHASH_ALL, der)), Ok(public_key)) => match Signature::parse_der(der) {
+            Ok(signature) => (signature, public_key),
+            Err(_) => return false,
+        },
+        _ => return false,
+    };
+    !signature.s.is_high() && libsecp256k1::verify(&Message::parse(&hash), &signature, &public_key)
+}
+
+/// Returns the serialization of the only output of `to_sign`
+fn to_sign_output() -> Vec<u8> {
+    [
+        &[0u8; 8][..],
+        &[TO_SIGN_SCRIPT_PUB_KEY.len() as u8],
+        &TO_SIGN_SCRIPT_PUB_KEY,
+    ]
+    .concat()
+}
+
+/// Returns the outpoint spent by `to_sign`
+fn to_sign_outpoint(to_sign: &BitcoinTransactionRef) -> Vec<u8> {
+    let input = &to_sign.inputs[0];
+    [input.reverse_transaction_id, &input.index.to_le_bytes()].concat()
+}
+
+/// Returns the legacy SIGHASH_ALL signature hash of `to_sign`
+fn legacy_sighash(to_sign: &BitcoinTransactionRef, script_code: &[u8]) -> [u8; 32] {
+    let mut preimage = to_sign.version.to_le_bytes().to_vec();
+    preimage.push(1);
+    preimage.extend(to_sign_outpoint(to_sign));
+    preimage.extend(variable_length_integer(script_code.len() as u64).unwrap());
+    preimage.extend(script_code);
+    preimage.extend(to_sign.inputs[0].sequence.to_le_bytes());
+    preimage.push(1);
+    preimage.extend(to_sign_output());
+    preimage.extend(to_sign.lock_time.to_le_bytes());
+    preimage.extend((SIGHASH_ALL as u32).to_le_bytes());
+    double_sha2(&preimage).try_into().unwrap()
+}
+
+/// Returns the BIP143 SIGHASH_ALL signature hash of `to_sign` spending an
+/// output of value 0
+fn segwit_v0_sighash(to_sign: &BitcoinTransactionRef, script_code: &[u8]) -> [u8; 32] {
+    let outpoint = to_sign_outpoint(to_sign);
+    let sequence = to_sign.inputs[0].sequence.to_le_bytes();
+
+    let mut preimage = to_sign.version.to_le_bytes().to_vec();
+    preimage.extend(double_sha2(&outpoint));
+    preimage.extend(double_sha2(&sequence));
+    preimage.extend(outpoint);
+    preimage.extend(variable_length_integer(script_code.len() as u64).unwrap());
+    preimage.extend(script_code);
+    preimage.extend([0u8; 8]);
+    preimage.extend(sequence);
+    preimage.extend(double_sha2(&to_sign_output()));
+    preimage.extend(to_sign.lock_time.to_le_bytes());
+    preimage.extend((SIGHASH_ALL as u32).to_le_bytes());
+    double_sha2(&preimage).try_into().unwrap()
+}
+
+/// Returns the BIP341 key path signature hash of `to_sign` spending an output
+/// of value 0 paying to 'script_pub_key', for SIGHASH_DEFAULT or SIGHASH_ALL
+fn taproot_sighash(
+    to_sign: &BitcoinTransactionRef,
+    script_pub_key: &[u8],
+    sighash_type: u8,
+) -> [u8; 32] {
+    let mut script_pub_keys = variable_length_integer(script_pub_key.len() as u64).unwrap();
+    script_pub_keys.extend(script_pub_key);
+
+    // the epoch and the sighash type
+    let mut message = vec![0x00, sighash_type];
+    message.extend(to_sign.version.to_le_bytes());
+    message.extend(to_sign.lock_time.to_le_bytes());
+    message.extend(sha256(&to_sign_outpoint(to_sign)));
+    message.extend(sha256(&[0u8; 8]));
+    message.extend(sha256(&script_pub_keys));
+    message.extend(sha256(&to_sign.inputs[0].sequence.to_le_bytes()));
+    message.extend(sha256(&to_sign_output()));
+    // the key path spend of the input at index 0, without annex
+    message.push(0x00);
+    message.extend(0u32.to_le_bytes());
+    tagged_hash("TapSighash", &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinFormat, BitcoinPublicKey};
+    use anychain_core::{hex, PublicKey as _};
+    use core::str::FromStr;
+
+    // the test vectors of BIP322
+    const SECRET_KEY: &str = "bb051cd0dda0246f33c5a9e133ebd8e7bc02a92af6c41adc131ccd7826c5b004";
+    const P2WPKH: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+    const P2TR: &str = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+
+    fn secret_key() -> SecretKey {
+        SecretKey::parse_slice(&hex::decode(SECRET_KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_message_hash() {
+        assert_eq!(
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1",
+            hex::encode(bip322_message_hash(b""))
+        );
+        assert_eq!(
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a",
+            hex::encode(bip322_message_hash(b"Hello World"))
+        );
+    }
+
+    #[test]
+    fn test_to_spend() {
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2WPKH).unwrap();
+        let script_pub_key = script_pub_key(&address).unwrap();
+        let mut txid = to_spend_txid(&script_pub_key, b"");
+        txid.reverse();
+        assert_eq!(
+            "c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7",
+            hex::encode(txid)
+        );
+        let mut txid = to_spend_txid(&script_pub_key, b"Hello World");
+        txid.reverse();
+        assert_eq!(
+            "b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b",
+            hex::encode(txid)
+        );
+    }
+
+    #[test]
+    fn test_verify_simple() {
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2WPKH).unwrap();
+        let empty = "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        let hello = "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        assert!(bip322_verify_simple(&address, b"", empty).unwrap());
+        assert!(bip322_verify_simple(&address, b"Hello World", hello).unwrap());
+        assert!(!bip322_verify_simple(&address, b"", hello).unwrap());
+        assert!(!bip322_verify_simple(&address, b"Hello World", empty).unwrap());
+
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2TR).unwrap();
+        let hello = "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==";
+        assert!(bip322_verify_simple(&address, b"Hello World", hello).unwrap());
+        assert!(!bip322_verify_simple(&address, b"", hello).unwrap());
+
+        assert!(bip322_verify_simple(&address, b"", "AkcwRAIg").is_err());
+        assert!(bip322_verify_simple(&address, b"", "").is_err());
+    }
+
+    #[test]
+    fn test_sign_simple() {
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2WPKH).unwrap();
+        let signature = bip322_sign_simple(&address, b"", &secret_key()).unwrap();
+        // libsecp256k1 derives the nonce with RFC6979, unlike the BIP322 vectors
+        assert_eq!(
+            "AkgwRQIhAPkJ1Q4oYS0htvyuSFHLxRQpFAY56b70UvE7Dxazen0ZAiAtZfFz1S6T6I23MWI2lK/pcNTWncuyL8UL+oMdydVgzAEhAsfxIAMZZEKUPYWI4BruhAQjzFT8FSFSajuFwrDL1Yhy",
+            signature
+        );
+        assert!(bip322_verify_simple(&address, b"", &signature).unwrap());
+
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2TR).unwrap();
+        let signature = bip322_sign_simple(&address, b"Hello World", &secret_key()).unwrap();
+        assert!(bip322_verify_simple(&address, b"Hello World", &signature).unwrap());
+        assert!(!bip322_verify_simple(&address, b"Hello", &signature).unwrap());
+
+        // another key cannot sign for the address
+        let other = SecretKey::parse(&[1; 32]).unwrap();
+        assert!(bip322_sign_simple(&address, b"Hello World", &other).is_err());
+    }
+
+    #[test]
+    fn test_full() {
+        let public_key = BitcoinPublicKey::<Bitcoin>::from_secret_key(&secret_key());
+        for format in [
+            BitcoinFormat::P2PKH,
+            BitcoinFormat::P2SH_P2WPKH,
+            BitcoinFormat::Bech32,
+        ] {
+            let address = public_key.to_address(&format).unwrap();
+            let signature = bip322_sign_full(&address, b"Hello World", &secret_key()).unwrap();
+            assert!(bip322_verify_full(&address, b"Hello World", &signature).unwrap());
+            assert!(!bip322_verify_full(&address, b"Hello", &signature).unwrap());
+        }
+
+        // a full signature of a witness only address holds the simple one
+        let address = BitcoinAddress::<Bitcoin>::from_str(P2TR).unwrap();
+        let signature = bip322_sign_full(&address, b"Hello World", &secret_key()).unwrap();
+        assert!(bip322_verify_full(&address, b"Hello World", &signature).unwrap());
+
+        // the legacy formats need the scriptSig of the full format
+        let address = public_key.to_address(&BitcoinFormat::P2PKH).unwrap();
+        assert!(bip322_sign_simple(&address, b"Hello World", &secret_key()).is_err());
+    }
+}
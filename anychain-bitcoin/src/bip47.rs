@@ -0,0 +1,422 @@
+//!
+//! BIP47 Payment Code
+//!
+//! This module implements version 1 reusable payment codes. A payment code
+//! holds the public key and chain code of a BIP32 account. The sender
+//! announces its own payment code once with a notification transaction, and
+//! both sides then derive a fresh address per payment from an ECDH shared
+//! secret between their payment code keys.
+//!
+
+use crate::{
+    BitcoinAddress, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, BitcoinTransactionOutput,
+    Opcode, Outpoint,
+};
+use anychain_core::{
+    crypto::{checksum, hmac_sha512, sha256},
+    hex,
+    libsecp256k1::{PublicKey, SecretKey},
+    no_std::*,
+    Address, AddressError,
+};
+use core::{fmt, str::FromStr};
+
+/// The base58 version byte of payment codes
+pub const PAYMENT_CODE_VERSION_BYTE: u8 = 0x47;
+
+/// Represents a version 1 BIP47 payment code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentCode {
+    /// The feature bits of the payment code
+    pub features: u8,
+    /// The public key of the account
+    pub public_key: PublicKey,
+    /// The chain code of the account
+    pub chain_code: [u8; 32],
+}
+
+/// Returns the non-hardened BIP32 child of the public key and chain code
+fn derive_public_key(
+    public_key: &PublicKey,
+    chain_code: &[u8; 32],
+    index: u32,
+) -> Result<PublicKey, AddressError> {
+    let data = [&public_key.serialize_compressed()[..], &index.to_be_bytes()].concat();
+    let tweak = SecretKey::parse_slice(&hmac_sha512(chain_code, &data)[..32])
+        .map_err(|_| AddressError::Message("invalid payment code child key".into()))?;
+    let mut child = *public_key;
+    child
+        .tweak_add_assign(&tweak)
+        .map_err(|_| AddressError::Message("invalid payment code child key".into()))?;
+    Ok(child)
+}
+
+/// Returns the x coordinate of the ECDH point of 'secret_key' and 'public_key'
+fn shared_secret(secret_key: &SecretKey, public_key: &PublicKey) -> Result<[u8; 32], AddressError> {
+    let mut point = *public_key;
+    point
+        .tweak_mul_assign(secret_key)
+        .map_err(|_| AddressError::Message("invalid payment code shared secret".into()))?;
+    Ok(point.serialize_compressed()[1..].try_into().unwrap())
+}
+
+/// Blinds or unblinds the public key x coordinate and the chain code of a
+/// binary payment code with the shared secret 'x' of the designated input
+/// spending 'outpoint'
+fn blind(payload: &[u8], x: &[u8; 32], outpoint: &Outpoint) -> Result<[u8; 80], AddressError> {
+    let mut payload: [u8; 80] = payload
+        .try_into()
+        .map_err(|_| AddressError::InvalidByteLength(payload.len()))?;
+    let outpoint = [
+        &outpoint.txid.as_bytes_internal()[..],
+        &outpoint.index.to_le_bytes(),
+    ]
+    .concat();
+    for (byte, mask) in payload[3..67].iter_mut().zip(hmac_sha512(&outpoint, x)) {
+        *byte ^= mask;
+    }
+    Ok(payload)
+}
+
+impl PaymentCode {
+    /// Returns the payment code of an account public key and chain code
+    pub fn new(public_key: PublicKey, chain_code: [u8; 32]) -> Self {
+        Self {
+            features: 0,
+            public_key,
+            chain_code,
+        }
+    }
+
+    /// Returns the 80 byte binary payment code
+    pub fn to_payload(&self) -> [u8; 80] {
+        let mut payload = [0u8; 80];
+        payload[0] = 0x01;
+        payload[1] = self.features;
+        payload[2..35].copy_from_slice(&self.public_key.serialize_compressed());
+        payload[35..67].copy_from_slice(&self.chain_code);
+        payload
+    }
+
+    /// Parses an 80 byte binary payment code
+    pub fn from_payload(payload: &[u8]) -> Result<Self, AddressError> {
+        if payload.len() != 80 {
+            return Err(AddressError::InvalidByteLength(payload.len()));
+        }
+        if payload[0] != 0x01 {
+            return Err(AddressError::Message(format!(
+                "unsupported payment code version {}",
+                payload[0]
+            )));
+        }
+        if !matches!(payload[2], 0x02 | 0x03) {
+            return Err(AddressError::Message(
+                "invalid payment code public key".into(),
+            ));
+        }
+        let public_key = PublicKey::parse_slice(&payload[2..35], None)
+            .map_err(|_| AddressError::Message("invalid payment code public key".into()))?;
+        Ok(Self {
+            features: payload[1],
+            public_key,
+            chain_code: payload[35..67].try_into().unwrap(),
+        })
+    }
+
+    /// Returns the public key the payment code derives at 'index'
+    pub fn derive_public_key(&self, index: u32) -> Result<PublicKey, AddressError> {
+        derive_public_key(&self.public_key, &self.chain_code, index)
+    }
+
+    /// Returns the P2PKH address notification transactions are sent to
+    pub fn notification_address<N: BitcoinNetwork>(
+        &self,
+    ) -> Result<BitcoinAddress<N>, AddressError> {
+        let public_key =
+            BitcoinPublicKey::<N>::from_secp256k1_public_key(self.derive_public_key(0)?, true);
+        BitcoinAddress::from_public_key(&public_key, &BitcoinFormat::P2PKH)
+    }
+}
+
+impl fmt::Display for PaymentCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut data = vec![PAYMENT_CODE_VERSION_BYTE];
+        data.extend(self.to_payload());
+        data.extend(&checksum(&data)[..4]);
+        write!(f, "{}", bs58::encode(data).into_string())
+    }
+}
+
+impl FromStr for PaymentCode {
+    type Err = AddressError;
+
+    fn from_str(payment_code: &str) -> Result<Self, Self::Err> {
+        let data = bs58::decode(payment_code)
+            .into_vec()
+            .map_err(|_| AddressError::InvalidAddress(payment_code.into()))?;
+        if data.len() != 85 || data[0] != PAYMENT_CODE_VERSION_BYTE {
+            return Err(AddressError::InvalidAddress(payment_code.into()));
+        }
+        let expected = &checksum(&data[..81])[..4];
+        if expected != &data[81..] {
+            return Err(AddressError::InvalidChecksum(
+                hex::encode(expected),
+                hex::encode(&data[81..]),
+            ));
+        }
+        Self::from_payload(&data[1..81])
+    }
+}
+
+/// Represents the secret side of a payment code: the secret key and chain
+/// code of the account (m/47'/coin'/account')
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentCodeSecret {
+    secret_key: SecretKey,
+    chain_code: [u8; 32],
+}
+
+impl PaymentCodeSecret {
+    /// Returns the payment code secret of an account secret key and chain code
+    pub fn new(secret_key: SecretKey, chain_code: [u8; 32]) -> Self {
+        Self {
+            secret_key,
+            chain_code,
+        }
+    }
+
+    /// Returns the payment code of the account
+    pub fn payment_code(&self) -> PaymentCode {
+        PaymentCode::new(
+            PublicKey::from_secret_key(&self.secret_key),
+            self.chain_code,
+        )
+    }
+
+    /// Returns the secret key the account derives at 'index'
+    pub fn derive_secret_key(&self, index: u32) -> Result<SecretKey, AddressError> {
+        let public_key = PublicKey::from_secret_key(&self.secret_key);
+        let data = [&public_key.serialize_compressed()[..], &index.to_be_bytes()].concat();
+        let mut child = SecretKey::parse_slice(&hmac_sha512(&self.chain_code, &data)[..32])
+            .map_err(|_| AddressError::Message("invalid payment code child key".into()))?;
+        child
+            .tweak_add_assign(&self.secret_key)
+            .map_err(|_| AddressError::Message("invalid payment code child key".into()))?;
+        Ok(child)
+    }
+
+    /// Returns the OP_RETURN payload of a notification transaction to
+    /// 'recipient': our payment code blinded with the secret key of the
+    /// designated input spending 'outpoint'
+    pub fn notification_payload(
+        &self,
+        recipient: &PaymentCode,
+        designated_secret_key: &SecretKey,
+        outpoint: &Outpoint,
+    ) -> Result<[u8; 80], AddressError> {
+        let x = shared_secret(designated_secret_key, &recipient.derive_public_key(0)?)?;
+        blind(&self.payment_code().to_payload(), &x, outpoint)
+    }
+
+    /// Returns the OP_RETURN output of a notification transaction to 'recipient'
+    pub fn notification_output(
+        &self,
+        recipient: &PaymentCode,
+        designated_secret_key: &SecretKey,
+        outpoint: &Outpoint,
+    ) -> Result<BitcoinTransactionOutput, AddressError> {
+        let payload = self.notification_payload(recipient, designated_secret_key, outpoint)?;
+        let mut script_pub_key = vec![Opcode::OP_RETURN as u8, 0x4c, 80];
+        script_pub_key.extend(payload);
+        Ok(BitcoinTransactionOutput {
+            amount: crate::BitcoinAmount(0),
+            script_pub_key,
+        })
+    }
+
+    /// Returns the payment code of the sender of a notification transaction,
+    /// given its OP_RETURN 'payload' and the public key and outpoint of its
+    /// designated input
+    pub fn read_notification(
+        &self,
+        payload: &[u8],
+        designated_public_key: &PublicKey,
+        outpoint: &Outpoint,
+    ) -> Result<PaymentCode, AddressError> {
+        let x = shared_secret(&self.derive_secret_key(0)?, designated_public_key)?;
+        // blinding is its own inverse
+        PaymentCode::from_payload(&blind(payload, &x, outpoint)?)
+    }
+
+    /// Returns the shared secret of the payment at 'index' between our key at
+    /// 'own_index' and the key of 'other' at 'other_index'
+    fn payment_secret(
+        &self,
+        own_index: u32,
+        other: &PaymentCode,
+        other_index: u32,
+    ) -> Result<SecretKey, AddressError> {
+        let x = shared_secret(
+            &self.derive_secret_key(own_index)?,
+            &other.derive_public_key(other_index)?,
+        )?;
+        SecretKey::parse(&sha256(&x))
+            .map_err(|_| AddressError::Message("invalid payment code shared secret".into()))
+    }
+
+    /// Returns the public key of the 'index'th payment to 'recipient'
+    pub fn send_public_key(
+        &self,
+        recipient: &PaymentCode,
+        index: u32,
+    ) -> Result<PublicKey, AddressError> {
+        let secret = self.payment_secret(0, recipient, index)?;
+        let mut public_key = recipient.derive_public_key(index)?;
+        public_key
+            .tweak_add_assign(&secret)
+            .map_err(|_| AddressError::Message("invalid payment code payment key".into()))?;
+        Ok(public_key)
+    }
+
+    /// Returns the address of the 'index'th payment to 'recipient'
+    pub fn send_address<N: BitcoinNetwork>(
+        &self,
+        recipient: &PaymentCode,
+        index: u32,
+        format: &BitcoinFormat,
+    ) -> Result<BitcoinAddress<N>, AddressError> {
+        let public_key = BitcoinPublicKey::<N>::from_secp256k1_public_key(
+            self.send_public_key(recipient, index)?,
+            true,
+        );
+        BitcoinAddress::from_public_key(&public_key, format)
+    }
+
+    /// Returns the secret key spending the 'index'th payment from 'sender'
+    pub fn receive_secret_key(
+        &self,
+        sender: &PaymentCode,
+        index: u32,
+    ) -> Result<SecretKey, AddressError> {
+        let secret = self.payment_secret(index, sender, 0)?;
+        let mut secret_key = self.derive_secret_key(index)?;
+        secret_key
+            .tweak_add_assign(&secret)
+            .map_err(|_| AddressError::Message("invalid payment code payment key".into()))?;
+        Ok(secret_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, Txid};
+
+    // the BIP47 test vectors
+    const ALICE: &str = "PM8TJTLJbPRGxSbc8EJi42Wrr6QbNSaSSVJ5Y3E4pbCYiTHUskHg13935Ubb7q8tx9GVbh2UuRnBc3WSyJHhUrw8KhprKnn9eDznYGieTzFcwQRya4GA";
+    const BOB: &str = "PM8TJS2JxQ5ztXUpBBRnpTbcUXbUHy2T1abfrb3KkAAtMEGNbey4oumH7Hc578WgQJhPjBxteQ5GHHToTYHE3A1w6p7tU6KSoFmWBVbFGjKPisZDbP97";
+
+    fn secret(secret_key: &str, chain_code: &str) -> PaymentCodeSecret {
+        PaymentCodeSecret::new(
+            SecretKey::parse_slice(&hex::decode(secret_key).unwrap()).unwrap(),
+            hex::decode(chain_code).unwrap().try_into().unwrap(),
+        )
+    }
+
+    fn alice() -> PaymentCodeSecret {
+        secret(
+            "0a5c1795378b3ba756efcb5ca47e605c3f4f8bcff99eced897b45a4b051e980d",
+            "671af9f65904632e2dcc0c6ad314e11d53fc82fa4c4ea27a4a14eccecc478fee",
+        )
+    }
+
+    fn bob() -> PaymentCodeSecret {
+        secret(
+            "b7f3d1104fc72d8226b9d78ce9340aa8be76d979390c22cf491104775813a642",
+            "1db1243aaa57c7fbea3072249c1bd4dab9482b4fee4d25e1c69707e8144dc137",
+        )
+    }
+
+    #[test]
+    fn test_payment_code() {
+        let alice = alice().payment_code();
+        assert_eq!(ALICE, alice.to_string());
+        assert_eq!(alice, ALICE.parse().unwrap());
+        assert_eq!(BOB, bob().payment_code().to_string());
+
+        assert_eq!(
+            "1JDdmqFLhpzcUwPeinhJbUPw4Co3aWLyzW",
+            alice.notification_address::<Bitcoin>().unwrap().to_string()
+        );
+        assert_eq!(
+            "1ChvUUvht2hUQufHBXF8NgLhW8SwE2ecGV",
+            bob()
+                .payment_code()
+                .notification_address::<Bitcoin>()
+                .unwrap()
+                .to_string()
+        );
+
+        let mut invalid = ALICE.to_string();
+        invalid.replace_range(10..11, "x");
+        assert!(invalid.parse::<PaymentCode>().is_err());
+    }
+
+    #[test]
+    fn test_notification() {
+        let designated = SecretKey::parse_slice(
+            &hex::decode("1b7a10f45118e2519a8dd46ef81591c1ae501d082b6610fdda3de7a3c932880d")
+                .unwrap(),
+        )
+        .unwrap();
+        let outpoint = Outpoint::new(
+            Txid::from_slice_internal(
+                &hex::decode("86f411ab1c8e70ae8a0795ab7a6757aea6e4d5ae1826fc7b8f00c597d500609c")
+                    .unwrap(),
+            )
+            .unwrap(),
+            1,
+        );
+
+        let bob_code = bob().payment_code();
+        let output = alice()
+            .notification_output(&bob_code, &designated, &outpoint)
+            .unwrap();
+        assert_eq!(
+            "6a4c50010002063e4eb95e62791b06c50e1a3a942e1ecaaa9afbbeb324d16ae6821e091611fa96c0cf048f607fe51a0327f5e2528979311c78cb2de0d682c61e1180fc3d543b00000000000000000000000000",
+            hex::encode(&output.script_pub_key)
+        );
+
+        let sender = bob()
+            .read_notification(
+                &output.script_pub_key[3..],
+                &PublicKey::from_secret_key(&designated),
+                &outpoint,
+            )
+            .unwrap();
+        assert_eq!(alice().payment_code(), sender);
+    }
+
+    #[test]
+    fn test_payment_addresses() {
+        let bob_code = bob().payment_code();
+        let alice_code = alice().payment_code();
+        let addresses = [
+            "141fi7TY3h936vRUKh1qfUZr8rSBuYbVBK",
+            "12u3Uued2fuko2nY4SoSFGCoGLCBUGPkk6",
+            "1FsBVhT5dQutGwaPePTYMe5qvYqqjxyftc",
+        ];
+        for (index, address) in addresses.iter().enumerate() {
+            let sent = alice()
+                .send_address::<Bitcoin>(&bob_code, index as u32, &BitcoinFormat::P2PKH)
+                .unwrap();
+            assert_eq!(*address, sent.to_string());
+
+            let secret_key = bob().receive_secret_key(&alice_code, index as u32).unwrap();
+            assert_eq!(
+                alice().send_public_key(&bob_code, index as u32).unwrap(),
+                PublicKey::from_secret_key(&secret_key)
+            );
+        }
+    }
+}
@@ -0,0 +1,625 @@
+//!
+//! Block
+//!
+//! This module contains the block header and merkle branch verification needed
+//! by light clients to confirm that a transaction is included in a block, and
+//! the merkle roots a block commits to.
+//!
+
+use crate::{BitcoinTransactionId, SliceReader, Txid};
+use anychain_core::{
+    hex,
+    no_std::{
+        io::{Read, Write},
+        *,
+    },
+    TransactionError,
+};
+use core::{fmt, str::FromStr};
+use sha2::{Digest, Sha256};
+
+/// Returns the double SHA-256 digest of 'data'
+fn hash256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// Represents a Bitcoin block header (80 bytes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// The block version
+    pub version: i32,
+    /// The hash of the previous block in internal byte order
+    pub prev_blockhash: [u8; 32],
+    /// The merkle root of the transactions in internal byte order
+    pub merkle_root: [u8; 32],
+    /// The block timestamp
+    pub time: u32,
+    /// The proof of work target in compact form
+    pub bits: u32,
+    /// The nonce
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    /// The size of a serialized block header
+    pub const SIZE: usize = 80;
+
+    /// Read and output a block header
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, TransactionError> {
+        let mut header = [0u8; Self::SIZE];
+        reader.read_exact(&mut header)?;
+        Ok(Self::from_bytes(&header))
+    }
+
+    /// Returns the block header of the given bytes
+    pub fn from_bytes(header: &[u8; 80]) -> Self {
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+        Self {
+            version: u32_at(0) as i32,
+            prev_blockhash: header[4..36].try_into().unwrap(),
+            merkle_root: header[36..68].try_into().unwrap(),
+            time: u32_at(68),
+            bits: u32_at(72),
+            nonce: u32_at(76),
+        }
+    }
+
+    /// Writes the serialized block header to 'writer'
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), TransactionError> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the serialized block header
+    pub fn to_bytes(&self) -> [u8; 80] {
+        let mut header = [0u8; Self::SIZE];
+        header[0..4].copy_from_slice(&self.version.to_le_bytes());
+        header[4..36].copy_from_slice(&self.prev_blockhash);
+        header[36..68].copy_from_slice(&self.merkle_root);
+        header[68..72].copy_from_slice(&self.time.to_le_bytes());
+        header[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        header[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        header
+    }
+
+    /// Returns the block hash in internal byte order
+    pub fn hash(&self) -> [u8; 32] {
+        hash256(&self.to_bytes())
+    }
+
+    /// Returns the block hash in the byte order used by Bitcoin RPC
+    pub fn block_hash(&self) -> String {
+        let mut hash = self.hash();
+        hash.reverse();
+        hex::encode(hash)
+    }
+
+    /// Returns the proof of work target encoded by 'bits' as a big-endian
+    /// 256-bit integer, or `None` if the compact form is negative or overflows
+    pub fn target(&self) -> Option<[u8; 32]> {
+        let exponent = (self.bits >> 24) as usize;
+        let mantissa = self.bits & 0x007fffff;
+        if self.bits & 0x00800000 != 0 && mantissa != 0 {
+            return None;
+        }
+
+        let mut target = [0u8; 32];
+        if exponent <= 3 {
+            let value = mantissa >> (8 * (3 - exponent));
+            target[28..].copy_from_slice(&value.to_be_bytes());
+            return Some(target);
+        }
+        for (i, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+            // the byte is multiplied by 256^power
+            match exponent - 1 - i {
+                power @ 0..=31 => target[31 - power] = *byte,
+                _ if *byte != 0 => return None,
+                _ => {}
+            }
+        }
+        Some(target)
+    }
+
+    /// Returns true if the block hash meets the target encoded by 'bits'
+    pub fn check_pow(&self) -> bool {
+        match self.target() {
+            Some(target) if target != [0u8; 32] => {
+                let mut hash = self.hash();
+                hash.reverse();
+                hash <= target
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if the header is followed by an auxiliary proof of work
+    pub fn is_auxpow(&self) -> bool {
+        self.version & VERSION_AUXPOW != 0
+    }
+
+    /// Returns the merged mining chain id encoded in the upper bits of the version
+    pub fn chain_id(&self) -> u32 {
+        self.version as u32 >> 16
+    }
+
+    /// Returns true if the transaction with the given id is included in the
+    /// block according to the merkle 'proof'
+    pub fn verify_inclusion(&self, txid: &BitcoinTransactionId, proof: &MerkleProof) -> bool {
+        proof.root(txid.txid().to_bytes_internal()) == Some(self.merkle_root)
+    }
+}
+
+impl fmt::Display for BlockHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for BlockHeader {
+    type Err = TransactionError;
+
+    fn from_str(header: &str) -> Result<Self, Self::Err> {
+        let header = hex::decode(header)?;
+        let header: [u8; 80] = header.try_into().map_err(|header: Vec<u8>| {
+            TransactionError::Message(format!("invalid block header length: {}", header.len()))
+        })?;
+        Ok(Self::from_bytes(&header))
+    }
+}
+
+/// Represents a merkle branch proving that a transaction is a leaf of the
+/// merkle tree of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// The position of the transaction in the block
+    pub index: u32,
+    /// The sibling hashes from the leaf up to the root, in internal byte order
+    pub branch: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    /// Returns a new merkle proof
+    pub fn new(index: u32, branch: Vec<[u8; 32]>) -> Self {
+        Self { index, branch }
+    }
+
+    /// Returns the merkle proof of the transaction at 'index' of a block
+    /// whose transactions have the ids 'txids', in block order
+    pub fn from_txids(txids: &[Txid], index: u32) -> Option<Self> {
+        let mut level: Vec<[u8; 32]> = txids.iter().map(Txid::to_bytes_internal).collect();
+        let mut position = index as usize;
+        if position >= level.len() {
+            return None;
+        }
+
+        let mut branch = vec![];
+        while level.len() > 1 {
+            branch.push(*level.get(position ^ 1).unwrap_or(&level[position]));
+            level = merkle_parents(&level);
+            position /= 2;
+        }
+        Some(Self::new(index, branch))
+    }
+
+    /// Returns the merkle root obtained by hashing 'leaf' (in internal byte
+    /// order) up the branch, or `None` if the index does not fit the branch
+    pub fn root(&self, leaf: [u8; 32]) -> Option<[u8; 32]> {
+        if self.branch.len() < 32 && self.index >> self.branch.len() != 0 {
+            return None;
+        }
+
+        let mut hash = leaf;
+        for (depth, sibling) in self.branch.iter().enumerate() {
+            // a node is never paired with a copy of itself on the left (CVE-2012-2459)
+            hash = match (self.index >> depth) & 1 {
+                0 => hash256(&[hash, *sibling].concat()),
+                _ if *sibling == hash => return None,
+                _ => hash256(&[*sibling, hash].concat()),
+            };
+        }
+        Some(hash)
+    }
+}
+
+/// Returns the level above 'level' of a merkle tree, pairing the last hash
+/// with itself if the level has an odd number of hashes
+fn merkle_parents(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| hash256(&[pair[0], *pair.last().unwrap()].concat()))
+        .collect()
+}
+
+/// Returns the root of the merkle tree of 'leaves', or `None` if there are none
+fn merkle_root_of(mut leaves: Vec<[u8; 32]>) -> Option<[u8; 32]> {
+    while leaves.len() > 1 {
+        leaves = merkle_parents(&leaves);
+    }
+    leaves.first().copied()
+}
+
+/// Returns the merkle root of a block whose transactions have the ids
+/// 'txids', in block order, in the internal byte order of the block header.
+/// Returns `None` for a block without transactions.
+pub fn merkle_root(txids: &[Txid]) -> Option<[u8; 32]> {
+    merkle_root_of(txids.iter().map(Txid::to_bytes_internal).collect())
+}
+
+/// Returns the witness merkle root of a block whose transactions have the
+/// wtxids 'wtxids', in block order, the root committed to by the coinbase
+/// (BIP141). The wtxid of the coinbase is taken as zero.
+pub fn witness_merkle_root(wtxids: &[Txid]) -> Option<[u8; 32]> {
+    let mut leaves: Vec<[u8; 32]> = wtxids.iter().map(Txid::to_bytes_internal).collect();
+    if let Some(coinbase) = leaves.first_mut() {
+        *coinbase = [0u8; 32];
+    }
+    merkle_root_of(leaves)
+}
+
+/// The version bit of block headers followed by an auxiliary proof of work
+pub const VERSION_AUXPOW: i32 = 1 << 8;
+
+/// The merged mining chain id of Dogecoin
+pub const DOGECOIN_CHAIN_ID: u32 = 0x62;
+
+/// The magic bytes preceding the merged mining commitment in a coinbase script
+const MERGED_MINING_HEADER: [u8; 4] = [0xfa, 0xbe, 0x6d, 0x6d];
+
+/// Represents the auxiliary proof of work of a merge mined block (Namecoin,
+/// Dogecoin), proving that a block of the parent chain commits to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxPow {
+    /// The serialized coinbase transaction of the parent block
+    pub coinbase_tx: Vec<u8>,
+    /// The parent block hash, unused by consensus
+    pub parent_hash: [u8; 32],
+    /// The merkle branch linking the coinbase to the parent block merkle root
+    pub coinbase_branch: MerkleProof,
+    /// The merkle branch linking the block hash to the chain merkle root
+    /// committed in the coinbase
+    pub blockchain_branch: MerkleProof,
+    /// The header of the parent block
+    pub parent_block: BlockHeader,
+}
+
+impl AuxPow {
+    /// Returns true if the proof commits to the block with the given hash (in
+    /// internal byte order) of the chain 'chain_id'. The proof of work of the
+    /// parent block is checked separately, with the hash function of the
+    /// parent chain.
+    pub fn verify(&self, block_hash: &[u8; 32], chain_id: u32) -> bool {
+        if self.coinbase_branch.index != 0
+            || self.blockchain_branch.branch.len() > 30
+            || self.parent_block.chain_id() == chain_id
+        {
+            return false;
+        }
+        let coinbase = hash256(&self.coinbase_tx);
+        if self.coinbase_branch.root(coinbase) != Some(self.parent_block.merkle_root) {
+            return false;
+        }
+        let mut root = match self.blockchain_branch.root(*block_hash) {
+            Some(root) => root,
+            None => return false,
+        };
+        root.reverse();
+
+        let script = match crate::BitcoinTransactionRef::parse(&self.coinbase_tx) {
+            Ok(transaction) if !transaction.inputs.is_empty() => transaction.inputs[0].script_sig,
+            _ => return false,
+        };
+        let find = |needle: &[u8], from: usize| {
+            script
+                .get(from..)
+                .and_then(|script| script.windows(needle.len()).position(|w| w == needle))
+                .map(|position| from + position)
+        };
+
+        let position = match find(&root, 0) {
+            Some(position) => position,
+            None => return false,
+        };
+        match find(&MERGED_MINING_HEADER, 0) {
+            // the merged mining header must be unique and precede the root
+            Some(header) => {
+                if find(&MERGED_MINING_HEADER, header + 1).is_some() || header + 4 != position {
+                    return false;
+                }
+            }
+            // legacy commitments without the header start the script
+            None if position > 20 => return false,
+            None => {}
+        }
+
+        let u32_at = |offset: usize| {
+            script
+                .get(offset..offset + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        let (size, nonce) = match (u32_at(position + 32), u32_at(position + 36)) {
+            (Some(size), Some(nonce)) => (size, nonce),
+            _ => return false,
+        };
+        let height = self.blockchain_branch.branch.len();
+        size == 1 << height
+            && self.blockchain_branch.index == expected_index(nonce, chain_id, height)
+    }
+
+    /// Returns the serialized auxiliary proof of work
+    pub fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut bytes = self.coinbase_tx.clone();
+        bytes.extend(self.parent_hash);
+        for proof in [&self.coinbase_branch, &self.blockchain_branch] {
+            bytes.extend(crate::variable_length_integer(proof.branch.len() as u64)?);
+            proof.branch.iter().for_each(|hash| bytes.extend(hash));
+            bytes.extend(proof.index.to_le_bytes());
+        }
+        bytes.extend(self.parent_block.to_bytes());
+        Ok(bytes)
+    }
+}
+
+/// Returns the slot of the chain 'chain_id' in a chain merkle tree of the
+/// given height, derived from the merged mining 'nonce'
+fn expected_index(nonce: u32, chain_id: u32, height: usize) -> u32 {
+    let mut rand = nonce;
+    rand = rand.wrapping_mul(1103515245).wrapping_add(12345);
+    rand = rand.wrapping_add(chain_id);
+    rand = rand.wrapping_mul(1103515245).wrapping_add(12345);
+    rand % (1 << height)
+}
+
+/// Represents the header of a block of a merge mined chain, carrying an
+/// auxiliary proof of work when the version has the VERSION_AUXPOW bit set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxPowHeader {
+    /// The block header
+    pub header: BlockHeader,
+    /// The auxiliary proof of work, if any
+    pub auxpow: Option<AuxPow>,
+}
+
+impl AuxPowHeader {
+    /// Parses a header at the start of 'bytes', returning it with its size
+    pub fn parse(bytes: &[u8]) -> Result<(Self, usize), TransactionError> {
+        let mut reader = SliceReader { bytes, position: 0 };
+        let header = BlockHeader::from_bytes(reader.take(BlockHeader::SIZE)?.try_into().unwrap());
+        if !header.is_auxpow() {
+            return Ok((
+                Self {
+                    header,
+                    auxpow: None,
+                },
+                reader.position,
+            ));
+        }
+
+        let coinbase_tx = crate::BitcoinTransactionRef::parse(&bytes[reader.position..])?;
+        let coinbase_tx = reader.take(coinbase_tx.as_bytes().len())?.to_vec();
+        let parent_hash = reader.take(32)?.try_into().unwrap();
+        let mut read_branch = || -> Result<MerkleProof, TransactionError> {
+            let count = reader.count(32, "merkle branch")?;
+            let branch = (0..count)
+                .map(|_| Ok(reader.take(32)?.try_into().unwrap()))
+                .collect::<Result<Vec<[u8; 32]>, TransactionError>>()?;
+            Ok(MerkleProof::new(reader.u32()?, branch))
+        };
+        let coinbase_branch = read_branch()?;
+        let blockchain_branch = read_branch()?;
+        let parent_block =
+            BlockHeader::from_bytes(reader.take(BlockHeader::SIZE)?.try_into().unwrap());
+
+        Ok((
+            Self {
+                header,
+                auxpow: Some(AuxPow {
+                    coinbase_tx,
+                    parent_hash,
+                    coinbase_branch,
+                    blockchain_branch,
+                    parent_block,
+                }),
+            },
+            reader.position,
+        ))
+    }
+
+    /// Returns the serialized header followed by its auxiliary proof of work
+    pub fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut bytes = self.header.to_bytes().to_vec();
+        if let Some(auxpow) = &self.auxpow {
+            bytes.extend(auxpow.to_bytes()?);
+        }
+        Ok(bytes)
+    }
+
+    /// Returns true if the header carries an auxiliary proof of work that
+    /// commits to it for the chain 'chain_id'
+    pub fn verify_auxpow(&self, chain_id: u32) -> bool {
+        match &self.auxpow {
+            Some(auxpow) => {
+                self.header.chain_id() == chain_id && auxpow.verify(&self.header.hash(), chain_id)
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinTransaction};
+    use anychain_core::Transaction;
+
+    // the header of block 100000
+    const HEADER: &str = "0100000050120119172a610421a6c3011dd330d9df07b63616c2cc1f1cd00200000000006657a9252aacd5c0b2940996ecff952228c3067cc38d4885efb5a4ac4247e9f337221b4d4c86041b0f2b5710";
+
+    fn hash(hex: &str) -> [u8; 32] {
+        let mut hash: [u8; 32] = hex::decode(hex).unwrap().try_into().unwrap();
+        hash.reverse();
+        hash
+    }
+
+    #[test]
+    fn test_block_header() {
+        let header = BlockHeader::from_str(HEADER).unwrap();
+        assert_eq!(1, header.version);
+        assert_eq!(0x1b04864c, header.bits);
+        assert_eq!(274148111, header.nonce);
+        assert_eq!(HEADER, header.to_string());
+        assert_eq!(
+            "000000000003ba27aa200b1cecaad478d2b00432346c3f1f3986da1afd33e506",
+            header.block_hash()
+        );
+        assert_eq!(
+            "000000000004864c000000000000000000000000000000000000000000000000",
+            hex::encode(header.target().unwrap())
+        );
+        assert!(header.check_pow());
+
+        let mut tampered = header;
+        tampered.nonce += 1;
+        assert!(!tampered.check_pow());
+
+        let mut bytes = vec![];
+        header.write_to(&mut bytes).unwrap();
+        assert_eq!(header, BlockHeader::read(&bytes[..]).unwrap());
+        assert!(BlockHeader::read(&bytes[..79]).is_err());
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        let header = BlockHeader::from_str(HEADER).unwrap();
+        let txids = [
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ];
+        let leaves: Vec<[u8; 32]> = txids.iter().map(|txid| hash(txid)).collect();
+        let left = hash256(&[leaves[0], leaves[1]].concat());
+        let right = hash256(&[leaves[2], leaves[3]].concat());
+
+        let proof = MerkleProof::new(2, vec![leaves[3], left]);
+        assert_eq!(Some(header.merkle_root), proof.root(leaves[2]));
+        let proof = MerkleProof::new(1, vec![leaves[0], right]);
+        assert_eq!(Some(header.merkle_root), proof.root(leaves[1]));
+
+        assert_ne!(Some(header.merkle_root), proof.root(leaves[2]));
+        assert_eq!(
+            None,
+            MerkleProof::new(4, vec![leaves[0], right]).root(leaves[1])
+        );
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let header = BlockHeader::from_str(HEADER).unwrap();
+        let txids: Vec<Txid> = [
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ]
+        .iter()
+        .map(|txid| Txid::from_str(txid).unwrap())
+        .collect();
+        assert_eq!(Some(header.merkle_root), merkle_root(&txids));
+        assert_eq!(None, merkle_root(&[]));
+        assert_eq!(Some(txids[0].to_bytes_internal()), merkle_root(&txids[..1]));
+
+        for index in 0..4 {
+            let proof = MerkleProof::from_txids(&txids, index).unwrap();
+            let leaf = txids[index as usize].to_bytes_internal();
+            assert_eq!(Some(header.merkle_root), proof.root(leaf));
+        }
+        assert_eq!(None, MerkleProof::from_txids(&txids, 4));
+
+        // the last hash of an odd level is paired with itself
+        let leaves: Vec<[u8; 32]> = txids.iter().map(Txid::to_bytes_internal).collect();
+        let root = hash256(
+            &[
+                hash256(&[leaves[0], leaves[1]].concat()),
+                hash256(&[leaves[2], leaves[2]].concat()),
+            ]
+            .concat(),
+        );
+        assert_eq!(Some(root), merkle_root(&txids[..3]));
+        let proof = MerkleProof::from_txids(&txids[..3], 2).unwrap();
+        assert_eq!(Some(root), proof.root(leaves[2]));
+
+        let mut wtxids = txids.clone();
+        wtxids[0] = Txid::default();
+        assert_eq!(merkle_root(&wtxids), witness_merkle_root(&txids));
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        // the only transaction of block 1 is its coinbase, so the merkle root is its txid
+        let header = BlockHeader::from_str("010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299").unwrap();
+        let coinbase = BitcoinTransaction::<Bitcoin>::from_str("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82cbf2342c858eeac00000000").unwrap();
+        let txid = coinbase.to_transaction_id().unwrap();
+
+        assert!(header.check_pow());
+        assert!(header.verify_inclusion(&txid, &MerkleProof::new(0, vec![])));
+        assert!(!header.verify_inclusion(&txid, &MerkleProof::new(0, vec![[0; 32]])));
+    }
+
+    #[test]
+    fn test_auxpow() {
+        let mut header = BlockHeader::from_str(HEADER).unwrap();
+        header.version = (DOGECOIN_CHAIN_ID << 16) as i32 | VERSION_AUXPOW | 4;
+        let mut commitment = header.hash();
+        commitment.reverse();
+
+        // a parent coinbase committing to the header in a chain merkle tree of size one
+        let script = [
+            &[0x03, 0x01, 0x02, 0x03][..],
+            &MERGED_MINING_HEADER,
+            &commitment,
+            &1u32.to_le_bytes(),
+            &0u32.to_le_bytes(),
+        ]
+        .concat();
+        let coinbase_tx = [
+            &hex::decode("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff").unwrap()[..],
+            &[script.len() as u8],
+            &script,
+            &hex::decode("ffffffff0100f2052a010000000151").unwrap(),
+            &[0; 4],
+        ]
+        .concat();
+        let mut parent_block = BlockHeader::from_str(HEADER).unwrap();
+        parent_block.merkle_root = hash256(&coinbase_tx);
+
+        let auxpow_header = AuxPowHeader {
+            header,
+            auxpow: Some(AuxPow {
+                coinbase_tx,
+                parent_hash: [0; 32],
+                coinbase_branch: MerkleProof::new(0, vec![]),
+                blockchain_branch: MerkleProof::new(0, vec![]),
+                parent_block,
+            }),
+        };
+        let bytes = [auxpow_header.to_bytes().unwrap(), vec![0x00]].concat();
+        let (parsed, size) = AuxPowHeader::parse(&bytes).unwrap();
+        assert_eq!(auxpow_header, parsed);
+        assert_eq!(bytes.len() - 1, size);
+        assert!(parsed.header.is_auxpow());
+        assert!(parsed.verify_auxpow(DOGECOIN_CHAIN_ID));
+        assert!(!parsed.verify_auxpow(1));
+
+        let mut tampered = parsed.clone();
+        tampered.header.nonce += 1;
+        assert!(!tampered.verify_auxpow(DOGECOIN_CHAIN_ID));
+        let mut tampered = parsed;
+        tampered.auxpow.as_mut().unwrap().coinbase_tx[50] ^= 1;
+        assert!(!tampered.verify_auxpow(DOGECOIN_CHAIN_ID));
+
+        // a header without the auxpow bit is a plain 80 byte header
+        let (plain, size) = AuxPowHeader::parse(&hex::decode(HEADER).unwrap()).unwrap();
+        assert_eq!((None, BlockHeader::SIZE), (plain.auxpow, size));
+    }
+}
@@ -0,0 +1,177 @@
+//!
+//! Transaction Builder
+//!
+//! This module implements the transaction builder of the core crate for
+//! Bitcoin, so that wallets holding several chains can build Bitcoin transfers
+//! like those of other chains: the builder spends the unspent outputs of the
+//! sender in the order they are given until the recipients and the fee are
+//! covered, and pays the rest back to the sender unless it is dust.
+//!
+
+use crate::{
+    create_script_pub_key, BitcoinAddress, BitcoinAmount, BitcoinNetwork, BitcoinTransaction,
+    BitcoinTransactionOutput, BitcoinTransactionParameters, SignatureHash, Utxo,
+};
+use anychain_core::{no_std::*, Transaction, TransactionBuilder, TransactionError};
+
+/// Builds Bitcoin transfers from the unspent outputs of a wallet
+#[derive(Debug, Clone)]
+pub struct BitcoinTransactionBuilder<N: BitcoinNetwork> {
+    utxos: Vec<Utxo<N>>,
+    sender: Option<BitcoinAddress<N>>,
+    outputs: Vec<BitcoinTransactionOutput>,
+    fee: BitcoinAmount,
+    sighash: SignatureHash,
+}
+
+impl<N: BitcoinNetwork> BitcoinTransactionBuilder<N> {
+    /// Returns a builder spending 'utxos', those paying to the sender
+    pub fn new(utxos: Vec<Utxo<N>>) -> Self {
+        Self {
+            utxos,
+            sender: None,
+            outputs: vec![],
+            fee: BitcoinAmount(0),
+            sighash: SignatureHash::SIGHASH_ALL,
+        }
+    }
+
+    /// Returns the builder signing the inputs with 'sighash' instead of
+    /// SIGHASH_ALL
+    pub fn with_sighash(mut self, sighash: SignatureHash) -> Self {
+        self.sighash = sighash;
+        self
+    }
+}
+
+impl<N: BitcoinNetwork> TransactionBuilder for BitcoinTransactionBuilder<N> {
+    type Transaction = BitcoinTransaction<N>;
+    type Amount = BitcoinAmount;
+    type Fee = BitcoinAmount;
+
+    fn set_sender(&mut self, sender: BitcoinAddress<N>) -> &mut Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    fn add_recipient(
+        &mut self,
+        recipient: BitcoinAddress<N>,
+        amount: BitcoinAmount,
+    ) -> Result<&mut Self, TransactionError> {
+        let output = BitcoinTransactionOutput::new(recipient, amount)?;
+        if output.is_dust() {
+            return Err(TransactionError::Message(format!(
+                "the amount {} of the recipient is dust",
+                amount
+            )));
+        }
+        self.outputs.push(output);
+        Ok(self)
+    }
+
+    fn set_fee(&mut self, fee: BitcoinAmount) -> &mut Self {
+        self.fee = fee;
+        self
+    }
+
+    fn build(&self) -> Result<BitcoinTransaction<N>, TransactionError> {
+        let sender = self
+            .sender
+            .clone()
+            .ok_or(TransactionError::InvalidInputs("sender".into()))?;
+        if self.outputs.is_empty() {
+            return Err(TransactionError::InvalidInputs("recipients".into()));
+        }
+        let script_pub_key = create_script_pub_key(&sender)?;
+
+        let mut target = self.fee;
+        for output in &self.outputs {
+            target = target.checked_add(output.amount)?;
+        }
+        let mut inputs = vec![];
+        let mut funds = BitcoinAmount(0);
+        for utxo in &self.utxos {
+            if funds >= target {
+                break;
+            }
+            if utxo.script_pub_key == script_pub_key {
+                inputs.push(utxo.to_input(self.sighash)?);
+                funds = funds.checked_add(utxo.amount)?;
+            }
+        }
+        if funds < target {
+            return Err(TransactionError::Message(format!(
+                "insufficient funds: {} of {} satoshis",
+                funds, target
+            )));
+        }
+
+        let mut outputs = self.outputs.clone();
+        let change = BitcoinTransactionOutput {
+            amount: funds.checked_sub(target)?,
+            script_pub_key,
+        };
+        // dust change is left to the fee
+        if change.amount.0 > 0 && !change.is_dust() {
+            outputs.push(change);
+        }
+        BitcoinTransaction::new(&BitcoinTransactionParameters::new(inputs, outputs)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, Outpoint, Txid};
+    use core::str::FromStr;
+
+    const TXID: &str = "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9";
+
+    fn address(address: &str) -> BitcoinAddress<Bitcoin> {
+        BitcoinAddress::from_str(address).unwrap()
+    }
+
+    #[test]
+    fn test_build() {
+        let sender = address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        let recipient = address("1HLoD9E4SDFFPDiYfNYnkBLQ85Y51J3Zb1");
+        let utxos = [10_000, 20_000, 30_000]
+            .into_iter()
+            .enumerate()
+            .map(|(index, amount)| {
+                Utxo::new(
+                    Outpoint::new(Txid::from_str(TXID).unwrap(), index as u32),
+                    BitcoinAmount(amount),
+                    create_script_pub_key(&sender).unwrap(),
+                )
+            })
+            .collect();
+        let mut builder = BitcoinTransactionBuilder::<Bitcoin>::new(utxos);
+        assert!(builder.build().is_err());
+
+        builder
+            .set_sender(sender.clone())
+            .add_recipient(recipient.clone(), BitcoinAmount(25_000))
+            .unwrap()
+            .set_fee(BitcoinAmount(1_000));
+        let transaction = builder.build().unwrap();
+        assert_eq!(2, transaction.parameters.inputs.len());
+        let outputs = &transaction.parameters.outputs;
+        assert_eq!(BitcoinAmount(25_000), outputs[0].amount);
+        assert_eq!(BitcoinAmount(4_000), outputs[1].amount);
+        assert_eq!(
+            create_script_pub_key(&sender).unwrap(),
+            outputs[1].script_pub_key
+        );
+
+        // the dust change goes to the fee
+        builder.set_fee(BitcoinAmount(4_900));
+        assert_eq!(1, builder.build().unwrap().parameters.outputs.len());
+        assert!(builder
+            .add_recipient(recipient.clone(), BitcoinAmount(100))
+            .is_err());
+        builder.add_recipient_value(recipient, 40_000).unwrap();
+        assert!(builder.build().is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//!
+//! CashTokens
+//!
+//! This module implements the token prefix of Bitcoin Cash outputs (CHIP-2022-02),
+//! which is serialized at the start of the locking bytecode field and carries a
+//! token category with a fungible token amount and/or a non-fungible token.
+//!
+
+use crate::{create_script_pub_key, BitcoinAddress, BitcoinAmount, BitcoinNetwork};
+use crate::{BitcoinTransactionOutput, SliceReader};
+use anychain_core::{hex, no_std::*, TransactionError};
+use core::fmt;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+
+/// The byte starting the token prefix of an output
+pub const PREFIX_TOKEN: u8 = 0xef;
+
+/// The maximum length of a non-fungible token commitment
+pub const MAX_COMMITMENT_LENGTH: usize = 40;
+
+/// The largest amount of fungible tokens an output may carry
+pub const MAX_FUNGIBLE_AMOUNT: u64 = i64::MAX as u64;
+
+const RESERVED_BIT: u8 = 0x80;
+const HAS_COMMITMENT_LENGTH: u8 = 0x40;
+const HAS_NFT: u8 = 0x20;
+const HAS_AMOUNT: u8 = 0x10;
+
+/// The capability of a non-fungible token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NftCapability {
+    /// The commitment can not be changed
+    None = 0,
+    /// The commitment can be changed once per transaction
+    Mutable = 1,
+    /// Any number of tokens of the category can be created
+    Minting = 2,
+}
+
+impl fmt::Display for NftCapability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NftCapability::None => write!(f, "none"),
+            NftCapability::Mutable => write!(f, "mutable"),
+            NftCapability::Minting => write!(f, "minting"),
+        }
+    }
+}
+
+/// Represents the non-fungible token carried by an output
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenNft {
+    /// The capability of the token
+    pub capability: NftCapability,
+    /// The commitment of the token, up to 40 bytes
+    pub commitment: Vec<u8>,
+}
+
+/// Represents the tokens carried by an output
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenData {
+    /// The category id, the txid of the category genesis outpoint in
+    /// internal byte order
+    pub category: [u8; 32],
+    /// The amount of fungible tokens, zero if the output carries none
+    pub amount: u64,
+    /// The non-fungible token, if any
+    pub nft: Option<TokenNft>,
+}
+
+impl TokenData {
+    /// Returns the token prefix of the tokens
+    pub fn to_prefix(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut bitfield = 0u8;
+        if let Some(nft) = &self.nft {
+            bitfield |= HAS_NFT | nft.capability as u8;
+            if !nft.commitment.is_empty() {
+                bitfield |= HAS_COMMITMENT_LENGTH;
+            }
+        }
+        if self.amount > 0 {
+            bitfield |= HAS_AMOUNT;
+        }
+
+        let mut prefix = vec![PREFIX_TOKEN];
+        prefix.extend(self.category);
+        prefix.push(bitfield);
+        if let Some(nft) = self.nft.as_ref().filter(|nft| !nft.commitment.is_empty()) {
+            prefix.extend(crate::variable_length_integer(nft.commitment.len() as u64)?);
+            prefix.extend(&nft.commitment);
+        }
+        if self.amount > 0 {
+            prefix.extend(crate::variable_length_integer(self.amount)?);
+        }
+
+        // encode first, validate the result with the decoder's rules
+        Self::from_prefix(&prefix)?;
+        Ok(prefix)
+    }
+
+    /// Returns the tokens of a token prefix and the size of the prefix, given
+    /// the locking bytecode field of an output starting with PREFIX_TOKEN
+    pub fn from_prefix(bytes: &[u8]) -> Result<(Self, usize), TransactionError> {
+        let invalid = |message: &str| {
+            Err(TransactionError::Message(format!(
+                "invalid token prefix: {}",
+                message
+            )))
+        };
+
+        let mut reader = SliceReader { bytes, position: 0 };
+        if reader.u8()? != PREFIX_TOKEN {
+            return invalid("missing PREFIX_TOKEN");
+        }
+        let category: [u8; 32] = reader.take(32)?.try_into().unwrap();
+        let bitfield = reader.u8()?;
+
+        if bitfield & RESERVED_BIT != 0 {
+            return invalid("reserved bit is set");
+        }
+        if bitfield & (HAS_NFT | HAS_AMOUNT) == 0 {
+            return invalid("no tokens");
+        }
+        if bitfield & HAS_NFT == 0 && bitfield & (HAS_COMMITMENT_LENGTH | 0x0f) != 0 {
+            return invalid("commitment or capability without a non-fungible token");
+        }
+        let capability = match bitfield & 0x0f {
+            0 => NftCapability::None,
+            1 => NftCapability::Mutable,
+            2 => NftCapability::Minting,
+            _ => return invalid("unknown capability"),
+        };
+
+        let nft = match bitfield & HAS_NFT != 0 {
+            true => {
+                let commitment = match bitfield & HAS_COMMITMENT_LENGTH != 0 {
+                    true => reader.bytes(MAX_COMMITMENT_LENGTH, "commitment")?.to_vec(),
+                    false => vec![],
+                };
+                if bitfield & HAS_COMMITMENT_LENGTH != 0 && commitment.is_empty() {
+                    return invalid("empty commitment");
+                }
+                Some(TokenNft {
+                    capability,
+                    commitment,
+                })
+            }
+            false => None,
+        };
+
+        let amount = match bitfield & HAS_AMOUNT != 0 {
+            true => read_amount(&mut reader)?,
+            false => 0,
+        };
+        if bitfield & HAS_AMOUNT != 0 && !(1..=MAX_FUNGIBLE_AMOUNT).contains(&amount) {
+            return invalid("fungible token amount out of range");
+        }
+
+        Ok((
+            Self {
+                category,
+                amount,
+                nft,
+            },
+            reader.position,
+        ))
+    }
+
+    /// Returns the tokens in the format of the `tokenData` of the Bitcoin Cash
+    /// node JSON, with the category in display byte order
+    pub fn to_json(&self) -> Value {
+        let mut category = self.category;
+        category.reverse();
+
+        let mut token = json!({
+            "category": hex::encode(category),
+            "amount": self.amount.to_string(),
+        });
+        if let Some(nft) = &self.nft {
+            token["nft"] = json!({
+                "capability": nft.capability.to_string(),
+                "commitment": hex::encode(&nft.commitment),
+            });
+        }
+        token
+    }
+}
+
+impl Serialize for TokenData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+/// Reads a fungible token amount, a minimally encoded compact size
+fn read_amount(reader: &mut SliceReader) -> Result<u64, TransactionError> {
+    let (amount, min) = match reader.u8()? {
+        flag @ 0..=252 => return Ok(flag as u64),
+        0xfd => (
+            u16::from_le_bytes(reader.take(2)?.try_into().unwrap()) as u64,
+            253,
+        ),
+        0xfe => (reader.u32()? as u64, 65536),
+        _ => (reader.u64()?, 4294967296),
+    };
+    match amount < min {
+        true => Err(TransactionError::InvalidVariableSizeInteger(
+            amount as usize,
+        )),
+        false => Ok(amount),
+    }
+}
+
+impl BitcoinTransactionOutput {
+    /// Returns an output paying 'amount' and 'token' to 'address'
+    pub fn token_output<N: BitcoinNetwork>(
+        address: BitcoinAddress<N>,
+        amount: BitcoinAmount,
+        token: &TokenData,
+    ) -> Result<Self, TransactionError> {
+        Ok(Self {
+            amount,
+            script_pub_key: [token.to_prefix()?, create_script_pub_key::<N>(&address)?].concat(),
+        })
+    }
+
+    /// Returns the tokens carried by the output, if its locking bytecode field
+    /// starts with a token prefix
+    pub fn token_data(&self) -> Result<Option<TokenData>, TransactionError> {
+        match self.script_pub_key.first() {
+            Some(&PREFIX_TOKEN) => Ok(Some(TokenData::from_prefix(&self.script_pub_key)?.0)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the locking bytecode of the output without its token prefix. An
+    /// output whose token prefix is invalid is returned whole.
+    pub fn locking_bytecode(&self) -> &[u8] {
+        match self.script_pub_key.first() {
+            Some(&PREFIX_TOKEN) => match TokenData::from_prefix(&self.script_pub_key) {
+                Ok((_, size)) => &self.script_pub_key[size..],
+                Err(_) => &self.script_pub_key,
+            },
+            _ => &self.script_pub_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitcoinCash;
+    use core::str::FromStr;
+
+    const CATEGORY: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    fn prefix(bitfield_and_data: &str) -> Vec<u8> {
+        hex::decode(format!("ef{}{}", CATEGORY, bitfield_and_data)).unwrap()
+    }
+
+    #[test]
+    fn test_from_prefix() {
+        let (token, size) = TokenData::from_prefix(&prefix("1001")).unwrap();
+        assert_eq!((1, None, 35), (token.amount, token.nft, size));
+
+        let (token, _) = TokenData::from_prefix(&prefix("20")).unwrap();
+        assert_eq!(0, token.amount);
+        assert_eq!(NftCapability::None, token.nft.unwrap().capability);
+
+        let (token, _) = TokenData::from_prefix(&prefix("7201ccfdfd00")).unwrap();
+        let nft = token.nft.unwrap();
+        assert_eq!(253, token.amount);
+        assert_eq!(
+            (NftCapability::Minting, vec![0xcc]),
+            (nft.capability, nft.commitment)
+        );
+
+        for invalid in [
+            "00",                   // no tokens
+            "a0",                   // reserved bit
+            "23",                   // unknown capability
+            "40",                   // commitment without a non-fungible token
+            "01",                   // capability without a non-fungible token
+            "6000",                 // empty commitment
+            "1000",                 // zero amount
+            "10fdfc00",             // non-minimal amount
+            "10ffffffffffffffffff", // amount above i64::MAX
+            "10",                   // missing amount
+        ] {
+            assert!(
+                TokenData::from_prefix(&prefix(invalid)).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_token_output() {
+        let address = BitcoinAddress::<BitcoinCash>::from_str(
+            "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+        )
+        .unwrap();
+        let token = TokenData {
+            category: hex::decode(CATEGORY).unwrap().try_into().unwrap(),
+            amount: 1000,
+            nft: Some(TokenNft {
+                capability: NftCapability::Mutable,
+                commitment: b"ticket".to_vec(),
+            }),
+        };
+        let output =
+            BitcoinTransactionOutput::token_output(address, BitcoinAmount(1000), &token).unwrap();
+
+        assert_eq!(Some(token.clone()), output.token_data().unwrap());
+        assert_eq!(
+            "76a91476a04053bda0a88bda5177b86a15c3b29f55987388ac",
+            hex::encode(output.locking_bytecode())
+        );
+
+        let plain = BitcoinTransactionOutput {
+            amount: BitcoinAmount(1000),
+            script_pub_key: output.locking_bytecode().to_vec(),
+        };
+        assert_eq!(None, plain.token_data().unwrap());
+        assert_eq!(plain.script_pub_key, plain.locking_bytecode());
+
+        let json = token.to_json();
+        assert_eq!("1000", json["amount"]);
+        assert_eq!("mutable", json["nft"]["capability"]);
+        assert_eq!(hex::encode(b"ticket"), json["nft"]["commitment"]);
+    }
+}
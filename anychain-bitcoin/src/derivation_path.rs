@@ -0,0 +1,158 @@
+//!
+//! Derivation Paths
+//!
+//! This module implements the account structure of BIP44, BIP49, BIP84 and
+//! BIP86 over the derivation paths of core, such as `m/84'/0'/0'/0/0`, whose
+//! purpose determines the format of the addresses derived under it.
+//!
+
+use crate::{
+    BitcoinAddress, BitcoinExtendedPrivateKey, BitcoinExtendedPublicKey, BitcoinFormat,
+    BitcoinNetwork, BitcoinPublicKey,
+};
+use anychain_core::{is_hardened, Address, AddressError, ExtendedKeyError, HARDENED_OFFSET};
+
+pub use anychain_core::{harden, DerivationPath};
+
+/// The purpose of a BIP44 style derivation path, which determines the
+/// address format of the path
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    /// P2PKH addresses (BIP44)
+    Bip44 = 44,
+    /// P2SH-P2WPKH addresses (BIP49)
+    Bip49 = 49,
+    /// P2WPKH addresses (BIP84)
+    Bip84 = 84,
+    /// P2TR addresses with a key path spend only (BIP86)
+    Bip86 = 86,
+}
+
+impl Purpose {
+    /// Returns the purpose of the unhardened index 'index'
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            44 => Some(Purpose::Bip44),
+            49 => Some(Purpose::Bip49),
+            84 => Some(Purpose::Bip84),
+            86 => Some(Purpose::Bip86),
+            _ => None,
+        }
+    }
+
+    /// Returns the purpose of 'path', if it follows BIP44, BIP49, BIP84 or
+    /// BIP86
+    pub fn of(path: &DerivationPath) -> Option<Self> {
+        match path.as_slice().first() {
+            Some(index) if is_hardened(*index) => Self::from_index(index - HARDENED_OFFSET),
+            _ => None,
+        }
+    }
+
+    /// Returns the path `m/purpose'/coin_type'/account'/chain/index` of the
+    /// purpose with the coin type of the network
+    pub fn path<N: BitcoinNetwork>(&self, account: u32, chain: u32, index: u32) -> DerivationPath {
+        DerivationPath::bip44(*self as u32, N::COIN_TYPE, account, chain, index)
+    }
+
+    /// Returns the address format of the purpose. The format of a BIP86 path
+    /// is Bech32, with the taproot output key as its witness program.
+    pub fn format(&self) -> BitcoinFormat {
+        match self {
+            Purpose::Bip44 => BitcoinFormat::P2PKH,
+            Purpose::Bip49 => BitcoinFormat::P2SH_P2WPKH,
+            Purpose::Bip84 | Purpose::Bip86 => BitcoinFormat::Bech32,
+        }
+    }
+
+    /// Returns the address of 'public_key' under the purpose
+    pub fn to_address<N: BitcoinNetwork>(
+        &self,
+        public_key: &BitcoinPublicKey<N>,
+    ) -> Result<BitcoinAddress<N>, AddressError> {
+        match self {
+            Purpose::Bip86 => BitcoinAddress::p2tr(public_key, None),
+            purpose => BitcoinAddress::from_public_key(public_key, &purpose.format()),
+        }
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinExtendedPrivateKey<N> {
+    /// Returns the descendant key at 'path' relative to the key
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self, ExtendedKeyError> {
+        path.iter()
+            .try_fold(self.clone(), |key, index| key.ckd_priv(*index))
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinExtendedPublicKey<N> {
+    /// Returns the descendant key at 'path' relative to the key, failing if
+    /// the path has a hardened index
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self, ExtendedKeyError> {
+        path.iter()
+            .try_fold(self.clone(), |key, index| key.ckd_pub(*index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, Litecoin};
+    use anychain_core::{Mnemonic, PrivateKey};
+    use core::str::FromStr;
+
+    #[test]
+    fn test_purpose_path() {
+        let path = DerivationPath::from_str("m/84'/0'/0'/0/5").unwrap();
+        assert_eq!(&[harden(84), harden(0), harden(0), 0, 5], path.as_slice());
+        assert_eq!(Some(Purpose::Bip84), Purpose::of(&path));
+        assert_eq!(path, Purpose::Bip84.path::<Bitcoin>(0, 0, 5));
+        assert_eq!(
+            "m/49'/2'/1'/1/0",
+            Purpose::Bip49.path::<Litecoin>(1, 1, 0).to_string()
+        );
+        assert_eq!(None, Purpose::of(&DerivationPath::new(vec![84, 0])));
+        assert_eq!(
+            None,
+            Purpose::of(&DerivationPath::from_str("m/48'/0'").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_purpose_addresses() {
+        let mnemonic = Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master = BitcoinExtendedPrivateKey::<Bitcoin>::from_mnemonic(&mnemonic, "").unwrap();
+
+        // the first receive address of the first account of each BIP
+        for (purpose, address) in [
+            (Purpose::Bip44, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"),
+            (Purpose::Bip49, "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf"),
+            (Purpose::Bip84, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"),
+            (
+                Purpose::Bip86,
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+        ] {
+            let path = purpose.path::<Bitcoin>(0, 0, 0);
+            let key = master.derive(&path).unwrap().private_key.to_public_key();
+            assert_eq!(address, purpose.to_address(&key).unwrap().to_string());
+        }
+
+        // normal children can be derived from the account extended public key
+        let account = master
+            .derive(&DerivationPath::from_str("m/84'/0'/0'").unwrap())
+            .unwrap();
+        let receive = DerivationPath::new(vec![0, 0]);
+        assert_eq!(
+            account.derive(&receive).unwrap().to_extended_public_key(),
+            account.to_extended_public_key().derive(&receive).unwrap()
+        );
+        assert!(account
+            .to_extended_public_key()
+            .derive(&receive.hardened())
+            .is_err());
+    }
+}
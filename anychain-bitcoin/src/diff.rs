@@ -0,0 +1,290 @@
+//!
+//! Transaction Diffs
+//!
+//! This module compares two versions of a transaction and reports what
+//! changed between them, so that review tools can show what a counterparty
+//! did to a transaction or a PSBT before it is signed. Inputs are matched by
+//! their outpoint and outputs by their scriptPubKey, so that reordering them
+//! is not reported as a change.
+//!
+
+use crate::{
+    BitcoinAmount, BitcoinNetwork, BitcoinTransaction, BitcoinTransactionOutput,
+    BitcoinTransactionParameters, Outpoint, Psbt, PsbtInput,
+};
+use anychain_core::no_std::*;
+
+/// Represents a change between two versions of a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionChange {
+    /// The version changed from the first to the second value
+    Version(u32, u32),
+    /// The lock time changed from the first to the second value
+    LockTime(u32, u32),
+    /// An input spending the outpoint was added
+    InputAdded(Outpoint),
+    /// The input spending the outpoint was removed
+    InputRemoved(Outpoint),
+    /// The sequence of the input spending the outpoint changed from the first
+    /// to the second value
+    SequenceChanged(Outpoint, u32, u32),
+    /// The scriptSig of the input spending the outpoint changed
+    ScriptSigChanged(Outpoint),
+    /// The witness of the input spending the outpoint changed, or its
+    /// signatures if the transaction is a PSBT
+    WitnessChanged(Outpoint),
+    /// The output was added
+    OutputAdded(BitcoinTransactionOutput),
+    /// The output was removed
+    OutputRemoved(BitcoinTransactionOutput),
+    /// The amount of the output paying to the scriptPubKey changed from the
+    /// first to the second amount
+    AmountChanged(Vec<u8>, BitcoinAmount, BitcoinAmount),
+    /// The inputs or outputs were reordered, reported if nothing else changed
+    Reordered,
+}
+
+/// Represents the changes between two versions of a transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionDiff {
+    /// The changes, those of the header first, then those of the inputs and
+    /// those of the outputs
+    pub changes: Vec<TransactionChange>,
+}
+
+impl TransactionDiff {
+    /// Returns true if the two versions are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns true if the inputs or the outputs were added, removed or
+    /// changed in amount, changes which alter what the transaction spends or
+    /// pays rather than how it is signed
+    pub fn changes_funds(&self) -> bool {
+        self.changes.iter().any(|change| {
+            matches!(
+                change,
+                TransactionChange::InputAdded(_)
+                    | TransactionChange::InputRemoved(_)
+                    | TransactionChange::OutputAdded(_)
+                    | TransactionChange::OutputRemoved(_)
+                    | TransactionChange::AmountChanged(..)
+            )
+        })
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
+    /// Returns the changes from the transaction to 'other'
+    pub fn diff(&self, other: &Self) -> TransactionDiff {
+        let mut changes = vec![];
+        if self.version != other.version {
+            changes.push(TransactionChange::Version(self.version, other.version));
+        }
+        if self.lock_time != other.lock_time {
+            changes.push(TransactionChange::LockTime(self.lock_time, other.lock_time));
+        }
+
+        for input in &self.inputs {
+            let outpoint = &input.outpoint;
+            let Some(new) = other.inputs.iter().find(|new| &new.outpoint == outpoint) else {
+                changes.push(TransactionChange::InputRemoved(outpoint.clone()));
+                continue;
+            };
+            if input.get_sequence() != new.get_sequence() {
+                changes.push(TransactionChange::SequenceChanged(
+                    outpoint.clone(),
+                    input.get_sequence(),
+                    new.get_sequence(),
+                ));
+            }
+            if input.script_sig != new.script_sig {
+                changes.push(TransactionChange::ScriptSigChanged(outpoint.clone()));
+            }
+            if input.witnesses != new.witnesses {
+                changes.push(TransactionChange::WitnessChanged(outpoint.clone()));
+            }
+        }
+        for new in &other.inputs {
+            if !self
+                .inputs
+                .iter()
+                .any(|input| input.outpoint == new.outpoint)
+            {
+                changes.push(TransactionChange::InputAdded(new.outpoint.clone()));
+            }
+        }
+
+        // outputs kept as they were, then outputs paying to the same script
+        // with another amount, are matched before the rest is added or removed
+        let mut removed: Vec<&BitcoinTransactionOutput> = self.outputs.iter().collect();
+        let mut added = vec![];
+        for new in &other.outputs {
+            match removed.iter().position(|output| *output == new) {
+                Some(index) => {
+                    removed.remove(index);
+                }
+                None => added.push(new),
+            }
+        }
+        added.retain(|new| {
+            let index = removed
+                .iter()
+                .position(|output| output.script_pub_key == new.script_pub_key);
+            match index {
+                Some(index) => {
+                    let output = removed.remove(index);
+                    changes.push(TransactionChange::AmountChanged(
+                        new.script_pub_key.clone(),
+                        output.amount,
+                        new.amount,
+                    ));
+                    false
+                }
+                None => true,
+            }
+        });
+        changes.extend(
+            removed
+                .into_iter()
+                .map(|output| TransactionChange::OutputRemoved(output.clone())),
+        );
+        changes.extend(
+            added
+                .into_iter()
+                .map(|output| TransactionChange::OutputAdded(output.clone())),
+        );
+
+        let outpoints = |parameters: &Self| -> Vec<Outpoint> {
+            parameters
+                .inputs
+                .iter()
+                .map(|input| input.outpoint.clone())
+                .collect()
+        };
+        if changes.is_empty()
+            && (outpoints(self) != outpoints(other) || self.outputs != other.outputs)
+        {
+            changes.push(TransactionChange::Reordered);
+        }
+        TransactionDiff { changes }
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Returns the changes from the transaction to 'other'
+    pub fn diff(&self, other: &Self) -> TransactionDiff {
+        self.parameters.diff(&other.parameters)
+    }
+}
+
+impl<N: BitcoinNetwork> Psbt<N> {
+    /// Returns the changes from the PSBT to 'other': the changes of the
+    /// unsigned transaction, and a changed witness for each input whose
+    /// signatures were added, removed or finalized
+    pub fn diff(&self, other: &Self) -> TransactionDiff {
+        let mut diff = self.unsigned_tx.diff(&other.unsigned_tx);
+        diff.changes
+            .retain(|change| change != &TransactionChange::Reordered);
+
+        let inputs = |psbt: &Self| {
+            psbt.unsigned_tx
+                .inputs
+                .iter()
+                .map(|input| input.outpoint.clone())
+                .zip(psbt.inputs.clone())
+                .collect::<Vec<_>>()
+        };
+        let new_inputs = inputs(other);
+        let signed = |input: &PsbtInput| {
+            (
+                input.partial_sigs.clone(),
+                input.final_script_sig.clone(),
+                input.final_script_witness.clone(),
+            )
+        };
+        for (outpoint, input) in inputs(self) {
+            let changed = new_inputs.iter().any(|(new_outpoint, new)| {
+                new_outpoint == &outpoint && signed(new) != signed(&input)
+            });
+            if changed {
+                diff.changes
+                    .push(TransactionChange::WitnessChanged(outpoint));
+            }
+        }
+        if diff.is_empty() && self.unsigned_tx != other.unsigned_tx {
+            diff.changes.push(TransactionChange::Reordered);
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bitcoin;
+    use anychain_core::{hex, Transaction};
+
+    // a signed P2WPKH spend taken from BIP143
+    const RAW: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    fn transaction() -> BitcoinTransaction<Bitcoin> {
+        BitcoinTransaction::<Bitcoin>::from_bytes(&hex::decode(RAW).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        let original = transaction();
+        assert!(original.diff(&original).is_empty());
+
+        let mut reordered = transaction();
+        reordered.parameters.inputs.swap(0, 1);
+        reordered.parameters.outputs.swap(0, 1);
+        assert_eq!(
+            vec![TransactionChange::Reordered],
+            original.diff(&reordered).changes
+        );
+
+        let mut changed = transaction();
+        let outpoint = changed.parameters.inputs[1].outpoint.clone();
+        changed.parameters.lock_time = 0;
+        changed.parameters.inputs[1].sequence = [0xfd, 0xff, 0xff, 0xff];
+        changed.parameters.inputs[1].witnesses.clear();
+        changed.parameters.outputs[0].amount = BitcoinAmount(1_000);
+        let removed = changed.parameters.outputs.remove(1);
+        let diff = original.diff(&changed);
+        assert_eq!(
+            vec![
+                TransactionChange::LockTime(17, 0),
+                TransactionChange::SequenceChanged(outpoint.clone(), u32::MAX, 0xfffffffd),
+                TransactionChange::WitnessChanged(outpoint),
+                TransactionChange::AmountChanged(
+                    original.parameters.outputs[0].script_pub_key.clone(),
+                    original.parameters.outputs[0].amount,
+                    BitcoinAmount(1_000),
+                ),
+                TransactionChange::OutputRemoved(removed),
+            ],
+            diff.changes
+        );
+        assert!(diff.changes_funds());
+    }
+
+    #[test]
+    fn test_psbt_diff() {
+        let psbt = Psbt::new(transaction().parameters);
+        let mut signed = psbt.clone();
+        signed.inputs[1]
+            .partial_sigs
+            .push((vec![0x02; 33], vec![0x30]));
+        let diff = psbt.diff(&signed);
+        assert_eq!(
+            vec![TransactionChange::WitnessChanged(
+                psbt.unsigned_tx.inputs[1].outpoint.clone()
+            )],
+            diff.changes
+        );
+        assert!(!diff.changes_funds());
+    }
+}
@@ -0,0 +1,412 @@
+//!
+//! BIP32 Extended Keys
+//!
+//! This module implements hierarchical deterministic keys (BIP32): the master
+//! key of a seed, child key derivation for hardened and normal children, and
+//! the base58check serialization of extended keys with the version bytes of
+//! the network.
+//!
+
+use crate::{BitcoinNetwork, BitcoinPrivateKey, BitcoinPublicKey};
+use anychain_core::{
+    crypto::{checksum, hash160, hmac_sha512},
+    hex,
+    libsecp256k1::{PublicKey, SecretKey},
+    no_std::*,
+    zeroize::{Zeroize, Zeroizing},
+    ExtendedKeyError, Mnemonic, PrivateKey,
+};
+use core::{fmt, str::FromStr};
+
+pub use anychain_core::{is_hardened, HARDENED_OFFSET};
+
+/// The HMAC key the master key of a seed is derived with
+const MASTER_KEY_SEED: &[u8] = b"Bitcoin seed";
+
+/// The length of a serialized extended key, without its checksum
+const EXTENDED_KEY_LENGTH: usize = 78;
+
+/// Represents a BIP32 extended private key, wiped from memory when dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinExtendedPrivateKey<N: BitcoinNetwork> {
+    /// The depth of the key, 0 for the master key
+    pub depth: u8,
+    /// The first 4 bytes of the identifier of the parent key
+    pub parent_fingerprint: [u8; 4],
+    /// The index of the key under its parent
+    pub child_number: u32,
+    /// The chain code of the key
+    pub chain_code: [u8; 32],
+    /// The private key
+    pub private_key: BitcoinPrivateKey<N>,
+}
+
+/// Represents a BIP32 extended public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinExtendedPublicKey<N: BitcoinNetwork> {
+    /// The depth of the key, 0 for the master key
+    pub depth: u8,
+    /// The first 4 bytes of the identifier of the parent key
+    pub parent_fingerprint: [u8; 4],
+    /// The index of the key under its parent
+    pub child_number: u32,
+    /// The chain code of the key
+    pub chain_code: [u8; 32],
+    /// The public key
+    pub public_key: BitcoinPublicKey<N>,
+}
+
+/// Returns the serialization of an extended key, given its version bytes and
+/// its 33 byte key
+fn serialize(
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: &[u8; 4],
+    child_number: u32,
+    chain_code: &[u8; 32],
+    key: &[u8],
+) -> [u8; EXTENDED_KEY_LENGTH] {
+    let mut bytes = [0u8; EXTENDED_KEY_LENGTH];
+    bytes[..4].copy_from_slice(&version);
+    bytes[4] = depth;
+    bytes[5..9].copy_from_slice(parent_fingerprint);
+    bytes[9..13].copy_from_slice(&child_number.to_be_bytes());
+    bytes[13..45].copy_from_slice(chain_code);
+    bytes[45..].copy_from_slice(key);
+    bytes
+}
+
+/// Returns the base58check encoding of a serialized extended key
+fn encode(bytes: &[u8; EXTENDED_KEY_LENGTH]) -> String {
+    let checksum = checksum(bytes);
+    bs58::encode([&bytes[..], &checksum[..4]].concat()).into_string()
+}
+
+/// Returns the serialized extended key of a base58check encoded extended key,
+/// given the version bytes it must start with
+fn decode(s: &str, version: [u8; 4]) -> Result<[u8; EXTENDED_KEY_LENGTH], ExtendedKeyError> {
+    let data = bs58::decode(s).into_vec()?;
+    if data.len() != EXTENDED_KEY_LENGTH + 4 {
+        return Err(ExtendedKeyError::InvalidByteLength(data.len()));
+    }
+
+    let (bytes, checksum_provided) = data.split_at(EXTENDED_KEY_LENGTH);
+    let checksum_gen = &checksum(bytes)[..4];
+    if checksum_gen != checksum_provided {
+        return Err(ExtendedKeyError::InvalidChecksum(
+            hex::encode(checksum_gen),
+            hex::encode(checksum_provided),
+        ));
+    }
+    if bytes[..4] != version {
+        return Err(ExtendedKeyError::InvalidVersionBytes(hex::encode(
+            &bytes[..4],
+        )));
+    }
+    // a master key has neither a parent nor an index
+    if bytes[4] == 0 && bytes[5..13] != [0u8; 8] {
+        return Err(ExtendedKeyError::Message(
+            "master key with a parent fingerprint or child number".into(),
+        ));
+    }
+    Ok(bytes.try_into().unwrap())
+}
+
+impl<N: BitcoinNetwork> BitcoinExtendedPrivateKey<N> {
+    /// Returns the master key of 'seed', which is 16 to 64 bytes long
+    pub fn new_master(seed: &[u8]) -> Result<Self, ExtendedKeyError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(ExtendedKeyError::InvalidByteLength(seed.len()));
+        }
+        let i = Zeroizing::new(hmac_sha512(MASTER_KEY_SEED, seed));
+        let secret_key = SecretKey::parse_slice(&i[..32])
+            .map_err(|_| ExtendedKeyError::Message("invalid master key".into()))?;
+
+        Ok(Self {
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+            chain_code: i[32..].try_into().unwrap(),
+            private_key: BitcoinPrivateKey::from_secret_key(&secret_key),
+        })
+    }
+
+    /// Returns the master key of the seed of 'mnemonic' and 'passphrase'
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self, ExtendedKeyError> {
+        Self::new_master(mnemonic.to_seed(passphrase).as_slice())
+    }
+
+    /// Returns the child key at 'index', hardened if 'index' is at least
+    /// HARDENED_OFFSET
+    pub fn ckd_priv(&self, index: u32) -> Result<Self, ExtendedKeyError> {
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(ExtendedKeyError::MaximumDepthExceeded)?;
+        let secret_key = self.private_key.to_secp256k1_secret_key();
+
+        let mut data = Zeroizing::new(match is_hardened(index) {
+            true => [&[0u8][..], &secret_key.serialize()].concat(),
+            false => self.to_extended_public_key().public_key.serialize(),
+        });
+        data.extend(index.to_be_bytes());
+
+        let i = Zeroizing::new(hmac_sha512(&self.chain_code, &data));
+        let mut child = SecretKey::parse_slice(&i[..32])
+            .map_err(|_| ExtendedKeyError::InvalidChildKey(index))?;
+        child
+            .tweak_add_assign(&secret_key)
+            .map_err(|_| ExtendedKeyError::InvalidChildKey(index))?;
+
+        Ok(Self {
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code: i[32..].try_into().unwrap(),
+            private_key: BitcoinPrivateKey::from_secret_key(&child),
+        })
+    }
+
+    /// Returns the extended public key of the extended private key
+    pub fn to_extended_public_key(&self) -> BitcoinExtendedPublicKey<N> {
+        BitcoinExtendedPublicKey {
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            public_key: self.private_key.to_public_key(),
+        }
+    }
+
+    /// Returns the hash160 of the public key, identifying the key
+    pub fn identifier(&self) -> [u8; 20] {
+        self.to_extended_public_key().identifier()
+    }
+
+    /// Returns the first 4 bytes of the identifier of the key
+    pub fn fingerprint(&self) -> [u8; 4] {
+        self.to_extended_public_key().fingerprint()
+    }
+
+    /// Returns the 78 byte serialization of the extended private key
+    pub fn serialize(&self) -> [u8; 78] {
+        let key = Zeroizing::new(
+            [
+                &[0u8][..],
+                &self.private_key.to_secp256k1_secret_key().serialize(),
+            ]
+            .concat(),
+        );
+        serialize(
+            N::EXTENDED_PRIVATE_KEY_VERSION,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &key,
+        )
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for BitcoinExtendedPrivateKey<N> {
+    type Err = ExtendedKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = decode(s, N::EXTENDED_PRIVATE_KEY_VERSION)?;
+        if bytes[45] != 0 {
+            return Err(ExtendedKeyError::Message(
+                "private key not prefixed with 0x00".into(),
+            ));
+        }
+
+        Ok(Self {
+            depth: bytes[4],
+            parent_fingerprint: bytes[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(bytes[9..13].try_into().unwrap()),
+            chain_code: bytes[13..45].try_into().unwrap(),
+            private_key: BitcoinPrivateKey::from_secret_key(&SecretKey::parse_slice(&bytes[46..])?),
+        })
+    }
+}
+
+impl<N: BitcoinNetwork> Drop for BitcoinExtendedPrivateKey<N> {
+    fn drop(&mut self) {
+        // the private key wipes itself
+        self.chain_code.zeroize();
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for BitcoinExtendedPrivateKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", encode(&self.serialize()))
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinExtendedPublicKey<N> {
+    /// Returns the normal child key at 'index', failing for a hardened index
+    pub fn ckd_pub(&self, index: u32) -> Result<Self, ExtendedKeyError> {
+        if is_hardened(index) {
+            return Err(ExtendedKeyError::HardenedFromPublicKey(index));
+        }
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(ExtendedKeyError::MaximumDepthExceeded)?;
+
+        let data = [&self.public_key.serialize()[..], &index.to_be_bytes()].concat();
+        let i = hmac_sha512(&self.chain_code, &data);
+        let tweak = SecretKey::parse_slice(&i[..32])
+            .map_err(|_| ExtendedKeyError::InvalidChildKey(index))?;
+        let mut child = self.public_key.to_secp256k1_public_key();
+        child
+            .tweak_add_assign(&tweak)
+            .map_err(|_| ExtendedKeyError::InvalidChildKey(index))?;
+
+        Ok(Self {
+            depth,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code: i[32..].try_into().unwrap(),
+            public_key: BitcoinPublicKey::from_secp256k1_public_key(child, true),
+        })
+    }
+
+    /// Returns the hash160 of the public key, identifying the key
+    pub fn identifier(&self) -> [u8; 20] {
+        hash160(&self.public_key.serialize()).try_into().unwrap()
+    }
+
+    /// Returns the first 4 bytes of the identifier of the key
+    pub fn fingerprint(&self) -> [u8; 4] {
+        self.identifier()[..4].try_into().unwrap()
+    }
+
+    /// Returns the 78 byte serialization of the extended public key
+    pub fn serialize(&self) -> [u8; 78] {
+        serialize(
+            N::EXTENDED_PUBLIC_KEY_VERSION,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.public_key.serialize(),
+        )
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for BitcoinExtendedPublicKey<N> {
+    type Err = ExtendedKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = decode(s, N::EXTENDED_PUBLIC_KEY_VERSION)?;
+        let public_key = PublicKey::parse_compressed(bytes[45..].try_into().unwrap())?;
+
+        Ok(Self {
+            depth: bytes[4],
+            parent_fingerprint: bytes[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(bytes[9..13].try_into().unwrap()),
+            chain_code: bytes[13..45].try_into().unwrap(),
+            public_key: BitcoinPublicKey::from_secp256k1_public_key(public_key, true),
+        })
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for BitcoinExtendedPublicKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", encode(&self.serialize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinTestnet};
+
+    // test vector 1 of BIP32
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    const KEYS: [(&str, &str); 3] = [
+        (
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+        ),
+        (
+            "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+        ),
+        (
+            "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+        ),
+    ];
+
+    #[test]
+    fn test_derivation() {
+        let master =
+            BitcoinExtendedPrivateKey::<Bitcoin>::new_master(&hex::decode(SEED).unwrap()).unwrap();
+        let hardened = master.ckd_priv(HARDENED_OFFSET).unwrap();
+        let normal = hardened.ckd_priv(1).unwrap();
+
+        for (key, (xprv, xpub)) in [&master, &hardened, &normal].into_iter().zip(KEYS) {
+            assert_eq!(xprv, key.to_string());
+            assert_eq!(xpub, key.to_extended_public_key().to_string());
+            assert_eq!(
+                *key,
+                BitcoinExtendedPrivateKey::<Bitcoin>::from_str(xprv).unwrap()
+            );
+            assert_eq!(
+                key.to_extended_public_key(),
+                BitcoinExtendedPublicKey::<Bitcoin>::from_str(xpub).unwrap()
+            );
+        }
+
+        assert_eq!("3442193e", hex::encode(master.fingerprint()));
+        assert_eq!(master.fingerprint(), hardened.parent_fingerprint);
+        assert_eq!(
+            normal.to_extended_public_key(),
+            hardened.to_extended_public_key().ckd_pub(1).unwrap()
+        );
+        assert!(hardened
+            .to_extended_public_key()
+            .ckd_pub(HARDENED_OFFSET)
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_mnemonic() {
+        let mnemonic = Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        assert_eq!(
+            "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF",
+            BitcoinExtendedPrivateKey::<Bitcoin>::from_mnemonic(&mnemonic, "TREZOR")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        let (xprv, xpub) = KEYS[0];
+        // wrong network
+        assert!(BitcoinExtendedPrivateKey::<BitcoinTestnet>::from_str(xprv).is_err());
+        // private key where a public key is expected
+        assert!(BitcoinExtendedPublicKey::<Bitcoin>::from_str(xprv).is_err());
+        // invalid checksum
+        let mut invalid = xpub.to_string();
+        invalid.replace_range(xpub.len() - 1.., "9");
+        assert!(BitcoinExtendedPublicKey::<Bitcoin>::from_str(&invalid).is_err());
+        // seed too short
+        assert!(BitcoinExtendedPrivateKey::<Bitcoin>::new_master(&[0; 15]).is_err());
+
+        let master =
+            BitcoinExtendedPrivateKey::<BitcoinTestnet>::new_master(&hex::decode(SEED).unwrap())
+                .unwrap();
+        assert!(master.to_string().starts_with("tprv"));
+        assert!(master
+            .to_extended_public_key()
+            .to_string()
+            .starts_with("tpub"));
+    }
+}
@@ -0,0 +1,378 @@
+//!
+//! Fee Rates
+//!
+//! This module implements fee rates and transaction weights, so that fees are
+//! computed from typed quantities rather than raw integers. A fee rate is kept
+//! in satoshis per 1,000 weight units, the finest unit fee rates are quoted
+//! in, and converted to and from satoshis per virtual byte. Fees are rounded
+//! up, so that a transaction never pays less than the rate it was built for.
+//! The weights of inputs are predicted from the format of the output they
+//! spend and its script, so that fees can be computed before signing.
+//!
+
+use crate::{
+    multisig_threshold, push_data, script_type, variable_length_integer_size, BitcoinAmount,
+    BitcoinFormat, BitcoinNetwork, BitcoinTransaction, Psbt,
+};
+use anychain_core::{no_std::*, AmountError, TransactionError};
+use core::{fmt, ops::Mul};
+
+/// The number of weight units of a virtual byte
+pub const WITNESS_SCALE_FACTOR: u64 = 4;
+
+/// Represents the weight of a transaction or an input in weight units (BIP141)
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Weight(pub u64);
+
+impl Weight {
+    /// The zero weight.
+    pub const ZERO: Weight = Weight(0);
+
+    /// Returns the weight of 'wu' weight units
+    pub const fn from_wu(wu: u64) -> Self {
+        Self(wu)
+    }
+
+    /// Returns the weight of 'vb' virtual bytes
+    pub const fn from_vb(vb: u64) -> Self {
+        Self(vb.saturating_mul(WITNESS_SCALE_FACTOR))
+    }
+
+    /// Returns the weight in weight units
+    pub const fn to_wu(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the virtual size of the weight, rounded up
+    pub const fn to_vbytes_ceil(self) -> u64 {
+        self.0.div_ceil(WITNESS_SCALE_FACTOR)
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} WU", self.0)
+    }
+}
+
+/// Represents a fee rate in satoshis per 1,000 weight units
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeeRate(u64);
+
+impl FeeRate {
+    /// The zero fee rate.
+    pub const ZERO: FeeRate = FeeRate(0);
+
+    /// Returns the fee rate of 'sat_kwu' satoshis per 1,000 weight units
+    pub const fn from_sat_per_kwu(sat_kwu: u64) -> Self {
+        Self(sat_kwu)
+    }
+
+    /// Returns the fee rate of 'sat_vb' satoshis per virtual byte
+    pub const fn from_sat_per_vb(sat_vb: u64) -> Self {
+        Self(sat_vb.saturating_mul(1000 / WITNESS_SCALE_FACTOR))
+    }
+
+    /// Returns the fee rate of 'sat_kvb' satoshis per 1,000 virtual bytes,
+    /// the unit of the fee rates of Bitcoin Core
+    pub const fn from_sat_per_kvb(sat_kvb: u64) -> Self {
+        Self(sat_kvb / WITNESS_SCALE_FACTOR)
+    }
+
+    /// Returns the fee rate paid by 'fee' for 'weight', rounded down
+    pub fn from_fee(fee: BitcoinAmount, weight: Weight) -> Result<Self, AmountError> {
+        if fee.0 < 0 || weight == Weight::ZERO {
+            return Err(AmountError::InvalidAmount(format!(
+                "fee rate of {} for {}",
+                fee, weight
+            )));
+        }
+        Ok(Self(fee.0 as u64 * 1000 / weight.0))
+    }
+
+    /// Returns the fee rate in satoshis per 1,000 weight units
+    pub const fn to_sat_per_kwu(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the fee rate in satoshis per 1,000 virtual bytes
+    pub const fn to_sat_per_kvb(self) -> u64 {
+        self.0.saturating_mul(WITNESS_SCALE_FACTOR)
+    }
+
+    /// Returns the fee rate in satoshis per virtual byte, rounded down
+    pub const fn to_sat_per_vb_floor(self) -> u64 {
+        self.0 / (1000 / WITNESS_SCALE_FACTOR)
+    }
+
+    /// Returns the fee rate in satoshis per virtual byte, rounded up
+    pub const fn to_sat_per_vb_ceil(self) -> u64 {
+        self.0.div_ceil(1000 / WITNESS_SCALE_FACTOR)
+    }
+
+    /// Returns the fee of 'weight' at the fee rate, rounded up
+    pub fn fee_wu(self, weight: Weight) -> Result<BitcoinAmount, AmountError> {
+        let fee = self
+            .0
+            .checked_mul(weight.0)
+            .map(|fee| fee.div_ceil(1000))
+            .and_then(|fee| i64::try_from(fee).ok())
+            .ok_or_else(|| {
+                AmountError::AmountOutOfBounds(format!("{} * {}", self, weight), "i64".into())
+            })?;
+        BitcoinAmount::from_satoshi(fee)
+    }
+
+    /// Returns the fee of 'vsize' virtual bytes at the fee rate
+    pub fn fee_vb(self, vsize: usize) -> Result<BitcoinAmount, AmountError> {
+        self.fee_wu(Weight::from_vb(vsize as u64))
+    }
+
+    /// Returns true if paying 'fee' for 'weight' meets the fee rate
+    pub fn is_met_by(self, fee: BitcoinAmount, weight: Weight) -> bool {
+        matches!(self.fee_wu(weight), Ok(required) if fee >= required)
+    }
+
+    /// Returns the sum of two fee rates, saturating at the maximum fee rate
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Mul<Weight> for FeeRate {
+    type Output = Result<BitcoinAmount, AmountError>;
+    fn mul(self, rhs: Weight) -> Self::Output {
+        self.fee_wu(rhs)
+    }
+}
+
+impl Mul<FeeRate> for Weight {
+    type Output = Result<BitcoinAmount, AmountError>;
+    fn mul(self, rhs: FeeRate) -> Self::Output {
+        rhs.fee_wu(self)
+    }
+}
+
+impl fmt::Display for FeeRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 % 250 {
+            0 => write!(f, "{} sat/vB", self.0 / 250),
+            _ => write!(f, "{} sat/kWU", self.0),
+        }
+    }
+}
+
+/// The largest size of an ECDSA signature with a low S value, sighash type
+/// included
+pub const MAX_ECDSA_SIGNATURE_SIZE: usize = 72;
+
+/// The size of the outpoint and the sequence of an input
+const OUTPOINT_AND_SEQUENCE_SIZE: usize = 32 + 4 + 4;
+
+/// The weight of an input spending a P2PKH output: a signature and a
+/// compressed public key in its scriptSig
+pub const P2PKH_INPUT_WEIGHT: Weight = Weight::from_vb(148);
+
+/// The weight of an input spending a P2WPKH output
+pub const P2WPKH_INPUT_WEIGHT: Weight = Weight::from_wu(272);
+
+/// The weight of an input spending a P2WPKH output nested in P2SH
+pub const P2SH_P2WPKH_INPUT_WEIGHT: Weight = Weight::from_wu(364);
+
+/// The weight of an input spending a taproot output by its key path with the
+/// default sighash type
+pub const P2TR_KEY_SPEND_INPUT_WEIGHT: Weight = Weight::from_wu(230);
+
+/// Returns the number of signatures satisfying a script: the threshold of a
+/// multisig script, or one for a single key script
+fn signatures_required(script: &[u8]) -> Result<usize, TransactionError> {
+    match (script_type(script), multisig_threshold(script)) {
+        (_, Some((m, _))) => Ok(m),
+        ("pubkey", _) => Ok(1),
+        _ => Err(TransactionError::Message(format!(
+            "cannot predict the satisfaction of a {} script",
+            script_type(script)
+        ))),
+    }
+}
+
+/// Returns the weight of an input spending an output of 'format' at its
+/// largest satisfaction. 'script' is the redeem script of a P2SH output or
+/// the witness script of a P2WSH output, which must then be a single key or
+/// a multisig script whose threshold determines the number of signatures.
+/// Without a script, a bech32 output is a P2WPKH output and a P2SH output
+/// nests a P2WPKH output. A bech32 script which is itself a taproot
+/// scriptPubKey is spent by its key path.
+pub fn input_weight(
+    format: &BitcoinFormat,
+    script: Option<&[u8]>,
+) -> Result<Weight, TransactionError> {
+    let legacy = |script_sig: usize| {
+        Weight::from_vb(
+            (OUTPOINT_AND_SEQUENCE_SIZE
+                + variable_length_integer_size(script_sig as u64)
+                + script_sig) as u64,
+        )
+    };
+    let witness = |script: &[u8]| -> Result<Weight, TransactionError> {
+        let signatures = signatures_required(script)?;
+        let dummy = multisig_threshold(script).is_some() as usize;
+        let items = dummy + signatures + 1;
+        let size = variable_length_integer_size(items as u64)
+            + dummy
+            + signatures * (1 + MAX_ECDSA_SIGNATURE_SIZE)
+            + variable_length_integer_size(script.len() as u64)
+            + script.len();
+        Ok(Weight::from_wu(
+            (4 * (OUTPOINT_AND_SEQUENCE_SIZE + 1) + size) as u64,
+        ))
+    };
+
+    match (format, script) {
+        (BitcoinFormat::P2PKH | BitcoinFormat::CashAddr, _) => Ok(P2PKH_INPUT_WEIGHT),
+        (BitcoinFormat::Bech32, None) => Ok(P2WPKH_INPUT_WEIGHT),
+        (BitcoinFormat::Bech32, Some([0x51, 0x20, key @ ..])) if key.len() == 32 => {
+            Ok(P2TR_KEY_SPEND_INPUT_WEIGHT)
+        }
+        (BitcoinFormat::Bech32 | BitcoinFormat::P2WSH, Some(script)) => witness(script),
+        (BitcoinFormat::P2WSH, None) => Err(TransactionError::Message(
+            "the weight of a P2WSH input depends on its witness script".into(),
+        )),
+        (BitcoinFormat::P2SH_P2WPKH, None) => Ok(P2SH_P2WPKH_INPUT_WEIGHT),
+        (BitcoinFormat::P2SH, None) => Err(TransactionError::Message(
+            "the weight of a P2SH input depends on its redeem script".into(),
+        )),
+        (BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH, Some([0x00, 0x14, hash @ ..]))
+            if hash.len() == 20 =>
+        {
+            Ok(P2SH_P2WPKH_INPUT_WEIGHT)
+        }
+        (BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH, Some(script)) => {
+            let signatures = signatures_required(script)?;
+            let dummy = multisig_threshold(script).is_some() as usize;
+            let script_sig =
+                dummy + signatures * (1 + MAX_ECDSA_SIGNATURE_SIZE) + push_data(script).len();
+            Ok(legacy(script_sig))
+        }
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Returns the weight of the transaction as a typed quantity
+    pub fn weight_units(&self) -> Result<Weight, TransactionError> {
+        Ok(Weight::from_wu(self.weight()? as u64))
+    }
+
+    /// Returns the fee rate the transaction pays with 'fee'
+    pub fn fee_rate(&self, fee: BitcoinAmount) -> Result<FeeRate, TransactionError> {
+        Ok(FeeRate::from_fee(fee, self.weight_units()?)?)
+    }
+}
+
+impl<N: BitcoinNetwork> Psbt<N> {
+    /// Returns the fee rate paid by the transaction, estimated from the weight
+    /// of the unsigned transaction, which excludes the signatures yet to be
+    /// added
+    pub fn fee_rate(&self) -> Result<FeeRate, TransactionError> {
+        let fee = BitcoinAmount::from_satoshi(self.fee()? as i64)?;
+        let weight = Weight::from_vb(self.unsigned_tx_bytes()?.len() as u64);
+        Ok(FeeRate::from_fee(fee, weight)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let rate = FeeRate::from_sat_per_vb(10);
+        assert_eq!(2_500, rate.to_sat_per_kwu());
+        assert_eq!(10_000, rate.to_sat_per_kvb());
+        assert_eq!(rate, FeeRate::from_sat_per_kvb(10_000));
+        assert_eq!("10 sat/vB", rate.to_string());
+
+        let rate = FeeRate::from_sat_per_kwu(253);
+        assert_eq!(1, rate.to_sat_per_vb_floor());
+        assert_eq!(2, rate.to_sat_per_vb_ceil());
+        assert_eq!("253 sat/kWU", rate.to_string());
+        assert!(FeeRate::from_sat_per_vb(1) < rate);
+        assert_eq!(
+            FeeRate::from_sat_per_vb(2),
+            FeeRate::from_sat_per_vb(1).saturating_add(FeeRate::from_sat_per_kvb(1_000))
+        );
+
+        assert_eq!(Weight(564), Weight::from_vb(141));
+        assert_eq!(141, Weight(561).to_vbytes_ceil());
+    }
+
+    #[test]
+    fn test_input_weight() {
+        let script = |hex: &str| anychain_core::hex::decode(hex).unwrap();
+        assert_eq!(
+            P2PKH_INPUT_WEIGHT,
+            input_weight(&BitcoinFormat::P2PKH, None).unwrap()
+        );
+        assert_eq!(
+            68,
+            input_weight(&BitcoinFormat::Bech32, None)
+                .unwrap()
+                .to_vbytes_ceil()
+        );
+        assert_eq!(
+            91,
+            input_weight(&BitcoinFormat::P2SH_P2WPKH, None)
+                .unwrap()
+                .to_vbytes_ceil()
+        );
+        let taproot =
+            script("5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c");
+        assert_eq!(
+            58,
+            input_weight(&BitcoinFormat::Bech32, Some(&taproot))
+                .unwrap()
+                .to_vbytes_ceil()
+        );
+
+        // 2-of-3 multisig
+        let multisig = script(concat!(
+            "52",
+            "21039ed714bf521e96e3f3609b74da898e44d0fb64ba68c62c57852470ffc28e3db5",
+            "2103a385ac59a31841764d55e7c8a243482a89073785524f0c45335afcf425d567b1",
+            "21025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357",
+            "53ae"
+        ));
+        assert_eq!(
+            Weight(418),
+            input_weight(&BitcoinFormat::P2WSH, Some(&multisig)).unwrap()
+        );
+        assert_eq!(
+            Weight::from_vb(297),
+            input_weight(&BitcoinFormat::P2SH_P2WPKH, Some(&multisig)).unwrap()
+        );
+        assert!(input_weight(&BitcoinFormat::P2WSH, None).is_err());
+        assert!(input_weight(&BitcoinFormat::P2WSH, Some(&[0x51])).is_err());
+    }
+
+    #[test]
+    fn test_fee() {
+        // 141 vB at 1 sat/vB
+        let rate = FeeRate::from_sat_per_vb(1);
+        assert_eq!(BitcoinAmount(141), (rate * Weight(561)).unwrap());
+        assert_eq!(BitcoinAmount(141), rate.fee_vb(141).unwrap());
+        // rounded up
+        assert_eq!(
+            BitcoinAmount(1),
+            FeeRate::from_sat_per_kwu(1).fee_wu(Weight(1)).unwrap()
+        );
+        assert!((FeeRate::from_sat_per_kwu(u64::MAX) * Weight(2)).is_err());
+
+        assert_eq!(
+            FeeRate::from_sat_per_vb(5),
+            FeeRate::from_fee(BitcoinAmount(1_000), Weight::from_vb(200)).unwrap()
+        );
+        assert!(FeeRate::from_fee(BitcoinAmount(1_000), Weight::ZERO).is_err());
+        assert!(rate.is_met_by(BitcoinAmount(141), Weight(561)));
+        assert!(!rate.is_met_by(BitcoinAmount(140), Weight(561)));
+    }
+}
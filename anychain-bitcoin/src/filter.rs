@@ -0,0 +1,235 @@
+//!
+//! Filter
+//!
+//! This module implements the basic compact block filter of BIP158, a Golomb-coded
+//! set of the scriptPubKeys touched by a block, so that watch-only wallets can test
+//! whether a block is relevant to them without downloading it.
+//!
+
+use crate::{read_variable_length_integer, write_variable_length_integer};
+use anychain_core::{no_std::*, TransactionError};
+use core::hash::Hasher;
+use sha2::{Digest, Sha256};
+use siphasher::sip::SipHasher24;
+
+/// Writes bits most significant first into a byte vector
+struct BitWriter {
+    bytes: Vec<u8>,
+    offset: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u8) {
+        for i in (0..bits).rev() {
+            if self.offset == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> self.offset;
+            }
+            self.offset = (self.offset + 1) % 8;
+        }
+    }
+}
+
+/// Reads bits most significant first from a byte slice
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u8) -> Result<u64, TransactionError> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self
+                .bytes
+                .get(self.position / 8)
+                .ok_or_else(|| TransactionError::Message("truncated block filter".into()))?;
+            value = (value << 1) | ((byte >> (7 - self.position % 8)) & 1) as u64;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// Represents a BIP158 compact block filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFilter {
+    /// The serialized filter: the number of items followed by the Golomb-Rice coded set
+    pub content: Vec<u8>,
+}
+
+impl BlockFilter {
+    /// The Golomb-Rice parameter of the basic filter
+    pub const P: u8 = 19;
+    /// The false positive rate parameter of the basic filter
+    pub const M: u64 = 784931;
+
+    /// Returns a filter given its serialized content
+    pub fn new(content: Vec<u8>) -> Self {
+        Self { content }
+    }
+
+    /// Returns the basic filter of the block with the given hash (in internal
+    /// byte order). 'scripts' are the scriptPubKeys of the block's outputs and
+    /// of the outputs spent by its inputs; empty and OP_RETURN scripts are skipped.
+    pub fn new_basic<'a>(
+        block_hash: &[u8; 32],
+        scripts: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Self {
+        let mut items: Vec<&[u8]> = scripts
+            .into_iter()
+            .filter(|script| !script.is_empty() && script[0] != 0x6a)
+            .collect();
+        items.sort();
+        items.dedup();
+
+        let mut values = hashed_set(block_hash, items.len() as u64, items);
+        values.sort();
+
+        let mut content = vec![];
+        // writing into a vector cannot fail
+        write_variable_length_integer(&mut content, values.len() as u64).unwrap();
+        let mut writer = BitWriter {
+            bytes: content,
+            offset: 0,
+        };
+        let mut last = 0;
+        for value in values {
+            let delta = value - last;
+            last = value;
+            for _ in 0..delta >> Self::P {
+                writer.write(1, 1);
+            }
+            writer.write(0, 1);
+            writer.write(delta, Self::P);
+        }
+
+        Self::new(writer.bytes)
+    }
+
+    /// Returns true if any of the 'queries' scripts may be in the filter of the
+    /// block with the given hash, with a false positive rate of 1/M
+    pub fn match_any<'a>(
+        &self,
+        block_hash: &[u8; 32],
+        queries: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<bool, TransactionError> {
+        let mut content = &self.content[..];
+        let n = read_variable_length_integer(&mut content)? as u64;
+        if n == 0 {
+            return Ok(false);
+        }
+
+        let mut queries = hashed_set(block_hash, n, queries);
+        queries.sort();
+
+        let mut reader = BitReader {
+            bytes: content,
+            position: 0,
+        };
+        let mut queries = queries.into_iter().peekable();
+        let mut value = 0;
+        for _ in 0..n {
+            let mut quotient = 0;
+            while reader.read(1)? == 1 {
+                quotient += 1;
+            }
+            value += (quotient << Self::P) + reader.read(Self::P)?;
+
+            while let Some(query) = queries.peek() {
+                match (*query).cmp(&value) {
+                    core::cmp::Ordering::Less => {
+                        queries.next();
+                    }
+                    core::cmp::Ordering::Equal => return Ok(true),
+                    core::cmp::Ordering::Greater => break,
+                }
+            }
+            if queries.peek().is_none() {
+                break;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the filter header chaining this filter to the header of the
+    /// previous block's filter, in internal byte order
+    pub fn header(&self, previous_header: &[u8; 32]) -> [u8; 32] {
+        let hash = Sha256::digest(Sha256::digest(&self.content));
+        Sha256::digest(Sha256::digest([&hash[..], previous_header].concat())).into()
+    }
+}
+
+/// Hashes each item with SipHash keyed by the block hash and maps it uniformly
+/// into the range [0, n * M)
+fn hashed_set<'a>(
+    block_hash: &[u8; 32],
+    n: u64,
+    items: impl IntoIterator<Item = &'a [u8]>,
+) -> Vec<u64> {
+    let k0 = u64::from_le_bytes(block_hash[0..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(block_hash[8..16].try_into().unwrap());
+    let range = n * BlockFilter::M;
+    items
+        .into_iter()
+        .map(|item| {
+            let mut hasher = SipHasher24::new_with_keys(k0, k1);
+            hasher.write(item);
+            ((hasher.finish() as u128 * range as u128) >> 64) as u64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::hex;
+
+    fn block_hash(hash: &str) -> [u8; 32] {
+        let mut hash: [u8; 32] = hex::decode(hash).unwrap().try_into().unwrap();
+        hash.reverse();
+        hash
+    }
+
+    #[test]
+    fn test_genesis_filter() {
+        // the basic filter of the genesis block from the BIP158 test vectors
+        let hash = block_hash("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        let script = hex::decode("4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac").unwrap();
+
+        let filter = BlockFilter::new_basic(&hash, [&script[..]]);
+        assert_eq!("017fa880", hex::encode(&filter.content));
+        assert_eq!(
+            "9f3c30f0c37fb977cf3e1a3173c631e8ff119ad3088b6f5b2bced0802139c202",
+            hex::encode(filter.header(&[0; 32]))
+        );
+
+        assert!(filter.match_any(&hash, [&script[..]]).unwrap());
+        assert!(!filter.match_any(&hash, [&[0x51][..]]).unwrap());
+    }
+
+    #[test]
+    fn test_filter_round_trip() {
+        let hash = [7u8; 32];
+        let scripts: Vec<Vec<u8>> = (0..100u32)
+            .map(|i| [vec![0x00, 0x14], [i as u8; 20].to_vec()].concat())
+            .chain([vec![], vec![0x6a, 0x01, 0x00]])
+            .collect();
+        let filter = BlockFilter::new_basic(&hash, scripts.iter().map(|s| &s[..]));
+
+        for script in &scripts[..100] {
+            assert!(filter.match_any(&hash, [&script[..]]).unwrap());
+        }
+        let unknown = [vec![0x00, 0x14], [0xffu8; 20].to_vec()].concat();
+        assert!(!filter.match_any(&hash, [&unknown[..]]).unwrap());
+        assert!(filter
+            .match_any(&hash, [&unknown[..], &scripts[42][..]])
+            .unwrap());
+
+        let empty = BlockFilter::new_basic(&hash, []);
+        assert_eq!(vec![0], empty.content);
+        assert!(!empty.match_any(&hash, [&scripts[0][..]]).unwrap());
+    }
+}
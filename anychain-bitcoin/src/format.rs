@@ -4,16 +4,16 @@ use anychain_core::{AddressError, Format};
 
 use core::fmt;
 use core::str::FromStr;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Represents the format of a Bitcoin address
-#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(non_camel_case_types)]
 pub enum BitcoinFormat {
     /// Pay-to-Pubkey Hash, e.g. 1NoZQSmjYHUZMbqLerwmT4xfe8A6mAo8TT
     P2PKH,
-    /// Pay-to-Script Hash, e.g. 34AgLJhwXrvmkZS1o5TrcdeevMt22Nar53
-    //P2SH,
+    /// Pay-to-Script Hash of an unknown redeem script, e.g. 34AgLJhwXrvmkZS1o5TrcdeevMt22Nar53
+    P2SH,
     /// Pay-to-Witness-Script Hash, e.g. bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3
     P2WSH,
     /// SegWit Pay-to-Witness-Public-Key Hash, e.g. 34AgLJhwXrvmkZS1o5TrcdeevMt22Nar53
@@ -54,6 +54,7 @@ impl fmt::Display for BitcoinFormat {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             BitcoinFormat::P2PKH => write!(f, "p2pkh"),
+            BitcoinFormat::P2SH => write!(f, "p2sh"),
             BitcoinFormat::P2WSH => write!(f, "p2wsh"),
             BitcoinFormat::P2SH_P2WPKH => write!(f, "p2sh_p2wpkh"),
             BitcoinFormat::Bech32 => write!(f, "bech32"),
@@ -68,6 +69,7 @@ impl FromStr for BitcoinFormat {
     fn from_str(format: &str) -> Result<Self, AddressError> {
         match format {
             "p2pkh" => Ok(BitcoinFormat::P2PKH),
+            "p2sh" => Ok(BitcoinFormat::P2SH),
             "p2sh_p2wpkh" => Ok(BitcoinFormat::P2SH_P2WPKH),
             "p2wsh" => Ok(BitcoinFormat::P2WSH),
             "bech32" => Ok(BitcoinFormat::Bech32),
@@ -0,0 +1,370 @@
+//!
+//! Interactive Transaction Construction
+//!
+//! This module implements the interactive transaction construction protocol
+//! of dual-funded Lightning channels (BOLT2), usable for any collaborative
+//! transaction such as a splice or a coinjoin. Two parties take turns adding
+//! and removing inputs and outputs, each tagged with a serial id whose parity
+//! identifies its owner: even for the initiator, odd for the other party.
+//! Negotiation ends once both parties send `tx_complete` in a row, and the
+//! shared transaction orders its inputs and outputs by serial id, so that both
+//! parties arrive at the same transaction.
+//!
+
+use crate::{
+    BitcoinNetwork, BitcoinTransaction, BitcoinTransactionInput, BitcoinTransactionOutput,
+    BitcoinTransactionParameters,
+};
+use anychain_core::{no_std::*, Transaction, TransactionError};
+
+/// The maximum number of inputs or outputs a party may add
+pub const MAX_INPUTS_OUTPUTS_RECEIVED: usize = 4096;
+
+/// The role of a party in the negotiation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InteractiveTxRole {
+    /// The party that starts the negotiation and uses even serial ids
+    Initiator,
+    /// The party that accepts the negotiation and uses odd serial ids
+    NonInitiator,
+}
+
+impl InteractiveTxRole {
+    /// Returns the parity of the serial ids of the role
+    fn parity(self) -> u64 {
+        match self {
+            InteractiveTxRole::Initiator => 0,
+            InteractiveTxRole::NonInitiator => 1,
+        }
+    }
+
+    /// Returns the role of the other party
+    fn counterparty(self) -> Self {
+        match self {
+            InteractiveTxRole::Initiator => InteractiveTxRole::NonInitiator,
+            InteractiveTxRole::NonInitiator => InteractiveTxRole::Initiator,
+        }
+    }
+}
+
+/// Represents a message of the negotiation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InteractiveTxMessage<N: BitcoinNetwork> {
+    /// `tx_add_input`, adding an input with a serial id
+    AddInput(u64, Box<BitcoinTransactionInput<N>>),
+    /// `tx_add_output`, adding an output with a serial id
+    AddOutput(u64, BitcoinTransactionOutput),
+    /// `tx_remove_input`, removing an input the sender added
+    RemoveInput(u64),
+    /// `tx_remove_output`, removing an output the sender added
+    RemoveOutput(u64),
+    /// `tx_complete`, the sender has nothing more to add
+    Complete,
+}
+
+/// Represents one party of an interactive transaction construction, keeping
+/// the inputs and outputs added by both parties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteractiveTxBuilder<N: BitcoinNetwork> {
+    /// The role of the party
+    role: InteractiveTxRole,
+    /// The version of the shared transaction
+    version: u32,
+    /// The lock time of the shared transaction
+    lock_time: u32,
+    /// The inputs with their serial ids
+    inputs: Vec<(u64, BitcoinTransactionInput<N>)>,
+    /// The outputs with their serial ids
+    outputs: Vec<(u64, BitcoinTransactionOutput)>,
+    /// The serial id of the next input or output of the party
+    next_serial_id: u64,
+    /// The number of inputs and outputs added by the counterparty
+    received: usize,
+    /// True if it is the turn of the party to send a message
+    our_turn: bool,
+    /// True if the last message sent, or received, was `tx_complete`
+    last_complete: (bool, bool),
+}
+
+impl<N: BitcoinNetwork> InteractiveTxBuilder<N> {
+    /// Returns the party of 'role' in the negotiation of a transaction with
+    /// 'version' and 'lock_time', the initiator sending the first message
+    pub fn new(role: InteractiveTxRole, version: u32, lock_time: u32) -> Self {
+        Self {
+            role,
+            version,
+            lock_time,
+            inputs: vec![],
+            outputs: vec![],
+            next_serial_id: role.parity(),
+            received: 0,
+            our_turn: role == InteractiveTxRole::Initiator,
+            last_complete: (false, false),
+        }
+    }
+
+    /// Returns true once both parties sent `tx_complete` in a row
+    pub fn is_complete(&self) -> bool {
+        self.last_complete == (true, true)
+    }
+
+    /// Returns true if it is the turn of the party to send a message
+    pub fn is_our_turn(&self) -> bool {
+        self.our_turn
+    }
+
+    /// Adds 'input' of the party and returns the message to send
+    pub fn add_input(
+        &mut self,
+        input: BitcoinTransactionInput<N>,
+    ) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        let serial_id = self.next_serial_id;
+        self.send(InteractiveTxMessage::AddInput(serial_id, Box::new(input)))
+    }
+
+    /// Adds 'output' of the party and returns the message to send
+    pub fn add_output(
+        &mut self,
+        output: BitcoinTransactionOutput,
+    ) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        let serial_id = self.next_serial_id;
+        self.send(InteractiveTxMessage::AddOutput(serial_id, output))
+    }
+
+    /// Removes the input of the party with 'serial_id' and returns the
+    /// message to send
+    pub fn remove_input(
+        &mut self,
+        serial_id: u64,
+    ) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        self.send(InteractiveTxMessage::RemoveInput(serial_id))
+    }
+
+    /// Removes the output of the party with 'serial_id' and returns the
+    /// message to send
+    pub fn remove_output(
+        &mut self,
+        serial_id: u64,
+    ) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        self.send(InteractiveTxMessage::RemoveOutput(serial_id))
+    }
+
+    /// Signals that the party has nothing more to add and returns the
+    /// message to send
+    pub fn complete(&mut self) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        self.send(InteractiveTxMessage::Complete)
+    }
+
+    /// Applies a message of the party and passes the turn
+    fn send(
+        &mut self,
+        message: InteractiveTxMessage<N>,
+    ) -> Result<InteractiveTxMessage<N>, TransactionError> {
+        if !self.our_turn {
+            return Err(TransactionError::Message(
+                "interactive tx: not our turn".into(),
+            ));
+        }
+        self.apply(self.role, message.clone())?;
+        if matches!(
+            message,
+            InteractiveTxMessage::AddInput(..) | InteractiveTxMessage::AddOutput(..)
+        ) {
+            self.next_serial_id += 2;
+        }
+        self.our_turn = false;
+        Ok(message)
+    }
+
+    /// Applies a message received from the counterparty and takes the turn
+    pub fn receive(&mut self, message: InteractiveTxMessage<N>) -> Result<(), TransactionError> {
+        if self.our_turn {
+            return Err(TransactionError::Message(
+                "interactive tx: message received out of turn".into(),
+            ));
+        }
+        if matches!(
+            message,
+            InteractiveTxMessage::AddInput(..) | InteractiveTxMessage::AddOutput(..)
+        ) {
+            self.received += 1;
+            if self.received > MAX_INPUTS_OUTPUTS_RECEIVED {
+                return Err(TransactionError::LimitExceeded(
+                    "interactive tx inputs and outputs".into(),
+                    MAX_INPUTS_OUTPUTS_RECEIVED,
+                    self.received,
+                ));
+            }
+        }
+        self.apply(self.role.counterparty(), message)?;
+        self.our_turn = true;
+        Ok(())
+    }
+
+    /// Applies a message sent by 'sender' to the shared transaction
+    fn apply(
+        &mut self,
+        sender: InteractiveTxRole,
+        message: InteractiveTxMessage<N>,
+    ) -> Result<(), TransactionError> {
+        let violation = |message: &str| {
+            Err(TransactionError::Message(format!(
+                "interactive tx: {}",
+                message
+            )))
+        };
+        let owned = |serial_id: u64| serial_id % 2 == sender.parity();
+        let is_complete = matches!(message, InteractiveTxMessage::Complete);
+
+        match message {
+            InteractiveTxMessage::AddInput(serial_id, _)
+            | InteractiveTxMessage::AddOutput(serial_id, _)
+            | InteractiveTxMessage::RemoveInput(serial_id)
+            | InteractiveTxMessage::RemoveOutput(serial_id)
+                if !owned(serial_id) =>
+            {
+                return violation("serial id of the wrong parity");
+            }
+            InteractiveTxMessage::AddInput(serial_id, input) => {
+                if self.inputs.iter().any(|(id, _)| *id == serial_id) {
+                    return violation("duplicate input serial id");
+                }
+                if self
+                    .inputs
+                    .iter()
+                    .any(|(_, i)| i.outpoint == input.outpoint)
+                {
+                    return violation("input spends an outpoint already spent");
+                }
+                self.inputs.push((serial_id, *input));
+            }
+            InteractiveTxMessage::AddOutput(serial_id, output) => {
+                if self.outputs.iter().any(|(id, _)| *id == serial_id) {
+                    return violation("duplicate output serial id");
+                }
+                self.outputs.push((serial_id, output));
+            }
+            InteractiveTxMessage::RemoveInput(serial_id) => {
+                let index = self.inputs.iter().position(|(id, _)| *id == serial_id);
+                match index {
+                    Some(index) => {
+                        self.inputs.remove(index);
+                    }
+                    None => return violation("unknown input serial id"),
+                }
+            }
+            InteractiveTxMessage::RemoveOutput(serial_id) => {
+                let index = self.outputs.iter().position(|(id, _)| *id == serial_id);
+                match index {
+                    Some(index) => {
+                        self.outputs.remove(index);
+                    }
+                    None => return violation("unknown output serial id"),
+                }
+            }
+            InteractiveTxMessage::Complete => {}
+        }
+
+        // any other message restarts the count of consecutive tx_complete
+        let (ours, theirs) = &mut self.last_complete;
+        match (sender == self.role, is_complete) {
+            (true, true) => *ours = true,
+            (false, true) => *theirs = true,
+            _ => self.last_complete = (false, false),
+        }
+        Ok(())
+    }
+
+    /// Returns the shared transaction once the negotiation is complete, its
+    /// inputs and outputs ordered by serial id
+    pub fn build(&self) -> Result<BitcoinTransaction<N>, TransactionError> {
+        if !self.is_complete() {
+            return Err(TransactionError::Message(
+                "interactive tx: negotiation is not complete".into(),
+            ));
+        }
+        let mut inputs = self.inputs.clone();
+        let mut outputs = self.outputs.clone();
+        inputs.sort_by_key(|(serial_id, _)| *serial_id);
+        outputs.sort_by_key(|(serial_id, _)| *serial_id);
+
+        let parameters = BitcoinTransactionParameters::new(
+            inputs.into_iter().map(|(_, input)| input).collect(),
+            outputs.into_iter().map(|(_, output)| output).collect(),
+        )?
+        .with_version(self.version)?
+        .with_lock_time(self.lock_time)?;
+        BitcoinTransaction::new(&parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinAmount, SignatureHash, Txid};
+    use core::str::FromStr;
+
+    const TXID: &str = "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9";
+
+    fn input(index: u32) -> BitcoinTransactionInput<Bitcoin> {
+        BitcoinTransactionInput::new(
+            Txid::from_str(TXID).unwrap().to_bytes_rpc().to_vec(),
+            index,
+            None,
+            None,
+            None,
+            None,
+            SignatureHash::SIGHASH_ALL,
+        )
+        .unwrap()
+    }
+
+    fn output(amount: i64) -> BitcoinTransactionOutput {
+        BitcoinTransactionOutput {
+            amount: BitcoinAmount(amount),
+            script_pub_key: vec![0x00, 0x14, amount as u8],
+        }
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let mut alice = InteractiveTxBuilder::<Bitcoin>::new(InteractiveTxRole::Initiator, 2, 0);
+        let mut bob = InteractiveTxBuilder::<Bitcoin>::new(InteractiveTxRole::NonInitiator, 2, 0);
+        assert!(bob.add_input(input(1)).is_err());
+
+        bob.receive(alice.add_input(input(0)).unwrap()).unwrap();
+        alice.receive(bob.add_input(input(1)).unwrap()).unwrap();
+        bob.receive(alice.add_output(output(1)).unwrap()).unwrap();
+        alice.receive(bob.add_output(output(2)).unwrap()).unwrap();
+        bob.receive(alice.add_output(output(3)).unwrap()).unwrap();
+        alice.receive(bob.remove_output(3).unwrap()).unwrap();
+        bob.receive(alice.complete().unwrap()).unwrap();
+        assert!(bob.build().is_err());
+        alice.receive(bob.complete().unwrap()).unwrap();
+        assert!(alice.is_complete() && bob.is_complete());
+
+        // both parties build the same transaction, ordered by serial id
+        let transaction = alice.build().unwrap();
+        assert_eq!(transaction, bob.build().unwrap());
+        let outputs = &transaction.parameters.outputs;
+        assert_eq!(vec![output(1), output(3)], *outputs);
+        assert_eq!(1, transaction.parameters.inputs[1].outpoint.index);
+    }
+
+    #[test]
+    fn test_violations() {
+        let mut bob = InteractiveTxBuilder::<Bitcoin>::new(InteractiveTxRole::NonInitiator, 2, 0);
+        // odd serial ids belong to the non-initiator
+        assert!(bob
+            .receive(InteractiveTxMessage::AddInput(1, Box::new(input(0))))
+            .is_err());
+        bob.receive(InteractiveTxMessage::AddInput(0, Box::new(input(0))))
+            .unwrap();
+        bob.complete().unwrap();
+        // the same outpoint twice
+        assert!(bob
+            .receive(InteractiveTxMessage::AddInput(2, Box::new(input(0))))
+            .is_err());
+        assert!(bob.receive(InteractiveTxMessage::RemoveOutput(4)).is_err());
+    }
+}
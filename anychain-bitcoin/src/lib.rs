@@ -8,20 +8,113 @@ extern crate thiserror;
 pub mod address;
 pub use self::address::*;
 
+pub mod bip21;
+pub use self::bip21::*;
+
+pub mod bip47;
+pub use self::bip47::*;
+
+pub mod bip322;
+pub use self::bip322::*;
+
+pub mod block;
+pub use self::block::*;
+
+pub mod builder;
+pub use self::builder::*;
+
+pub mod cashtokens;
+pub use self::cashtokens::*;
+
+pub mod derivation_path;
+pub use self::derivation_path::*;
+
+pub mod diff;
+pub use self::diff::*;
+
+pub mod extended_key;
+pub use self::extended_key::*;
+
+pub mod fee_rate;
+pub use self::fee_rate::*;
+
+pub mod filter;
+pub use self::filter::*;
+
 pub mod format;
 pub use self::format::*;
 
+pub mod interactive_tx;
+pub use self::interactive_tx::*;
+
+pub mod lightning;
+pub use self::lightning::*;
+
+pub mod message;
+pub use self::message::*;
+
+pub mod miniscript;
+pub use self::miniscript::*;
+
 pub mod network;
 pub use self::network::*;
 
+pub mod omni;
+pub use self::omni::*;
+
+pub mod payjoin;
+pub use self::payjoin::*;
+
+pub mod policy;
+pub use self::policy::*;
+
+pub mod private_key;
+pub use self::private_key::*;
+
+pub mod psbt;
+pub use self::psbt::*;
+
 pub mod public_key;
 pub use self::public_key::*;
 
+pub mod runes;
+pub use self::runes::*;
+
+pub mod txid;
+pub use self::txid::*;
+
+pub mod utxo;
+pub use self::utxo::*;
+
+pub mod watch_only;
+pub use self::watch_only::*;
+
 pub mod witness_program;
 pub use self::witness_program::*;
 
+pub mod script;
+pub use self::script::*;
+
+pub mod sigops;
+pub use self::sigops::*;
+
+pub mod silent_payment;
+pub use self::silent_payment::*;
+
+pub mod taproot;
+pub use self::taproot::*;
+
 pub mod transaction;
 pub use self::transaction::*;
 
+pub mod transaction_ref;
+pub use self::transaction_ref::*;
+
 pub mod amount;
 pub use self::amount::*;
+
+mod serde_hex;
+
+// re-exported for the networks declared with `bitcoin_network!`
+#[doc(hidden)]
+pub use anychain_core;
@@ -0,0 +1,193 @@
+//!
+//! Lightning Output Templates
+//!
+//! This module builds the outputs of Lightning channel transactions, so that
+//! channel opening transactions can be built with the transaction types of
+//! this crate: the 2-of-2 multisig funding output (BOLT3) paid to P2WSH, its
+//! taproot variant paying to the MuSig2 aggregate of the two funding keys
+//! (BIP327) as used by simple taproot channels, and the anchor outputs of
+//! anchor channels.
+//!
+
+use crate::{tagged_hash, tweak_public_key, BitcoinAmount, BitcoinTransactionOutput, Opcode};
+use anychain_core::{
+    crypto::sha256,
+    libsecp256k1::{PublicKey, SecretKey},
+    no_std::*,
+    TransactionError,
+};
+
+/// The amount of an anchor output of an anchor channel (BOLT3)
+pub const ANCHOR_OUTPUT_VALUE: BitcoinAmount = BitcoinAmount(330);
+
+/// Returns the two funding keys in compressed form, sorted lexicographically
+fn sorted_keys(key_a: &PublicKey, key_b: &PublicKey) -> [[u8; 33]; 2] {
+    let mut keys = [key_a.serialize_compressed(), key_b.serialize_compressed()];
+    keys.sort();
+    keys
+}
+
+/// Returns the P2WSH scriptPubKey of a witness script
+fn p2wsh(witness_script: &[u8]) -> Vec<u8> {
+    [&[0x00, 0x20][..], &sha256(witness_script)].concat()
+}
+
+/// Returns the MuSig2 aggregate x-only key of 'keys' in the given order
+/// (BIP327 KeyAgg), or `None` if the aggregate is the point at infinity
+fn key_agg(keys: &[[u8; 33]]) -> Option<[u8; 32]> {
+    let list = tagged_hash("KeyAgg list", &keys.concat());
+    let second = keys.iter().find(|key| *key != &keys[0]);
+    let points = keys
+        .iter()
+        .map(|key| {
+            let mut point = PublicKey::parse_compressed(key).ok()?;
+            if Some(key) != second {
+                let coefficient =
+                    tagged_hash("KeyAgg coefficient", &[&list[..], &key[..]].concat());
+                point
+                    .tweak_mul_assign(&SecretKey::parse(&coefficient).ok()?)
+                    .ok()?;
+            }
+            Some(point)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let aggregate = PublicKey::combine(&points).ok()?;
+    Some(aggregate.serialize_compressed()[1..].try_into().unwrap())
+}
+
+/// Returns the witness script of a channel funding output,
+/// `2 <key1> <key2> 2 OP_CHECKMULTISIG` with the keys sorted
+/// lexicographically (BOLT3)
+pub fn funding_witness_script(key_a: &PublicKey, key_b: &PublicKey) -> Vec<u8> {
+    let [key1, key2] = sorted_keys(key_a, key_b);
+    [&[0x52, 0x21][..], &key1, &[0x21], &key2, &[0x52, 0xae]].concat()
+}
+
+/// Returns the P2WSH scriptPubKey of a channel funding output
+pub fn funding_script_pub_key(key_a: &PublicKey, key_b: &PublicKey) -> Vec<u8> {
+    p2wsh(&funding_witness_script(key_a, key_b))
+}
+
+/// Returns the P2TR scriptPubKey of the funding output of a simple taproot
+/// channel: the MuSig2 aggregate of the sorted funding keys, tweaked without
+/// a script tree (BIP86)
+pub fn taproot_funding_script_pub_key(
+    key_a: &PublicKey,
+    key_b: &PublicKey,
+) -> Result<Vec<u8>, TransactionError> {
+    let output_key = key_agg(&sorted_keys(key_a, key_b))
+        .and_then(|internal_key| tweak_public_key(&internal_key, None))
+        .ok_or_else(|| TransactionError::Message("invalid funding keys".into()))?;
+    Ok([&[0x51, 0x20][..], &output_key].concat())
+}
+
+/// Returns the witness script of an anchor output of 'funding_key', spendable
+/// by its owner at once or by anyone 16 blocks after confirmation:
+/// `<key> OP_CHECKSIG OP_IFDUP OP_NOTIF OP_16 OP_CHECKSEQUENCEVERIFY OP_ENDIF`
+pub fn anchor_witness_script(funding_key: &PublicKey) -> Vec<u8> {
+    [
+        &[0x21][..],
+        &funding_key.serialize_compressed(),
+        &[Opcode::OP_CHECKSIG as u8, 0x73, 0x64, 0x60, 0xb2, 0x68],
+    ]
+    .concat()
+}
+
+impl BitcoinTransactionOutput {
+    /// Returns the P2WSH funding output of a channel of 'amount' between the
+    /// two funding keys
+    pub fn channel_funding(amount: BitcoinAmount, key_a: &PublicKey, key_b: &PublicKey) -> Self {
+        Self {
+            amount,
+            script_pub_key: funding_script_pub_key(key_a, key_b),
+        }
+    }
+
+    /// Returns the P2TR funding output of a simple taproot channel of
+    /// 'amount' between the two funding keys
+    pub fn channel_funding_taproot(
+        amount: BitcoinAmount,
+        key_a: &PublicKey,
+        key_b: &PublicKey,
+    ) -> Result<Self, TransactionError> {
+        Ok(Self {
+            amount,
+            script_pub_key: taproot_funding_script_pub_key(key_a, key_b)?,
+        })
+    }
+
+    /// Returns the 330 satoshi anchor output of 'funding_key'
+    pub fn channel_anchor(funding_key: &PublicKey) -> Self {
+        Self {
+            amount: ANCHOR_OUTPUT_VALUE,
+            script_pub_key: p2wsh(&anchor_witness_script(funding_key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::hex;
+
+    fn key(hex: &str) -> PublicKey {
+        PublicKey::parse_slice(&hex::decode(hex).unwrap(), None).unwrap()
+    }
+
+    // the funding keys of the BOLT3 test vectors
+    const LOCAL: &str = "023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb";
+    const REMOTE: &str = "030e9f7b623d2ccc7c9bd44d66d5ce21ce504c0acf6385a132cec6d3c39fa711c1";
+
+    #[test]
+    fn test_funding() {
+        // the funding witness script of BOLT3, whatever the order of the keys
+        let script = "5221023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb21030e9f7b623d2ccc7c9bd44d66d5ce21ce504c0acf6385a132cec6d3c39fa711c152ae";
+        let (local, remote) = (key(LOCAL), key(REMOTE));
+        assert_eq!(script, hex::encode(funding_witness_script(&local, &remote)));
+        assert_eq!(script, hex::encode(funding_witness_script(&remote, &local)));
+
+        let output =
+            BitcoinTransactionOutput::channel_funding(BitcoinAmount(10_000_000), &local, &remote);
+        assert_eq!(
+            "0020c015c4a6be010e21657068fc2e6a9d02b27ebe4d490a25846f7237f104d1a3cd",
+            hex::encode(output.script_pub_key)
+        );
+
+        let taproot = taproot_funding_script_pub_key(&local, &remote).unwrap();
+        assert_eq!(
+            taproot,
+            taproot_funding_script_pub_key(&remote, &local).unwrap()
+        );
+        assert_eq!([0x51, 0x20], taproot[..2]);
+    }
+
+    #[test]
+    fn test_key_agg() {
+        // the key aggregation vectors of BIP327
+        let keys = [
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "03dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "023590a94e768f8e1815c2f24b4d80a8e3149316c3518ce7b7ad338368d038ca66",
+        ]
+        .map(|hex| key(hex).serialize_compressed());
+        assert_eq!(
+            "90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c",
+            hex::encode(key_agg(&keys).unwrap())
+        );
+        assert_eq!(
+            "6204de8b083426dc6eaf9502d27024d53fc826bf7d2012148a0575435df54b2b",
+            hex::encode(key_agg(&[keys[2], keys[1], keys[0]]).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_anchor() {
+        let output = BitcoinTransactionOutput::channel_anchor(&key(LOCAL));
+        assert_eq!(ANCHOR_OUTPUT_VALUE, output.amount);
+        assert_eq!(
+            "21023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54ebac736460b268",
+            hex::encode(anchor_witness_script(&key(LOCAL)))
+        );
+        assert_eq!(34, output.script_pub_key.len());
+    }
+}
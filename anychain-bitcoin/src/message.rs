@@ -0,0 +1,275 @@
+//!
+//! Message
+//!
+//! This module implements the legacy signed message format of Bitcoin Core's
+//! `signmessage`/`verifymessage`: the message is framed with the network's magic
+//! prefix and signed with a 65 byte recoverable signature encoded in base64.
+//! Bitcoin addresses implement the core `MessageSigner` and `MessageVerifier`
+//! on the raw 65 byte signature.
+//!
+
+use crate::{
+    variable_length_integer, BitcoinAddress, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey,
+};
+use anychain_core::{
+    crypto::{checksum as double_sha2, sign_deterministic},
+    libsecp256k1::{self, Message, RecoveryId, SecretKey, Signature},
+    no_std::*,
+    Address, AddressError, MessageSigner, MessageVerifier,
+};
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Returns the hash signed for 'message': the double SHA-256 of the network's
+/// message prefix and the message, each preceded by its length
+pub fn signed_message_hash<N: BitcoinNetwork>(message: &[u8]) -> [u8; 32] {
+    let prefix = N::MESSAGE_PREFIX.as_bytes();
+    let mut data = variable_length_integer(prefix.len() as u64).unwrap();
+    data.extend(prefix);
+    data.extend(variable_length_integer(message.len() as u64).unwrap());
+    data.extend(message);
+    double_sha2(&data).try_into().unwrap()
+}
+
+/// Returns the base64 message signature given the 64 byte compact signature
+/// 'rs' and its recovery id, for a P2PKH address of a compressed or
+/// uncompressed public key
+pub fn encode_message_signature(
+    rs: &[u8],
+    recid: u8,
+    compressed: bool,
+) -> Result<String, AddressError> {
+    if rs.len() != 64 || recid > 3 {
+        return Err(AddressError::Message("invalid message signature".into()));
+    }
+    let header = 27 + recid + if compressed { 4 } else { 0 };
+    Ok(base64_encode(&[&[header], rs].concat()))
+}
+
+/// Signs 'message' with 'secret_key' and returns the base64 message signature
+pub fn sign_message<N: BitcoinNetwork>(
+    message: &[u8],
+    secret_key: &SecretKey,
+    compressed: bool,
+) -> String {
+    let hash = Message::parse(&signed_message_hash::<N>(message));
+    let (signature, recid) = sign_deterministic(&hash, secret_key);
+    encode_message_signature(&signature.serialize(), recid.serialize(), compressed).unwrap()
+}
+
+/// Returns true if 'signature' is a valid base64 signature of 'message' by the
+/// key of 'address'
+pub fn verify_message<N: BitcoinNetwork>(
+    address: &BitcoinAddress<N>,
+    message: &[u8],
+    signature: &str,
+) -> Result<bool, AddressError> {
+    let signature = base64_decode(signature)
+        .ok_or_else(|| AddressError::Message("invalid message signature".into()))?;
+    MessageVerifier::verify_message(address, message, &signature)
+}
+
+impl<N: BitcoinNetwork> MessageSigner for BitcoinAddress<N> {
+    /// Returns the 65 byte signature of 'message' by the compressed key of
+    /// 'secret_key'. Segwit P2WPKH and P2SH-P2WPKH signatures use the header
+    /// bytes introduced by Electrum, and script addresses have no signature in
+    /// this format.
+    fn sign_message(
+        secret_key: &Self::SecretKey,
+        format: &Self::Format,
+        message: &[u8],
+    ) -> Result<Vec<u8>, AddressError> {
+        let header = match format {
+            BitcoinFormat::P2PKH | BitcoinFormat::CashAddr => 31,
+            BitcoinFormat::P2SH_P2WPKH => 35,
+            BitcoinFormat::Bech32 => 39,
+            BitcoinFormat::P2SH | BitcoinFormat::P2WSH => {
+                return Err(AddressError::Message(format!(
+                    "no message signature for {} addresses",
+                    format
+                )))
+            }
+        };
+        let hash = Message::parse(&signed_message_hash::<N>(message));
+        let (signature, recid) = sign_deterministic(&hash, secret_key);
+        Ok([&[header + recid.serialize()], &signature.serialize()[..]].concat())
+    }
+}
+
+impl<N: BitcoinNetwork> MessageVerifier for BitcoinAddress<N> {
+    /// Returns true if 'signature', the 65 byte header and compact signature,
+    /// is a valid signature of 'message' by the key of this address. P2PKH
+    /// addresses are checked against the key recovered from the signature, and
+    /// so are the segwit P2WPKH and P2SH-P2WPKH addresses whose signatures use
+    /// the header bytes introduced by Electrum.
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<bool, AddressError> {
+        let invalid = || AddressError::Message("invalid message signature".into());
+
+        if signature.len() != 65 {
+            return Err(invalid());
+        }
+
+        let (format, compressed) = match signature[0] {
+            27..=30 => (BitcoinFormat::P2PKH, false),
+            31..=34 => (BitcoinFormat::P2PKH, true),
+            35..=38 => (BitcoinFormat::P2SH_P2WPKH, true),
+            39..=42 => (BitcoinFormat::Bech32, true),
+            _ => return Err(invalid()),
+        };
+        let recid = RecoveryId::parse((signature[0] - 27) % 4).map_err(|_| invalid())?;
+        let rs = Signature::parse_standard_slice(&signature[1..]).map_err(|_| invalid())?;
+
+        let hash = Message::parse(&signed_message_hash::<N>(message));
+        let public_key = match libsecp256k1::recover(&hash, &rs, &recid) {
+            Ok(public_key) => {
+                BitcoinPublicKey::<N>::from_secp256k1_public_key(public_key, compressed)
+            }
+            Err(_) => return Ok(false),
+        };
+
+        // a compressed P2PKH signature may also prove the ownership of a segwit address
+        let format = match (format, self.format()) {
+            (BitcoinFormat::P2PKH, BitcoinFormat::P2SH_P2WPKH | BitcoinFormat::Bech32)
+                if compressed =>
+            {
+                self.format()
+            }
+            (format, _) => format,
+        };
+        Ok(BitcoinAddress::<N>::from_public_key(&public_key, &format)? == *self)
+    }
+}
+
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            let c = match i <= chunk.len() {
+                true => BASE64_TABLE[(value >> (18 - 6 * i) & 0x3f) as usize] as char,
+                false => '=',
+            };
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+pub(crate) fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.as_bytes();
+    if !data.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut decoded = vec![];
+    for (index, chunk) in data.chunks(4).enumerate() {
+        let last = index == data.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut value = 0u32;
+        for c in &chunk[..4 - padding] {
+            let sextet = BASE64_TABLE.iter().position(|b| b == c)?;
+            value = value << 6 | sextet as u32;
+        }
+        value <<= 6 * padding;
+        decoded.extend(&value.to_be_bytes()[1..4 - padding]);
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, Litecoin};
+    use anychain_core::{hex, PublicKey};
+    use core::str::FromStr;
+
+    const SECRET_KEY: &str = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+    const MESSAGE: &[u8] = b"Hello, anychain";
+    const SIGNATURE: &str =
+        "H0rxsXYMItNCohYCdxdPa4yBuOvfdxzqBbN12aVX45LQcaWZD/X++DtauSqXWWTUZktEw3LzKrzOoG2bMZYPxlc=";
+
+    fn secret_key() -> SecretKey {
+        SecretKey::parse_slice(&hex::decode(SECRET_KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sign_message() {
+        assert_eq!(
+            SIGNATURE,
+            sign_message::<Bitcoin>(MESSAGE, &secret_key(), true)
+        );
+        assert_ne!(
+            SIGNATURE,
+            sign_message::<Litecoin>(MESSAGE, &secret_key(), true)
+        );
+    }
+
+    #[test]
+    fn test_verify_message() {
+        let address =
+            BitcoinAddress::<Bitcoin>::from_str("1LoVGDgRs9hTfTNJNuXKSpywcbdvwRXpmK").unwrap();
+        assert!(verify_message(&address, MESSAGE, SIGNATURE).unwrap());
+        assert!(!verify_message(&address, b"Hello, anychain!", SIGNATURE).unwrap());
+
+        // the uncompressed key has a different address
+        let uncompressed = sign_message::<Bitcoin>(MESSAGE, &secret_key(), false);
+        assert!(!verify_message(&address, MESSAGE, &uncompressed).unwrap());
+        let address =
+            BitcoinAddress::<Bitcoin>::from_str("1GAehh7TsJAHuUAeKZcXf5CnwuGuGgyX2S").unwrap();
+        assert!(verify_message(&address, MESSAGE, &uncompressed).unwrap());
+
+        // a compressed key signature also proves the ownership of its P2WPKH address
+        let public_key = BitcoinPublicKey::<Bitcoin>::from_secret_key(&secret_key());
+        let address = public_key.to_address(&BitcoinFormat::Bech32).unwrap();
+        assert!(verify_message(&address, MESSAGE, SIGNATURE).unwrap());
+
+        assert!(verify_message(&address, MESSAGE, "H0rx").is_err());
+        assert!(verify_message(&address, MESSAGE, &SIGNATURE[1..]).is_err());
+    }
+
+    #[test]
+    fn test_message_signer() {
+        type N = Bitcoin;
+
+        for format in [
+            BitcoinFormat::P2PKH,
+            BitcoinFormat::P2SH_P2WPKH,
+            BitcoinFormat::Bech32,
+        ] {
+            let address = BitcoinAddress::<N>::from_secret_key(&secret_key(), &format).unwrap();
+            let signature =
+                BitcoinAddress::<N>::sign_message(&secret_key(), &format, MESSAGE).unwrap();
+            assert!(address.verify_message(MESSAGE, &signature).unwrap());
+            assert!(!address.verify_message(b"Hello", &signature).unwrap());
+        }
+
+        // the P2PKH signature is the one of Bitcoin Core
+        let signature =
+            BitcoinAddress::<N>::sign_message(&secret_key(), &BitcoinFormat::P2PKH, MESSAGE)
+                .unwrap();
+        assert_eq!(SIGNATURE, base64_encode(&signature));
+
+        assert!(
+            BitcoinAddress::<N>::sign_message(&secret_key(), &BitcoinFormat::P2WSH, MESSAGE)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(Some(data.to_vec()), base64_decode(&base64_encode(data)));
+        }
+        assert_eq!("Zm9vYg==", base64_encode(b"foob"));
+        assert_eq!(None, base64_decode("Zm9=vYg="));
+    }
+}
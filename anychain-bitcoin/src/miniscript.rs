@@ -0,0 +1,553 @@
+//!
+//! Miniscript
+//!
+//! This module implements a subset of miniscript: spending policies such as
+//! `and(pk(A),or(pk(B),older(144)))` are parsed and compiled to miniscript
+//! fragments, which are encoded as a P2WSH witness script or a tapscript
+//! leaf. The size of the largest witness satisfying a script is computed from
+//! its fragments, so that the fee of spending it can be estimated up front.
+//!
+//! The compilation is deterministic rather than optimal: `and` compiles to
+//! `and_v`, `or` to `or_i` and `thresh` of keys to `multi` or `multi_a`, which
+//! yields valid, non-malleable scripts for every policy of the subset.
+//!
+
+use crate::push_int;
+use anychain_core::{hex, libsecp256k1::PublicKey, no_std::*, TransactionError};
+use core::{fmt, str::FromStr};
+
+const OP_IF: u8 = 0x63;
+const OP_ELSE: u8 = 0x67;
+const OP_ENDIF: u8 = 0x68;
+const OP_VERIFY: u8 = 0x69;
+const OP_SIZE: u8 = 0x82;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_NUMEQUAL: u8 = 0x9c;
+const OP_NUMEQUALVERIFY: u8 = 0x9d;
+const OP_HASH160: u8 = 0xa9;
+const OP_SHA256: u8 = 0xa8;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKSIGVERIFY: u8 = 0xad;
+const OP_CHECKMULTISIG: u8 = 0xae;
+const OP_CHECKMULTISIGVERIFY: u8 = 0xaf;
+const OP_CHECKLOCKTIMEVERIFY: u8 = 0xb1;
+const OP_CHECKSEQUENCEVERIFY: u8 = 0xb2;
+const OP_CHECKSIGADD: u8 = 0xba;
+
+/// The maximum number of keys of a `multi` fragment
+const MAX_MULTI_KEYS: usize = 20;
+
+/// The script context a miniscript is encoded for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScriptContext {
+    /// A P2WSH witness script, with compressed keys and ECDSA signatures
+    Segwitv0,
+    /// A tapscript leaf, with x-only keys and Schnorr signatures
+    Tapscript,
+}
+
+impl ScriptContext {
+    /// Returns the size of the largest signature of the context, with its
+    /// length prefix
+    fn signature_size(&self) -> usize {
+        match self {
+            // a 72 byte DER signature and the sighash byte
+            ScriptContext::Segwitv0 => 1 + 73,
+            // a 64 byte signature and a non-default sighash byte
+            ScriptContext::Tapscript => 1 + 65,
+        }
+    }
+
+    /// Returns the serialization of 'key' in the context
+    fn serialize_key(&self, key: &PublicKey) -> Vec<u8> {
+        match self {
+            ScriptContext::Segwitv0 => key.serialize_compressed().to_vec(),
+            ScriptContext::Tapscript => key.serialize_compressed()[1..].to_vec(),
+        }
+    }
+}
+
+/// Represents a spending policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// A signature of the key
+    Key(PublicKey),
+    /// An absolute locktime of at least the height or time
+    After(u32),
+    /// A relative locktime of at least the number of blocks or time units
+    Older(u32),
+    /// The preimage of the SHA256 hash
+    Sha256([u8; 32]),
+    /// The preimage of the HASH160 hash
+    Hash160([u8; 20]),
+    /// Both policies
+    And(Box<Policy>, Box<Policy>),
+    /// Either policy
+    Or(Box<Policy>, Box<Policy>),
+    /// At least k of the policies
+    Threshold(usize, Vec<Policy>),
+}
+
+/// Represents a miniscript fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Miniscript {
+    /// `pk(K)`, a signature of the key checked with `CHECKSIG`
+    Pk(PublicKey),
+    /// `after(n)`, `<n> CHECKLOCKTIMEVERIFY`
+    After(u32),
+    /// `older(n)`, `<n> CHECKSEQUENCEVERIFY`
+    Older(u32),
+    /// `sha256(h)`, a 32 byte preimage of the hash
+    Sha256([u8; 32]),
+    /// `hash160(h)`, a 32 byte preimage of the hash
+    Hash160([u8; 20]),
+    /// `multi(k,K1,...,Kn)`, `CHECKMULTISIG` outside of tapscript
+    Multi(usize, Vec<PublicKey>),
+    /// `multi_a(k,K1,...,Kn)`, `CHECKSIGADD` in tapscript
+    MultiA(usize, Vec<PublicKey>),
+    /// `v:X`, the fragment followed by a verify
+    Verify(Box<Miniscript>),
+    /// `and_v(X,Y)`, the verify fragment X followed by Y
+    AndV(Box<Miniscript>, Box<Miniscript>),
+    /// `or_i(X,Y)`, X or Y selected by the satisfier with `IF`
+    OrI(Box<Miniscript>, Box<Miniscript>),
+}
+
+/// Returns the arguments of 'fragment', the comma separated arguments between
+/// the parentheses following 'name'
+fn arguments<'a>(fragment: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let inner = fragment
+        .strip_prefix(name)?
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    let mut arguments = vec![];
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                arguments.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(&inner[start..]);
+    Some(arguments)
+}
+
+impl FromStr for Policy {
+    type Err = TransactionError;
+
+    /// Returns the policy of 's'. Keys are hex encoded compressed or x-only
+    /// keys, the probabilities of `or` branches (`9@pk(A)`) are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransactionError::Message(format!("invalid policy: {}", s));
+        let number = |arguments: Vec<&str>| -> Result<u32, TransactionError> {
+            match arguments[..] {
+                [n] => match n.parse::<u32>() {
+                    Ok(n) if (1..0x8000_0000).contains(&n) => Ok(n),
+                    _ => Err(invalid()),
+                },
+                _ => Err(invalid()),
+            }
+        };
+        let hash = |arguments: Vec<&str>, length: usize| -> Result<Vec<u8>, TransactionError> {
+            match arguments[..] {
+                [hash] => match hex::decode(hash) {
+                    Ok(hash) if hash.len() == length => Ok(hash),
+                    _ => Err(invalid()),
+                },
+                _ => Err(invalid()),
+            }
+        };
+        let branch = |policy: &str| match policy.split_once('@') {
+            Some((weight, policy)) if weight.parse::<u32>().is_ok() => Policy::from_str(policy),
+            _ => Policy::from_str(policy),
+        };
+
+        if let Some(arguments) = arguments(s, "pk") {
+            let key = match arguments[..] {
+                [key] => hex::decode(key).map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            };
+            let key = match key.len() {
+                32 => PublicKey::parse_slice(&[&[0x02], &key[..]].concat(), None),
+                _ => PublicKey::parse_slice(&key, None),
+            };
+            Ok(Policy::Key(key.map_err(|_| invalid())?))
+        } else if let Some(arguments) = arguments(s, "after") {
+            Ok(Policy::After(number(arguments)?))
+        } else if let Some(arguments) = arguments(s, "older") {
+            Ok(Policy::Older(number(arguments)?))
+        } else if let Some(arguments) = arguments(s, "sha256") {
+            Ok(Policy::Sha256(hash(arguments, 32)?.try_into().unwrap()))
+        } else if let Some(arguments) = arguments(s, "hash160") {
+            Ok(Policy::Hash160(hash(arguments, 20)?.try_into().unwrap()))
+        } else if let Some(arguments) = arguments(s, "and") {
+            match arguments[..] {
+                [x, y] => Ok(Policy::And(
+                    Box::new(Policy::from_str(x)?),
+                    Box::new(Policy::from_str(y)?),
+                )),
+                _ => Err(invalid()),
+            }
+        } else if let Some(arguments) = arguments(s, "or") {
+            match arguments[..] {
+                [x, y] => Ok(Policy::Or(Box::new(branch(x)?), Box::new(branch(y)?))),
+                _ => Err(invalid()),
+            }
+        } else if let Some(arguments) = arguments(s, "thresh") {
+            let k = arguments[0].parse::<usize>().map_err(|_| invalid())?;
+            let policies = arguments[1..]
+                .iter()
+                .map(|policy| Policy::from_str(policy))
+                .collect::<Result<Vec<Policy>, TransactionError>>()?;
+            match (1..=policies.len()).contains(&k) {
+                true => Ok(Policy::Threshold(k, policies)),
+                false => Err(invalid()),
+            }
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl Policy {
+    /// Returns the miniscript of the policy in 'context'. A threshold must
+    /// be of keys only, unless it requires one or all of its policies.
+    pub fn compile(&self, context: ScriptContext) -> Result<Miniscript, TransactionError> {
+        let and = |x: &Policy, y: &Policy| -> Result<Miniscript, TransactionError> {
+            Ok(Miniscript::AndV(
+                Box::new(Miniscript::Verify(Box::new(x.compile(context)?))),
+                Box::new(y.compile(context)?),
+            ))
+        };
+        let or = |x: &Policy, y: &Policy| -> Result<Miniscript, TransactionError> {
+            Ok(Miniscript::OrI(
+                Box::new(x.compile(context)?),
+                Box::new(y.compile(context)?),
+            ))
+        };
+
+        match self {
+            Policy::Key(key) => Ok(Miniscript::Pk(*key)),
+            Policy::After(n) => Ok(Miniscript::After(*n)),
+            Policy::Older(n) => Ok(Miniscript::Older(*n)),
+            Policy::Sha256(hash) => Ok(Miniscript::Sha256(*hash)),
+            Policy::Hash160(hash) => Ok(Miniscript::Hash160(*hash)),
+            Policy::And(x, y) => and(x, y),
+            Policy::Or(x, y) => or(x, y),
+            Policy::Threshold(k, policies) => {
+                let keys: Option<Vec<PublicKey>> = policies
+                    .iter()
+                    .map(|policy| match policy {
+                        Policy::Key(key) => Some(*key),
+                        _ => None,
+                    })
+                    .collect();
+                match (keys, context) {
+                    (Some(keys), ScriptContext::Segwitv0) if keys.len() <= MAX_MULTI_KEYS => {
+                        Ok(Miniscript::Multi(*k, keys))
+                    }
+                    (Some(keys), ScriptContext::Tapscript) => Ok(Miniscript::MultiA(*k, keys)),
+                    _ if policies.len() == 1 => policies[0].compile(context),
+                    // nest the policies from the right, and(A,and(B,C))
+                    _ if *k == policies.len() || *k == 1 => {
+                        let (last, rest) = policies.split_last().unwrap();
+                        let policy = rest.iter().rev().fold(last.clone(), |policy, x| match *k {
+                            1 => Policy::Or(Box::new(x.clone()), Box::new(policy)),
+                            _ => Policy::And(Box::new(x.clone()), Box::new(policy)),
+                        });
+                        policy.compile(context)
+                    }
+                    _ => Err(TransactionError::Message(
+                        "unsupported threshold of policies other than keys".into(),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl Miniscript {
+    /// Returns the script of the fragment in 'context'
+    pub fn encode(&self, context: ScriptContext) -> Vec<u8> {
+        let push_key = |key: &PublicKey| {
+            let key = context.serialize_key(key);
+            [vec![key.len() as u8], key].concat()
+        };
+
+        match self {
+            Miniscript::Pk(key) => [push_key(key), vec![OP_CHECKSIG]].concat(),
+            Miniscript::After(n) => [push_int(*n as i64), vec![OP_CHECKLOCKTIMEVERIFY]].concat(),
+            Miniscript::Older(n) => [push_int(*n as i64), vec![OP_CHECKSEQUENCEVERIFY]].concat(),
+            Miniscript::Sha256(hash) => {
+                let mut script = [OP_SIZE].to_vec();
+                script.extend(push_int(32));
+                script.extend([OP_EQUALVERIFY, OP_SHA256, 32]);
+                script.extend(hash);
+                script.push(OP_EQUAL);
+                script
+            }
+            Miniscript::Hash160(hash) => {
+                let mut script = [OP_SIZE].to_vec();
+                script.extend(push_int(32));
+                script.extend([OP_EQUALVERIFY, OP_HASH160, 20]);
+                script.extend(hash);
+                script.push(OP_EQUAL);
+                script
+            }
+            Miniscript::Multi(k, keys) => {
+                let mut script = push_int(*k as i64);
+                keys.iter().for_each(|key| script.extend(push_key(key)));
+                script.extend(push_int(keys.len() as i64));
+                script.push(OP_CHECKMULTISIG);
+                script
+            }
+            Miniscript::MultiA(k, keys) => {
+                let mut script = vec![];
+                for (i, key) in keys.iter().enumerate() {
+                    script.extend(push_key(key));
+                    script.push(if i == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD });
+                }
+                script.extend(push_int(*k as i64));
+                script.push(OP_NUMEQUAL);
+                script
+            }
+            Miniscript::Verify(x) => {
+                let mut script = x.encode(context);
+                // merge the verify into the last opcode if it has a verify form
+                match script.last_mut() {
+                    Some(last @ &mut OP_CHECKSIG) => *last = OP_CHECKSIGVERIFY,
+                    Some(last @ &mut OP_CHECKMULTISIG) => *last = OP_CHECKMULTISIGVERIFY,
+                    Some(last @ &mut OP_EQUAL) => *last = OP_EQUALVERIFY,
+                    Some(last @ &mut OP_NUMEQUAL) => *last = OP_NUMEQUALVERIFY,
+                    _ => script.push(OP_VERIFY),
+                }
+                script
+            }
+            Miniscript::AndV(x, y) => [x.encode(context), y.encode(context)].concat(),
+            Miniscript::OrI(x, y) => [
+                vec![OP_IF],
+                x.encode(context),
+                vec![OP_ELSE],
+                y.encode(context),
+                vec![OP_ENDIF],
+            ]
+            .concat(),
+        }
+    }
+
+    /// Returns the number of witness elements and their total size, length
+    /// prefixes included, of the largest satisfaction of the fragment
+    fn max_satisfaction(&self, context: ScriptContext) -> (usize, usize) {
+        let signature = context.signature_size();
+        match self {
+            Miniscript::Pk(_) => (1, signature),
+            Miniscript::After(_) | Miniscript::Older(_) => (0, 0),
+            Miniscript::Sha256(_) | Miniscript::Hash160(_) => (1, 1 + 32),
+            // the dummy element and k signatures
+            Miniscript::Multi(k, _) => (1 + k, 1 + k * signature),
+            // k signatures and an empty element for each other key
+            Miniscript::MultiA(k, keys) => (keys.len(), k * signature + keys.len() - k),
+            Miniscript::Verify(x) => x.max_satisfaction(context),
+            Miniscript::AndV(x, y) => {
+                let (x, y) = (x.max_satisfaction(context), y.max_satisfaction(context));
+                (x.0 + y.0, x.1 + y.1)
+            }
+            // X is selected with a 0x01 element, Y with an empty element
+            Miniscript::OrI(x, y) => {
+                let (x, y) = (x.max_satisfaction(context), y.max_satisfaction(context));
+                (1 + x.0.max(y.0), (x.1 + 2).max(y.1 + 1))
+            }
+        }
+    }
+
+    /// Returns the total size of the witness elements, length prefixes
+    /// included, of the largest satisfaction of the fragment
+    pub fn max_satisfaction_size(&self, context: ScriptContext) -> usize {
+        self.max_satisfaction(context).1
+    }
+
+    /// Returns the weight of an input spending the script at its largest
+    /// satisfaction: a P2WSH input in Segwitv0, or the script path spend of
+    /// the only leaf of a taproot output in Tapscript
+    pub fn max_satisfaction_weight(&self, context: ScriptContext) -> usize {
+        let (elements, size) = self.max_satisfaction(context);
+        let script = self.encode(context).len();
+        let control_block = match context {
+            ScriptContext::Segwitv0 => 0,
+            ScriptContext::Tapscript => 1 + 33,
+        };
+        let items = match context {
+            ScriptContext::Segwitv0 => elements + 1,
+            ScriptContext::Tapscript => elements + 2,
+        };
+        // outpoint, empty scriptSig and sequence, at 4 weight units per byte
+        4 * (32 + 4 + 1 + 4)
+            + crate::variable_length_integer_size(items as u64)
+            + size
+            + crate::variable_length_integer_size(script as u64)
+            + script
+            + control_block
+    }
+}
+
+impl fmt::Display for Miniscript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keys = |keys: &[PublicKey]| {
+            keys.iter()
+                .map(|key| hex::encode(key.serialize_compressed()))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        match self {
+            Miniscript::Pk(key) => write!(f, "pk({})", hex::encode(key.serialize_compressed())),
+            Miniscript::After(n) => write!(f, "after({})", n),
+            Miniscript::Older(n) => write!(f, "older({})", n),
+            Miniscript::Sha256(hash) => write!(f, "sha256({})", hex::encode(hash)),
+            Miniscript::Hash160(hash) => write!(f, "hash160({})", hex::encode(hash)),
+            Miniscript::Multi(k, pks) => write!(f, "multi({},{})", k, keys(pks)),
+            Miniscript::MultiA(k, pks) => write!(f, "multi_a({},{})", k, keys(pks)),
+            Miniscript::Verify(x) => write!(f, "v:{}", x),
+            Miniscript::AndV(x, y) => write!(f, "and_v({},{})", x, y),
+            Miniscript::OrI(x, y) => write!(f, "or_i({},{})", x, y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinAddress};
+
+    const A: &str = "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+    const B: &str = "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9";
+    const C: &str = "03e60fce93b59e9ec53011aabc21c23e97b2a31369b87a5ae9c44ee89e2a6dec0a";
+
+    fn compile(policy: &str, context: ScriptContext) -> Miniscript {
+        Policy::from_str(policy).unwrap().compile(context).unwrap()
+    }
+
+    #[test]
+    fn test_compile() {
+        let miniscript = compile(
+            &format!("and(pk({}),older(144))", A),
+            ScriptContext::Segwitv0,
+        );
+        assert_eq!(
+            format!("and_v(v:pk({}),older(144))", A),
+            miniscript.to_string()
+        );
+        assert_eq!(
+            format!("21{}ad029000b2", A),
+            hex::encode(miniscript.encode(ScriptContext::Segwitv0))
+        );
+
+        let miniscript = compile(
+            &format!("or(9@pk({}),1@pk({}))", A, B),
+            ScriptContext::Segwitv0,
+        );
+        assert_eq!(
+            format!("6321{}ac6721{}ac68", A, B),
+            hex::encode(miniscript.encode(ScriptContext::Segwitv0))
+        );
+
+        let policy = format!("thresh(2,pk({}),pk({}),pk({}))", A, B, C);
+        assert_eq!(
+            format!("5221{}21{}21{}53ae", A, B, C),
+            hex::encode(compile(&policy, ScriptContext::Segwitv0).encode(ScriptContext::Segwitv0))
+        );
+        assert_eq!(
+            format!("20{}ac20{}ba20{}ba529c", &A[2..], &B[2..], &C[2..]),
+            hex::encode(
+                compile(&policy, ScriptContext::Tapscript).encode(ScriptContext::Tapscript)
+            )
+        );
+
+        let hash = "9c1185a5c5e9fc54612808977ee8f548b2258d31ddadef707ba62c1e1d2e9a6c";
+        assert_eq!(
+            format!("82012088a820{}87", hash),
+            hex::encode(
+                compile(&format!("sha256({})", hash), ScriptContext::Segwitv0)
+                    .encode(ScriptContext::Segwitv0)
+            )
+        );
+
+        // a threshold of all its policies is a chain of ands
+        assert_eq!(
+            format!("and_v(v:pk({}),and_v(v:after(500000),older(10)))", A),
+            compile(
+                &format!("thresh(3,pk({}),after(500000),older(10))", A),
+                ScriptContext::Segwitv0
+            )
+            .to_string()
+        );
+
+        for invalid in [
+            "pk(02)".to_string(),
+            "older(0)".into(),
+            format!("and(pk({}))", A),
+            format!("thresh(0,pk({}))", A),
+            format!("or(pk({}),older(1)", A),
+            "sha256(00)".into(),
+        ] {
+            assert!(Policy::from_str(&invalid).is_err(), "{}", invalid);
+        }
+        let policy = format!("thresh(2,pk({}),older(1),after(1))", A);
+        assert!(Policy::from_str(&policy)
+            .unwrap()
+            .compile(ScriptContext::Segwitv0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_max_satisfaction() {
+        let miniscript = compile(
+            &format!("and(pk({}),older(144))", A),
+            ScriptContext::Segwitv0,
+        );
+        assert_eq!(
+            74,
+            miniscript.max_satisfaction_size(ScriptContext::Segwitv0)
+        );
+        // 164 for the input, 2 witness elements: a signature and the 39 byte script
+        assert_eq!(
+            164 + 1 + 74 + 1 + 39,
+            miniscript.max_satisfaction_weight(ScriptContext::Segwitv0)
+        );
+        assert!(
+            BitcoinAddress::<Bitcoin>::p2wsh(&miniscript.encode(ScriptContext::Segwitv0)).is_ok()
+        );
+
+        // the key branch, its selector and the signature outweigh the timelock branch
+        let miniscript = compile(
+            &format!("or(pk({}),older(144))", A),
+            ScriptContext::Segwitv0,
+        );
+        assert_eq!(
+            76,
+            miniscript.max_satisfaction_size(ScriptContext::Segwitv0)
+        );
+
+        let policy = format!("thresh(2,pk({}),pk({}),pk({}))", A, B, C);
+        let multi = compile(&policy, ScriptContext::Segwitv0);
+        assert_eq!(
+            1 + 2 * 74,
+            multi.max_satisfaction_size(ScriptContext::Segwitv0)
+        );
+        let multi_a = compile(&policy, ScriptContext::Tapscript);
+        assert_eq!(
+            2 * 66 + 1,
+            multi_a.max_satisfaction_size(ScriptContext::Tapscript)
+        );
+        // 3 elements, the script and a 33 byte control block
+        assert_eq!(
+            164 + 1 + 133 + 1 + 104 + 34,
+            multi_a.max_satisfaction_weight(ScriptContext::Tapscript)
+        );
+    }
+}
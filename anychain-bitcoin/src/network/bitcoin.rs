@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct Bitcoin;
 
 impl Network for Bitcoin {
@@ -13,12 +13,14 @@ impl Network for Bitcoin {
 }
 
 impl BitcoinNetwork for Bitcoin {
+    const SILENT_PAYMENT_HRP: Option<&'static str> = Some("sp");
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x00)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x05)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x05)),
             BitcoinFormat::Bech32 => Ok(Prefix::AddressPrefix("bc".to_string())),
             f => Err(AddressError::Message(format!(
                 "{} does not support address format {}",
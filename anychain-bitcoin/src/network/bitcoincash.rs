@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct BitcoinCash;
 
 impl Network for BitcoinCash {
@@ -13,16 +13,18 @@ impl Network for BitcoinCash {
 }
 
 impl BitcoinNetwork for BitcoinCash {
+    const SEGWIT: bool = false;
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x00)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x05)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x05)),
             BitcoinFormat::Bech32 => Ok(Prefix::AddressPrefix("bc".to_string())),
             BitcoinFormat::CashAddr => Ok(Prefix::AddressPrefix("bitcoincash".to_string())),
         }
     }
+    const COIN_TYPE: u32 = 145;
 
     /// Returns the network of the given address prefix.
     fn from_address_prefix(prefix: Prefix) -> Result<Self, AddressError> {
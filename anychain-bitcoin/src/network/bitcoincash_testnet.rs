@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct BitcoinCashTestnet;
 
 impl Network for BitcoinCashTestnet {
@@ -13,12 +13,18 @@ impl Network for BitcoinCashTestnet {
 }
 
 impl BitcoinNetwork for BitcoinCashTestnet {
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+    const WIF_VERSION: u8 = 0xef;
+    const SEGWIT: bool = false;
+    const COIN_TYPE: u32 = 1;
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x6f)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0xc4)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0xc4)),
             BitcoinFormat::Bech32 => Ok(Prefix::AddressPrefix("tb".to_string())),
             BitcoinFormat::CashAddr => Ok(Prefix::AddressPrefix("bchtest".to_string())),
         }
@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct Dogecoin;
 
 impl Network for Dogecoin {
@@ -13,12 +13,20 @@ impl Network for Dogecoin {
 }
 
 impl BitcoinNetwork for Dogecoin {
+    const MESSAGE_PREFIX: &'static str = "Dogecoin Signed Message:\n";
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x02, 0xfa, 0xca, 0xfd];
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x02, 0xfa, 0xc3, 0x98];
+    const WIF_VERSION: u8 = 0x9e;
+    const SEGWIT: bool = false;
+    const COIN_TYPE: u32 = 3;
+    const MAX_MONEY: i64 = 10_000_000_000 * 100_000_000;
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x1e)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x16)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x16)),
             f => Err(AddressError::Message(format!(
                 "{} does not support address format {}",
                 Self::NAME,
@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct DogecoinTestnet;
 
 impl Network for DogecoinTestnet {
@@ -15,12 +15,20 @@ impl Network for DogecoinTestnet {
 pub static mut LOOP: u8 = 0;
 
 impl BitcoinNetwork for DogecoinTestnet {
+    const MESSAGE_PREFIX: &'static str = "Dogecoin Signed Message:\n";
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x04, 0x32, 0xa9, 0xa8];
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x04, 0x32, 0xa2, 0x43];
+    const WIF_VERSION: u8 = 0xf1;
+    const SEGWIT: bool = false;
+    const COIN_TYPE: u32 = 1;
+    const MAX_MONEY: i64 = 10_000_000_000 * 100_000_000;
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x71)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0xc4)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0xc4)),
             f => Err(AddressError::Message(format!(
                 "{} does not support address format {}",
                 Self::NAME,
@@ -5,7 +5,7 @@ use anychain_core::{AddressError, Network, NetworkError};
 use core::{fmt, str::FromStr};
 use serde::Serialize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct LitecoinTestnet;
 
 impl Network for LitecoinTestnet {
@@ -13,12 +13,20 @@ impl Network for LitecoinTestnet {
 }
 
 impl BitcoinNetwork for LitecoinTestnet {
+    const MESSAGE_PREFIX: &'static str = "Litecoin Signed Message:\n";
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x04, 0x36, 0xf6, 0xe1];
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x04, 0x36, 0xef, 0x7d];
+    const WIF_VERSION: u8 = 0xef;
+    const DEPRECATED_P2SH_VERSION: Option<u8> = Some(0xc4);
+    const COIN_TYPE: u32 = 1;
+    const MAX_MONEY: i64 = 84_000_000 * 100_000_000;
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
         match format {
             BitcoinFormat::P2PKH => Ok(Prefix::Version(0x6f)),
             BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
-            BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x3a)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0x3a)),
             BitcoinFormat::Bech32 => Ok(Prefix::AddressPrefix("tltc".to_string())),
             f => Err(AddressError::Message(format!(
                 "{} does not support address format {}",
@@ -32,7 +40,7 @@ impl BitcoinNetwork for LitecoinTestnet {
     fn from_address_prefix(prefix: Prefix) -> Result<Self, AddressError> {
         match prefix {
             Prefix::Version(version) => match version {
-                0x6f | 0x3a => Ok(Self),
+                0x6f | 0x3a | 0xc4 => Ok(Self),
                 _ => Err(AddressError::Message(format!(
                     "Invalid version byte {:#0x} for {} network",
                     version,
@@ -8,6 +8,12 @@ pub use self::bitcoin::*;
 pub mod bitcoin_testnet;
 pub use self::bitcoin_testnet::*;
 
+pub mod signet;
+pub use self::signet::*;
+
+pub mod testnet4;
+pub use self::testnet4::*;
+
 pub mod bitcoincash;
 pub use self::bitcoincash::*;
 
@@ -26,8 +32,152 @@ pub use self::dogecoin::*;
 pub mod dogecoin_testnet;
 pub use self::dogecoin_testnet::*;
 
+/// Declares a new Bitcoin network given its name, base58 version bytes and
+/// bech32 human-readable part, so that Bitcoin forks and private chains can be
+/// supported without patching the crate. The message prefix, the BIP32
+/// version bytes, the WIF version byte, the BIP44 coin type and the money
+/// supply bound are optional and default to those of Bitcoin.
+///
+/// ```
+/// anychain_bitcoin::bitcoin_network! {
+///     /// The Bitcoin regression test network
+///     pub struct Regtest {
+///         name: "bitcoin regtest",
+///         p2pkh: 0x6f,
+///         p2sh: 0xc4,
+///         bech32: "bcrt",
+///         extended_public_key_version: [0x04, 0x35, 0x87, 0xcf],
+///         extended_private_key_version: [0x04, 0x35, 0x83, 0x94],
+///         wif_version: 0xef,
+///         coin_type: 1,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! bitcoin_network {
+    (
+        $(#[$attribute:meta])*
+        $visibility:vis struct $network:ident {
+            name: $name:expr,
+            p2pkh: $p2pkh:expr,
+            p2sh: $p2sh:expr,
+            bech32: $hrp:expr
+            $(, message_prefix: $message_prefix:expr)?
+            $(, extended_public_key_version: $extended_public_key_version:expr)?
+            $(, extended_private_key_version: $extended_private_key_version:expr)?
+            $(, wif_version: $wif_version:expr)?
+            $(, coin_type: $coin_type:expr)?
+            $(, max_money: $max_money:expr)?
+            $(,)?
+        }
+    ) => {
+        $(#[$attribute])*
+        #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $visibility struct $network;
+
+        impl $crate::anychain_core::Network for $network {
+            const NAME: &'static str = $name;
+        }
+
+        impl $crate::BitcoinNetwork for $network {
+            $(const MESSAGE_PREFIX: &'static str = $message_prefix;)?
+            $(const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = $extended_public_key_version;)?
+            $(const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = $extended_private_key_version;)?
+            $(const WIF_VERSION: u8 = $wif_version;)?
+            $(const COIN_TYPE: u32 = $coin_type;)?
+            $(const MAX_MONEY: i64 = $max_money;)?
+
+            /// Returns the address prefix of the given network.
+            fn to_address_prefix(
+                format: $crate::BitcoinFormat,
+            ) -> Result<$crate::Prefix, $crate::anychain_core::AddressError> {
+                match format {
+                    $crate::BitcoinFormat::P2PKH => Ok($crate::Prefix::Version($p2pkh)),
+                    $crate::BitcoinFormat::P2WSH => Ok($crate::Prefix::Version(0x00)),
+                    $crate::BitcoinFormat::P2SH | $crate::BitcoinFormat::P2SH_P2WPKH => {
+                        Ok($crate::Prefix::Version($p2sh))
+                    }
+                    $crate::BitcoinFormat::Bech32 => Ok($crate::Prefix::from_prefix($hrp)),
+                    f => Err($crate::anychain_core::AddressError::Message(
+                        $crate::anychain_core::no_std::format!(
+                            "{} does not support address format {}",
+                            $name,
+                            f,
+                        ),
+                    )),
+                }
+            }
+
+            /// Returns the network of the given address prefix.
+            fn from_address_prefix(
+                prefix: $crate::Prefix,
+            ) -> Result<Self, $crate::anychain_core::AddressError> {
+                match prefix {
+                    $crate::Prefix::Version(version) if version == $p2pkh || version == $p2sh => {
+                        Ok(Self)
+                    }
+                    $crate::Prefix::AddressPrefix(prefix) if prefix == $hrp => Ok(Self),
+                    _ => Err($crate::anychain_core::AddressError::Message(
+                        $crate::anychain_core::no_std::format!(
+                            "Invalid address prefix for {} network",
+                            $name,
+                        ),
+                    )),
+                }
+            }
+        }
+
+        impl core::str::FromStr for $network {
+            type Err = $crate::anychain_core::NetworkError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $name => Ok(Self),
+                    _ => Err($crate::anychain_core::NetworkError::InvalidNetwork(s.into())),
+                }
+            }
+        }
+
+        impl core::fmt::Display for $network {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "{}", $name)
+            }
+        }
+    };
+}
+
 /// The interface for a Bitcoin network.
 pub trait BitcoinNetwork: Network {
+    /// The prefix of messages signed with `signmessage` on the given network.
+    const MESSAGE_PREFIX: &'static str = "Bitcoin Signed Message:\n";
+
+    /// The human-readable part of silent payment addresses (BIP352), if the
+    /// network supports them.
+    const SILENT_PAYMENT_HRP: Option<&'static str> = None;
+
+    /// The version bytes of BIP32 extended public keys on the given network.
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+    /// The version bytes of BIP32 extended private keys on the given network.
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+
+    /// The version byte of private keys in wallet import format on the given network.
+    const WIF_VERSION: u8 = 0x80;
+
+    /// The coin type of BIP44 derivation paths on the given network (SLIP-0044).
+    const COIN_TYPE: u32 = 0;
+
+    /// The largest amount of satoshis a transaction may move on the given
+    /// network, the `MAX_MONEY` of its node.
+    const MAX_MONEY: i64 = 21_000_000 * 100_000_000;
+
+    /// Whether the given network supports segregated witness transactions.
+    const SEGWIT: bool = true;
+
+    /// The version byte P2SH addresses used to be encoded with on the given
+    /// network, still accepted when parsing addresses.
+    const DEPRECATED_P2SH_VERSION: Option<u8> = None;
+
     /// Returns the address prefix of the given network.
     fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError>;
 
@@ -44,19 +194,25 @@ pub enum Prefix {
 }
 
 impl Prefix {
-    pub fn version(self) -> u8 {
-        if let Self::Version(version) = self {
-            version
-        } else {
-            panic!("Attempt to get version byte from an AddressPrefix");
+    /// Returns the version byte, failing on an address prefix
+    pub fn version(self) -> Result<u8, AddressError> {
+        match self {
+            Self::Version(version) => Ok(version),
+            Self::AddressPrefix(prefix) => Err(AddressError::InvalidPrefix(format!(
+                "expected a version byte, found the address prefix {}",
+                prefix
+            ))),
         }
     }
 
-    pub fn prefix(self) -> String {
-        if let Self::AddressPrefix(prefix) = self {
-            prefix
-        } else {
-            panic!("Attempt to get prefix from a version");
+    /// Returns the address prefix, failing on a version byte
+    pub fn prefix(self) -> Result<String, AddressError> {
+        match self {
+            Self::AddressPrefix(prefix) => Ok(prefix),
+            Self::Version(version) => Err(AddressError::InvalidPrefix(format!(
+                "expected an address prefix, found the version byte {:#04x}",
+                version
+            ))),
         }
     }
 
@@ -68,3 +224,57 @@ impl Prefix {
         Self::AddressPrefix(prefix.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitcoinAddress, BitcoinFormat, BitcoinNetwork, BitcoinTestnet};
+    use anychain_core::{libsecp256k1::SecretKey, Address, Network};
+    use core::str::FromStr;
+
+    crate::bitcoin_network! {
+        /// The Bitcoin regression test network
+        struct Regtest {
+            name: "bitcoin regtest",
+            p2pkh: 0x6f,
+            p2sh: 0xc4,
+            bech32: "bcrt",
+            message_prefix: "Regtest Signed Message:\n",
+        }
+    }
+
+    #[test]
+    fn test_custom_network() {
+        assert_eq!("bitcoin regtest", Regtest::NAME);
+        assert_eq!(Regtest, Regtest::from_str(&Regtest.to_string()).unwrap());
+        assert_eq!("Regtest Signed Message:\n", Regtest::MESSAGE_PREFIX);
+        assert_eq!(
+            BitcoinTestnet::EXTENDED_PUBLIC_KEY_VERSION,
+            [0x04, 0x35, 0x87, 0xcf]
+        );
+
+        let secret_key = SecretKey::parse(&[1; 32]).unwrap();
+        let address =
+            BitcoinAddress::<Regtest>::from_secret_key(&secret_key, &BitcoinFormat::Bech32)
+                .unwrap();
+        assert!(address.to_string().starts_with("bcrt1q"));
+        assert_eq!(
+            address,
+            BitcoinAddress::<Regtest>::from_str(&address.to_string()).unwrap()
+        );
+
+        let address =
+            BitcoinAddress::<Regtest>::from_secret_key(&secret_key, &BitcoinFormat::P2PKH).unwrap();
+        let testnet =
+            BitcoinAddress::<BitcoinTestnet>::from_secret_key(&secret_key, &BitcoinFormat::P2PKH)
+                .unwrap();
+        assert_eq!(testnet.to_string(), address.to_string());
+        assert!(BitcoinAddress::<Regtest>::from_str("1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS").is_err());
+
+        let bech32 = Regtest::to_address_prefix(BitcoinFormat::Bech32).unwrap();
+        assert_eq!("bcrt", bech32.clone().prefix().unwrap());
+        assert!(bech32.version().is_err());
+        let p2pkh = Regtest::to_address_prefix(BitcoinFormat::P2PKH).unwrap();
+        assert_eq!(0x6f, p2pkh.clone().version().unwrap());
+        assert!(p2pkh.prefix().is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{BitcoinFormat, BitcoinNetwork, Prefix};
+use anychain_core::no_std::*;
+use anychain_core::{AddressError, Network, NetworkError};
+
+use core::{fmt, str::FromStr};
+use serde::Serialize;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Signet;
+
+impl Network for Signet {
+    const NAME: &'static str = "bitcoin signet";
+}
+
+impl BitcoinNetwork for Signet {
+    const SILENT_PAYMENT_HRP: Option<&'static str> = Some("tsp");
+    const EXTENDED_PUBLIC_KEY_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+    const EXTENDED_PRIVATE_KEY_VERSION: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+    const WIF_VERSION: u8 = 0xef;
+    const COIN_TYPE: u32 = 1;
+
+    /// Returns the address prefix of the given network.
+    fn to_address_prefix(format: BitcoinFormat) -> Result<Prefix, AddressError> {
+        match format {
+            BitcoinFormat::P2PKH => Ok(Prefix::Version(0x6f)),
+            BitcoinFormat::P2WSH => Ok(Prefix::Version(0x00)),
+            BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => Ok(Prefix::Version(0xc4)),
+            BitcoinFormat::Bech32 => Ok(Prefix::AddressPrefix("tb".to_string())),
+            f => Err(AddressError::Message(format!(
+                "{} does not support address format {}",
+                Self::NAME,
+                f,
+            ))),
+        }
+    }
+
+    /// Returns the network of the given address prefix.
+    fn from_address_prefix(prefix: Prefix) -> Result<Self, AddressError> {
+        match prefix {
+            Prefix::Version(version) => match version {
+                0x6f | 0xc4 => Ok(Self),
+                _ => Err(AddressError::Message(format!(
+                    "Invalid version byte {:#0x} for {} network",
+                    version,
+                    Self::NAME,
+                ))),
+            },
+            Prefix::AddressPrefix(prefix) => match prefix.as_str() {
+                "tb" => Ok(Self),
+                _ => Err(AddressError::Message(format!(
+                    "Invalid Bech32 prefix for {} network",
+                    Self::NAME,
+                ))),
+            },
+        }
+    }
+}
+
+impl FromStr for Signet {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::NAME => Ok(Self),
+            _ => Err(NetworkError::InvalidNetwork(s.into())),
+        }
+    }
+}
+
+impl fmt::Display for Signet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
@@ -0,0 +1,436 @@
+//!
+//! Omni
+//!
+//! This module encodes and decodes the Omni Layer messages carried in the
+//! OP_RETURN output of a Class C Omni transaction: the "omni" marker followed by
+//! the big-endian message version, message type and type specific fields.
+//!
+
+use crate::Opcode;
+use anychain_core::{no_std::*, TransactionError};
+
+/// The marker prefixing the payload of a Class C Omni transaction
+pub const OMNI_MARKER: &[u8; 4] = b"omni";
+
+/// The maximum length of the strings of an Omni message
+const MAX_STRING_LEN: usize = 255;
+
+/// OP_PUSHDATA1
+const OP_PUSHDATA1: u8 = 0x4c;
+
+/// The ecosystem an Omni property lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OmniEcosystem {
+    Main = 1,
+    Test = 2,
+}
+
+impl OmniEcosystem {
+    fn from_byte(byte: u8) -> Result<Self, TransactionError> {
+        match byte {
+            1 => Ok(Self::Main),
+            2 => Ok(Self::Test),
+            _ => Err(TransactionError::Message(format!(
+                "invalid omni ecosystem {}",
+                byte
+            ))),
+        }
+    }
+}
+
+/// The divisibility of the tokens of an Omni property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OmniPropertyType {
+    Indivisible = 1,
+    Divisible = 2,
+}
+
+impl OmniPropertyType {
+    fn from_u16(value: u16) -> Result<Self, TransactionError> {
+        match value {
+            1 => Ok(Self::Indivisible),
+            2 => Ok(Self::Divisible),
+            _ => Err(TransactionError::Message(format!(
+                "invalid omni property type {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// The description of a new Omni property
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OmniProperty {
+    /// The ecosystem of the property
+    pub ecosystem: OmniEcosystem,
+    /// The divisibility of the tokens
+    pub property_type: OmniPropertyType,
+    /// The property replaced by this one, or 0 for a new property
+    pub previous_property_id: u32,
+    pub category: String,
+    pub subcategory: String,
+    pub name: String,
+    pub url: String,
+    pub data: String,
+}
+
+/// Represents an Omni Layer message
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OmniPayload {
+    /// Transfers 'amount' tokens of 'property_id' to the reference output (type 0)
+    SimpleSend { property_id: u32, amount: i64 },
+    /// Transfers all the tokens of an ecosystem to the reference output (type 4)
+    SendAll { ecosystem: OmniEcosystem },
+    /// Creates a property with a fixed number of tokens (type 50)
+    CreatePropertyFixed { property: OmniProperty, amount: i64 },
+    /// Creates a property whose tokens are granted by the issuer (type 54)
+    CreatePropertyManaged { property: OmniProperty },
+    /// Issues 'amount' tokens of a managed property (type 55)
+    GrantTokens {
+        property_id: u32,
+        amount: i64,
+        memo: String,
+    },
+    /// Destroys 'amount' tokens of a managed property (type 56)
+    RevokeTokens {
+        property_id: u32,
+        amount: i64,
+        memo: String,
+    },
+}
+
+impl OmniPayload {
+    /// Returns the message type
+    pub fn message_type(&self) -> u16 {
+        match self {
+            Self::SimpleSend { .. } => 0,
+            Self::SendAll { .. } => 4,
+            Self::CreatePropertyFixed { .. } => 50,
+            Self::CreatePropertyManaged { .. } => 54,
+            Self::GrantTokens { .. } => 55,
+            Self::RevokeTokens { .. } => 56,
+        }
+    }
+
+    /// Returns the serialized message, without the "omni" marker
+    pub fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
+        // all the supported messages are of version 0
+        let mut payload = 0u16.to_be_bytes().to_vec();
+        payload.extend(self.message_type().to_be_bytes());
+
+        match self {
+            Self::SimpleSend {
+                property_id,
+                amount,
+            } => {
+                payload.extend(property_id.to_be_bytes());
+                payload.extend(amount.to_be_bytes());
+            }
+            Self::SendAll { ecosystem } => payload.push(*ecosystem as u8),
+            Self::CreatePropertyFixed { property, amount } => {
+                write_property(&mut payload, property)?;
+                payload.extend(amount.to_be_bytes());
+            }
+            Self::CreatePropertyManaged { property } => write_property(&mut payload, property)?,
+            Self::GrantTokens {
+                property_id,
+                amount,
+                memo,
+            }
+            | Self::RevokeTokens {
+                property_id,
+                amount,
+                memo,
+            } => {
+                payload.extend(property_id.to_be_bytes());
+                payload.extend(amount.to_be_bytes());
+                write_string(&mut payload, memo)?;
+            }
+        }
+        Ok(payload)
+    }
+
+    /// Returns the message of the serialized 'payload', without the "omni" marker
+    pub fn from_bytes(payload: &[u8]) -> Result<Self, TransactionError> {
+        let mut reader = PayloadReader(payload);
+        let version = reader.u16()?;
+        let message_type = reader.u16()?;
+        if version != 0 {
+            return Err(TransactionError::Message(format!(
+                "unsupported omni message version {}",
+                version
+            )));
+        }
+
+        let message = match message_type {
+            0 => Self::SimpleSend {
+                property_id: reader.u32()?,
+                amount: reader.i64()?,
+            },
+            4 => Self::SendAll {
+                ecosystem: OmniEcosystem::from_byte(reader.u8()?)?,
+            },
+            50 => Self::CreatePropertyFixed {
+                property: reader.property()?,
+                amount: reader.i64()?,
+            },
+            54 => Self::CreatePropertyManaged {
+                property: reader.property()?,
+            },
+            55 => Self::GrantTokens {
+                property_id: reader.u32()?,
+                amount: reader.i64()?,
+                memo: reader.string()?,
+            },
+            56 => Self::RevokeTokens {
+                property_id: reader.u32()?,
+                amount: reader.i64()?,
+                memo: reader.string()?,
+            },
+            _ => {
+                return Err(TransactionError::Message(format!(
+                    "unsupported omni message type {}",
+                    message_type
+                )))
+            }
+        };
+
+        if !reader.0.is_empty() {
+            return Err(TransactionError::TrailingBytes);
+        }
+        Ok(message)
+    }
+
+    /// Returns the OP_RETURN script carrying the message
+    pub fn to_script(&self) -> Result<Vec<u8>, TransactionError> {
+        let data = [&OMNI_MARKER[..], &self.to_bytes()?].concat();
+        let mut script = vec![Opcode::OP_RETURN as u8];
+        match data.len() {
+            0..=75 => script.push(data.len() as u8),
+            76..=255 => script.extend([OP_PUSHDATA1, data.len() as u8]),
+            _ => {
+                return Err(TransactionError::Message(format!(
+                    "omni payload of {} bytes is too large",
+                    data.len()
+                )))
+            }
+        }
+        script.extend(data);
+        Ok(script)
+    }
+
+    /// Returns the message carried by an OP_RETURN 'script'
+    pub fn from_script(script: &[u8]) -> Result<Self, TransactionError> {
+        let data = match script {
+            [0x6a, OP_PUSHDATA1, size, data @ ..] if *size as usize == data.len() && *size > 75 => {
+                data
+            }
+            [0x6a, size @ 1..=75, data @ ..] if *size as usize == data.len() => data,
+            _ => {
+                return Err(TransactionError::InvalidScriptPubKey(
+                    "not an omni data output".into(),
+                ))
+            }
+        };
+        match data.strip_prefix(&OMNI_MARKER[..]) {
+            Some(payload) => Self::from_bytes(payload),
+            None => Err(TransactionError::InvalidScriptPubKey(
+                "missing omni marker".into(),
+            )),
+        }
+    }
+}
+
+fn write_string(payload: &mut Vec<u8>, string: &str) -> Result<(), TransactionError> {
+    if string.len() > MAX_STRING_LEN || string.contains('\0') {
+        return Err(TransactionError::Message(format!(
+            "invalid omni string {:?}",
+            string
+        )));
+    }
+    payload.extend(string.as_bytes());
+    payload.push(0);
+    Ok(())
+}
+
+fn write_property(payload: &mut Vec<u8>, property: &OmniProperty) -> Result<(), TransactionError> {
+    payload.push(property.ecosystem as u8);
+    payload.extend((property.property_type as u16).to_be_bytes());
+    payload.extend(property.previous_property_id.to_be_bytes());
+    for string in [
+        &property.category,
+        &property.subcategory,
+        &property.name,
+        &property.url,
+        &property.data,
+    ] {
+        write_string(payload, string)?;
+    }
+    Ok(())
+}
+
+/// A cursor over the fields of an Omni message
+struct PayloadReader<'a>(&'a [u8]);
+
+impl PayloadReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], TransactionError> {
+        if self.0.len() < N {
+            return Err(TransactionError::Message(
+                "unexpected end of omni payload".into(),
+            ));
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, TransactionError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, TransactionError> {
+        Ok(u16::from_be_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, TransactionError> {
+        Ok(u32::from_be_bytes(self.take()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, TransactionError> {
+        Ok(i64::from_be_bytes(self.take()?))
+    }
+
+    /// Reads a null terminated string
+    fn string(&mut self) -> Result<String, TransactionError> {
+        let end = self
+            .0
+            .iter()
+            .position(|byte| *byte == 0)
+            .filter(|end| *end <= MAX_STRING_LEN)
+            .ok_or_else(|| TransactionError::Message("invalid omni string".into()))?;
+        let string = String::from_utf8(self.0[..end].to_vec())
+            .map_err(|_| TransactionError::Message("invalid omni string".into()))?;
+        self.0 = &self.0[end + 1..];
+        Ok(string)
+    }
+
+    fn property(&mut self) -> Result<OmniProperty, TransactionError> {
+        Ok(OmniProperty {
+            ecosystem: OmniEcosystem::from_byte(self.u8()?)?,
+            property_type: OmniPropertyType::from_u16(self.u16()?)?,
+            previous_property_id: self.u32()?,
+            category: self.string()?,
+            subcategory: self.string()?,
+            name: self.string()?,
+            url: self.string()?,
+            data: self.string()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::hex;
+
+    fn property() -> OmniProperty {
+        OmniProperty {
+            ecosystem: OmniEcosystem::Main,
+            property_type: OmniPropertyType::Divisible,
+            previous_property_id: 0,
+            category: "Companies".into(),
+            subcategory: "Bitcoin Mining".into(),
+            name: "Quantum Miner".into(),
+            url: "".into(),
+            data: "".into(),
+        }
+    }
+
+    #[test]
+    fn test_simple_send() {
+        // 1 USDT
+        let payload = OmniPayload::SimpleSend {
+            property_id: 31,
+            amount: 100_000_000,
+        };
+        let script = payload.to_script().unwrap();
+        assert_eq!(
+            "6a146f6d6e69000000000000001f0000000005f5e100",
+            hex::encode(&script)
+        );
+        assert_eq!(payload, OmniPayload::from_script(&script).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payloads = [
+            OmniPayload::SendAll {
+                ecosystem: OmniEcosystem::Main,
+            },
+            OmniPayload::CreatePropertyFixed {
+                property: property(),
+                amount: 1_000_000,
+            },
+            OmniPayload::CreatePropertyManaged {
+                property: property(),
+            },
+            OmniPayload::GrantTokens {
+                property_id: 3,
+                amount: 1_000,
+                memo: "first grant".into(),
+            },
+            OmniPayload::RevokeTokens {
+                property_id: 3,
+                amount: 1_000,
+                memo: "".into(),
+            },
+        ];
+        for payload in payloads {
+            let bytes = payload.to_bytes().unwrap();
+            assert_eq!(payload, OmniPayload::from_bytes(&bytes).unwrap());
+            let script = payload.to_script().unwrap();
+            assert_eq!(payload, OmniPayload::from_script(&script).unwrap());
+        }
+
+        assert_eq!(
+            "0000000401",
+            hex::encode(
+                OmniPayload::SendAll {
+                    ecosystem: OmniEcosystem::Main
+                }
+                .to_bytes()
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            "00000037000000030000000000000064676f00",
+            hex::encode(
+                OmniPayload::GrantTokens {
+                    property_id: 3,
+                    amount: 100,
+                    memo: "go".into(),
+                }
+                .to_bytes()
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(OmniPayload::from_bytes(&hex::decode("0000000401ff").unwrap()).is_err());
+        assert!(OmniPayload::from_bytes(&hex::decode("0000000403").unwrap()).is_err());
+        assert!(OmniPayload::from_bytes(&hex::decode("00000003").unwrap()).is_err());
+        assert!(
+            OmniPayload::from_bytes(&hex::decode("00000037000000030000000000000064").unwrap())
+                .is_err()
+        );
+        assert!(OmniPayload::from_script(&hex::decode("6a0401020304").unwrap()).is_err());
+
+        let mut property = property();
+        property.name = "a".repeat(256);
+        assert!(OmniPayload::CreatePropertyManaged { property }
+            .to_bytes()
+            .is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//!
+//! PayJoin
+//!
+//! This module implements the PSBT handling of BIP78. The receiver turns the
+//! sender's original PSBT into a proposal by contributing inputs and adding
+//! their value to its payment output. The sender checks the proposal before
+//! signing it, so that the receiver cannot take more than the agreed fee or
+//! tamper with the sender's inputs and outputs.
+//!
+
+use crate::{script_type, BitcoinAmount, BitcoinNetwork, Outpoint, Psbt, PsbtInput, PsbtOutput};
+use anychain_core::{no_std::*, AmountError, TransactionError};
+
+/// The parameters the sender passes to the receiver along with the original
+/// PSBT
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayjoinParams {
+    /// The sender output the receiver may take additional fees from
+    pub additional_fee_output_index: Option<usize>,
+    /// The maximum amount in satoshis the sender pays for the receiver inputs
+    pub max_additional_fee_contribution: u64,
+    /// Whether the receiver must keep its payment output script and amount
+    pub disable_output_substitution: bool,
+}
+
+/// An input the receiver adds to the original transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayjoinInput {
+    /// The position of the input in the proposal
+    pub position: usize,
+    /// The output the input spends
+    pub outpoint: Outpoint,
+    /// The input map carrying the utxo information of the spent output
+    pub psbt_input: PsbtInput,
+}
+
+fn error(message: &str) -> TransactionError {
+    TransactionError::Message(format!("payjoin: {}", message))
+}
+
+/// Checks that 'original' is a broadcastable original PSBT: every input is
+/// finalized, carries its utxo and spends the same script type
+pub fn payjoin_check_original<N: BitcoinNetwork>(
+    original: &Psbt<N>,
+) -> Result<(), TransactionError> {
+    if original.inputs.is_empty() {
+        return Err(error("original psbt has no inputs"));
+    }
+    let mut script_types = vec![];
+    for (input, map) in original.unsigned_tx.inputs.iter().zip(&original.inputs) {
+        if !map.is_finalized() {
+            return Err(error("original psbt input is not finalized"));
+        }
+        script_types.push(script_type(&map.utxo(&input.outpoint)?.script_pub_key));
+    }
+    if script_types.iter().any(|t| *t != script_types[0]) {
+        return Err(error("original psbt mixes input script types"));
+    }
+    original.fee()?;
+    Ok(())
+}
+
+/// Returns the payjoin proposal of the receiver for 'original'. The receiver
+/// 'inputs' are inserted at their positions with the sequence of the sender
+/// inputs and their value is added to the output at 'receiver_output'. Up to
+/// the agreed maximum, 'additional_fee' is taken from the sender's additional
+/// fee output. The sender's signatures and key paths are removed, and the
+/// receiver signs and finalizes its own inputs before answering.
+pub fn payjoin_proposal<N: BitcoinNetwork>(
+    original: &Psbt<N>,
+    receiver_output: usize,
+    inputs: Vec<PayjoinInput>,
+    additional_fee: u64,
+    params: &PayjoinParams,
+) -> Result<Psbt<N>, TransactionError> {
+    payjoin_check_original(original)?;
+    if receiver_output >= original.outputs.len() {
+        return Err(error("receiver output does not exist"));
+    }
+
+    let mut proposal = original.clone();
+    proposal.unknown.clear();
+    proposal.inputs = vec![PsbtInput::default(); proposal.inputs.len()];
+    proposal.outputs = vec![PsbtOutput::default(); proposal.outputs.len()];
+
+    let template = original.unsigned_tx.inputs[0].clone();
+    let mut contribution = 0i64;
+    for input in inputs {
+        if input.position > proposal.inputs.len() {
+            return Err(error("receiver input position out of range"));
+        }
+        if original
+            .unsigned_tx
+            .inputs
+            .iter()
+            .any(|i| i.outpoint == input.outpoint)
+        {
+            return Err(error("receiver input is already spent by the sender"));
+        }
+        contribution += input.psbt_input.utxo(&input.outpoint)?.amount.0;
+
+        let mut transaction_input = template.clone();
+        transaction_input.outpoint = input.outpoint;
+        proposal
+            .unsigned_tx
+            .inputs
+            .insert(input.position, transaction_input);
+        proposal.inputs.insert(input.position, input.psbt_input);
+    }
+    proposal.unsigned_tx.outputs[receiver_output].amount.0 += contribution;
+
+    if additional_fee > 0 {
+        if additional_fee > params.max_additional_fee_contribution {
+            return Err(error("additional fee exceeds the sender contribution"));
+        }
+        let output = params
+            .additional_fee_output_index
+            .filter(|index| *index != receiver_output)
+            .and_then(|index| proposal.unsigned_tx.outputs.get_mut(index))
+            .ok_or_else(|| error("no additional fee output"))?;
+        if output.amount.0 < additional_fee as i64 {
+            return Err(error("additional fee output is too small"));
+        }
+        output.amount.0 -= additional_fee as i64;
+    }
+
+    Ok(proposal)
+}
+
+/// Checks the payjoin 'proposal' of the receiver paid at 'payee_script_pub_key'
+/// against the sender's 'original' PSBT and the 'params' sent along with it
+pub fn payjoin_validate_proposal<N: BitcoinNetwork>(
+    original: &Psbt<N>,
+    proposal: &Psbt<N>,
+    payee_script_pub_key: &[u8],
+    params: &PayjoinParams,
+) -> Result<(), TransactionError> {
+    let original_tx = &original.unsigned_tx;
+    let proposal_tx = &proposal.unsigned_tx;
+    if proposal_tx.version != original_tx.version || proposal_tx.lock_time != original_tx.lock_time
+    {
+        return Err(error("version or lock time changed"));
+    }
+    if proposal.unknown.iter().any(|(key, _)| key[0] == 0x01)
+        || proposal
+            .outputs
+            .iter()
+            .any(|o| !o.bip32_derivation.is_empty())
+    {
+        return Err(error("proposal contains key paths"));
+    }
+
+    let mut sender_value = 0i64;
+    let mut sender_inputs = 0;
+    let mut receiver_value = 0i64;
+    for (input, map) in proposal_tx.inputs.iter().zip(&proposal.inputs) {
+        let original_index = original_tx
+            .inputs
+            .iter()
+            .position(|i| i.outpoint == input.outpoint);
+        match original_index {
+            Some(index) => {
+                let original_input = &original_tx.inputs[index];
+                if input.sequence != original_input.sequence {
+                    return Err(error("sender input sequence changed"));
+                }
+                if map.is_finalized() || !map.partial_sigs.is_empty() || map.has_utxo() {
+                    return Err(error("sender input was not cleared"));
+                }
+                sender_value += original.inputs[index]
+                    .utxo(&original_input.outpoint)?
+                    .amount
+                    .0;
+                sender_inputs += 1;
+            }
+            None => {
+                if !map.is_finalized() {
+                    return Err(error("receiver input is not finalized"));
+                }
+                let utxo = map.utxo(&input.outpoint)?;
+                let original_input = &original.inputs[0];
+                let original_utxo = original_input.utxo(&original_tx.inputs[0].outpoint)?;
+                if script_type(&utxo.script_pub_key) != script_type(&original_utxo.script_pub_key) {
+                    return Err(error("receiver input script type differs"));
+                }
+                if original_tx
+                    .inputs
+                    .iter()
+                    .all(|i| i.sequence != input.sequence)
+                {
+                    return Err(error("receiver input sequence differs"));
+                }
+                receiver_value += utxo.amount.0;
+            }
+        }
+    }
+    if sender_inputs != original_tx.inputs.len() {
+        return Err(error("sender input missing"));
+    }
+    if proposal_tx.inputs.len() == sender_inputs {
+        return Err(error("receiver contributed no inputs"));
+    }
+
+    // match the sender outputs of the original against the proposal
+    let mut matched = vec![false; proposal_tx.outputs.len()];
+    let mut original_sender_outputs = 0i64;
+    let mut proposal_sender_outputs = 0i64;
+    for (index, output) in original_tx.outputs.iter().enumerate() {
+        let found = proposal_tx
+            .outputs
+            .iter()
+            .enumerate()
+            .position(|(i, o)| !matched[i] && o.script_pub_key == output.script_pub_key);
+        let payee = output.script_pub_key == payee_script_pub_key;
+
+        match found {
+            Some(i) => {
+                matched[i] = true;
+                let amount = proposal_tx.outputs[i].amount.0;
+                if payee {
+                    if params.disable_output_substitution && amount < output.amount.0 {
+                        return Err(error("payee output decreased"));
+                    }
+                    continue;
+                }
+                if amount != output.amount.0
+                    && (params.additional_fee_output_index != Some(index)
+                        || amount > output.amount.0)
+                {
+                    return Err(error("sender output changed"));
+                }
+                original_sender_outputs += output.amount.0;
+                proposal_sender_outputs += amount;
+            }
+            None if payee && !params.disable_output_substitution => {}
+            None if payee => return Err(error("payee output substituted")),
+            None => return Err(error("sender output missing")),
+        }
+    }
+
+    let contribution = original_sender_outputs - proposal_sender_outputs;
+    if contribution > params.max_additional_fee_contribution as i64 {
+        return Err(error("sender fee contribution exceeds the maximum"));
+    }
+
+    let outputs = proposal_tx.outputs.iter().map(|o| o.amount);
+    let outputs = outputs.sum::<Result<BitcoinAmount, AmountError>>()?;
+    let inputs = BitcoinAmount(sender_value).checked_add(BitcoinAmount(receiver_value))?;
+    let fee = inputs
+        .checked_sub(outputs)
+        .map_err(|_| error("proposal pays a negative fee"))?;
+    if (fee.0 as u64) < original.fee()? {
+        return Err(error("proposal fee is below the original fee"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Bitcoin, BitcoinAmount, BitcoinTransactionInput, BitcoinTransactionOutput,
+        BitcoinTransactionParameters,
+    };
+    use anychain_core::hex;
+
+    const SENDER: &str = "00142b05d564e6a7a33c087f16e0f730d1440123799d";
+    const RECEIVER: &str = "00148b8a08af1d9fb13d48fcb6e8cc34bb8f2bea2e25";
+    const CHANGE: &str = "0014ba8298d3e1b1d1e7b01bdecb4ad1f41c21b0d6f5";
+
+    fn output(script: &str, amount: i64) -> BitcoinTransactionOutput {
+        BitcoinTransactionOutput {
+            amount: BitcoinAmount(amount),
+            script_pub_key: hex::decode(script).unwrap(),
+        }
+    }
+
+    fn input(txid: u8) -> BitcoinTransactionInput<Bitcoin> {
+        let mut raw = vec![txid; 32];
+        raw.extend([0, 0, 0, 0, 0, 0xfd, 0xff, 0xff, 0xff]);
+        BitcoinTransactionInput::read(&mut &raw[..]).unwrap()
+    }
+
+    fn original() -> Psbt<Bitcoin> {
+        let parameters = BitcoinTransactionParameters::new(
+            vec![input(1)],
+            vec![output(RECEIVER, 50_000), output(CHANGE, 49_000)],
+        )
+        .unwrap();
+        let mut psbt = Psbt::new(parameters);
+        psbt.inputs[0].witness_utxo = Some(output(SENDER, 100_000));
+        psbt.inputs[0].final_script_witness = Some(vec![vec![0x30; 71], vec![0x02; 33]]);
+        psbt
+    }
+
+    fn params() -> PayjoinParams {
+        PayjoinParams {
+            additional_fee_output_index: Some(1),
+            max_additional_fee_contribution: 300,
+            disable_output_substitution: false,
+        }
+    }
+
+    fn proposal(additional_fee: u64) -> Result<Psbt<Bitcoin>, TransactionError> {
+        let receiver_input = PayjoinInput {
+            position: 1,
+            outpoint: input(2).outpoint,
+            psbt_input: PsbtInput {
+                witness_utxo: Some(output(RECEIVER, 20_000)),
+                ..Default::default()
+            },
+        };
+        let mut proposal = payjoin_proposal(
+            &original(),
+            0,
+            vec![receiver_input],
+            additional_fee,
+            &params(),
+        )?;
+        // the receiver signs its input
+        proposal.inputs[1].final_script_witness = Some(vec![vec![0x30; 71], vec![0x03; 33]]);
+        Ok(proposal)
+    }
+
+    #[test]
+    fn test_proposal() {
+        let proposal = proposal(200).unwrap();
+        assert_eq!(2, proposal.unsigned_tx.inputs.len());
+        assert_eq!(
+            proposal.unsigned_tx.inputs[0].sequence,
+            proposal.unsigned_tx.inputs[1].sequence
+        );
+        assert_eq!(70_000, proposal.unsigned_tx.outputs[0].amount.0);
+        assert_eq!(48_800, proposal.unsigned_tx.outputs[1].amount.0);
+        assert!(!proposal.inputs[0].is_finalized());
+
+        let payee = hex::decode(RECEIVER).unwrap();
+        payjoin_validate_proposal(&original(), &proposal, &payee, &params()).unwrap();
+
+        assert!(self::proposal(400).is_err());
+    }
+
+    #[test]
+    fn test_invalid_proposal() {
+        let payee = hex::decode(RECEIVER).unwrap();
+        let validate = |proposal: &Psbt<Bitcoin>| {
+            payjoin_validate_proposal(&original(), proposal, &payee, &params())
+        };
+
+        let mut proposal = proposal(200).unwrap();
+        proposal.unsigned_tx.outputs[1].amount.0 -= 200;
+        assert!(validate(&proposal).is_err());
+
+        let mut proposal = self::proposal(0).unwrap();
+        proposal.unsigned_tx.lock_time = 1;
+        assert!(validate(&proposal).is_err());
+
+        let mut proposal = self::proposal(0).unwrap();
+        proposal.inputs[1].final_script_witness = None;
+        assert!(validate(&proposal).is_err());
+
+        let mut proposal = self::proposal(0).unwrap();
+        proposal.inputs[0].witness_utxo = Some(output(SENDER, 100_000));
+        assert!(validate(&proposal).is_err());
+
+        let mut proposal = self::proposal(0).unwrap();
+        proposal.unsigned_tx.inputs[1]
+            .set_sequence(0xffffffff)
+            .unwrap();
+        assert!(validate(&proposal).is_err());
+
+        let mut proposal = self::proposal(0).unwrap();
+        proposal.unsigned_tx.outputs[0].amount.0 = 10_000;
+        let substitution = PayjoinParams {
+            disable_output_substitution: true,
+            ..params()
+        };
+        assert!(validate(&proposal).is_ok());
+        assert!(payjoin_validate_proposal(&original(), &proposal, &payee, &substitution).is_err());
+    }
+}
@@ -0,0 +1,447 @@
+//!
+//! Mempool Policy
+//!
+//! This module implements relay policy checks of Bitcoin Core that go beyond
+//! consensus, so that a transaction can be checked before it is broadcast:
+//! dust thresholds, version 3 (TRUC) transactions (BIP431), pay-to-anchor
+//! (P2A) outputs carrying ephemeral dust, and the standardness rules a node
+//! applies before it relays a transaction at all.
+//!
+
+use crate::{
+    is_high_s_signature, is_minimally_pushed, is_push_only, multisig_threshold, parse_script,
+    script_type, witness_item_data, BitcoinAmount, BitcoinNetwork, BitcoinTransaction,
+    BitcoinTransactionInput, BitcoinTransactionOutput, BitcoinTransactionParameters, FeeRate,
+    MAX_STANDARD_TX_SIGOPS_COST,
+};
+use anychain_core::{no_std::*, TransactionError};
+
+/// The version of topologically restricted until confirmation transactions
+pub const TRUC_VERSION: u32 = 3;
+
+/// The maximum virtual size of a TRUC transaction
+pub const TRUC_MAX_VSIZE: usize = 10_000;
+
+/// The maximum virtual size of a TRUC transaction with an unconfirmed parent
+pub const TRUC_CHILD_MAX_VSIZE: usize = 1_000;
+
+/// The pay-to-anchor scriptPubKey `OP_1 <0x4e73>`, spendable by anyone with
+/// an empty witness
+pub const P2A_SCRIPT_PUB_KEY: [u8; 4] = [0x51, 0x02, 0x4e, 0x73];
+
+/// The fee rate dust thresholds are computed with, 3 sat/vB
+pub const DUST_RELAY_FEE: FeeRate = FeeRate::from_sat_per_kvb(3_000);
+
+/// The maximum weight of a transaction relayed by Bitcoin Core
+pub const MAX_STANDARD_TX_WEIGHT: usize = 400_000;
+
+/// The maximum size of a standard scriptSig, enough for a 15-of-15 multisig
+/// spent through P2SH
+pub const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1_650;
+
+/// The default maximum size of the scriptPubKeys of the OP_RETURN outputs of
+/// a transaction, `-datacarriersize` of Bitcoin Core
+pub const MAX_OP_RETURN_RELAY: usize = 83;
+
+/// The maximum number of keys of a standard bare multisig output
+pub const MAX_STANDARD_MULTISIG_KEYS: usize = 3;
+
+/// The relay options of a node that standardness depends on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StandardnessPolicy {
+    /// The maximum total size of the OP_RETURN scriptPubKeys, `None` if the
+    /// node does not relay data carrier outputs (`-datacarrier=0`)
+    pub max_datacarrier_size: Option<usize>,
+    /// True if the node relays bare multisig outputs (`-permitbaremultisig`)
+    pub permit_bare_multisig: bool,
+}
+
+impl Default for StandardnessPolicy {
+    fn default() -> Self {
+        Self {
+            max_datacarrier_size: Some(MAX_OP_RETURN_RELAY),
+            permit_bare_multisig: true,
+        }
+    }
+}
+
+/// Returns the smallest amount an output paying to 'script_pub_key' may carry
+/// without being dust: the value below which spending it costs more than a
+/// third of it at the dust relay fee rate. Unspendable outputs are never dust.
+pub fn dust_threshold(script_pub_key: &[u8]) -> u64 {
+    if script_pub_key.first() == Some(&0x6a) {
+        return 0;
+    }
+    let output_size =
+        8 + crate::variable_length_integer_size(script_pub_key.len() as u64) + script_pub_key.len();
+    // the size of the input spending the output, witness data discounted
+    let input_size = match script_pub_key {
+        [0x00 | 0x51..=0x60, size, program @ ..] if *size as usize == program.len() => {
+            32 + 4 + 1 + 107 / 4 + 4
+        }
+        _ => 32 + 4 + 1 + 107 + 4,
+    };
+    (output_size + input_size) as u64 * DUST_RELAY_FEE.to_sat_per_kvb() / 1000
+}
+
+impl BitcoinTransactionOutput {
+    /// Returns a pay-to-anchor output of 'amount', usually zero
+    pub fn anchor(amount: BitcoinAmount) -> Self {
+        Self {
+            amount,
+            script_pub_key: P2A_SCRIPT_PUB_KEY.to_vec(),
+        }
+    }
+
+    /// Returns true if the output pays to the pay-to-anchor script
+    pub fn is_anchor(&self) -> bool {
+        self.script_pub_key == P2A_SCRIPT_PUB_KEY
+    }
+
+    /// Returns true if the output carries less than its dust threshold
+    pub fn is_dust(&self) -> bool {
+        (self.amount.0 as u64) < dust_threshold(&self.script_pub_key)
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
+    /// Returns the parameters of a version 3 (TRUC) transaction
+    pub fn new_truc(
+        inputs: Vec<BitcoinTransactionInput<N>>,
+        outputs: Vec<BitcoinTransactionOutput>,
+    ) -> Result<Self, TransactionError> {
+        Self::new(inputs, outputs)?.with_version(TRUC_VERSION)
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Returns true if the transaction opts into the TRUC relay policy
+    pub fn is_truc(&self) -> bool {
+        self.parameters.version == TRUC_VERSION
+    }
+
+    /// Checks the TRUC rules of the transaction paying 'fee', given the
+    /// transactions it spends from that are still unconfirmed. A TRUC
+    /// transaction is limited to 10,000 vB and to a single unconfirmed parent,
+    /// which must be TRUC as well and then limits the child to 1,000 vB. A
+    /// non-TRUC transaction may not spend an unconfirmed TRUC transaction.
+    /// Only a TRUC transaction may pay no fee, to be relayed as the parent of
+    /// a package whose child pays for both.
+    pub fn check_truc(
+        &self,
+        fee: u64,
+        unconfirmed_parents: &[BitcoinTransaction<N>],
+    ) -> Result<(), TransactionError> {
+        let violation =
+            |message: &str| Err(TransactionError::Message(format!("truc: {}", message)));
+
+        if !self.is_truc() {
+            if unconfirmed_parents.iter().any(|parent| parent.is_truc()) {
+                return violation("non-truc transaction spends an unconfirmed truc transaction");
+            }
+            if fee == 0 {
+                return violation("only truc transactions may pay no fee");
+            }
+            return Ok(());
+        }
+
+        let vsize = self.vsize()?;
+        if vsize > TRUC_MAX_VSIZE {
+            return Err(TransactionError::LimitExceeded(
+                "truc vsize".into(),
+                TRUC_MAX_VSIZE,
+                vsize,
+            ));
+        }
+        match unconfirmed_parents {
+            [] => Ok(()),
+            [parent] if !parent.is_truc() => {
+                violation("truc transaction spends an unconfirmed non-truc transaction")
+            }
+            [_] if vsize > TRUC_CHILD_MAX_VSIZE => Err(TransactionError::LimitExceeded(
+                "truc child vsize".into(),
+                TRUC_CHILD_MAX_VSIZE,
+                vsize,
+            )),
+            [_] => Ok(()),
+            _ => violation("truc transaction has more than one unconfirmed parent"),
+        }
+    }
+
+    /// Checks the ephemeral dust rule: a transaction may create a single dust
+    /// output, typically a zero value anchor, only if it pays no fee, so that
+    /// a child spending the dust output is needed to get it mined
+    pub fn check_ephemeral_dust(&self, fee: u64) -> Result<(), TransactionError> {
+        let dust = self
+            .parameters
+            .outputs
+            .iter()
+            .filter(|output| output.is_dust())
+            .count();
+        match (dust, fee) {
+            (0, _) | (1, 0) => Ok(()),
+            (1, _) => Err(TransactionError::Message(
+                "a transaction with a dust output must pay no fee".into(),
+            )),
+            _ => Err(TransactionError::Message(
+                "a transaction may have at most one dust output".into(),
+            )),
+        }
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Checks that a node with the default relay options accepts the
+    /// transaction as standard, see [`Self::check_standard_with`]
+    pub fn check_standard(&self) -> Result<(), TransactionError> {
+        self.check_standard_with(&StandardnessPolicy::default())
+    }
+
+    /// Checks the standardness rules of Bitcoin Core under 'policy', so that
+    /// a transaction a node would refuse to relay is caught before it is
+    /// broadcast. The transaction must have version 1 to 3, weigh at most
+    /// 400,000 WU and have a sigop cost of at most 16,000. Its scriptSigs must be push-only, minimally pushed and at
+    /// most 1,650 bytes, and its ECDSA signatures must have a low S value.
+    /// Its outputs must pay to standard templates, bare multisig with at most
+    /// 3 keys if the node permits it, with data carrier outputs within the
+    /// data carrier size and at most one dust output. Errors carry the reject reason of the node.
+    pub fn check_standard_with(&self, policy: &StandardnessPolicy) -> Result<(), TransactionError> {
+        let non_standard = |reason: &str| {
+            Err(TransactionError::Message(format!(
+                "non-standard: {}",
+                reason
+            )))
+        };
+
+        if !(1..=TRUC_VERSION).contains(&self.parameters.version) {
+            return non_standard("version");
+        }
+        let weight = self.weight()?;
+        if weight > MAX_STANDARD_TX_WEIGHT {
+            return Err(TransactionError::LimitExceeded(
+                "tx-size".into(),
+                MAX_STANDARD_TX_WEIGHT,
+                weight,
+            ));
+        }
+        let sigop_cost = self.sigop_cost();
+        if sigop_cost > MAX_STANDARD_TX_SIGOPS_COST {
+            return Err(TransactionError::LimitExceeded(
+                "bad-txns-too-many-sigops".into(),
+                MAX_STANDARD_TX_SIGOPS_COST,
+                sigop_cost,
+            ));
+        }
+
+        for input in &self.parameters.inputs {
+            let script_sig = &input.script_sig;
+            if script_sig.len() > MAX_STANDARD_SCRIPTSIG_SIZE {
+                return Err(TransactionError::LimitExceeded(
+                    "scriptsig-size".into(),
+                    MAX_STANDARD_SCRIPTSIG_SIZE,
+                    script_sig.len(),
+                ));
+            }
+            if !is_push_only(script_sig) {
+                return non_standard("scriptsig-not-pushonly");
+            }
+            if !is_minimally_pushed(script_sig) {
+                return non_standard(
+                    "non-mandatory-script-verify-flag (Data push larger than necessary)",
+                );
+            }
+            let high_s = parse_script(script_sig)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|instruction| instruction.data)
+                .chain(input.witnesses.iter().map(|item| witness_item_data(item)))
+                .any(is_high_s_signature);
+            if high_s {
+                return non_standard("non-mandatory-script-verify-flag (Non-canonical signature: S value is unnecessarily high)");
+            }
+        }
+
+        let mut data_carrier_size = None;
+        for output in &self.parameters.outputs {
+            let script_pub_key = &output.script_pub_key;
+            match script_type(script_pub_key) {
+                "nonstandard" => return non_standard("scriptpubkey"),
+                "nulldata" => *data_carrier_size.get_or_insert(0) += script_pub_key.len(),
+                "multisig" => match multisig_threshold(script_pub_key) {
+                    Some((_, n)) if n > MAX_STANDARD_MULTISIG_KEYS => {
+                        return non_standard("scriptpubkey")
+                    }
+                    _ if !policy.permit_bare_multisig => return non_standard("bare-multisig"),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        match (data_carrier_size, policy.max_datacarrier_size) {
+            (None, _) => {}
+            (Some(size), Some(max)) if size <= max => {}
+            _ => return non_standard("datacarrier"),
+        }
+
+        let dust = self
+            .parameters
+            .outputs
+            .iter()
+            .filter(|output| output.is_dust())
+            .count();
+        if dust > 1 {
+            return non_standard("dust");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinAddress};
+    use anychain_core::{hex, Transaction};
+
+    // a signed P2WPKH spend taken from BIP143
+    const RAW: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    fn transaction(version: u32) -> BitcoinTransaction<Bitcoin> {
+        let mut transaction =
+            BitcoinTransaction::<Bitcoin>::from_bytes(&hex::decode(RAW).unwrap()).unwrap();
+        transaction.parameters.version = version;
+        transaction
+    }
+
+    #[test]
+    fn test_dust_threshold() {
+        let script = |hex: &str| hex::decode(hex).unwrap();
+        assert_eq!(
+            546,
+            dust_threshold(&script(
+                "76a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac"
+            ))
+        );
+        assert_eq!(
+            294,
+            dust_threshold(&script("00142b05d564e6a7a33c087f16e0f730d1440123799d"))
+        );
+        assert_eq!(240, dust_threshold(&P2A_SCRIPT_PUB_KEY));
+        assert_eq!(0, dust_threshold(&script("6a0474657374")));
+    }
+
+    #[test]
+    fn test_anchor() {
+        let anchor = BitcoinTransactionOutput::anchor(BitcoinAmount(0));
+        assert!(anchor.is_anchor() && anchor.is_dust());
+        assert_eq!(
+            "bc1pfeessrawgf",
+            BitcoinAddress::<Bitcoin>::from_script_pub_key(&anchor.script_pub_key)
+                .unwrap()
+                .to_string()
+        );
+
+        let mut transaction = transaction(TRUC_VERSION);
+        transaction.parameters.outputs.push(anchor.clone());
+        assert!(transaction.check_ephemeral_dust(0).is_ok());
+        assert!(transaction.check_ephemeral_dust(1).is_err());
+
+        transaction.parameters.outputs.push(anchor);
+        assert!(transaction.check_ephemeral_dust(0).is_err());
+    }
+
+    #[test]
+    fn test_truc() {
+        let parameters = BitcoinTransactionParameters::<Bitcoin>::new_truc(vec![], vec![]).unwrap();
+        assert_eq!(TRUC_VERSION, parameters.version);
+
+        let parent = transaction(TRUC_VERSION);
+        let child = transaction(TRUC_VERSION);
+        assert!(child.vsize().unwrap() < TRUC_CHILD_MAX_VSIZE);
+        assert!(parent.check_truc(0, &[]).is_ok());
+        assert!(child
+            .check_truc(1000, core::slice::from_ref(&parent))
+            .is_ok());
+        assert!(child
+            .check_truc(1000, &[parent.clone(), parent.clone()])
+            .is_err());
+        assert!(child.check_truc(1000, &[transaction(2)]).is_err());
+
+        let legacy = transaction(2);
+        assert!(legacy.check_truc(1000, &[]).is_ok());
+        assert!(legacy.check_truc(0, &[]).is_err());
+        assert!(legacy.check_truc(1000, &[parent]).is_err());
+
+        let mut large = transaction(TRUC_VERSION);
+        large.parameters.outputs[0].script_pub_key = vec![0x6a; 1_000];
+        assert!(large.check_truc(1000, &[]).is_ok());
+        assert!(large
+            .check_truc(1000, &[transaction(TRUC_VERSION)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_standard() {
+        assert!(transaction(2).check_standard().is_ok());
+        assert!(transaction(4).check_standard().is_err());
+
+        let mut sigops = transaction(2);
+        sigops.parameters.outputs[1].script_pub_key = vec![0xac; 3_999];
+        assert!(sigops.check_standard().is_err());
+
+        let mut push = transaction(2);
+        push.parameters.inputs[0].script_sig = vec![0x51, 0x87];
+        assert!(push.check_standard().is_err());
+        push.parameters.inputs[0].script_sig = vec![0x01, 0x01];
+        assert!(push.check_standard().is_err());
+        push.parameters.inputs[0].script_sig = crate::push_data(&[0x11; 1_700]);
+        assert!(push.check_standard().is_err());
+
+        // the same witness signature with its S value negated
+        let mut high_s = transaction(2);
+        let witness = &mut high_s.parameters.inputs[1].witnesses[0];
+        let mut signature =
+            anychain_core::libsecp256k1::Signature::parse_der(&witness[1..witness.len() - 1])
+                .unwrap();
+        signature.s = -signature.s;
+        let mut item = signature.serialize_der().as_ref().to_vec();
+        item.push(0x01);
+        item.insert(0, item.len() as u8);
+        *witness = item;
+        assert!(high_s.check_standard().is_err());
+
+        let mut data = transaction(2);
+        data.parameters.outputs[1].amount = BitcoinAmount(0);
+        data.parameters.outputs[1].script_pub_key = [vec![0x6a, 0x4c, 80], vec![0x11; 80]].concat();
+        assert!(data.check_standard().is_ok());
+        data.parameters.outputs[1].script_pub_key.push(0x11);
+        data.parameters.outputs[1].script_pub_key[2] = 81;
+        assert!(data.check_standard().is_err());
+        let policy = StandardnessPolicy {
+            max_datacarrier_size: None,
+            ..Default::default()
+        };
+        assert!(transaction(2).check_standard_with(&policy).is_ok());
+        assert!(data.check_standard_with(&policy).is_err());
+
+        let mut multisig = transaction(2);
+        multisig.parameters.outputs[1].script_pub_key = hex::decode(concat!(
+            "51",
+            "21039ed714bf521e96e3f3609b74da898e44d0fb64ba68c62c57852470ffc28e3db5",
+            "2103a385ac59a31841764d55e7c8a243482a89073785524f0c45335afcf425d567b1",
+            "52ae"
+        ))
+        .unwrap();
+        assert!(multisig.check_standard().is_ok());
+        let policy = StandardnessPolicy {
+            permit_bare_multisig: false,
+            ..Default::default()
+        };
+        assert!(multisig.check_standard_with(&policy).is_err());
+
+        let mut dust = transaction(2);
+        dust.parameters.outputs[0].amount = BitcoinAmount(1);
+        assert!(dust.check_standard().is_ok());
+        dust.parameters.outputs[1].amount = BitcoinAmount(1);
+        assert!(dust.check_standard().is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{BitcoinAddress, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, BitcoinTransaction};
+use anychain_core::no_std::*;
+use anychain_core::{
+    crypto::{checksum, sign_deterministic},
+    hex,
+    libsecp256k1::{self, Message, SecretKey},
+    zeroize::Zeroizing,
+    Address, AddressError, PrivateKey, PrivateKeyError, TransactionError,
+};
+use core::{fmt, marker::PhantomData, str::FromStr};
+
+/// Represents a Bitcoin private key, wiped from memory when dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinPrivateKey<N: BitcoinNetwork> {
+    /// The ECDSA secret key
+    secret_key: SecretKey,
+    /// If true, the public key is serialized in compressed form
+    compressed: bool,
+    /// PhantomData
+    _network: PhantomData<N>,
+}
+
+impl<N: BitcoinNetwork> PrivateKey for BitcoinPrivateKey<N> {
+    type SecretKey = SecretKey;
+    type PublicKey = BitcoinPublicKey<N>;
+    type Address = BitcoinAddress<N>;
+    type Format = BitcoinFormat;
+
+    /// Returns a private key with a compressed public key given a secret key.
+    fn from_secret_key(secret_key: &Self::SecretKey) -> Self {
+        Self::from_secp256k1_secret_key(secret_key, true)
+    }
+
+    /// Returns the public key of the corresponding private key.
+    fn to_public_key(&self) -> Self::PublicKey {
+        BitcoinPublicKey::from_secp256k1_public_key(
+            libsecp256k1::PublicKey::from_secret_key(&self.secret_key),
+            self.compressed,
+        )
+    }
+
+    /// Returns the address of the corresponding private key.
+    fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError> {
+        BitcoinAddress::from_public_key(&self.to_public_key(), format)
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinPrivateKey<N> {
+    /// Returns a private key given a secp256k1 secret key.
+    pub fn from_secp256k1_secret_key(secret_key: &SecretKey, compressed: bool) -> Self {
+        Self {
+            secret_key: *secret_key,
+            compressed,
+            _network: PhantomData,
+        }
+    }
+
+    /// Returns the secp256k1 secret key of the private key.
+    pub fn to_secp256k1_secret_key(&self) -> SecretKey {
+        self.secret_key
+    }
+
+    /// Returns `true` if the public key is in compressed form.
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Returns the private key in wallet import format.
+    pub fn to_wif(&self) -> String {
+        let mut wif = Zeroizing::new(vec![N::WIF_VERSION]);
+        wif.extend(self.secret_key.serialize());
+        if self.compressed {
+            wif.push(0x01);
+        }
+        let checksum = checksum(&wif);
+        wif.extend(&checksum[..4]);
+        bs58::encode(&*wif).into_string()
+    }
+
+    /// Signs input 'vin' of 'transaction' with the private key. The input must
+    /// pay to the address of the private key in the format of the input.
+    pub fn sign_transaction_input(
+        &self,
+        transaction: &mut BitcoinTransaction<N>,
+        vin: u32,
+    ) -> Result<(), TransactionError> {
+        let public_key = self.to_public_key();
+        let input = transaction.input(vin)?;
+        let format = input.get_format().ok_or(TransactionError::MissingFormat)?;
+        let address = self.to_address(&format)?;
+        if let Some(expected) = input.get_address() {
+            if expected != address {
+                return Err(TransactionError::Message(format!(
+                    "input {} pays to {}, not to {}",
+                    vin, expected, address
+                )));
+            }
+        }
+        if format == BitcoinFormat::P2SH_P2WPKH && input.redeem_script.is_none() {
+            input.redeem_script =
+                Some(BitcoinAddress::<N>::create_redeem_script(&public_key).to_vec());
+        }
+
+        let digest = transaction.digest(vin)?;
+        let (signature, _) = sign_deterministic(&Message::parse_slice(&digest)?, &self.secret_key);
+        transaction
+            .input(vin)?
+            .sign(signature.serialize().to_vec(), public_key.serialize())?;
+        transaction.set_segwit()
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for BitcoinPrivateKey<N> {
+    type Err = PrivateKeyError;
+
+    /// Returns a private key given a private key in wallet import format.
+    fn from_str(wif: &str) -> Result<Self, Self::Err> {
+        let data = Zeroizing::new(bs58::decode(wif).into_vec()?);
+        let compressed = match data.len() {
+            37 => false,
+            38 if data[33] == 0x01 => true,
+            length => return Err(PrivateKeyError::InvalidByteLength(length)),
+        };
+
+        let (payload, checksum_provided) = data.split_at(data.len() - 4);
+        let checksum_gen = &checksum(payload)[..4];
+        if checksum_gen != checksum_provided {
+            return Err(PrivateKeyError::InvalidChecksum(
+                hex::encode(checksum_gen),
+                hex::encode(checksum_provided),
+            ));
+        }
+
+        if data[0] != N::WIF_VERSION {
+            return Err(PrivateKeyError::InvalidPrefix(format!(
+                "{:#04x} for {} network",
+                data[0],
+                N::NAME
+            )));
+        }
+
+        Ok(Self::from_secp256k1_secret_key(
+            &SecretKey::parse_slice(&data[1..33])?,
+            compressed,
+        ))
+    }
+}
+
+impl<N: BitcoinNetwork> Drop for BitcoinPrivateKey<N> {
+    fn drop(&mut self) {
+        self.secret_key.clear();
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for BitcoinPrivateKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_wif())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        network::*, BitcoinAmount, BitcoinTransactionInput, BitcoinTransactionOutput,
+        BitcoinTransactionParameters, SignatureHash,
+    };
+    use anychain_core::{hex, Transaction};
+
+    const SECRET_KEY: &str = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+
+    fn test_wif<N: BitcoinNetwork>(wif: &str, compressed: bool) {
+        let private_key = BitcoinPrivateKey::<N>::from_str(wif).unwrap();
+        assert_eq!(
+            SECRET_KEY,
+            hex::encode(private_key.to_secp256k1_secret_key().serialize())
+        );
+        assert_eq!(compressed, private_key.is_compressed());
+        assert_eq!(compressed, private_key.to_public_key().is_compressed());
+        assert_eq!(wif, private_key.to_string());
+    }
+
+    #[test]
+    fn test_from_str() {
+        test_wif::<Bitcoin>("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ", false);
+        test_wif::<Bitcoin>("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617", true);
+        test_wif::<BitcoinTestnet>("cMzLdeGd5vEqxB8B6VFQoRopQ3sLAAvEzDAoQgvX54xwofSWj1fx", true);
+        test_wif::<Dogecoin>("QP2GKa5kuU2i2G3xJMH5KL9NErbVYGxMoRiF5trrJJvHzrJ2Ebp7", true);
+
+        // wrong network
+        assert!(BitcoinPrivateKey::<BitcoinTestnet>::from_str(
+            "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617"
+        )
+        .is_err());
+        // invalid checksum
+        assert!(BitcoinPrivateKey::<Bitcoin>::from_str(
+            "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98618"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_to_address() {
+        let private_key = BitcoinPrivateKey::<Bitcoin>::from_str(
+            "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+        )
+        .unwrap();
+        assert_eq!(
+            "1GAehh7TsJAHuUAeKZcXf5CnwuGuGgyX2S",
+            private_key
+                .to_address(&BitcoinFormat::P2PKH)
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_sign_transaction_input() {
+        type N = Bitcoin;
+
+        let private_key = BitcoinPrivateKey::<N>::from_str(
+            "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617",
+        )
+        .unwrap();
+        let public_key = private_key.to_public_key().to_secp256k1_public_key();
+
+        for format in [
+            BitcoinFormat::P2PKH,
+            BitcoinFormat::P2SH_P2WPKH,
+            BitcoinFormat::Bech32,
+        ] {
+            let input = BitcoinTransactionInput::<N>::new(
+                [0x11; 32].to_vec(),
+                0,
+                Some(private_key.to_public_key()),
+                Some(format.clone()),
+                None,
+                Some(BitcoinAmount(100000)),
+                SignatureHash::SIGHASH_ALL,
+            )
+            .unwrap();
+            let output = BitcoinTransactionOutput::new(
+                private_key.to_address(&BitcoinFormat::Bech32).unwrap(),
+                BitcoinAmount(90000),
+            )
+            .unwrap();
+            let parameters =
+                BitcoinTransactionParameters::<N>::new(vec![input], vec![output]).unwrap();
+            let mut transaction = BitcoinTransaction::<N>::new(&parameters).unwrap();
+
+            let digest = transaction.digest(0).unwrap();
+            private_key
+                .sign_transaction_input(&mut transaction, 0)
+                .unwrap();
+
+            let input = &transaction.parameters.inputs[0];
+            assert!(input.is_signed);
+            let signature = match format {
+                BitcoinFormat::P2PKH => input.script_sig[1..input.script_sig[0] as usize].to_vec(),
+                _ => input.witnesses[0][1..input.witnesses[0].len() - 1].to_vec(),
+            };
+            let signature = libsecp256k1::Signature::parse_der(&signature).unwrap();
+            assert!(libsecp256k1::verify(
+                &Message::parse_slice(&digest).unwrap(),
+                &signature,
+                &public_key
+            ));
+            assert_eq!(
+                format != BitcoinFormat::P2PKH,
+                transaction.parameters.segwit_flag
+            );
+        }
+
+        let other =
+            BitcoinPrivateKey::<N>::from_secret_key(&SecretKey::parse(&[0x11; 32]).unwrap());
+        let input = BitcoinTransactionInput::<N>::new(
+            [0x11; 32].to_vec(),
+            0,
+            None,
+            None,
+            Some(other.to_address(&BitcoinFormat::P2PKH).unwrap()),
+            None,
+            SignatureHash::SIGHASH_ALL,
+        )
+        .unwrap();
+        let parameters = BitcoinTransactionParameters::<N>::new(vec![input], vec![]).unwrap();
+        let mut transaction = BitcoinTransaction::<N>::new(&parameters).unwrap();
+        assert!(private_key
+            .sign_transaction_input(&mut transaction, 0)
+            .is_err());
+    }
+}
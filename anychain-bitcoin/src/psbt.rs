@@ -0,0 +1,481 @@
+//!
+//! Partially Signed Bitcoin Transactions
+//!
+//! This module implements the version 0 PSBT format of BIP174: the unsigned
+//! transaction plus one key-value map per input and output. The fields needed
+//! to sign and finalize an input are typed, everything else is kept verbatim
+//! so that a round trip never drops data.
+//!
+
+use crate::message::{base64_decode, base64_encode};
+use crate::{
+    BitcoinAmount, BitcoinNetwork, BitcoinTransactionOutput, BitcoinTransactionParameters,
+    BitcoinTransactionRef, DecodeLimits, Outpoint, SliceReader, Txid,
+};
+use anychain_core::{no_std::*, AmountError, TransactionError};
+use core::{fmt, marker::PhantomData, str::FromStr};
+
+/// The magic bytes every PSBT starts with
+pub const PSBT_MAGIC: [u8; 5] = *b"psbt\xff";
+
+/// An unparsed key-value pair, the key including its type byte
+pub type PsbtPair = (Vec<u8>, Vec<u8>);
+
+/// Represents the map of a PSBT input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtInput {
+    /// The full transaction creating the spent output
+    pub non_witness_utxo: Option<Vec<u8>>,
+    /// The spent output, for segwit inputs
+    pub witness_utxo: Option<BitcoinTransactionOutput>,
+    /// The signatures collected so far, keyed by public key
+    pub partial_sigs: Vec<PsbtPair>,
+    /// The sighash type signatures must use
+    pub sighash_type: Option<u32>,
+    /// The redeem script of a P2SH input
+    pub redeem_script: Option<Vec<u8>>,
+    /// The witness script of a P2WSH input
+    pub witness_script: Option<Vec<u8>>,
+    /// The key origins of the signing keys, keyed by public key
+    pub bip32_derivation: Vec<PsbtPair>,
+    /// The finalized scriptSig
+    pub final_script_sig: Option<Vec<u8>>,
+    /// The finalized witness stack
+    pub final_script_witness: Option<Vec<Vec<u8>>>,
+    /// Any other pairs of the map
+    pub unknown: Vec<PsbtPair>,
+}
+
+impl PsbtInput {
+    /// Returns true if the input carries its final scriptSig or witness
+    pub fn is_finalized(&self) -> bool {
+        self.final_script_sig.is_some() || self.final_script_witness.is_some()
+    }
+
+    /// Returns true if the input carries the output it spends
+    pub fn has_utxo(&self) -> bool {
+        self.non_witness_utxo.is_some() || self.witness_utxo.is_some()
+    }
+
+    /// Returns the output spent at 'outpoint', taken from the witness utxo or
+    /// looked up in the previous transaction after checking its txid
+    pub fn utxo(&self, outpoint: &Outpoint) -> Result<BitcoinTransactionOutput, TransactionError> {
+        if let Some(output) = &self.witness_utxo {
+            return Ok(output.clone());
+        }
+        let previous = self
+            .non_witness_utxo
+            .as_ref()
+            .ok_or_else(|| TransactionError::Message("missing utxo information".into()))?;
+        let previous = BitcoinTransactionRef::parse(previous)?;
+
+        if Txid::from_bytes_rpc(previous.txid()) != outpoint.txid {
+            return Err(TransactionError::Message(
+                "non witness utxo does not match the outpoint".into(),
+            ));
+        }
+        let output = previous
+            .outputs
+            .get(outpoint.index as usize)
+            .ok_or_else(|| TransactionError::Message("spent output does not exist".into()))?;
+        Ok(BitcoinTransactionOutput {
+            amount: crate::BitcoinAmount(output.amount as i64),
+            script_pub_key: output.script_pub_key.to_vec(),
+        })
+    }
+
+    /// Removes the signatures and final fields, keeping the utxo information
+    pub fn clear_signatures(&mut self) {
+        self.partial_sigs.clear();
+        self.final_script_sig = None;
+        self.final_script_witness = None;
+    }
+
+    fn read(reader: &mut SliceReader) -> Result<Self, TransactionError> {
+        let mut input = Self::default();
+        for (key, value) in read_map(reader)? {
+            match (key[0], key.len()) {
+                (0x00, 1) => input.non_witness_utxo = Some(value),
+                (0x01, 1) => {
+                    input.witness_utxo = Some(BitcoinTransactionOutput::read(&mut &value[..])?)
+                }
+                (0x02, _) => input.partial_sigs.push((key[1..].to_vec(), value)),
+                (0x03, 1) if value.len() == 4 => {
+                    input.sighash_type = Some(u32::from_le_bytes(value[..].try_into().unwrap()))
+                }
+                (0x04, 1) => input.redeem_script = Some(value),
+                (0x05, 1) => input.witness_script = Some(value),
+                (0x06, _) => input.bip32_derivation.push((key[1..].to_vec(), value)),
+                (0x07, 1) => input.final_script_sig = Some(value),
+                (0x08, 1) => {
+                    let mut reader = SliceReader {
+                        bytes: &value,
+                        position: 0,
+                    };
+                    let count = reader.count(value.len(), "witness items")?;
+                    let mut witness = vec![];
+                    for _ in 0..count {
+                        witness.push(reader.bytes(value.len(), "witness item")?.to_vec());
+                    }
+                    input.final_script_witness = Some(witness);
+                }
+                (0x00..=0x08, _) => {
+                    return Err(TransactionError::Message(format!(
+                        "invalid psbt input key type {}",
+                        key[0]
+                    )))
+                }
+                _ => input.unknown.push((key, value)),
+            }
+        }
+        Ok(input)
+    }
+
+    fn write(&self, psbt: &mut Vec<u8>) -> Result<(), TransactionError> {
+        if let Some(transaction) = &self.non_witness_utxo {
+            write_pair(psbt, &[0x00], transaction);
+        }
+        if let Some(output) = &self.witness_utxo {
+            write_pair(psbt, &[0x01], &output.serialize()?);
+        }
+        for (public_key, signature) in &self.partial_sigs {
+            write_pair(psbt, &[&[0x02], &public_key[..]].concat(), signature);
+        }
+        if let Some(sighash_type) = self.sighash_type {
+            write_pair(psbt, &[0x03], &sighash_type.to_le_bytes());
+        }
+        if let Some(script) = &self.redeem_script {
+            write_pair(psbt, &[0x04], script);
+        }
+        if let Some(script) = &self.witness_script {
+            write_pair(psbt, &[0x05], script);
+        }
+        for (public_key, origin) in &self.bip32_derivation {
+            write_pair(psbt, &[&[0x06], &public_key[..]].concat(), origin);
+        }
+        if let Some(script) = &self.final_script_sig {
+            write_pair(psbt, &[0x07], script);
+        }
+        if let Some(witness) = &self.final_script_witness {
+            let mut stack = compact_size(witness.len());
+            for item in witness {
+                stack.extend(compact_size(item.len()));
+                stack.extend(item);
+            }
+            write_pair(psbt, &[0x08], &stack);
+        }
+        for (key, value) in &self.unknown {
+            write_pair(psbt, key, value);
+        }
+        psbt.push(0x00);
+        Ok(())
+    }
+}
+
+/// Represents the map of a PSBT output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtOutput {
+    /// The redeem script of a P2SH output
+    pub redeem_script: Option<Vec<u8>>,
+    /// The witness script of a P2WSH output
+    pub witness_script: Option<Vec<u8>>,
+    /// The key origins of the output keys, keyed by public key
+    pub bip32_derivation: Vec<PsbtPair>,
+    /// Any other pairs of the map
+    pub unknown: Vec<PsbtPair>,
+}
+
+impl PsbtOutput {
+    fn read(reader: &mut SliceReader) -> Result<Self, TransactionError> {
+        let mut output = Self::default();
+        for (key, value) in read_map(reader)? {
+            match (key[0], key.len()) {
+                (0x00, 1) => output.redeem_script = Some(value),
+                (0x01, 1) => output.witness_script = Some(value),
+                (0x02, _) => output.bip32_derivation.push((key[1..].to_vec(), value)),
+                (0x00..=0x02, _) => {
+                    return Err(TransactionError::Message(format!(
+                        "invalid psbt output key type {}",
+                        key[0]
+                    )))
+                }
+                _ => output.unknown.push((key, value)),
+            }
+        }
+        Ok(output)
+    }
+
+    fn write(&self, psbt: &mut Vec<u8>) {
+        if let Some(script) = &self.redeem_script {
+            write_pair(psbt, &[0x00], script);
+        }
+        if let Some(script) = &self.witness_script {
+            write_pair(psbt, &[0x01], script);
+        }
+        for (public_key, origin) in &self.bip32_derivation {
+            write_pair(psbt, &[&[0x02], &public_key[..]].concat(), origin);
+        }
+        for (key, value) in &self.unknown {
+            write_pair(psbt, key, value);
+        }
+        psbt.push(0x00);
+    }
+}
+
+/// Represents a version 0 partially signed Bitcoin transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Psbt<N: BitcoinNetwork> {
+    /// The unsigned transaction, without scriptSigs or witnesses
+    pub unsigned_tx: BitcoinTransactionParameters<N>,
+    /// Any other pairs of the global map, such as extended public keys
+    pub unknown: Vec<PsbtPair>,
+    /// The input maps, one per transaction input
+    pub inputs: Vec<PsbtInput>,
+    /// The output maps, one per transaction output
+    pub outputs: Vec<PsbtOutput>,
+    _network: PhantomData<N>,
+}
+
+impl<N: BitcoinNetwork> Psbt<N> {
+    /// Returns a PSBT with empty maps for the inputs and outputs of
+    /// 'unsigned_tx'. Any scriptSigs and witnesses are dropped.
+    pub fn new(mut unsigned_tx: BitcoinTransactionParameters<N>) -> Self {
+        for input in &mut unsigned_tx.inputs {
+            input.script_sig.clear();
+            input.witnesses.clear();
+            input.is_signed = false;
+        }
+        unsigned_tx.segwit_flag = false;
+        Self {
+            inputs: vec![PsbtInput::default(); unsigned_tx.inputs.len()],
+            outputs: vec![PsbtOutput::default(); unsigned_tx.outputs.len()],
+            unsigned_tx,
+            unknown: vec![],
+            _network: PhantomData,
+        }
+    }
+
+    /// Returns the serialization of the unsigned transaction
+    pub fn unsigned_tx_bytes(&self) -> Result<Vec<u8>, TransactionError> {
+        let transaction = &self.unsigned_tx;
+        let mut bytes = transaction.version.to_le_bytes().to_vec();
+        bytes.extend(compact_size(transaction.inputs.len()));
+        for input in &transaction.inputs {
+            bytes.extend(input.outpoint.txid.as_bytes_internal());
+            bytes.extend(input.outpoint.index.to_le_bytes());
+            bytes.push(0x00);
+            bytes.extend(&input.sequence);
+        }
+        bytes.extend(compact_size(transaction.outputs.len()));
+        for output in &transaction.outputs {
+            output.write_to(&mut bytes)?;
+        }
+        bytes.extend(transaction.lock_time.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Returns the fee paid by the transaction, or an error if the value of a
+    /// spent output is unknown
+    pub fn fee(&self) -> Result<u64, TransactionError> {
+        let mut inputs = vec![];
+        for (input, map) in self.unsigned_tx.inputs.iter().zip(&self.inputs) {
+            inputs.push(map.utxo(&input.outpoint)?.amount);
+        }
+        let inputs = inputs.iter().sum::<Result<BitcoinAmount, AmountError>>()?;
+        let outputs = self.unsigned_tx.outputs.iter().map(|output| output.amount);
+        let outputs = outputs.sum::<Result<BitcoinAmount, AmountError>>()?;
+        let fee = inputs
+            .checked_sub(outputs)
+            .map_err(|_| TransactionError::Message("negative fee".into()))?;
+        Ok(fee.0 as u64)
+    }
+
+    /// Returns the serialized PSBT
+    pub fn serialize(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut psbt = PSBT_MAGIC.to_vec();
+        write_pair(&mut psbt, &[0x00], &self.unsigned_tx_bytes()?);
+        for (key, value) in &self.unknown {
+            write_pair(&mut psbt, key, value);
+        }
+        psbt.push(0x00);
+
+        for input in &self.inputs {
+            input.write(&mut psbt)?;
+        }
+        for output in &self.outputs {
+            output.write(&mut psbt);
+        }
+        Ok(psbt)
+    }
+
+    /// Parses a serialized PSBT
+    pub fn deserialize(psbt: &[u8]) -> Result<Self, TransactionError> {
+        let mut reader = SliceReader {
+            bytes: psbt,
+            position: 0,
+        };
+        if reader.take(5)? != PSBT_MAGIC {
+            return Err(TransactionError::Message("invalid psbt magic".into()));
+        }
+
+        let mut unsigned_tx = None;
+        let mut unknown = vec![];
+        for (key, value) in read_map(&mut reader)? {
+            match (key[0], key.len()) {
+                (0x00, 1) if unsigned_tx.is_none() => unsigned_tx = Some(value),
+                (0x00, _) => {
+                    return Err(TransactionError::Message(
+                        "invalid psbt unsigned transaction key".into(),
+                    ))
+                }
+                _ => unknown.push((key, value)),
+            }
+        }
+        let unsigned_tx = unsigned_tx
+            .ok_or_else(|| TransactionError::Message("missing psbt unsigned transaction".into()))?;
+
+        let limits = DecodeLimits {
+            strict: true,
+            ..Default::default()
+        };
+        let view = BitcoinTransactionRef::parse_with_limits(&unsigned_tx, &limits)?;
+        if view.segwit_flag || view.inputs.iter().any(|input| !input.script_sig.is_empty()) {
+            return Err(TransactionError::Message(
+                "psbt unsigned transaction has scriptSigs or witnesses".into(),
+            ));
+        }
+        let unsigned_tx = BitcoinTransactionParameters::<N>::read(&unsigned_tx[..])?;
+
+        let mut inputs = vec![];
+        for _ in 0..unsigned_tx.inputs.len() {
+            inputs.push(PsbtInput::read(&mut reader)?);
+        }
+        let mut outputs = vec![];
+        for _ in 0..unsigned_tx.outputs.len() {
+            outputs.push(PsbtOutput::read(&mut reader)?);
+        }
+        if reader.position != psbt.len() {
+            return Err(TransactionError::TrailingBytes);
+        }
+
+        Ok(Self {
+            unsigned_tx,
+            unknown,
+            inputs,
+            outputs,
+            _network: PhantomData,
+        })
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for Psbt<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let psbt = self.serialize().map_err(|_| fmt::Error)?;
+        write!(f, "{}", base64_encode(&psbt))
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for Psbt<N> {
+    type Err = TransactionError;
+
+    /// Parses a base64 encoded PSBT
+    fn from_str(psbt: &str) -> Result<Self, Self::Err> {
+        let psbt = base64_decode(psbt)
+            .ok_or_else(|| TransactionError::Message("invalid psbt base64".into()))?;
+        Self::deserialize(&psbt)
+    }
+}
+
+fn compact_size(size: usize) -> Vec<u8> {
+    crate::variable_length_integer(size as u64).unwrap()
+}
+
+fn write_pair(psbt: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    psbt.extend(compact_size(key.len()));
+    psbt.extend(key);
+    psbt.extend(compact_size(value.len()));
+    psbt.extend(value);
+}
+
+/// Reads the pairs of a map up to its separator, rejecting duplicate keys
+fn read_map(reader: &mut SliceReader) -> Result<Vec<PsbtPair>, TransactionError> {
+    let limit = reader.bytes.len();
+    let mut pairs: Vec<PsbtPair> = vec![];
+    loop {
+        let key = reader.bytes(limit, "psbt key")?;
+        if key.is_empty() {
+            return Ok(pairs);
+        }
+        if pairs.iter().any(|(k, _)| k[..] == key[..]) {
+            return Err(TransactionError::Message("duplicate psbt key".into()));
+        }
+        let value = reader.bytes(limit, "psbt value")?;
+        pairs.push((key.to_vec(), value.to_vec()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinAmount};
+    use anychain_core::hex;
+
+    // the unsigned transaction of the BIP174 test vectors
+    const UNSIGNED_TX: &str = "0200000002ab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40000000000feffffffab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40100000000feffffff02603bea0b000000001976a914768a40bbd740cbe81d988e71de2a4d5c71396b1d88ac8e240000000000001976a9146f4620b553fa095e721b9ee0efe9fa039cca459788ac00000000";
+
+    fn psbt() -> Psbt<Bitcoin> {
+        let unsigned_tx = hex::decode(UNSIGNED_TX).unwrap();
+        Psbt::new(BitcoinTransactionParameters::read(&unsigned_tx[..]).unwrap())
+    }
+
+    #[test]
+    fn test_unsigned_psbt() {
+        let psbt = psbt();
+        assert_eq!(UNSIGNED_TX, hex::encode(psbt.unsigned_tx_bytes().unwrap()));
+
+        // the BIP174 creator test vector
+        let encoded = "cHNidP8BAKACAAAAAqsJSaCMWvfEm4IS9Bfi8Vqz9cM9zxU4IagTn4d6W3vkAAAAAAD+////qwlJoIxa98SbghL0F+LxWrP1wz3PFTghqBOfh3pbe+QBAAAAAP7///8CYDvqCwAAAAAZdqkUdopAu9dAy+gdmI5x3ipNXHE5ax2IrI4kAAAAAAAAGXapFG9GILVT+glechue4O/p+gOcykWXiKwAAAAAAAAAAAA=";
+        assert_eq!(encoded, psbt.to_string());
+        assert_eq!(psbt, encoded.parse().unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut psbt = psbt();
+        psbt.inputs[0].witness_utxo = Some(BitcoinTransactionOutput {
+            amount: BitcoinAmount(200_000_000),
+            script_pub_key: hex::decode("0014d85c2b71d0060b09c9886aeb815e50991dda124d").unwrap(),
+        });
+        psbt.inputs[0]
+            .partial_sigs
+            .push((vec![0x02; 33], vec![0x30; 71]));
+        psbt.inputs[0].sighash_type = Some(1);
+        psbt.inputs[1].final_script_witness = Some(vec![vec![0x30; 71], vec![0x03; 33]]);
+        psbt.outputs[1]
+            .bip32_derivation
+            .push((vec![0x03; 33], vec![0; 8]));
+        psbt.unknown.push((vec![0xfc, 0x01], vec![0xab]));
+
+        let bytes = psbt.serialize().unwrap();
+        assert_eq!(psbt, Psbt::deserialize(&bytes).unwrap());
+        assert!(psbt.inputs[1].is_finalized());
+
+        let outpoint = &psbt.unsigned_tx.inputs[0].outpoint;
+        assert_eq!(200_000_000, psbt.inputs[0].utxo(outpoint).unwrap().amount.0);
+    }
+
+    #[test]
+    fn test_invalid_psbt() {
+        let bytes = psbt().serialize().unwrap();
+        assert!(Psbt::<Bitcoin>::deserialize(&bytes[1..]).is_err());
+        assert!(Psbt::<Bitcoin>::deserialize(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Psbt::<Bitcoin>::deserialize(&[&bytes[..], &[0x00]].concat()).is_err());
+
+        // an unsigned transaction with a scriptSig
+        let unsigned_tx = format!("{}0151{}", &UNSIGNED_TX[..82], &UNSIGNED_TX[84..]);
+        let mut bytes = PSBT_MAGIC.to_vec();
+        write_pair(&mut bytes, &[0x00], &hex::decode(unsigned_tx).unwrap());
+        bytes.extend([0x00; 5]);
+        assert!(Psbt::<Bitcoin>::deserialize(&bytes).is_err());
+    }
+}
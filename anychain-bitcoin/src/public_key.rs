@@ -1,4 +1,6 @@
 use crate::{BitcoinAddress, BitcoinFormat, BitcoinNetwork};
+use anychain_core::crypto::secp256k1_verify;
+use anychain_core::no_std::*;
 use anychain_core::{hex, libsecp256k1, Address, AddressError, PublicKey, PublicKeyError};
 use core::{fmt, marker::PhantomData, str::FromStr};
 
@@ -31,6 +33,13 @@ impl<N: BitcoinNetwork> PublicKey for BitcoinPublicKey<N> {
     fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError> {
         Self::Address::from_public_key(self, format)
     }
+
+    /// Returns true if 'signature', the 64 byte r || s with an optional
+    /// recovery id, is a signature of the 32 byte 'message_hash' by this
+    /// public key.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError> {
+        secp256k1_verify(&self.public_key, message_hash, signature)
+    }
 }
 
 impl<N: BitcoinNetwork> BitcoinPublicKey<N> {
@@ -46,6 +55,25 @@ impl<N: BitcoinNetwork> BitcoinPublicKey<N> {
         }
     }
 
+    /// Returns the public key of its serialization, a 33 byte compressed or a
+    /// 65 byte uncompressed key. Hybrid keys are rejected, as they are not
+    /// standard.
+    pub fn from_slice(public_key: &[u8]) -> Result<Self, PublicKeyError> {
+        let compressed = match public_key {
+            [0x02 | 0x03, ..] if public_key.len() == 33 => true,
+            [0x04, ..] if public_key.len() == 65 => false,
+            [prefix, ..] if public_key.len() == 33 || public_key.len() == 65 => {
+                return Err(PublicKeyError::InvalidPrefix(format!("{:02x}", prefix)))
+            }
+            _ => return Err(PublicKeyError::InvalidByteLength(public_key.len())),
+        };
+        Ok(Self {
+            public_key: libsecp256k1::PublicKey::parse_slice(public_key, None)?,
+            compressed,
+            _network: PhantomData,
+        })
+    }
+
     /// Returns the secp256k1 public key of the public key.
     pub fn to_secp256k1_public_key(&self) -> libsecp256k1::PublicKey {
         self.public_key
@@ -63,17 +91,25 @@ impl<N: BitcoinNetwork> BitcoinPublicKey<N> {
     pub fn is_compressed(&self) -> bool {
         self.compressed
     }
+
+    /// Returns an error if the public key is uncompressed, which segwit
+    /// outputs may not be locked to by policy, as they would be unspendable
+    pub fn check_segwit(&self, format: &BitcoinFormat) -> Result<(), AddressError> {
+        match self.compressed {
+            true => Ok(()),
+            false => Err(AddressError::IncompatibleFormats(
+                "uncompressed public key".into(),
+                format.to_string(),
+            )),
+        }
+    }
 }
 
 impl<N: BitcoinNetwork> FromStr for BitcoinPublicKey<N> {
     type Err = PublicKeyError;
 
     fn from_str(public_key: &str) -> Result<Self, Self::Err> {
-        Ok(Self {
-            public_key: libsecp256k1::PublicKey::parse_slice(&hex::decode(public_key)?, None)?,
-            compressed: public_key.len() == 66,
-            _network: PhantomData,
-        })
+        Self::from_slice(&hex::decode(public_key)?)
     }
 }
 
@@ -553,4 +589,52 @@ mod tests {
         let public_key = "02468791fee1444df3a6e786e2f9da79198f8902387e1fa5a2c051950c4df51ab402468791fee1444df3a6e786e2f9da79198f8902387e1fa5a2c051950c4df51ab4";
         assert!(BitcoinPublicKey::<N>::from_str(public_key).is_err());
     }
+
+    #[test]
+    fn test_uncompressed() {
+        type N = Bitcoin;
+
+        let hex_key = "0489efe59c51e542f4cc7e2464ba3835d0a1a3daf351e70db57053c4712aca58796a933d1331078c364b94dd53aba2357a01f446c22efedcea8ebce2167a9e1df8";
+        let public_key = BitcoinPublicKey::<N>::from_slice(&hex::decode(hex_key).unwrap()).unwrap();
+        assert!(!public_key.is_compressed());
+        assert_eq!(65, public_key.serialize().len());
+        assert_eq!(
+            "1KGHasyEpQZSHLea2GV3taTFZcw3uP7AAJ",
+            public_key
+                .to_address(&BitcoinFormat::P2PKH)
+                .unwrap()
+                .to_string()
+        );
+
+        // segwit outputs may not be locked to uncompressed keys
+        assert!(public_key.to_address(&BitcoinFormat::P2SH_P2WPKH).is_err());
+        assert!(public_key.to_address(&BitcoinFormat::Bech32).is_err());
+
+        // hybrid keys are not standard
+        let hybrid = format!("06{}", &hex_key[2..]);
+        assert!(BitcoinPublicKey::<N>::from_str(&hybrid).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        type N = Bitcoin;
+
+        let secret_key = libsecp256k1::SecretKey::parse(&[1u8; 32]).unwrap();
+        let public_key = BitcoinPublicKey::<N>::from_secret_key(&secret_key);
+        let hash = anychain_core::crypto::sha256(b"anychain");
+        let message = libsecp256k1::Message::parse(&hash);
+        let (signature, recid) = libsecp256k1::sign(&message, &secret_key);
+        let mut signature = signature.serialize().to_vec();
+
+        assert!(public_key.verify(&hash, &signature).unwrap());
+        assert!(!public_key.verify(&[0u8; 32], &signature).unwrap());
+
+        // a trailing recovery id is ignored
+        signature.push(recid.serialize());
+        assert!(public_key.verify(&hash, &signature).unwrap());
+
+        // malformed input is an error
+        assert!(public_key.verify(&hash[..31], &signature).is_err());
+        assert!(public_key.verify(&hash, &signature[..63]).is_err());
+    }
 }
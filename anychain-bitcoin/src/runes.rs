@@ -0,0 +1,694 @@
+//!
+//! Runes
+//!
+//! This module encodes and decodes Runestones, the messages of the Runes
+//! protocol. A Runestone is carried by an output whose script is OP_RETURN
+//! OP_13 followed by data pushes, whose concatenation is a sequence of LEB128
+//! integers: tag/value pairs for the etching, mint and pointer fields, then the
+//! body tag and the delta encoded edicts transferring runes between outputs.
+//!
+
+use crate::BitcoinTransactionOutput;
+use anychain_core::{no_std::*, TransactionError};
+use core::{fmt, str::FromStr};
+
+/// OP_RETURN
+const OP_RETURN: u8 = 0x6a;
+
+/// OP_13, the magic number of Runestones
+const MAGIC_NUMBER: u8 = 0x5d;
+
+/// The largest data push allowed by consensus
+const MAX_PUSH_SIZE: usize = 520;
+
+/// The largest divisibility of a rune
+const MAX_DIVISIBILITY: u8 = 38;
+
+/// The largest spacers bitmap: one bit for each gap of the longest rune name
+const MAX_SPACERS: u32 = 0b0000_0111_1111_1111_1111_1111_1111_1111;
+
+mod tag {
+    pub const BODY: u128 = 0;
+    pub const DIVISIBILITY: u128 = 1;
+    pub const FLAGS: u128 = 2;
+    pub const SPACERS: u128 = 3;
+    pub const RUNE: u128 = 4;
+    pub const SYMBOL: u128 = 5;
+    pub const PREMINE: u128 = 6;
+    pub const CAP: u128 = 8;
+    pub const AMOUNT: u128 = 10;
+    pub const HEIGHT_START: u128 = 12;
+    pub const HEIGHT_END: u128 = 14;
+    pub const OFFSET_START: u128 = 16;
+    pub const OFFSET_END: u128 = 18;
+    pub const MINT: u128 = 20;
+    pub const POINTER: u128 = 22;
+}
+
+mod flag {
+    pub const ETCHING: u128 = 1 << 0;
+    pub const TERMS: u128 = 1 << 1;
+    pub const TURBO: u128 = 1 << 2;
+}
+
+/// Appends the LEB128 encoding of 'value' to 'buffer'
+fn encode_varint(mut value: u128, buffer: &mut Vec<u8>) {
+    while value >> 7 > 0 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Returns the LEB128 integer at the start of 'buffer' and its length
+fn decode_varint(buffer: &[u8]) -> Result<(u128, usize), TransactionError> {
+    let mut value = 0u128;
+    for (i, byte) in buffer.iter().enumerate() {
+        if i > 18 {
+            return Err(cenotaph("overlong varint"));
+        }
+        let bits = (*byte & 0x7f) as u128;
+        if i == 18 && bits > 0b11 {
+            return Err(cenotaph("varint overflow"));
+        }
+        value |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(cenotaph("truncated varint"))
+}
+
+/// Returns the error of a malformed Runestone. Runes sent to a transaction
+/// with a malformed Runestone are burned.
+fn cenotaph(reason: &str) -> TransactionError {
+    TransactionError::Message(format!("cenotaph: {}", reason))
+}
+
+/// The id of a rune: the height and the index in the block of its etching
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RuneId {
+    pub block: u64,
+    pub tx: u32,
+}
+
+impl RuneId {
+    /// Returns the id of the rune etched by the 'tx'-th transaction of 'block'
+    pub fn new(block: u64, tx: u32) -> Option<Self> {
+        match block == 0 && tx > 0 {
+            true => None,
+            false => Some(Self { block, tx }),
+        }
+    }
+
+    /// Returns the delta encoding of this id following 'previous'
+    fn delta(&self, previous: &RuneId) -> (u128, u128) {
+        let block = self.block - previous.block;
+        let tx = match block {
+            0 => self.tx - previous.tx,
+            _ => self.tx,
+        };
+        (block as u128, tx as u128)
+    }
+
+    /// Returns the id following this one by the given deltas
+    fn next(&self, block: u128, tx: u128) -> Option<Self> {
+        let block = self.block.checked_add(u64::try_from(block).ok()?)?;
+        let tx = match block == self.block {
+            true => self.tx.checked_add(u32::try_from(tx).ok()?)?,
+            false => u32::try_from(tx).ok()?,
+        };
+        Self::new(block, tx)
+    }
+}
+
+impl fmt::Display for RuneId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.block, self.tx)
+    }
+}
+
+impl FromStr for RuneId {
+    type Err = TransactionError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let (block, tx) = id
+            .split_once(':')
+            .ok_or_else(|| TransactionError::Message(format!("invalid rune id {}", id)))?;
+        Self::new(block.parse()?, tx.parse()?)
+            .ok_or_else(|| TransactionError::Message(format!("invalid rune id {}", id)))
+    }
+}
+
+/// The name of a rune, a bijective base 26 number over the letters A to Z
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rune(pub u128);
+
+impl fmt::Display for Rune {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // u128::MAX + 1 does not fit, so the longest name is spelled out
+        if self.0 == u128::MAX {
+            return write!(f, "BCGDENLQRQWDSLRUGSNLBTMFIJAV");
+        }
+        let mut n = self.0 + 1;
+        let mut name = vec![];
+        while n > 0 {
+            name.push(b'A' + ((n - 1) % 26) as u8);
+            n = (n - 1) / 26;
+        }
+        name.reverse();
+        write!(
+            f,
+            "{}",
+            name.into_iter().map(char::from).collect::<String>()
+        )
+    }
+}
+
+impl FromStr for Rune {
+    type Err = TransactionError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransactionError::Message(format!("invalid rune name {}", name));
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let mut value = 0u128;
+        for (i, c) in name.bytes().enumerate() {
+            if i > 0 {
+                value = value.checked_add(1).ok_or_else(invalid)?;
+            }
+            let digit = match c {
+                b'A'..=b'Z' => (c - b'A') as u128,
+                _ => return Err(invalid()),
+            };
+            value = value
+                .checked_mul(26)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or_else(invalid)?;
+        }
+        Ok(Rune(value))
+    }
+}
+
+/// Transfers 'amount' units of the rune 'id' to the output at index 'output'.
+/// An output index equal to the number of outputs splits the amount among all
+/// the outputs that are not OP_RETURN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edict {
+    pub id: RuneId,
+    pub amount: u128,
+    pub output: u32,
+}
+
+/// The open mint terms of an etched rune
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Terms {
+    /// The amount of each mint
+    pub amount: Option<u128>,
+    /// The number of mints allowed
+    pub cap: Option<u128>,
+    /// The absolute block heights the mints are open between
+    pub height: (Option<u64>, Option<u64>),
+    /// The block heights relative to the etching the mints are open between
+    pub offset: (Option<u64>, Option<u64>),
+}
+
+/// The creation of a new rune
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Etching {
+    pub divisibility: Option<u8>,
+    /// The amount allocated to the etcher
+    pub premine: Option<u128>,
+    /// The name of the rune, or a reserved name if `None`
+    pub rune: Option<Rune>,
+    /// The bitmap of the spacers displayed after each letter of the name
+    pub spacers: Option<u32>,
+    pub symbol: Option<char>,
+    pub terms: Option<Terms>,
+    /// Whether the rune opts in to future protocol changes
+    pub turbo: bool,
+}
+
+impl Etching {
+    /// Returns the total supply of the rune, or `None` if it overflows
+    pub fn supply(&self) -> Option<u128> {
+        let premine = self.premine.unwrap_or_default();
+        let cap = self.terms.and_then(|terms| terms.cap).unwrap_or_default();
+        let amount = self
+            .terms
+            .and_then(|terms| terms.amount)
+            .unwrap_or_default();
+        premine.checked_add(cap.checked_mul(amount)?)
+    }
+}
+
+/// Represents a Runestone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Runestone {
+    pub edicts: Vec<Edict>,
+    pub etching: Option<Etching>,
+    /// The rune minted by the transaction
+    pub mint: Option<RuneId>,
+    /// The output receiving the runes not allocated by the edicts
+    pub pointer: Option<u32>,
+}
+
+impl Runestone {
+    /// Returns the sequence of LEB128 integers encoding the Runestone
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        let mut field = |tag: u128, value: u128| {
+            encode_varint(tag, &mut payload);
+            encode_varint(value, &mut payload);
+        };
+
+        if let Some(etching) = &self.etching {
+            let mut flags = flag::ETCHING;
+            if etching.terms.is_some() {
+                flags |= flag::TERMS;
+            }
+            if etching.turbo {
+                flags |= flag::TURBO;
+            }
+            field(tag::FLAGS, flags);
+
+            let fields = [
+                (tag::RUNE, etching.rune.map(|rune| rune.0)),
+                (tag::DIVISIBILITY, etching.divisibility.map(u128::from)),
+                (tag::SPACERS, etching.spacers.map(u128::from)),
+                (tag::SYMBOL, etching.symbol.map(|symbol| symbol as u128)),
+                (tag::PREMINE, etching.premine),
+            ];
+            for (tag, value) in fields {
+                if let Some(value) = value {
+                    field(tag, value);
+                }
+            }
+
+            if let Some(terms) = &etching.terms {
+                let fields = [
+                    (tag::AMOUNT, terms.amount),
+                    (tag::CAP, terms.cap),
+                    (tag::HEIGHT_START, terms.height.0.map(u128::from)),
+                    (tag::HEIGHT_END, terms.height.1.map(u128::from)),
+                    (tag::OFFSET_START, terms.offset.0.map(u128::from)),
+                    (tag::OFFSET_END, terms.offset.1.map(u128::from)),
+                ];
+                for (tag, value) in fields {
+                    if let Some(value) = value {
+                        field(tag, value);
+                    }
+                }
+            }
+        }
+
+        if let Some(mint) = &self.mint {
+            field(tag::MINT, mint.block as u128);
+            field(tag::MINT, mint.tx as u128);
+        }
+        if let Some(pointer) = self.pointer {
+            field(tag::POINTER, pointer as u128);
+        }
+
+        if !self.edicts.is_empty() {
+            encode_varint(tag::BODY, &mut payload);
+            let mut edicts = self.edicts.clone();
+            edicts.sort_by_key(|edict| edict.id);
+
+            let mut previous = RuneId::default();
+            for edict in edicts {
+                let (block, tx) = edict.id.delta(&previous);
+                encode_varint(block, &mut payload);
+                encode_varint(tx, &mut payload);
+                encode_varint(edict.amount, &mut payload);
+                encode_varint(edict.output as u128, &mut payload);
+                previous = edict.id;
+            }
+        }
+        payload
+    }
+
+    /// Returns the OP_RETURN script carrying the Runestone
+    pub fn to_script(&self) -> Vec<u8> {
+        let mut script = vec![OP_RETURN, MAGIC_NUMBER];
+        for chunk in self.to_payload().chunks(MAX_PUSH_SIZE) {
+            match chunk.len() {
+                0..=75 => script.push(chunk.len() as u8),
+                76..=255 => script.extend([0x4c, chunk.len() as u8]),
+                _ => {
+                    script.push(0x4d);
+                    script.extend((chunk.len() as u16).to_le_bytes());
+                }
+            }
+            script.extend(chunk);
+        }
+        script
+    }
+
+    /// Returns the Runestone of the first output of 'outputs' carrying one, or
+    /// `None` if there is none. A malformed Runestone is an error.
+    pub fn decipher(
+        outputs: &[BitcoinTransactionOutput],
+    ) -> Result<Option<Self>, TransactionError> {
+        for output in outputs {
+            if let Some(payload) = Self::payload(&output.script_pub_key)? {
+                return Self::from_payload(&payload, outputs.len()).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the concatenated data pushes of a Runestone 'script', or `None`
+    /// if the script does not start with OP_RETURN OP_13
+    fn payload(script: &[u8]) -> Result<Option<Vec<u8>>, TransactionError> {
+        let mut script = match script {
+            [OP_RETURN, MAGIC_NUMBER, rest @ ..] => rest,
+            _ => return Ok(None),
+        };
+
+        let mut payload = vec![];
+        while let Some((opcode, rest)) = script.split_first() {
+            let (size, rest) = match opcode {
+                0x00..=0x4b => (*opcode as usize, rest),
+                0x4c if !rest.is_empty() => (rest[0] as usize, &rest[1..]),
+                0x4d if rest.len() >= 2 => {
+                    (u16::from_le_bytes([rest[0], rest[1]]) as usize, &rest[2..])
+                }
+                0x4e if rest.len() >= 4 => (
+                    u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize,
+                    &rest[4..],
+                ),
+                0x4c..=0x4e => return Err(cenotaph("invalid script")),
+                _ => return Err(cenotaph("non-pushdata opcode")),
+            };
+            if rest.len() < size {
+                return Err(cenotaph("invalid script"));
+            }
+            payload.extend(&rest[..size]);
+            script = &rest[size..];
+        }
+        Ok(Some(payload))
+    }
+
+    /// Returns the Runestone of 'payload' for a transaction with
+    /// 'output_count' outputs
+    fn from_payload(payload: &[u8], output_count: usize) -> Result<Self, TransactionError> {
+        let mut integers = vec![];
+        let mut position = 0;
+        while position < payload.len() {
+            let (integer, size) = decode_varint(&payload[position..])?;
+            integers.push(integer);
+            position += size;
+        }
+
+        let mut fields = Fields::default();
+        let mut edicts = vec![];
+        for pair in integers.chunks(2) {
+            match pair {
+                [tag::BODY, ..] => break,
+                [tag, value] => fields.push(*tag, *value),
+                _ => return Err(cenotaph("truncated field")),
+            }
+        }
+
+        // the integers after the body tag are the edicts
+        let body_start = integers
+            .iter()
+            .step_by(2)
+            .position(|tag| *tag == tag::BODY)
+            .map(|index| 2 * index + 1);
+        if let Some(start) = body_start {
+            let mut id = RuneId::default();
+            for edict in integers[start..].chunks(4) {
+                let [block, tx, amount, output] = edict else {
+                    return Err(cenotaph("trailing integers"));
+                };
+                id = id
+                    .next(*block, *tx)
+                    .ok_or_else(|| cenotaph("edict rune id"))?;
+                let output = u32::try_from(*output)
+                    .ok()
+                    .filter(|output| *output as usize <= output_count)
+                    .ok_or_else(|| cenotaph("edict output"))?;
+                edicts.push(Edict {
+                    id,
+                    amount: *amount,
+                    output,
+                });
+            }
+        }
+
+        let mut flags = fields.take(tag::FLAGS, |[flags]| Some(flags)).unwrap_or(0);
+        let mut take_flag = |flag: u128| {
+            let set = flags & flag != 0;
+            flags &= !flag;
+            set
+        };
+        let etching = take_flag(flag::ETCHING);
+        let terms = take_flag(flag::TERMS);
+        let turbo = take_flag(flag::TURBO);
+
+        let etching = etching.then(|| Etching {
+            divisibility: fields.take(tag::DIVISIBILITY, |[divisibility]| {
+                u8::try_from(divisibility)
+                    .ok()
+                    .filter(|divisibility| *divisibility <= MAX_DIVISIBILITY)
+            }),
+            premine: fields.take(tag::PREMINE, |[premine]| Some(premine)),
+            rune: fields.take(tag::RUNE, |[rune]| Some(Rune(rune))),
+            spacers: fields.take(tag::SPACERS, |[spacers]| {
+                u32::try_from(spacers)
+                    .ok()
+                    .filter(|spacers| *spacers <= MAX_SPACERS)
+            }),
+            symbol: fields.take(tag::SYMBOL, |[symbol]| {
+                char::from_u32(u32::try_from(symbol).ok()?)
+            }),
+            terms: terms.then(|| Terms {
+                amount: fields.take(tag::AMOUNT, |[amount]| Some(amount)),
+                cap: fields.take(tag::CAP, |[cap]| Some(cap)),
+                height: (
+                    fields.take(tag::HEIGHT_START, |[height]| u64::try_from(height).ok()),
+                    fields.take(tag::HEIGHT_END, |[height]| u64::try_from(height).ok()),
+                ),
+                offset: (
+                    fields.take(tag::OFFSET_START, |[offset]| u64::try_from(offset).ok()),
+                    fields.take(tag::OFFSET_END, |[offset]| u64::try_from(offset).ok()),
+                ),
+            }),
+            turbo,
+        });
+
+        let mint = fields.take(tag::MINT, |[block, tx]| {
+            RuneId::new(u64::try_from(block).ok()?, u32::try_from(tx).ok()?)
+        });
+        let pointer = fields.take(tag::POINTER, |[pointer]| {
+            u32::try_from(pointer)
+                .ok()
+                .filter(|pointer| (*pointer as usize) < output_count)
+        });
+
+        if etching.is_some_and(|etching| etching.supply().is_none()) {
+            return Err(cenotaph("supply overflow"));
+        }
+        if flags != 0 {
+            return Err(cenotaph("unrecognized flag"));
+        }
+        // unknown odd tags are ignored, unknown even tags are not
+        if fields.0.iter().any(|(tag, _)| tag % 2 == 0) {
+            return Err(cenotaph("unrecognized even tag"));
+        }
+
+        Ok(Self {
+            edicts,
+            etching,
+            mint,
+            pointer,
+        })
+    }
+}
+
+/// The values of the fields of a Runestone, grouped by tag in order of appearance
+#[derive(Default)]
+struct Fields(Vec<(u128, Vec<u128>)>);
+
+impl Fields {
+    fn push(&mut self, tag: u128, value: u128) {
+        match self.0.iter_mut().find(|(t, _)| *t == tag) {
+            Some((_, values)) => values.push(value),
+            None => self.0.push((tag, vec![value])),
+        }
+    }
+
+    /// Returns the first N values of 'tag' converted by 'f', consuming them
+    /// only if the conversion succeeds
+    fn take<const N: usize, T>(
+        &mut self,
+        tag: u128,
+        f: impl FnOnce([u128; N]) -> Option<T>,
+    ) -> Option<T> {
+        let index = self.0.iter().position(|(t, _)| *t == tag)?;
+        let values: [u128; N] = self.0[index].1.get(..N)?.try_into().unwrap();
+        let value = f(values)?;
+        self.0[index].1.drain(..N);
+        if self.0[index].1.is_empty() {
+            self.0.remove(index);
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitcoinAmount;
+    use anychain_core::hex;
+
+    fn output(script_pub_key: Vec<u8>) -> BitcoinTransactionOutput {
+        BitcoinTransactionOutput {
+            amount: BitcoinAmount(0),
+            script_pub_key,
+        }
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 840000, u64::MAX as u128, u128::MAX] {
+            let mut buffer = vec![];
+            encode_varint(value, &mut buffer);
+            assert_eq!((value, buffer.len()), decode_varint(&buffer).unwrap());
+        }
+        let mut buffer = vec![];
+        encode_varint(840000, &mut buffer);
+        assert_eq!("c0a233", hex::encode(&buffer));
+
+        assert!(decode_varint(&[0x80]).is_err());
+        assert!(decode_varint(&[0xff; 19]).is_err());
+    }
+
+    #[test]
+    fn test_rune() {
+        for (name, value) in [("A", 0), ("Z", 25), ("AA", 26), ("AZ", 51), ("BA", 52)] {
+            assert_eq!(Rune(value), Rune::from_str(name).unwrap());
+            assert_eq!(name, Rune(value).to_string());
+        }
+        assert_eq!(
+            Rune(u128::MAX),
+            Rune::from_str("BCGDENLQRQWDSLRUGSNLBTMFIJAV").unwrap()
+        );
+        assert_eq!("BCGDENLQRQWDSLRUGSNLBTMFIJAV", Rune(u128::MAX).to_string());
+        assert!(Rune::from_str("BCGDENLQRQWDSLRUGSNLBTMFIJAW").is_err());
+        assert!(Rune::from_str("abc").is_err());
+
+        assert_eq!(
+            RuneId::new(840000, 1),
+            Some(RuneId::from_str("840000:1").unwrap())
+        );
+        assert!(RuneId::from_str("0:1").is_err());
+    }
+
+    #[test]
+    fn test_transfer() {
+        let runestone = Runestone {
+            edicts: vec![Edict {
+                id: RuneId::new(840000, 1).unwrap(),
+                amount: 100,
+                output: 1,
+            }],
+            ..Default::default()
+        };
+        let script = runestone.to_script();
+        assert_eq!("6a5d0700c0a233016401", hex::encode(&script));
+
+        let outputs = [output(script), output(vec![0x51])];
+        assert_eq!(Some(runestone), Runestone::decipher(&outputs).unwrap());
+        assert_eq!(None, Runestone::decipher(&outputs[1..]).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let runestone = Runestone {
+            edicts: vec![
+                Edict {
+                    id: RuneId::new(840001, 7).unwrap(),
+                    amount: u128::MAX,
+                    output: 2,
+                },
+                Edict {
+                    id: RuneId::new(840000, 3).unwrap(),
+                    amount: 5,
+                    output: 0,
+                },
+                Edict {
+                    id: RuneId::new(840000, 9).unwrap(),
+                    amount: 6,
+                    output: 1,
+                },
+            ],
+            etching: Some(Etching {
+                divisibility: Some(2),
+                premine: Some(1_000),
+                rune: Some(Rune::from_str("ANYCHAINRUNE").unwrap()),
+                spacers: Some(0b1000_0000),
+                symbol: Some('¤'),
+                terms: Some(Terms {
+                    amount: Some(100),
+                    cap: Some(1_000_000),
+                    height: (Some(840000), None),
+                    offset: (None, Some(4032)),
+                }),
+                turbo: true,
+            }),
+            mint: RuneId::new(1, 0),
+            pointer: Some(0),
+        };
+        let outputs = [output(runestone.to_script()), output(vec![0x51])];
+        // the edicts are sorted by rune id to be delta encoded
+        let mut expected = runestone.clone();
+        expected.edicts.sort_by_key(|edict| edict.id);
+        assert_eq!(Some(expected), Runestone::decipher(&outputs).unwrap());
+
+        // a payload larger than a push is split over several pushes
+        let runestone = Runestone {
+            edicts: vec![
+                Edict {
+                    id: RuneId::new(840000, 1).unwrap(),
+                    amount: u128::MAX,
+                    output: 0,
+                };
+                40
+            ],
+            ..Default::default()
+        };
+        let outputs = [output(runestone.to_script())];
+        assert_eq!(Some(runestone), Runestone::decipher(&outputs).unwrap());
+    }
+
+    #[test]
+    fn test_cenotaph() {
+        let decipher = |payload: &str| {
+            let script = [vec![OP_RETURN, MAGIC_NUMBER], {
+                let payload = hex::decode(payload).unwrap();
+                [vec![payload.len() as u8], payload].concat()
+            }]
+            .concat();
+            Runestone::decipher(&[output(script), output(vec![0x51])])
+        };
+
+        assert!(decipher("").unwrap().is_some());
+        // an unknown odd tag is ignored, an unknown even tag is not
+        assert!(decipher("7f01").unwrap().is_some());
+        assert!(decipher("7e01").is_err());
+        // an edict to a missing output
+        assert!(decipher("000101010a").is_err());
+        // trailing integers after the edicts
+        assert!(decipher("00010101000a").is_err());
+        // an unknown flag
+        assert!(decipher("0208").is_err());
+        // a truncated field
+        assert!(decipher("16").is_err());
+        // a non-push opcode
+        assert!(Runestone::decipher(&[output(vec![OP_RETURN, MAGIC_NUMBER, 0x51])]).is_err());
+    }
+}
@@ -0,0 +1,570 @@
+//!
+//! Script
+//!
+//! This module contains helpers to walk a Bitcoin script instruction by instruction,
+//! render it in the `asm` notation used by Bitcoin Core and classify the standard
+//! output templates. Scripts of forks which assign opcodes of their own are
+//! rendered through an [`OpcodeSet`], e.g. the Bitcoin Cash opcodes behind the
+//! `fork-opcodes` feature.
+//!
+
+use anychain_core::{crypto::sha256, hex, libsecp256k1::Signature, no_std::*};
+
+/// Represents a single instruction of a Bitcoin script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction<'a> {
+    /// The opcode of the instruction
+    pub opcode: u8,
+    /// The data pushed by the instruction, if the opcode is a push
+    pub data: Option<&'a [u8]>,
+}
+
+/// An iterator over the instructions of a script, yielding `None` on a truncated push
+pub struct Instructions<'a> {
+    script: &'a [u8],
+}
+
+impl<'a> Instructions<'a> {
+    pub fn new(script: &'a [u8]) -> Self {
+        Self { script }
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Option<Instruction<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&opcode, rest) = self.script.split_first()?;
+
+        let (size, rest) = match opcode {
+            0x00..=0x4b => (opcode as usize, rest),
+            0x4c if !rest.is_empty() => (rest[0] as usize, &rest[1..]),
+            0x4d if rest.len() >= 2 => {
+                (u16::from_le_bytes([rest[0], rest[1]]) as usize, &rest[2..])
+            }
+            0x4e if rest.len() >= 4 => (
+                u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize,
+                &rest[4..],
+            ),
+            0x4c..=0x4e => {
+                self.script = &[];
+                return Some(None);
+            }
+            _ => {
+                self.script = rest;
+                return Some(Some(Instruction { opcode, data: None }));
+            }
+        };
+
+        if size > rest.len() {
+            self.script = &[];
+            return Some(None);
+        }
+
+        let (data, rest) = rest.split_at(size);
+        self.script = rest;
+        Some(Some(Instruction {
+            opcode,
+            data: Some(data),
+        }))
+    }
+}
+
+/// Returns the instructions of a script, or `None` if the script contains a truncated push.
+pub fn parse_script(script: &[u8]) -> Option<Vec<Instruction<'_>>> {
+    Instructions::new(script).collect()
+}
+
+/// Returns the name of an opcode as printed by Bitcoin Core.
+pub fn opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x00 => "0",
+        0x4c => "OP_PUSHDATA1",
+        0x4d => "OP_PUSHDATA2",
+        0x4e => "OP_PUSHDATA4",
+        0x4f => "-1",
+        0x50 => "OP_RESERVED",
+        0x51 => "1",
+        0x52 => "2",
+        0x53 => "3",
+        0x54 => "4",
+        0x55 => "5",
+        0x56 => "6",
+        0x57 => "7",
+        0x58 => "8",
+        0x59 => "9",
+        0x5a => "10",
+        0x5b => "11",
+        0x5c => "12",
+        0x5d => "13",
+        0x5e => "14",
+        0x5f => "15",
+        0x60 => "16",
+        0x61 => "OP_NOP",
+        0x62 => "OP_VER",
+        0x63 => "OP_IF",
+        0x64 => "OP_NOTIF",
+        0x65 => "OP_VERIF",
+        0x66 => "OP_VERNOTIF",
+        0x67 => "OP_ELSE",
+        0x68 => "OP_ENDIF",
+        0x69 => "OP_VERIFY",
+        0x6a => "OP_RETURN",
+        0x6b => "OP_TOALTSTACK",
+        0x6c => "OP_FROMALTSTACK",
+        0x6d => "OP_2DROP",
+        0x6e => "OP_2DUP",
+        0x6f => "OP_3DUP",
+        0x70 => "OP_2OVER",
+        0x71 => "OP_2ROT",
+        0x72 => "OP_2SWAP",
+        0x73 => "OP_IFDUP",
+        0x74 => "OP_DEPTH",
+        0x75 => "OP_DROP",
+        0x76 => "OP_DUP",
+        0x77 => "OP_NIP",
+        0x78 => "OP_OVER",
+        0x79 => "OP_PICK",
+        0x7a => "OP_ROLL",
+        0x7b => "OP_ROT",
+        0x7c => "OP_SWAP",
+        0x7d => "OP_TUCK",
+        0x7e => "OP_CAT",
+        0x7f => "OP_SUBSTR",
+        0x80 => "OP_LEFT",
+        0x81 => "OP_RIGHT",
+        0x82 => "OP_SIZE",
+        0x83 => "OP_INVERT",
+        0x84 => "OP_AND",
+        0x85 => "OP_OR",
+        0x86 => "OP_XOR",
+        0x87 => "OP_EQUAL",
+        0x88 => "OP_EQUALVERIFY",
+        0x89 => "OP_RESERVED1",
+        0x8a => "OP_RESERVED2",
+        0x8b => "OP_1ADD",
+        0x8c => "OP_1SUB",
+        0x8d => "OP_2MUL",
+        0x8e => "OP_2DIV",
+        0x8f => "OP_NEGATE",
+        0x90 => "OP_ABS",
+        0x91 => "OP_NOT",
+        0x92 => "OP_0NOTEQUAL",
+        0x93 => "OP_ADD",
+        0x94 => "OP_SUB",
+        0x95 => "OP_MUL",
+        0x96 => "OP_DIV",
+        0x97 => "OP_MOD",
+        0x98 => "OP_LSHIFT",
+        0x99 => "OP_RSHIFT",
+        0x9a => "OP_BOOLAND",
+        0x9b => "OP_BOOLOR",
+        0x9c => "OP_NUMEQUAL",
+        0x9d => "OP_NUMEQUALVERIFY",
+        0x9e => "OP_NUMNOTEQUAL",
+        0x9f => "OP_LESSTHAN",
+        0xa0 => "OP_GREATERTHAN",
+        0xa1 => "OP_LESSTHANOREQUAL",
+        0xa2 => "OP_GREATERTHANOREQUAL",
+        0xa3 => "OP_MIN",
+        0xa4 => "OP_MAX",
+        0xa5 => "OP_WITHIN",
+        0xa6 => "OP_RIPEMD160",
+        0xa7 => "OP_SHA1",
+        0xa8 => "OP_SHA256",
+        0xa9 => "OP_HASH160",
+        0xaa => "OP_HASH256",
+        0xab => "OP_CODESEPARATOR",
+        0xac => "OP_CHECKSIG",
+        0xad => "OP_CHECKSIGVERIFY",
+        0xae => "OP_CHECKMULTISIG",
+        0xaf => "OP_CHECKMULTISIGVERIFY",
+        0xb0 => "OP_NOP1",
+        0xb1 => "OP_CHECKLOCKTIMEVERIFY",
+        0xb2 => "OP_CHECKSEQUENCEVERIFY",
+        0xb3 => "OP_NOP4",
+        0xb4 => "OP_NOP5",
+        0xb5 => "OP_NOP6",
+        0xb6 => "OP_NOP7",
+        0xb7 => "OP_NOP8",
+        0xb8 => "OP_NOP9",
+        0xb9 => "OP_NOP10",
+        0xba => "OP_CHECKSIGADD",
+        _ => "OP_UNKNOWN",
+    }
+}
+
+/// A set of opcodes, mapping the opcodes of a chain to their names, so that
+/// scripts of forks which assign opcodes of their own can be disassembled.
+pub trait OpcodeSet {
+    /// Returns the name of an opcode as printed by the node of the chain.
+    fn opcode_name(opcode: u8) -> &'static str;
+}
+
+/// The opcodes of Bitcoin, tapscript included.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitcoinOpcodes;
+
+impl OpcodeSet for BitcoinOpcodes {
+    fn opcode_name(opcode: u8) -> &'static str {
+        opcode_name(opcode)
+    }
+}
+
+/// The opcodes of Bitcoin Cash, which re-purposes some disabled opcodes and
+/// adds data signatures, native introspection and CashTokens opcodes.
+#[cfg(feature = "fork-opcodes")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitcoinCashOpcodes;
+
+#[cfg(feature = "fork-opcodes")]
+impl OpcodeSet for BitcoinCashOpcodes {
+    fn opcode_name(opcode: u8) -> &'static str {
+        match opcode {
+            0x7e => "OP_CAT",
+            0x7f => "OP_SPLIT",
+            0x80 => "OP_NUM2BIN",
+            0x81 => "OP_BIN2NUM",
+            0xba => "OP_CHECKDATASIG",
+            0xbb => "OP_CHECKDATASIGVERIFY",
+            0xbc => "OP_REVERSEBYTES",
+            0xc0 => "OP_INPUTINDEX",
+            0xc1 => "OP_ACTIVEBYTECODE",
+            0xc2 => "OP_TXVERSION",
+            0xc3 => "OP_TXINPUTCOUNT",
+            0xc4 => "OP_TXOUTPUTCOUNT",
+            0xc5 => "OP_TXLOCKTIME",
+            0xc6 => "OP_UTXOVALUE",
+            0xc7 => "OP_UTXOBYTECODE",
+            0xc8 => "OP_OUTPOINTTXHASH",
+            0xc9 => "OP_OUTPOINTINDEX",
+            0xca => "OP_INPUTBYTECODE",
+            0xcb => "OP_INPUTSEQUENCENUMBER",
+            0xcc => "OP_OUTPUTVALUE",
+            0xcd => "OP_OUTPUTBYTECODE",
+            0xce => "OP_UTXOTOKENCATEGORY",
+            0xcf => "OP_UTXOTOKENCOMMITMENT",
+            0xd0 => "OP_UTXOTOKENAMOUNT",
+            0xd1 => "OP_OUTPUTTOKENCATEGORY",
+            0xd2 => "OP_OUTPUTTOKENCOMMITMENT",
+            0xd3 => "OP_OUTPUTTOKENAMOUNT",
+            _ => opcode_name(opcode),
+        }
+    }
+}
+
+/// Decodes a minimally sized script number of at most 4 bytes.
+pub(crate) fn script_num(data: &[u8]) -> i64 {
+    let mut value = 0i64;
+    for (i, byte) in data.iter().enumerate() {
+        value |= (*byte as i64) << (8 * i);
+    }
+    match data.last() {
+        Some(last) if last & 0x80 != 0 => -(value & !(0x80i64 << (8 * (data.len() - 1)))),
+        _ => value,
+    }
+}
+
+/// Returns the minimal script that pushes the given number, matching `CScript() << value`
+/// in Bitcoin Core.
+pub fn push_int(value: i64) -> Vec<u8> {
+    match value {
+        0 => return vec![0x00],
+        -1 | 1..=16 => return vec![(0x50 + value) as u8],
+        _ => {}
+    }
+
+    let mut data = vec![];
+    let mut abs = value.unsigned_abs();
+    while abs > 0 {
+        data.push(abs as u8);
+        abs >>= 8;
+    }
+    // the most significant bit holds the sign
+    match data.last() {
+        Some(last) if last & 0x80 != 0 => data.push(if value < 0 { 0x80 } else { 0x00 }),
+        _ if value < 0 => *data.last_mut().unwrap() |= 0x80,
+        _ => {}
+    }
+
+    [vec![data.len() as u8], data].concat()
+}
+
+/// Returns the script that pushes the given data, with the smallest push
+/// opcode that fits its length. Empty data is pushed with OP_0.
+pub fn push_data(data: &[u8]) -> Vec<u8> {
+    let mut script = match data.len() {
+        0..=75 => vec![data.len() as u8],
+        76..=255 => vec![0x4c, data.len() as u8],
+        256..=65535 => [&[0x4d][..], &(data.len() as u16).to_le_bytes()].concat(),
+        _ => [&[0x4e][..], &(data.len() as u32).to_le_bytes()].concat(),
+    };
+    script.extend(data);
+    script
+}
+
+/// Returns the sighash suffix of a pushed item if it is a strictly encoded signature.
+fn sighash_suffix(data: &[u8]) -> Option<&'static str> {
+    let (sighash, der) = data.split_last()?;
+    let name = match sighash {
+        0x01 => "ALL",
+        0x02 => "NONE",
+        0x03 => "SINGLE",
+        0x81 => "ALL|ANYONECANPAY",
+        0x82 => "NONE|ANYONECANPAY",
+        0x83 => "SINGLE|ANYONECANPAY",
+        _ => return None,
+    };
+    Signature::parse_der(der).ok().map(|_| name)
+}
+
+/// Returns the `asm` representation of a script as printed by Bitcoin Core.
+/// If `decode_sighash` is set, pushed signatures are printed with their sighash type.
+pub fn script_asm(script: &[u8], decode_sighash: bool) -> String {
+    script_asm_with::<BitcoinOpcodes>(script, decode_sighash)
+}
+
+/// Returns the `asm` representation of a script with the opcode names of `O`.
+pub fn script_asm_with<O: OpcodeSet>(script: &[u8], decode_sighash: bool) -> String {
+    let decode_sighash = decode_sighash && script.first() != Some(&0x6a);
+    let mut items = vec![];
+
+    for instruction in Instructions::new(script) {
+        let instruction = match instruction {
+            Some(instruction) => instruction,
+            None => {
+                items.push("[error]".to_string());
+                break;
+            }
+        };
+        match instruction.data {
+            Some(data) if data.len() <= 4 => items.push(format!("{}", script_num(data))),
+            Some(data) => match decode_sighash.then(|| sighash_suffix(data)).flatten() {
+                Some(suffix) => items.push(format!(
+                    "{}[{}]",
+                    hex::encode(&data[..data.len() - 1]),
+                    suffix
+                )),
+                None => items.push(hex::encode(data)),
+            },
+            None => items.push(O::opcode_name(instruction.opcode).to_string()),
+        }
+    }
+
+    items.join(" ")
+}
+
+/// Returns the standard template type of a scriptPubKey as named by Bitcoin Core.
+pub fn script_type(script: &[u8]) -> &'static str {
+    match script {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => "pubkeyhash",
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => "scripthash",
+        [0x00, 0x14, program @ ..] if program.len() == 20 => "witness_v0_keyhash",
+        [0x00, 0x20, program @ ..] if program.len() == 32 => "witness_v0_scripthash",
+        [0x51, 0x20, program @ ..] if program.len() == 32 => "witness_v1_taproot",
+        [0x51, 0x02, 0x4e, 0x73] => "anchor",
+        [0x51..=0x60, size, program @ ..]
+            if (2..=40).contains(&program.len()) && *size as usize == program.len() =>
+        {
+            "witness_unknown"
+        }
+        [0x21, key @ .., 0xac] if key.len() == 33 => "pubkey",
+        [0x41, key @ .., 0xac] if key.len() == 65 => "pubkey",
+        [0x6a, rest @ ..] => match parse_script(rest) {
+            Some(instructions) if instructions.iter().all(|i| i.opcode <= 0x60) => "nulldata",
+            _ => "nonstandard",
+        },
+        _ if is_multisig(script) => "multisig",
+        _ => "nonstandard",
+    }
+}
+
+/// Returns the script hash of a scriptPubKey used by the Electrum protocol to
+/// identify addresses: the SHA256 of the script, reversed and hex encoded.
+pub fn electrum_script_hash(script_pub_key: &[u8]) -> String {
+    let mut hash = sha256(script_pub_key);
+    hash.reverse();
+    hex::encode(hash)
+}
+
+/// Returns true if the script is a bare `m <pubkeys> n OP_CHECKMULTISIG` script.
+fn is_multisig(script: &[u8]) -> bool {
+    multisig_threshold(script).is_some()
+}
+
+/// Returns the threshold `m` and the number of keys `n` of a bare
+/// `m <pubkeys> n OP_CHECKMULTISIG` script.
+pub fn multisig_threshold(script: &[u8]) -> Option<(usize, usize)> {
+    let instructions = match parse_script(script) {
+        Some(instructions) if instructions.len() >= 4 => instructions,
+        _ => return None,
+    };
+    let (first, rest) = instructions.split_first().unwrap();
+    let (last, rest) = rest.split_last().unwrap();
+    let (n, keys) = rest.split_last().unwrap();
+
+    let small_int = |opcode: u8| match opcode {
+        0x51..=0x60 => Some((opcode - 0x50) as usize),
+        _ => None,
+    };
+
+    match (small_int(first.opcode), small_int(n.opcode)) {
+        (Some(m), Some(n))
+            if last.opcode == 0xae
+                && m <= n
+                && n == keys.len()
+                && keys.iter().all(
+                    |key| matches!(key.data, Some(data) if data.len() == 33 || data.len() == 65),
+                ) =>
+        {
+            Some((m, n))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the script only pushes data, as a standard scriptSig does.
+pub fn is_push_only(script: &[u8]) -> bool {
+    Instructions::new(script).all(|instruction| matches!(instruction, Some(i) if i.opcode <= 0x60))
+}
+
+/// Returns true if every push of the script uses the smallest opcode able to
+/// push its data, e.g. `OP_1` rather than a one byte push of 0x01.
+pub fn is_minimally_pushed(script: &[u8]) -> bool {
+    Instructions::new(script).all(|instruction| match instruction {
+        Some(Instruction {
+            opcode,
+            data: Some(data),
+        }) => match data {
+            [] => opcode == 0x00,
+            [1..=16] | [0x81] => false,
+            _ => push_data(data)[0] == opcode,
+        },
+        Some(_) => true,
+        None => false,
+    })
+}
+
+/// Returns true if a pushed item is an ECDSA signature with a high S value,
+/// which is malleable and not relayed (BIP62).
+pub fn is_high_s_signature(data: &[u8]) -> bool {
+    sighash_suffix(data).is_some()
+        && matches!(Signature::parse_der(&data[..data.len() - 1]), Ok(signature) if signature.s.is_high())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_electrum_script_hash() {
+        // the script hash of 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa from the protocol docs
+        let script = hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert_eq!(
+            "8b01df4e368ea28f8dc0423bcf7a4923e3a12d307c875e47a0cfbf90b5c39161",
+            electrum_script_hash(&script)
+        );
+        let address =
+            crate::BitcoinAddress::<crate::Bitcoin>::from_str("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa")
+                .unwrap();
+        assert_eq!(
+            electrum_script_hash(&script),
+            address.electrum_script_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_script_asm() {
+        let p2pkh = hex::decode("76a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac").unwrap();
+        assert_eq!(
+            "OP_DUP OP_HASH160 a457b684d7f0d539a46a45bbc043f35b59d0d963 OP_EQUALVERIFY OP_CHECKSIG",
+            script_asm(&p2pkh, false)
+        );
+        assert_eq!("pubkeyhash", script_type(&p2pkh));
+
+        let op_return = hex::decode("6a0400e1f505").unwrap();
+        assert_eq!("OP_RETURN 100000000", script_asm(&op_return, false));
+        assert_eq!("nulldata", script_type(&op_return));
+
+        assert_eq!("0 -1 [error]", script_asm(&[0x00, 0x4f, 0x4c], false));
+    }
+
+    #[test]
+    fn test_script_asm_sighash() {
+        let script_sig = hex::decode("483045022100ef3b2e3a0b0b3c8b4ce4e1a5b0e6f3f2ad8c36c5e03ea9e50b8c8e1e6da9d9a6022071a2b52e9c9e06a3e0b6b0dbee4c2b6b0e58b70c27d6c7f42c9d3d7fad0d9c0201").unwrap();
+        assert!(script_asm(&script_sig, true).ends_with("[ALL]"));
+        assert!(!script_asm(&script_sig, false).ends_with("[ALL]"));
+    }
+
+    #[cfg(feature = "fork-opcodes")]
+    #[test]
+    fn test_fork_opcodes() {
+        use crate::Opcode;
+
+        // <sig> <msg> <pubkey> OP_CHECKDATASIG OP_REVERSEBYTES
+        let script = [
+            vec![0x01, 0xaa, 0x01, 0xbb, 0x01, 0xcc],
+            vec![Opcode::OP_CHECKDATASIG as u8, Opcode::OP_REVERSEBYTES as u8],
+        ]
+        .concat();
+        assert_eq!(
+            "-42 -59 -76 OP_CHECKDATASIG OP_REVERSEBYTES",
+            script_asm_with::<BitcoinCashOpcodes>(&script, false)
+        );
+        assert_eq!(
+            "-42 -59 -76 OP_CHECKSIGADD OP_UNKNOWN",
+            script_asm(&script, false)
+        );
+        assert_eq!("OP_SPLIT", BitcoinCashOpcodes::opcode_name(0x7f));
+        assert_eq!("OP_DUP", BitcoinCashOpcodes::opcode_name(0x76));
+    }
+
+    #[test]
+    fn test_multisig() {
+        let script = hex::decode(concat!(
+            "51",
+            "21039ed714bf521e96e3f3609b74da898e44d0fb64ba68c62c57852470ffc28e3db5",
+            "2103a385ac59a31841764d55e7c8a243482a89073785524f0c45335afcf425d567b1",
+            "52ae"
+        ))
+        .unwrap();
+        assert_eq!("multisig", script_type(&script));
+    }
+
+    #[test]
+    fn test_push_policy() {
+        assert!(is_push_only(&[0x00, 0x51, 0x01, 0x11]));
+        assert!(!is_push_only(&[0x51, 0x87]));
+        assert!(!is_push_only(&[0x4c]));
+
+        assert!(is_minimally_pushed(&push_data(&[0x11; 80])));
+        assert!(is_minimally_pushed(&[0x00, 0x51, 0x01, 0x11]));
+        assert!(!is_minimally_pushed(&[0x01, 0x01]));
+        assert!(!is_minimally_pushed(&[0x4c, 0x01, 0x11]));
+        assert!(!is_minimally_pushed(&[0x4c, 0x00]));
+    }
+
+    #[test]
+    fn test_push_int() {
+        assert_eq!(vec![0x00], push_int(0));
+        assert_eq!(vec![0x51], push_int(1));
+        assert_eq!(vec![0x60], push_int(16));
+        assert_eq!(vec![0x4f], push_int(-1));
+        assert_eq!(vec![0x01, 0x11], push_int(17));
+        assert_eq!(vec![0x02, 0x80, 0x00], push_int(128));
+        assert_eq!(vec![0x01, 0x82], push_int(-2));
+        assert_eq!(vec![0x03, 0x40, 0xd1, 0x0c], push_int(840000));
+        assert_eq!(840000, script_num(&push_int(840000)[1..]));
+        assert_eq!(-255, script_num(&push_int(-255)[1..]));
+    }
+
+    #[test]
+    fn test_push_data() {
+        assert_eq!(vec![0x00], push_data(&[]));
+        assert_eq!(vec![0x02, 0xab, 0xcd], push_data(&[0xab, 0xcd]));
+        assert_eq!(&[0x4c, 76], &push_data(&[0; 76])[..2]);
+        assert_eq!(&[0x4d, 0x08, 0x02], &push_data(&[0; 520])[..3]);
+        assert_eq!(523, push_data(&[0; 520]).len());
+    }
+}
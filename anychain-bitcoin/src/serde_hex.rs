@@ -0,0 +1,111 @@
+//! Serde helpers encoding byte fields as hex strings.
+
+use anychain_core::{hex, no_std::*};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A borrowed byte slice serialized as a hex string
+struct Hex<'a>(&'a [u8]);
+
+impl Serialize for Hex<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+/// An owned byte vector deserialized from a hex string
+struct HexBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for HexBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(HexBuf(hex::decode(s).map_err(D::Error::custom)?))
+    }
+}
+
+pub(crate) mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        Hex(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        Ok(HexBuf::deserialize(deserializer)?.0)
+    }
+}
+
+pub(crate) mod array {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const LEN: usize>(
+        bytes: &[u8; LEN],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Hex(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const LEN: usize>(
+        deserializer: D,
+    ) -> Result<[u8; LEN], D::Error> {
+        let bytes = HexBuf::deserialize(deserializer)?.0;
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::custom(format!("expected {} bytes, found {}", LEN, len)))
+    }
+}
+
+pub(crate) mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bytes.as_deref().map(Hex).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Ok(Option::<HexBuf>::deserialize(deserializer)?.map(|bytes| bytes.0))
+    }
+}
+
+pub(crate) mod vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(items: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|item| Hex(item)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        Ok(Vec::<HexBuf>::deserialize(deserializer)?
+            .into_iter()
+            .map(|item| item.0)
+            .collect())
+    }
+}
+
+pub(crate) mod option_flagged {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<(Vec<u8>, bool)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|(bytes, flag)| (Hex(bytes), flag))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(Vec<u8>, bool)>, D::Error> {
+        Ok(Option::<(HexBuf, bool)>::deserialize(deserializer)?
+            .map(|(bytes, flag)| (bytes.0, flag)))
+    }
+}
@@ -0,0 +1,191 @@
+//!
+//! Signature Operations
+//!
+//! This module counts the signature operations (sigops) of scripts and
+//! transactions the way Bitcoin Core does, so that miners and pool software
+//! can fill blocks within the consensus sigop limit and wallets can keep
+//! transactions within the standard one. Sigops are weighed like bytes
+//! (BIP141): a legacy or P2SH sigop costs four units, a witness sigop one.
+//!
+
+use crate::{witness_item_data, BitcoinNetwork, BitcoinTransaction, Instructions};
+use anychain_core::no_std::*;
+
+/// The maximum sigop cost of a block
+pub const MAX_BLOCK_SIGOPS_COST: usize = 80_000;
+
+/// The maximum sigop cost of a transaction relayed by Bitcoin Core
+pub const MAX_STANDARD_TX_SIGOPS_COST: usize = MAX_BLOCK_SIGOPS_COST / 5;
+
+/// The number of sigops a `CHECKMULTISIG` counts for when its number of keys
+/// is not known
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+/// Returns the number of sigops of a script. `OP_CHECKSIG` counts for one.
+/// `OP_CHECKMULTISIG` counts for its number of keys if 'accurate' is set and
+/// the keys are counted by a preceding `OP_1` to `OP_16`, as in redeem and
+/// witness scripts, and for 20 otherwise. Counting stops at a truncated push.
+pub fn count_sigops(script: &[u8], accurate: bool) -> usize {
+    let mut count = 0;
+    let mut last_opcode = 0xff;
+    for instruction in Instructions::new(script).map_while(|instruction| instruction) {
+        count += match instruction.opcode {
+            0xac | 0xad => 1,
+            0xae | 0xaf if accurate && (0x51..=0x60).contains(&last_opcode) => {
+                (last_opcode - 0x50) as usize
+            }
+            0xae | 0xaf => MAX_PUBKEYS_PER_MULTISIG,
+            _ => 0,
+        };
+        last_opcode = instruction.opcode;
+    }
+    count
+}
+
+/// Returns true if the scriptPubKey is `OP_HASH160 <20 bytes> OP_EQUAL`
+fn is_p2sh(script_pub_key: Option<&[u8]>) -> bool {
+    matches!(script_pub_key, Some([0xa9, 0x14, hash @ .., 0x87]) if hash.len() == 20)
+}
+
+/// Returns the data of the last push of a scriptSig, the redeem script of a
+/// P2SH spend
+fn last_push(script_sig: &[u8]) -> Option<&[u8]> {
+    Instructions::new(script_sig)
+        .map_while(|instruction| instruction)
+        .last()
+        .and_then(|instruction| instruction.data)
+}
+
+/// Returns the number of sigops of a witness program spent with 'witnesses',
+/// stored with their length prefixes
+fn witness_program_sigops(program: &[u8], witnesses: &[Vec<u8>]) -> usize {
+    match program {
+        [0x00, 0x14, hash @ ..] if hash.len() == 20 => 1,
+        [0x00, 0x20, hash @ ..] if hash.len() == 32 => witnesses
+            .last()
+            .map_or(0, |script| count_sigops(witness_item_data(script), true)),
+        _ => 0,
+    }
+}
+
+impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Returns the number of sigops of the scriptSigs and scriptPubKeys of
+    /// the transaction, counted without looking at the outputs it spends
+    pub fn legacy_sigop_count(&self) -> usize {
+        let inputs = self.parameters.inputs.iter().map(|input| &input.script_sig);
+        let outputs = self
+            .parameters
+            .outputs
+            .iter()
+            .map(|output| &output.script_pub_key);
+        inputs
+            .chain(outputs)
+            .map(|script| count_sigops(script, false))
+            .sum()
+    }
+
+    /// Returns the number of sigops of the redeem scripts of the inputs
+    /// spending P2SH outputs. Inputs whose scriptPubKey is not known count
+    /// for none.
+    pub fn p2sh_sigop_count(&self) -> usize {
+        self.parameters
+            .inputs
+            .iter()
+            .filter(|input| is_p2sh(input.script_pub_key.as_deref()))
+            .filter_map(|input| last_push(&input.script_sig))
+            .map(|redeem_script| count_sigops(redeem_script, true))
+            .sum()
+    }
+
+    /// Returns the number of sigops of the inputs spending witness programs,
+    /// native or nested in P2SH. Inputs whose scriptPubKey is not known count
+    /// for none, and taproot spends count for none as they are budgeted by
+    /// their witness size instead (BIP342).
+    pub fn witness_sigop_count(&self) -> usize {
+        self.parameters
+            .inputs
+            .iter()
+            .map(|input| match input.script_pub_key.as_deref() {
+                script_pub_key if is_p2sh(script_pub_key) => last_push(&input.script_sig)
+                    .map_or(0, |program| {
+                        witness_program_sigops(program, &input.witnesses)
+                    }),
+                Some(program) => witness_program_sigops(program, &input.witnesses),
+                None => 0,
+            })
+            .sum()
+    }
+
+    /// Returns the sigop cost of the transaction: four times its legacy and
+    /// P2SH sigops plus its witness sigops
+    pub fn sigop_cost(&self) -> usize {
+        (self.legacy_sigop_count() + self.p2sh_sigop_count()) * 4 + self.witness_sigop_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bitcoin;
+    use anychain_core::{crypto::sha256, hex, Transaction};
+
+    // a signed P2WPKH spend taken from BIP143
+    const RAW: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    // 2 <key> <key> <key> 3 OP_CHECKMULTISIG
+    const MULTISIG: &str = concat!(
+        "52",
+        "21039ed714bf521e96e3f3609b74da898e44d0fb64ba68c62c57852470ffc28e3db5",
+        "2103a385ac59a31841764d55e7c8a243482a89073785524f0c45335afcf425d567b1",
+        "21025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357",
+        "53ae"
+    );
+
+    #[test]
+    fn test_count_sigops() {
+        let multisig = hex::decode(MULTISIG).unwrap();
+        assert_eq!(3, count_sigops(&multisig, true));
+        assert_eq!(20, count_sigops(&multisig, false));
+        assert_eq!(
+            1,
+            count_sigops(
+                &hex::decode("76a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac").unwrap(),
+                false
+            )
+        );
+        // OP_CHECKSIGVERIFY OP_CHECKSIG, then a truncated push
+        assert_eq!(2, count_sigops(&[0xad, 0xac, 0x4c], true));
+    }
+
+    #[test]
+    fn test_sigop_cost() {
+        let mut transaction =
+            BitcoinTransaction::<Bitcoin>::from_bytes(&hex::decode(RAW).unwrap()).unwrap();
+        // the two P2PKH outputs
+        assert_eq!(2, transaction.legacy_sigop_count());
+        assert_eq!(0, transaction.witness_sigop_count());
+
+        transaction.parameters.inputs[1].script_pub_key =
+            Some(hex::decode("00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap());
+        assert_eq!(1, transaction.witness_sigop_count());
+        assert_eq!(9, transaction.sigop_cost());
+
+        // a P2WSH multisig nested in P2SH
+        let multisig = hex::decode(MULTISIG).unwrap();
+        let program = [vec![0x00, 0x20], sha256(&multisig).to_vec()].concat();
+        let input = &mut transaction.parameters.inputs[1];
+        input.script_pub_key = Some([vec![0xa9, 0x14], vec![0; 20], vec![0x87]].concat());
+        input.script_sig = crate::push_data(&program);
+        input.witnesses = vec![[vec![multisig.len() as u8], multisig.clone()].concat()];
+        assert_eq!(3, transaction.witness_sigop_count());
+        assert_eq!(0, transaction.p2sh_sigop_count());
+
+        // the multisig as a P2SH redeem script
+        let input = &mut transaction.parameters.inputs[1];
+        input.script_sig = crate::push_data(&multisig);
+        input.witnesses.clear();
+        assert_eq!(3, transaction.p2sh_sigop_count());
+        // the pushed redeem script is not counted as a legacy script
+        assert_eq!((2 + 3) * 4, transaction.sigop_cost());
+    }
+}
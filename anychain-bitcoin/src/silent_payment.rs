@@ -0,0 +1,369 @@
+//!
+//! Silent Payment
+//!
+//! This module implements the sending side of silent payments (BIP352). A
+//! silent payment address holds a scan key and a spend key, and the sender
+//! derives a fresh taproot output for the recipient from an ECDH shared secret
+//! between the scan key and the keys of the inputs of the transaction, so that
+//! the outputs cannot be linked to the address.
+//!
+
+use crate::{
+    create_script_pub_key, tagged_hash, BitcoinAmount, BitcoinNetwork, BitcoinTransactionOutput,
+    BitcoinTransactionParameters, Outpoint,
+};
+use anychain_core::{
+    encoding::{bech32_decode_with_version, bech32_encode_with_version, Variant},
+    libsecp256k1::{curve::Scalar, PublicKey, SecretKey},
+    no_std::*,
+    AddressError, TransactionError,
+};
+use core::{fmt, marker::PhantomData, str::FromStr};
+
+/// Represents a silent payment address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentPaymentAddress<N: BitcoinNetwork> {
+    /// The key the recipient scans the chain with
+    pub scan_key: PublicKey,
+    /// The key the recipient spends the outputs with, possibly tweaked by a label
+    pub spend_key: PublicKey,
+    _network: PhantomData<N>,
+}
+
+impl<N: BitcoinNetwork> SilentPaymentAddress<N> {
+    /// Returns the silent payment address of the given scan and spend keys
+    pub fn new(scan_key: PublicKey, spend_key: PublicKey) -> Self {
+        Self {
+            scan_key,
+            spend_key,
+            _network: PhantomData,
+        }
+    }
+
+    fn hrp() -> Result<&'static str, AddressError> {
+        N::SILENT_PAYMENT_HRP.ok_or_else(|| {
+            AddressError::Message(format!(
+                "{} does not support silent payment addresses",
+                N::NAME
+            ))
+        })
+    }
+}
+
+impl<N: BitcoinNetwork> fmt::Display for SilentPaymentAddress<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hrp = Self::hrp().map_err(|_| fmt::Error)?;
+        let keys = [
+            self.scan_key.serialize_compressed(),
+            self.spend_key.serialize_compressed(),
+        ]
+        .concat();
+        // version 0
+        let address =
+            bech32_encode_with_version(hrp, 0, &keys, Variant::Bech32m).map_err(|_| fmt::Error)?;
+        write!(f, "{}", address)
+    }
+}
+
+impl<N: BitcoinNetwork> FromStr for SilentPaymentAddress<N> {
+    type Err = AddressError;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        let (hrp, version, keys, variant) = bech32_decode_with_version(address)?;
+        if hrp != Self::hrp()? || variant != Variant::Bech32m {
+            return Err(AddressError::InvalidAddress(address.into()));
+        }
+
+        // later versions must keep the keys in front for backward compatibility
+        let keys = match version {
+            0 if keys.len() == 66 => &keys[..],
+            1..=30 if keys.len() >= 66 => &keys[..66],
+            _ => return Err(AddressError::InvalidAddress(address.into())),
+        };
+        let parse = |key: &[u8]| {
+            PublicKey::parse_slice(key, None)
+                .ok()
+                .filter(|_| key[0] != 0x04)
+                .ok_or_else(|| AddressError::InvalidAddress(address.into()))
+        };
+        Ok(Self::new(parse(&keys[..33])?, parse(&keys[33..])?))
+    }
+}
+
+/// An input of a transaction paying to silent payment addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentPaymentInput {
+    /// The outpoint spent by the input
+    pub outpoint: Outpoint,
+    /// The secret key of the input, or `None` if the input type is not eligible
+    /// for silent payments (e.g. P2WSH) and does not contribute to the shared secret
+    pub secret_key: Option<SecretKey>,
+    /// Whether the input spends a taproot output, whose key has an even y coordinate
+    pub is_taproot: bool,
+}
+
+/// Returns the x-only taproot output keys paying to 'recipients' from a
+/// transaction spending 'inputs', in the order of the recipients
+pub fn silent_payment_output_keys<N: BitcoinNetwork>(
+    inputs: &[SilentPaymentInput],
+    recipients: &[SilentPaymentAddress<N>],
+) -> Result<Vec<[u8; 32]>, TransactionError> {
+    let smallest_outpoint = inputs
+        .iter()
+        .map(|input| {
+            [
+                &input.outpoint.txid.as_bytes_internal()[..],
+                &input.outpoint.index.to_le_bytes(),
+            ]
+            .concat()
+        })
+        .min()
+        .ok_or(TransactionError::MissingSpendParameters)?;
+
+    // the sum of the secret keys, those of taproot inputs negated as needed
+    // to match their x-only public keys
+    let mut sum = Scalar::default();
+    for input in inputs {
+        if let Some(secret_key) = &input.secret_key {
+            let scalar: Scalar = (*secret_key).into();
+            let odd = PublicKey::from_secret_key(secret_key).serialize_compressed()[0] == 0x03;
+            sum += if input.is_taproot && odd {
+                -scalar
+            } else {
+                scalar
+            };
+        }
+    }
+    let sum = SecretKey::try_from(sum).map_err(|_| {
+        TransactionError::Message("the input keys of a silent payment sum to zero".into())
+    })?;
+
+    let input_hash = tagged_hash(
+        "BIP0352/Inputs",
+        &[
+            &smallest_outpoint[..],
+            &PublicKey::from_secret_key(&sum).serialize_compressed(),
+        ]
+        .concat(),
+    );
+    let mut tweak = SecretKey::parse(&input_hash)?;
+    tweak.tweak_mul_assign(&sum)?;
+
+    let mut output_keys = vec![];
+    let mut counters: Vec<(PublicKey, u32)> = vec![];
+    for recipient in recipients {
+        let k = match counters
+            .iter_mut()
+            .find(|(scan_key, _)| *scan_key == recipient.scan_key)
+        {
+            Some((_, k)) => {
+                *k += 1;
+                *k
+            }
+            None => {
+                counters.push((recipient.scan_key, 0));
+                0
+            }
+        };
+
+        let mut shared_secret = recipient.scan_key;
+        shared_secret.tweak_mul_assign(&tweak)?;
+        let t_k = tagged_hash(
+            "BIP0352/SharedSecret",
+            &[&shared_secret.serialize_compressed()[..], &k.to_be_bytes()].concat(),
+        );
+
+        let mut output_key = recipient.spend_key;
+        output_key.tweak_add_assign(&SecretKey::parse(&t_k)?)?;
+        output_keys.push(output_key.serialize_compressed()[1..].try_into().unwrap());
+    }
+    Ok(output_keys)
+}
+
+impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
+    /// Appends outputs paying 'recipients' by silent payments. 'secret_keys'
+    /// holds the secret key of each input, `None` for the inputs that are not
+    /// eligible. The inputs must be complete, as the outputs commit to them.
+    pub fn add_silent_payments(
+        &mut self,
+        secret_keys: &[Option<SecretKey>],
+        recipients: &[(SilentPaymentAddress<N>, BitcoinAmount)],
+    ) -> Result<(), TransactionError> {
+        if secret_keys.len() != self.inputs.len() {
+            return Err(TransactionError::InvalidInputs(
+                "a secret key for each input".into(),
+            ));
+        }
+
+        let mut inputs = vec![];
+        for (input, secret_key) in self.inputs.iter().zip(secret_keys) {
+            let script_pub_key = match (&input.script_pub_key, &input.address) {
+                (Some(script_pub_key), _) => script_pub_key.clone(),
+                (None, Some(address)) => create_script_pub_key(address)?,
+                (None, None) => return Err(TransactionError::MissingOutpointScriptPublicKey),
+            };
+            inputs.push(SilentPaymentInput {
+                outpoint: input.outpoint.clone(),
+                secret_key: *secret_key,
+                is_taproot: matches!(script_pub_key.as_slice(), [0x51, 0x20, ..]),
+            });
+        }
+
+        let addresses: Vec<_> = recipients
+            .iter()
+            .map(|(address, _)| address.clone())
+            .collect();
+        let output_keys = silent_payment_output_keys(&inputs, &addresses)?;
+        for (output_key, (_, amount)) in output_keys.into_iter().zip(recipients) {
+            self.outputs.push(BitcoinTransactionOutput {
+                amount: *amount,
+                script_pub_key: [&[0x51, 0x20], &output_key[..]].concat(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinTestnet, BitcoinTransactionInput, Litecoin, SignatureHash, Txid};
+    use anychain_core::hex;
+
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+
+    fn secret_key(hex: &str) -> SecretKey {
+        SecretKey::parse_slice(&hex::decode(hex).unwrap()).unwrap()
+    }
+
+    fn outpoint(txid: &str, index: u32) -> Outpoint {
+        Outpoint::new(Txid::from_hex_rpc(txid).unwrap(), index)
+    }
+
+    fn inputs() -> Vec<SilentPaymentInput> {
+        vec![
+            SilentPaymentInput {
+                outpoint: outpoint(
+                    "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+                    0,
+                ),
+                secret_key: Some(secret_key(
+                    "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1",
+                )),
+                is_taproot: false,
+            },
+            SilentPaymentInput {
+                outpoint: outpoint(
+                    "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d",
+                    0,
+                ),
+                secret_key: Some(secret_key(
+                    "93f5ed907ad5b2bdbbdcb6d9116ebc0a4e1f92f910d5260237fa45a9408aad16",
+                )),
+                is_taproot: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_address() {
+        let address = SilentPaymentAddress::<Bitcoin>::from_str(ADDRESS).unwrap();
+        assert_eq!(
+            "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4",
+            hex::encode(address.scan_key.serialize_compressed())
+        );
+        assert_eq!(ADDRESS, address.to_string());
+
+        let testnet =
+            SilentPaymentAddress::<BitcoinTestnet>::new(address.scan_key, address.spend_key);
+        assert!(testnet.to_string().starts_with("tsp1q"));
+        assert!(SilentPaymentAddress::<BitcoinTestnet>::from_str(ADDRESS).is_err());
+        assert!(SilentPaymentAddress::<Litecoin>::from_str(ADDRESS).is_err());
+        assert!(SilentPaymentAddress::<Bitcoin>::from_str(&ADDRESS[..ADDRESS.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_output_keys() {
+        let address = SilentPaymentAddress::<Bitcoin>::from_str(ADDRESS).unwrap();
+        let output_keys =
+            silent_payment_output_keys(&inputs(), core::slice::from_ref(&address)).unwrap();
+        assert_eq!(
+            "eee78f4383ed1a7147f7beb5bcaf4762d8c11708b77f9df1bbe54eb98c2a8e50",
+            hex::encode(output_keys[0])
+        );
+
+        // the outputs to the same scan key are told apart by a counter, and the
+        // order of the inputs does not matter
+        let mut reversed = inputs();
+        reversed.reverse();
+        let output_keys =
+            silent_payment_output_keys(&reversed, &[address.clone(), address]).unwrap();
+        assert_eq!(
+            "eee78f4383ed1a7147f7beb5bcaf4762d8c11708b77f9df1bbe54eb98c2a8e50",
+            hex::encode(output_keys[0])
+        );
+        assert_eq!(
+            "7b24f76105198f28322b190fb6736dc3cae9fbf5743aee9eb113498041dbc801",
+            hex::encode(output_keys[1])
+        );
+    }
+
+    #[test]
+    fn test_add_silent_payments() {
+        let address = SilentPaymentAddress::<Bitcoin>::from_str(ADDRESS).unwrap();
+        let inputs = inputs()
+            .into_iter()
+            .map(|input| {
+                let mut transaction_input = BitcoinTransactionInput::<Bitcoin>::new(
+                    input.outpoint.txid.to_bytes_rpc().to_vec(),
+                    input.outpoint.index,
+                    None,
+                    None,
+                    None,
+                    None,
+                    SignatureHash::SIGHASH_ALL,
+                )
+                .unwrap();
+                transaction_input.script_pub_key = Some(vec![0x76, 0xa9]);
+                transaction_input
+            })
+            .collect();
+        let mut parameters = BitcoinTransactionParameters::<Bitcoin>::new(inputs, vec![]).unwrap();
+        let secret_keys: Vec<_> = self::inputs()
+            .into_iter()
+            .map(|input| input.secret_key)
+            .collect();
+
+        parameters
+            .add_silent_payments(&secret_keys, &[(address.clone(), BitcoinAmount(10_000))])
+            .unwrap();
+        assert_eq!(
+            "5120eee78f4383ed1a7147f7beb5bcaf4762d8c11708b77f9df1bbe54eb98c2a8e50",
+            hex::encode(&parameters.outputs[0].script_pub_key)
+        );
+        assert!(parameters
+            .add_silent_payments(&secret_keys[1..], &[(address, BitcoinAmount(10_000))])
+            .is_err());
+    }
+
+    #[test]
+    fn test_taproot_input() {
+        let address = SilentPaymentAddress::<Bitcoin>::from_str(ADDRESS).unwrap();
+        // the public key of this secret key has an odd y coordinate, so it is
+        // negated when spent from a taproot output
+        let mut inputs = inputs();
+        inputs[1].secret_key = Some(SecretKey::parse(&[1; 32]).unwrap());
+        let output_keys =
+            silent_payment_output_keys(&inputs, core::slice::from_ref(&address)).unwrap();
+        assert_eq!(
+            "afcd01e5709a06c4a90848c075cf330bf95c2026e3d3aa30a5627fe6a6a944a1",
+            hex::encode(output_keys[0])
+        );
+        inputs[1].is_taproot = true;
+        let output_keys = silent_payment_output_keys(&inputs, &[address]).unwrap();
+        assert_eq!(
+            "88aeaf9104edca1b3a5667aa5f910ada43a73c21a5d5876b441e3ada55db25b3",
+            hex::encode(output_keys[0])
+        );
+    }
+}
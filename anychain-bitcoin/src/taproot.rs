@@ -0,0 +1,207 @@
+//!
+//! Taproot
+//!
+//! This module implements the pieces of BIP340 and BIP341 needed to spend a
+//! taproot output through its key path: tagged hashes, the output key tweak and
+//! Schnorr signatures over x-only public keys.
+//!
+
+pub use anychain_core::crypto::tagged_hash;
+use anychain_core::libsecp256k1::{curve::Scalar, PublicKey, SecretKey};
+
+/// Returns the point with the given x coordinate and an even y coordinate
+fn lift_x(x: &[u8; 32]) -> Option<PublicKey> {
+    PublicKey::parse_compressed(&[&[0x02], &x[..]].concat().try_into().unwrap()).ok()
+}
+
+/// Returns the x coordinate of 'public_key' and whether its y coordinate is odd
+fn x_only(public_key: &PublicKey) -> ([u8; 32], bool) {
+    let serialized = public_key.serialize_compressed();
+    (serialized[1..].try_into().unwrap(), serialized[0] == 0x03)
+}
+
+/// Returns the scalar of 'bytes' reduced modulo the curve order
+fn scalar(bytes: &[u8; 32]) -> Scalar {
+    let mut scalar = Scalar::default();
+    let _ = scalar.set_b32(bytes);
+    scalar
+}
+
+/// Returns the x-only public key of 'secret_key'
+pub fn x_only_public_key(secret_key: &SecretKey) -> [u8; 32] {
+    x_only(&PublicKey::from_secret_key(secret_key)).0
+}
+
+/// Returns the BIP341 tweak committing the x-only 'internal_key' to an
+/// optional script tree 'merkle_root'
+fn tap_tweak(internal_key: &[u8; 32], merkle_root: Option<&[u8; 32]>) -> Option<SecretKey> {
+    let data = [
+        &internal_key[..],
+        merkle_root.map_or(&[][..], |root| &root[..]),
+    ]
+    .concat();
+    SecretKey::parse(&tagged_hash("TapTweak", &data)).ok()
+}
+
+/// Returns the x-only taproot output key of the x-only 'internal_key' and the
+/// script tree 'merkle_root', or `None` if the key is not on the curve.
+/// Without a merkle root this is the BIP86 key path only output key.
+pub fn tweak_public_key(
+    internal_key: &[u8; 32],
+    merkle_root: Option<&[u8; 32]>,
+) -> Option<[u8; 32]> {
+    let mut output_key = lift_x(internal_key)?;
+    output_key
+        .tweak_add_assign(&tap_tweak(internal_key, merkle_root)?)
+        .ok()?;
+    Some(x_only(&output_key).0)
+}
+
+/// Returns the secret key of the taproot output key given by
+/// `tweak_public_key` for the internal key of 'secret_key'
+pub fn tweak_secret_key(
+    secret_key: &SecretKey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Option<SecretKey> {
+    let (internal_key, odd) = x_only(&PublicKey::from_secret_key(secret_key));
+    let scalar: Scalar = (*secret_key).into();
+    let mut secret_key = SecretKey::try_from(if odd { -scalar } else { scalar }).ok()?;
+    secret_key
+        .tweak_add_assign(&tap_tweak(&internal_key, merkle_root)?)
+        .ok()?;
+    Some(secret_key)
+}
+
+/// Returns the BIP340 Schnorr signature of the 32 byte 'message' by
+/// 'secret_key', using 'aux_rand' as auxiliary randomness for the nonce, or
+/// `None` if the nonce is zero
+pub fn schnorr_sign(
+    message: &[u8; 32],
+    secret_key: &SecretKey,
+    aux_rand: &[u8; 32],
+) -> Option<[u8; 64]> {
+    let (public_key, odd) = x_only(&PublicKey::from_secret_key(secret_key));
+    let d: Scalar = (*secret_key).into();
+    let d = if odd { -d } else { d };
+
+    let mut t = tagged_hash("BIP0340/aux", aux_rand);
+    for (t, d) in t.iter_mut().zip(d.b32()) {
+        *t ^= d;
+    }
+    let nonce = tagged_hash("BIP0340/nonce", &[&t[..], &public_key, message].concat());
+    // the nonce is zero with negligible probability
+    let k = SecretKey::try_from(scalar(&nonce)).ok()?;
+    let (r, odd) = x_only(&PublicKey::from_secret_key(&k));
+    let k: Scalar = k.into();
+    let k = if odd { -k } else { k };
+
+    let e = scalar(&tagged_hash(
+        "BIP0340/challenge",
+        &[&r[..], &public_key, message].concat(),
+    ));
+    let s = k + e * d;
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&r);
+    signature[32..].copy_from_slice(&s.b32());
+    Some(signature)
+}
+
+/// Returns true if 'signature' is a valid BIP340 Schnorr signature of the 32
+/// byte 'message' by the x-only 'public_key'
+pub fn schnorr_verify(message: &[u8; 32], signature: &[u8; 64], public_key: &[u8; 32]) -> bool {
+    let r: [u8; 32] = signature[..32].try_into().unwrap();
+    let s = match SecretKey::parse_slice(&signature[32..]) {
+        Ok(s) => s,
+        Err(_) => return false,
+    };
+    // P with an odd y coordinate is -P
+    let mut negated =
+        match PublicKey::parse_compressed(&[&[0x03], &public_key[..]].concat().try_into().unwrap())
+        {
+            Ok(negated) => negated,
+            Err(_) => return false,
+        };
+    let e = tagged_hash("BIP0340/challenge", &[&r[..], public_key, message].concat());
+    let e = match SecretKey::try_from(scalar(&e)) {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+    if negated.tweak_mul_assign(&e).is_err() {
+        return false;
+    }
+
+    // R = s⋅G - e⋅P must have an even y coordinate and the x coordinate r
+    match PublicKey::combine(&[PublicKey::from_secret_key(&s), negated]) {
+        Ok(point) => x_only(&point) == (r, false),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::hex;
+
+    fn bytes<const L: usize>(hex: &str) -> [u8; L] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_tagged_hash() {
+        assert_eq!(
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1",
+            hex::encode(tagged_hash("BIP0322-signed-message", b""))
+        );
+    }
+
+    #[test]
+    fn test_schnorr() {
+        // BIP340 test vector 1
+        let secret_key = SecretKey::parse(&bytes(
+            "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+        ))
+        .unwrap();
+        let public_key = x_only_public_key(&secret_key);
+        assert_eq!(
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            hex::encode(public_key)
+        );
+
+        let message = bytes("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89");
+        let aux_rand = bytes("0000000000000000000000000000000000000000000000000000000000000001");
+        let signature = schnorr_sign(&message, &secret_key, &aux_rand).unwrap();
+        assert_eq!(
+            "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+            hex::encode(signature)
+        );
+        assert!(schnorr_verify(&message, &signature, &public_key));
+
+        let mut tampered = signature;
+        tampered[63] ^= 1;
+        assert!(!schnorr_verify(&message, &tampered, &public_key));
+
+        // BIP340 test vector 5: the public key is not on the curve
+        let public_key = bytes("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34");
+        assert!(!schnorr_verify(&message, &signature, &public_key));
+    }
+
+    #[test]
+    fn test_tweak() {
+        // BIP86 first receiving key of the test mnemonic
+        let internal_key =
+            bytes("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115");
+        let output_key = tweak_public_key(&internal_key, None).unwrap();
+        assert_eq!(
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            hex::encode(output_key)
+        );
+
+        let secret_key = SecretKey::parse(&[1; 32]).unwrap();
+        let tweaked = tweak_secret_key(&secret_key, None).unwrap();
+        assert_eq!(
+            tweak_public_key(&x_only_public_key(&secret_key), None).unwrap(),
+            x_only_public_key(&tweaked)
+        );
+    }
+}
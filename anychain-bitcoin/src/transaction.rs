@@ -1,21 +1,28 @@
 use crate::{
-    BitcoinAddress, BitcoinAmount, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, WitnessProgram,
-    BASE32_DECODE_TABLE,
+    push_data, push_int, schnorr_verify, script_asm, script_num, script_type, tagged_hash,
+    BitcoinAddress, BitcoinAmount, BitcoinFormat, BitcoinNetwork, BitcoinPublicKey, OmniPayload,
+    Runestone, TokenData, Txid, WitnessProgram, CASH_ADDR_P2SH, TRUC_VERSION,
 };
 use anychain_core::{
-    crypto::checksum as double_sha2, libsecp256k1::Signature, Transaction, TransactionError,
-    TransactionId,
+    crypto::checksum as double_sha2,
+    encoding::{base58check_decode, segwit_decode},
+    libsecp256k1::{self, Message, SecretKey, Signature},
+    AddressError, AmountError, EntropyRng, EntropySource, MultiSignTransaction, SigningPayload,
+    Transaction, TransactionError, TransactionId,
 };
 use anychain_core::{
     hex,
-    no_std::{io::Read, *},
+    no_std::{
+        io::{self, Read, Write},
+        *,
+    },
     PublicKey,
 };
 
-use base58::FromBase58;
-use bech32::{u5, FromBase32};
 use core::{fmt, str::FromStr};
-use serde::Serialize;
+use rand::seq::SliceRandom;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
 pub use sha2::{Digest, Sha256};
 
 /// Returns the variable length integer of the given value.
@@ -33,17 +40,70 @@ pub fn variable_length_integer(value: u64) -> Result<Vec<u8>, TransactionError>
     }
 }
 
+/// Writes the variable length integer of the given value.
+pub fn write_variable_length_integer<W: Write>(
+    mut writer: W,
+    value: u64,
+) -> Result<(), TransactionError> {
+    match value {
+        0..=252 => writer.write_all(&[value as u8])?,
+        253..=65535 => {
+            writer.write_all(&[0xfd])?;
+            writer.write_all(&(value as u16).to_le_bytes())?;
+        }
+        65536..=4294967295 => {
+            writer.write_all(&[0xfe])?;
+            writer.write_all(&(value as u32).to_le_bytes())?;
+        }
+        _ => {
+            writer.write_all(&[0xff])?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the size of the variable length integer of the given value.
+pub fn variable_length_integer_size(value: u64) -> usize {
+    match value {
+        0..=252 => 1,
+        253..=65535 => 3,
+        65536..=4294967295 => 5,
+        _ => 9,
+    }
+}
+
 /// Decode the value of a variable length integer.
 /// https://en.bitcoin.it/wiki/Protocol_documentation#Variable_length_integer
-pub fn read_variable_length_integer<R: Read>(mut reader: R) -> Result<usize, TransactionError> {
+pub fn read_variable_length_integer<R: Read>(reader: R) -> Result<usize, TransactionError> {
+    read_compact_size(reader, false)
+}
+
+/// Fill 'buf' from the reader. In strict mode a short read is an error,
+/// otherwise the bytes that could not be read are left untouched.
+fn read_bytes<R: Read>(
+    mut reader: R,
+    buf: &mut [u8],
+    strict: bool,
+) -> Result<(), TransactionError> {
+    match strict {
+        true => reader.read_exact(buf)?,
+        false => {
+            let _ = reader.read(buf)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_compact_size<R: Read>(mut reader: R, strict: bool) -> Result<usize, TransactionError> {
     let mut flag = [0u8; 1];
-    let _ = reader.read(&mut flag)?;
+    read_bytes(&mut reader, &mut flag, strict)?;
 
     match flag[0] {
         0..=252 => Ok(flag[0] as usize),
         0xfd => {
             let mut size = [0u8; 2];
-            let _ = reader.read(&mut size)?;
+            read_bytes(&mut reader, &mut size, strict)?;
             match u16::from_le_bytes(size) {
                 s if s < 253 => Err(TransactionError::InvalidVariableSizeInteger(s as usize)),
                 s => Ok(s as usize),
@@ -51,7 +111,7 @@ pub fn read_variable_length_integer<R: Read>(mut reader: R) -> Result<usize, Tra
         }
         0xfe => {
             let mut size = [0u8; 4];
-            let _ = reader.read(&mut size)?;
+            read_bytes(&mut reader, &mut size, strict)?;
             match u32::from_le_bytes(size) {
                 s if s < 65536 => Err(TransactionError::InvalidVariableSizeInteger(s as usize)),
                 s => Ok(s as usize),
@@ -59,7 +119,7 @@ pub fn read_variable_length_integer<R: Read>(mut reader: R) -> Result<usize, Tra
         }
         _ => {
             let mut size = [0u8; 8];
-            let _ = reader.read(&mut size)?;
+            read_bytes(&mut reader, &mut size, strict)?;
             match u64::from_le_bytes(size) {
                 s if s < 4294967296 => {
                     Err(TransactionError::InvalidVariableSizeInteger(s as usize))
@@ -70,9 +130,170 @@ pub fn read_variable_length_integer<R: Read>(mut reader: R) -> Result<usize, Tra
     }
 }
 
+/// Limits enforced while deserializing a transaction, so that malicious input
+/// fails early instead of making the parser allocate without bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// The maximum number of inputs
+    pub max_inputs: usize,
+    /// The maximum number of outputs
+    pub max_outputs: usize,
+    /// The maximum length of a scriptSig or scriptPubKey
+    pub max_script_len: usize,
+    /// The maximum number of witness items of an input
+    pub max_witness_items: usize,
+    /// The maximum size of the serialized transaction
+    pub max_tx_size: usize,
+    /// Whether truncated fields and trailing bytes are rejected
+    pub strict: bool,
+}
+
+impl DecodeLimits {
+    /// The maximum size of a transaction, bounded by the block weight limit
+    pub const MAX_TX_SIZE: usize = 4_000_000;
+    /// The maximum size of a script that can be executed
+    pub const MAX_SCRIPT_SIZE: usize = 10_000;
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            // an input takes at least 41 bytes and an output at least 9 bytes
+            max_inputs: Self::MAX_TX_SIZE / 41,
+            max_outputs: Self::MAX_TX_SIZE / 9,
+            max_script_len: Self::MAX_SCRIPT_SIZE,
+            max_witness_items: 10_000,
+            max_tx_size: Self::MAX_TX_SIZE,
+            strict: false,
+        }
+    }
+}
+
+/// A reader that fails once more than a given number of bytes are requested
+struct LimitedReader<R: Read> {
+    reader: R,
+    remaining: usize,
+    exceeded: bool,
+}
+
+impl<R: Read> LimitedReader<R> {
+    fn new(reader: R, limit: usize) -> Self {
+        Self {
+            reader,
+            remaining: limit,
+            exceeded: false,
+        }
+    }
+
+    /// Accounts for 'size' more bytes, returning false once the limit is exceeded
+    fn consume(&mut self, size: usize) -> bool {
+        if size > self.remaining {
+            self.exceeded = true;
+            return false;
+        }
+        self.remaining -= size;
+        true
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        match self.consume(buf.len()) {
+            true => self.reader.read(buf),
+            // fail the same way as a reader that has run out of bytes
+            false => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        match self.consume(buf.len()) {
+            true => self.reader.read_exact(buf),
+            false => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+/// A writer feeding the bytes written into a hash function, so that a
+/// preimage can be hashed as it is serialized instead of being held in memory
+struct DigestWriter<'a, D: Digest>(&'a mut D);
+
+impl<D: Digest> Write for DigestWriter<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.0.update(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
+/// A deterministic random number generator producing the SHA256 hashes of
+/// its seed followed by a counter, which does not depend on the random number
+/// generators of the platform
+struct SeededRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    position: usize,
+}
+
+impl SeededRng {
+    fn new(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            counter: 0,
+            block: [0u8; 32],
+            position: 32,
+        }
+    }
+}
+
+impl EntropySource for SeededRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.position == 32 {
+                self.block = Sha256::new()
+                    .chain_update(self.seed)
+                    .chain_update(self.counter.to_le_bytes())
+                    .finalize()
+                    .into();
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+}
+
 pub struct BitcoinVector;
 
 impl BitcoinVector {
+    /// Read and output a vector with a variable length integer, failing early
+    /// if the length exceeds 'max' or, in strict mode, if the length is truncated
+    pub fn read_limited<R: Read, E, F>(
+        mut reader: R,
+        max: usize,
+        name: &str,
+        strict: bool,
+        func: F,
+    ) -> Result<Vec<E>, TransactionError>
+    where
+        F: Fn(&mut R) -> Result<E, TransactionError>,
+    {
+        let count = read_compact_size(&mut reader, strict)?;
+        if count > max {
+            return Err(TransactionError::LimitExceeded(name.into(), max, count));
+        }
+        (0..count).map(|_| func(&mut reader)).collect()
+    }
+
     /// Read and output a vector with a variable length integer
     pub fn read<R: Read, E, F>(mut reader: R, func: F) -> Result<Vec<E>, TransactionError>
     where
@@ -91,7 +312,7 @@ impl BitcoinVector {
         F: Fn(&mut R) -> Result<E, TransactionError>,
     {
         let count = read_variable_length_integer(&mut reader)?;
-        Ok((count, Self::read(reader, func)))
+        Ok((count, (0..count).map(|_| func(&mut reader)).collect()))
     }
 }
 
@@ -101,10 +322,7 @@ pub fn create_script_pub_key<N: BitcoinNetwork>(
 ) -> Result<Vec<u8>, TransactionError> {
     match address.format() {
         BitcoinFormat::P2PKH => {
-            let bytes = &address.to_string().from_base58()?;
-
-            // Trim the prefix (1st byte) and the checksum (last 4 bytes)
-            let pub_key_hash = bytes[1..(bytes.len() - 4)].to_vec();
+            let (_, pub_key_hash) = base58check_decode(&address.to_string())?;
 
             let mut script = vec![];
             script.push(Opcode::OP_DUP as u8);
@@ -116,16 +334,13 @@ pub fn create_script_pub_key<N: BitcoinNetwork>(
             Ok(script)
         }
         BitcoinFormat::P2WSH => {
-            let (_, data, _) = bech32::decode(&address.to_string())?;
-            let (v, script) = data.split_at(1);
-            let script = Vec::from_base32(script)?;
-            let mut script_bytes = vec![v[0].to_u8(), script.len() as u8];
+            let (_, version, script) = segwit_decode(&address.to_string())?;
+            let mut script_bytes = vec![version, script.len() as u8];
             script_bytes.extend(script);
             Ok(script_bytes)
         }
-        BitcoinFormat::P2SH_P2WPKH => {
-            let script_bytes = &address.to_string().from_base58()?;
-            let script_hash = script_bytes[1..(script_bytes.len() - 4)].to_vec();
+        BitcoinFormat::P2SH | BitcoinFormat::P2SH_P2WPKH => {
+            let (_, script_hash) = base58check_decode(&address.to_string())?;
 
             let mut script = vec![];
             script.push(Opcode::OP_HASH160 as u8);
@@ -135,42 +350,29 @@ pub fn create_script_pub_key<N: BitcoinNetwork>(
             Ok(script)
         }
         BitcoinFormat::Bech32 => {
-            let (_, data, _) = bech32::decode(&address.to_string())?;
-            let (v, program) = data.split_at(1);
-            let program = Vec::from_base32(program)?;
-            let mut program_bytes = vec![v[0].to_u8(), program.len() as u8];
+            let (_, version, program) = segwit_decode(&address.to_string())?;
+            let mut program_bytes = vec![version, program.len() as u8];
             program_bytes.extend(program);
 
             Ok(WitnessProgram::new(&program_bytes)?.to_scriptpubkey())
         }
         BitcoinFormat::CashAddr => {
-            let address = address.to_string();
-            let prefix = N::to_address_prefix(BitcoinFormat::CashAddr)?.prefix();
-
-            let start = if address.starts_with(&prefix) {
-                prefix.len() + 1
-            } else {
-                0
-            };
-
-            // trim the prefix and the checksum
-            let bytes_u8 = address.as_bytes()[start..address.len() - 8].to_vec();
-
-            let bytes_u5: Vec<u5> = bytes_u8
-                .iter()
-                .map(|byte| u5::try_from_u8(BASE32_DECODE_TABLE[*byte as usize] as u8).unwrap())
-                .collect();
-            let payload = Vec::<u8>::from_base32(&bytes_u5)?;
-            // trim the version byte, left the public key hash
-            let hash = payload[1..].to_vec();
+            let (version, hash) = address.cash_addr_payload()?;
 
             let mut script = vec![];
-            script.push(Opcode::OP_DUP as u8);
-            script.push(Opcode::OP_HASH160 as u8);
-            script.extend(variable_length_integer(hash.len() as u64)?);
-            script.extend(hash);
-            script.push(Opcode::OP_EQUALVERIFY as u8);
-            script.push(Opcode::OP_CHECKSIG as u8);
+            if version == CASH_ADDR_P2SH {
+                script.push(Opcode::OP_HASH160 as u8);
+                script.extend(variable_length_integer(hash.len() as u64)?);
+                script.extend(hash);
+                script.push(Opcode::OP_EQUAL as u8);
+            } else {
+                script.push(Opcode::OP_DUP as u8);
+                script.push(Opcode::OP_HASH160 as u8);
+                script.extend(variable_length_integer(hash.len() as u64)?);
+                script.extend(hash);
+                script.push(Opcode::OP_EQUALVERIFY as u8);
+                script.push(Opcode::OP_CHECKSIG as u8);
+            }
 
             Ok(script)
         }
@@ -181,30 +383,22 @@ pub fn create_script_pub_key<N: BitcoinNetwork>(
 /// output of a tx that spends 'amount' basic units of omni
 /// layer asset as indicated by 'property_id'.
 pub fn create_script_op_return(property_id: u32, amount: i64) -> Result<Vec<u8>, TransactionError> {
-    let mut script = vec![];
-
-    let msg_type: u16 = 0;
-    let msg_version: u16 = 0;
-
-    script.push(Opcode::OP_RETURN as u8);
-    script.push(Opcode::OP_PUSHBYTES_20 as u8);
-    script.push(b'o');
-    script.push(b'm');
-    script.push(b'n');
-    script.push(b'i');
-    script.append(&mut msg_version.to_be_bytes().to_vec());
-    script.append(&mut msg_type.to_be_bytes().to_vec());
-    script.append(&mut property_id.to_be_bytes().to_vec());
-    script.append(&mut amount.to_be_bytes().to_vec());
-
-    Ok(script)
+    OmniPayload::SimpleSend {
+        property_id,
+        amount,
+    }
+    .to_script()
 }
 
 /// Represents a Bitcoin signature hash
 /// https://en.bitcoin.it/wiki/OP_CHECKSIG
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum SignatureHash {
+    /// Signs all inputs and outputs, the implied sighash type of a 64 byte
+    /// taproot signature (BIP341).
+    SIGHASH_DEFAULT = 0x00,
+
     /// Signs all inputs and outputs.
     SIGHASH_ALL = 0x01,
 
@@ -242,6 +436,7 @@ pub enum SignatureHash {
 impl fmt::Display for SignatureHash {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            SignatureHash::SIGHASH_DEFAULT => write!(f, "SIGHASH_DEFAULT"),
             SignatureHash::SIGHASH_ALL => write!(f, "SIGHASH_ALL"),
             SignatureHash::SIGHASH_NONE => write!(f, "SIGHASH_NONE"),
             SignatureHash::SIGHASH_SINGLE => write!(f, "SIGHASH_SINGLE"),
@@ -277,8 +472,15 @@ impl fmt::Display for SignatureHash {
 }
 
 impl SignatureHash {
-    pub fn from_byte(byte: &u8) -> Self {
-        match byte {
+    /// Returns the signature hash of 'byte', failing if it is not a sighash type
+    pub fn from_byte(byte: &u8) -> Result<Self, TransactionError> {
+        Self::try_from_byte(*byte).ok_or(TransactionError::InvalidSignatureHash(*byte))
+    }
+
+    /// Returns the signature hash of 'byte', or None if it is not a sighash type
+    pub fn try_from_byte(byte: u8) -> Option<Self> {
+        let sighash = match byte {
+            0x00 => SignatureHash::SIGHASH_DEFAULT,
             0x01 => SignatureHash::SIGHASH_ALL,
             0x02 => SignatureHash::SIGHASH_NONE,
             0x03 => SignatureHash::SIGHASH_SINGLE,
@@ -291,8 +493,67 @@ impl SignatureHash {
             0xc1 => SignatureHash::SIGHASH_ALL_SIGHASH_FORKID_SIGHASH_ANYONECANPAY,
             0xc2 => SignatureHash::SIGHASH_NONE_SIGHASH_FORKID_SIGHASH_ANYONECANPAY,
             0xc3 => SignatureHash::SIGHASH_SINGLE_SIGHASH_FORKID_SIGHASH_ANYONECANPAY,
-            _ => panic!("Unrecognized signature hash"),
-        }
+            _ => return None,
+        };
+        Some(sighash)
+    }
+}
+
+/// Returns 'amount' as the json number of coins with 8 decimals Bitcoin Core
+/// renders amounts as, e.g. 0.00100000
+fn json_amount(amount: BitcoinAmount) -> Result<Value, TransactionError> {
+    let sign = if amount.0 < 0 { "-" } else { "" };
+    let satoshis = amount.0.unsigned_abs();
+    let number = format!(
+        "{}{}.{:08}",
+        sign,
+        satoshis / 100_000_000,
+        satoshis % 100_000_000
+    );
+    serde_json::Number::from_str(&number)
+        .map(Value::Number)
+        .map_err(|error| TransactionError::Message(format!("{}", error)))
+}
+
+/// The first byte of the annex, the optional last witness item of a taproot spend (BIP341)
+pub const TAPROOT_ANNEX_TAG: u8 = 0x50;
+
+/// Returns a witness item stored with its length prefix without the prefix
+pub(crate) fn witness_item_data(item: &[u8]) -> &[u8] {
+    let prefix = match item.first() {
+        Some(0xfd) => 3,
+        Some(0xfe) => 5,
+        Some(0xff) => 9,
+        _ => 1,
+    };
+    item.get(prefix..).unwrap_or_default()
+}
+
+/// Returns the annex of a witness stack stored with length prefixes. A stack
+/// of two or more items whose last item starts with 0x50 carries an annex.
+fn witness_annex(witnesses: &[Vec<u8>]) -> Option<&[u8]> {
+    match witnesses {
+        [_, .., last] => match witness_item_data(last) {
+            annex @ [TAPROOT_ANNEX_TAG, ..] => Some(annex),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the sighash type of the signature of a parsed witness stack. The
+/// annex is not part of the stack the scripts see and is skipped, so that a
+/// taproot key path spend is a single signature, whose sighash type is the
+/// default one if it is 64 bytes long.
+fn witness_sighash(witnesses: &[Vec<u8>]) -> Option<SignatureHash> {
+    let stack = match witness_annex(witnesses) {
+        Some(_) => &witnesses[..witnesses.len() - 1],
+        None => witnesses,
+    };
+    let signature = witness_item_data(stack.first()?);
+    match (stack.len(), signature.len()) {
+        (1, 64) => Some(SignatureHash::SIGHASH_DEFAULT),
+        _ => SignatureHash::try_from_byte(*signature.last()?),
     }
 }
 
@@ -307,6 +568,12 @@ pub enum Opcode {
     OP_EQUALVERIFY = 0x88,
     OP_RETURN = 0x6a,
     OP_PUSHBYTES_20 = 0x14,
+    #[cfg(feature = "fork-opcodes")]
+    OP_CHECKDATASIG = 0xba,
+    #[cfg(feature = "fork-opcodes")]
+    OP_CHECKDATASIGVERIFY = 0xbb,
+    #[cfg(feature = "fork-opcodes")]
+    OP_REVERSEBYTES = 0xbc,
 }
 
 impl fmt::Display for Opcode {
@@ -319,31 +586,81 @@ impl fmt::Display for Opcode {
             Opcode::OP_EQUALVERIFY => write!(f, "OP_EQUALVERIFY"),
             Opcode::OP_RETURN => write!(f, "OP_RETURN"),
             Opcode::OP_PUSHBYTES_20 => write!(f, "OP_PUSHBYTES_20"),
+            #[cfg(feature = "fork-opcodes")]
+            Opcode::OP_CHECKDATASIG => write!(f, "OP_CHECKDATASIG"),
+            #[cfg(feature = "fork-opcodes")]
+            Opcode::OP_CHECKDATASIGVERIFY => write!(f, "OP_CHECKDATASIGVERIFY"),
+            #[cfg(feature = "fork-opcodes")]
+            Opcode::OP_REVERSEBYTES => write!(f, "OP_REVERSEBYTES"),
         }
     }
 }
 
-/// Represents a Bitcoin transaction outpoint
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents a Bitcoin transaction outpoint, serialized in the `txid:vout`
+/// form
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Outpoint {
-    /// Hash of the previous transaction (32 bytes) (uses reversed hash order from Bitcoin RPC)
-    pub reverse_transaction_id: Vec<u8>,
+    /// The id of the previous transaction
+    pub txid: Txid,
     /// The index of certain utxo in the previous transaction (4 bytes)
     pub index: u32,
 }
 
 impl Outpoint {
     /// Returns a new Bitcoin transaction outpoint
-    pub fn new(reverse_transaction_id: Vec<u8>, index: u32) -> Self {
-        Self {
-            reverse_transaction_id,
-            index,
+    pub fn new(txid: Txid, index: u32) -> Self {
+        Self { txid, index }
+    }
+
+    /// Returns the null outpoint spent by coinbase inputs
+    pub fn null() -> Self {
+        Self::new(Txid::default(), u32::MAX)
+    }
+
+    /// Returns true if this is the null outpoint spent by coinbase inputs
+    pub fn is_null(&self) -> bool {
+        self.index == u32::MAX && self.txid.is_all_zeros()
+    }
+}
+
+impl FromStr for Outpoint {
+    type Err = TransactionError;
+
+    /// Returns the outpoint of a `txid:vout` string, the txid in RPC order
+    fn from_str(outpoint: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransactionError::Message(format!("invalid outpoint {}", outpoint));
+
+        let (txid, index) = outpoint.split_once(':').ok_or_else(invalid)?;
+        if !index.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
         }
+        let index = index.parse::<u32>().map_err(|_| invalid())?;
+        Ok(Self::new(Txid::from_hex_rpc(txid)?, index))
+    }
+}
+
+impl fmt::Display for Outpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.txid, self.index)
+    }
+}
+
+impl Serialize for Outpoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Outpoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let outpoint = String::deserialize(deserializer)?;
+        Self::from_str(&outpoint).map_err(D::Error::custom)
     }
 }
 
 /// Represents a Bitcoin transaction input
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct BitcoinTransactionInput<N: BitcoinNetwork> {
     /// The outpoint (36 bytes)
     pub outpoint: Outpoint,
@@ -354,29 +671,86 @@ pub struct BitcoinTransactionInput<N: BitcoinNetwork> {
     /// The address format of the utxo pointed by this input
     pub format: Option<BitcoinFormat>,
     /// The 'scriptPubkey' of the utxo pointed by this input
+    #[serde(with = "crate::serde_hex::option")]
     pub script_pub_key: Option<Vec<u8>>,
     /// An optional redeem script in case of an SegWit utxo
+    #[serde(with = "crate::serde_hex::option")]
     pub redeem_script: Option<Vec<u8>>,
     /// The transaction input script (variable size)
+    #[serde(with = "crate::serde_hex::bytes")]
     pub script_sig: Vec<u8>,
     /// The sequence number (4 bytes) (0xFFFFFFFF unless lock > 0)
     /// Also used in replace-by-fee (BIP 125)
-    pub sequence: Vec<u8>,
+    #[serde(with = "crate::serde_hex::array")]
+    pub sequence: [u8; 4],
     /// The signature hash (4 bytes) (used in signing raw transaction only)
     pub sighash_code: SignatureHash,
     /// The witnesses in a SegWit transaction
+    #[serde(with = "crate::serde_hex::vec")]
     pub witnesses: Vec<Vec<u8>>,
     /// If true, the input has been signed
     pub is_signed: bool,
     /// Provide more flexibility for multiple signatures (for P2WSH)
+    #[serde(with = "crate::serde_hex::option_flagged")]
     pub additional_witness: Option<(Vec<u8>, bool)>,
     /// Option for additional witness stack script args
+    #[serde(with = "crate::serde_hex::option")]
     pub witness_script_data: Option<Vec<u8>>,
 }
 
 impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
     const DEFAULT_SEQUENCE: [u8; 4] = [0xf2, 0xff, 0xff, 0xff];
 
+    /// The witness reserved value of the coinbase input (BIP141)
+    pub const WITNESS_RESERVED_VALUE: [u8; 32] = [0u8; 32];
+
+    /// Returns a coinbase input whose scriptSig starts with the block 'height'
+    /// as required by BIP34, followed by the arbitrary 'extra' data
+    pub fn coinbase(height: u32, extra: &[u8]) -> Result<Self, TransactionError> {
+        let script_sig = [push_int(height as i64), extra.to_vec()].concat();
+        if !(2..=100).contains(&script_sig.len()) {
+            return Err(TransactionError::Message(format!(
+                "coinbase script_sig must be 2 to 100 bytes long, found {}",
+                script_sig.len()
+            )));
+        }
+
+        Ok(Self {
+            outpoint: Outpoint::null(),
+            balance: None,
+            address: None,
+            format: None,
+            script_pub_key: None,
+            redeem_script: None,
+            script_sig,
+            sequence: u32::MAX.to_le_bytes(),
+            sighash_code: SignatureHash::SIGHASH_ALL,
+            witnesses: vec![],
+            is_signed: true,
+            additional_witness: None,
+            witness_script_data: None,
+        })
+    }
+
+    /// Returns true if this input spends the null outpoint of a coinbase
+    pub fn is_coinbase(&self) -> bool {
+        self.outpoint.is_null()
+    }
+
+    /// Returns the block height encoded in the scriptSig of a coinbase input (BIP34)
+    pub fn coinbase_height(&self) -> Option<u32> {
+        if !self.is_coinbase() {
+            return None;
+        }
+        let height = match self.script_sig.first()? {
+            0x00 => 0,
+            opcode @ 0x51..=0x60 => (opcode - 0x50) as i64,
+            size @ 0x01..=0x05 => script_num(self.script_sig.get(1..1 + *size as usize)?),
+            _ => return None,
+        };
+        u32::try_from(height).ok()
+    }
+
     /// Returns a new Bitcoin transaction input.
     pub fn new(
         transaction_id: Vec<u8>,
@@ -387,19 +761,14 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
         balance: Option<BitcoinAmount>,
         sighash: SignatureHash,
     ) -> Result<Self, TransactionError> {
-        if transaction_id.len() != 32 {
-            return Err(TransactionError::InvalidTransactionId(transaction_id.len()));
-        }
-
-        // Byte-wise reverse of computed SHA-256 hash values
-        // https://bitcoin.org/en/developer-reference#hash-byte-order
-        let mut reverse_transaction_id = transaction_id;
-        reverse_transaction_id.reverse();
+        // the transaction id is given in the byte order used by Bitcoin RPC
+        let txid = Txid::from_slice_rpc(&transaction_id)?;
 
         let format = match format {
             Some(f) => Some(f),
             None => Some(BitcoinFormat::P2PKH),
         };
+        Self::check_format(format.as_ref().unwrap())?;
 
         let (address, script_pub_key, redeem_script) = match public_key {
             Some(pk) => {
@@ -431,14 +800,14 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
         };
 
         Ok(Self {
-            outpoint: Outpoint::new(reverse_transaction_id, index),
+            outpoint: Outpoint::new(txid, index),
             balance,
             address,
             format,
             script_pub_key,
             redeem_script,
             script_sig: vec![],
-            sequence: BitcoinTransactionInput::<N>::DEFAULT_SEQUENCE.to_vec(),
+            sequence: BitcoinTransactionInput::<N>::DEFAULT_SEQUENCE,
             sighash_code: sighash,
             witnesses: vec![],
             is_signed: false,
@@ -471,17 +840,35 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
     }
 
     pub fn set_format(&mut self, format: BitcoinFormat) -> Result<(), TransactionError> {
+        Self::check_format(&format)?;
         self.format = Some(format);
         Ok(())
     }
 
+    /// Returns an error if inputs of 'format' are spent through a segwit path
+    /// on a network without segwit
+    fn check_format(format: &BitcoinFormat) -> Result<(), TransactionError> {
+        match format {
+            BitcoinFormat::P2SH_P2WPKH | BitcoinFormat::Bech32 | BitcoinFormat::P2WSH
+                if !N::SEGWIT =>
+            {
+                Err(TransactionError::Message(format!(
+                    "{} does not support segwit inputs of format {}",
+                    N::NAME,
+                    format
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn set_balance(&mut self, balance: i64) -> Result<(), TransactionError> {
         self.balance = Some(BitcoinAmount(balance));
         Ok(())
     }
 
     pub fn set_sequence(&mut self, sequence: u32) -> Result<(), TransactionError> {
-        self.sequence = u32::to_le_bytes(sequence).to_vec();
+        self.sequence = u32::to_le_bytes(sequence);
         Ok(())
     }
 
@@ -494,6 +881,32 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
         self.address.clone()
     }
 
+    /// Sets the scriptSig of a legacy P2SH input: the pushes of 'script_args',
+    /// the signatures and other data satisfying the redeem script in the
+    /// order the script consumes them, followed by the push of the redeem
+    /// script. A multisig redeem script needs an empty first argument, which
+    /// is pushed as OP_0.
+    pub fn set_p2sh_script_sig(&mut self, script_args: &[Vec<u8>]) -> Result<(), TransactionError> {
+        let redeem_script = match &self.redeem_script {
+            Some(script) => script,
+            None => {
+                return Err(TransactionError::Message(
+                    "Missing redeem script".to_string(),
+                ))
+            }
+        };
+        let mut script_sig: Vec<u8> = script_args.iter().flat_map(|arg| push_data(arg)).collect();
+        script_sig.extend(push_data(redeem_script));
+        self.script_sig = script_sig;
+        self.is_signed = true;
+        Ok(())
+    }
+
+    /// Returns the annex of the witness of a taproot spend, starting with 0x50
+    pub fn annex(&self) -> Option<&[u8]> {
+        witness_annex(&self.witnesses)
+    }
+
     pub fn get_format(&self) -> Option<BitcoinFormat> {
         self.format.clone()
     }
@@ -503,8 +916,7 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
     }
 
     pub fn get_sequence(&self) -> u32 {
-        let sequence: [u8; 4] = self.sequence.clone().try_into().unwrap();
-        u32::from_le_bytes(sequence)
+        u32::from_le_bytes(self.sequence)
     }
 
     pub fn get_sighash(&self) -> SignatureHash {
@@ -512,30 +924,55 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
     }
 
     /// Read and output a Bitcoin transaction input
-    pub fn read<R: Read>(mut reader: &mut R) -> Result<Self, TransactionError> {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, TransactionError> {
+        Self::read_with_limits(reader, &DecodeLimits::default())
+    }
+
+    /// Read and output a Bitcoin transaction input, enforcing the given limits
+    pub fn read_with_limits<R: Read>(
+        mut reader: &mut R,
+        limits: &DecodeLimits,
+    ) -> Result<Self, TransactionError> {
         let mut transaction_hash = [0u8; 32];
         let mut vin = [0u8; 4];
         let mut sequence = [0u8; 4];
 
-        let _ = reader.read(&mut transaction_hash)?;
-        let _ = reader.read(&mut vin)?;
-
-        let outpoint = Outpoint::new(transaction_hash.to_vec(), u32::from_le_bytes(vin));
-
-        let script_sig: Vec<u8> = BitcoinVector::read(&mut reader, |s| {
-            let mut byte = [0u8; 1];
-            let _ = s.read(&mut byte)?;
-            Ok(byte[0])
-        })?;
-
-        let _ = reader.read(&mut sequence)?;
+        read_bytes(&mut reader, &mut transaction_hash, limits.strict)?;
+        read_bytes(&mut reader, &mut vin, limits.strict)?;
 
-        let script_sig_len = read_variable_length_integer(&script_sig[..])?;
+        let outpoint = Outpoint::new(
+            Txid::from_bytes_internal(transaction_hash),
+            u32::from_le_bytes(vin),
+        );
 
-        let sighash_code = SignatureHash::from_byte(&match script_sig_len {
-            0 => 0x01,
-            length => script_sig[length],
-        });
+        let script_sig: Vec<u8> = BitcoinVector::read_limited(
+            &mut reader,
+            limits.max_script_len,
+            "script_sig",
+            limits.strict,
+            |s| {
+                let mut byte = [0u8; 1];
+                read_bytes(s, &mut byte, limits.strict)?;
+                Ok(byte[0])
+            },
+        )?;
+
+        read_bytes(&mut reader, &mut sequence, limits.strict)?;
+
+        // the sighash type is guessed from the byte ending the first push of
+        // the scriptSig, which is no signature for coinbase inputs, the redeem
+        // script pushes of P2SH-P2WPKH inputs and other scripts, so a byte of
+        // no sighash type falls back to SIGHASH_ALL
+        let sighash_code = match outpoint.is_null() {
+            true => SignatureHash::SIGHASH_ALL,
+            false => match read_variable_length_integer(&script_sig[..])? {
+                0 => SignatureHash::SIGHASH_ALL,
+                length => script_sig
+                    .get(length)
+                    .and_then(|byte| SignatureHash::try_from_byte(*byte))
+                    .unwrap_or(SignatureHash::SIGHASH_ALL),
+            },
+        };
 
         Ok(Self {
             outpoint,
@@ -545,7 +982,7 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
             script_pub_key: None,
             redeem_script: None,
             script_sig: script_sig.to_vec(),
-            sequence: sequence.to_vec(),
+            sequence,
             sighash_code,
             witnesses: vec![],
             is_signed: !script_sig.is_empty(),
@@ -556,11 +993,17 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
 
     /// Returns the serialized transaction input.
     pub fn serialize(&self, raw: bool) -> Result<Vec<u8>, TransactionError> {
-        let mut input = vec![];
-        input.extend(&self.outpoint.reverse_transaction_id);
-        input.extend(&self.outpoint.index.to_le_bytes());
+        let mut input = Vec::with_capacity(self.size_hint());
+        self.write_to(&mut input, raw)?;
+        Ok(input)
+    }
+
+    /// Writes the serialized transaction input to 'writer'.
+    pub fn write_to<W: Write>(&self, mut writer: W, raw: bool) -> Result<(), TransactionError> {
+        writer.write_all(self.outpoint.txid.as_bytes_internal())?;
+        writer.write_all(&self.outpoint.index.to_le_bytes())?;
         match raw {
-            true => input.extend(vec![0x00]),
+            true => writer.write_all(&[0x00])?,
             false => match self.script_sig.len() {
                 0 => match &self.address {
                     Some(address) => match address.format() {
@@ -571,30 +1014,87 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
                                     return Err(TransactionError::MissingOutpointScriptPublicKey)
                                 }
                             };
-                            input.extend(variable_length_integer(script_pub_key.len() as u64)?);
-                            input.extend(script_pub_key);
+                            write_variable_length_integer(
+                                &mut writer,
+                                script_pub_key.len() as u64,
+                            )?;
+                            writer.write_all(script_pub_key)?;
                         }
-                        _ => input.extend(vec![0x00]),
+                        _ => writer.write_all(&[0x00])?,
                     },
-                    None => input.extend(vec![0x00]),
+                    None => writer.write_all(&[0x00])?,
                 },
                 _ => {
-                    input.extend(variable_length_integer(self.script_sig.len() as u64)?);
-                    input.extend(&self.script_sig);
+                    write_variable_length_integer(&mut writer, self.script_sig.len() as u64)?;
+                    writer.write_all(&self.script_sig)?;
                 }
             },
         };
 
-        input.extend(&self.sequence);
+        writer.write_all(&self.sequence)?;
 
-        Ok(input)
+        Ok(())
     }
 
-    /// Insert 'signature' and 'public_key' into this input to make it signed
+    /// Returns an upper bound of the serialized size of the input, excluding witnesses
+    fn size_hint(&self) -> usize {
+        let script_len = match &self.script_pub_key {
+            Some(script) => self.script_sig.len().max(script.len()),
+            None => self.script_sig.len(),
+        };
+        36 + variable_length_integer_size(script_len as u64) + script_len + 4
+    }
+
+    /// Adds the signature of the input and 'public_key' to its scriptSig or
+    /// witness, after checking that the output spent by the input pays to
+    /// 'public_key'
     pub fn sign(
         &mut self,
         signature: Vec<u8>,
         public_key: Vec<u8>,
+    ) -> Result<(), TransactionError> {
+        self.check_public_key(&public_key)?;
+        self.sign_unchecked(signature, public_key)
+    }
+
+    /// Checks that 'public_key' hashes to the scriptPubKey of the output spent
+    /// by the input in the format of the input, so that a signature is never
+    /// inserted with a key that cannot spend the output. Inputs without a
+    /// known scriptPubKey pass the check.
+    pub fn check_public_key(&self, public_key: &[u8]) -> Result<(), TransactionError> {
+        let (script_pub_key, format) = match (&self.script_pub_key, &self.format) {
+            (Some(script), Some(format)) => (script, format),
+            _ => return Ok(()),
+        };
+        if !matches!(
+            format,
+            BitcoinFormat::P2PKH
+                | BitcoinFormat::CashAddr
+                | BitcoinFormat::P2SH_P2WPKH
+                | BitcoinFormat::Bech32
+        ) {
+            return Ok(());
+        }
+        let public_key =
+            BitcoinPublicKey::<N>::from_slice(public_key).map_err(AddressError::from)?;
+        let address = public_key.to_address(format)?;
+        match create_script_pub_key(&address)? == *script_pub_key {
+            true => Ok(()),
+            false => Err(TransactionError::Message(format!(
+                "the address {} of the public key does not match the scriptPubKey {} of the input",
+                address,
+                hex::encode(script_pub_key)
+            ))),
+        }
+    }
+
+    /// Adds the signature of the input and 'public_key' without checking the
+    /// public key against the output spent, for callers that have checked it
+    /// themselves
+    pub fn sign_unchecked(
+        &mut self,
+        signature: Vec<u8>,
+        public_key: Vec<u8>,
     ) -> Result<(), TransactionError> {
         let mut signature = Signature::parse_standard_slice(&signature)?
             .serialize_der()
@@ -602,6 +1102,16 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
             .to_vec();
         signature.push(self.sighash_code as u8);
 
+        let segwit = matches!(
+            self.get_format(),
+            Some(BitcoinFormat::P2SH_P2WPKH | BitcoinFormat::Bech32)
+        );
+        if segwit && public_key.len() != 33 {
+            return Err(TransactionError::Message(
+                "segwit inputs require a compressed public key".to_string(),
+            ));
+        }
+
         let signature = [variable_length_integer(signature.len() as u64)?, signature].concat();
         let public_key = [
             variable_length_integer(public_key.len() as u64)?,
@@ -609,7 +1119,7 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
         ]
         .concat();
 
-        match self.get_format().unwrap() {
+        match self.get_format().ok_or(TransactionError::MissingFormat)? {
             BitcoinFormat::P2PKH | BitcoinFormat::CashAddr => {
                 self.script_sig = [signature, public_key].concat()
             }
@@ -635,6 +1145,11 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
                     "P2WSH signing not supported".to_string(),
                 ))
             }
+            BitcoinFormat::P2SH => {
+                return Err(TransactionError::Message(
+                    "P2SH inputs are signed with set_p2sh_script_sig".to_string(),
+                ))
+            }
         }
 
         self.is_signed = true;
@@ -644,11 +1159,12 @@ impl<N: BitcoinNetwork> BitcoinTransactionInput<N> {
 }
 
 /// Represents a Bitcoin transaction output
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct BitcoinTransactionOutput {
     /// The amount (in Satoshi)
     pub amount: BitcoinAmount,
     /// The public key script
+    #[serde(with = "crate::serde_hex::bytes")]
     pub script_pub_key: Vec<u8>,
 }
 
@@ -678,94 +1194,415 @@ impl BitcoinTransactionOutput {
         Ok(data_output)
     }
 
+    /// Returns the data output carrying the omni message 'payload'
+    pub fn omni_payload_output(payload: &OmniPayload) -> Result<Self, TransactionError> {
+        Ok(BitcoinTransactionOutput {
+            amount: BitcoinAmount(0),
+            script_pub_key: payload.to_script()?,
+        })
+    }
+
+    /// Returns the data output carrying 'runestone'
+    pub fn runestone_output(runestone: &Runestone) -> Self {
+        BitcoinTransactionOutput {
+            amount: BitcoinAmount(0),
+            script_pub_key: runestone.to_script(),
+        }
+    }
+
     /// Read and output a Bitcoin transaction output
-    pub fn read<R: Read>(mut reader: &mut R) -> Result<Self, TransactionError> {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, TransactionError> {
+        Self::read_with_limits(reader, &DecodeLimits::default())
+    }
+
+    /// Read and output a Bitcoin transaction output, enforcing the given limits.
+    /// The amount is only required to be non-negative here, as the supply
+    /// bound depends on the network the transaction is read for.
+    pub fn read_with_limits<R: Read>(
+        mut reader: &mut R,
+        limits: &DecodeLimits,
+    ) -> Result<Self, TransactionError> {
         let mut amount = [0u8; 8];
-        let _ = reader.read(&mut amount)?;
+        read_bytes(&mut reader, &mut amount, limits.strict)?;
+
+        let script_pub_key: Vec<u8> = BitcoinVector::read_limited(
+            &mut reader,
+            limits.max_script_len,
+            "script_pub_key",
+            limits.strict,
+            |s| {
+                let mut byte = [0u8; 1];
+                read_bytes(s, &mut byte, limits.strict)?;
+                Ok(byte[0])
+            },
+        )?;
 
-        let script_pub_key: Vec<u8> = BitcoinVector::read(&mut reader, |s| {
-            let mut byte = [0u8; 1];
-            let _ = s.read(&mut byte)?;
-            Ok(byte[0])
+        let amount = u64::from_le_bytes(amount);
+        let amount = i64::try_from(amount).map_err(|_| {
+            AmountError::AmountOutOfBounds(amount.to_string(), i64::MAX.to_string())
         })?;
 
         Ok(Self {
-            amount: BitcoinAmount::from_satoshi(u64::from_le_bytes(amount) as i64)?,
+            amount: BitcoinAmount(amount),
             script_pub_key,
         })
     }
 
     /// Returns the serialized transaction output.
     pub fn serialize(&self) -> Result<Vec<u8>, TransactionError> {
-        let mut output = vec![];
-        output.extend(&self.amount.0.to_le_bytes());
-        output.extend(variable_length_integer(self.script_pub_key.len() as u64)?);
-        output.extend(&self.script_pub_key);
+        let mut output = Vec::with_capacity(self.size_hint());
+        self.write_to(&mut output)?;
         Ok(output)
     }
+
+    /// Writes the serialized transaction output to 'writer'.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), TransactionError> {
+        writer.write_all(&self.amount.0.to_le_bytes())?;
+        write_variable_length_integer(&mut writer, self.script_pub_key.len() as u64)?;
+        writer.write_all(&self.script_pub_key)?;
+        Ok(())
+    }
+
+    /// Returns the serialized size of the output
+    fn size_hint(&self) -> usize {
+        let script_len = self.script_pub_key.len() as u64;
+        8 + variable_length_integer_size(script_len) + script_len as usize
+    }
 }
 
-/// Represents an Bitcoin transaction id and witness transaction id
-/// https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#transaction-id
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct BitcoinTransactionId {
-    txid: Vec<u8>,
-    wtxid: Vec<u8>,
+/// A structured, serializable view of a Bitcoin transaction input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxInView {
+    /// The id of the previous transaction, in the byte order used by Bitcoin RPC
+    pub txid: String,
+    /// The index of the spent output in the previous transaction
+    pub vout: u32,
+    /// The sequence number
+    pub sequence: u32,
+    /// The signature hash type of the input
+    pub sighash: SignatureHash,
+    /// The hex encoded input script
+    pub script_sig: String,
+    /// The address of the spent output, if known
+    pub address: Option<String>,
+    /// The amount of the spent output, if known
+    pub amount: Option<BitcoinAmount>,
 }
 
-impl TransactionId for BitcoinTransactionId {}
+impl<N: BitcoinNetwork> From<&BitcoinTransactionInput<N>> for TxInView {
+    fn from(input: &BitcoinTransactionInput<N>) -> Self {
+        Self {
+            txid: input.outpoint.txid.to_string(),
+            vout: input.outpoint.index,
+            sequence: input.get_sequence(),
+            sighash: input.get_sighash(),
+            script_sig: hex::encode(&input.script_sig),
+            address: input.get_address().map(|address| address.to_string()),
+            amount: input.get_balance(),
+        }
+    }
+}
 
-impl fmt::Display for BitcoinTransactionId {
+impl fmt::Display for TxInView {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &hex::encode(&self.txid))
+        match serde_json::to_string(self) {
+            Ok(json) => write!(f, "{}", json),
+            Err(_) => Err(fmt::Error),
+        }
     }
 }
 
-/// Represents the Bitcoin transaction parameters
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct BitcoinTransactionParameters<N: BitcoinNetwork> {
-    /// The version number (4 bytes)
-    pub version: u32,
-    /// The transaction inputs
-    pub inputs: Vec<BitcoinTransactionInput<N>>,
-    /// The transaction outputs
-    pub outputs: Vec<BitcoinTransactionOutput>,
-    /// The lock time (4 bytes)
-    pub lock_time: u32,
-    /// An optional 2 bytes to indicate SegWit transactions
-    pub segwit_flag: bool,
+/// A structured, serializable view of a Bitcoin transaction output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxOutView {
+    /// The amount (in Satoshi)
+    pub amount: BitcoinAmount,
+    /// The hex encoded public key script
+    pub script_pub_key: String,
+    /// The address paid by the output, if it can be derived from the script
+    pub address: Option<String>,
+    /// The CashTokens carried by the output, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<TokenData>,
 }
 
-impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
-    /// Returns a BitcoinTransactionParameters given the inputs and outputs
-    pub fn new(
-        inputs: Vec<BitcoinTransactionInput<N>>,
-        outputs: Vec<BitcoinTransactionOutput>,
-    ) -> Result<Self, TransactionError> {
-        Ok(Self {
-            version: 2,
-            inputs,
+impl TxOutView {
+    /// Returns the view of an output, rendering its recipient as an address of network `N`.
+    pub fn new<N: BitcoinNetwork>(output: &BitcoinTransactionOutput) -> Self {
+        Self {
+            amount: output.amount,
+            script_pub_key: hex::encode(&output.script_pub_key),
+            address: BitcoinAddress::<N>::from_script_pub_key(output.locking_bytecode())
+                .ok()
+                .map(|address| address.to_string()),
+            token: output.token_data().ok().flatten(),
+        }
+    }
+}
+
+impl fmt::Display for TxOutView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match serde_json::to_string(self) {
+            Ok(json) => write!(f, "{}", json),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
+/// Represents an Bitcoin transaction id and witness transaction id
+/// https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#transaction-id
+///
+/// The txid of a transaction excludes its witnesses, while the wtxid commits
+/// to them; both are equal for a transaction without witnesses. Two ids are
+/// equal only if both their txids and wtxids are, so the ids of a segwit
+/// transaction with different witnesses differ. Compare the `txid()` of two
+/// ids to tell whether they identify the same transaction.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BitcoinTransactionId {
+    txid: Txid,
+    wtxid: Txid,
+}
+
+impl TransactionId for BitcoinTransactionId {}
+
+impl BitcoinTransactionId {
+    /// Returns the id of a transaction given its txid and wtxid
+    pub fn new(txid: Txid, wtxid: Txid) -> Self {
+        Self { txid, wtxid }
+    }
+
+    /// Returns the transaction id
+    pub fn txid(&self) -> Txid {
+        self.txid
+    }
+
+    /// Returns the witness transaction id
+    pub fn wtxid(&self) -> Txid {
+        self.wtxid
+    }
+
+    /// Returns true if the wtxid differs from the txid, which is the case for
+    /// a transaction with witnesses
+    pub fn has_witness(&self) -> bool {
+        self.txid != self.wtxid
+    }
+}
+
+impl FromStr for BitcoinTransactionId {
+    type Err = TransactionError;
+
+    /// Returns the id of a transaction without witnesses given its txid in
+    /// RPC order, the inverse of `Display`. The wtxid of such a transaction is
+    /// its txid.
+    fn from_str(txid: &str) -> Result<Self, Self::Err> {
+        let txid = Txid::from_hex_rpc(txid)?;
+        Ok(Self::new(txid, txid))
+    }
+}
+
+impl fmt::Display for BitcoinTransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.txid)
+    }
+}
+
+/// Represents the Bitcoin transaction parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BitcoinTransactionParameters<N: BitcoinNetwork> {
+    /// The version number (4 bytes)
+    pub version: u32,
+    /// The transaction inputs
+    pub inputs: Vec<BitcoinTransactionInput<N>>,
+    /// The transaction outputs
+    pub outputs: Vec<BitcoinTransactionOutput>,
+    /// The lock time (4 bytes)
+    pub lock_time: u32,
+    /// An optional 2 bytes to indicate SegWit transactions
+    pub segwit_flag: bool,
+}
+
+impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
+    /// Returns a BitcoinTransactionParameters given the inputs and outputs
+    pub fn new(
+        inputs: Vec<BitcoinTransactionInput<N>>,
+        outputs: Vec<BitcoinTransactionOutput>,
+    ) -> Result<Self, TransactionError> {
+        Ok(Self {
+            version: 2,
+            inputs,
             outputs,
             lock_time: 0,
             segwit_flag: false,
         })
     }
 
+    /// Returns the parameters with 'version' instead of version 2. Versions 1
+    /// to 3 are relayed, version 3 opting into the TRUC policy (BIP431), and
+    /// inputs with a relative lock time (BIP68) need version 2 or above.
+    pub fn with_version(mut self, version: u32) -> Result<Self, TransactionError> {
+        if !(1..=TRUC_VERSION).contains(&version) {
+            return Err(TransactionError::Message(format!(
+                "non-standard transaction version {}",
+                version
+            )));
+        }
+        // the relative lock time of an input is disabled by bit 31 of its sequence
+        if version < 2
+            && self
+                .inputs
+                .iter()
+                .any(|input| input.get_sequence() & (1 << 31) == 0)
+        {
+            return Err(TransactionError::Message(format!(
+                "relative lock times are ignored by transaction version {}",
+                version
+            )));
+        }
+        self.version = version;
+        Ok(self)
+    }
+
+    /// Returns the parameters with 'lock_time' instead of 0, failing if every
+    /// input has the final sequence 0xffffffff, which disables the lock time
+    pub fn with_lock_time(mut self, lock_time: u32) -> Result<Self, TransactionError> {
+        if lock_time != 0
+            && !self.inputs.is_empty()
+            && self
+                .inputs
+                .iter()
+                .all(|input| input.get_sequence() == u32::MAX)
+        {
+            return Err(TransactionError::Message(format!(
+                "lock time {} is disabled by the final sequences of the inputs",
+                lock_time
+            )));
+        }
+        self.lock_time = lock_time;
+        Ok(self)
+    }
+
+    /// Shuffles the inputs and the outputs with randomness drawn from
+    /// 'entropy', so that their order reveals neither which output is the
+    /// change nor how the inputs were selected. Fails once an input is signed,
+    /// as signatures commit to the order.
+    pub fn shuffle<E: EntropySource + ?Sized>(
+        &mut self,
+        entropy: &mut E,
+    ) -> Result<(), TransactionError> {
+        if self.inputs.iter().any(|input| input.is_signed) {
+            return Err(TransactionError::Message(
+                "cannot shuffle the inputs of a signed transaction".into(),
+            ));
+        }
+        let mut rng = EntropyRng(entropy);
+        self.inputs.shuffle(&mut rng);
+        self.outputs.shuffle(&mut rng);
+        Ok(())
+    }
+
+    /// Shuffles the inputs and the outputs with a random number generator
+    /// seeded with 'seed', so that the same seed always gives the same order
+    pub fn shuffle_with_seed(&mut self, seed: [u8; 32]) -> Result<(), TransactionError> {
+        self.shuffle(&mut SeededRng::new(seed))
+    }
+
+    /// Returns the parameters of a coinbase transaction at the given block
+    /// 'height' paying 'outputs'. If the block contains segwit transactions,
+    /// 'witness_root' is the merkle root of its wtxids, and the coinbase gets
+    /// the witness reserved value and the witness commitment output (BIP141).
+    pub fn coinbase(
+        height: u32,
+        extra: &[u8],
+        mut outputs: Vec<BitcoinTransactionOutput>,
+        witness_root: Option<[u8; 32]>,
+    ) -> Result<Self, TransactionError> {
+        let mut input = BitcoinTransactionInput::<N>::coinbase(height, extra)?;
+        let segwit_flag = witness_root.is_some();
+
+        if let Some(witness_root) = witness_root {
+            let reserved_value = BitcoinTransactionInput::<N>::WITNESS_RESERVED_VALUE;
+            let commitment = double_sha2(&[witness_root, reserved_value].concat());
+
+            let mut script_pub_key = vec![Opcode::OP_RETURN as u8, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+            script_pub_key.extend(commitment);
+            outputs.push(BitcoinTransactionOutput {
+                amount: BitcoinAmount(0),
+                script_pub_key,
+            });
+            input.witnesses = vec![[vec![0x20], reserved_value.to_vec()].concat()];
+        }
+
+        Ok(Self {
+            version: 2,
+            inputs: vec![input],
+            outputs,
+            lock_time: 0,
+            segwit_flag,
+        })
+    }
+
     /// Read and output the Bitcoin transaction parameters
-    pub fn read<R: Read>(mut reader: R) -> Result<Self, TransactionError> {
+    pub fn read<R: Read>(reader: R) -> Result<Self, TransactionError> {
+        Self::read_with_limits(reader, &DecodeLimits::default())
+    }
+
+    /// Read and output the Bitcoin transaction parameters, failing early if
+    /// the transaction exceeds any of the given limits
+    pub fn read_with_limits<R: Read>(
+        reader: R,
+        limits: &DecodeLimits,
+    ) -> Result<Self, TransactionError> {
+        let mut reader = LimitedReader::new(reader, limits.max_tx_size);
+        let parameters = match Self::read_limited(&mut reader, limits) {
+            Err(_) if reader.exceeded => {
+                return Err(TransactionError::LimitExceeded(
+                    "transaction size".into(),
+                    limits.max_tx_size,
+                    limits.max_tx_size + 1,
+                ))
+            }
+            result => result?,
+        };
+
+        if limits.strict {
+            let mut byte = [0u8; 1];
+            if reader.reader.read(&mut byte)? != 0 {
+                return Err(TransactionError::TrailingBytes);
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    fn read_limited<R: Read>(
+        mut reader: R,
+        limits: &DecodeLimits,
+    ) -> Result<Self, TransactionError> {
         let mut version = [0u8; 4];
-        let _ = reader.read(&mut version)?;
+        read_bytes(&mut reader, &mut version, limits.strict)?;
 
-        let mut inputs = BitcoinVector::read(&mut reader, BitcoinTransactionInput::<N>::read)?;
+        let mut inputs = BitcoinVector::read_limited(
+            &mut reader,
+            limits.max_inputs,
+            "inputs",
+            limits.strict,
+            |r| BitcoinTransactionInput::<N>::read_with_limits(r, limits),
+        )?;
 
         let segwit_flag = match inputs.is_empty() {
             true => {
                 let mut flag = [0u8; 1];
-                let _ = reader.read(&mut flag)?;
+                read_bytes(&mut reader, &mut flag, limits.strict)?;
                 match flag[0] {
                     1 => {
-                        inputs =
-                            BitcoinVector::read(&mut reader, BitcoinTransactionInput::<N>::read)?;
+                        inputs = BitcoinVector::read_limited(
+                            &mut reader,
+                            limits.max_inputs,
+                            "inputs",
+                            limits.strict,
+                            |r| BitcoinTransactionInput::<N>::read_with_limits(r, limits),
+                        )?;
                         true
                     }
                     _ => return Err(TransactionError::InvalidSegwitFlag(flag[0] as usize)),
@@ -774,22 +1611,58 @@ impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
             false => false,
         };
 
-        let outputs = BitcoinVector::read(&mut reader, BitcoinTransactionOutput::read)?;
+        let outputs = BitcoinVector::read_limited(
+            &mut reader,
+            limits.max_outputs,
+            "outputs",
+            limits.strict,
+            |r| BitcoinTransactionOutput::read_with_limits(r, limits),
+        )?;
+
+        // the outputs are bounded by the supply of the network, e.g. Dogecoin
+        // outputs may exceed 21M coins
+        let mut total: i64 = 0;
+        for output in &outputs {
+            total = total.saturating_add(output.amount.0);
+            if total > N::MAX_MONEY {
+                return Err(AmountError::AmountOutOfBounds(
+                    total.to_string(),
+                    N::MAX_MONEY.to_string(),
+                )
+                .into());
+            }
+        }
 
         if segwit_flag {
             for input in &mut inputs {
-                let witnesses: Vec<Vec<u8>> = BitcoinVector::read(&mut reader, |s| {
-                    let (size, witness) = BitcoinVector::read_witness(s, |sr| {
-                        let mut byte = [0u8; 1];
-                        let _ = sr.read(&mut byte)?;
-                        Ok(byte[0])
-                    })?;
-                    Ok([variable_length_integer(size as u64)?, witness?].concat())
-                })?;
+                let witnesses: Vec<Vec<u8>> = BitcoinVector::read_limited(
+                    &mut reader,
+                    limits.max_witness_items,
+                    "witness items",
+                    limits.strict,
+                    |s| {
+                        let witness = BitcoinVector::read_limited(
+                            s,
+                            limits.max_tx_size,
+                            "witness item",
+                            limits.strict,
+                            |sr| {
+                                let mut byte = [0u8; 1];
+                                read_bytes(sr, &mut byte, limits.strict)?;
+                                Ok(byte[0])
+                            },
+                        )?;
+                        Ok([variable_length_integer(witness.len() as u64)?, witness].concat())
+                    },
+                )?;
 
                 if !witnesses.is_empty() {
-                    input.sighash_code =
-                        SignatureHash::from_byte(&witnesses[0][&witnesses[0].len() - 1]);
+                    // the witness of a coinbase input is the witness reserved value
+                    if !input.is_coinbase() {
+                        if let Some(sighash) = witness_sighash(&witnesses) {
+                            input.sighash_code = sighash;
+                        }
+                    }
                     input.is_signed = true;
                 }
 
@@ -798,7 +1671,7 @@ impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
         }
 
         let mut lock_time = [0u8; 4];
-        let _ = reader.read(&mut lock_time)?;
+        read_bytes(&mut reader, &mut lock_time, limits.strict)?;
 
         let transaction_parameters = BitcoinTransactionParameters::<N> {
             version: u32::from_le_bytes(version),
@@ -813,7 +1686,8 @@ impl<N: BitcoinNetwork> BitcoinTransactionParameters<N> {
 }
 
 /// Represents a Bitcoin transaction
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct BitcoinTransaction<N: BitcoinNetwork> {
     /// The transaction parameters (version, inputs, outputs, lock_time, segwit_flag)
     pub parameters: BitcoinTransactionParameters<N>,
@@ -821,7 +1695,8 @@ pub struct BitcoinTransaction<N: BitcoinNetwork> {
 
 impl<N: BitcoinNetwork> fmt::Display for BitcoinTransaction<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(self.to_bytes().unwrap()))
+        let bytes = self.to_bytes().map_err(|_| fmt::Error)?;
+        write!(f, "{}", hex::encode(bytes))
     }
 }
 
@@ -849,86 +1724,222 @@ impl<N: BitcoinNetwork> Transaction for BitcoinTransaction<N> {
 
     /// Returns the transaction in bytes.
     fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
-        let mut transaction = self.parameters.version.to_le_bytes().to_vec();
-
-        if self.parameters.segwit_flag {
-            transaction.extend(vec![0x00, 0x01]);
-        }
-
-        transaction.extend(variable_length_integer(self.parameters.inputs.len() as u64)?);
-        let mut has_witness = false;
-        for input in &self.parameters.inputs {
-            if !has_witness {
-                has_witness = !input.witnesses.is_empty();
-            }
-            transaction.extend(input.serialize(!input.is_signed)?);
-        }
-
-        transaction.extend(variable_length_integer(
-            self.parameters.outputs.len() as u64
-        )?);
-        for output in &self.parameters.outputs {
-            transaction.extend(output.serialize()?);
-        }
-
-        if has_witness {
-            for input in &self.parameters.inputs {
-                match input.witnesses.len() {
-                    0 => transaction.extend(vec![0x00]),
-                    _ => {
-                        transaction.extend(variable_length_integer(input.witnesses.len() as u64)?);
-                        for witness in &input.witnesses {
-                            transaction.extend(witness);
-                        }
-                    }
-                };
-            }
-        }
-
-        transaction.extend(&self.parameters.lock_time.to_le_bytes());
-
+        let mut transaction = Vec::with_capacity(self.size_hint());
+        self.write_to(&mut transaction)?;
         Ok(transaction)
     }
 
     /// Returns the transaction id.
     fn to_transaction_id(&self) -> Result<Self::TransactionId, TransactionError> {
-        let mut txid = double_sha2(&self.to_transaction_bytes_without_witness()?).to_vec();
-        let mut wtxid = double_sha2(&self.to_bytes()?).to_vec();
+        let txid = double_sha2(&self.to_transaction_bytes_without_witness()?);
+        let wtxid = double_sha2(&self.to_bytes()?);
 
-        txid.reverse();
-        wtxid.reverse();
-
-        Ok(Self::TransactionId { txid, wtxid })
+        Ok(Self::TransactionId {
+            txid: Txid::from_slice_internal(&txid)?,
+            wtxid: Txid::from_slice_internal(&wtxid)?,
+        })
     }
 
+    /// Bitcoin transactions need a signature per input, inserted with
+    /// `MultiSignTransaction::apply_signature` instead.
     fn sign(&mut self, _signature: Vec<u8>, _recid: u8) -> Result<Vec<u8>, TransactionError> {
-        panic!(
-            "trait method sign() deprecated for bitcoin, use custom methods for signature\
-             insertion in its own impl block instead."
-        );
+        Err(TransactionError::Message(
+            "bitcoin transactions are signed per input, use apply_signature() instead".into(),
+        ))
+    }
+}
+
+impl<N: BitcoinNetwork> MultiSignTransaction for BitcoinTransaction<N> {
+    /// Returns the sighash of each input not signed yet
+    fn signing_payloads(&self) -> Result<Vec<SigningPayload>, TransactionError> {
+        (0..self.parameters.inputs.len())
+            .filter(|index| !self.parameters.inputs[*index].is_signed)
+            .map(|index| {
+                Ok(SigningPayload {
+                    index,
+                    digest: self.digest(index as u32)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Inserts the signature of input 'index': an ECDSA signature, or the
+    /// Schnorr signature of the tweaked key of a taproot input, checking that
+    /// the BIP86 output key of 'public_key' is the key of the output spent and
+    /// that the signature verifies against the sighash of the input
+    fn apply_signature(
+        &mut self,
+        index: usize,
+        signature: Vec<u8>,
+        public_key: &Self::PublicKey,
+    ) -> Result<(), TransactionError> {
+        let input = self.input(index as u32)?;
+        let output_key: Option<[u8; 32]> = match input.script_pub_key.as_deref() {
+            Some([0x51, 0x20, key @ ..]) => key.try_into().ok(),
+            _ => None,
+        };
+        if let Some(output_key) = output_key {
+            let signature: [u8; 64] = signature.as_slice().try_into().map_err(|_| {
+                TransactionError::Message(format!(
+                    "invalid schnorr signature length {}",
+                    signature.len()
+                ))
+            })?;
+            let address = BitcoinAddress::<N>::p2tr(public_key, None)?;
+            if create_script_pub_key(&address)? != [&[0x51, 0x20][..], &output_key].concat() {
+                return Err(TransactionError::Message(format!(
+                    "the taproot output key of the public key does not match input {}",
+                    index
+                )));
+            }
+            let digest: [u8; 32] = self
+                .digest(index as u32)?
+                .try_into()
+                .map_err(|_| TransactionError::Message("invalid taproot sighash length".into()))?;
+            if !schnorr_verify(&digest, &signature, &output_key) {
+                return Err(TransactionError::Message(
+                    "the signature does not verify under the taproot output key".into(),
+                ));
+            }
+            let input = self.input(index as u32)?;
+            let mut signature = signature.to_vec();
+            if input.sighash_code != SignatureHash::SIGHASH_DEFAULT {
+                signature.push(input.sighash_code as u8);
+            }
+            input.witnesses = vec![[vec![signature.len() as u8], signature].concat()];
+            input.is_signed = true;
+            self.parameters.segwit_flag = N::SEGWIT;
+            return Ok(());
+        }
+        input.sign(signature, public_key.serialize())?;
+        self.set_segwit()
     }
 }
 
 impl<N: BitcoinNetwork> BitcoinTransaction<N> {
+    /// Returns true if the transaction is a coinbase, spending only the null outpoint
+    pub fn is_coinbase(&self) -> bool {
+        matches!(&self.parameters.inputs[..], [input] if input.is_coinbase())
+    }
+
+    /// Returns a transaction given the transaction bytes, rejecting truncated
+    /// fields and trailing bytes.
+    pub fn from_bytes_strict(transaction: &[u8]) -> Result<Self, TransactionError> {
+        let limits = DecodeLimits {
+            strict: true,
+            ..Default::default()
+        };
+        Ok(Self {
+            parameters: BitcoinTransactionParameters::read_with_limits(transaction, &limits)?,
+        })
+    }
+
     /// Return the P2PKH hash preimage of the raw transaction.
     pub fn p2pkh_hash_preimage(
         &self,
         vin: usize,
         sighash: SignatureHash,
     ) -> Result<Vec<u8>, TransactionError> {
-        let mut preimage = self.parameters.version.to_le_bytes().to_vec();
-        preimage.extend(variable_length_integer(self.parameters.inputs.len() as u64)?);
-        for (index, input) in self.parameters.inputs.iter().enumerate() {
-            preimage.extend(input.serialize(index != vin)?);
-        }
-        preimage.extend(variable_length_integer(
-            self.parameters.outputs.len() as u64
-        )?);
-        for output in &self.parameters.outputs {
-            preimage.extend(output.serialize()?);
+        let mut preimage = Vec::with_capacity(self.size_hint());
+        self.write_p2pkh_preimage(&mut preimage, vin, sighash)?;
+        Ok(preimage)
+    }
+
+    /// Feeds the P2PKH hash preimage of input 'vin' into 'digest' as it is
+    /// serialized, without holding the preimage in memory
+    pub fn hash_p2pkh_preimage<D: Digest>(
+        &self,
+        vin: usize,
+        sighash: SignatureHash,
+        digest: &mut D,
+    ) -> Result<(), TransactionError> {
+        self.write_p2pkh_preimage(DigestWriter(digest), vin, sighash)
+    }
+
+    fn write_p2pkh_preimage<W: Write>(
+        &self,
+        mut writer: W,
+        vin: usize,
+        sighash: SignatureHash,
+    ) -> Result<(), TransactionError> {
+        writer.write_all(&self.parameters.version.to_le_bytes())?;
+        self.write_inputs_and_outputs(&mut writer, |index, _| index != vin)?;
+        writer.write_all(&self.parameters.lock_time.to_le_bytes())?;
+        writer.write_all(&(sighash as u32).to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Return the legacy hash preimage of the P2SH input 'vin', whose scriptCode
+    /// is the redeem script in place of the scriptPubKey of the output spent.
+    /// SIGHASH_NONE drops the outputs and SIGHASH_SINGLE keeps the outputs up
+    /// to 'vin', blanking those before it, both zeroing the sequences of the
+    /// other inputs. SIGHASH_ANYONECANPAY keeps input 'vin' only.
+    pub fn p2sh_hash_preimage(
+        &self,
+        vin: usize,
+        redeem_script: &[u8],
+        sighash: SignatureHash,
+    ) -> Result<Vec<u8>, TransactionError> {
+        if vin >= self.parameters.inputs.len() {
+            return Err(TransactionError::InvalidInputs(format!("input {}", vin)));
         }
-        preimage.extend(&self.parameters.lock_time.to_le_bytes());
-        preimage.extend(&(sighash as u32).to_le_bytes());
+        if sighash == SignatureHash::SIGHASH_DEFAULT || sighash as u32 & 0x40 != 0 {
+            return Err(TransactionError::UnsupportedPreimage(sighash.to_string()));
+        }
+
+        let anyone_can_pay = sighash as u32 & 0x80 != 0;
+        let base_type = sighash as u32 & 0x1f;
+        let none = base_type == SignatureHash::SIGHASH_NONE as u32;
+        let single = base_type == SignatureHash::SIGHASH_SINGLE as u32;
+        // the legacy algorithm signs the hash 1 for an input without a matching
+        // output, which has no preimage
+        if single && vin >= self.parameters.outputs.len() {
+            return Err(TransactionError::Message(format!(
+                "no output {} to sign with {}",
+                vin, sighash
+            )));
+        }
+
+        let mut preimage = Vec::with_capacity(self.size_hint() + redeem_script.len());
+        preimage.write_all(&self.parameters.version.to_le_bytes())?;
+        let inputs = match anyone_can_pay {
+            true => &self.parameters.inputs[vin..=vin],
+            false => &self.parameters.inputs[..],
+        };
+        write_variable_length_integer(&mut preimage, inputs.len() as u64)?;
+        for (index, input) in inputs.iter().enumerate() {
+            let signed = anyone_can_pay || index == vin;
+            preimage.write_all(input.outpoint.txid.as_bytes_internal())?;
+            preimage.write_all(&input.outpoint.index.to_le_bytes())?;
+            match signed {
+                true => {
+                    write_variable_length_integer(&mut preimage, redeem_script.len() as u64)?;
+                    preimage.write_all(redeem_script)?;
+                }
+                false => preimage.write_all(&[0x00])?,
+            }
+            match signed || !(none || single) {
+                true => preimage.write_all(&input.sequence)?,
+                false => preimage.write_all(&[0u8; 4])?,
+            }
+        }
+        let outputs = match (none, single) {
+            (true, _) => &self.parameters.outputs[..0],
+            (_, true) => &self.parameters.outputs[..=vin],
+            _ => &self.parameters.outputs[..],
+        };
+        write_variable_length_integer(&mut preimage, outputs.len() as u64)?;
+        for (index, output) in outputs.iter().enumerate() {
+            match single && index != vin {
+                // an output of amount -1 and an empty script
+                true => {
+                    preimage.write_all(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00])?
+                }
+                false => output.write_to(&mut preimage)?,
+            }
+        }
+        preimage.write_all(&self.parameters.lock_time.to_le_bytes())?;
+        preimage.write_all(&(sighash as u32).to_le_bytes())?;
         Ok(preimage)
     }
 
@@ -939,21 +1950,33 @@ impl<N: BitcoinNetwork> BitcoinTransaction<N> {
         vin: usize,
         sighash: SignatureHash,
     ) -> Result<Vec<u8>, TransactionError> {
-        let mut prev_outputs = vec![];
-        let mut prev_sequences = vec![];
-        let mut outputs = vec![];
-
-        for input in &self.parameters.inputs {
-            prev_outputs.extend(&input.outpoint.reverse_transaction_id);
-            prev_outputs.extend(&input.outpoint.index.to_le_bytes());
-            prev_sequences.extend(&input.sequence);
-        }
+        let mut preimage = vec![];
+        self.write_segwit_preimage(&mut preimage, vin, sighash)?;
+        Ok(preimage)
+    }
 
-        for output in &self.parameters.outputs {
-            outputs.extend(&output.serialize()?);
-        }
+    /// Feeds the SegWit hash preimage of input 'vin' into 'digest' as it is
+    /// serialized. The hashes of the outpoints, sequences and outputs it
+    /// commits to are computed one input or output at a time as well.
+    pub fn hash_segwit_preimage<D: Digest>(
+        &self,
+        vin: usize,
+        sighash: SignatureHash,
+        digest: &mut D,
+    ) -> Result<(), TransactionError> {
+        self.write_segwit_preimage(DigestWriter(digest), vin, sighash)
+    }
 
-        let input = &self.parameters.inputs[vin];
+    fn write_segwit_preimage<W: Write>(
+        &self,
+        mut writer: W,
+        vin: usize,
+        sighash: SignatureHash,
+    ) -> Result<(), TransactionError> {
+        let input = match self.parameters.inputs.get(vin) {
+            Some(input) => input,
+            None => return Err(TransactionError::InvalidInputs(format!("input {}", vin))),
+        };
         let format = match &input.address {
             Some(address) => address.format(),
             None => return Err(TransactionError::MissingOutpointAddress),
@@ -989,56 +2012,371 @@ impl<N: BitcoinNetwork> BitcoinTransaction<N> {
             script_code.push(Opcode::OP_EQUALVERIFY as u8);
             script_code.push(Opcode::OP_CHECKSIG as u8);
         }
-        let script_code = [
-            variable_length_integer(script_code.len() as u64)?,
-            script_code,
-        ]
-        .concat();
-        let hash_prev_outputs = double_sha2(&prev_outputs);
-        let hash_sequence = double_sha2(&prev_sequences);
-        let hash_outputs = double_sha2(&outputs);
         let balance = match &input.balance {
             Some(balance) => balance.0.to_le_bytes(),
             None => return Err(TransactionError::MissingOutpointAmount),
         };
 
+        let mut prev_outputs = Sha256::new();
+        let mut prev_sequences = Sha256::new();
+        let mut outputs = Sha256::new();
+        for input in &self.parameters.inputs {
+            prev_outputs.update(input.outpoint.txid.as_bytes_internal());
+            prev_outputs.update(input.outpoint.index.to_le_bytes());
+            prev_sequences.update(input.sequence);
+        }
+        for output in &self.parameters.outputs {
+            output.write_to(DigestWriter(&mut outputs))?;
+        }
+        // the double SHA256 of the data hashed so far
+        let hash256 = |sha256: Sha256| Sha256::digest(sha256.finalize());
+
+        writer.write_all(&self.parameters.version.to_le_bytes())?;
+        writer.write_all(&hash256(prev_outputs))?;
+        writer.write_all(&hash256(prev_sequences))?;
+        writer.write_all(input.outpoint.txid.as_bytes_internal())?;
+        writer.write_all(&input.outpoint.index.to_le_bytes())?;
+        write_variable_length_integer(&mut writer, script_code.len() as u64)?;
+        writer.write_all(&script_code)?;
+        writer.write_all(&balance)?;
+        writer.write_all(&input.sequence)?;
+        writer.write_all(&hash256(outputs))?;
+        writer.write_all(&self.parameters.lock_time.to_le_bytes())?;
+        writer.write_all(&(sighash as u32).to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Return the BIP143 hash preimage of input 'vin' signed by Bitcoin Cash
+    /// and eCash. The 'fork_id' takes the upper 24 bits of the sighash type,
+    /// which is zero on both chains, and the script code is the redeem script
+    /// of the input if any or its scriptPubKey.
+    /// https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/replay-protected-sighash.md
+    pub fn bip143_forkid_hash_preimage(
+        &self,
+        vin: usize,
+        sighash: SignatureHash,
+        fork_id: u32,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let input = match self.parameters.inputs.get(vin) {
+            Some(input) => input,
+            None => return Err(TransactionError::InvalidInputs(format!("input {}", vin))),
+        };
+        let script_code = match (&input.redeem_script, &input.script_pub_key) {
+            (Some(script), _) | (None, Some(script)) => script,
+            (None, None) => return Err(TransactionError::MissingOutpointScriptPublicKey),
+        };
+        let balance = match &input.balance {
+            Some(balance) => balance.0.to_le_bytes(),
+            None => return Err(TransactionError::MissingOutpointAmount),
+        };
+
+        let anyone_can_pay = sighash as u32 & 0x80 != 0;
+        let base_type = sighash as u32 & 0x1f;
+
+        let mut hash_prev_outputs = [0u8; 32].to_vec();
+        let mut hash_sequence = [0u8; 32].to_vec();
+        let mut hash_outputs = [0u8; 32].to_vec();
+
+        if !anyone_can_pay {
+            let mut prev_outputs = Vec::with_capacity(36 * self.parameters.inputs.len());
+            for input in &self.parameters.inputs {
+                prev_outputs.extend(input.outpoint.txid.as_bytes_internal());
+                prev_outputs.extend(&input.outpoint.index.to_le_bytes());
+            }
+            hash_prev_outputs = double_sha2(&prev_outputs);
+        }
+        if !anyone_can_pay
+            && base_type != SignatureHash::SIGHASH_NONE as u32
+            && base_type != SignatureHash::SIGHASH_SINGLE as u32
+        {
+            let mut sequences = Vec::with_capacity(4 * self.parameters.inputs.len());
+            for input in &self.parameters.inputs {
+                sequences.extend(&input.sequence);
+            }
+            hash_sequence = double_sha2(&sequences);
+        }
+        if base_type == SignatureHash::SIGHASH_SINGLE as u32 {
+            if let Some(output) = self.parameters.outputs.get(vin) {
+                hash_outputs = double_sha2(&output.serialize()?);
+            }
+        } else if base_type != SignatureHash::SIGHASH_NONE as u32 {
+            let mut outputs = vec![];
+            for output in &self.parameters.outputs {
+                output.write_to(&mut outputs)?;
+            }
+            hash_outputs = double_sha2(&outputs);
+        }
+
         let mut preimage = vec![];
         preimage.extend(&self.parameters.version.to_le_bytes());
         preimage.extend(hash_prev_outputs);
         preimage.extend(hash_sequence);
-        preimage.extend(&input.outpoint.reverse_transaction_id);
+        preimage.extend(input.outpoint.txid.as_bytes_internal());
         preimage.extend(&input.outpoint.index.to_le_bytes());
-        preimage.extend(&script_code);
+        preimage.extend(variable_length_integer(script_code.len() as u64)?);
+        preimage.extend(script_code);
         preimage.extend(&balance);
         preimage.extend(&input.sequence);
         preimage.extend(hash_outputs);
         preimage.extend(&self.parameters.lock_time.to_le_bytes());
-        preimage.extend(&(sighash as u32).to_le_bytes());
+        preimage.extend(&((fork_id << 8) | sighash as u32).to_le_bytes());
 
         Ok(preimage)
     }
 
-    /// Returns the transaction with the traditional serialization (no witness).
-    pub fn to_transaction_bytes_without_witness(&self) -> Result<Vec<u8>, TransactionError> {
-        let mut transaction = self.parameters.version.to_le_bytes().to_vec();
+    /// Return the BIP341 signature message of the key path spend of input
+    /// 'vin', prefixed with the epoch 0. The amounts and scriptPubKeys of all
+    /// the inputs are committed to, and so is the annex of the input if its
+    /// witness carries one.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message
+    pub fn taproot_hash_preimage(
+        &self,
+        vin: usize,
+        sighash: SignatureHash,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let mut preimage = vec![];
+        self.write_taproot_preimage(&mut preimage, vin, sighash)?;
+        Ok(preimage)
+    }
 
-        transaction.extend(variable_length_integer(self.parameters.inputs.len() as u64)?);
-        for input in &self.parameters.inputs {
-            transaction.extend(input.serialize(false)?);
+    /// Feeds the BIP341 signature message of input 'vin' into 'digest' as it
+    /// is serialized, e.g. into a SHA256 already fed the two hashes of the
+    /// `TapSighash` tag
+    pub fn hash_taproot_preimage<D: Digest>(
+        &self,
+        vin: usize,
+        sighash: SignatureHash,
+        digest: &mut D,
+    ) -> Result<(), TransactionError> {
+        self.write_taproot_preimage(DigestWriter(digest), vin, sighash)
+    }
+
+    fn write_taproot_preimage<W: Write>(
+        &self,
+        mut writer: W,
+        vin: usize,
+        sighash: SignatureHash,
+    ) -> Result<(), TransactionError> {
+        let input = match self.parameters.inputs.get(vin) {
+            Some(input) => input,
+            None => return Err(TransactionError::InvalidInputs(format!("input {}", vin))),
+        };
+        if sighash as u32 & 0x40 != 0 {
+            return Err(TransactionError::UnsupportedPreimage(sighash.to_string()));
         }
 
-        transaction.extend(variable_length_integer(
-            self.parameters.outputs.len() as u64
-        )?);
-        for output in &self.parameters.outputs {
-            transaction.extend(output.serialize()?);
+        let anyone_can_pay = sighash as u32 & 0x80 != 0;
+        let base_type = sighash as u32 & 0x03;
+
+        writer.write_all(&[0x00, sighash as u8])?;
+        writer.write_all(&self.parameters.version.to_le_bytes())?;
+        writer.write_all(&self.parameters.lock_time.to_le_bytes())?;
+
+        if !anyone_can_pay {
+            let mut prev_outputs = Sha256::new();
+            let mut amounts = Sha256::new();
+            let mut script_pub_keys = Sha256::new();
+            let mut sequences = Sha256::new();
+            for input in &self.parameters.inputs {
+                prev_outputs.update(input.outpoint.txid.as_bytes_internal());
+                prev_outputs.update(input.outpoint.index.to_le_bytes());
+                match &input.balance {
+                    Some(balance) => amounts.update(balance.0.to_le_bytes()),
+                    None => return Err(TransactionError::MissingOutpointAmount),
+                }
+                match &input.script_pub_key {
+                    Some(script) => {
+                        script_pub_keys.update(variable_length_integer(script.len() as u64)?);
+                        script_pub_keys.update(script);
+                    }
+                    None => return Err(TransactionError::MissingOutpointScriptPublicKey),
+                }
+                sequences.update(input.sequence);
+            }
+            writer.write_all(&prev_outputs.finalize())?;
+            writer.write_all(&amounts.finalize())?;
+            writer.write_all(&script_pub_keys.finalize())?;
+            writer.write_all(&sequences.finalize())?;
         }
+        if base_type != SignatureHash::SIGHASH_NONE as u32
+            && base_type != SignatureHash::SIGHASH_SINGLE as u32
+        {
+            let mut outputs = Sha256::new();
+            for output in &self.parameters.outputs {
+                output.write_to(DigestWriter(&mut outputs))?;
+            }
+            writer.write_all(&outputs.finalize())?;
+        }
+
+        // the spend type of a key path spend only flags the presence of the annex
+        let annex = input.annex();
+        writer.write_all(&[annex.is_some() as u8])?;
+
+        if anyone_can_pay {
+            let balance = match &input.balance {
+                Some(balance) => balance.0.to_le_bytes(),
+                None => return Err(TransactionError::MissingOutpointAmount),
+            };
+            let script_pub_key = match &input.script_pub_key {
+                Some(script) => script,
+                None => return Err(TransactionError::MissingOutpointScriptPublicKey),
+            };
+            writer.write_all(input.outpoint.txid.as_bytes_internal())?;
+            writer.write_all(&input.outpoint.index.to_le_bytes())?;
+            writer.write_all(&balance)?;
+            write_variable_length_integer(&mut writer, script_pub_key.len() as u64)?;
+            writer.write_all(script_pub_key)?;
+            writer.write_all(&input.sequence)?;
+        } else {
+            writer.write_all(&(vin as u32).to_le_bytes())?;
+        }
+        if let Some(annex) = annex {
+            let mut sha_annex = Sha256::new();
+            sha_annex.update(variable_length_integer(annex.len() as u64)?);
+            sha_annex.update(annex);
+            writer.write_all(&sha_annex.finalize())?;
+        }
+
+        if base_type == SignatureHash::SIGHASH_SINGLE as u32 {
+            match self.parameters.outputs.get(vin) {
+                Some(output) => {
+                    let mut sha_single_output = Sha256::new();
+                    output.write_to(DigestWriter(&mut sha_single_output))?;
+                    writer.write_all(&sha_single_output.finalize())?;
+                }
+                None => {
+                    return Err(TransactionError::InvalidInputs(format!(
+                        "SIGHASH_SINGLE of input {} without output {}",
+                        vin, vin
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
 
-        transaction.extend(&self.parameters.lock_time.to_le_bytes());
+    /// Signs the P2PKH input 'vin', in base58 or CashAddr format, of a Bitcoin
+    /// Cash or eCash transaction with 'secret_key'. The sighash type of the
+    /// input must carry SIGHASH_FORKID.
+    pub fn sign_p2pkh_forkid(
+        &mut self,
+        vin: usize,
+        secret_key: &SecretKey,
+        fork_id: u32,
+    ) -> Result<(), TransactionError> {
+        let input = self.input(vin as u32)?;
+        let sighash = input.sighash_code;
+        if sighash as u32 & 0x40 == 0 {
+            return Err(TransactionError::Message(format!(
+                "{} does not carry SIGHASH_FORKID",
+                sighash
+            )));
+        }
+        match input.get_format() {
+            Some(BitcoinFormat::P2PKH) | Some(BitcoinFormat::CashAddr) => {}
+            _ => return Err(TransactionError::UnsupportedPreimage("non-P2PKH".into())),
+        }
 
+        let preimage = self.bip143_forkid_hash_preimage(vin, sighash, fork_id)?;
+        let message = Message::parse_slice(&double_sha2(&preimage))?;
+        let (signature, _) = libsecp256k1::sign(&message, secret_key);
+        let public_key = libsecp256k1::PublicKey::from_secret_key(secret_key);
+
+        self.parameters.inputs[vin].sign(
+            signature.serialize().to_vec(),
+            public_key.serialize_compressed().to_vec(),
+        )
+    }
+
+    /// Returns the transaction with the traditional serialization (no witness).
+    pub fn to_transaction_bytes_without_witness(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut transaction = Vec::with_capacity(self.size_hint());
+        transaction.write_all(&self.parameters.version.to_le_bytes())?;
+        self.write_inputs_and_outputs(&mut transaction, |_, _| false)?;
+        transaction.write_all(&self.parameters.lock_time.to_le_bytes())?;
         Ok(transaction)
     }
 
+    /// Returns the weight of the transaction (BIP141): three times its size
+    /// without witnesses plus its full size.
+    pub fn weight(&self) -> Result<usize, TransactionError> {
+        Ok(self.to_transaction_bytes_without_witness()?.len() * 3 + self.to_bytes()?.len())
+    }
+
+    /// Returns the virtual size of the transaction, its weight divided by four
+    /// and rounded up.
+    pub fn vsize(&self) -> Result<usize, TransactionError> {
+        Ok(self.weight()?.div_ceil(4))
+    }
+
+    /// Writes the serialized transaction, including witnesses, to 'writer'.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), TransactionError> {
+        let parameters = &self.parameters;
+
+        writer.write_all(&parameters.version.to_le_bytes())?;
+        if parameters.segwit_flag {
+            writer.write_all(&[0x00, 0x01])?;
+        }
+        self.write_inputs_and_outputs(&mut writer, |_, input| !input.is_signed)?;
+
+        if parameters
+            .inputs
+            .iter()
+            .any(|input| !input.witnesses.is_empty())
+        {
+            for input in &parameters.inputs {
+                write_variable_length_integer(&mut writer, input.witnesses.len() as u64)?;
+                for witness in &input.witnesses {
+                    writer.write_all(witness)?;
+                }
+            }
+        }
+
+        writer.write_all(&parameters.lock_time.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Returns an upper bound of the serialized size of the transaction, used
+    /// to allocate the buffers of the serializers in one go.
+    pub fn size_hint(&self) -> usize {
+        let parameters = &self.parameters;
+        let inputs: usize = parameters
+            .inputs
+            .iter()
+            .map(|input| {
+                let witnesses: usize = input.witnesses.iter().map(Vec::len).sum();
+                input.size_hint() + 9 + witnesses
+            })
+            .sum();
+        let outputs: usize = parameters
+            .outputs
+            .iter()
+            .map(|output| output.size_hint())
+            .sum();
+        4 + 2 + 9 + inputs + 9 + outputs + 4
+    }
+
+    /// Writes the inputs and outputs of the transaction to 'writer', serializing
+    /// each input raw when 'raw' returns true for its index.
+    fn write_inputs_and_outputs<W: Write>(
+        &self,
+        mut writer: W,
+        raw: impl Fn(usize, &BitcoinTransactionInput<N>) -> bool,
+    ) -> Result<(), TransactionError> {
+        let parameters = &self.parameters;
+
+        write_variable_length_integer(&mut writer, parameters.inputs.len() as u64)?;
+        for (index, input) in parameters.inputs.iter().enumerate() {
+            input.write_to(&mut writer, raw(index, input))?;
+        }
+
+        write_variable_length_integer(&mut writer, parameters.outputs.len() as u64)?;
+        for output in &parameters.outputs {
+            output.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
+
     pub fn input(
         &mut self,
         index: u32,
@@ -1052,13 +2390,49 @@ impl<N: BitcoinNetwork> BitcoinTransaction<N> {
         Ok(&mut self.parameters.inputs[index as usize])
     }
 
-    pub fn digest(&mut self, index: u32) -> Result<Vec<u8>, TransactionError> {
-        let input = self.input(index)?;
+    pub fn digest(&self, index: u32) -> Result<Vec<u8>, TransactionError> {
+        let input = self.parameters.inputs.get(index as usize).ok_or_else(|| {
+            TransactionError::Message(format!(
+                "you are referring to input {}, which is out of bound",
+                index
+            ))
+        })?;
         let sighash = input.sighash_code;
+        // a segwit version 1 output with a 32 byte witness program pays to taproot
+        let is_taproot = matches!(
+            input.script_pub_key.as_deref(),
+            Some(script) if script.len() == 34 && script.starts_with(&[0x51, 0x20])
+        );
+        if is_taproot {
+            let preimage = self.taproot_hash_preimage(index as usize, sighash)?;
+            return Ok(tagged_hash("TapSighash", &preimage).to_vec());
+        }
         match input.get_address() {
             Some(addr) => {
                 let preimage = match addr.format() {
+                    _ if sighash as u32 & 0x40 != 0 => {
+                        self.bip143_forkid_hash_preimage(index as usize, sighash, 0)?
+                    }
                     BitcoinFormat::P2PKH => self.p2pkh_hash_preimage(index as usize, sighash)?,
+                    BitcoinFormat::P2SH => {
+                        match &self.parameters.inputs[index as usize].redeem_script {
+                            // the redeem script of a P2SH input is not a witness program
+                            Some(script) if !matches!(&script[..], [0x00, 0x14 | 0x20, ..]) => {
+                                self.p2sh_hash_preimage(index as usize, script, sighash)?
+                            }
+                            _ => return Err(TransactionError::InvalidInputs("P2SH".into())),
+                        }
+                    }
+                    BitcoinFormat::P2SH_P2WPKH => {
+                        match &self.parameters.inputs[index as usize].redeem_script {
+                            // a redeem script that is not a witness program is spent
+                            // by the legacy algorithm
+                            Some(script) if !matches!(&script[..], [0x00, 0x14 | 0x20, ..]) => {
+                                self.p2sh_hash_preimage(index as usize, script, sighash)?
+                            }
+                            _ => self.segwit_hash_preimage(index as usize, sighash)?,
+                        }
+                    }
                     _ => self.segwit_hash_preimage(index as usize, sighash)?,
                 };
                 Ok(double_sha2(&preimage).to_vec())
@@ -1067,7 +2441,204 @@ impl<N: BitcoinNetwork> BitcoinTransaction<N> {
         }
     }
 
+    /// Returns a structured view of each input of the transaction.
+    pub fn inputs(&self) -> Vec<TxInView> {
+        self.parameters.inputs.iter().map(TxInView::from).collect()
+    }
+
+    /// Returns a structured view of each output of the transaction.
+    pub fn outputs(&self) -> Vec<TxOutView> {
+        self.parameters
+            .outputs
+            .iter()
+            .map(TxOutView::new::<N>)
+            .collect()
+    }
+
+    /// Returns each input of the transaction formatted as a json string.
+    #[deprecated(note = "use `inputs()` for structured data or `describe()` for logging")]
+    pub fn get_inputs(&self) -> Vec<String> {
+        self.inputs()
+            .iter()
+            .map(|input| input.to_string())
+            .collect()
+    }
+
+    /// Returns each output of the transaction formatted as a json string.
+    #[deprecated(note = "use `outputs()` for structured data or `describe()` for logging")]
+    pub fn get_outputs(&self) -> Vec<String> {
+        self.outputs()
+            .iter()
+            .map(|output| output.to_string())
+            .collect()
+    }
+
+    /// Returns a multi-line rendering of the transaction for logs and support
+    /// tools: its ids and sizes, the format, amount, sighash and signing
+    /// status of each input, the address, amount and type of each output, and
+    /// the fee and fee rate if the amounts of all inputs are known.
+    pub fn describe(&self) -> Result<String, TransactionError> {
+        let parameters = &self.parameters;
+        let id = self.to_transaction_id()?;
+        let mut lines = vec![
+            format!("txid {}", id.txid()),
+            format!("wtxid {}", id.wtxid()),
+            format!(
+                "version {}, lock time {}, size {} B, {} vB, {}",
+                parameters.version,
+                parameters.lock_time,
+                self.to_bytes()?.len(),
+                self.vsize()?,
+                self.weight_units()?
+            ),
+        ];
+
+        lines.push(format!("{} inputs", parameters.inputs.len()));
+        for (index, input) in parameters.inputs.iter().enumerate() {
+            let format = input
+                .get_format()
+                .map_or("unknown format".to_string(), |format| format.to_string());
+            let amount = input
+                .get_balance()
+                .map_or("unknown amount".to_string(), |amount| {
+                    format!("{} sat", amount)
+                });
+            let address = input
+                .get_address()
+                .map_or("unknown address".to_string(), |address| address.to_string());
+            lines.push(format!(
+                "  #{} {} {} {} {}, sighash {}, sequence {:#010x}, {}",
+                index,
+                input.outpoint,
+                format,
+                address,
+                amount,
+                input.get_sighash(),
+                input.get_sequence(),
+                if input.is_signed {
+                    "signed"
+                } else {
+                    "unsigned"
+                }
+            ));
+        }
+
+        lines.push(format!("{} outputs", parameters.outputs.len()));
+        for (index, output) in self.outputs().iter().enumerate() {
+            let script_pub_key = &parameters.outputs[index].script_pub_key;
+            lines.push(format!(
+                "  #{} {} {} sat, {}",
+                index,
+                output.address.as_deref().unwrap_or("no address"),
+                output.amount,
+                script_type(script_pub_key)
+            ));
+        }
+
+        let spent = parameters
+            .inputs
+            .iter()
+            .map(|input| input.get_balance().map(|amount| amount.0))
+            .sum::<Option<i64>>();
+        let paid = parameters
+            .outputs
+            .iter()
+            .map(|output| output.amount.0)
+            .sum::<i64>();
+        lines.push(match spent.map(|spent| spent - paid) {
+            Some(fee) if fee >= 0 => {
+                format!("fee {} sat, {}", fee, self.fee_rate(BitcoinAmount(fee))?)
+            }
+            Some(fee) => format!("fee invalid, outputs exceed inputs by {} sat", -fee),
+            None => "fee unknown, the amounts of the inputs are missing".to_string(),
+        });
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns the transaction in the json shape produced by Bitcoin Core's `decoderawtransaction`.
+    pub fn to_json(&self) -> Result<Value, TransactionError> {
+        let transaction_id = self.to_transaction_id()?;
+        let size = self.to_bytes()?.len();
+        let weight = self.to_transaction_bytes_without_witness()?.len() * 3 + size;
+
+        let mut vin = vec![];
+        for input in &self.parameters.inputs {
+            let mut item = match self.is_coinbase() {
+                true => json!({
+                    "coinbase": hex::encode(&input.script_sig),
+                }),
+                false => json!({
+                    "txid": input.outpoint.txid.to_string(),
+                    "vout": input.outpoint.index,
+                    "scriptSig": {
+                        "asm": script_asm(&input.script_sig, true),
+                        "hex": hex::encode(&input.script_sig),
+                    },
+                }),
+            };
+            if !input.witnesses.is_empty() {
+                let mut witnesses = vec![];
+                for witness in &input.witnesses {
+                    // witness items are stored with their length prefix
+                    let data = witness_item_data(witness);
+                    if read_variable_length_integer(&witness[..])? != data.len() {
+                        return Err(TransactionError::Message(format!(
+                            "malformed witness item {}",
+                            hex::encode(witness)
+                        )));
+                    }
+                    witnesses.push(hex::encode(data));
+                }
+                item["txinwitness"] = json!(witnesses);
+            }
+            item["sequence"] = json!(input.get_sequence());
+            vin.push(item);
+        }
+
+        let vout = self
+            .parameters
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(n, output)| {
+                let locking_bytecode = output.locking_bytecode();
+                let mut script_pub_key = json!({
+                    "asm": script_asm(locking_bytecode, false),
+                    "hex": hex::encode(locking_bytecode),
+                    "type": script_type(locking_bytecode),
+                });
+                if let Ok(address) = BitcoinAddress::<N>::from_script_pub_key(locking_bytecode) {
+                    script_pub_key["address"] = json!(address.to_string());
+                }
+                let mut item = json!({
+                    "value": json_amount(output.amount)?,
+                    "n": n,
+                    "scriptPubKey": script_pub_key,
+                });
+                if let Ok(Some(token)) = output.token_data() {
+                    item["tokenData"] = token.to_json();
+                }
+                Ok(item)
+            })
+            .collect::<Result<Vec<Value>, TransactionError>>()?;
+
+        Ok(json!({
+            "txid": transaction_id.txid.to_string(),
+            "hash": transaction_id.wtxid.to_string(),
+            "version": self.parameters.version,
+            "size": size,
+            "vsize": weight.div_ceil(4),
+            "weight": weight,
+            "locktime": self.parameters.lock_time,
+            "vin": vin,
+            "vout": vout,
+        }))
+    }
+
     pub fn set_segwit(&mut self) -> Result<(), TransactionError> {
+        if !N::SEGWIT {
+            return Ok(());
+        }
         for input in self.parameters.inputs.clone() {
             if self.parameters.segwit_flag {
                 break;
@@ -1093,3 +2664,942 @@ impl<N: BitcoinNetwork> FromStr for BitcoinTransaction<N> {
         Self::from_bytes(&hex::decode(transaction)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bitcoin;
+
+    type N = Bitcoin;
+
+    const TXID: &str = "61d520ccb74288c96bc1a2b20ea1c0d5a704776dd0164a396efec3ea7040349d";
+    const INPUT_ADDRESS: &str = "1GUwicFwsZbdE3XyJYjmPryiiuTiK7mZgS";
+    const OUTPUT_ADDRESS: &str = "bc1qztqceddvavsxdgju4cz6z42tawu444m8uttmxg";
+
+    fn transaction() -> BitcoinTransaction<N> {
+        let input = BitcoinTransactionInput::<N>::new(
+            hex::decode(TXID).unwrap(),
+            1,
+            None,
+            None,
+            Some(BitcoinAddress::<N>::from_str(INPUT_ADDRESS).unwrap()),
+            Some(BitcoinAmount(150000)),
+            SignatureHash::SIGHASH_ALL,
+        )
+        .unwrap();
+        let output = BitcoinTransactionOutput::new(
+            BitcoinAddress::<N>::from_str(OUTPUT_ADDRESS).unwrap(),
+            BitcoinAmount(100000),
+        )
+        .unwrap();
+        let parameters = BitcoinTransactionParameters::<N>::new(vec![input], vec![output]).unwrap();
+        BitcoinTransaction::<N>::new(&parameters).unwrap()
+    }
+
+    #[test]
+    fn test_input_output_views() {
+        let transaction = transaction();
+
+        let inputs = transaction.inputs();
+        assert_eq!(1, inputs.len());
+        assert_eq!(TXID, inputs[0].txid);
+        assert_eq!(1, inputs[0].vout);
+        assert_eq!(0xfffffff2, inputs[0].sequence);
+        assert_eq!(SignatureHash::SIGHASH_ALL, inputs[0].sighash);
+        assert_eq!(Some(INPUT_ADDRESS.to_string()), inputs[0].address);
+        assert_eq!(Some(BitcoinAmount(150000)), inputs[0].amount);
+
+        let outputs = transaction.outputs();
+        assert_eq!(1, outputs.len());
+        assert_eq!(BitcoinAmount(100000), outputs[0].amount);
+        assert_eq!(
+            "001412c18cb5aceb2066a25cae05a1554bebb95ad767",
+            outputs[0].script_pub_key
+        );
+        assert_eq!(Some(OUTPUT_ADDRESS.to_string()), outputs[0].address);
+
+        #[allow(deprecated)]
+        {
+            assert_eq!(inputs[0].to_string(), transaction.get_inputs()[0]);
+            assert_eq!(outputs[0].to_string(), transaction.get_outputs()[0]);
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let description = transaction().describe().unwrap();
+        let lines: Vec<&str> = description.lines().collect();
+        assert_eq!(8, lines.len());
+        assert_eq!(
+            format!(
+                "  #0 {}:1 p2pkh {} 150000 sat, sighash SIGHASH_ALL, sequence 0xfffffff2, unsigned",
+                TXID, INPUT_ADDRESS
+            ),
+            lines[4]
+        );
+        assert_eq!(
+            format!("  #0 {} 100000 sat, witness_v0_keyhash", OUTPUT_ADDRESS),
+            lines[6]
+        );
+        assert!(lines[7].starts_with("fee 50000 sat, "));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let transaction = transaction();
+
+        let json = serde_json::to_string(&transaction).unwrap();
+        assert!(
+            json.contains("\"script_pub_key\":\"001412c18cb5aceb2066a25cae05a1554bebb95ad767\"")
+        );
+        assert!(json.contains(INPUT_ADDRESS));
+
+        let decoded: BitcoinTransaction<N> = serde_json::from_str(&json).unwrap();
+        assert_eq!(transaction, decoded);
+
+        // the sequence is exactly 4 bytes
+        let sequence = format!(
+            "\"sequence\":\"{}\"",
+            hex::encode(transaction.parameters.inputs[0].sequence)
+        );
+        assert!(json.contains(&sequence));
+        for invalid in ["\"sequence\":\"ffffff\"", "\"sequence\":\"ffffffffff\""] {
+            let json = json.replace(&sequence, invalid);
+            assert!(serde_json::from_str::<BitcoinTransaction<N>>(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_outpoint() {
+        let outpoint = Outpoint::from_str(&format!("{}:1", TXID)).unwrap();
+        assert_eq!(transaction().parameters.inputs[0].outpoint, outpoint);
+        assert_eq!(format!("{}:1", TXID), outpoint.to_string());
+
+        let json = serde_json::to_string(&outpoint).unwrap();
+        assert_eq!(format!("\"{}:1\"", TXID), json);
+        assert_eq!(outpoint, serde_json::from_str(&json).unwrap());
+
+        for invalid in [TXID, &format!("{}:", TXID), &format!("{}:+1", TXID), "00:1"] {
+            assert!(Outpoint::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        // a signed P2WPKH spend taken from BIP143
+        let raw = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+        let transaction = BitcoinTransaction::<N>::from_str(raw).unwrap();
+        let json = transaction.to_json().unwrap();
+
+        assert_eq!(
+            "e8151a2af31c368a35053ddd4bdb285a8595c769a3ad83e0fa02314a602d4609",
+            json["txid"]
+        );
+        assert_eq!(2, json["vin"].as_array().unwrap().len());
+        assert_eq!(
+            "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357",
+            json["vin"][1]["txinwitness"][1]
+        );
+        assert_eq!("pubkeyhash", json["vout"][0]["scriptPubKey"]["type"]);
+        assert_eq!(
+            "1Cu32FVupVCgHkMMRJdYJugxwo2Aprgk7H",
+            json["vout"][0]["scriptPubKey"]["address"]
+        );
+        assert_eq!(
+            "1.12340000",
+            serde_json::to_string(&json["vout"][0]["value"]).unwrap()
+        );
+        assert_eq!(17, json["locktime"]);
+
+        // a witness item whose length prefix exceeds its data
+        let mut malformed = transaction.clone();
+        malformed.parameters.inputs[1].witnesses = vec![vec![0x05]];
+        assert!(malformed.to_json().is_err());
+
+        // a coinbase input is rendered as its scriptSig and sequence
+        let mut coinbase = transaction;
+        coinbase.parameters.inputs.truncate(1);
+        coinbase.parameters.inputs[0].outpoint = Outpoint::null();
+        coinbase.parameters.inputs[0].script_sig = vec![0x03, 0x40, 0x0d, 0x03];
+        coinbase.parameters.outputs[0].amount = BitcoinAmount(-5);
+        let json = coinbase.to_json().unwrap();
+        assert_eq!(
+            json!({"coinbase": "03400d03", "sequence": 0xffffffee_u32}),
+            json["vin"][0]
+        );
+        assert_eq!(
+            "-0.00000005",
+            serde_json::to_string(&json["vout"][0]["value"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let bytes = transaction().to_bytes().unwrap();
+        let limits = DecodeLimits::default();
+        assert!(BitcoinTransactionParameters::<N>::read_with_limits(&bytes[..], &limits).is_ok());
+
+        // an input count of 2^32 must fail before anything is allocated
+        let mut malicious = 2u32.to_le_bytes().to_vec();
+        malicious.extend([0xff, 0, 0, 0, 0, 1, 0, 0, 0]);
+        assert!(matches!(
+            BitcoinTransactionParameters::<N>::read(&malicious[..]),
+            Err(TransactionError::LimitExceeded(name, _, 0x100000000)) if name == "inputs"
+        ));
+
+        let limits = DecodeLimits {
+            max_script_len: 21,
+            ..Default::default()
+        };
+        assert!(matches!(
+            BitcoinTransactionParameters::<N>::read_with_limits(&bytes[..], &limits),
+            Err(TransactionError::LimitExceeded(name, 21, 22)) if name == "script_pub_key"
+        ));
+
+        let limits = DecodeLimits {
+            max_tx_size: bytes.len() - 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            BitcoinTransactionParameters::<N>::read_with_limits(&bytes[..], &limits),
+            Err(TransactionError::LimitExceeded(name, _, _)) if name == "transaction size"
+        ));
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let bytes = transaction().to_bytes().unwrap();
+        let parsed = BitcoinTransaction::<N>::from_bytes_strict(&bytes).unwrap();
+        assert_eq!(bytes, parsed.to_bytes().unwrap());
+
+        let trailing = [bytes.clone(), vec![0]].concat();
+        assert!(BitcoinTransaction::<N>::from_bytes(&trailing).is_ok());
+        assert!(matches!(
+            BitcoinTransaction::<N>::from_bytes_strict(&trailing),
+            Err(TransactionError::TrailingBytes)
+        ));
+
+        // a truncated lock time silently parses as zero unless strict
+        let truncated = &bytes[..bytes.len() - 2];
+        assert!(BitcoinTransaction::<N>::from_bytes(truncated).is_ok());
+        assert!(BitcoinTransaction::<N>::from_bytes_strict(truncated).is_err());
+
+        // a script_sig running past the end of the input
+        let truncated = [&bytes[..41], &[0x10, 0xaa]].concat();
+        assert!(BitcoinTransaction::<N>::from_bytes_strict(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_script_sig_sighash() {
+        // the signed P2SH-P2WPKH example of BIP143, whose scriptSig pushes the
+        // redeem script, ending with the byte 0x89 of no sighash type
+        let raw = "01000000000101db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a5477010000001716001479091972186c449eb1ded22b78e40d009bdf0089feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac02473044022047ac8e878352d3ebbde1c94ce3a10d057c24175747116f8288e5d794d12d482f0220217f36a485cae903c713331d877c1f64677e3622ad4010726870540656fe9dcb012103ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a2687392040000";
+        let transaction = BitcoinTransaction::<N>::from_str(raw).unwrap();
+        assert_eq!(raw, hex::encode(transaction.to_bytes().unwrap()));
+        let input = &transaction.parameters.inputs[0];
+        assert_eq!(SignatureHash::SIGHASH_ALL, input.get_sighash());
+        assert_eq!(0xfffffffe, input.get_sequence());
+
+        // the signed native P2WPKH example of BIP143, whose first input is a
+        // P2PK spend with a SIGHASH_ALL signature
+        let raw = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+        let transaction = BitcoinTransaction::<N>::from_str(raw).unwrap();
+        assert_eq!(raw, hex::encode(transaction.to_bytes().unwrap()));
+        let inputs = &transaction.parameters.inputs;
+        assert_eq!(SignatureHash::SIGHASH_ALL, inputs[0].get_sighash());
+        assert_eq!(SignatureHash::SIGHASH_ALL, inputs[1].get_sighash());
+
+        assert!(SignatureHash::from_byte(&0x04).is_err());
+        assert_eq!(
+            SignatureHash::SIGHASH_ALL,
+            SignatureHash::from_byte(&0x01).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transaction_id() {
+        let raw = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+        let id = BitcoinTransaction::<N>::from_str(raw)
+            .unwrap()
+            .to_transaction_id()
+            .unwrap();
+        assert!(id.has_witness());
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(id, serde_json::from_str(&json).unwrap());
+
+        // the displayed txid parses back to the same txid, without the wtxid
+        let parsed = BitcoinTransactionId::from_str(&id.to_string()).unwrap();
+        assert_eq!(id.txid(), parsed.txid());
+        assert!(!parsed.has_witness());
+        assert_ne!(id, parsed);
+
+        let bytes = transaction()
+            .to_transaction_bytes_without_witness()
+            .unwrap();
+        let legacy = BitcoinTransaction::<N>::from_bytes(&bytes)
+            .unwrap()
+            .to_transaction_id()
+            .unwrap();
+        assert_eq!(legacy.txid(), legacy.wtxid());
+        assert_eq!(
+            legacy,
+            BitcoinTransactionId::from_str(&legacy.to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_taproot_annex() {
+        use crate::{bip322_to_sign, bip322_to_spend, schnorr_verify};
+
+        // the BIP322 signature of "Hello World" by a P2TR address, a key path
+        // spend of a virtual transaction with SIGHASH_ALL
+        let address = BitcoinAddress::<N>::from_str(
+            "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3",
+        )
+        .unwrap();
+        let script_pub_key = create_script_pub_key(&address).unwrap();
+        let signature = hex::decode("ddebd3eb25012ffa82937d9f25f9644e047bb2f472ab6c5089bbb53588ada2884cb5bcc53911f32d8dcf9548733b694d120db6a4e485194559e8d8fe668d269f01").unwrap();
+        let txid: [u8; 32] = double_sha2(&bip322_to_spend(&script_pub_key, b"Hello World"))
+            .try_into()
+            .unwrap();
+
+        let parse = |witness: &[Vec<u8>]| {
+            let mut transaction =
+                BitcoinTransaction::<N>::from_bytes(&bip322_to_sign(&txid, &[], witness)).unwrap();
+            let input = transaction.input(0).unwrap();
+            input.balance = Some(BitcoinAmount(0));
+            input.script_pub_key = Some(script_pub_key.clone());
+            transaction
+        };
+        let verify = |transaction: &mut BitcoinTransaction<N>| {
+            let sighash: [u8; 32] = transaction.digest(0).unwrap().try_into().unwrap();
+            schnorr_verify(
+                &sighash,
+                signature[..64].try_into().unwrap(),
+                script_pub_key[2..].try_into().unwrap(),
+            )
+        };
+
+        let mut transaction = parse(core::slice::from_ref(&signature));
+        assert_eq!(None, transaction.input(0).unwrap().annex());
+        assert!(verify(&mut transaction));
+
+        // the annex is neither the signature nor its sighash type, and it is
+        // committed to by the signature
+        let annex = vec![TAPROOT_ANNEX_TAG, 0x02];
+        let mut transaction = parse(&[signature.clone(), annex.clone()]);
+        let input = transaction.input(0).unwrap();
+        assert_eq!(Some(&annex[..]), input.annex());
+        assert_eq!(SignatureHash::SIGHASH_ALL, input.sighash_code);
+        assert!(!verify(&mut transaction));
+
+        // a 64 byte signature has the default sighash type
+        let transaction = parse(&[signature[..64].to_vec(), annex]);
+        assert_eq!(
+            SignatureHash::SIGHASH_DEFAULT,
+            transaction.parameters.inputs[0].sighash_code
+        );
+    }
+
+    #[test]
+    fn test_version_and_lock_time() {
+        let transaction = transaction();
+        let parameters = transaction
+            .parameters
+            .clone()
+            .with_version(1)
+            .unwrap()
+            .with_lock_time(840000)
+            .unwrap();
+        assert_eq!((1, 840000), (parameters.version, parameters.lock_time));
+
+        // parsed transactions keep their version and lock time
+        let bytes = BitcoinTransaction::<N>::new(&parameters)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        let parsed = BitcoinTransaction::<N>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            (1, 840000),
+            (parsed.parameters.version, parsed.parameters.lock_time)
+        );
+        assert_eq!(bytes, parsed.to_bytes().unwrap());
+
+        let parameters = transaction.parameters.clone();
+        assert!(parameters.clone().with_version(TRUC_VERSION).is_ok());
+        assert!(parameters.clone().with_version(0).is_err());
+        assert!(parameters.clone().with_version(4).is_err());
+
+        // a relative lock time of 10 blocks needs version 2
+        let mut relative = parameters.clone();
+        relative.inputs[0].set_sequence(10).unwrap();
+        assert!(relative.clone().with_version(1).is_err());
+        assert!(relative.with_version(2).is_ok());
+
+        let mut last = parameters;
+        last.inputs[0].set_sequence(u32::MAX).unwrap();
+        assert!(last.clone().with_lock_time(840000).is_err());
+        assert!(last.with_lock_time(0).is_ok());
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let input = |index| {
+            BitcoinTransactionInput::<N>::new(
+                hex::decode(TXID).unwrap(),
+                index,
+                None,
+                None,
+                Some(BitcoinAddress::<N>::from_str(INPUT_ADDRESS).unwrap()),
+                Some(BitcoinAmount(150000)),
+                SignatureHash::SIGHASH_ALL,
+            )
+            .unwrap()
+        };
+        let output = |amount| {
+            BitcoinTransactionOutput::new(
+                BitcoinAddress::<N>::from_str(OUTPUT_ADDRESS).unwrap(),
+                BitcoinAmount(amount),
+            )
+            .unwrap()
+        };
+        let parameters = BitcoinTransactionParameters::<N>::new(
+            (0..8).map(input).collect(),
+            (1..9).map(output).collect(),
+        )
+        .unwrap();
+
+        let mut shuffled = parameters.clone();
+        shuffled.shuffle_with_seed([7; 32]).unwrap();
+        let mut again = parameters.clone();
+        again.shuffle_with_seed([7; 32]).unwrap();
+        assert_eq!(shuffled, again);
+        assert_ne!(parameters, shuffled);
+
+        // the same inputs and outputs in another order
+        let mut indices: Vec<u32> = shuffled.inputs.iter().map(|i| i.outpoint.index).collect();
+        indices.sort();
+        assert_eq!((0..8).collect::<Vec<u32>>(), indices);
+        let mut amounts: Vec<i64> = shuffled.outputs.iter().map(|o| o.amount.0).collect();
+        amounts.sort();
+        assert_eq!((1..9).collect::<Vec<i64>>(), amounts);
+
+        let mut other = parameters.clone();
+        other.shuffle_with_seed([8; 32]).unwrap();
+        assert_ne!(shuffled, other);
+
+        shuffled.inputs[0].is_signed = true;
+        assert!(shuffled.shuffle_with_seed([7; 32]).is_err());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let raw = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+        let transaction = BitcoinTransaction::<N>::from_str(raw).unwrap();
+
+        let mut bytes = vec![];
+        transaction.write_to(&mut bytes).unwrap();
+        assert_eq!(raw, hex::encode(&bytes));
+        assert!(transaction.size_hint() >= bytes.len());
+
+        for value in [0, 252, 253, 65535, 65536, 4294967295, 4294967296] {
+            let mut buffer = vec![];
+            write_variable_length_integer(&mut buffer, value).unwrap();
+            assert_eq!(variable_length_integer(value).unwrap(), buffer);
+            assert_eq!(variable_length_integer_size(value), buffer.len());
+        }
+    }
+
+    #[test]
+    fn test_coinbase() {
+        // the coinbase of the genesis block, whose scriptSig predates BIP34
+        let genesis = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+        let transaction = BitcoinTransaction::<N>::from_str(genesis).unwrap();
+        assert!(transaction.is_coinbase());
+        assert_eq!(
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            transaction.to_transaction_id().unwrap().to_string()
+        );
+        assert_eq!(genesis, transaction.to_string());
+        assert!(!self::transaction().is_coinbase());
+
+        let output = BitcoinTransactionOutput::new(
+            BitcoinAddress::<N>::from_str(OUTPUT_ADDRESS).unwrap(),
+            BitcoinAmount(312500000),
+        )
+        .unwrap();
+        let parameters = BitcoinTransactionParameters::<N>::coinbase(
+            840000,
+            b"anychain",
+            vec![output],
+            Some([0x11; 32]),
+        )
+        .unwrap();
+        let coinbase = BitcoinTransaction::<N>::new(&parameters).unwrap();
+
+        let parsed = BitcoinTransaction::<N>::from_bytes(&coinbase.to_bytes().unwrap()).unwrap();
+        assert_eq!(coinbase.to_bytes().unwrap(), parsed.to_bytes().unwrap());
+        assert!(parsed.is_coinbase());
+        let input = &parsed.parameters.inputs[0];
+        assert_eq!(Some(840000), input.coinbase_height());
+        assert_eq!(vec![[vec![0x20], vec![0; 32]].concat()], input.witnesses);
+        assert_eq!(2, parsed.parameters.outputs.len());
+        assert_eq!(
+            "6a24aa21a9ed",
+            hex::encode(&parsed.parameters.outputs[1].script_pub_key[..6])
+        );
+
+        assert!(BitcoinTransactionInput::<N>::coinbase(1, &[]).is_err());
+        assert!(BitcoinTransactionInput::<N>::coinbase(1, &[0; 100]).is_err());
+    }
+
+    #[test]
+    fn test_bip143_forkid_hash_preimage() {
+        // the native P2WPKH example of BIP143, whose preimage matches the one of
+        // a fork id zero spend of the same P2PKH script code with SIGHASH_ALL
+        let raw = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+        let mut transaction = BitcoinTransaction::<N>::from_str(raw).unwrap();
+        let input = &mut transaction.parameters.inputs[1];
+        input.script_pub_key =
+            Some(hex::decode("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac").unwrap());
+        input.balance = Some(BitcoinAmount(600000000));
+
+        let preimage = transaction
+            .bip143_forkid_hash_preimage(1, SignatureHash::SIGHASH_ALL, 0)
+            .unwrap();
+        assert_eq!(
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670",
+            hex::encode(double_sha2(&preimage))
+        );
+
+        let preimage = transaction
+            .bip143_forkid_hash_preimage(1, SignatureHash::SIGHASH_ALL_SIGHASH_FORKID, 0)
+            .unwrap();
+        assert_eq!("41000000", hex::encode(&preimage[preimage.len() - 4..]));
+        assert!(transaction
+            .bip143_forkid_hash_preimage(0, SignatureHash::SIGHASH_ALL_SIGHASH_FORKID, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_sign_p2pkh_forkid() {
+        type N = crate::BitcoinCash;
+
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let address = BitcoinAddress::<N>::cash_addr(&BitcoinPublicKey::from_secp256k1_public_key(
+            public_key, true,
+        ))
+        .unwrap();
+        let input = BitcoinTransactionInput::<N>::new(
+            hex::decode(TXID).unwrap(),
+            0,
+            None,
+            None,
+            Some(address.clone()),
+            Some(BitcoinAmount(150000)),
+            SignatureHash::SIGHASH_ALL_SIGHASH_FORKID,
+        )
+        .unwrap();
+        let output = BitcoinTransactionOutput::new(address, BitcoinAmount(100000)).unwrap();
+        let parameters = BitcoinTransactionParameters::<N>::new(vec![input], vec![output]).unwrap();
+        let mut transaction = BitcoinTransaction::<N>::new(&parameters).unwrap();
+
+        let digest = transaction.digest(0).unwrap();
+        transaction.sign_p2pkh_forkid(0, &secret_key, 0).unwrap();
+
+        let script_sig = &transaction.parameters.inputs[0].script_sig;
+        let signature = &script_sig[1..1 + script_sig[0] as usize];
+        assert_eq!(Some(&0x41), signature.last());
+        let signature = Signature::parse_der(&signature[..signature.len() - 1]).unwrap();
+        assert!(libsecp256k1::verify(
+            &Message::parse_slice(&digest).unwrap(),
+            &signature,
+            &public_key
+        ));
+
+        transaction.parameters.inputs[0].sighash_code = SignatureHash::SIGHASH_ALL;
+        assert!(transaction.sign_p2pkh_forkid(0, &secret_key, 0).is_err());
+    }
+
+    #[test]
+    fn test_streaming_sighash() {
+        let mut transaction = transaction();
+        let sighash = SignatureHash::SIGHASH_ALL;
+        let mut digest = Sha256::new();
+        transaction
+            .hash_p2pkh_preimage(0, sighash, &mut digest)
+            .unwrap();
+        let preimage = transaction.p2pkh_hash_preimage(0, sighash).unwrap();
+        assert_eq!(Sha256::digest(&preimage), digest.finalize());
+
+        // the input of the fixture as a P2WPKH input
+        let address = BitcoinAddress::<N>::from_str(OUTPUT_ADDRESS).unwrap();
+        let input = transaction.input(0).unwrap();
+        input.script_pub_key = Some(create_script_pub_key(&address).unwrap());
+        input.address = Some(address);
+        let mut digest = Sha256::new();
+        transaction
+            .hash_segwit_preimage(0, sighash, &mut digest)
+            .unwrap();
+        assert_eq!(
+            transaction.digest(0).unwrap(),
+            Sha256::digest(digest.finalize()).to_vec()
+        );
+
+        // the tagged hash of the BIP341 signature message is the taproot sighash
+        let address = BitcoinAddress::<N>::from_str(
+            "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3",
+        )
+        .unwrap();
+        transaction.input(0).unwrap().script_pub_key =
+            Some(create_script_pub_key(&address).unwrap());
+        let tag = Sha256::digest(b"TapSighash");
+        let mut digest = Sha256::new().chain_update(tag).chain_update(tag);
+        transaction
+            .hash_taproot_preimage(0, SignatureHash::SIGHASH_DEFAULT, &mut digest)
+            .unwrap();
+        transaction.input(0).unwrap().sighash_code = SignatureHash::SIGHASH_DEFAULT;
+        assert_eq!(transaction.digest(0).unwrap(), digest.finalize().to_vec());
+        assert!(transaction
+            .hash_taproot_preimage(1, sighash, &mut Sha256::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_multi_sign() {
+        let mut transaction = transaction();
+        assert!(transaction.sign(vec![0; 64], 0).is_err());
+
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let public_key = BitcoinPublicKey::<N>::from_secp256k1_public_key(public_key, true);
+        let address = BitcoinAddress::<N>::p2pkh(&public_key).unwrap();
+        let input = transaction.input(0).unwrap();
+        input.address = Some(address.clone());
+        input.script_pub_key = Some(create_script_pub_key(&address).unwrap());
+
+        let payloads = transaction.signing_payloads().unwrap();
+        assert_eq!(1, payloads.len());
+        assert_eq!(transaction.digest(0).unwrap(), payloads[0].digest);
+        let signed = transaction
+            .sign_with(|payload| {
+                let message = Message::parse_slice(&payload.digest)?;
+                let (signature, _) = libsecp256k1::sign(&message, &secret_key);
+                Ok((signature.serialize().to_vec(), public_key.clone()))
+            })
+            .unwrap();
+        assert!(transaction.parameters.inputs[0].is_signed);
+        assert!(transaction.signing_payloads().unwrap().is_empty());
+        assert_eq!(transaction.to_bytes().unwrap(), signed);
+    }
+
+    #[test]
+    fn test_multi_sign_taproot() {
+        use crate::{schnorr_sign, tweak_secret_key};
+
+        let mut transaction = transaction();
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = BitcoinPublicKey::<N>::from_secp256k1_public_key(
+            libsecp256k1::PublicKey::from_secret_key(&secret_key),
+            true,
+        );
+        let address = BitcoinAddress::<N>::p2tr(&public_key, None).unwrap();
+        let input = transaction.input(0).unwrap();
+        input.address = Some(address.clone());
+        input.script_pub_key = Some(create_script_pub_key(&address).unwrap());
+        input.sighash_code = SignatureHash::SIGHASH_DEFAULT;
+
+        let digest: [u8; 32] = transaction.signing_payloads().unwrap()[0]
+            .digest
+            .clone()
+            .try_into()
+            .unwrap();
+        let tweaked = tweak_secret_key(&secret_key, None).unwrap();
+        let signature = schnorr_sign(&digest, &tweaked, &[0; 32]).unwrap();
+
+        // another key, a signature by the untweaked key and a signature of
+        // another message are rejected
+        let other = BitcoinPublicKey::<N>::from_secp256k1_public_key(
+            libsecp256k1::PublicKey::from_secret_key(&SecretKey::parse(&[0x22; 32]).unwrap()),
+            true,
+        );
+        assert!(transaction
+            .apply_signature(0, signature.to_vec(), &other)
+            .is_err());
+        let untweaked = schnorr_sign(&digest, &secret_key, &[0; 32]).unwrap();
+        assert!(transaction
+            .apply_signature(0, untweaked.to_vec(), &public_key)
+            .is_err());
+        let other_message = schnorr_sign(&[0x01; 32], &tweaked, &[0; 32]).unwrap();
+        assert!(transaction
+            .apply_signature(0, other_message.to_vec(), &public_key)
+            .is_err());
+        assert!(transaction
+            .apply_signature(0, signature[..63].to_vec(), &public_key)
+            .is_err());
+        assert!(!transaction.parameters.inputs[0].is_signed);
+
+        transaction
+            .apply_signature(0, signature.to_vec(), &public_key)
+            .unwrap();
+        let input = &transaction.parameters.inputs[0];
+        assert!(input.is_signed);
+        assert_eq!(vec![[&[64][..], &signature].concat()], input.witnesses);
+    }
+
+    #[test]
+    fn test_public_key_binding() {
+        let mut transaction = transaction();
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let digest = transaction.digest(0).unwrap();
+        let (signature, _) =
+            libsecp256k1::sign(&Message::parse_slice(&digest).unwrap(), &secret_key);
+
+        // the input pays to another key
+        let input = transaction.input(0).unwrap();
+        let public_key = public_key.serialize_compressed().to_vec();
+        assert!(input
+            .sign(signature.serialize().to_vec(), public_key.clone())
+            .is_err());
+        assert!(!input.is_signed);
+
+        // an input without a format has no known script to sign
+        let format = input.format.take();
+        assert!(matches!(
+            input.sign_unchecked(signature.serialize().to_vec(), public_key.clone()),
+            Err(TransactionError::MissingFormat)
+        ));
+        assert!(!input.is_signed);
+        input.format = format;
+
+        input
+            .sign_unchecked(signature.serialize().to_vec(), public_key.clone())
+            .unwrap();
+        assert!(input.is_signed);
+
+        // the key the input pays to passes the check
+        let address =
+            BitcoinAddress::<N>::p2pkh(&BitcoinPublicKey::from_slice(&public_key).unwrap())
+                .unwrap();
+        input.script_pub_key = Some(create_script_pub_key(&address).unwrap());
+        assert!(input.check_public_key(&public_key).is_ok());
+    }
+
+    #[test]
+    fn test_sign_p2sh() {
+        // with the scriptPubKey as its redeem script, the legacy P2SH algorithm
+        // is the P2PKH one
+        let transaction = transaction();
+        let script_pub_key = transaction.parameters.inputs[0]
+            .script_pub_key
+            .clone()
+            .unwrap();
+        assert_eq!(
+            transaction
+                .p2pkh_hash_preimage(0, SignatureHash::SIGHASH_ALL)
+                .unwrap(),
+            transaction
+                .p2sh_hash_preimage(0, &script_pub_key, SignatureHash::SIGHASH_ALL)
+                .unwrap()
+        );
+        assert!(transaction
+            .p2sh_hash_preimage(1, &script_pub_key, SignatureHash::SIGHASH_ALL)
+            .is_err());
+
+        // the legacy layouts of the sighash types, signing input 1 of 2
+        let mut transaction = transaction;
+        let mut input = transaction.parameters.inputs[0].clone();
+        input.outpoint.index = 2;
+        input.sequence = [0xfe, 0xff, 0xff, 0xff];
+        transaction.parameters.inputs.push(input);
+        let mut output = transaction.parameters.outputs[0].clone();
+        output.amount = BitcoinAmount(50000);
+        transaction.parameters.outputs.insert(0, output);
+        let parameters = &transaction.parameters;
+        let outpoint = |index: usize| {
+            let outpoint = &parameters.inputs[index].outpoint;
+            [
+                outpoint.txid.as_bytes_internal(),
+                &outpoint.index.to_le_bytes()[..],
+            ]
+            .concat()
+        };
+        let signed = [
+            outpoint(1),
+            push_data(&script_pub_key),
+            parameters.inputs[1].sequence.to_vec(),
+        ]
+        .concat();
+        let unsigned = |sequence: &[u8]| [&outpoint(0), &[0x00][..], sequence].concat();
+        let first = parameters.outputs[0].serialize().unwrap();
+        let output = parameters.outputs[1].serialize().unwrap();
+        let preimage = |inputs: &[&[u8]], outputs: &[&[u8]], sighash: u8| {
+            [
+                &parameters.version.to_le_bytes()[..],
+                &[inputs.len() as u8],
+                &inputs.concat(),
+                &[outputs.len() as u8],
+                &outputs.concat(),
+                &parameters.lock_time.to_le_bytes(),
+                &[sighash, 0, 0, 0],
+            ]
+            .concat()
+        };
+        let p2sh = |sighash| transaction.p2sh_hash_preimage(1, &script_pub_key, sighash);
+
+        let all = unsigned(&parameters.inputs[0].sequence);
+        assert_eq!(
+            preimage(&[&all, &signed], &[&first, &output], 0x01),
+            p2sh(SignatureHash::SIGHASH_ALL).unwrap()
+        );
+        assert_eq!(
+            preimage(&[&unsigned(&[0; 4]), &signed], &[], 0x02),
+            p2sh(SignatureHash::SIGHASH_NONE).unwrap()
+        );
+        let blank = [&[0xff; 8][..], &[0x00]].concat();
+        assert_eq!(
+            preimage(&[&unsigned(&[0; 4]), &signed], &[&blank, &output], 0x03),
+            p2sh(SignatureHash::SIGHASH_SINGLE).unwrap()
+        );
+        assert_eq!(
+            preimage(&[&signed], &[&first, &output], 0x81),
+            p2sh(SignatureHash::SIGHASH_ALL_SIGHASH_ANYONECANPAY).unwrap()
+        );
+        assert_eq!(
+            preimage(&[&signed], &[], 0x82),
+            p2sh(SignatureHash::SIGHASH_NONE_SIGHASH_ANYONECANPAY).unwrap()
+        );
+        assert_eq!(
+            preimage(&[&signed], &[&blank, &output], 0x83),
+            p2sh(SignatureHash::SIGHASH_SINGLE_SIGHASH_ANYONECANPAY).unwrap()
+        );
+
+        // SIGHASH_SINGLE without a matching output, and the sighash types of
+        // other algorithms
+        transaction.parameters.outputs.pop();
+        let p2sh = |sighash| transaction.p2sh_hash_preimage(1, &script_pub_key, sighash);
+        assert!(p2sh(SignatureHash::SIGHASH_SINGLE).is_err());
+        assert!(p2sh(SignatureHash::SIGHASH_DEFAULT).is_err());
+        assert!(p2sh(SignatureHash::SIGHASH_ALL_SIGHASH_FORKID).is_err());
+
+        // a 1-of-1 multisig redeem script
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let redeem_script = [
+            &[0x51, 0x21][..],
+            &public_key.serialize_compressed(),
+            &[0x51, 0xae],
+        ]
+        .concat();
+        let address = BitcoinAddress::<N>::p2sh(&redeem_script).unwrap();
+        let mut input = BitcoinTransactionInput::<N>::new(
+            hex::decode(TXID).unwrap(),
+            0,
+            None,
+            Some(BitcoinFormat::P2SH),
+            Some(address.clone()),
+            None,
+            SignatureHash::SIGHASH_ALL,
+        )
+        .unwrap();
+        input.set_redeem_script(redeem_script.clone()).unwrap();
+        let output = BitcoinTransactionOutput::new(address, BitcoinAmount(100000)).unwrap();
+        let parameters = BitcoinTransactionParameters::<N>::new(vec![input], vec![output]).unwrap();
+        let mut transaction = BitcoinTransaction::<N>::new(&parameters).unwrap();
+
+        let digest = transaction.digest(0).unwrap();
+        let preimage = transaction
+            .p2sh_hash_preimage(0, &redeem_script, SignatureHash::SIGHASH_ALL)
+            .unwrap();
+        assert_eq!(double_sha2(&preimage), digest);
+
+        let (signature, _) =
+            libsecp256k1::sign(&Message::parse_slice(&digest).unwrap(), &secret_key);
+        let signature = [signature.serialize_der().as_ref(), &[0x01]].concat();
+        let input = transaction.input(0).unwrap();
+        input
+            .set_p2sh_script_sig(&[vec![], signature.clone()])
+            .unwrap();
+        assert_eq!(
+            [
+                &[0x00][..],
+                &push_data(&signature),
+                &[redeem_script.len() as u8],
+                &redeem_script
+            ]
+            .concat(),
+            input.script_sig
+        );
+        assert!(input.is_signed);
+    }
+
+    #[test]
+    fn test_segwit_support() {
+        type N = crate::Dogecoin;
+
+        let new_input = |format| {
+            BitcoinTransactionInput::<N>::new(
+                hex::decode(TXID).unwrap(),
+                0,
+                None,
+                Some(format),
+                None,
+                None,
+                SignatureHash::SIGHASH_ALL,
+            )
+        };
+        assert!(new_input(BitcoinFormat::P2PKH).is_ok());
+        assert!(new_input(BitcoinFormat::Bech32).is_err());
+        assert!(new_input(BitcoinFormat::P2SH_P2WPKH).is_err());
+
+        let mut input = new_input(BitcoinFormat::P2PKH).unwrap();
+        assert!(input.set_format(BitcoinFormat::P2WSH).is_err());
+        assert!(transaction().parameters.inputs[0]
+            .clone()
+            .set_format(BitcoinFormat::Bech32)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_output_supply_bound() {
+        use crate::{Bitcoin, Dogecoin};
+
+        let raw = |satoshis: u64| {
+            [
+                &1u32.to_le_bytes()[..],
+                &[0x01],
+                &hex::decode(TXID).unwrap(),
+                &[0x00; 4],
+                &[0x00],
+                &[0xff; 4],
+                &[0x01],
+                &satoshis.to_le_bytes(),
+                &[0x19, 0x76, 0xa9, 0x14],
+                &[0x00; 20],
+                &[0x88, 0xac],
+                &[0x00; 4],
+            ]
+            .concat()
+        };
+
+        // 30M DOGE, above the 21M coins of Bitcoin
+        let bytes = raw(30_000_000 * 100_000_000);
+        let transaction = BitcoinTransaction::<Dogecoin>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            BitcoinAmount(3_000_000_000_000_000),
+            transaction.parameters.outputs[0].amount
+        );
+        assert_eq!(
+            "30000000.00000000",
+            serde_json::to_string(&transaction.to_json().unwrap()["vout"][0]["value"]).unwrap()
+        );
+        assert!(BitcoinTransaction::<Bitcoin>::from_bytes(&bytes).is_err());
+
+        let above_supply = raw(Dogecoin::MAX_MONEY as u64 + 1);
+        assert!(BitcoinTransaction::<Dogecoin>::from_bytes(&above_supply).is_err());
+        assert!(BitcoinTransaction::<Dogecoin>::from_bytes(&raw(u64::MAX)).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{BitcoinNetwork, BitcoinTransaction, DecodeLimits};
+use anychain_core::{no_std::*, Transaction, TransactionError};
+use core::ops::Range;
+use sha2::{Digest, Sha256};
+
+/// A cursor over a byte slice handing out borrowed sub-slices
+pub(crate) struct SliceReader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub(crate) fn take(&mut self, size: usize) -> Result<&'a [u8], TransactionError> {
+        let end = self
+            .position
+            .checked_add(size)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| TransactionError::Message("unexpected end of transaction".into()))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, TransactionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, TransactionError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, TransactionError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn compact_size(&mut self) -> Result<usize, TransactionError> {
+        let (size, min) = match self.u8()? {
+            flag @ 0..=252 => return Ok(flag as usize),
+            0xfd => (
+                u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+                253,
+            ),
+            0xfe => (self.u32()? as u64, 65536),
+            _ => (self.u64()?, 4294967296),
+        };
+        match size < min {
+            true => Err(TransactionError::InvalidVariableSizeInteger(size as usize)),
+            false => Ok(size as usize),
+        }
+    }
+
+    /// Reads a compact size and fails early if it exceeds 'max'
+    pub(crate) fn count(&mut self, max: usize, name: &str) -> Result<usize, TransactionError> {
+        let count = self.compact_size()?;
+        if count > max {
+            return Err(TransactionError::LimitExceeded(name.into(), max, count));
+        }
+        Ok(count)
+    }
+
+    /// Reads a length prefixed byte string
+    pub(crate) fn bytes(&mut self, max: usize, name: &str) -> Result<&'a [u8], TransactionError> {
+        let size = self.count(max, name)?;
+        self.take(size)
+    }
+}
+
+/// A transaction input borrowing its scripts from the raw transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxInRef<'a> {
+    /// The previous transaction hash in internal byte order
+    pub reverse_transaction_id: &'a [u8],
+    /// The index of the previous output
+    pub index: u32,
+    /// The scriptSig of the input
+    pub script_sig: &'a [u8],
+    /// The sequence number of the input
+    pub sequence: u32,
+    /// The witness stack of the input
+    pub witness: WitnessRef<'a>,
+}
+
+/// A transaction output borrowing its script from the raw transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxOutRef<'a> {
+    /// The amount in satoshis
+    pub amount: u64,
+    /// The scriptPubKey of the output
+    pub script_pub_key: &'a [u8],
+}
+
+/// The serialized witness stack of an input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WitnessRef<'a> {
+    count: usize,
+    bytes: &'a [u8],
+}
+
+impl<'a> WitnessRef<'a> {
+    /// Returns the number of witness items
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the witness stack is empty
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the serialized witness stack, excluding the item count
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns an iterator over the witness items, without their length prefixes
+    pub fn iter(&self) -> impl Iterator<Item = &'a [u8]> {
+        let mut reader = SliceReader {
+            bytes: self.bytes,
+            position: 0,
+        };
+        // the items were validated while parsing
+        (0..self.count).map(move |_| reader.bytes(usize::MAX, "witness item").unwrap())
+    }
+}
+
+/// A borrowed view of a serialized Bitcoin transaction. Scripts and witnesses
+/// index into the original byte slice instead of being copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinTransactionRef<'a> {
+    /// The version number
+    pub version: u32,
+    /// The transaction inputs
+    pub inputs: Vec<TxInRef<'a>>,
+    /// The transaction outputs
+    pub outputs: Vec<TxOutRef<'a>>,
+    /// The lock time
+    pub lock_time: u32,
+    /// Whether the transaction is serialized with witnesses
+    pub segwit_flag: bool,
+    bytes: &'a [u8],
+    inputs_and_outputs: Range<usize>,
+}
+
+impl<'a> BitcoinTransactionRef<'a> {
+    /// Parses a transaction with the default decoding limits
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, TransactionError> {
+        Self::parse_with_limits(bytes, &DecodeLimits::default())
+    }
+
+    /// Parses a transaction enforcing the given limits. A view never reads past
+    /// the end of its slice, and trailing bytes are rejected in strict mode.
+    pub fn parse_with_limits(
+        bytes: &'a [u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, TransactionError> {
+        if bytes.len() > limits.max_tx_size {
+            return Err(TransactionError::LimitExceeded(
+                "transaction size".into(),
+                limits.max_tx_size,
+                bytes.len(),
+            ));
+        }
+
+        let mut reader = SliceReader { bytes, position: 0 };
+        let version = reader.u32()?;
+
+        let segwit_flag = matches!(reader.bytes.get(4..6), Some([0x00, _]));
+        if segwit_flag {
+            match reader.take(2)?[1] {
+                1 => {}
+                flag => return Err(TransactionError::InvalidSegwitFlag(flag as usize)),
+            }
+        }
+
+        let start = reader.position;
+        let count = reader.count(limits.max_inputs, "inputs")?;
+        let mut inputs = Vec::new();
+        for _ in 0..count {
+            inputs.push(TxInRef {
+                reverse_transaction_id: reader.take(32)?,
+                index: reader.u32()?,
+                script_sig: reader.bytes(limits.max_script_len, "script_sig")?,
+                sequence: reader.u32()?,
+                witness: WitnessRef::default(),
+            });
+        }
+
+        let count = reader.count(limits.max_outputs, "outputs")?;
+        let mut outputs = Vec::new();
+        for _ in 0..count {
+            outputs.push(TxOutRef {
+                amount: reader.u64()?,
+                script_pub_key: reader.bytes(limits.max_script_len, "script_pub_key")?,
+            });
+        }
+        let inputs_and_outputs = start..reader.position;
+
+        if segwit_flag {
+            for input in &mut inputs {
+                let count = reader.count(limits.max_witness_items, "witness items")?;
+                let start = reader.position;
+                for _ in 0..count {
+                    reader.bytes(limits.max_tx_size, "witness item")?;
+                }
+                input.witness = WitnessRef {
+                    count,
+                    bytes: &bytes[start..reader.position],
+                };
+            }
+        }
+
+        let lock_time = reader.u32()?;
+
+        if limits.strict && reader.position != bytes.len() {
+            return Err(TransactionError::TrailingBytes);
+        }
+
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+            segwit_flag,
+            bytes: &bytes[..reader.position],
+            inputs_and_outputs,
+        })
+    }
+
+    /// Returns the serialized transaction the view borrows from
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the transaction id in display byte order, hashing the
+    /// serialization without witnesses straight from the borrowed bytes
+    pub fn txid(&self) -> [u8; 32] {
+        let hash = Sha256::new()
+            .chain_update(&self.bytes[..4])
+            .chain_update(&self.bytes[self.inputs_and_outputs.clone()])
+            .chain_update(&self.bytes[self.bytes.len() - 4..])
+            .finalize();
+        let mut txid: [u8; 32] = Sha256::digest(hash).into();
+        txid.reverse();
+        txid
+    }
+
+    /// Returns the witness transaction id in display byte order
+    pub fn wtxid(&self) -> [u8; 32] {
+        let mut wtxid: [u8; 32] = Sha256::digest(Sha256::digest(self.bytes)).into();
+        wtxid.reverse();
+        wtxid
+    }
+
+    /// Returns an owned transaction parsed from the borrowed bytes
+    pub fn to_transaction<N: BitcoinNetwork>(
+        &self,
+    ) -> Result<BitcoinTransaction<N>, TransactionError> {
+        BitcoinTransaction::<N>::from_bytes(self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bitcoin;
+    use anychain_core::hex;
+
+    // a signed P2WPKH spend taken from BIP143
+    const RAW: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    #[test]
+    fn test_parse() {
+        let bytes = hex::decode(RAW).unwrap();
+        let transaction = BitcoinTransactionRef::parse(&bytes).unwrap();
+
+        assert_eq!(1, transaction.version);
+        assert_eq!(17, transaction.lock_time);
+        assert!(transaction.segwit_flag);
+        assert_eq!(2, transaction.inputs.len());
+        assert_eq!(2, transaction.outputs.len());
+        assert_eq!(0xffffffee, transaction.inputs[0].sequence);
+        assert_eq!(0x49, transaction.inputs[0].script_sig.len());
+        assert!(transaction.inputs[0].witness.is_empty());
+        assert_eq!(112340000, transaction.outputs[0].amount);
+
+        let witness: Vec<&[u8]> = transaction.inputs[1].witness.iter().collect();
+        assert_eq!(2, witness.len());
+        assert_eq!(
+            "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357",
+            hex::encode(witness[1])
+        );
+
+        // the views point into the original buffer
+        let range = bytes.as_ptr_range();
+        assert!(range.contains(&transaction.outputs[1].script_pub_key.as_ptr()));
+
+        assert_eq!(
+            "e8151a2af31c368a35053ddd4bdb285a8595c769a3ad83e0fa02314a602d4609",
+            hex::encode(transaction.txid())
+        );
+        let owned = transaction.to_transaction::<Bitcoin>().unwrap();
+        assert_eq!(bytes, owned.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let bytes = hex::decode(RAW).unwrap();
+        assert!(BitcoinTransactionRef::parse(&bytes[..bytes.len() - 1]).is_err());
+
+        let trailing = [bytes.clone(), vec![0]].concat();
+        assert!(BitcoinTransactionRef::parse(&trailing).is_ok());
+        let limits = DecodeLimits {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            BitcoinTransactionRef::parse_with_limits(&trailing, &limits),
+            Err(TransactionError::TrailingBytes)
+        ));
+
+        let limits = DecodeLimits {
+            max_outputs: 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            BitcoinTransactionRef::parse_with_limits(&bytes, &limits),
+            Err(TransactionError::LimitExceeded(name, 1, 2)) if name == "outputs"
+        ));
+    }
+}
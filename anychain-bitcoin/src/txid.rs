@@ -0,0 +1,131 @@
+//!
+//! Transaction Ids
+//!
+//! This module implements the transaction id as a type that knows its byte
+//! order. A txid is the double SHA256 of a transaction; its bytes are kept in
+//! the internal order they are hashed and serialized in, and are reversed when
+//! displayed, the order used by Bitcoin Core RPC and block explorers. Every
+//! constructor names the order of its input, so that the two cannot be mixed
+//! up.
+//!
+
+use anychain_core::{hex, no_std::*, TransactionError};
+use core::{fmt, str::FromStr};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Represents a transaction id, stored in internal byte order
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Txid([u8; 32]);
+
+impl Txid {
+    /// Returns the txid of the bytes in internal order, as they are hashed and
+    /// as they appear in serialized outpoints
+    pub const fn from_bytes_internal(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the txid of the bytes in RPC order, as they are displayed
+    pub fn from_bytes_rpc(mut bytes: [u8; 32]) -> Self {
+        bytes.reverse();
+        Self(bytes)
+    }
+
+    /// Returns the txid of a 32 byte slice in internal order
+    pub fn from_slice_internal(bytes: &[u8]) -> Result<Self, TransactionError> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| TransactionError::InvalidTransactionId(bytes.len()))?;
+        Ok(Self(bytes))
+    }
+
+    /// Returns the txid of a 32 byte slice in RPC order
+    pub fn from_slice_rpc(bytes: &[u8]) -> Result<Self, TransactionError> {
+        let txid = Self::from_slice_internal(bytes)?;
+        Ok(Self::from_bytes_rpc(txid.0))
+    }
+
+    /// Returns the txid of a hex string in RPC order, as printed by Bitcoin
+    /// Core and block explorers
+    pub fn from_hex_rpc(txid: &str) -> Result<Self, TransactionError> {
+        Self::from_slice_rpc(&hex::decode(txid)?)
+    }
+
+    /// Returns the bytes of the txid in internal order
+    pub fn to_bytes_internal(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Returns the bytes of the txid in RPC order
+    pub fn to_bytes_rpc(&self) -> [u8; 32] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns a reference to the bytes of the txid in internal order
+    pub fn as_bytes_internal(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns true if the txid is all zeros, the txid of the null outpoint
+    pub fn is_all_zeros(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+}
+
+impl FromStr for Txid {
+    type Err = TransactionError;
+
+    fn from_str(txid: &str) -> Result<Self, Self::Err> {
+        Self::from_hex_rpc(txid)
+    }
+}
+
+impl fmt::Display for Txid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes_rpc()))
+    }
+}
+
+impl Serialize for Txid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Txid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let txid = String::deserialize(deserializer)?;
+        Self::from_hex_rpc(&txid).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the coinbase of block 170, spent by the first bitcoin transaction
+    const TXID: &str = "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9";
+
+    #[test]
+    fn test_byte_order() {
+        let txid = Txid::from_hex_rpc(TXID).unwrap();
+        assert_eq!(TXID, txid.to_string());
+        assert_eq!(0xc9, txid.to_bytes_internal()[0]);
+        assert_eq!(0x04, txid.to_bytes_rpc()[0]);
+        assert_eq!(txid, Txid::from_bytes_rpc(txid.to_bytes_rpc()));
+        assert_eq!(txid, Txid::from_bytes_internal(txid.to_bytes_internal()));
+        assert_eq!(txid, Txid::from_str(TXID).unwrap());
+
+        assert!(Txid::from_hex_rpc(&TXID[2..]).is_err());
+        assert!(Txid::default().is_all_zeros());
+    }
+
+    #[test]
+    fn test_serde() {
+        let txid = Txid::from_hex_rpc(TXID).unwrap();
+        let json = serde_json::to_string(&txid).unwrap();
+        assert_eq!(format!("\"{}\"", TXID), json);
+        assert_eq!(txid, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -0,0 +1,178 @@
+//!
+//! Unspent Transaction Outputs
+//!
+//! This module defines the unspent output a wallet can spend, and the provider
+//! trait through which coin selection and transaction building read the
+//! outputs of a wallet, so that integrations can plug in their own stores of
+//! unspent outputs.
+//!
+
+use crate::{
+    BitcoinAddress, BitcoinAmount, BitcoinNetwork, BitcoinTransactionInput, DerivationPath,
+    Outpoint, SignatureHash,
+};
+use anychain_core::{no_std::*, AddressError, AmountError, TransactionError};
+use core::marker::PhantomData;
+
+/// Represents an unspent transaction output of a wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utxo<N: BitcoinNetwork> {
+    /// The outpoint of the output
+    pub outpoint: Outpoint,
+    /// The amount of the output
+    pub amount: BitcoinAmount,
+    /// The 'scriptPubkey' of the output
+    pub script_pub_key: Vec<u8>,
+    /// The derivation path of the key the output pays to, if known
+    pub derivation_path: Option<DerivationPath>,
+    /// The number of confirmations of the transaction of the output, 0 while
+    /// it is unconfirmed
+    pub confirmations: u32,
+    _network: PhantomData<N>,
+}
+
+impl<N: BitcoinNetwork> Utxo<N> {
+    /// Returns an unspent output given its outpoint, amount and scriptPubKey
+    pub fn new(outpoint: Outpoint, amount: BitcoinAmount, script_pub_key: Vec<u8>) -> Self {
+        Self {
+            outpoint,
+            amount,
+            script_pub_key,
+            derivation_path: None,
+            confirmations: 0,
+            _network: PhantomData,
+        }
+    }
+
+    /// Returns the output with the derivation path of the key it pays to
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = Some(derivation_path);
+        self
+    }
+
+    /// Returns the output with 'confirmations' confirmations
+    pub fn with_confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Returns true if the transaction of the output is in a block
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmations > 0
+    }
+
+    /// Returns the address the output pays to
+    pub fn address(&self) -> Result<BitcoinAddress<N>, AddressError> {
+        BitcoinAddress::from_script_pub_key(&self.script_pub_key)
+    }
+
+    /// Returns the unsigned input spending the output with 'sighash'
+    pub fn to_input(
+        &self,
+        sighash: SignatureHash,
+    ) -> Result<BitcoinTransactionInput<N>, TransactionError> {
+        let address = self.address()?;
+        let mut input = BitcoinTransactionInput::new(
+            self.outpoint.txid.to_bytes_rpc().to_vec(),
+            self.outpoint.index,
+            None,
+            Some(address.format()),
+            Some(address),
+            Some(self.amount),
+            sighash,
+        )?;
+        input.script_pub_key = Some(self.script_pub_key.clone());
+        Ok(input)
+    }
+}
+
+/// A source of the unspent outputs of a wallet, e.g. a database, an indexer
+/// or a node
+pub trait UtxoProvider<N: BitcoinNetwork> {
+    /// Returns the unspent outputs the wallet can spend
+    fn utxos(&self) -> Result<Vec<Utxo<N>>, TransactionError>;
+
+    /// Returns the unspent outputs with at least 'min_confirmations'
+    /// confirmations
+    fn confirmed_utxos(&self, min_confirmations: u32) -> Result<Vec<Utxo<N>>, TransactionError> {
+        let mut utxos = self.utxos()?;
+        utxos.retain(|utxo| utxo.confirmations >= min_confirmations);
+        Ok(utxos)
+    }
+
+    /// Returns the total amount of the unspent outputs
+    fn balance(&self) -> Result<BitcoinAmount, TransactionError> {
+        let balance: Result<BitcoinAmount, AmountError> =
+            self.utxos()?.iter().map(|utxo| &utxo.amount).sum();
+        Ok(balance?)
+    }
+}
+
+impl<N: BitcoinNetwork> UtxoProvider<N> for [Utxo<N>] {
+    fn utxos(&self) -> Result<Vec<Utxo<N>>, TransactionError> {
+        Ok(self.to_vec())
+    }
+}
+
+impl<N: BitcoinNetwork> UtxoProvider<N> for Vec<Utxo<N>> {
+    fn utxos(&self) -> Result<Vec<Utxo<N>>, TransactionError> {
+        Ok(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_script_pub_key, Bitcoin, BitcoinFormat, Txid};
+    use core::str::FromStr;
+
+    const TXID: &str = "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9";
+
+    fn utxo(index: u32, amount: i64, confirmations: u32) -> Utxo<Bitcoin> {
+        let address =
+            BitcoinAddress::<Bitcoin>::from_str("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+                .unwrap();
+        Utxo::new(
+            Outpoint::new(Txid::from_str(TXID).unwrap(), index),
+            BitcoinAmount(amount),
+            create_script_pub_key(&address).unwrap(),
+        )
+        .with_confirmations(confirmations)
+    }
+
+    #[test]
+    fn test_to_input() {
+        let utxo = utxo(1, 10_000, 6)
+            .with_derivation_path(DerivationPath::from_str("m/84'/0'/0'/0/0").unwrap());
+        assert!(utxo.is_confirmed());
+        let input = utxo.to_input(SignatureHash::SIGHASH_ALL).unwrap();
+        assert_eq!(utxo.outpoint, input.outpoint);
+        assert_eq!(Some(utxo.amount), input.get_balance());
+        assert_eq!(Some(BitcoinFormat::Bech32), input.get_format());
+        assert_eq!(Some(utxo.script_pub_key), input.script_pub_key);
+    }
+
+    #[test]
+    fn test_p2sh_to_input() {
+        let address = BitcoinAddress::<Bitcoin>::p2sh(&[0x51]).unwrap();
+        let utxo = Utxo::<Bitcoin>::new(
+            Outpoint::new(Txid::from_str(TXID).unwrap(), 0),
+            BitcoinAmount(10_000),
+            create_script_pub_key(&address).unwrap(),
+        );
+        // the redeem script is unknown, so the input is not taken for P2SH-P2WPKH
+        let mut input = utxo.to_input(SignatureHash::SIGHASH_ALL).unwrap();
+        assert_eq!(Some(BitcoinFormat::P2SH), input.get_format());
+        assert!(input.sign(vec![0x30], vec![0x02; 33]).is_err());
+        assert!(!input.is_signed);
+    }
+
+    #[test]
+    fn test_provider() {
+        let utxos = vec![utxo(0, 10_000, 0), utxo(1, 20_000, 1), utxo(2, 30_000, 6)];
+        assert_eq!(3, utxos.utxos().unwrap().len());
+        assert_eq!(BitcoinAmount(60_000), utxos.balance().unwrap());
+        assert_eq!(2, utxos.confirmed_utxos(1).unwrap().len());
+        assert_eq!(1, utxos[..].confirmed_utxos(6).unwrap().len());
+    }
+}
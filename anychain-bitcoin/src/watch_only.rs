@@ -0,0 +1,208 @@
+//!
+//! Watch-Only Accounts
+//!
+//! This module generates the addresses of a BIP44 style account from its
+//! extended public key alone, so that deposit addresses can be handed out
+//! without any private key in memory. The account keeps track of the issued
+//! and used addresses of each chain, and refuses to issue addresses beyond the
+//! gap limit that wallets scan ahead of the last used address.
+//!
+
+use crate::{BitcoinAddress, BitcoinExtendedPublicKey, BitcoinNetwork, Purpose};
+use anychain_core::{no_std::*, AddressError};
+
+/// The number of unused addresses wallets scan past the last used address
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// The chain of an account, the index following the account key in its path
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Chain {
+    /// The external chain of addresses given out to receive payments
+    Receive = 0,
+    /// The internal chain of change addresses
+    Change = 1,
+}
+
+/// The addresses issued and used on a chain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ChainState {
+    /// The index of the next address to issue
+    next: u32,
+    /// The index of the last address that received a payment
+    last_used: Option<u32>,
+}
+
+impl ChainState {
+    /// Returns the index of the first address past the last used address
+    fn first_unused(&self) -> u32 {
+        self.last_used.map_or(0, |index| index + 1)
+    }
+}
+
+/// Represents an account whose addresses are derived from its extended
+/// public key, e.g. the key at `m/84'/0'/0'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchOnlyAccount<N: BitcoinNetwork> {
+    /// The purpose of the account, which determines the address format
+    purpose: Purpose,
+    /// The keys of the receive and change chains
+    chains: [BitcoinExtendedPublicKey<N>; 2],
+    /// The issued and used addresses of the receive and change chains
+    states: [ChainState; 2],
+    /// The number of unused addresses that may be issued in a row
+    gap_limit: u32,
+}
+
+impl<N: BitcoinNetwork> WatchOnlyAccount<N> {
+    /// Returns an account given its extended public key and the purpose
+    /// its addresses are formatted for
+    pub fn new(xpub: &BitcoinExtendedPublicKey<N>, purpose: Purpose) -> Result<Self, AddressError> {
+        Ok(Self {
+            purpose,
+            chains: [
+                xpub.ckd_pub(Chain::Receive as u32)?,
+                xpub.ckd_pub(Chain::Change as u32)?,
+            ],
+            states: Default::default(),
+            gap_limit: DEFAULT_GAP_LIMIT,
+        })
+    }
+
+    /// Returns the account with 'gap_limit' instead of the default gap limit
+    pub fn with_gap_limit(mut self, gap_limit: u32) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Returns the address at 'index' on 'chain'
+    pub fn address(&self, chain: Chain, index: u32) -> Result<BitcoinAddress<N>, AddressError> {
+        let key = self.chains[chain as usize].ckd_pub(index)?;
+        self.purpose.to_address(&key.public_key)
+    }
+
+    /// Returns the receive address at 'index'
+    pub fn receive_address(&self, index: u32) -> Result<BitcoinAddress<N>, AddressError> {
+        self.address(Chain::Receive, index)
+    }
+
+    /// Returns the change address at 'index'
+    pub fn change_address(&self, index: u32) -> Result<BitcoinAddress<N>, AddressError> {
+        self.address(Chain::Change, index)
+    }
+
+    /// Issues the next address of 'chain' and returns it with its index,
+    /// failing if the address would be more than the gap limit past the last
+    /// used address
+    pub fn next_address(&mut self, chain: Chain) -> Result<(u32, BitcoinAddress<N>), AddressError> {
+        let state = &self.states[chain as usize];
+        if state.next - state.first_unused() >= self.gap_limit {
+            return Err(AddressError::Message(format!(
+                "{} unused addresses issued, the gap limit",
+                self.gap_limit
+            )));
+        }
+        let index = state.next;
+        let address = self.address(chain, index)?;
+        self.states[chain as usize].next += 1;
+        Ok((index, address))
+    }
+
+    /// Issues the next receive address
+    pub fn next_receive_address(&mut self) -> Result<(u32, BitcoinAddress<N>), AddressError> {
+        self.next_address(Chain::Receive)
+    }
+
+    /// Issues the next change address
+    pub fn next_change_address(&mut self) -> Result<(u32, BitcoinAddress<N>), AddressError> {
+        self.next_address(Chain::Change)
+    }
+
+    /// Records that the address at 'index' on 'chain' received a payment, so
+    /// that the gap limit counts from it
+    pub fn mark_used(&mut self, chain: Chain, index: u32) {
+        let state = &mut self.states[chain as usize];
+        state.last_used = state.last_used.max(Some(index));
+        state.next = state.next.max(index.saturating_add(1));
+    }
+
+    /// Returns the number of issued addresses of 'chain' past its last used
+    /// address
+    pub fn gap(&self, chain: Chain) -> u32 {
+        let state = &self.states[chain as usize];
+        state.next - state.first_unused()
+    }
+
+    /// Returns the addresses of 'chain' to watch with their indices: every
+    /// address up to the gap limit past the last used address
+    pub fn lookahead(&self, chain: Chain) -> Result<Vec<(u32, BitcoinAddress<N>)>, AddressError> {
+        let end = self.states[chain as usize].first_unused() + self.gap_limit;
+        (0..end)
+            .map(|index| Ok((index, self.address(chain, index)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bitcoin, BitcoinExtendedPrivateKey, DerivationPath};
+    use anychain_core::Mnemonic;
+    use core::str::FromStr;
+
+    fn account() -> WatchOnlyAccount<Bitcoin> {
+        let mnemonic = Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let xpub = BitcoinExtendedPrivateKey::<Bitcoin>::from_mnemonic(&mnemonic, "")
+            .unwrap()
+            .derive(&DerivationPath::from_str("m/84'/0'/0'").unwrap())
+            .unwrap()
+            .to_extended_public_key();
+        WatchOnlyAccount::new(&xpub, Purpose::Bip84).unwrap()
+    }
+
+    #[test]
+    fn test_addresses() {
+        // the addresses of BIP84
+        let account = account();
+        assert_eq!(
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            account.receive_address(0).unwrap().to_string()
+        );
+        assert_eq!(
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+            account.receive_address(1).unwrap().to_string()
+        );
+        assert_eq!(
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            account.change_address(0).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_gap_limit() {
+        let mut account = account().with_gap_limit(3);
+        for index in 0..3 {
+            let (issued, address) = account.next_receive_address().unwrap();
+            assert_eq!(index, issued);
+            assert_eq!(account.receive_address(index).unwrap(), address);
+        }
+        assert_eq!(3, account.gap(Chain::Receive));
+        assert!(account.next_receive_address().is_err());
+        // the change chain has a gap of its own
+        assert!(account.next_change_address().is_ok());
+
+        account.mark_used(Chain::Receive, 1);
+        assert_eq!(1, account.gap(Chain::Receive));
+        assert_eq!(3, account.next_receive_address().unwrap().0);
+        assert_eq!(4, account.next_receive_address().unwrap().0);
+        assert!(account.next_receive_address().is_err());
+
+        // a payment to an address not issued yet moves the issued index along
+        account.mark_used(Chain::Receive, 10);
+        assert_eq!(0, account.gap(Chain::Receive));
+        assert_eq!(11, account.next_receive_address().unwrap().0);
+        assert_eq!(14, account.lookahead(Chain::Receive).unwrap().len());
+    }
+}
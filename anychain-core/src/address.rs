@@ -1,4 +1,6 @@
+use crate::extended_key::ExtendedKeyError;
 use crate::format::Format;
+use crate::network::Network;
 use crate::no_std::*;
 use crate::public_key::{PublicKey, PublicKeyError};
 use core::{
@@ -14,6 +16,7 @@ pub trait Address:
     type SecretKey;
     type Format: Format;
     type PublicKey: PublicKey;
+    type Network: Network;
 
     /// Returns the address corresponding to the given private key.
     fn from_secret_key(
@@ -27,9 +30,18 @@ pub trait Address:
         format: &Self::Format,
     ) -> Result<Self, AddressError>;
 
+    /// Returns true if 'address' is a valid address of the chain and, for
+    /// chains whose addresses encode their network, of its network.
     fn is_valid(address: &str) -> bool {
         Self::from_str(address).is_ok()
     }
+
+    /// Returns the network the address belongs to, or `AnyNetwork` if the
+    /// address is valid on every network of the chain.
+    fn network(&self) -> Self::Network;
+
+    /// Returns the format of the address.
+    fn format(&self) -> Self::Format;
 }
 
 #[derive(Debug, Error)]
@@ -61,6 +73,9 @@ pub enum AddressError {
     #[error("invalid address prefix length: {0:?}")]
     InvalidPrefixLength(usize),
 
+    #[error("invalid payment uri: {0}")]
+    InvalidUri(String),
+
     #[error("{0}")]
     Message(String),
 
@@ -71,6 +86,28 @@ pub enum AddressError {
     PublicKeyError(PublicKeyError),
 }
 
+impl AddressError {
+    /// Returns the numeric code of the error, for FFI and logging layers.
+    /// Codes are stable: a variant keeps its code and codes are not reused.
+    pub fn code(&self) -> u32 {
+        match self {
+            AddressError::Crate(..) => 1001,
+            AddressError::IncompatibleFormats(..) => 1002,
+            AddressError::InvalidAddress(_) => 1003,
+            AddressError::InvalidByteLength(_) => 1004,
+            AddressError::InvalidCharacterLength(_) => 1005,
+            AddressError::InvalidChecksum(..) => 1006,
+            AddressError::InvalidNetwork(..) => 1007,
+            AddressError::InvalidPrefix(_) => 1008,
+            AddressError::InvalidPrefixLength(_) => 1009,
+            AddressError::InvalidUri(_) => 1010,
+            AddressError::Message(_) => 1011,
+            AddressError::MissingPublicKey => 1012,
+            AddressError::PublicKeyError(_) => 1013,
+        }
+    }
+}
+
 impl From<crate::no_std::io::Error> for AddressError {
     fn from(error: crate::no_std::io::Error) -> Self {
         AddressError::Crate("crate::no_std::io", format!("{:?}", error))
@@ -95,11 +132,17 @@ impl From<PublicKeyError> for AddressError {
     }
 }
 
-// impl From<base58::FromBase58Error> for AddressError {
-//     fn from(error: base58::FromBase58Error) -> Self {
-//         AddressError::Crate("base58", format!("{:?}", error))
-//     }
-// }
+impl From<ExtendedKeyError> for AddressError {
+    fn from(error: ExtendedKeyError) -> Self {
+        AddressError::Crate("bip32", format!("{:?}", error))
+    }
+}
+
+impl From<bs58::decode::Error> for AddressError {
+    fn from(error: bs58::decode::Error) -> Self {
+        AddressError::Crate("bs58", format!("{:?}", error))
+    }
+}
 
 impl From<bech32::Error> for AddressError {
     fn from(error: bech32::Error) -> Self {
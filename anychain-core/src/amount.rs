@@ -8,6 +8,80 @@ use core::{
 pub trait Amount:
     Copy + Clone + Debug + Display + Send + Sync + 'static + Eq + Ord + Sized + Hash
 {
+    /// The number of decimal places of the main unit in the smallest unit,
+    /// e.g. 8 for BTC in satoshis and 18 for ETH in wei
+    const DECIMALS: u32;
+
+    /// Returns the amount of 'value' in the smallest unit of the chain.
+    fn from_smallest_unit(value: u128) -> Result<Self, AmountError>;
+
+    /// Returns the amount in the smallest unit of the chain, failing if it
+    /// is negative or exceeds u128.
+    fn to_smallest_unit(&self) -> Result<u128, AmountError>;
+
+    /// Returns the sum of two amounts, failing on overflow.
+    fn checked_add(self, rhs: Self) -> Result<Self, AmountError>;
+
+    /// Returns the difference of two amounts, failing if it is negative.
+    fn checked_sub(self, rhs: Self) -> Result<Self, AmountError>;
+
+    /// Returns the amount of a decimal in the main unit, e.g. "0.0005".
+    fn from_decimal_str(s: &str) -> Result<Self, AmountError> {
+        Self::from_smallest_unit(parse_decimal(s, Self::DECIMALS)?)
+    }
+
+    /// Returns the amount as a decimal in the main unit.
+    fn to_decimal_string(&self) -> Result<String, AmountError> {
+        Ok(format_decimal(self.to_smallest_unit()?, Self::DECIMALS))
+    }
+}
+
+/// Returns the value in the smallest unit of a non-negative decimal with at
+/// most 'decimals' decimal places, without rounding
+pub fn parse_decimal(s: &str, decimals: u32) -> Result<u128, AmountError> {
+    let invalid = || AmountError::InvalidAmount(s.to_string());
+    let out_of_bounds = || AmountError::AmountOutOfBounds(s.to_string(), u128::MAX.to_string());
+
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || fraction.len() > decimals as usize
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let unit = 10u128.checked_pow(decimals).ok_or_else(out_of_bounds)?;
+    let integer = match integer {
+        "" => 0,
+        integer => integer.parse::<u128>().map_err(|_| out_of_bounds())?,
+    };
+    let fraction = match fraction {
+        "" => 0,
+        fraction => format!("{:0<1$}", fraction, decimals as usize)
+            .parse::<u128>()
+            .map_err(|_| invalid())?,
+    };
+    integer
+        .checked_mul(unit)
+        .and_then(|value| value.checked_add(fraction))
+        .ok_or_else(out_of_bounds)
+}
+
+/// Returns a value in the smallest unit as a decimal with 'decimals' decimal
+/// places, without trailing zeros in the fraction
+pub fn format_decimal(value: u128, decimals: u32) -> String {
+    let (integer, fraction) = match 10u128.checked_pow(decimals) {
+        Some(unit) => (value / unit, value % unit),
+        None => (0, value),
+    };
+    let fraction = format!("{:01$}", fraction, decimals as usize);
+    match fraction.trim_end_matches('0') {
+        "" => format!("{}", integer),
+        fraction => format!("{}.{}", integer, fraction),
+    }
 }
 
 #[derive(Debug, Error)]
@@ -91,3 +165,19 @@ fn test() {
     let s = to_basic_unit("0.0001037910", 7);
     assert_eq!("00001038", s.unwrap());
 }
+
+#[test]
+fn test_decimal() {
+    // 100 ETH in wei exceeds i64
+    let wei = parse_decimal("100", 18).unwrap();
+    assert_eq!(100_000_000_000_000_000_000, wei);
+    assert_eq!("100", format_decimal(wei, 18));
+    assert_eq!(50_000, parse_decimal("0.0005", 8).unwrap());
+    assert_eq!("0.0005", format_decimal(50_000, 8));
+    assert_eq!(5, parse_decimal(".5", 1).unwrap());
+
+    assert!(parse_decimal("0.000000001", 8).is_err());
+    assert!(parse_decimal("-1", 8).is_err());
+    assert!(parse_decimal(".", 8).is_err());
+    assert!(parse_decimal("340282366920938463463374607431768211456", 0).is_err());
+}
@@ -0,0 +1,182 @@
+//!
+//! Derivation Paths
+//!
+//! This module implements the derivation paths of hierarchical deterministic
+//! wallets such as `m/44'/60'/0'/0/0`, shared by BIP32 and SLIP-0010 chains.
+//! A path is the sequence of child indices from the master key, hardened
+//! indices being those from `HARDENED_OFFSET` on.
+//!
+
+use crate::extended_key::ExtendedKeyError;
+use crate::no_std::*;
+use core::{fmt, str::FromStr};
+
+/// The index of the first hardened child
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Returns true if 'index' is the index of a hardened child
+pub fn is_hardened(index: u32) -> bool {
+    index >= HARDENED_OFFSET
+}
+
+/// Returns the hardened index of 'index'
+pub fn harden(index: u32) -> u32 {
+    index | HARDENED_OFFSET
+}
+
+/// Represents a derivation path, the child indices from the master key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Returns the path of the child indices 'path'
+    pub fn new(path: Vec<u32>) -> Self {
+        Self(path)
+    }
+
+    /// Returns the BIP44 style path `m/purpose'/coin_type'/account'/chain/index`
+    pub fn bip44(purpose: u32, coin_type: u32, account: u32, chain: u32, index: u32) -> Self {
+        Self(vec![
+            harden(purpose),
+            harden(coin_type),
+            harden(account),
+            chain,
+            index,
+        ])
+    }
+
+    /// Returns the path extended with the child 'index'
+    pub fn child(&self, index: u32) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        Self(path)
+    }
+
+    /// Returns the path of the parent key, or `None` for the master key path
+    pub fn parent(&self) -> Option<Self> {
+        self.0.split_last().map(|(_, parent)| Self(parent.to_vec()))
+    }
+
+    /// Returns the path with all its indices hardened
+    pub fn hardened(&self) -> Self {
+        Self(self.0.iter().map(|index| harden(*index)).collect())
+    }
+
+    /// Returns true if all the indices of the path are hardened, as SLIP-0010
+    /// requires of Ed25519 paths
+    pub fn is_hardened(&self) -> bool {
+        self.0.iter().all(|index| is_hardened(*index))
+    }
+
+    /// Returns an iterator over the child indices of the path
+    pub fn iter(&self) -> core::slice::Iter<'_, u32> {
+        self.0.iter()
+    }
+
+    /// Returns the child indices of the path
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// Returns the number of child indices of the path
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the path is the master key path `m`
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<u32>> for DerivationPath {
+    fn from(path: Vec<u32>) -> Self {
+        Self(path)
+    }
+}
+
+impl<'a> IntoIterator for &'a DerivationPath {
+    type Item = &'a u32;
+    type IntoIter = core::slice::Iter<'a, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = ExtendedKeyError;
+
+    /// Returns the derivation path of 's', marking hardened indices with `'`,
+    /// `h` or `H`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ExtendedKeyError::InvalidDerivationPath(s.into());
+
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+        parts
+            .map(|part| {
+                let (index, hardened) = match part.strip_suffix(['\'', 'h', 'H']) {
+                    Some(index) => (index, true),
+                    None => (part, false),
+                };
+                // reject signs and indices that do not fit in 31 bits
+                let index = match index.bytes().all(|byte| byte.is_ascii_digit()) {
+                    true => index.parse::<u32>().map_err(|_| invalid())?,
+                    false => return Err(invalid()),
+                };
+                match (is_hardened(index), hardened) {
+                    (true, _) => Err(invalid()),
+                    (false, true) => Ok(harden(index)),
+                    (false, false) => Ok(index),
+                }
+            })
+            .collect::<Result<Vec<u32>, Self::Err>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            match is_hardened(*index) {
+                true => write!(f, "/{}'", index - HARDENED_OFFSET)?,
+                false => write!(f, "/{}", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation_path() {
+        let path = DerivationPath::from_str("m/44'/60'/0'/0/5").unwrap();
+        assert_eq!(&[harden(44), harden(60), harden(0), 0, 5], path.as_slice());
+        assert_eq!("m/44'/60'/0'/0/5", path.to_string());
+        assert_eq!(path, DerivationPath::bip44(44, 60, 0, 0, 5));
+        assert_eq!(path, DerivationPath::from_str("m/44h/60H/0'/0/5").unwrap());
+        assert_eq!("m/44'/60'/0'/0", path.parent().unwrap().to_string());
+        assert_eq!(path, path.parent().unwrap().child(5));
+        assert!(!path.is_hardened());
+        assert!(path.hardened().is_hardened());
+        assert_eq!(
+            vec![0, 5],
+            path.iter().skip(3).copied().collect::<Vec<u32>>()
+        );
+
+        let master = DerivationPath::from_str("m").unwrap();
+        assert!(master.is_empty());
+        assert_eq!(None, master.parent());
+
+        for invalid in ["", "44'/0'", "m/", "m/a", "m/-1", "m/2147483648", "m/1''"] {
+            assert!(DerivationPath::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//!
+//! Entropy
+//!
+//! This module abstracts the randomness signing helpers consume, such as the
+//! extra entropy of ECDSA nonces, sr25519 nonces or the shuffling of
+//! transaction inputs and outputs. An `EntropySource` only fills buffers with
+//! random bytes, so a hardware random number generator of an embedded target
+//! plugs in with a single method, while any cryptographic `rand_core`
+//! generator is one already. With the `os-rng` feature, `OsEntropy` draws
+//! from the operating system.
+//!
+
+use rand_core::{CryptoRng, RngCore};
+
+/// A source of cryptographically secure random bytes
+pub trait EntropySource {
+    /// Fills 'dest' with random bytes
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+}
+
+impl<R: RngCore + CryptoRng> EntropySource for R {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RngCore::fill_bytes(self, dest)
+    }
+}
+
+/// Adapts an entropy source to the `rand_core` generator traits, for the
+/// helpers of the crates that expect a generator
+pub struct EntropyRng<'a, E: EntropySource + ?Sized>(pub &'a mut E);
+
+impl<E: EntropySource + ?Sized> RngCore for EntropyRng<'_, E> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.0.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.0.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<E: EntropySource + ?Sized> CryptoRng for EntropyRng<'_, E> {}
+
+/// The entropy source of the operating system
+#[cfg(feature = "os-rng")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsEntropy;
+
+#[cfg(feature = "os-rng")]
+impl EntropySource for OsEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RngCore::fill_bytes(&mut rand_core::OsRng, dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A counter standing for a hardware generator, which must not be used
+    /// out of tests
+    struct Counter(u8);
+
+    impl EntropySource for Counter {
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest {
+                *byte = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_entropy_rng() {
+        let mut counter = Counter(1);
+        let source: &mut dyn EntropySource = &mut counter;
+        let mut rng = EntropyRng(source);
+        assert_eq!(0x04030201, rng.next_u32());
+        assert_eq!(0x0c0b0a0908070605, rng.next_u64());
+        let mut bytes = [0u8; 3];
+        RngCore::fill_bytes(&mut rng, &mut bytes);
+        assert_eq!([13, 14, 15], bytes);
+
+        // cryptographic generators are entropy sources
+        let mut rng = EntropyRng(&mut rng);
+        assert_eq!(0x13121110, rng.next_u32());
+    }
+}
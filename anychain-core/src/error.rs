@@ -1,7 +1,10 @@
 use crate::no_std::String;
 use crate::AddressError;
 use crate::AmountError;
+use crate::ExtendedKeyError;
 use crate::FormatError;
+use crate::MnemonicError;
+use crate::PrivateKeyError;
 use crate::PublicKeyError;
 use crate::TransactionError;
 
@@ -22,6 +25,15 @@ pub enum Error {
     #[error("Invalid PublickKey: {0:}")]
     InvalidPublickKey(#[from] PublicKeyError),
 
+    #[error("Invalid PrivateKey: {0:}")]
+    InvalidPrivateKey(#[from] PrivateKeyError),
+
+    #[error("Invalid ExtendedKey: {0:}")]
+    InvalidExtendedKey(#[from] ExtendedKeyError),
+
+    #[error("Invalid Mnemonic: {0:}")]
+    InvalidMnemonic(#[from] MnemonicError),
+
     #[error("Invalid Format: {0:}")]
     InvalidFormat(#[from] FormatError),
 
@@ -0,0 +1,43 @@
+use crate::no_std::*;
+
+#[derive(Debug, Error)]
+pub enum ExtendedKeyError {
+    #[error("{0}: {1}")]
+    Crate(&'static str, String),
+
+    #[error("cannot derive hardened child {0} from an extended public key")]
+    HardenedFromPublicKey(u32),
+
+    #[error("invalid byte length: {0}")]
+    InvalidByteLength(usize),
+
+    #[error("invalid extended key checksum: {{ expected: {0}, found: {1} }}")]
+    InvalidChecksum(String, String),
+
+    #[error("invalid child key at index {0}")]
+    InvalidChildKey(u32),
+
+    #[error("invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
+    #[error("invalid extended key version bytes: {0}")]
+    InvalidVersionBytes(String),
+
+    #[error("maximum derivation depth exceeded")]
+    MaximumDepthExceeded,
+
+    #[error("{0}")]
+    Message(String),
+}
+
+impl From<bs58::decode::Error> for ExtendedKeyError {
+    fn from(error: bs58::decode::Error) -> Self {
+        ExtendedKeyError::Crate("bs58", format!("{:?}", error))
+    }
+}
+
+impl From<libsecp256k1::Error> for ExtendedKeyError {
+    fn from(error: libsecp256k1::Error) -> Self {
+        ExtendedKeyError::Crate("libsecp256k1", format!("{:?}", error))
+    }
+}
@@ -23,15 +23,36 @@ pub use self::address::*;
 pub mod amount;
 pub use self::amount::*;
 
+pub mod derivation_path;
+pub use self::derivation_path::*;
+
+pub mod entropy;
+pub use self::entropy::*;
+
+pub mod extended_key;
+pub use self::extended_key::*;
+
 pub mod format;
 pub use self::format::*;
 
+pub mod message;
+pub use self::message::*;
+
+pub mod mnemonic;
+pub use self::mnemonic::*;
+
 pub mod network;
 pub use self::network::*;
 
+pub mod private_key;
+pub use self::private_key::*;
+
 pub mod public_key;
 pub use self::public_key::*;
 
+pub mod slip10;
+pub use self::slip10::*;
+
 pub mod transaction;
 pub use self::transaction::*;
 
@@ -47,4 +68,6 @@ pub use libsecp256k1;
 
 pub use hex;
 
+pub use zeroize;
+
 // pub use ethereum_types;
@@ -0,0 +1,30 @@
+//!
+//! Message
+//!
+//! The chain-agnostic interface to signed messages, which prove the ownership
+//! of an address without a transaction. Each chain frames the message its own
+//! way before signing it, as Bitcoin's `signmessage` or Ethereum's
+//! `personal_sign` do, and the signature is returned in its raw byte form.
+//!
+
+use crate::address::{Address, AddressError};
+use crate::no_std::*;
+
+/// Signs messages in the signed message format of a chain.
+pub trait MessageSigner: Address {
+    /// Returns the signature of 'message' by 'secret_key', which verifies
+    /// against the address of 'secret_key' in 'format'.
+    fn sign_message(
+        secret_key: &Self::SecretKey,
+        format: &Self::Format,
+        message: &[u8],
+    ) -> Result<Vec<u8>, AddressError>;
+}
+
+/// Verifies messages signed in the signed message format of a chain.
+pub trait MessageVerifier: Address {
+    /// Returns true if 'signature' is a signature of 'message' by the key of
+    /// this address. A malformed signature is an error, while a well formed
+    /// signature by another key is not.
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<bool, AddressError>;
+}
@@ -0,0 +1,279 @@
+//!
+//! BIP39 Mnemonic
+//!
+//! This module implements mnemonic sentences (BIP39): the encoding of 128 to
+//! 256 bits of entropy with a checksum as 12 to 24 words of a wordlist, and the
+//! derivation of the 64 byte seed of a mnemonic, the input of BIP32 master key
+//! generation. English is always available, other wordlists are behind
+//! features. Only wordlists whose words are unaffected by NFKD normalization
+//! are provided, so the seed of a mnemonic is derived from its words as is.
+//!
+
+use crate::crypto::{pbkdf2_hmac_sha512, sha256};
+use crate::no_std::*;
+use core::{fmt, str::FromStr};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+/// The number of PBKDF2 iterations of seed derivation
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// The language of the wordlist of a mnemonic
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    English,
+    #[cfg(feature = "chinese-simplified")]
+    ChineseSimplified,
+    #[cfg(feature = "chinese-traditional")]
+    ChineseTraditional,
+    #[cfg(feature = "czech")]
+    Czech,
+    #[cfg(feature = "italian")]
+    Italian,
+    #[cfg(feature = "portuguese")]
+    Portuguese,
+}
+
+impl Language {
+    /// Returns the 2048 words of the wordlist of the language
+    pub fn wordlist(&self) -> Vec<&'static str> {
+        let words = match self {
+            Language::English => include_str!("wordlist/english.txt"),
+            #[cfg(feature = "chinese-simplified")]
+            Language::ChineseSimplified => include_str!("wordlist/chinese_simplified.txt"),
+            #[cfg(feature = "chinese-traditional")]
+            Language::ChineseTraditional => include_str!("wordlist/chinese_traditional.txt"),
+            #[cfg(feature = "czech")]
+            Language::Czech => include_str!("wordlist/czech.txt"),
+            #[cfg(feature = "italian")]
+            Language::Italian => include_str!("wordlist/italian.txt"),
+            #[cfg(feature = "portuguese")]
+            Language::Portuguese => include_str!("wordlist/portuguese.txt"),
+        };
+        words.lines().collect()
+    }
+}
+
+/// Represents a BIP39 mnemonic sentence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    /// The language of the words
+    language: Language,
+    /// The words of the mnemonic
+    words: Vec<&'static str>,
+}
+
+impl Mnemonic {
+    /// Returns the mnemonic of 'entropy', which is 16 to 32 bytes long and a
+    /// multiple of 4 bytes
+    pub fn from_entropy(entropy: &[u8], language: Language) -> Result<Self, MnemonicError> {
+        if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+            return Err(MnemonicError::InvalidEntropyLength(entropy.len()));
+        }
+
+        // the entropy followed by the first entropy/32 bits of its hash
+        let checksum = sha256(entropy)[0];
+        let bits = entropy.len() * 8 + entropy.len() / 4;
+        let bit = |i: usize| match i < entropy.len() * 8 {
+            true => entropy[i / 8] >> (7 - i % 8) & 1,
+            false => checksum >> (7 - (i - entropy.len() * 8)) & 1,
+        };
+
+        let wordlist = language.wordlist();
+        let words = (0..bits / 11)
+            .map(|word| {
+                let index = (0..11).fold(0, |index, i| index << 1 | bit(word * 11 + i) as usize);
+                wordlist[index]
+            })
+            .collect();
+        Ok(Self { language, words })
+    }
+
+    /// Returns a new mnemonic of 'word_count' words, which is 12, 15, 18, 21
+    /// or 24, with entropy drawn from 'rng'
+    pub fn generate<R: Rng>(
+        rng: &mut R,
+        word_count: usize,
+        language: Language,
+    ) -> Result<Self, MnemonicError> {
+        if !(12..=24).contains(&word_count) || !word_count.is_multiple_of(3) {
+            return Err(MnemonicError::InvalidWordCount(word_count));
+        }
+        let mut entropy = vec![0u8; word_count * 4 / 3];
+        rng.fill(&mut entropy[..]);
+        Self::from_entropy(&entropy, language)
+    }
+
+    /// Returns the mnemonic of 'phrase', whose words are separated by
+    /// whitespace, validating its words and its checksum
+    pub fn from_phrase(phrase: &str, language: Language) -> Result<Self, MnemonicError> {
+        let wordlist = language.wordlist();
+        let indices = phrase
+            .split_whitespace()
+            .map(|word| {
+                wordlist
+                    .iter()
+                    .position(|w| *w == word)
+                    .ok_or_else(|| MnemonicError::InvalidWord(word.into()))
+            })
+            .collect::<Result<Vec<usize>, MnemonicError>>()?;
+        if !(12..=24).contains(&indices.len()) || !indices.len().is_multiple_of(3) {
+            return Err(MnemonicError::InvalidWordCount(indices.len()));
+        }
+
+        let mut entropy = vec![0u8; indices.len() * 4 / 3];
+        let bit = |i: usize| indices[i / 11] >> (10 - i % 11) & 1;
+        for (i, byte) in entropy.iter_mut().enumerate() {
+            *byte = (0..8).fold(0, |byte, j| byte << 1 | bit(i * 8 + j) as u8);
+        }
+
+        let mnemonic = Self::from_entropy(&entropy, language)?;
+        if mnemonic.words.last() != indices.last().and_then(|index| wordlist.get(*index)) {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+        Ok(mnemonic)
+    }
+
+    /// Returns the entropy the mnemonic encodes
+    pub fn to_entropy(&self) -> Result<Vec<u8>, MnemonicError> {
+        let wordlist = self.language.wordlist();
+        let indices = self
+            .words
+            .iter()
+            .map(|word| {
+                wordlist
+                    .iter()
+                    .position(|w| w == word)
+                    .ok_or_else(|| MnemonicError::InvalidWord(word.to_string()))
+            })
+            .collect::<Result<Vec<usize>, MnemonicError>>()?;
+        let bit = |i: usize| indices[i / 11] >> (10 - i % 11) & 1;
+        Ok((0..self.words.len() * 4 / 3)
+            .map(|i| (0..8).fold(0, |byte, j| byte << 1 | bit(i * 8 + j) as u8))
+            .collect())
+    }
+
+    /// Returns the 64 byte seed of the mnemonic and 'passphrase', wiped from
+    /// memory when dropped. A passphrase that is not ASCII must be NFKD
+    /// normalized by the caller.
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; 64]> {
+        let phrase = Zeroizing::new(self.to_string());
+        let salt = Zeroizing::new(format!("mnemonic{}", passphrase));
+        Zeroizing::new(pbkdf2_hmac_sha512(
+            phrase.as_bytes(),
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+        ))
+    }
+
+    /// Returns the language of the mnemonic
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Returns the words of the mnemonic
+    pub fn words(&self) -> &[&'static str] {
+        &self.words
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = MnemonicError;
+
+    /// Returns the mnemonic of an English phrase
+    fn from_str(phrase: &str) -> Result<Self, Self::Err> {
+        Self::from_phrase(phrase, Language::English)
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.words.join(" "))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MnemonicError {
+    #[error("invalid mnemonic checksum")]
+    InvalidChecksum,
+
+    #[error("invalid entropy length: {0}")]
+    InvalidEntropyLength(usize),
+
+    #[error("invalid word: {0}")]
+    InvalidWord(String),
+
+    #[error("invalid word count: {0}")]
+    InvalidWordCount(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    // test vectors of the reference implementation, with passphrase "TREZOR"
+    const VECTORS: [(&str, &str, &str); 4] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+            "274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+        ),
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for (entropy, phrase, seed) in VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            let mnemonic = Mnemonic::from_entropy(&entropy, Language::English).unwrap();
+            assert_eq!(phrase, mnemonic.to_string());
+            assert_eq!(mnemonic, Mnemonic::from_str(phrase).unwrap());
+            assert_eq!(entropy, mnemonic.to_entropy().unwrap());
+            assert_eq!(seed, hex::encode(mnemonic.to_seed("TREZOR")));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        // invalid checksum
+        assert!(Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+        )
+        .is_err());
+        // unknown word
+        assert!(Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abou"
+        )
+        .is_err());
+        // invalid word count
+        assert!(Mnemonic::from_str("abandon abandon about").is_err());
+        // invalid entropy length
+        assert!(Mnemonic::from_entropy(&[0; 15], Language::English).is_err());
+        assert!(Mnemonic::from_entropy(&[0; 18], Language::English).is_err());
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0x0101_0101_0101_0101);
+        let mnemonic = Mnemonic::generate(&mut rng, 24, Language::English).unwrap();
+        assert_eq!(24, mnemonic.words().len());
+        assert_eq!(mnemonic, Mnemonic::from_str(&mnemonic.to_string()).unwrap());
+        assert!(Mnemonic::generate(&mut rng, 13, Language::English).is_err());
+    }
+}
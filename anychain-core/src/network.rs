@@ -7,11 +7,37 @@ use core::{
 
 /// The interface for a generic network.
 pub trait Network:
-    Copy + Clone + Debug + Display + FromStr + Send + Sync + 'static + Eq + Ord + Sized + Hash
+    Copy + Clone + Debug + Default + Display + FromStr + Send + Sync + 'static + Eq + Ord + Sized + Hash
 {
     const NAME: &'static str;
 }
 
+/// The network of the addresses of a chain that are valid on every network
+/// of the chain, e.g. Ethereum and Tron addresses.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AnyNetwork;
+
+impl Network for AnyNetwork {
+    const NAME: &'static str = "any";
+}
+
+impl FromStr for AnyNetwork {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::NAME => Ok(Self),
+            _ => Err(NetworkError::InvalidNetwork(s.into())),
+        }
+    }
+}
+
+impl Display for AnyNetwork {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum NetworkError {
     #[error("invalid extended private key prefix: {0}")]
@@ -4,7 +4,18 @@ use core::{cmp, fmt, mem};
 
 pub trait Read {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
-    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error>;
+
+    /// Fills 'buf', failing with `ErrorKind::UnexpectedEof` if the reader
+    /// runs out of bytes first
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), Error> {
+        while !buf.is_empty() {
+            match self.read(buf)? {
+                0 => return Err(ErrorKind::UnexpectedEof.into()),
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<R: Read + ?Sized> Read for &mut R {
@@ -41,7 +52,7 @@ impl Read for &[u8] {
     #[inline]
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
         if buf.len() > self.len() {
-            return Err(Error);
+            return Err(ErrorKind::UnexpectedEof.into());
         }
         let (a, b) = self.split_at(buf.len());
 
@@ -61,7 +72,23 @@ impl Read for &[u8] {
 
 pub trait Write {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error>;
-    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error>;
+
+    /// Writes all of 'buf', failing with `ErrorKind::WriteZero` if the writer
+    /// stops accepting bytes first
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Error> {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return Err(ErrorKind::WriteZero.into()),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 impl<W: Write + ?Sized> Write for &mut W {
@@ -74,13 +101,18 @@ impl<W: Write + ?Sized> Write for &mut W {
     fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
         (**self).write_all(buf)
     }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        (**self).flush()
+    }
 }
 
 impl Write for &mut [u8] {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
         let amt = cmp::min(buf.len(), self.len());
-        let (a, b) = mem::replace(self, &mut []).split_at_mut(amt);
+        let (a, b) = mem::take(self).split_at_mut(amt);
         a.copy_from_slice(&buf[..amt]);
         *self = b;
         Ok(amt)
@@ -88,15 +120,9 @@ impl Write for &mut [u8] {
 
     #[inline]
     fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
-        let amt = cmp::min(buf.len(), self.len());
-        let (a, b) = mem::replace(self, &mut []).split_at_mut(amt);
-        a.copy_from_slice(&buf[..amt]);
-        *self = b;
-
-        if amt == buf.len() {
-            Ok(())
-        } else {
-            Err(Error)
+        match self.write(buf)? == buf.len() {
+            true => Ok(()),
+            false => Err(ErrorKind::WriteZero.into()),
         }
     }
 }
@@ -115,12 +141,135 @@ impl Write for Vec<u8> {
     }
 }
 
-#[derive(Debug)]
-pub struct Error;
+/// An in-memory reader and writer over a buffer, at a position advanced by
+/// every read and write
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: u64,
+}
+
+impl<T> Cursor<T> {
+    /// Returns a cursor at the start of 'inner'
+    pub const fn new(inner: T) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// Returns the bytes of the buffer from the position on
+    fn remaining_slice(&self) -> &[u8] {
+        let inner = self.inner.as_ref();
+        let start = cmp::min(self.pos, inner.len() as u64) as usize;
+        &inner[start..]
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let n = self.remaining_slice().read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.remaining_slice().read_exact(buf)?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl Write for Cursor<&mut [u8]> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let start = cmp::min(self.pos, self.inner.len() as u64) as usize;
+        let n = (&mut self.inner[start..]).write(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for Cursor<Vec<u8>> {
+    /// Overwrites the bytes at the position, extending the vector past its
+    /// end and padding it with zeros if the position is beyond it
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let start = usize::try_from(self.pos).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+        let end = start + buf.len();
+        if self.inner.len() < end {
+            self.inner.resize(end, 0);
+        }
+        self.inner[start..end].copy_from_slice(buf);
+        self.pos = end as u64;
+        Ok(buf.len())
+    }
+}
+
+/// The categories of io errors, named as in std
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A parameter, such as a position, was out of range
+    InvalidInput,
+    /// The data read was malformed
+    InvalidData,
+    /// The reader ran out of bytes before the buffer was filled
+    UnexpectedEof,
+    /// The writer stopped accepting bytes before the buffer was written
+    WriteZero,
+    Other,
+}
+
+impl ErrorKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::InvalidInput => "invalid input parameter",
+            ErrorKind::InvalidData => "invalid data",
+            ErrorKind::UnexpectedEof => "unexpected end of file",
+            ErrorKind::WriteZero => "write zero",
+            ErrorKind::Other => "other error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl From<ErrorKind> for Error {
+    #[inline]
+    fn from(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+}
 
 impl fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Error in no_std::io operation")
+        write!(f, "Error in no_std::io operation: {}", self.kind.as_str())
     }
 }
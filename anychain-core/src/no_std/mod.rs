@@ -1,13 +1,15 @@
 #[cfg(not(feature = "std"))]
 #[doc(hidden)]
 pub use alloc::{
-    borrow::ToOwned, format, string::FromUtf8Error, string::String, string::ToString, vec, vec::Vec,
+    borrow::ToOwned, boxed::Box, format, string::FromUtf8Error, string::String, string::ToString,
+    vec, vec::Vec,
 };
 
 #[cfg(feature = "std")]
 #[doc(hidden)]
 pub use std::{
-    borrow::ToOwned, format, string::FromUtf8Error, string::String, string::ToString, vec, vec::Vec,
+    borrow::ToOwned, boxed::Box, format, string::FromUtf8Error, string::String, string::ToString,
+    vec, vec::Vec,
 };
 
 #[cfg(not(feature = "std"))]
@@ -0,0 +1,67 @@
+use crate::address::{Address, AddressError};
+use crate::format::Format;
+use crate::no_std::*;
+use crate::public_key::PublicKey;
+use core::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+/// Generic private key.
+pub trait PrivateKey: Clone + Debug + Display + FromStr + Send + Sync + 'static + Sized {
+    type SecretKey;
+    type PublicKey: PublicKey;
+    type Address: Address;
+    type Format: Format;
+
+    /// Returns a private key given a secret key.
+    fn from_secret_key(secret_key: &Self::SecretKey) -> Self;
+
+    /// Returns the public key of the corresponding private key.
+    fn to_public_key(&self) -> Self::PublicKey;
+
+    /// Returns the address of the corresponding private key.
+    fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError>;
+}
+
+#[derive(Debug, Error)]
+pub enum PrivateKeyError {
+    #[error("{0}: {1}")]
+    Crate(&'static str, String),
+
+    #[error("invalid byte length: {0}")]
+    InvalidByteLength(usize),
+
+    #[error("invalid character length: {0}")]
+    InvalidCharacterLength(usize),
+
+    #[error("invalid private key checksum: {{ expected: {0}, found: {1} }}")]
+    InvalidChecksum(String, String),
+
+    #[error("invalid network: {{ expected: {0}, found: {1} }}")]
+    InvalidNetwork(String, String),
+
+    #[error("invalid private key prefix: {0}")]
+    InvalidPrefix(String),
+
+    #[error("{0}")]
+    Message(String),
+}
+
+impl From<bs58::decode::Error> for PrivateKeyError {
+    fn from(error: bs58::decode::Error) -> Self {
+        PrivateKeyError::Crate("bs58", format!("{:?}", error))
+    }
+}
+
+impl From<hex::FromHexError> for PrivateKeyError {
+    fn from(error: hex::FromHexError) -> Self {
+        PrivateKeyError::Crate("hex", format!("{:?}", error))
+    }
+}
+
+impl From<libsecp256k1::Error> for PrivateKeyError {
+    fn from(error: libsecp256k1::Error) -> Self {
+        PrivateKeyError::Crate("libsecp256k1", format!("{:?}", error))
+    }
+}
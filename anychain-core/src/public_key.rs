@@ -17,6 +17,11 @@ pub trait PublicKey: Clone + Debug + Display + FromStr + Send + Sync + 'static +
 
     /// Returns an address corresponding to this public key.
     fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError>;
+
+    /// Returns true if 'signature' is a signature of 'message_hash' by this
+    /// public key. The expected hash and signature encodings are those of the
+    /// chain, and malformed input is an error rather than a failed check.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError>;
 }
 
 #[derive(Debug, Error)]
@@ -46,11 +51,11 @@ impl From<crate::no_std::io::Error> for PublicKeyError {
     }
 }
 
-// impl From<base58::FromBase58Error> for PublicKeyError {
-//     fn from(error: base58::FromBase58Error) -> Self {
-//         PublicKeyError::Crate("base58", format!("{:?}", error))
-//     }
-// }
+impl From<bs58::decode::Error> for PublicKeyError {
+    fn from(error: bs58::decode::Error) -> Self {
+        PublicKeyError::Crate("bs58", format!("{:?}", error))
+    }
+}
 
 impl From<bech32::Error> for PublicKeyError {
     fn from(error: bech32::Error) -> Self {
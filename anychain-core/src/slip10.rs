@@ -0,0 +1,238 @@
+//!
+//! SLIP-0010
+//!
+//! This module implements the hierarchical deterministic key derivation of
+//! SLIP-0010, which extends BIP32 to other curves. One seed, such as the seed
+//! of a BIP39 mnemonic, derives the keys of Ed25519 chains like Solana,
+//! Stellar, NEAR and TON as well as those of secp256k1 chains, where it
+//! matches BIP32. Ed25519 keys only have hardened children.
+//!
+
+use crate::crypto::{ed25519_public_key, hmac_sha512};
+use crate::derivation_path::{is_hardened, DerivationPath};
+use crate::extended_key::ExtendedKeyError;
+use crate::mnemonic::Mnemonic;
+use crate::no_std::*;
+use libsecp256k1::{PublicKey, SecretKey};
+use zeroize::Zeroizing;
+
+/// The curves of SLIP-0010 key derivation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Slip10Curve {
+    Ed25519,
+    Secp256k1,
+}
+
+impl Slip10Curve {
+    /// Returns the HMAC key the master key of a seed is derived with
+    fn seed_key(&self) -> &'static [u8] {
+        match self {
+            Slip10Curve::Ed25519 => b"ed25519 seed",
+            Slip10Curve::Secp256k1 => b"Bitcoin seed",
+        }
+    }
+}
+
+/// Represents a SLIP-0010 extended private key, whose secret key is wiped from
+/// memory when dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slip10Key {
+    /// The curve of the key
+    pub curve: Slip10Curve,
+    /// The depth of the key, 0 for the master key
+    pub depth: u8,
+    /// The index of the key under its parent
+    pub child_number: u32,
+    /// The chain code of the key
+    pub chain_code: [u8; 32],
+    /// The 32 byte secret key, an Ed25519 seed or a secp256k1 scalar
+    secret_key: Zeroizing<[u8; 32]>,
+}
+
+impl Slip10Key {
+    /// Returns the master key of 'seed' on 'curve', 16 to 64 bytes long
+    pub fn new_master(curve: Slip10Curve, seed: &[u8]) -> Result<Self, ExtendedKeyError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(ExtendedKeyError::InvalidByteLength(seed.len()));
+        }
+        let mut i = Zeroizing::new(hmac_sha512(curve.seed_key(), seed));
+        // an invalid secp256k1 key is derived again from the output itself
+        while curve == Slip10Curve::Secp256k1 && SecretKey::parse_slice(&i[..32]).is_err() {
+            i = Zeroizing::new(hmac_sha512(curve.seed_key(), &*i));
+        }
+        Ok(Self::from_hmac(curve, 0, 0, &i))
+    }
+
+    /// Returns the master key on 'curve' of the seed of 'mnemonic' and
+    /// 'passphrase'
+    pub fn from_mnemonic(
+        curve: Slip10Curve,
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+    ) -> Result<Self, ExtendedKeyError> {
+        Self::new_master(curve, mnemonic.to_seed(passphrase).as_slice())
+    }
+
+    /// Returns the key of the 64 byte HMAC output 'i', the secret key being its
+    /// first half and the chain code its second half
+    fn from_hmac(curve: Slip10Curve, depth: u8, child_number: u32, i: &[u8; 64]) -> Self {
+        Self {
+            curve,
+            depth,
+            child_number,
+            chain_code: i[32..].try_into().unwrap(),
+            secret_key: Zeroizing::new(i[..32].try_into().unwrap()),
+        }
+    }
+
+    /// Returns the child key at 'index', hardened if 'index' is at least
+    /// HARDENED_OFFSET. Ed25519 keys have hardened children only.
+    pub fn ckd_priv(&self, index: u32) -> Result<Self, ExtendedKeyError> {
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(ExtendedKeyError::MaximumDepthExceeded)?;
+
+        let mut data = Zeroizing::new(match (is_hardened(index), self.curve) {
+            (true, _) => [&[0u8][..], &*self.secret_key].concat(),
+            (false, Slip10Curve::Secp256k1) => self.public_key()?,
+            (false, Slip10Curve::Ed25519) => {
+                return Err(ExtendedKeyError::Message(format!(
+                    "ed25519 keys have no normal child {}",
+                    index
+                )))
+            }
+        });
+        data.extend(index.to_be_bytes());
+
+        let mut i = Zeroizing::new(hmac_sha512(&self.chain_code, &data));
+        if self.curve == Slip10Curve::Secp256k1 {
+            // an invalid child is derived again from the chain code half
+            loop {
+                if let Some(child) = self.tweak(&i[..32]) {
+                    i[..32].copy_from_slice(&child.serialize());
+                    break;
+                }
+                let data = [&[1u8][..], &i[32..], &index.to_be_bytes()].concat();
+                i = Zeroizing::new(hmac_sha512(&self.chain_code, &data));
+            }
+        }
+        Ok(Self::from_hmac(self.curve, depth, index, &i))
+    }
+
+    /// Returns the secp256k1 secret key 'tweak' plus the secret key, or `None`
+    /// if 'tweak' is not below the curve order or the sum is zero
+    fn tweak(&self, tweak: &[u8]) -> Option<SecretKey> {
+        let mut child = SecretKey::parse_slice(tweak).ok()?;
+        let secret_key = SecretKey::parse(&self.secret_key).ok()?;
+        child.tweak_add_assign(&secret_key).ok()?;
+        Some(child)
+    }
+
+    /// Returns the descendant key at 'path' relative to the key
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self, ExtendedKeyError> {
+        path.iter()
+            .try_fold(self.clone(), |key, index| key.ckd_priv(*index))
+    }
+
+    /// Returns the 32 byte secret key, an Ed25519 seed or a secp256k1 scalar
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret_key
+    }
+
+    /// Returns the public key, 32 bytes for Ed25519 and 33 compressed bytes
+    /// for secp256k1
+    pub fn public_key(&self) -> Result<Vec<u8>, ExtendedKeyError> {
+        Ok(match self.curve {
+            Slip10Curve::Ed25519 => ed25519_public_key(&self.secret_key).to_vec(),
+            Slip10Curve::Secp256k1 => {
+                let secret_key = SecretKey::parse(&self.secret_key)?;
+                PublicKey::from_secret_key(&secret_key)
+                    .serialize_compressed()
+                    .to_vec()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn test_ed25519() {
+        // the test vector 1 of SLIP-0010 for ed25519
+        let master =
+            Slip10Key::new_master(Slip10Curve::Ed25519, &hex::decode(SEED).unwrap()).unwrap();
+        for (path, chain_code, secret_key, public_key) in [
+            (
+                "m",
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+            ),
+            (
+                "m/0'",
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "m/0'/1'",
+                "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+            ),
+        ] {
+            let key = master
+                .derive(&DerivationPath::from_str(path).unwrap())
+                .unwrap();
+            assert_eq!(chain_code, hex::encode(key.chain_code), "{}", path);
+            assert_eq!(secret_key, hex::encode(key.secret_key()), "{}", path);
+            assert_eq!(
+                public_key,
+                hex::encode(key.public_key().unwrap()),
+                "{}",
+                path
+            );
+        }
+        assert!(master.ckd_priv(0).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1() {
+        // the test vector 1 of SLIP-0010 for secp256k1, which is BIP32's
+        let master =
+            Slip10Key::new_master(Slip10Curve::Secp256k1, &hex::decode(SEED).unwrap()).unwrap();
+        for (path, chain_code, secret_key) in [
+            (
+                "m",
+                "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508",
+                "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+            ),
+            (
+                "m/0'",
+                "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141",
+                "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea",
+            ),
+            (
+                "m/0'/1",
+                "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19",
+                "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+            ),
+        ] {
+            let key = master
+                .derive(&DerivationPath::from_str(path).unwrap())
+                .unwrap();
+            assert_eq!(chain_code, hex::encode(key.chain_code), "{}", path);
+            assert_eq!(secret_key, hex::encode(key.secret_key()), "{}", path);
+        }
+        assert_eq!(
+            "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2",
+            hex::encode(master.public_key().unwrap())
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::address::{Address, AddressError};
-use crate::amount::AmountError;
+use crate::amount::{Amount, AmountError};
 use crate::format::Format;
 use crate::no_std::*;
 use crate::public_key::PublicKey;
@@ -49,6 +49,84 @@ pub trait Transaction: Clone + Send + Sync + 'static {
     fn to_transaction_id(&self) -> Result<Self::TransactionId, TransactionError>;
 }
 
+/// Represents a message an external signer signs for a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SigningPayload {
+    /// The index of the signature, the index of the input of a UTXO
+    /// transaction or 0 for an account transaction
+    pub index: usize,
+    /// The 32 byte digest to sign
+    pub digest: Vec<u8>,
+}
+
+/// The interface for transactions signed by external signers, one signature
+/// per payload, so that transactions of UTXO and account chains share one
+/// signing workflow: request the payloads, sign each digest, and apply each
+/// signature with the public key it verifies under.
+pub trait MultiSignTransaction: Transaction {
+    /// Returns the payloads to sign, one per signature the transaction needs.
+    fn signing_payloads(&self) -> Result<Vec<SigningPayload>, TransactionError>;
+
+    /// Inserts the 64 byte signature of payload 'index' made by 'public_key'.
+    fn apply_signature(
+        &mut self,
+        index: usize,
+        signature: Vec<u8>,
+        public_key: &Self::PublicKey,
+    ) -> Result<(), TransactionError>;
+
+    /// Signs every payload with 'signer', which returns the signature of a
+    /// payload and the public key it verifies under, and returns the signed
+    /// transaction bytes.
+    fn sign_with<F>(&mut self, mut signer: F) -> Result<Vec<u8>, TransactionError>
+    where
+        F: FnMut(&SigningPayload) -> Result<(Vec<u8>, Self::PublicKey), TransactionError>,
+    {
+        for payload in self.signing_payloads()? {
+            let (signature, public_key) = signer(&payload)?;
+            self.apply_signature(payload.index, signature, &public_key)?;
+        }
+        self.to_bytes()
+    }
+}
+
+/// The interface for building transfers without the transaction parameters
+/// of a chain, so that wallets holding several chains build them alike: set
+/// the sender, add the recipients, set the fee, and build the unsigned
+/// transaction.
+pub trait TransactionBuilder {
+    type Transaction: Transaction;
+    type Amount: Amount;
+    /// The fee of the chain, e.g. an amount or a gas limit and price
+    type Fee;
+
+    /// Sets the address the transfer is sent from.
+    fn set_sender(&mut self, sender: <Self::Transaction as Transaction>::Address) -> &mut Self;
+
+    /// Adds a recipient of 'amount', failing if the chain does not pay it.
+    fn add_recipient(
+        &mut self,
+        recipient: <Self::Transaction as Transaction>::Address,
+        amount: Self::Amount,
+    ) -> Result<&mut Self, TransactionError>;
+
+    /// Adds a recipient of 'value' in the smallest unit of the chain, e.g.
+    /// satoshis or wei.
+    fn add_recipient_value(
+        &mut self,
+        recipient: <Self::Transaction as Transaction>::Address,
+        value: u128,
+    ) -> Result<&mut Self, TransactionError> {
+        self.add_recipient(recipient, Self::Amount::from_smallest_unit(value)?)
+    }
+
+    /// Sets the fee of the transfer.
+    fn set_fee(&mut self, fee: Self::Fee) -> &mut Self;
+
+    /// Returns the unsigned transaction of the transfer.
+    fn build(&self) -> Result<Self::Transaction, TransactionError>;
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TransactionError {
     #[error("{0}")]
@@ -70,7 +148,7 @@ pub enum TransactionError {
     InvalidBindingSig(),
 
     #[error("invalid chain id {0}")]
-    InvalidChainId(u8),
+    InvalidChainId(u64),
 
     #[error("invalid ephemeral key {0}")]
     InvalidEphemeralKey(String),
@@ -93,6 +171,9 @@ pub enum TransactionError {
     #[error("invalid segwit flag: {0}")]
     InvalidSegwitFlag(usize),
 
+    #[error("invalid signature hash type: {0:#04x}")]
+    InvalidSignatureHash(u8),
+
     #[error("invalid spend description for address")]
     InvalidSpendDescription,
 
@@ -107,12 +188,18 @@ pub enum TransactionError {
     #[error("invalid variable size integer: {0}")]
     InvalidVariableSizeInteger(usize),
 
+    #[error("{0} exceeds the limit: {{ limit: {1}, found: {2} }}")]
+    LimitExceeded(String, usize, usize),
+
     #[error("{0}")]
     Message(String),
 
     #[error("missing diversifier, check that the address is a Sapling address")]
     MissingDiversifier,
 
+    #[error("missing address format")]
+    MissingFormat,
+
     #[error("missing outpoint address")]
     MissingOutpointAddress,
 
@@ -137,6 +224,9 @@ pub enum TransactionError {
     #[error("Null Error")]
     NullError(()),
 
+    #[error("unexpected trailing bytes after the transaction")]
+    TrailingBytes,
+
     #[error("Joinsplits are not supported")]
     UnsupportedJoinsplits,
 
@@ -150,6 +240,52 @@ pub enum TransactionError {
     EndOfArray,
 }
 
+impl TransactionError {
+    /// Returns the numeric code of the error, for FFI and logging layers.
+    /// Codes are stable: a variant keeps its code and codes are not reused.
+    /// The errors of addresses keep their own codes.
+    pub fn code(&self) -> u32 {
+        match self {
+            TransactionError::AddressError(error) => error.code(),
+            TransactionError::AmountError(_) => 2001,
+            TransactionError::ConflictingWitnessAnchors() => 2002,
+            TransactionError::Crate(..) => 2003,
+            TransactionError::FailedNoteDecryption(_) => 2004,
+            TransactionError::InvalidBindingSig() => 2005,
+            TransactionError::InvalidChainId(_) => 2006,
+            TransactionError::InvalidEphemeralKey(_) => 2007,
+            TransactionError::InvalidInputs(_) => 2008,
+            TransactionError::InvalidOutputAddress(_) => 2009,
+            TransactionError::InvalidOutputDescription(_) => 2010,
+            TransactionError::InvalidRlpLength(_) => 2011,
+            TransactionError::InvalidScriptPubKey(_) => 2012,
+            TransactionError::InvalidSegwitFlag(_) => 2013,
+            TransactionError::InvalidSpendDescription => 2014,
+            TransactionError::InvalidTransactionId(_) => 2015,
+            TransactionError::InvalidTransactionState => 2016,
+            TransactionError::InvalidVariableSizeInteger(_) => 2017,
+            TransactionError::LimitExceeded(..) => 2018,
+            TransactionError::Message(_) => 2019,
+            TransactionError::MissingDiversifier => 2020,
+            TransactionError::MissingOutpointAddress => 2021,
+            TransactionError::MissingOutpointAmount => 2022,
+            TransactionError::MissingOutpointScriptPublicKey => 2023,
+            TransactionError::MissingOutputParameters => 2024,
+            TransactionError::MissingSpendDescription => 2025,
+            TransactionError::MissingSpendParameters => 2026,
+            TransactionError::MissingSignature => 2027,
+            TransactionError::NullError(_) => 2028,
+            TransactionError::TrailingBytes => 2029,
+            TransactionError::UnsupportedJoinsplits => 2030,
+            TransactionError::UnsupportedPreimage(_) => 2031,
+            TransactionError::EndOfObject => 2032,
+            TransactionError::EndOfArray => 2033,
+            TransactionError::InvalidSignatureHash(_) => 2034,
+            TransactionError::MissingFormat => 2035,
+        }
+    }
+}
+
 impl From<crate::no_std::io::Error> for TransactionError {
     fn from(error: crate::no_std::io::Error) -> Self {
         TransactionError::Crate("crate::no_std::io", format!("{:?}", error))
@@ -168,11 +304,11 @@ impl From<()> for TransactionError {
     }
 }
 
-// impl From<base58::FromBase58Error> for TransactionError {
-//     fn from(error: base58::FromBase58Error) -> Self {
-//         TransactionError::Crate("base58", format!("{:?}", error))
-//     }
-// }
+impl From<bs58::decode::Error> for TransactionError {
+    fn from(error: bs58::decode::Error) -> Self {
+        TransactionError::Crate("bs58", format!("{:?}", error))
+    }
+}
 
 impl From<bech32::Error> for TransactionError {
     fn from(error: bech32::Error) -> Self {
@@ -198,6 +334,12 @@ impl From<hex::FromHexError> for TransactionError {
     }
 }
 
+impl From<crate::rlp::RlpError> for TransactionError {
+    fn from(error: crate::rlp::RlpError) -> Self {
+        TransactionError::Crate("rlp", format!("{:?}", error))
+    }
+}
+
 impl From<rlp::DecoderError> for TransactionError {
     fn from(error: rlp::DecoderError) -> Self {
         TransactionError::Crate("rlp", format!("{:?}", error))
@@ -217,13 +359,23 @@ impl From<serde_json::error::Error> for TransactionError {
 }
 
 #[cfg(test)]
-#[cfg(feature = "alloc")]
 mod tests {
-    use crate::func_selector;
+    use crate::{func_selector, AddressError, TransactionError};
 
     #[test]
     fn test_func_selector() {
         let selector = func_selector("transfer(address,uint256)");
         assert_eq!("a9059cbb", hex::encode(selector));
     }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(2019, TransactionError::Message("".into()).code());
+        assert_eq!(2034, TransactionError::InvalidSignatureHash(0x04).code());
+        assert_eq!(2035, TransactionError::MissingFormat.code());
+        // the errors of addresses keep their codes
+        let error = AddressError::InvalidByteLength(21);
+        assert_eq!(1004, error.code());
+        assert_eq!(1004, TransactionError::from(error).code());
+    }
 }
@@ -0,0 +1,380 @@
+//!
+//! CBOR
+//!
+//! The deterministic encoding of CBOR (RFC 8949, section 4.2.1), as signed in
+//! Filecoin messages and Cardano transaction bodies. Every value has a single
+//! accepted encoding: arguments take their shortest form, lengths are
+//! definite, and the keys of a map are unique and sorted by the bytewise order
+//! of their encodings. Floating point values are not supported.
+//!
+
+use crate::no_std::*;
+
+/// The deepest nesting of arrays, maps and tags accepted by `CborValue::decode`
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CborError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("non-canonical encoding")]
+    NonCanonical,
+
+    #[error("indefinite length items are not supported")]
+    IndefiniteLength,
+
+    #[error("map keys are not sorted or not unique")]
+    UnsortedMap,
+
+    #[error("unsupported item: {0:#04x}")]
+    Unsupported(u8),
+
+    #[error("invalid utf-8 string")]
+    InvalidUtf8,
+
+    #[error("items nested deeper than {}", MAX_DEPTH)]
+    TooDeep,
+
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+}
+
+/// A CBOR data item
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CborValue {
+    /// An unsigned integer
+    Unsigned(u64),
+    /// The negative integer -1 - n of n
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    /// A map of definite length, sorted by `encode`
+    Map(Vec<(CborValue, CborValue)>),
+    /// A tagged item, such as a CID (tag 42) or a bignum (tags 2 and 3)
+    Tag(u64, Box<CborValue>),
+    Bool(bool),
+    Null,
+}
+
+/// Appends the head of an item of major type 'major' and argument 'argument'
+/// to 'output', in its shortest form
+fn encode_head(major: u8, argument: u64, output: &mut Vec<u8>) {
+    let major = major << 5;
+    match argument {
+        0..=23 => output.push(major | argument as u8),
+        24..=0xff => output.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            output.push(major | 25);
+            output.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            output.push(major | 26);
+            output.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            output.push(major | 27);
+            output.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+/// Decodes the head at the front of 'input' into its major type and
+/// argument, rejecting arguments not in their shortest form
+fn decode_head(input: &mut &[u8]) -> Result<(u8, u64), CborError> {
+    let (&first, rest) = input.split_first().ok_or(CborError::UnexpectedEnd)?;
+    *input = rest;
+    let (major, info) = (first >> 5, first & 0x1f);
+    let (argument, min) = match info {
+        0..=23 => return Ok((major, info as u64)),
+        24 => (take(input, 1)?[0] as u64, 24),
+        25 => (
+            u16::from_be_bytes(take(input, 2)?.try_into().unwrap()) as u64,
+            0x100,
+        ),
+        26 => (
+            u32::from_be_bytes(take(input, 4)?.try_into().unwrap()) as u64,
+            0x1_0000,
+        ),
+        27 => (
+            u64::from_be_bytes(take(input, 8)?.try_into().unwrap()),
+            0x1_0000_0000,
+        ),
+        31 => return Err(CborError::IndefiniteLength),
+        _ => return Err(CborError::Unsupported(first)),
+    };
+    // floats reuse the heads of 16, 32 and 64 bit arguments
+    if major == 7 {
+        return Err(CborError::Unsupported(first));
+    }
+    match argument >= min {
+        true => Ok((major, argument)),
+        false => Err(CborError::NonCanonical),
+    }
+}
+
+/// Takes 'len' bytes from the front of 'input'
+fn take<'a>(input: &mut &'a [u8], len: u64) -> Result<&'a [u8], CborError> {
+    match usize::try_from(len) {
+        Ok(len) if len <= input.len() => {
+            let (taken, rest) = input.split_at(len);
+            *input = rest;
+            Ok(taken)
+        }
+        _ => Err(CborError::UnexpectedEnd),
+    }
+}
+
+impl CborValue {
+    /// Returns the integer item of 'value'
+    pub fn int(value: i128) -> Self {
+        match value {
+            0.. => CborValue::Unsigned(value as u64),
+            _ => CborValue::Negative((-1 - value) as u64),
+        }
+    }
+
+    /// Appends the encoding of the item to 'output'
+    pub fn encode_to(&self, output: &mut Vec<u8>) {
+        match self {
+            CborValue::Unsigned(value) => encode_head(0, *value, output),
+            CborValue::Negative(value) => encode_head(1, *value, output),
+            CborValue::Bytes(bytes) => {
+                encode_head(2, bytes.len() as u64, output);
+                output.extend_from_slice(bytes);
+            }
+            CborValue::Text(text) => {
+                encode_head(3, text.len() as u64, output);
+                output.extend_from_slice(text.as_bytes());
+            }
+            CborValue::Array(items) => {
+                encode_head(4, items.len() as u64, output);
+                for item in items {
+                    item.encode_to(output);
+                }
+            }
+            CborValue::Map(entries) => {
+                let mut entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+                    .iter()
+                    .map(|(key, value)| (key.encode(), value.encode()))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries.dedup_by(|a, b| a.0 == b.0);
+                encode_head(5, entries.len() as u64, output);
+                for (key, value) in entries {
+                    output.extend(key);
+                    output.extend(value);
+                }
+            }
+            CborValue::Tag(tag, item) => {
+                encode_head(6, *tag, output);
+                item.encode_to(output);
+            }
+            CborValue::Bool(value) => output.push(0xf4 | *value as u8),
+            CborValue::Null => output.push(0xf6),
+        }
+    }
+
+    /// Returns the encoding of the item. The entries of maps are sorted, and
+    /// of entries with the same key, only the first one is kept.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.encode_to(&mut output);
+        output
+    }
+
+    /// Decodes the item 'input', which must hold nothing else and be in the
+    /// deterministic encoding
+    pub fn decode(mut input: &[u8]) -> Result<Self, CborError> {
+        let item = Self::decode_prefix(&mut input, 0)?;
+        match input.is_empty() {
+            true => Ok(item),
+            false => Err(CborError::TrailingBytes(input.len())),
+        }
+    }
+
+    /// Decodes the item at the front of 'input', at a nesting of 'depth'
+    /// items, and advances 'input' past it
+    fn decode_prefix(input: &mut &[u8], depth: usize) -> Result<Self, CborError> {
+        if depth == MAX_DEPTH {
+            return Err(CborError::TooDeep);
+        }
+        let first = *input.first().ok_or(CborError::UnexpectedEnd)?;
+        let (major, argument) = decode_head(input)?;
+        Ok(match major {
+            0 => CborValue::Unsigned(argument),
+            1 => CborValue::Negative(argument),
+            2 => CborValue::Bytes(take(input, argument)?.to_vec()),
+            3 => {
+                let text = take(input, argument)?.to_vec();
+                CborValue::Text(String::from_utf8(text).map_err(|_| CborError::InvalidUtf8)?)
+            }
+            4 => {
+                // every item takes at least a byte
+                let mut items = Vec::with_capacity((argument as usize).min(input.len()));
+                for _ in 0..argument {
+                    items.push(Self::decode_prefix(input, depth + 1)?);
+                }
+                CborValue::Array(items)
+            }
+            5 => {
+                let mut entries = Vec::with_capacity((argument as usize).min(input.len()));
+                let mut last_key: Option<&[u8]> = None;
+                for _ in 0..argument {
+                    let start = *input;
+                    let key = Self::decode_prefix(input, depth + 1)?;
+                    let encoded = &start[..start.len() - input.len()];
+                    if last_key.is_some_and(|last| last >= encoded) {
+                        return Err(CborError::UnsortedMap);
+                    }
+                    last_key = Some(encoded);
+                    entries.push((key, Self::decode_prefix(input, depth + 1)?));
+                }
+                CborValue::Map(entries)
+            }
+            6 => CborValue::Tag(argument, Box::new(Self::decode_prefix(input, depth + 1)?)),
+            _ => match argument {
+                20 | 21 => CborValue::Bool(argument == 21),
+                22 => CborValue::Null,
+                _ => return Err(CborError::Unsupported(first)),
+            },
+        })
+    }
+
+    /// Returns the value of an unsigned integer item
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            CborValue::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer item
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            CborValue::Unsigned(value) => Some(*value as i128),
+            CborValue::Negative(value) => Some(-1 - *value as i128),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a byte string item
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            CborValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the string of a text item
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            CborValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the items of an array item
+    pub fn as_array(&self) -> Option<&[CborValue]> {
+        match self {
+            CborValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the entry of key 'key' of a map item
+    pub fn get(&self, key: &CborValue) -> Option<&CborValue> {
+        match self {
+            CborValue::Map(entries) => entries
+                .iter()
+                .find(|(entry, _)| entry == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        // the examples of the appendix A of RFC 8949
+        for (value, encoding) in [
+            (CborValue::int(0), "00"),
+            (CborValue::int(23), "17"),
+            (CborValue::int(24), "1818"),
+            (CborValue::int(1000), "1903e8"),
+            (CborValue::int(1000000), "1a000f4240"),
+            (CborValue::int(1000000000000), "1b000000e8d4a51000"),
+            (CborValue::int(-1), "20"),
+            (CborValue::int(-1000), "3903e7"),
+            (CborValue::Bytes(vec![1, 2, 3, 4]), "4401020304"),
+            (CborValue::Text("IETF".into()), "6449455446"),
+            (CborValue::Text("\u{00fc}".into()), "62c3bc"),
+            (CborValue::Bool(false), "f4"),
+            (CborValue::Bool(true), "f5"),
+            (CborValue::Null, "f6"),
+            (
+                CborValue::Tag(1, Box::new(CborValue::int(1363896240))),
+                "c11a514b67b0",
+            ),
+            (
+                CborValue::Array(vec![
+                    CborValue::int(1),
+                    CborValue::Array(vec![CborValue::int(2), CborValue::int(3)]),
+                ]),
+                "8201820203",
+            ),
+        ] {
+            assert_eq!(encoding, hex::encode(value.encode()));
+            assert_eq!(
+                value,
+                CborValue::decode(&hex::decode(encoding).unwrap()).unwrap()
+            );
+        }
+
+        // map entries are sorted by their encoded keys
+        let map = CborValue::Map(vec![
+            (CborValue::Text("b".into()), CborValue::int(2)),
+            (CborValue::int(-1), CborValue::int(1)),
+            (CborValue::int(1), CborValue::Array(vec![])),
+        ]);
+        assert_eq!("a301802001616202", hex::encode(map.encode()));
+        let decoded = CborValue::decode(&map.encode()).unwrap();
+        assert_eq!(Some(&CborValue::int(1)), decoded.get(&CborValue::int(-1)));
+        assert_eq!(Some(-1), CborValue::Negative(0).as_i128());
+    }
+
+    #[test]
+    fn test_strict_decode() {
+        for (encoding, error) in [
+            // an argument not in its shortest form
+            ("1817", CborError::NonCanonical),
+            ("190017", CborError::NonCanonical),
+            ("5f42010243030405ff", CborError::IndefiniteLength),
+            // a duplicate key and keys out of order
+            ("a2016161016162", CborError::UnsortedMap),
+            ("a2026161016162", CborError::UnsortedMap),
+            ("f93c00", CborError::Unsupported(0xf9)),
+            ("1c", CborError::Unsupported(0x1c)),
+            ("62c3", CborError::UnexpectedEnd),
+            ("62c328", CborError::InvalidUtf8),
+            ("0000", CborError::TrailingBytes(1)),
+            ("", CborError::UnexpectedEnd),
+        ] {
+            assert_eq!(
+                Err(error),
+                CborValue::decode(&hex::decode(encoding).unwrap()),
+                "{}",
+                encoding
+            );
+        }
+
+        let nested = [vec![0x81; MAX_DEPTH], vec![0x00]].concat();
+        assert_eq!(Err(CborError::TooDeep), CborValue::decode(&nested));
+        assert!(CborValue::decode(&nested[1..]).is_ok());
+    }
+}
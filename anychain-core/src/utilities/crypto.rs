@@ -1,7 +1,13 @@
+use crate::entropy::{EntropyRng, EntropySource};
 use crate::no_std::Vec;
+use crate::PublicKeyError;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use libsecp256k1::{curve::Scalar, Message, RecoveryId, SecretKey, Signature, ECMULT_GEN_CONTEXT};
 use ripemd::Ripemd160;
+use schnorrkel::{context::attach_rng, signing_context, ExpansionMode, MiniSecretKey};
 use sha2::{Digest, Sha256, Sha512};
 use sha3::Keccak256;
+use zeroize::Zeroize;
 
 #[inline]
 pub fn sha256(input: &[u8]) -> [u8; 32] {
@@ -17,6 +23,82 @@ pub fn sha512(input: &[u8]) -> [u8; 64] {
     hasher.finalize().into()
 }
 
+/// Returns the BIP340 tagged hash of 'data':
+/// SHA256(SHA256(tag) || SHA256(tag) || data)
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// Returns the HMAC-SHA256 (RFC 2104) of 'data' keyed with 'key'
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    match key.len() > block.len() {
+        true => block[..32].copy_from_slice(&sha256(key)),
+        false => block[..key.len()].copy_from_slice(key),
+    }
+
+    let mut pad = block.map(|byte| byte ^ 0x36);
+    let inner = Sha256::new()
+        .chain_update(pad)
+        .chain_update(data)
+        .finalize();
+    pad = block.map(|byte| byte ^ 0x5c);
+    let mac = Sha256::new()
+        .chain_update(pad)
+        .chain_update(inner)
+        .finalize();
+    block.zeroize();
+    pad.zeroize();
+    mac.into()
+}
+
+/// Returns the HMAC-SHA512 (RFC 2104) of 'data' keyed with 'key'
+pub fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut block = [0u8; 128];
+    match key.len() > block.len() {
+        true => block[..64].copy_from_slice(&sha512(key)),
+        false => block[..key.len()].copy_from_slice(key),
+    }
+
+    let mut pad = block.map(|byte| byte ^ 0x36);
+    let inner = Sha512::new()
+        .chain_update(pad)
+        .chain_update(data)
+        .finalize();
+    pad = block.map(|byte| byte ^ 0x5c);
+    let mac = Sha512::new()
+        .chain_update(pad)
+        .chain_update(inner)
+        .finalize();
+    // the key is often secret, e.g. a chain code or a mnemonic
+    block.zeroize();
+    pad.zeroize();
+    mac.into()
+}
+
+/// Returns the PBKDF2 (RFC 8018) key of 'password' and 'salt' with HMAC-SHA512
+/// as the pseudorandom function and a single 64 byte output block
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 64] {
+    let mut u = hmac_sha512(password, &[salt, &1u32.to_be_bytes()].concat());
+    let mut key = u;
+    for _ in 1..iterations {
+        u = hmac_sha512(password, &u);
+        key.iter_mut().zip(u).for_each(|(byte, u)| *byte ^= u);
+    }
+    u.zeroize();
+    key
+}
+
+/// Returns the Keccak-256 hash of 'input', the hash of Ethereum and Tron
+/// addresses, transaction ids and typed data (EIP-712). It is the Keccak
+/// submission to the SHA-3 competition, which differs from the standardized
+/// SHA3-256 in its padding.
 #[inline]
 pub fn keccak256(input: &[u8]) -> [u8; 32] {
     let mut hasher = Keccak256::new();
@@ -44,22 +126,55 @@ pub fn blake2b_160(input: &[u8]) -> [u8; 20] {
 /// Returns a 32-byte hash for given data
 #[inline]
 pub fn blake2b_256(ingest: &[u8]) -> [u8; 32] {
-    let digest =
-        blake2b_simd::Params::new()
-            .hash_length(32)
-            .to_state()
-            .update(ingest)
-            .finalize();
+    let digest = blake2b_simd::Params::new()
+        .hash_length(32)
+        .to_state()
+        .update(ingest)
+        .finalize();
 
     let mut hash = [0u8; 32];
     hash.clone_from_slice(digest.as_bytes());
     hash
 }
 
+/// Returns the 64 byte BLAKE2b hash of 'input'
+#[inline]
+pub fn blake2b_512(input: &[u8]) -> [u8; 64] {
+    let digest = blake2b_simd::Params::new()
+        .hash_length(64)
+        .to_state()
+        .update(input)
+        .finalize();
+
+    let mut hash = [0u8; 64];
+    hash.copy_from_slice(digest.as_bytes());
+    hash
+}
+
+/// Returns the 32 byte BLAKE2b hash of 'input' with the personalization
+/// 'personal', which separates the hashes of different purposes, e.g. the
+/// signature hashes of Zcash, personalized with "ZcashSigHash" and the
+/// branch id of the network upgrade
+#[inline]
+pub fn blake2b_personal(personal: &[u8; 16], input: &[u8]) -> [u8; 32] {
+    let digest = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(personal)
+        .to_state()
+        .update(input)
+        .finalize();
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(digest.as_bytes());
+    hash
+}
+
 pub fn checksum(data: &[u8]) -> crate::no_std::Vec<u8> {
     Sha256::digest(Sha256::digest(data)).to_vec()
 }
 
+/// Returns the RIPEMD160 of the SHA256 of 'bytes', the hash of public keys
+/// and scripts in Bitcoin addresses
 pub fn hash160(bytes: &[u8]) -> crate::no_std::Vec<u8> {
     Ripemd160::digest(Sha256::digest(bytes)).to_vec()
 }
@@ -77,3 +192,536 @@ pub fn blake2b_checksum(ingest: &[u8]) -> Vec<u8> {
         .as_bytes()
         .to_vec()
 }
+
+/// Generates the deterministic nonces of RFC6979 with HMAC-SHA256, the
+/// candidates tried in turn until one yields a valid signature
+struct Rfc6979 {
+    k: [u8; 32],
+    v: [u8; 32],
+}
+
+impl Rfc6979 {
+    /// Seeds the generator with the secret key, the message reduced modulo
+    /// the curve order, and the extra data of section 3.6, if any
+    fn new(secret_key: &[u8; 32], message: &[u8; 32], extra_entropy: &[u8]) -> Self {
+        let mut generator = Self {
+            k: [0; 32],
+            v: [1; 32],
+        };
+        for separator in [0x00, 0x01] {
+            let data = [
+                &generator.v[..],
+                &[separator],
+                secret_key,
+                message,
+                extra_entropy,
+            ]
+            .concat();
+            generator.k = hmac_sha256(&generator.k, &data);
+            generator.v = hmac_sha256(&generator.k, &generator.v);
+        }
+        generator
+    }
+
+    /// Returns the next candidate nonce, which may be out of range
+    fn next(&mut self) -> [u8; 32] {
+        self.v = hmac_sha256(&self.k, &self.v);
+        let candidate = self.v;
+        self.k = hmac_sha256(&self.k, &[&self.v[..], &[0x00]].concat());
+        self.v = hmac_sha256(&self.k, &self.v);
+        candidate
+    }
+}
+
+impl Drop for Rfc6979 {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
+
+/// Signs 'message' with a nonce derived from the secret key, the message and
+/// 'extra_entropy' (RFC6979), returning a low-S signature
+fn sign_rfc6979(
+    message: &Message,
+    secret_key: &SecretKey,
+    extra_entropy: &[u8],
+) -> (Signature, RecoveryId) {
+    let mut key = secret_key.serialize();
+    let mut generator = Rfc6979::new(&key, &message.0.b32(), extra_entropy);
+    key.zeroize();
+    let secret_key: Scalar = (*secret_key).into();
+    loop {
+        let mut nonce = Scalar::default();
+        let overflow = bool::from(nonce.set_b32(&generator.next()));
+        if overflow || nonce.is_zero() {
+            continue;
+        }
+        let signature = ECMULT_GEN_CONTEXT.sign_raw(&secret_key, &message.0, &nonce);
+        nonce.clear();
+        // the recovery id is 0 to 3 by construction
+        if let Ok((r, s, Ok(recid))) =
+            signature.map(|(r, s, recid)| (r, s, RecoveryId::parse(recid)))
+        {
+            return (Signature { r, s }, recid);
+        }
+    }
+}
+
+/// Signs 'message' with the deterministic nonce of RFC6979, the same
+/// signature `libsecp256k1::sign` returns
+pub fn sign_deterministic(message: &Message, secret_key: &SecretKey) -> (Signature, RecoveryId) {
+    sign_rfc6979(message, secret_key, &[])
+}
+
+/// Signs 'message' with a nonce derived from the secret key, the message and
+/// 32 bytes of auxiliary randomness, as the extra data of RFC6979. The
+/// signature stays safe if the randomness is weak, and differs between
+/// signings of the same message if it is not, hardening signers against
+/// fault attacks that compare two signatures.
+pub fn sign_with_aux_rand(
+    message: &Message,
+    secret_key: &SecretKey,
+    aux_rand: &[u8; 32],
+) -> (Signature, RecoveryId) {
+    sign_rfc6979(message, secret_key, aux_rand)
+}
+
+/// Signs 'message' as `sign_with_aux_rand` does, with 32 bytes of auxiliary
+/// randomness drawn from 'entropy'
+pub fn sign_with_entropy<E: EntropySource + ?Sized>(
+    message: &Message,
+    secret_key: &SecretKey,
+    entropy: &mut E,
+) -> (Signature, RecoveryId) {
+    let mut aux_rand = [0u8; 32];
+    entropy.fill_bytes(&mut aux_rand);
+    sign_with_aux_rand(message, secret_key, &aux_rand)
+}
+
+/// Returns true if 'signature' is the secp256k1 ECDSA signature of the 32
+/// byte 'hash' by 'public_key'. The signature is the 64 byte r || s, and a
+/// trailing recovery id, as in 65 byte recoverable signatures, is ignored.
+pub fn secp256k1_verify(
+    public_key: &libsecp256k1::PublicKey,
+    hash: &[u8],
+    signature: &[u8],
+) -> Result<bool, PublicKeyError> {
+    let message = match hash.len() {
+        32 => Message::parse_slice(hash)?,
+        len => return Err(PublicKeyError::InvalidByteLength(len)),
+    };
+    let signature = match signature.len() {
+        64 | 65 => Signature::parse_standard_slice(&signature[..64])?,
+        len => return Err(PublicKeyError::InvalidByteLength(len)),
+    };
+    Ok(libsecp256k1::verify(&message, &signature, public_key))
+}
+
+/// Returns the Ed25519 (RFC8032) public key of the 32 byte secret key 'seed'
+pub fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+/// Returns the Ed25519 signature of 'message' by the 32 byte secret key
+/// 'seed'. Signing is deterministic and needs no randomness.
+pub fn ed25519_sign(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+    SigningKey::from_bytes(seed).sign(message).to_bytes()
+}
+
+/// Returns true if 'signature' is the Ed25519 signature of 'message' by
+/// 'public_key'. Verification is strict: small order public keys and
+/// malleable signatures are rejected.
+pub fn ed25519_verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    match VerifyingKey::from_bytes(public_key) {
+        Ok(public_key) => public_key
+            .verify_strict(message, &ed25519_dalek::Signature::from_bytes(signature))
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// The signing context of Substrate based chains
+pub const SUBSTRATE_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Returns the sr25519 keypair of the 32 byte mini secret key 'seed',
+/// expanded the way Substrate expands it
+fn sr25519_keypair(seed: &[u8; 32]) -> Result<schnorrkel::Keypair, PublicKeyError> {
+    Ok(MiniSecretKey::from_bytes(seed)
+        .map_err(|error| PublicKeyError::Crate("schnorrkel", format!("{:?}", error)))?
+        .expand_to_keypair(ExpansionMode::Ed25519))
+}
+
+/// Returns the sr25519 public key of the 32 byte mini secret key 'seed'
+pub fn sr25519_public_key(seed: &[u8; 32]) -> Result<[u8; 32], PublicKeyError> {
+    Ok(sr25519_keypair(seed)?.public.to_bytes())
+}
+
+/// Returns the sr25519 signature of 'message' in the Substrate signing
+/// context by the mini secret key 'seed'. The signature nonce is drawn from
+/// 'entropy', so signing the same message twice gives different signatures.
+pub fn sr25519_sign<E: EntropySource + ?Sized>(
+    seed: &[u8; 32],
+    message: &[u8],
+    entropy: &mut E,
+) -> Result<[u8; 64], PublicKeyError> {
+    let transcript = signing_context(SUBSTRATE_SIGNING_CONTEXT).bytes(message);
+    Ok(sr25519_keypair(seed)?
+        .sign(attach_rng(transcript, EntropyRng(entropy)))
+        .to_bytes())
+}
+
+/// Returns true if 'signature' is the sr25519 signature of 'message' in the
+/// Substrate signing context by 'public_key'
+pub fn sr25519_verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        schnorrkel::PublicKey::from_bytes(public_key),
+        schnorrkel::Signature::from_bytes(signature),
+    ) else {
+        return false;
+    };
+    public_key
+        .verify_simple(SUBSTRATE_SIGNING_CONTEXT, message, &signature)
+        .is_ok()
+}
+
+/// The signature schemes of `verify_batch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// ECDSA over secp256k1, of 32 byte message hashes
+    Secp256k1,
+    /// Ed25519 (RFC8032)
+    Ed25519,
+    /// sr25519 in the Substrate signing context
+    Sr25519,
+}
+
+/// Verifies the (message, signature, public key) triples of 'items' and
+/// returns the validity of each. Ed25519 and sr25519 triples are checked at
+/// once with a batch verification, much faster than one by one, and only a
+/// failed batch is checked again one by one to locate its invalid signatures.
+/// A valid Ed25519 batch is accepted as a whole, which is slightly more
+/// lenient than the strict `ed25519_verify` for signatures crafted from
+/// points of small order. ECDSA has no batch verification, so secp256k1
+/// triples, of 32 byte message hashes, are checked in a loop over the
+/// precomputed context. Malformed triples are invalid.
+pub fn verify_batch(scheme: SignatureScheme, items: &[(&[u8], &[u8], &[u8])]) -> Vec<bool> {
+    match scheme {
+        SignatureScheme::Secp256k1 => items
+            .iter()
+            .map(|(message, signature, public_key)| {
+                match libsecp256k1::PublicKey::parse_slice(public_key, None) {
+                    Ok(public_key) => {
+                        secp256k1_verify(&public_key, message, signature).unwrap_or(false)
+                    }
+                    Err(_) => false,
+                }
+            })
+            .collect(),
+        SignatureScheme::Ed25519 => {
+            let parsed = items.iter().map(|(message, signature, public_key)| {
+                let public_key = VerifyingKey::from_bytes((*public_key).try_into().ok()?).ok()?;
+                let signature = ed25519_dalek::Signature::from_slice(signature).ok()?;
+                // small order keys are rejected, as by `ed25519_verify`
+                (!public_key.is_weak()).then_some((*message, signature, public_key))
+            });
+            let valid = |(message, signature, public_key): &(&[u8], &[u8], &[u8])| match (
+                (*public_key).try_into(),
+                (*signature).try_into(),
+            ) {
+                (Ok(public_key), Ok(signature)) => ed25519_verify(public_key, message, signature),
+                _ => false,
+            };
+            batch_or_single(items, parsed, valid, |batch| {
+                let messages: Vec<&[u8]> = batch.iter().map(|item| item.0).collect();
+                let signatures: Vec<_> = batch.iter().map(|item| item.1).collect();
+                let public_keys: Vec<_> = batch.iter().map(|item| item.2).collect();
+                ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).is_ok()
+            })
+        }
+        SignatureScheme::Sr25519 => {
+            let parsed = items.iter().map(|(message, signature, public_key)| {
+                let public_key = schnorrkel::PublicKey::from_bytes(public_key).ok()?;
+                let signature = schnorrkel::Signature::from_bytes(signature).ok()?;
+                Some((*message, signature, public_key))
+            });
+            let valid = |(message, signature, public_key): &(&[u8], &[u8], &[u8])| match (
+                (*public_key).try_into(),
+                (*signature).try_into(),
+            ) {
+                (Ok(public_key), Ok(signature)) => sr25519_verify(public_key, message, signature),
+                _ => false,
+            };
+            batch_or_single(items, parsed, valid, |batch| {
+                let context = signing_context(SUBSTRATE_SIGNING_CONTEXT);
+                let transcripts = batch.iter().map(|item| context.bytes(item.0));
+                let signatures: Vec<_> = batch.iter().map(|item| item.1).collect();
+                let public_keys: Vec<_> = batch.iter().map(|item| item.2).collect();
+                schnorrkel::verify_batch_deterministic(
+                    transcripts,
+                    &signatures,
+                    &public_keys,
+                    false,
+                )
+                .is_ok()
+            })
+        }
+    }
+}
+
+/// Returns the validity of 'items' given their 'parsed' forms, `None` for
+/// the malformed ones. The well formed items are checked together by
+/// 'verify_batch', and one by one by 'verify' if the batch fails.
+fn batch_or_single<'a, T>(
+    items: &[(&'a [u8], &'a [u8], &'a [u8])],
+    parsed: impl Iterator<Item = Option<T>>,
+    verify: impl Fn(&(&'a [u8], &'a [u8], &'a [u8])) -> bool,
+    verify_batch: impl FnOnce(&[T]) -> bool,
+) -> Vec<bool> {
+    let parsed: Vec<Option<T>> = parsed.collect();
+    let well_formed: Vec<bool> = parsed.iter().map(Option::is_some).collect();
+    let batch: Vec<T> = parsed.into_iter().flatten().collect();
+    match verify_batch(&batch) {
+        true => well_formed,
+        false => items.iter().map(verify).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::{CryptoRng, RngCore};
+
+    #[test]
+    fn test_hmac_sha256() {
+        // test case 2 of RFC4231
+        assert_eq!(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?"))
+        );
+    }
+
+    #[test]
+    fn test_sign_deterministic() {
+        let secret_key = SecretKey::parse(&[0x11; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        for digest in [[0u8; 32], sha256(b"Satoshi Nakamoto"), [0xff; 32]] {
+            let message = Message::parse(&digest);
+            let (signature, recid) = sign_deterministic(&message, &secret_key);
+            assert_eq!(
+                libsecp256k1::sign(&message, &secret_key),
+                (signature, recid)
+            );
+            assert!(!signature.s.is_high());
+
+            let (hardened, recid) = sign_with_aux_rand(&message, &secret_key, &[0x01; 32]);
+            assert_ne!(signature, hardened);
+            assert_eq!(
+                sign_with_aux_rand(&message, &secret_key, &[0x01; 32]),
+                (hardened, recid)
+            );
+            let mut rng = TestRng(rand::rngs::mock::StepRng::new(0x0101_0101_0101_0101, 0));
+            assert_eq!(
+                sign_with_entropy(&message, &secret_key, &mut rng),
+                (hardened, recid)
+            );
+            assert!(libsecp256k1::verify(&message, &hardened, &public_key));
+            assert_eq!(
+                public_key,
+                libsecp256k1::recover(&message, &hardened, &recid).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_ed25519() {
+        // test 2 of RFC8032
+        let seed = hex::decode("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let public_key = ed25519_public_key(&seed);
+        assert_eq!(
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            hex::encode(public_key)
+        );
+        let signature = ed25519_sign(&seed, &[0x72]);
+        assert_eq!(
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            hex::encode(signature)
+        );
+        assert!(ed25519_verify(&public_key, &[0x72], &signature));
+        assert!(!ed25519_verify(&public_key, &[0x73], &signature));
+        assert!(!ed25519_verify(&[0; 32], &[0x72], &signature));
+    }
+
+    /// A deterministic generator for tests, which must not sign with it
+    struct TestRng(rand::rngs::mock::StepRng);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.0.fill_bytes(dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.0.try_fill_bytes(dest)
+        }
+    }
+
+    impl CryptoRng for TestRng {}
+
+    #[test]
+    fn test_sr25519() {
+        // the development account Alice
+        let seed = hex::decode("e5be9a5092b81bca64be81d212e7f2f9eba183bb7a90954f7b76361f6edb5c0a")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let public_key = sr25519_public_key(&seed).unwrap();
+        assert_eq!(
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d",
+            hex::encode(public_key)
+        );
+        let mut rng = TestRng(rand::rngs::mock::StepRng::new(1, 1));
+        let signature = sr25519_sign(&seed, b"message", &mut rng).unwrap();
+        assert!(sr25519_verify(&public_key, b"message", &signature));
+        assert!(!sr25519_verify(&public_key, b"massage", &signature));
+        assert!(!sr25519_verify(&public_key, b"message", &[0; 64]));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let messages: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 32]).collect();
+
+        // ed25519
+        let signed: Vec<([u8; 64], [u8; 32])> = messages
+            .iter()
+            .map(|message| {
+                let seed = [message[0] + 1; 32];
+                (ed25519_sign(&seed, message), ed25519_public_key(&seed))
+            })
+            .collect();
+        let mut items: Vec<(&[u8], &[u8], &[u8])> = messages
+            .iter()
+            .zip(&signed)
+            .map(|(message, (signature, public_key))| {
+                (&message[..], &signature[..], &public_key[..])
+            })
+            .collect();
+        assert!(verify_batch(SignatureScheme::Ed25519, &items)
+            .iter()
+            .all(|valid| *valid));
+        items[3].0 = &messages[4];
+        items[5].1 = &[0; 63];
+        let expected: Vec<bool> = (0..8).map(|i| i != 3 && i != 5).collect();
+        assert_eq!(expected, verify_batch(SignatureScheme::Ed25519, &items));
+
+        // sr25519
+        let signed: Vec<([u8; 64], [u8; 32])> = messages
+            .iter()
+            .map(|message| {
+                let seed = [message[0] + 1; 32];
+                let mut rng = TestRng(rand::rngs::mock::StepRng::new(1, 1));
+                (
+                    sr25519_sign(&seed, message, &mut rng).unwrap(),
+                    sr25519_public_key(&seed).unwrap(),
+                )
+            })
+            .collect();
+        let mut items: Vec<(&[u8], &[u8], &[u8])> = messages
+            .iter()
+            .zip(&signed)
+            .map(|(message, (signature, public_key))| {
+                (&message[..], &signature[..], &public_key[..])
+            })
+            .collect();
+        assert!(verify_batch(SignatureScheme::Sr25519, &items)
+            .iter()
+            .all(|valid| *valid));
+        items[0].2 = &signed[1].1;
+        let expected: Vec<bool> = (0..8).map(|i| i != 0).collect();
+        assert_eq!(expected, verify_batch(SignatureScheme::Sr25519, &items));
+
+        // secp256k1
+        let signed: Vec<([u8; 64], [u8; 33])> = messages
+            .iter()
+            .map(|message| {
+                let secret_key = SecretKey::parse(&[message[0] + 1; 32]).unwrap();
+                let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+                let (signature, _) =
+                    sign_deterministic(&Message::parse_slice(message).unwrap(), &secret_key);
+                (signature.serialize(), public_key.serialize_compressed())
+            })
+            .collect();
+        let mut items: Vec<(&[u8], &[u8], &[u8])> = messages
+            .iter()
+            .zip(&signed)
+            .map(|(message, (signature, public_key))| {
+                (&message[..], &signature[..], &public_key[..])
+            })
+            .collect();
+        items[7].0 = &messages[7][..31];
+        let expected: Vec<bool> = (0..8).map(|i| i != 7).collect();
+        assert_eq!(expected, verify_batch(SignatureScheme::Secp256k1, &items));
+    }
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            hex::encode(keccak256(b""))
+        );
+        // the selector of the ERC-20 transfer function
+        assert_eq!(
+            "a9059cbb",
+            hex::encode(&keccak256(b"transfer(address,uint256)")[..4])
+        );
+    }
+
+    #[test]
+    fn test_blake2b() {
+        assert_eq!(
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+            hex::encode(blake2b_256(b""))
+        );
+        // the example of RFC7693
+        assert_eq!(
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            hex::encode(blake2b_512(b"abc"))
+        );
+        // the default personalization is zero
+        assert_eq!(blake2b_256(b"abc"), blake2b_personal(&[0; 16], b"abc"));
+        assert_ne!(
+            blake2b_256(b"abc"),
+            blake2b_personal(b"ZcashSigHash\xb4\xd0\xd6\xc2", b"abc")
+        );
+    }
+
+    #[test]
+    fn test_hash160() {
+        // the key hash of the address 1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH
+        assert_eq!(
+            "751e76e8199196d454941c45d1b3a323f1433bd6",
+            hex::encode(hash160(
+                &hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                    .unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tagged_hash() {
+        assert_eq!(
+            sha256(&[&sha256(b"TapLeaf")[..], &sha256(b"TapLeaf"), b"abc"].concat()),
+            tagged_hash("TapLeaf", b"abc")
+        );
+    }
+}
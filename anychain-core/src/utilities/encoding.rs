@@ -0,0 +1,191 @@
+//!
+//! Address Encodings
+//!
+//! This module implements the checksummed string encodings shared by the
+//! addresses of several chains: bech32 (BIP173) and bech32m (BIP350), for
+//! segwit addresses and for the addresses of chains such as Cosmos and
+//! Cardano, and base58check, for legacy Bitcoin addresses and the addresses
+//! of chains such as Tron, Ripple and Neo. Strings mixing upper and lower
+//! case, and human-readable parts with characters outside US-ASCII 33 to 126,
+//! are rejected when decoding bech32.
+//!
+
+use crate::crypto::checksum;
+use crate::no_std::*;
+use crate::AddressError;
+use bech32::{u5, FromBase32, ToBase32};
+
+pub use bech32::Variant;
+
+/// Returns the bech32 or bech32m encoding of the bytes 'data' with the
+/// human-readable part 'hrp'
+pub fn bech32_encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, AddressError> {
+    Ok(bech32::encode(hrp, data.to_base32(), variant)?)
+}
+
+/// Returns the human-readable part in lower case, the bytes and the variant
+/// of a bech32 or bech32m string
+pub fn bech32_decode(s: &str) -> Result<(String, Vec<u8>, Variant), AddressError> {
+    let (hrp, data, variant) = bech32::decode(s)?;
+    Ok((hrp, Vec::from_base32(&data)?, variant))
+}
+
+/// Returns the encoding of a version, as the first 5 bit group, followed by
+/// the bytes 'data', the layout of segwit and silent payment addresses
+pub fn bech32_encode_with_version(
+    hrp: &str,
+    version: u8,
+    data: &[u8],
+    variant: Variant,
+) -> Result<String, AddressError> {
+    let data = [vec![u5::try_from_u8(version)?], data.to_base32()].concat();
+    Ok(bech32::encode(hrp, data, variant)?)
+}
+
+/// Returns the human-readable part, the version, the bytes and the variant
+/// of a string encoded with a version
+pub fn bech32_decode_with_version(s: &str) -> Result<(String, u8, Vec<u8>, Variant), AddressError> {
+    let (hrp, data, variant) = bech32::decode(s)?;
+    let (version, data) = data
+        .split_first()
+        .ok_or_else(|| AddressError::InvalidAddress(s.into()))?;
+    Ok((hrp, version.to_u8(), Vec::from_base32(data)?, variant))
+}
+
+/// Returns the variant of the addresses of a witness version: bech32 for
+/// version 0 and bech32m for versions 1 to 16 (BIP350)
+pub fn witness_variant(version: u8) -> Variant {
+    match version {
+        0 => Variant::Bech32,
+        _ => Variant::Bech32m,
+    }
+}
+
+/// Checks the witness version and program length rules of BIP141
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), AddressError> {
+    match (version, program.len()) {
+        (0, 20 | 32) | (1..=16, 2..=40) => Ok(()),
+        (0, length) | (1..=16, length) => Err(AddressError::InvalidByteLength(length)),
+        (version, _) => Err(AddressError::Message(format!(
+            "invalid witness version {}",
+            version
+        ))),
+    }
+}
+
+/// Returns the segwit address of a witness version and program
+pub fn segwit_encode(hrp: &str, version: u8, program: &[u8]) -> Result<String, AddressError> {
+    check_witness_program(version, program)?;
+    bech32_encode_with_version(hrp, version, program, witness_variant(version))
+}
+
+/// Returns the human-readable part, the witness version and the witness
+/// program of a segwit address, failing if the address is not encoded in
+/// the variant of its witness version
+pub fn segwit_decode(address: &str) -> Result<(String, u8, Vec<u8>), AddressError> {
+    let (hrp, version, program, variant) = bech32_decode_with_version(address)?;
+    if variant != witness_variant(version) {
+        return Err(AddressError::InvalidAddress(address.into()));
+    }
+    check_witness_program(version, &program)?;
+    Ok((hrp, version, program))
+}
+
+/// Returns the base58 encoding of a version byte followed by 'payload' and
+/// the first 4 bytes of the double SHA256 of both
+pub fn base58check_encode(version: u8, payload: &[u8]) -> String {
+    let mut data = vec![version];
+    data.extend(payload);
+    let checksum = checksum(&data);
+    data.extend(&checksum[..4]);
+    bs58::encode(data).into_string()
+}
+
+/// Returns the version byte and the payload of a base58check string. The
+/// checksum is compared in constant time.
+pub fn base58check_decode(s: &str) -> Result<(u8, Vec<u8>), AddressError> {
+    let data = bs58::decode(s).into_vec()?;
+    if data.len() < 5 {
+        return Err(AddressError::InvalidByteLength(data.len()));
+    }
+
+    let (bytes, checksum_provided) = data.split_at(data.len() - 4);
+    let checksum_gen = &checksum(bytes)[..4];
+    if !constant_time_eq(checksum_gen, checksum_provided) {
+        return Err(AddressError::InvalidChecksum(
+            base58check_encode(bytes[0], &bytes[1..]),
+            s.into(),
+        ));
+    }
+    Ok((bytes[0], bytes[1..].to_vec()))
+}
+
+/// Returns whether 'a' and 'b' are equal, in a time independent of the
+/// position of the first byte they differ at
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segwit() {
+        // the test vectors of BIP350
+        let (hrp, version, program) =
+            segwit_decode("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap();
+        assert_eq!(("bc", 0), (hrp.as_str(), version));
+        assert_eq!(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            segwit_encode("bc", 0, &program).unwrap()
+        );
+        let address = "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y";
+        let (_, version, program) = segwit_decode(address).unwrap();
+        assert_eq!((1, 40), (version, program.len()));
+        assert_eq!(address, segwit_encode("bc", 1, &program).unwrap());
+
+        // mixed case
+        assert!(segwit_decode("bc1qW508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_err());
+        // a version 0 program with the bech32m checksum
+        assert!(segwit_decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh").is_err());
+        // a version 1 program with the bech32 checksum
+        assert!(
+            segwit_decode("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd")
+                .is_err()
+        );
+        assert!(segwit_encode("bc", 0, &[0; 21]).is_err());
+        assert!(segwit_encode("bc", 17, &[0; 32]).is_err());
+    }
+
+    #[test]
+    fn test_bech32() {
+        let encoded = bech32_encode("cosmos", &[0x55; 20], Variant::Bech32).unwrap();
+        assert_eq!(
+            ("cosmos".to_string(), vec![0x55; 20], Variant::Bech32),
+            bech32_decode(&encoded).unwrap()
+        );
+        assert!(bech32_decode(&encoded.replace("cosmos", "COSMOS")).is_err());
+        // a character out of range in the human-readable part
+        assert!(bech32_decode("\x7f1axkwrx").is_err());
+    }
+
+    #[test]
+    fn test_base58check() {
+        let address = "1HLoD9E4SDFFPDiYfNYnkBLQ85Y51J3Zb1";
+        let (version, hash) = base58check_decode(address).unwrap();
+        assert_eq!(
+            (0, "b3407d4b4d1fca87fb930abe3fa6c2baed6e6fd8"),
+            (version, hex::encode(&hash).as_str())
+        );
+        assert_eq!(address, base58check_encode(version, &hash));
+
+        // the last character changed
+        assert!(matches!(
+            base58check_decode("1HLoD9E4SDFFPDiYfNYnkBLQ85Y51J3Zb2"),
+            Err(AddressError::InvalidChecksum(expected, _)) if expected == address
+        ));
+        assert!(base58check_decode("1111").is_err());
+        assert!(base58check_decode("0OIl").is_err());
+    }
+}
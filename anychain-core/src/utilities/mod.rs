@@ -1,8 +1,18 @@
 use crate::no_std::*;
 
 //#[cfg_attr(test, macro_use)]
+pub mod cbor;
+
 pub mod crypto;
 
+pub mod encoding;
+
+pub mod protobuf;
+
+pub mod rlp;
+
+pub mod scale;
+
 pub fn to_hex_string(bytes: &[u8]) -> String {
     bytes
         .iter()
@@ -0,0 +1,354 @@
+//!
+//! Protobuf
+//!
+//! A minimal implementation of the protocol buffers wire format, enough to
+//! build and read messages such as the Cosmos `SignDoc` or the raw data of
+//! Tron transactions without generated code. A message is a sequence of
+//! fields, each a varint key holding the field number and the wire type,
+//! followed by a varint, a fixed width integer or a length delimited payload.
+//! `ProtoWriter` writes fields in call order and, as proto3 does for scalar
+//! fields, omits those of default value, so fields must be written in field
+//! number order for the canonical encoding signers expect.
+//!
+
+use crate::no_std::*;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProtoError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("varint longer than 10 bytes or above 2^64")]
+    VarintOverflow,
+
+    #[error("invalid field number: {0}")]
+    InvalidFieldNumber(u64),
+
+    #[error("unsupported wire type: {0}")]
+    InvalidWireType(u8),
+}
+
+/// The wire types of protobuf fields, but the deprecated groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireType {
+    Varint = 0,
+    Fixed64 = 1,
+    Len = 2,
+    Fixed32 = 5,
+}
+
+/// Appends the varint of 'value' to 'output': its seven bit groups, least
+/// significant first, with the high bit set on all but the last byte
+pub fn encode_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Decodes a varint from the front of 'input' and advances 'input' past it
+pub fn decode_varint(input: &mut &[u8]) -> Result<u64, ProtoError> {
+    let mut value = 0u64;
+    for (index, byte) in input.iter().enumerate() {
+        // the tenth byte holds the last bit of a u64
+        if index == 9 && *byte > 1 {
+            return Err(ProtoError::VarintOverflow);
+        }
+        value |= ((byte & 0x7f) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            *input = &input[index + 1..];
+            return Ok(value);
+        }
+    }
+    Err(ProtoError::UnexpectedEnd)
+}
+
+/// Returns the zigzag encoding of a signed integer, as used by `sint64`
+pub fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Returns the signed integer of a zigzag encoding
+pub fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// A writer of protobuf messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtoWriter {
+    buffer: Vec<u8>,
+}
+
+impl ProtoWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the key of field 'field' of wire type 'wire_type'
+    pub fn key(&mut self, field: u32, wire_type: WireType) -> &mut Self {
+        encode_varint((field as u64) << 3 | wire_type as u64, &mut self.buffer);
+        self
+    }
+
+    /// Writes the `uint64`, `uint32`, `int32` or `enum` field 'field', unless
+    /// 'value' is 0. Negative `int32` and `int64` values are written as their
+    /// two's complement u64.
+    pub fn uint64(&mut self, field: u32, value: u64) -> &mut Self {
+        if value != 0 {
+            self.key(field, WireType::Varint);
+            encode_varint(value, &mut self.buffer);
+        }
+        self
+    }
+
+    /// Writes the `int64` field 'field', unless 'value' is 0
+    pub fn int64(&mut self, field: u32, value: i64) -> &mut Self {
+        self.uint64(field, value as u64)
+    }
+
+    /// Writes the `sint64` or `sint32` field 'field', unless 'value' is 0
+    pub fn sint64(&mut self, field: u32, value: i64) -> &mut Self {
+        self.uint64(field, zigzag(value))
+    }
+
+    /// Writes the `bool` field 'field', unless 'value' is false
+    pub fn bool(&mut self, field: u32, value: bool) -> &mut Self {
+        self.uint64(field, value as u64)
+    }
+
+    /// Writes the `fixed64`, `sfixed64` or `double` field 'field', unless
+    /// 'value' is 0
+    pub fn fixed64(&mut self, field: u32, value: u64) -> &mut Self {
+        if value != 0 {
+            self.key(field, WireType::Fixed64);
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        self
+    }
+
+    /// Writes the `fixed32`, `sfixed32` or `float` field 'field', unless
+    /// 'value' is 0
+    pub fn fixed32(&mut self, field: u32, value: u32) -> &mut Self {
+        if value != 0 {
+            self.key(field, WireType::Fixed32);
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        self
+    }
+
+    /// Writes the `bytes` field 'field', unless 'value' is empty
+    pub fn bytes(&mut self, field: u32, value: &[u8]) -> &mut Self {
+        if !value.is_empty() {
+            self.len_delimited(field, value);
+        }
+        self
+    }
+
+    /// Writes the `string` field 'field', unless 'value' is empty
+    pub fn string(&mut self, field: u32, value: &str) -> &mut Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    /// Writes the embedded message field 'field' of encoding 'message'. A
+    /// message is written even if empty, as it is then set but of default
+    /// value, which differs from unset.
+    pub fn message(&mut self, field: u32, message: &[u8]) -> &mut Self {
+        self.len_delimited(field, message)
+    }
+
+    /// Writes a length delimited field whatever its payload, as an element of
+    /// a repeated `bytes`, `string` or message field must be
+    pub fn len_delimited(&mut self, field: u32, payload: &[u8]) -> &mut Self {
+        self.key(field, WireType::Len);
+        encode_varint(payload.len() as u64, &mut self.buffer);
+        self.buffer.extend_from_slice(payload);
+        self
+    }
+
+    /// Returns the encoding of the message written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns the encoding of the message
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+/// The value of a field, as read from the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Len(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> ProtoValue<'a> {
+    /// Returns the integer of a varint or fixed width value
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ProtoValue::Varint(value) | ProtoValue::Fixed64(value) => Some(*value),
+            ProtoValue::Fixed32(value) => Some(*value as u64),
+            ProtoValue::Len(_) => None,
+        }
+    }
+
+    /// Returns the payload of a length delimited value
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            ProtoValue::Len(payload) => Some(payload),
+            _ => None,
+        }
+    }
+}
+
+/// A reader of the fields of a protobuf message, in wire order. Fields are
+/// (field number, value) pairs, and reading stops at the first malformed one.
+#[derive(Debug, Clone)]
+pub struct ProtoReader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    pub fn new(message: &'a [u8]) -> Self {
+        Self { input: message }
+    }
+
+    /// Reads the field at the front of the input
+    fn read_field(&mut self) -> Result<(u32, ProtoValue<'a>), ProtoError> {
+        let key = decode_varint(&mut self.input)?;
+        let field = key >> 3;
+        if field == 0 || field > u32::MAX as u64 >> 3 {
+            return Err(ProtoError::InvalidFieldNumber(field));
+        }
+        let value = match key & 0x7 {
+            0 => ProtoValue::Varint(decode_varint(&mut self.input)?),
+            1 => ProtoValue::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = decode_varint(&mut self.input)?;
+                let len = usize::try_from(len).map_err(|_| ProtoError::UnexpectedEnd)?;
+                ProtoValue::Len(self.take(len)?)
+            }
+            5 => ProtoValue::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            wire_type => return Err(ProtoError::InvalidWireType(wire_type as u8)),
+        };
+        Ok((field as u32, value))
+    }
+
+    /// Takes 'len' bytes from the front of the input
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProtoError> {
+        if self.input.len() < len {
+            return Err(ProtoError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(taken)
+    }
+}
+
+impl<'a> Iterator for ProtoReader<'a> {
+    type Item = Result<(u32, ProtoValue<'a>), ProtoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let field = self.read_field();
+        if field.is_err() {
+            // a malformed field ends the message
+            self.input = &[];
+        }
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        for (value, encoding) in [
+            (0u64, "00"),
+            (1, "01"),
+            (150, "9601"),
+            (300, "ac02"),
+            (u64::MAX, "ffffffffffffffffff01"),
+        ] {
+            let mut output = vec![];
+            encode_varint(value, &mut output);
+            assert_eq!(encoding, hex::encode(&output));
+            assert_eq!(value, decode_varint(&mut &output[..]).unwrap());
+        }
+        let overflow = hex::decode("ffffffffffffffffff02").unwrap();
+        assert_eq!(
+            Err(ProtoError::VarintOverflow),
+            decode_varint(&mut &overflow[..])
+        );
+        assert_eq!(
+            Err(ProtoError::UnexpectedEnd),
+            decode_varint(&mut &[0x96][..])
+        );
+
+        for value in [0i64, -1, 1, -2, i64::MAX, i64::MIN] {
+            assert_eq!(value, unzigzag(zigzag(value)));
+        }
+        assert_eq!(3, zigzag(-2));
+    }
+
+    #[test]
+    fn test_message() {
+        // the examples of the protobuf encoding guide
+        let mut writer = ProtoWriter::new();
+        writer.uint64(1, 150);
+        assert_eq!("089601", hex::encode(writer.as_bytes()));
+        let mut writer = ProtoWriter::new();
+        writer.string(2, "testing");
+        assert_eq!("120774657374696e67", hex::encode(writer.as_bytes()));
+
+        let mut inner = ProtoWriter::new();
+        inner.uint64(1, 150);
+        let mut writer = ProtoWriter::new();
+        writer
+            .uint64(1, 0)
+            .string(2, "")
+            .message(3, inner.as_bytes())
+            .sint64(4, -2)
+            .fixed64(5, 7)
+            .bool(6, true);
+        // default scalars are omitted
+        assert_eq!(
+            "1a0308960120032907000000000000003001",
+            hex::encode(writer.as_bytes())
+        );
+
+        let fields: Vec<_> = ProtoReader::new(writer.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (3, ProtoValue::Len(inner.as_bytes())),
+                (4, ProtoValue::Varint(3)),
+                (5, ProtoValue::Fixed64(7)),
+                (6, ProtoValue::Varint(1)),
+            ],
+            fields
+        );
+
+        // a truncated payload ends the message with an error
+        let mut reader = ProtoReader::new(&[0x12, 0x07, 0x74]);
+        assert_eq!(Some(Err(ProtoError::UnexpectedEnd)), reader.next());
+        assert_eq!(None, reader.next());
+        assert_eq!(
+            Some(Err(ProtoError::InvalidWireType(3))),
+            ProtoReader::new(&[0x0b]).next()
+        );
+        assert_eq!(
+            Some(Err(ProtoError::InvalidFieldNumber(0))),
+            ProtoReader::new(&[0x00, 0x00]).next()
+        );
+    }
+}
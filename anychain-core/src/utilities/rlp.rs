@@ -0,0 +1,346 @@
+//!
+//! RLP
+//!
+//! The Recursive Length Prefix encoding of Ethereum, as specified in the
+//! appendix B of the yellow paper. An item is a byte string or a list of
+//! items. Strings of one byte below 0x80 are their own encoding, other items
+//! are preceded by a header holding their kind and the length of their
+//! payload, and integers are the big endian byte strings of their value with
+//! no leading zeros. Decoding is strict: an item has exactly one accepted
+//! encoding, which is the one `encode` returns.
+//!
+
+use crate::no_std::*;
+
+/// The deepest nesting of lists accepted by `RlpItem::decode`
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RlpError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("non-canonical encoding")]
+    NonCanonical,
+
+    #[error("integer with leading zeros")]
+    LeadingZeros,
+
+    #[error("integer does not fit its type")]
+    Overflow,
+
+    #[error("expected a byte string")]
+    ExpectedBytes,
+
+    #[error("expected a list")]
+    ExpectedList,
+
+    #[error("expected a list of {0} items, found {1}")]
+    InvalidListLength(usize, usize),
+
+    #[error("lists nested deeper than {}", MAX_DEPTH)]
+    TooDeep,
+
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+}
+
+/// A decoded RLP item
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+/// Appends the header of an item of 'len' payload bytes to 'output', given
+/// the offset of the kind of item, 0x80 for strings and 0xc0 for lists
+fn encode_header(offset: u8, len: usize, output: &mut Vec<u8>) {
+    match len {
+        0..=55 => output.push(offset + len as u8),
+        _ => {
+            let len = len.to_be_bytes();
+            let zeros = len.iter().take_while(|byte| **byte == 0).count();
+            output.push(offset + 55 + (len.len() - zeros) as u8);
+            output.extend_from_slice(&len[zeros..]);
+        }
+    }
+}
+
+/// Appends the encoding of the byte string 'bytes' to 'output'
+pub fn encode_bytes(bytes: &[u8], output: &mut Vec<u8>) {
+    match bytes {
+        [byte] if *byte < 0x80 => output.push(*byte),
+        _ => {
+            encode_header(0x80, bytes.len(), output);
+            output.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Appends the encoding of the integer 'value' to 'output'
+pub fn encode_uint(value: u128, output: &mut Vec<u8>) {
+    let bytes = value.to_be_bytes();
+    let zeros = value.leading_zeros() as usize / 8;
+    encode_bytes(&bytes[zeros..], output)
+}
+
+/// Appends the encoding of the list of already encoded items 'payload' to
+/// 'output'
+pub fn encode_list(payload: &[u8], output: &mut Vec<u8>) {
+    encode_header(0xc0, payload.len(), output);
+    output.extend_from_slice(payload);
+}
+
+/// Returns the big endian integer of 'bytes', which must have no leading zeros
+pub fn decode_uint(bytes: &[u8]) -> Result<u128, RlpError> {
+    match bytes {
+        [0, ..] => Err(RlpError::LeadingZeros),
+        _ if bytes.len() > 16 => Err(RlpError::Overflow),
+        _ => Ok(bytes
+            .iter()
+            .fold(0u128, |value, byte| value << 8 | *byte as u128)),
+    }
+}
+
+/// Splits the item at the front of 'input' into whether it is a list, its
+/// payload and the rest of 'input', rejecting any non-canonical header
+fn split_item(input: &[u8]) -> Result<(bool, &[u8], &[u8]), RlpError> {
+    let (&first, rest) = input.split_first().ok_or(RlpError::UnexpectedEnd)?;
+    let (is_list, offset) = match first {
+        0x00..=0x7f => return Ok((false, &input[..1], rest)),
+        0x80..=0xbf => (false, 0x80),
+        0xc0..=0xff => (true, 0xc0),
+    };
+
+    let (len, rest) = match first - offset {
+        short @ 0..=55 => (short as usize, rest),
+        long => {
+            let size = (long - 55) as usize;
+            if rest.len() < size {
+                return Err(RlpError::UnexpectedEnd);
+            }
+            let (len, rest) = rest.split_at(size);
+            if len[0] == 0 {
+                return Err(RlpError::NonCanonical);
+            }
+            if size > core::mem::size_of::<usize>() {
+                return Err(RlpError::Overflow);
+            }
+            let len = len
+                .iter()
+                .fold(0usize, |len, byte| len << 8 | *byte as usize);
+            // short payloads have a short header
+            if len <= 55 {
+                return Err(RlpError::NonCanonical);
+            }
+            (len, rest)
+        }
+    };
+    if rest.len() < len {
+        return Err(RlpError::UnexpectedEnd);
+    }
+    let (payload, rest) = rest.split_at(len);
+    // a single byte below 0x80 is its own encoding
+    if !is_list && len == 1 && payload[0] < 0x80 {
+        return Err(RlpError::NonCanonical);
+    }
+    Ok((is_list, payload, rest))
+}
+
+impl RlpItem {
+    /// Returns the integer item of 'value'
+    pub fn uint(value: u128) -> Self {
+        let zeros = value.leading_zeros() as usize / 8;
+        RlpItem::Bytes(value.to_be_bytes()[zeros..].to_vec())
+    }
+
+    /// Appends the encoding of the item to 'output'
+    pub fn encode_to(&self, output: &mut Vec<u8>) {
+        match self {
+            RlpItem::Bytes(bytes) => encode_bytes(bytes, output),
+            RlpItem::List(items) => {
+                let mut payload = Vec::new();
+                for item in items {
+                    item.encode_to(&mut payload);
+                }
+                encode_list(&payload, output);
+            }
+        }
+    }
+
+    /// Returns the encoding of the item
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.encode_to(&mut output);
+        output
+    }
+
+    /// Decodes the item 'input', which must hold nothing else
+    pub fn decode(input: &[u8]) -> Result<Self, RlpError> {
+        let (item, rest) = Self::decode_prefix(input, 0)?;
+        match rest.is_empty() {
+            true => Ok(item),
+            false => Err(RlpError::TrailingBytes(rest.len())),
+        }
+    }
+
+    /// Decodes the item at the front of 'input', at a nesting of 'depth'
+    /// lists, and returns it with the rest of 'input'
+    fn decode_prefix(input: &[u8], depth: usize) -> Result<(Self, &[u8]), RlpError> {
+        let (is_list, mut payload, rest) = split_item(input)?;
+        if !is_list {
+            return Ok((RlpItem::Bytes(payload.to_vec()), rest));
+        }
+        if depth == MAX_DEPTH {
+            return Err(RlpError::TooDeep);
+        }
+        let mut items = Vec::new();
+        while !payload.is_empty() {
+            let (item, remaining) = Self::decode_prefix(payload, depth + 1)?;
+            items.push(item);
+            payload = remaining;
+        }
+        Ok((RlpItem::List(items), rest))
+    }
+
+    /// Returns the bytes of a byte string item
+    pub fn as_bytes(&self) -> Result<&[u8], RlpError> {
+        match self {
+            RlpItem::Bytes(bytes) => Ok(bytes),
+            RlpItem::List(_) => Err(RlpError::ExpectedBytes),
+        }
+    }
+
+    /// Returns the items of a list item
+    pub fn as_list(&self) -> Result<&[RlpItem], RlpError> {
+        match self {
+            RlpItem::List(items) => Ok(items),
+            RlpItem::Bytes(_) => Err(RlpError::ExpectedList),
+        }
+    }
+
+    /// Returns the items of a list item of exactly 'len' items
+    pub fn as_list_of(&self, len: usize) -> Result<&[RlpItem], RlpError> {
+        let items = self.as_list()?;
+        match items.len() == len {
+            true => Ok(items),
+            false => Err(RlpError::InvalidListLength(len, items.len())),
+        }
+    }
+
+    /// Returns the integer of a byte string item
+    pub fn as_uint(&self) -> Result<u128, RlpError> {
+        decode_uint(self.as_bytes()?)
+    }
+
+    /// Returns the integer of a byte string item that fits a u64
+    pub fn as_u64(&self) -> Result<u64, RlpError> {
+        u64::try_from(self.as_uint()?).map_err(|_| RlpError::Overflow)
+    }
+}
+
+impl From<&[u8]> for RlpItem {
+    fn from(bytes: &[u8]) -> Self {
+        RlpItem::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for RlpItem {
+    fn from(bytes: Vec<u8>) -> Self {
+        RlpItem::Bytes(bytes)
+    }
+}
+
+impl From<Vec<RlpItem>> for RlpItem {
+    fn from(items: Vec<RlpItem>) -> Self {
+        RlpItem::List(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        // the examples of the Ethereum wiki
+        let dog = RlpItem::from(&b"dog"[..]);
+        assert_eq!("83646f67", hex::encode(dog.encode()));
+        let cat_dog = RlpItem::List(vec![b"cat".to_vec().into(), b"dog".to_vec().into()]);
+        assert_eq!("c88363617483646f67", hex::encode(cat_dog.encode()));
+        assert_eq!("80", hex::encode(RlpItem::uint(0).encode()));
+        assert_eq!("00", hex::encode(RlpItem::from(&[0u8][..]).encode()));
+        assert_eq!("0f", hex::encode(RlpItem::uint(15).encode()));
+        assert_eq!("820400", hex::encode(RlpItem::uint(1024).encode()));
+        assert_eq!("c0", hex::encode(RlpItem::List(vec![]).encode()));
+
+        // the set theoretical representation of three
+        let zero = RlpItem::List(vec![]);
+        let one = RlpItem::List(vec![zero.clone()]);
+        let two = RlpItem::List(vec![zero.clone(), one.clone()]);
+        let three = RlpItem::List(vec![zero, one, two]);
+        assert_eq!("c7c0c1c0c3c0c1c0", hex::encode(three.encode()));
+        assert_eq!(three, RlpItem::decode(&three.encode()).unwrap());
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let encoded = RlpItem::from(&lorem[..]).encode();
+        assert_eq!("b838", hex::encode(&encoded[..2]));
+        assert_eq!(
+            &lorem[..],
+            RlpItem::decode(&encoded).unwrap().as_bytes().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_strict_decode() {
+        let item = RlpItem::decode(&hex::decode("c88363617483646f67").unwrap()).unwrap();
+        let items = item.as_list_of(2).unwrap();
+        assert_eq!(b"cat", items[0].as_bytes().unwrap());
+        assert_eq!(
+            Err(RlpError::InvalidListLength(3, 2)),
+            item.as_list_of(3).map(|_| ())
+        );
+        assert_eq!(Err(RlpError::ExpectedBytes), item.as_uint());
+        assert_eq!(
+            1024,
+            RlpItem::decode(&[0x82, 0x04, 0x00])
+                .unwrap()
+                .as_u64()
+                .unwrap()
+        );
+
+        for (encoding, error) in [
+            // a single byte below 0x80 with a header
+            ("8100", RlpError::NonCanonical),
+            // a short string with a long header
+            ("b803646f67", RlpError::NonCanonical),
+            // a long length with leading zeros
+            ("b9003801", RlpError::NonCanonical),
+            ("83646f", RlpError::UnexpectedEnd),
+            ("c3836361", RlpError::UnexpectedEnd),
+            ("83646f6700", RlpError::TrailingBytes(1)),
+            ("", RlpError::UnexpectedEnd),
+        ] {
+            assert_eq!(
+                Err(error),
+                RlpItem::decode(&hex::decode(encoding).unwrap()),
+                "{}",
+                encoding
+            );
+        }
+        assert_eq!(
+            Err(RlpError::LeadingZeros),
+            RlpItem::decode(&[0x82, 0x00, 0x01]).unwrap().as_uint()
+        );
+
+        let mut nested = RlpItem::List(vec![]);
+        for _ in 0..MAX_DEPTH {
+            nested = RlpItem::List(vec![nested]);
+        }
+        assert_eq!(Err(RlpError::TooDeep), RlpItem::decode(&nested.encode()));
+        let RlpItem::List(inner) = nested else {
+            unreachable!()
+        };
+        assert_eq!(inner[0], RlpItem::decode(&inner[0].encode()).unwrap());
+    }
+}
@@ -0,0 +1,438 @@
+//!
+//! SCALE
+//!
+//! A minimal implementation of SCALE, the Simple Concatenated Aggregate
+//! Little-Endian codec of Substrate based chains. Integers are little endian,
+//! `Compact` integers take one to seventeen bytes depending on their value,
+//! vectors and strings are preceded by their compact length, and enums, such
+//! as `Option` and `Result`, by the byte index of their variant. Types encode
+//! without any framing, so a value is decoded knowing only its type.
+//!
+
+use crate::no_std::*;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScaleError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("non-canonical compact integer")]
+    NonCanonicalCompact,
+
+    #[error("integer does not fit its type")]
+    Overflow,
+
+    #[error("invalid boolean byte: {0}")]
+    InvalidBool(u8),
+
+    #[error("invalid enum variant: {0}")]
+    InvalidVariant(u8),
+
+    #[error("invalid utf-8 string")]
+    InvalidUtf8,
+
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+}
+
+/// A value with a SCALE encoding
+pub trait Encode {
+    /// Appends the encoding of the value to 'output'
+    fn encode_to(&self, output: &mut Vec<u8>);
+
+    /// Returns the encoding of the value
+    fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.encode_to(&mut output);
+        output
+    }
+}
+
+/// A value decodable from its SCALE encoding
+pub trait Decode: Sized {
+    /// Decodes a value from the front of 'input' and advances 'input' past it
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError>;
+
+    /// Decodes a value from 'input', which must hold nothing else
+    fn decode_all(mut input: &[u8]) -> Result<Self, ScaleError> {
+        let value = Self::decode(&mut input)?;
+        match input.is_empty() {
+            true => Ok(value),
+            false => Err(ScaleError::TrailingBytes(input.len())),
+        }
+    }
+}
+
+/// Takes 'len' bytes from the front of 'input'
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], ScaleError> {
+    if input.len() < len {
+        return Err(ScaleError::UnexpectedEnd);
+    }
+    let (taken, rest) = input.split_at(len);
+    *input = rest;
+    Ok(taken)
+}
+
+macro_rules! impl_integer {
+    ($($int:ty),*) => {$(
+        impl Encode for $int {
+            fn encode_to(&self, output: &mut Vec<u8>) {
+                output.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+
+        impl Decode for $int {
+            fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+                let bytes = take(input, core::mem::size_of::<$int>())?;
+                Ok(<$int>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+impl_integer!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Encode for bool {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        output.push(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(ScaleError::InvalidBool(byte)),
+        }
+    }
+}
+
+/// An unsigned integer in the compact encoding: values below 2^6, 2^14 and
+/// 2^30 take one, two and four bytes, with the mode in the two low bits of the
+/// first byte, and larger values a length byte followed by their significant
+/// bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Compact<T>(pub T);
+
+/// Appends the compact encoding of 'value' to 'output'
+fn encode_compact(value: u128, output: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => output.push((value as u8) << 2),
+        0x40..=0x3fff => output.extend_from_slice(&((value as u16) << 2 | 0b01).to_le_bytes()),
+        0x4000..=0x3fff_ffff => {
+            output.extend_from_slice(&((value as u32) << 2 | 0b10).to_le_bytes())
+        }
+        _ => {
+            let bytes = value.to_le_bytes();
+            let len = 16 - value.leading_zeros() as usize / 8;
+            output.push(((len - 4) as u8) << 2 | 0b11);
+            output.extend_from_slice(&bytes[..len]);
+        }
+    }
+}
+
+/// Decodes a compact integer from the front of 'input'. Only the shortest
+/// encoding of a value is accepted, so that every value has one encoding.
+fn decode_compact(input: &mut &[u8]) -> Result<u128, ScaleError> {
+    let first = u8::decode(input)?;
+    let (value, min) = match first & 0b11 {
+        0b00 => (first as u128 >> 2, 0),
+        0b01 => {
+            let value = u16::from_le_bytes([first, u8::decode(input)?]);
+            (value as u128 >> 2, 0x40)
+        }
+        0b10 => {
+            let mut bytes = [first, 0, 0, 0];
+            bytes[1..].copy_from_slice(take(input, 3)?);
+            (u32::from_le_bytes(bytes) as u128 >> 2, 0x4000)
+        }
+        _ => {
+            let len = (first >> 2) as usize + 4;
+            if len > 16 {
+                return Err(ScaleError::Overflow);
+            }
+            let mut bytes = [0u8; 16];
+            bytes[..len].copy_from_slice(take(input, len)?);
+            if bytes[len - 1] == 0 {
+                return Err(ScaleError::NonCanonicalCompact);
+            }
+            (u128::from_le_bytes(bytes), 0x4000_0000)
+        }
+    };
+    match value >= min {
+        true => Ok(value),
+        false => Err(ScaleError::NonCanonicalCompact),
+    }
+}
+
+macro_rules! impl_compact {
+    ($($int:ty),*) => {$(
+        impl Encode for Compact<$int> {
+            fn encode_to(&self, output: &mut Vec<u8>) {
+                encode_compact(self.0 as u128, output);
+            }
+        }
+
+        impl Decode for Compact<$int> {
+            fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+                let value = decode_compact(input)?;
+                Ok(Compact(<$int>::try_from(value).map_err(|_| ScaleError::Overflow)?))
+            }
+        }
+    )*};
+}
+
+impl_compact!(u8, u16, u32, u64, u128);
+
+/// Appends the compact length prefix of a collection to 'output'
+fn encode_len(len: usize, output: &mut Vec<u8>) {
+    encode_compact(len as u128, output);
+}
+
+/// Decodes the compact length prefix of a collection
+fn decode_len(input: &mut &[u8]) -> Result<usize, ScaleError> {
+    usize::try_from(decode_compact(input)?).map_err(|_| ScaleError::Overflow)
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        encode_len(self.len(), output);
+        for item in self {
+            item.encode_to(output);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        self.as_slice().encode_to(output)
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        let len = decode_len(input)?;
+        // every item takes at least a byte, unless it is zero sized
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        // fixed size arrays have no length prefix
+        for item in self {
+            item.encode_to(output);
+        }
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::decode(input)?);
+        }
+        Ok(items.try_into().ok().unwrap())
+    }
+}
+
+impl Encode for str {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        self.as_bytes().encode_to(output)
+    }
+}
+
+impl Encode for String {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        self.as_str().encode_to(output)
+    }
+}
+
+impl Decode for String {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        let len = decode_len(input)?;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ScaleError::InvalidUtf8)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        (**self).encode_to(output)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        match self {
+            None => output.push(0),
+            Some(value) => {
+                output.push(1);
+                value.encode_to(output);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        match u8::decode(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(input)?)),
+            variant => Err(ScaleError::InvalidVariant(variant)),
+        }
+    }
+}
+
+impl<T: Encode, E: Encode> Encode for Result<T, E> {
+    fn encode_to(&self, output: &mut Vec<u8>) {
+        match self {
+            Ok(value) => {
+                output.push(0);
+                value.encode_to(output);
+            }
+            Err(error) => {
+                output.push(1);
+                error.encode_to(output);
+            }
+        }
+    }
+}
+
+impl<T: Decode, E: Decode> Decode for Result<T, E> {
+    fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+        match u8::decode(input)? {
+            0 => Ok(Ok(T::decode(input)?)),
+            1 => Ok(Err(E::decode(input)?)),
+            variant => Err(ScaleError::InvalidVariant(variant)),
+        }
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($($name:ident),+)),*) => {$(
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode_to(&self, output: &mut Vec<u8>) {
+                let ($($name,)+) = self;
+                $($name.encode_to(output);)+
+            }
+        }
+
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            fn decode(input: &mut &[u8]) -> Result<Self, ScaleError> {
+                Ok(($($name::decode(input)?,)+))
+            }
+        }
+    )*};
+}
+
+impl_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+impl Encode for () {
+    fn encode_to(&self, _output: &mut Vec<u8>) {}
+}
+
+impl Decode for () {
+    fn decode(_input: &mut &[u8]) -> Result<Self, ScaleError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact() {
+        // the examples of the SCALE specification
+        for (value, encoding) in [
+            (0u128, "00"),
+            (1, "04"),
+            (42, "a8"),
+            (63, "fc"),
+            (64, "0101"),
+            (69, "1501"),
+            (16383, "fdff"),
+            (16384, "02000100"),
+            (1073741823, "feffffff"),
+            (1073741824, "0300000040"),
+            (u64::MAX as u128, "13ffffffffffffffff"),
+            (100000000000000, "0b00407a10f35a"),
+        ] {
+            assert_eq!(encoding, hex::encode(Compact(value).encode()));
+            assert_eq!(
+                value,
+                Compact::<u128>::decode_all(&hex::decode(encoding).unwrap())
+                    .unwrap()
+                    .0
+            );
+        }
+        assert_eq!(
+            Compact(u128::MAX),
+            Compact::<u128>::decode_all(&Compact(u128::MAX).encode()).unwrap()
+        );
+
+        // only the shortest encoding is accepted
+        assert_eq!(
+            Err(ScaleError::NonCanonicalCompact),
+            Compact::<u32>::decode_all(&[0x01, 0x00])
+        );
+        assert_eq!(
+            Err(ScaleError::NonCanonicalCompact),
+            Compact::<u64>::decode_all(&[0x07, 0xff, 0xff, 0xff, 0xff, 0x00])
+        );
+        assert_eq!(
+            Err(ScaleError::Overflow),
+            Compact::<u8>::decode_all(&[0x01, 0x04])
+        );
+        assert_eq!(
+            Err(ScaleError::UnexpectedEnd),
+            Compact::<u32>::decode_all(&[0x02, 0x00])
+        );
+    }
+
+    #[test]
+    fn test_codec() {
+        assert_eq!("2a000000", hex::encode(42u32.encode()));
+        assert_eq!("feff", hex::encode((-2i16).encode()));
+        assert_eq!("0c010002", hex::encode(vec![1u8, 0, 2].encode()));
+        assert_eq!("0c616263", hex::encode("abc".encode()));
+        assert_eq!("0104", hex::encode(Some(Compact(1u32)).encode()));
+        assert_eq!("00", hex::encode(Ok::<(), u8>(()).encode()));
+        assert_eq!("01002a00", hex::encode((true, [0u8, 42], 0u8).encode()));
+
+        let value: (Vec<Option<u16>>, String, Result<u8, bool>) =
+            (vec![Some(7), None], "anychain".into(), Err(true));
+        assert_eq!(value, Decode::decode_all(&value.encode()).unwrap());
+
+        assert_eq!(Err(ScaleError::InvalidBool(2)), bool::decode_all(&[2]));
+        assert_eq!(
+            Err(ScaleError::InvalidVariant(2)),
+            Option::<u8>::decode_all(&[2, 0])
+        );
+        assert_eq!(Err(ScaleError::TrailingBytes(1)), u8::decode_all(&[0, 0]));
+        assert_eq!(
+            Err(ScaleError::UnexpectedEnd),
+            Vec::<u16>::decode_all(&[0x08, 0, 0, 0])
+        );
+        assert_eq!(
+            Err(ScaleError::InvalidUtf8),
+            String::decode_all(&[0x04, 0xff])
+        );
+    }
+}
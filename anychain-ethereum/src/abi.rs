@@ -0,0 +1,194 @@
+//!
+//! ABI
+//!
+//! This module implements the pieces of the Solidity contract ABI that token
+//! calls need, without a full ABI library: function selectors, the 32 byte
+//! words of static arguments, and `bytes` and `uint256[]` arguments. A call
+//! is the 4 byte selector of the function signature followed by its
+//! arguments, each static argument one word. Dynamic arguments are the offset
+//! of their contents in the arguments instead, and their contents, a length
+//! word followed by the zero padded bytes or the words, follow all the heads.
+//!
+
+use crate::address::EthereumAddress;
+use anychain_core::{crypto::keccak256, hex, no_std::*, TransactionError};
+use core::str::FromStr;
+use ethereum_types::U256;
+
+/// Returns the selector of the function 'signature', such as
+/// `transfer(address,uint256)`: the first 4 bytes of its Keccak-256 hash
+pub fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[..4].try_into().unwrap()
+}
+
+/// Returns the word of 'address', left padded with zeros
+pub fn encode_address(address: &EthereumAddress) -> Result<[u8; 32], TransactionError> {
+    let address = hex::decode(&address.to_string()[2..])?;
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&address);
+    Ok(word)
+}
+
+/// Returns the big endian word of 'value'
+pub fn encode_uint(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+/// Returns the address of 'word', whose 12 leading bytes must be zeros
+pub fn decode_address(word: &[u8; 32]) -> Result<EthereumAddress, TransactionError> {
+    if word[..12].iter().any(|byte| *byte != 0) {
+        return Err(TransactionError::Message(format!(
+            "invalid address word {}",
+            hex::encode(word)
+        )));
+    }
+    Ok(EthereumAddress::from_str(&hex::encode(&word[12..]))?)
+}
+
+/// Returns the integer of 'word'
+pub fn decode_uint(word: &[u8; 32]) -> U256 {
+    U256::from_big_endian(word)
+}
+
+/// Returns the call of the function of 'selector' with the argument words
+/// 'words'
+pub fn encode_call(selector: [u8; 4], words: &[[u8; 32]]) -> Vec<u8> {
+    let mut call = selector.to_vec();
+    for word in words {
+        call.extend_from_slice(word);
+    }
+    call
+}
+
+/// Represents an argument of a call
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Argument {
+    /// A static argument, such as an `address` or a `uint256`
+    Word([u8; 32]),
+    /// A `bytes` argument
+    Bytes(Vec<u8>),
+    /// An array of static arguments, such as a `uint256[]`
+    Words(Vec<[u8; 32]>),
+}
+
+/// The kinds of the arguments of a call, which its decoding takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgumentKind {
+    Word,
+    Bytes,
+    Words,
+}
+
+/// Returns the call of the function of 'selector' with 'arguments'
+pub fn encode_arguments(selector: [u8; 4], arguments: &[Argument]) -> Vec<u8> {
+    let mut head = selector.to_vec();
+    let mut tail = vec![];
+    for argument in arguments {
+        let offset = encode_uint(U256::from(32 * arguments.len() + tail.len()));
+        match argument {
+            Argument::Word(word) => head.extend_from_slice(word),
+            Argument::Bytes(bytes) => {
+                head.extend_from_slice(&offset);
+                tail.extend_from_slice(&encode_uint(U256::from(bytes.len())));
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len().next_multiple_of(32), 0);
+            }
+            Argument::Words(words) => {
+                head.extend_from_slice(&offset);
+                tail.extend_from_slice(&encode_uint(U256::from(words.len())));
+                for word in words {
+                    tail.extend_from_slice(word);
+                }
+            }
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+/// Returns the integer of 'word' as an offset or a length, which must fit
+/// in 32 bits
+fn decode_len(word: &[u8]) -> Result<usize, TransactionError> {
+    match word[..28].iter().all(|byte| *byte == 0) {
+        true => Ok(u32::from_be_bytes(word[28..32].try_into().unwrap()) as usize),
+        false => Err(TransactionError::Message(format!(
+            "invalid offset or length {}",
+            hex::encode(word)
+        ))),
+    }
+}
+
+/// Splits 'data' into its selector and its arguments of kinds 'kinds',
+/// failing on offsets or lengths out of the call and on dirty padding
+pub fn decode_arguments(
+    data: &[u8],
+    kinds: &[ArgumentKind],
+) -> Result<([u8; 4], Vec<Argument>), TransactionError> {
+    let (selector, words) = decode_call(data)?;
+    if words.len() < kinds.len() {
+        return Err(TransactionError::Message(format!(
+            "expected {} arguments, found {} words",
+            kinds.len(),
+            words.len()
+        )));
+    }
+    let arguments = &data[4..];
+    // returns the length word at 'offset' and the rest of the arguments
+    let contents = |offset: &[u8; 32]| -> Result<(usize, &[u8]), TransactionError> {
+        let offset = decode_len(offset)?;
+        match arguments.get(offset..).filter(|rest| rest.len() >= 32) {
+            Some(rest) => Ok((decode_len(&rest[..32])?, &rest[32..])),
+            None => Err(TransactionError::Message(format!(
+                "offset {} out of the call",
+                offset
+            ))),
+        }
+    };
+    let invalid = || TransactionError::Message("arguments out of the call".into());
+
+    let arguments = kinds
+        .iter()
+        .zip(&words)
+        .map(|(kind, word)| match kind {
+            ArgumentKind::Word => Ok(Argument::Word(*word)),
+            ArgumentKind::Bytes => {
+                let (len, rest) = contents(word)?;
+                let padded = rest.get(..len.next_multiple_of(32)).ok_or_else(invalid)?;
+                if padded[len..].iter().any(|byte| *byte != 0) {
+                    return Err(TransactionError::Message("dirty bytes padding".into()));
+                }
+                Ok(Argument::Bytes(padded[..len].to_vec()))
+            }
+            ArgumentKind::Words => {
+                let (len, rest) = contents(word)?;
+                let words = rest.get(..len.checked_mul(32).ok_or_else(invalid)?);
+                Ok(Argument::Words(
+                    words
+                        .ok_or_else(invalid)?
+                        .chunks(32)
+                        .map(|word| word.try_into().unwrap())
+                        .collect(),
+                ))
+            }
+        })
+        .collect::<Result<Vec<Argument>, TransactionError>>()?;
+    Ok((selector, arguments))
+}
+
+/// Splits 'data' into its selector and its argument words, failing unless
+/// the arguments are whole words
+pub fn decode_call(data: &[u8]) -> Result<([u8; 4], Vec<[u8; 32]>), TransactionError> {
+    if data.len() < 4 || !(data.len() - 4).is_multiple_of(32) {
+        return Err(TransactionError::Message(format!(
+            "invalid call data length {}",
+            data.len()
+        )));
+    }
+    let words = data[4..]
+        .chunks(32)
+        .map(|word| word.try_into().unwrap())
+        .collect();
+    Ok((data[..4].try_into().unwrap(), words))
+}
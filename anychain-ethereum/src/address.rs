@@ -3,9 +3,13 @@ use crate::public_key::EthereumPublicKey;
 use anychain_core::hex;
 use anychain_core::no_std::*;
 use anychain_core::utilities::crypto::keccak256;
-use anychain_core::{libsecp256k1, to_hex_string, Address, AddressError, Error, PublicKey};
+use anychain_core::{
+    libsecp256k1, to_hex_string, Address, AddressError, AnyNetwork, Error, PublicKey,
+};
 use core::{convert::TryFrom, fmt, str::FromStr};
+use ethereum_types::U256;
 use regex::Regex;
+use rlp::RlpStream;
 use serde::{Deserialize, Serialize};
 
 /// Represents an Ethereum address
@@ -16,6 +20,7 @@ impl Address for EthereumAddress {
     type SecretKey = libsecp256k1::SecretKey;
     type Format = EthereumFormat;
     type PublicKey = EthereumPublicKey;
+    type Network = AnyNetwork;
 
     /// Returns the address corresponding to the given private key.
     fn from_secret_key(
@@ -33,26 +38,100 @@ impl Address for EthereumAddress {
         // public_key.from_private_key();
         Ok(Self::checksum_address(public_key))
     }
+
+    /// Returns `AnyNetwork`, as an address is valid on every EVM network.
+    fn network(&self) -> AnyNetwork {
+        AnyNetwork
+    }
+
+    /// Returns the format of the address.
+    fn format(&self) -> EthereumFormat {
+        EthereumFormat::Standard
+    }
 }
 
 impl EthereumAddress {
     /// Returns the checksum address given a public key.
     /// Adheres to EIP-55 (https://eips.ethereum.org/EIPS/eip-55).
     pub fn checksum_address(public_key: &EthereumPublicKey) -> Self {
-        let hash = keccak256(&public_key.to_secp256k1_public_key().serialize()[1..]);
-        let address = to_hex_string(&hash[12..]).to_lowercase();
-
-        let hash = to_hex_string(&keccak256(address.as_bytes()));
-        let mut checksum_address = "0x".to_string();
-        for c in 0..40 {
-            let ch = match &hash[c..=c] {
-                "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" => address[c..=c].to_lowercase(),
-                _ => address[c..=c].to_uppercase(),
-            };
-            checksum_address.push_str(&ch);
+        Self::from_hash(&keccak256(
+            &public_key.to_secp256k1_public_key().serialize()[1..],
+        ))
+    }
+
+    /// Returns the address of 'address', whose letters must be all lowercase,
+    /// all uppercase, or of the mixed case of its checksum. The checksum is
+    /// that of EIP-55, or that of ERC-1191 for the chain 'chain_id' if given.
+    pub fn from_checksum_str(address: &str, chain_id: Option<u64>) -> Result<Self, AddressError> {
+        let address = address.strip_prefix("0x").unwrap_or(address);
+        if address.len() != 40 {
+            return Err(AddressError::InvalidByteLength(address.len()));
+        }
+        if let Some(c) = address.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(AddressError::InvalidAddress(format!(
+                "invalid character {} in {}",
+                c, address
+            )));
         }
 
-        EthereumAddress(checksum_address)
+        let lowercase = address.to_lowercase();
+        if address != lowercase && address != address.to_uppercase() {
+            let expected = checksum(&lowercase, chain_id);
+            if expected[2..] != *address {
+                return Err(AddressError::InvalidChecksum(
+                    expected,
+                    format!("0x{}", address),
+                ));
+            }
+        }
+        Ok(EthereumAddress(checksum(&lowercase, None)))
+    }
+
+    /// Returns the address in the mixed case of its checksum, that of EIP-55,
+    /// or that of ERC-1191 for the chain 'chain_id' if given
+    pub fn to_checksum_string(&self, chain_id: Option<u64>) -> String {
+        checksum(&self.0[2..].to_lowercase(), chain_id)
+    }
+
+    /// Returns the address of the contract the account 'sender' creates with
+    /// its transaction or CREATE of nonce 'nonce': the last 20 bytes of the
+    /// Keccak-256 hash of the RLP list of the sender and the nonce
+    pub fn create(sender: &Self, nonce: U256) -> Result<Self, AddressError> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&sender.address_bytes()?);
+        stream.append(&nonce);
+        Ok(Self::from_hash(&keccak256(&stream.out())))
+    }
+
+    /// Returns the address of the contract the contract 'deployer' creates
+    /// with CREATE2 of salt 'salt' and init code 'init_code' (EIP-1014): the
+    /// last 20 bytes of keccak256(0xff || deployer || salt || keccak256(init_code))
+    pub fn create2(
+        deployer: &Self,
+        salt: &[u8; 32],
+        init_code: &[u8],
+    ) -> Result<Self, AddressError> {
+        let data = [
+            &[0xff],
+            &deployer.address_bytes()?[..],
+            salt,
+            &keccak256(init_code),
+        ]
+        .concat();
+        Ok(Self::from_hash(&keccak256(&data)))
+    }
+
+    /// Returns the address of the last 20 bytes of 'hash'
+    fn from_hash(hash: &[u8; 32]) -> Self {
+        EthereumAddress(checksum(&hex::encode(&hash[12..]), None))
+    }
+
+    /// Returns the 20 bytes of the address
+    fn address_bytes(&self) -> Result<Vec<u8>, AddressError> {
+        match self.0.get(2..).map(hex::decode) {
+            Some(Ok(bytes)) if bytes.len() == 20 => Ok(bytes),
+            _ => Err(AddressError::InvalidAddress(self.0.clone())),
+        }
     }
 
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
@@ -72,6 +151,28 @@ impl EthereumAddress {
     }
 }
 
+/// Returns the 0x prefixed checksum address of the 40 lowercase hex digits
+/// 'address': each letter is uppercase if the nibble of the same index of the
+/// Keccak-256 hash of the address is 8 or more. ERC-1191 hashes the address
+/// preceded by its chain id 'chain_id' and 0x instead.
+fn checksum(address: &str, chain_id: Option<u64>) -> String {
+    let hash = match chain_id {
+        Some(chain_id) => keccak256(format!("{}0x{}", chain_id, address).as_bytes()),
+        None => keccak256(address.as_bytes()),
+    };
+    let hash = to_hex_string(&hash);
+
+    let mut checksum_address = "0x".to_string();
+    for c in 0..40 {
+        let ch = match &hash[c..=c] {
+            "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" => address[c..=c].to_lowercase(),
+            _ => address[c..=c].to_uppercase(),
+        };
+        checksum_address.push_str(&ch);
+    }
+    checksum_address
+}
+
 impl<'a> TryFrom<&'a str> for EthereumAddress {
     type Error = AddressError;
 
@@ -83,27 +184,10 @@ impl<'a> TryFrom<&'a str> for EthereumAddress {
 impl FromStr for EthereumAddress {
     type Err = AddressError;
 
+    /// Returns the address of 'address', failing on a mixed case other than
+    /// that of its EIP-55 checksum
     fn from_str(address: &str) -> Result<Self, Self::Err> {
-        let regex = Regex::new(r"^0x").unwrap();
-        let address = address.to_lowercase();
-        let address = regex.replace_all(&address, "").to_string();
-
-        if address.len() != 40 {
-            let err = AddressError::InvalidByteLength(address.len());
-            return Err(err);
-        }
-
-        let hash = to_hex_string(&keccak256(address.as_bytes()));
-        let mut checksum_address = "0x".to_string();
-        for c in 0..40 {
-            let ch = match &hash[c..=c] {
-                "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" => address[c..=c].to_lowercase(),
-                _ => address[c..=c].to_uppercase(),
-            };
-            checksum_address.push_str(&ch);
-        }
-
-        Ok(EthereumAddress(checksum_address))
+        Self::from_checksum_str(address, None)
     }
 }
 
@@ -146,29 +230,28 @@ mod tests {
     mod checksum_address {
         use super::*;
 
-        const KEYPAIRS: [(&str, &str); 5] =
-            [
-                (
-                    "f89f23eaeac18252fedf81bb8318d3c111d48c19b0680dcf6e0a8d5136caf287",
-                    "0x9141B7539E7902872095C408BfA294435e2b8c8a",
-                ),
-                (
-                    "a93701ea343247db13466f6448ffbca658726e2b4a77530db3eca3c9250b4f0d",
-                    "0xa0967B1F698DC497A694FE955666D1dDd398145C",
-                ),
-                (
-                    "de61e35e2e5eb9504d52f5042126591d80144d49f74b8ced68f4959a3e8edffd",
-                    "0xD5d13d1dD277BB9041e560A63ee29c086D370b0A",
-                ),
-                (
-                    "56f01d5e01b6fd1cc123d8d1eae0d148e00c025b5be2ef624775f7a1b802e9c1",
-                    "0xc4488ebbE882fa2aF1D466CB2C8ecafE316c067a",
-                ),
-                (
-                    "363af8b4d3ff22bb0e4ffc2ff198b4b5be0316f8a507ad5fe32f021c3d1ae8ad",
-                    "0xF9001e6AEE6EA439D713fBbF960EbA76f4770E2B",
-                ),
-            ];
+        const KEYPAIRS: [(&str, &str); 5] = [
+            (
+                "f89f23eaeac18252fedf81bb8318d3c111d48c19b0680dcf6e0a8d5136caf287",
+                "0x9141B7539E7902872095C408BfA294435e2b8c8a",
+            ),
+            (
+                "a93701ea343247db13466f6448ffbca658726e2b4a77530db3eca3c9250b4f0d",
+                "0xa0967B1F698DC497A694FE955666D1dDd398145C",
+            ),
+            (
+                "de61e35e2e5eb9504d52f5042126591d80144d49f74b8ced68f4959a3e8edffd",
+                "0xD5d13d1dD277BB9041e560A63ee29c086D370b0A",
+            ),
+            (
+                "56f01d5e01b6fd1cc123d8d1eae0d148e00c025b5be2ef624775f7a1b802e9c1",
+                "0xc4488ebbE882fa2aF1D466CB2C8ecafE316c067a",
+            ),
+            (
+                "363af8b4d3ff22bb0e4ffc2ff198b4b5be0316f8a507ad5fe32f021c3d1ae8ad",
+                "0xF9001e6AEE6EA439D713fBbF960EbA76f4770E2B",
+            ),
+        ];
 
         #[test]
         fn from_str() {
@@ -186,6 +269,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_checksum() {
+        // the examples of EIP-55
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let lowercase = EthereumAddress::from_str(&address.to_lowercase()).unwrap();
+        assert_eq!(address, lowercase.to_string());
+        assert_eq!(address, lowercase.to_checksum_string(None));
+        assert_eq!(
+            lowercase,
+            EthereumAddress::from_str(&address.to_uppercase().replace("0X", "0x")).unwrap()
+        );
+        assert!(matches!(
+            EthereumAddress::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(AddressError::InvalidChecksum(_, _))
+        ));
+        assert!(EthereumAddress::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg").is_err());
+
+        // the examples of ERC-1191 for RSK Mainnet and Testnet
+        let rsk = "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD";
+        let rsk_testnet = "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd";
+        assert_eq!(rsk, lowercase.to_checksum_string(Some(30)));
+        assert_eq!(rsk_testnet, lowercase.to_checksum_string(Some(31)));
+        assert_eq!(
+            lowercase,
+            EthereumAddress::from_checksum_str(rsk, Some(30)).unwrap()
+        );
+        assert!(EthereumAddress::from_checksum_str(rsk, Some(31)).is_err());
+        assert!(EthereumAddress::from_str(rsk).is_err());
+    }
+
+    #[test]
+    fn test_contract_address() {
+        let sender =
+            EthereumAddress::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        assert_eq!(
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            EthereumAddress::create(&sender, U256::zero())
+                .unwrap()
+                .to_string()
+                .to_lowercase()
+        );
+        assert_eq!(
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            EthereumAddress::create(&sender, U256::one())
+                .unwrap()
+                .to_string()
+                .to_lowercase()
+        );
+
+        // the examples of EIP-1014
+        let deployer = EthereumAddress::from_str(&"00".repeat(20)).unwrap();
+        assert_eq!(
+            "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38",
+            EthereumAddress::create2(&deployer, &[0; 32], &[0])
+                .unwrap()
+                .to_string()
+        );
+        let deployer =
+            EthereumAddress::from_str("0xdeadbeef00000000000000000000000000000000").unwrap();
+        assert_eq!(
+            "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3",
+            EthereumAddress::create2(&deployer, &[0; 32], &[0])
+                .unwrap()
+                .to_string()
+        );
+        assert!(EthereumAddress::create(&EthereumAddress::default(), U256::zero()).is_err());
+    }
+
     #[test]
     fn test_address() {
         let pubkey = EthereumPublicKey::from_str(
@@ -194,6 +345,8 @@ mod tests {
         )
         .unwrap();
         let address = EthereumAddress::from_public_key(&pubkey, &EthereumFormat::Standard).unwrap();
-        println!("{}", address)
+        println!("{}", address);
+        assert_eq!(AnyNetwork, address.network());
+        assert_eq!("any", address.network().to_string());
     }
 }
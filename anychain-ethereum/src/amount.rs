@@ -1,3 +1,4 @@
+use anychain_core::no_std::*;
 use anychain_core::{to_basic_unit as to_wei, Amount, AmountError};
 
 use core::fmt;
@@ -56,15 +57,45 @@ impl fmt::Display for Denomination {
     }
 }
 
-impl Amount for EthereumAmount {}
+impl Amount for EthereumAmount {
+    const DECIMALS: u32 = 18;
+
+    fn from_smallest_unit(value: u128) -> Result<Self, AmountError> {
+        Ok(Self(U256::from(value)))
+    }
+
+    fn to_smallest_unit(&self) -> Result<u128, AmountError> {
+        match self.0 > U256::from(u128::MAX) {
+            true => Err(AmountError::AmountOutOfBounds(
+                self.0.to_string(),
+                u128::MAX.to_string(),
+            )),
+            false => Ok(self.0.as_u128()),
+        }
+    }
+
+    fn checked_add(self, rhs: Self) -> Result<Self, AmountError> {
+        self.0.checked_add(rhs.0).map(Self).ok_or_else(|| {
+            AmountError::AmountOutOfBounds(format!("{} + {}", self, rhs), U256::MAX.to_string())
+        })
+    }
+
+    fn checked_sub(self, rhs: Self) -> Result<Self, AmountError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or_else(|| AmountError::InvalidAmount(format!("{} - {}", self, rhs)))
+    }
+}
 
 impl EthereumAmount {
     pub fn u256_from_str(val: &str) -> Result<U256, AmountError> {
         match U256::from_dec_str(val) {
             Ok(wei) => Ok(wei),
-            Err(error) => Err(
-                AmountError::Crate("uint", anychain_core::no_std::format!("{:?}", error))
-            ),
+            Err(error) => Err(AmountError::Crate(
+                "uint",
+                anychain_core::no_std::format!("{:?}", error),
+            )),
         }
     }
 
@@ -443,4 +474,31 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_amount_trait() {
+        // 100 ETH in wei exceeds i64
+        let amount = EthereumAmount::from_decimal_str("100").unwrap();
+        assert_eq!(EthereumAmount::from_eth("100").unwrap(), amount);
+        assert_eq!(
+            100_000_000_000_000_000_000,
+            amount.to_smallest_unit().unwrap()
+        );
+        assert_eq!("100", amount.to_decimal_string().unwrap());
+        assert_eq!(
+            "1.5",
+            EthereumAmount::from_smallest_unit(1_500_000_000_000_000_000)
+                .unwrap()
+                .to_decimal_string()
+                .unwrap()
+        );
+
+        assert!(amount
+            .checked_sub(amount.checked_add(amount).unwrap())
+            .is_err());
+        assert!(EthereumAmount(U256::MAX)
+            .checked_add(EthereumAmount(U256::one()))
+            .is_err());
+        assert!(EthereumAmount(U256::MAX).to_smallest_unit().is_err());
+    }
 }
@@ -0,0 +1,385 @@
+//!
+//! EIP-712
+//!
+//! This module implements the hashing of typed structured data of EIP-712,
+//! which wallets sign for off-chain orders and permits. A struct is hashed as
+//! the Keccak-256 hash of its type hash followed by the encoding of its
+//! fields, each one word: atomic values padded to a word, the hash of dynamic
+//! bytes and strings, the hash of the concatenated encodings of array items,
+//! and the hash of nested structs. The signed digest is
+//! keccak256("\x19\x01" || domainSeparator || hashStruct(message)).
+//!
+
+use crate::abi::{encode_address, encode_uint};
+use crate::address::EthereumAddress;
+use anychain_core::{
+    crypto::{keccak256, sign_deterministic},
+    libsecp256k1::{Message, SecretKey},
+    no_std::*,
+    AddressError,
+};
+use ethereum_types::U256;
+
+/// Represents a field of a struct type, of name 'name' and of type 'kind',
+/// such as `uint256`, `address[]` or the name of another struct type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Eip712Field {
+    pub name: String,
+    pub kind: String,
+}
+
+impl Eip712Field {
+    pub fn new(name: &str, kind: &str) -> Self {
+        Self {
+            name: name.into(),
+            kind: kind.into(),
+        }
+    }
+}
+
+/// Represents the struct types of typed data, each a name and its fields
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Eip712Types(Vec<(String, Vec<Eip712Field>)>);
+
+impl Eip712Types {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the struct type 'name' of the fields 'fields', given as
+    /// (name, type) pairs
+    pub fn insert(&mut self, name: &str, fields: &[(&str, &str)]) -> &mut Self {
+        let fields = fields
+            .iter()
+            .map(|(name, kind)| Eip712Field::new(name, kind))
+            .collect();
+        self.0.retain(|(other, _)| other != name);
+        self.0.push((name.into(), fields));
+        self
+    }
+
+    /// Returns the fields of the struct type 'name'
+    pub fn get(&self, name: &str) -> Option<&[Eip712Field]> {
+        self.0
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, fields)| &fields[..])
+    }
+
+    /// Returns the fields of the struct type 'name', failing if it is unknown
+    fn fields(&self, name: &str) -> Result<&[Eip712Field], AddressError> {
+        self.get(name)
+            .ok_or_else(|| AddressError::Message(format!("unknown EIP-712 type {}", name)))
+    }
+
+    /// Adds the struct types 'name' references, directly or not, to
+    /// 'dependencies'
+    fn dependencies(&self, name: &str, dependencies: &mut Vec<String>) -> Result<(), AddressError> {
+        if dependencies.iter().any(|dependency| dependency == name) {
+            return Ok(());
+        }
+        dependencies.push(name.into());
+        for field in self.fields(name)? {
+            // the item type of array types
+            let kind = field.kind.split('[').next().unwrap();
+            if self.get(kind).is_some() {
+                self.dependencies(kind, dependencies)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the encoding of the struct type 'name', such as
+    /// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`:
+    /// the type followed by the types it references, sorted by name
+    pub fn encode_type(&self, name: &str) -> Result<String, AddressError> {
+        let mut dependencies = vec![];
+        self.dependencies(name, &mut dependencies)?;
+        dependencies[1..].sort();
+
+        let mut encoding = String::new();
+        for dependency in dependencies {
+            let fields: Vec<String> = self
+                .fields(&dependency)?
+                .iter()
+                .map(|field| format!("{} {}", field.kind, field.name))
+                .collect();
+            encoding.push_str(&format!("{}({})", dependency, fields.join(",")));
+        }
+        Ok(encoding)
+    }
+
+    /// Returns the type hash of the struct type 'name'
+    pub fn type_hash(&self, name: &str) -> Result<[u8; 32], AddressError> {
+        Ok(keccak256(self.encode_type(name)?.as_bytes()))
+    }
+
+    /// Returns the hash of the struct 'value' of type 'name'
+    pub fn hash_struct(&self, name: &str, value: &Eip712Value) -> Result<[u8; 32], AddressError> {
+        let Eip712Value::Struct(values) = value else {
+            return Err(AddressError::Message(format!("expected a {} struct", name)));
+        };
+        let mut encoding = self.type_hash(name)?.to_vec();
+        for field in self.fields(name)? {
+            let value = values
+                .iter()
+                .find(|(name, _)| *name == field.name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| AddressError::Message(format!("missing field {}", field.name)))?;
+            encoding.extend_from_slice(&self.encode_value(&field.kind, value)?);
+        }
+        Ok(keccak256(&encoding))
+    }
+
+    /// Returns the word of 'value' as a field of type 'kind'
+    fn encode_value(&self, kind: &str, value: &Eip712Value) -> Result<[u8; 32], AddressError> {
+        let invalid = || AddressError::Message(format!("invalid {} value {:?}", kind, value));
+
+        if let Some(item) = kind.strip_suffix(']') {
+            let (item, len) = item.rsplit_once('[').ok_or_else(invalid)?;
+            let Eip712Value::Array(values) = value else {
+                return Err(invalid());
+            };
+            if !len.is_empty() && len.parse::<usize>() != Ok(values.len()) {
+                return Err(invalid());
+            }
+            let mut encoding = vec![];
+            for value in values {
+                encoding.extend_from_slice(&self.encode_value(item, value)?);
+            }
+            return Ok(keccak256(&encoding));
+        }
+        if self.get(kind).is_some() {
+            return self.hash_struct(kind, value);
+        }
+
+        match (kind, value) {
+            ("address", Eip712Value::Address(address)) => {
+                encode_address(address).map_err(|error| AddressError::Message(error.to_string()))
+            }
+            ("bool", Eip712Value::Bool(value)) => Ok(encode_uint(U256::from(*value as u8))),
+            ("bytes", Eip712Value::Bytes(bytes)) => Ok(keccak256(bytes)),
+            ("string", Eip712Value::String(string)) => Ok(keccak256(string.as_bytes())),
+            (_, Eip712Value::Uint(value))
+                if kind.starts_with("uint") || kind.starts_with("int") =>
+            {
+                Ok(encode_uint(*value))
+            }
+            (_, Eip712Value::Bytes(bytes)) if kind.starts_with("bytes") => {
+                let len = kind[5..].parse::<usize>().map_err(|_| invalid())?;
+                if len == 0 || len > 32 || bytes.len() != len {
+                    return Err(invalid());
+                }
+                let mut word = [0u8; 32];
+                word[..len].copy_from_slice(bytes);
+                Ok(word)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Represents a value of typed data. Values of `intN` types are the `Uint` of
+/// their two's complement over 256 bits, and those of `bytesN` types are
+/// `Bytes` of N bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Eip712Value {
+    Uint(U256),
+    Address(EthereumAddress),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Eip712Value>),
+    /// The values of the fields of a struct, by field name
+    Struct(Vec<(String, Eip712Value)>),
+}
+
+/// Represents the domain of typed data, which binds signatures to a dapp, a
+/// contract and a chain. Only the fields set are part of the domain type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Eip712Domain {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub chain_id: Option<U256>,
+    pub verifying_contract: Option<EthereumAddress>,
+    pub salt: Option<[u8; 32]>,
+}
+
+impl Eip712Domain {
+    /// Returns the domain separator, the hash of the domain as an
+    /// `EIP712Domain` struct
+    pub fn separator(&self) -> Result<[u8; 32], AddressError> {
+        let mut fields = vec![];
+        let mut values = vec![];
+        let mut add = |name: &'static str, kind: &'static str, value: Eip712Value| {
+            fields.push((name, kind));
+            values.push((name.into(), value));
+        };
+        if let Some(name) = &self.name {
+            add("name", "string", Eip712Value::String(name.clone()));
+        }
+        if let Some(version) = &self.version {
+            add("version", "string", Eip712Value::String(version.clone()));
+        }
+        if let Some(chain_id) = self.chain_id {
+            add("chainId", "uint256", Eip712Value::Uint(chain_id));
+        }
+        if let Some(contract) = &self.verifying_contract {
+            let value = Eip712Value::Address(contract.clone());
+            add("verifyingContract", "address", value);
+        }
+        if let Some(salt) = self.salt {
+            add("salt", "bytes32", Eip712Value::Bytes(salt.to_vec()));
+        }
+
+        let mut types = Eip712Types::new();
+        types.insert("EIP712Domain", &fields);
+        types.hash_struct("EIP712Domain", &Eip712Value::Struct(values))
+    }
+}
+
+/// Represents typed data: the struct 'message' of type 'primary_type' in
+/// 'domain'
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Eip712TypedData {
+    pub domain: Eip712Domain,
+    pub types: Eip712Types,
+    pub primary_type: String,
+    pub message: Eip712Value,
+}
+
+impl Eip712TypedData {
+    /// Returns the digest signed for the typed data,
+    /// keccak256("\x19\x01" || domainSeparator || hashStruct(message))
+    pub fn signing_hash(&self) -> Result<[u8; 32], AddressError> {
+        let domain_separator = self.domain.separator()?;
+        let message = self.types.hash_struct(&self.primary_type, &self.message)?;
+        Ok(keccak256(
+            &[&[0x19, 0x01], &domain_separator[..], &message[..]].concat(),
+        ))
+    }
+
+    /// Returns the 65 byte r || s || v signature of the typed data by
+    /// 'secret_key', where v is the recovery id plus 27, as `eth_signTypedData`
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<Vec<u8>, AddressError> {
+        let message = Message::parse(&self.signing_hash()?);
+        let (signature, recid) = sign_deterministic(&message, secret_key);
+        let mut signature = signature.serialize().to_vec();
+        signature.push(recid.serialize() + 27);
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::hex;
+    use core::str::FromStr;
+
+    fn person(name: &str, wallet: &str) -> Eip712Value {
+        Eip712Value::Struct(vec![
+            ("name".into(), Eip712Value::String(name.into())),
+            (
+                "wallet".into(),
+                Eip712Value::Address(EthereumAddress::from_str(wallet).unwrap()),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_mail() {
+        // the example of EIP-712
+        let mut types = Eip712Types::new();
+        types
+            .insert(
+                "Mail",
+                &[("from", "Person"), ("to", "Person"), ("contents", "string")],
+            )
+            .insert("Person", &[("name", "string"), ("wallet", "address")]);
+        let typed_data = Eip712TypedData {
+            domain: Eip712Domain {
+                name: Some("Ether Mail".into()),
+                version: Some("1".into()),
+                chain_id: Some(U256::one()),
+                verifying_contract: Some(
+                    EthereumAddress::from_str("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC")
+                        .unwrap(),
+                ),
+                salt: None,
+            },
+            types,
+            primary_type: "Mail".into(),
+            message: Eip712Value::Struct(vec![
+                (
+                    "from".into(),
+                    person("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"),
+                ),
+                (
+                    "to".into(),
+                    person("Bob", "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"),
+                ),
+                ("contents".into(), Eip712Value::String("Hello, Bob!".into())),
+            ]),
+        };
+
+        assert_eq!(
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+            typed_data.types.encode_type("Mail").unwrap()
+        );
+        assert_eq!(
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2",
+            hex::encode(typed_data.types.type_hash("Mail").unwrap())
+        );
+        assert_eq!(
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f",
+            hex::encode(typed_data.domain.separator().unwrap())
+        );
+        assert_eq!(
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e",
+            hex::encode(
+                typed_data
+                    .types
+                    .hash_struct("Mail", &typed_data.message)
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2",
+            hex::encode(typed_data.signing_hash().unwrap())
+        );
+
+        let secret_key = SecretKey::parse(&keccak256(b"cow")).unwrap();
+        assert_eq!(
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+             07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562\
+             1c",
+            hex::encode(typed_data.sign(&secret_key).unwrap())
+        );
+
+        // a missing field and a value of the wrong type
+        let mut invalid = typed_data.clone();
+        invalid.message = person("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826");
+        assert!(invalid.signing_hash().is_err());
+        let mut types = Eip712Types::new();
+        types.insert("Amounts", &[("values", "uint256[2]"), ("tag", "bytes4")]);
+        let amounts = |values: Vec<Eip712Value>, tag: Vec<u8>| {
+            Eip712Value::Struct(vec![
+                ("values".into(), Eip712Value::Array(values)),
+                ("tag".into(), Eip712Value::Bytes(tag)),
+            ])
+        };
+        let one = Eip712Value::Uint(U256::one());
+        assert!(types
+            .hash_struct(
+                "Amounts",
+                &amounts(vec![one.clone(), one.clone()], vec![0; 4])
+            )
+            .is_ok());
+        assert!(types
+            .hash_struct("Amounts", &amounts(vec![one.clone()], vec![0; 4]))
+            .is_err());
+        assert!(types
+            .hash_struct("Amounts", &amounts(vec![one.clone(), one], vec![0; 5]))
+            .is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//!
+//! ENS
+//!
+//! This module implements the namehash of the Ethereum Name Service and the
+//! calldata of the calls resolving a name: `resolver(bytes32)` to the ENS
+//! registry returns the resolver of a name, and `addr(bytes32)` to that
+//! resolver returns the address of the name. Names are normalized with a lite
+//! UTS-46: letters are lowercased, and labels must be non-empty and free of
+//! ASCII characters other than letters, digits, hyphens and underscores.
+//! Non-ASCII characters are only lowercased, without the mapping tables of the
+//! full normalization.
+//!
+
+use crate::abi::encode_call;
+use anychain_core::{crypto::keccak256, no_std::*, AddressError};
+
+/// The address of the ENS registry, the same on Ethereum and its testnets
+pub const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+/// The selector of `resolver(bytes32)`
+pub const ENS_RESOLVER: [u8; 4] = [0x01, 0x78, 0xb8, 0xbf];
+/// The selector of `addr(bytes32)`
+pub const ENS_ADDR: [u8; 4] = [0x3b, 0x3b, 0x57, 0xde];
+
+/// Returns the normalized 'name', failing on empty labels and on disallowed
+/// ASCII characters
+pub fn normalize_name(name: &str) -> Result<String, AddressError> {
+    let name = name.to_lowercase();
+    if name.is_empty() {
+        return Ok(name);
+    }
+    for label in name.split('.') {
+        if label.is_empty() {
+            return Err(AddressError::Message(format!("empty label in {}", name)));
+        }
+        if let Some(c) = label
+            .chars()
+            .find(|c| c.is_ascii() && !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+        {
+            return Err(AddressError::Message(format!(
+                "invalid character {:?} in {}",
+                c, name
+            )));
+        }
+    }
+    Ok(name)
+}
+
+/// Returns the namehash of the normalized 'name': that of the empty name is
+/// zero, and that of `label.parent` is the Keccak-256 hash of the namehash of
+/// `parent` followed by the hash of `label`
+pub fn namehash(name: &str) -> Result<[u8; 32], AddressError> {
+    let name = normalize_name(name)?;
+    let mut node = [0u8; 32];
+    if !name.is_empty() {
+        for label in name.rsplit('.') {
+            node = keccak256(&[node, keccak256(label.as_bytes())].concat());
+        }
+    }
+    Ok(node)
+}
+
+/// Returns the calldata of `resolver(bytes32)` for 'name', to call the ENS
+/// registry with
+pub fn encode_resolver_call(name: &str) -> Result<Vec<u8>, AddressError> {
+    Ok(encode_call(ENS_RESOLVER, &[namehash(name)?]))
+}
+
+/// Returns the calldata of `addr(bytes32)` for 'name', to call the resolver
+/// of the name with
+pub fn encode_addr_call(name: &str) -> Result<Vec<u8>, AddressError> {
+    Ok(encode_call(ENS_ADDR, &[namehash(name)?]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::selector;
+    use crate::address::EthereumAddress;
+    use anychain_core::hex;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(ENS_RESOLVER, selector("resolver(bytes32)"));
+        assert_eq!(ENS_ADDR, selector("addr(bytes32)"));
+        assert!(EthereumAddress::from_str(ENS_REGISTRY).is_ok());
+
+        // the examples of EIP-137
+        assert_eq!([0; 32], namehash("").unwrap());
+        assert_eq!(
+            "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae",
+            hex::encode(namehash("eth").unwrap())
+        );
+        assert_eq!(
+            "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f",
+            hex::encode(namehash("foo.eth").unwrap())
+        );
+        assert_eq!(namehash("foo.eth").unwrap(), namehash("FOO.Eth").unwrap());
+
+        assert_eq!(
+            format!(
+                "3b3b57de{}",
+                "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+            ),
+            hex::encode(encode_addr_call("foo.eth").unwrap())
+        );
+        assert_eq!(
+            &ENS_RESOLVER[..],
+            &encode_resolver_call("foo.eth").unwrap()[..4]
+        );
+
+        assert!(namehash("foo..eth").is_err());
+        assert!(namehash("foo.eth.").is_err());
+        assert!(namehash("foo bar.eth").is_err());
+    }
+}
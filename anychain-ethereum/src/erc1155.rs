@@ -0,0 +1,206 @@
+//!
+//! ERC-1155
+//!
+//! This module builds and decodes the calldata of the ERC-1155 calls that move
+//! tokens of a multi token contract, `safeTransferFrom` for an amount of one
+//! token and `safeBatchTransferFrom` for amounts of several, with the `bytes`
+//! data argument passed on to the hooks of contract recipients.
+//!
+
+use crate::abi::{
+    decode_address, decode_arguments, decode_uint, encode_address, encode_arguments, encode_uint,
+    Argument, ArgumentKind,
+};
+use crate::address::EthereumAddress;
+use anychain_core::{hex, no_std::*, TransactionError};
+use ethereum_types::U256;
+
+/// The selector of `safeTransferFrom(address,address,uint256,uint256,bytes)`
+pub const ERC1155_SAFE_TRANSFER_FROM: [u8; 4] = [0xf2, 0x42, 0x43, 0x2a];
+/// The selector of
+/// `safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)`
+pub const ERC1155_SAFE_BATCH_TRANSFER_FROM: [u8; 4] = [0x2e, 0xb2, 0xc2, 0xd6];
+
+/// Represents an ERC-1155 transfer call from 'from' to 'to'
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Erc1155Call {
+    /// Sends 'amount' tokens of id 'id'
+    SafeTransferFrom {
+        from: EthereumAddress,
+        to: EthereumAddress,
+        id: U256,
+        amount: U256,
+        data: Vec<u8>,
+    },
+    /// Sends the amounts of 'amounts' of the tokens of the same index in 'ids'
+    SafeBatchTransferFrom {
+        from: EthereumAddress,
+        to: EthereumAddress,
+        ids: Vec<U256>,
+        amounts: Vec<U256>,
+        data: Vec<u8>,
+    },
+}
+
+impl Erc1155Call {
+    /// Returns the calldata of the call, failing on a batch whose ids and
+    /// amounts differ in number
+    pub fn encode(&self) -> Result<Vec<u8>, TransactionError> {
+        Ok(match self {
+            Erc1155Call::SafeTransferFrom {
+                from,
+                to,
+                id,
+                amount,
+                data,
+            } => encode_arguments(
+                ERC1155_SAFE_TRANSFER_FROM,
+                &[
+                    Argument::Word(encode_address(from)?),
+                    Argument::Word(encode_address(to)?),
+                    Argument::Word(encode_uint(*id)),
+                    Argument::Word(encode_uint(*amount)),
+                    Argument::Bytes(data.clone()),
+                ],
+            ),
+            Erc1155Call::SafeBatchTransferFrom {
+                from,
+                to,
+                ids,
+                amounts,
+                data,
+            } => {
+                if ids.len() != amounts.len() {
+                    return Err(TransactionError::Message(format!(
+                        "{} ids for {} amounts",
+                        ids.len(),
+                        amounts.len()
+                    )));
+                }
+                encode_arguments(
+                    ERC1155_SAFE_BATCH_TRANSFER_FROM,
+                    &[
+                        Argument::Word(encode_address(from)?),
+                        Argument::Word(encode_address(to)?),
+                        Argument::Words(ids.iter().map(|id| encode_uint(*id)).collect()),
+                        Argument::Words(amounts.iter().map(|a| encode_uint(*a)).collect()),
+                        Argument::Bytes(data.clone()),
+                    ],
+                )
+            }
+        })
+    }
+
+    /// Returns the call of the calldata 'data', failing on other methods and
+    /// on malformed arguments
+    pub fn decode(data: &[u8]) -> Result<Self, TransactionError> {
+        use Argument::{Bytes, Word, Words};
+        use ArgumentKind as Kind;
+        let kinds = match data.get(..4) {
+            Some(selector) if selector == ERC1155_SAFE_BATCH_TRANSFER_FROM => [
+                Kind::Word,
+                Kind::Word,
+                Kind::Words,
+                Kind::Words,
+                Kind::Bytes,
+            ],
+            _ => [Kind::Word, Kind::Word, Kind::Word, Kind::Word, Kind::Bytes],
+        };
+        let uints = |words: &[[u8; 32]]| words.iter().map(decode_uint).collect::<Vec<U256>>();
+        let (selector, arguments) = decode_arguments(data, &kinds)?;
+        match (selector, &arguments[..]) {
+            (
+                ERC1155_SAFE_TRANSFER_FROM,
+                [Word(from), Word(to), Word(id), Word(amount), Bytes(bytes)],
+            ) => Ok(Erc1155Call::SafeTransferFrom {
+                from: decode_address(from)?,
+                to: decode_address(to)?,
+                id: decode_uint(id),
+                amount: decode_uint(amount),
+                data: bytes.clone(),
+            }),
+            (
+                ERC1155_SAFE_BATCH_TRANSFER_FROM,
+                [Word(from), Word(to), Words(ids), Words(amounts), Bytes(bytes)],
+            ) if ids.len() == amounts.len() => Ok(Erc1155Call::SafeBatchTransferFrom {
+                from: decode_address(from)?,
+                to: decode_address(to)?,
+                ids: uints(ids),
+                amounts: uints(amounts),
+                data: bytes.clone(),
+            }),
+            _ => Err(TransactionError::Message(format!(
+                "not an ERC-1155 transfer: {}",
+                hex::encode(data)
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::selector;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_erc1155_call() {
+        assert_eq!(
+            ERC1155_SAFE_TRANSFER_FROM,
+            selector("safeTransferFrom(address,address,uint256,uint256,bytes)")
+        );
+        assert_eq!(
+            ERC1155_SAFE_BATCH_TRANSFER_FROM,
+            selector("safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)")
+        );
+
+        let from = EthereumAddress::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F").unwrap();
+        let to = EthereumAddress::from_str("0x3535353535353535353535353535353535353535").unwrap();
+        let call = Erc1155Call::SafeTransferFrom {
+            from: from.clone(),
+            to: to.clone(),
+            id: U256::from(1),
+            amount: U256::from(10),
+            data: vec![],
+        };
+        let data = call.encode().unwrap();
+        // the empty data is a zero length at offset 0xa0
+        assert_eq!(4 + 6 * 32, data.len());
+        assert_eq!(
+            format!("{:064x}", 0xa0),
+            hex::encode(&data[4 + 4 * 32..4 + 5 * 32])
+        );
+        assert_eq!(call, Erc1155Call::decode(&data).unwrap());
+
+        let call = Erc1155Call::SafeBatchTransferFrom {
+            from: from.clone(),
+            to: to.clone(),
+            ids: vec![U256::from(1), U256::from(2)],
+            amounts: vec![U256::from(10), U256::from(20)],
+            data: b"hi".to_vec(),
+        };
+        let data = call.encode().unwrap();
+        let words: Vec<String> = data[4..].chunks(32).map(hex::encode).collect();
+        // the heads, then the ids, the amounts and the data
+        assert_eq!(
+            [0xa0, 0x100, 0x160, 2, 1, 2, 2, 10, 20, 2]
+                .iter()
+                .map(|word| format!("{:064x}", word))
+                .collect::<Vec<String>>(),
+            words[2..12]
+        );
+        assert_eq!(format!("6869{}", "00".repeat(30)), words[12]);
+        assert_eq!(call, Erc1155Call::decode(&data).unwrap());
+
+        assert!(Erc1155Call::SafeBatchTransferFrom {
+            from,
+            to,
+            ids: vec![U256::from(1)],
+            amounts: vec![],
+            data: vec![],
+        }
+        .encode()
+        .is_err());
+        assert!(Erc1155Call::decode(&data[..data.len() - 32]).is_err());
+    }
+}
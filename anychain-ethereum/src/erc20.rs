@@ -0,0 +1,181 @@
+//!
+//! ERC-20
+//!
+//! This module builds and decodes the calldata of the ERC-20 token calls that
+//! move funds, `transfer`, `approve` and `transferFrom`, so that a token
+//! transfer is an ordinary transaction to the token contract with this data.
+//!
+
+use crate::abi::{
+    decode_address, decode_call, decode_uint, encode_address, encode_call, encode_uint,
+};
+use crate::address::EthereumAddress;
+use anychain_core::{hex, no_std::*, TransactionError};
+use ethereum_types::U256;
+
+/// The selector of `transfer(address,uint256)`
+pub const ERC20_TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// The selector of `approve(address,uint256)`
+pub const ERC20_APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// The selector of `transferFrom(address,address,uint256)`
+pub const ERC20_TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+/// Represents an ERC-20 call moving or allowing to move 'amount' tokens
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Erc20Call {
+    /// Sends tokens of the caller to 'recipient'
+    Transfer {
+        recipient: EthereumAddress,
+        amount: U256,
+    },
+    /// Allows 'spender' to send tokens of the caller
+    Approve {
+        spender: EthereumAddress,
+        amount: U256,
+    },
+    /// Sends tokens of 'sender' to 'recipient', spending the allowance of the
+    /// caller
+    TransferFrom {
+        sender: EthereumAddress,
+        recipient: EthereumAddress,
+        amount: U256,
+    },
+}
+
+impl Erc20Call {
+    /// Returns the name of the method of the call
+    pub fn method(&self) -> &'static str {
+        match self {
+            Erc20Call::Transfer { .. } => "transfer",
+            Erc20Call::Approve { .. } => "approve",
+            Erc20Call::TransferFrom { .. } => "transferFrom",
+        }
+    }
+
+    /// Returns the address the call gives tokens or an allowance to
+    pub fn recipient(&self) -> &EthereumAddress {
+        match self {
+            Erc20Call::Transfer { recipient, .. } | Erc20Call::TransferFrom { recipient, .. } => {
+                recipient
+            }
+            Erc20Call::Approve { spender, .. } => spender,
+        }
+    }
+
+    /// Returns the amount of tokens of the call
+    pub fn amount(&self) -> U256 {
+        match self {
+            Erc20Call::Transfer { amount, .. }
+            | Erc20Call::Approve { amount, .. }
+            | Erc20Call::TransferFrom { amount, .. } => *amount,
+        }
+    }
+
+    /// Returns the calldata of the call
+    pub fn encode(&self) -> Result<Vec<u8>, TransactionError> {
+        Ok(match self {
+            Erc20Call::Transfer { recipient, amount } => encode_call(
+                ERC20_TRANSFER,
+                &[encode_address(recipient)?, encode_uint(*amount)],
+            ),
+            Erc20Call::Approve { spender, amount } => encode_call(
+                ERC20_APPROVE,
+                &[encode_address(spender)?, encode_uint(*amount)],
+            ),
+            Erc20Call::TransferFrom {
+                sender,
+                recipient,
+                amount,
+            } => encode_call(
+                ERC20_TRANSFER_FROM,
+                &[
+                    encode_address(sender)?,
+                    encode_address(recipient)?,
+                    encode_uint(*amount),
+                ],
+            ),
+        })
+    }
+
+    /// Returns the call of the calldata 'data', failing on other methods and
+    /// on malformed arguments
+    pub fn decode(data: &[u8]) -> Result<Self, TransactionError> {
+        let (selector, words) = decode_call(data)?;
+        match (selector, &words[..]) {
+            (ERC20_TRANSFER, [recipient, amount]) => Ok(Erc20Call::Transfer {
+                recipient: decode_address(recipient)?,
+                amount: decode_uint(amount),
+            }),
+            (ERC20_APPROVE, [spender, amount]) => Ok(Erc20Call::Approve {
+                spender: decode_address(spender)?,
+                amount: decode_uint(amount),
+            }),
+            (ERC20_TRANSFER_FROM, [sender, recipient, amount]) => Ok(Erc20Call::TransferFrom {
+                sender: decode_address(sender)?,
+                recipient: decode_address(recipient)?,
+                amount: decode_uint(amount),
+            }),
+            _ => Err(TransactionError::Message(format!(
+                "not an ERC-20 call: {}",
+                hex::encode(data)
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::selector;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_erc20_call() {
+        assert_eq!(ERC20_TRANSFER, selector("transfer(address,uint256)"));
+        assert_eq!(ERC20_APPROVE, selector("approve(address,uint256)"));
+        assert_eq!(
+            ERC20_TRANSFER_FROM,
+            selector("transferFrom(address,address,uint256)")
+        );
+
+        let recipient =
+            EthereumAddress::from_str("0x3535353535353535353535353535353535353535").unwrap();
+        let call = Erc20Call::Transfer {
+            recipient: recipient.clone(),
+            amount: U256::from(1_000_000u64),
+        };
+        let data = call.encode().unwrap();
+        assert_eq!(
+            format!(
+                "a9059cbb000000000000000000000000{}{:064x}",
+                "35".repeat(20),
+                1_000_000
+            ),
+            hex::encode(&data)
+        );
+        // the same calldata as the ethabi encoder
+        assert_eq!(
+            crate::encode_transfer("transfer", &recipient, U256::from(1_000_000u64)).unwrap(),
+            data
+        );
+        assert_eq!(call, Erc20Call::decode(&data).unwrap());
+        assert_eq!(("transfer", &recipient), (call.method(), call.recipient()));
+
+        let call = Erc20Call::TransferFrom {
+            sender: EthereumAddress::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+                .unwrap(),
+            recipient,
+            amount: U256::MAX,
+        };
+        let data = call.encode().unwrap();
+        assert_eq!(4 + 3 * 32, data.len());
+        assert_eq!(call, Erc20Call::decode(&data).unwrap());
+
+        // truncated arguments, a dirty address word and another method
+        assert!(Erc20Call::decode(&data[..data.len() - 1]).is_err());
+        let mut dirty = data.clone();
+        dirty[4] = 1;
+        assert!(Erc20Call::decode(&dirty).is_err());
+        assert!(Erc20Call::decode(&[&ERC20_APPROVE[..], &data[4..]].concat()).is_err());
+    }
+}
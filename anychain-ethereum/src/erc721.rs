@@ -0,0 +1,133 @@
+//!
+//! ERC-721
+//!
+//! This module builds and decodes the calldata of the ERC-721 call that moves
+//! a non-fungible token, `safeTransferFrom`, with or without its `bytes` data
+//! argument, which is passed on to the `onERC721Received` hook of contract
+//! recipients.
+//!
+
+use crate::abi::{
+    decode_address, decode_arguments, decode_uint, encode_address, encode_arguments, encode_uint,
+    Argument, ArgumentKind,
+};
+use crate::address::EthereumAddress;
+use anychain_core::{hex, no_std::*, TransactionError};
+use ethereum_types::U256;
+
+/// The selector of `safeTransferFrom(address,address,uint256)`
+pub const ERC721_SAFE_TRANSFER_FROM: [u8; 4] = [0x42, 0x84, 0x2e, 0x0e];
+/// The selector of `safeTransferFrom(address,address,uint256,bytes)`
+pub const ERC721_SAFE_TRANSFER_FROM_WITH_DATA: [u8; 4] = [0xb8, 0x8d, 0x4f, 0xde];
+
+/// Represents an ERC-721 `safeTransferFrom` call, sending the token of id
+/// 'token_id' of 'from' to 'to'. The call has a data argument unless 'data'
+/// is `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Erc721Transfer {
+    pub from: EthereumAddress,
+    pub to: EthereumAddress,
+    pub token_id: U256,
+    pub data: Option<Vec<u8>>,
+}
+
+impl Erc721Transfer {
+    /// Returns the calldata of the call
+    pub fn encode(&self) -> Result<Vec<u8>, TransactionError> {
+        let mut arguments = vec![
+            Argument::Word(encode_address(&self.from)?),
+            Argument::Word(encode_address(&self.to)?),
+            Argument::Word(encode_uint(self.token_id)),
+        ];
+        let selector = match &self.data {
+            Some(data) => {
+                arguments.push(Argument::Bytes(data.clone()));
+                ERC721_SAFE_TRANSFER_FROM_WITH_DATA
+            }
+            None => ERC721_SAFE_TRANSFER_FROM,
+        };
+        Ok(encode_arguments(selector, &arguments))
+    }
+
+    /// Returns the call of the calldata 'data', failing on other methods and
+    /// on malformed arguments
+    pub fn decode(data: &[u8]) -> Result<Self, TransactionError> {
+        use Argument::{Bytes, Word};
+        let mut kinds = vec![ArgumentKind::Word; 3];
+        if data.starts_with(&ERC721_SAFE_TRANSFER_FROM_WITH_DATA) {
+            kinds.push(ArgumentKind::Bytes);
+        }
+        let (selector, arguments) = decode_arguments(data, &kinds)?;
+        match (selector, &arguments[..]) {
+            (ERC721_SAFE_TRANSFER_FROM, [Word(from), Word(to), Word(token_id)])
+                if data.len() == 4 + 3 * 32 =>
+            {
+                Ok(Self {
+                    from: decode_address(from)?,
+                    to: decode_address(to)?,
+                    token_id: decode_uint(token_id),
+                    data: None,
+                })
+            }
+            (
+                ERC721_SAFE_TRANSFER_FROM_WITH_DATA,
+                [Word(from), Word(to), Word(token_id), Bytes(bytes)],
+            ) => Ok(Self {
+                from: decode_address(from)?,
+                to: decode_address(to)?,
+                token_id: decode_uint(token_id),
+                data: Some(bytes.clone()),
+            }),
+            _ => Err(TransactionError::Message(format!(
+                "not an ERC-721 transfer: {}",
+                hex::encode(data)
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::selector;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_erc721_transfer() {
+        assert_eq!(
+            ERC721_SAFE_TRANSFER_FROM,
+            selector("safeTransferFrom(address,address,uint256)")
+        );
+        assert_eq!(
+            ERC721_SAFE_TRANSFER_FROM_WITH_DATA,
+            selector("safeTransferFrom(address,address,uint256,bytes)")
+        );
+
+        let mut transfer = Erc721Transfer {
+            from: EthereumAddress::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F").unwrap(),
+            to: EthereumAddress::from_str("0x3535353535353535353535353535353535353535").unwrap(),
+            token_id: U256::from(7),
+            data: None,
+        };
+        let data = transfer.encode().unwrap();
+        assert_eq!(4 + 3 * 32, data.len());
+        assert_eq!(transfer, Erc721Transfer::decode(&data).unwrap());
+
+        transfer.data = Some(vec![0xca, 0xfe]);
+        let data = transfer.encode().unwrap();
+        // the data is at offset 0x80, after the 4 heads
+        assert_eq!(
+            format!("{:064x}{:064x}cafe{}", 0x80, 2, "00".repeat(30)),
+            hex::encode(&data[4 + 3 * 32..])
+        );
+        assert_eq!(transfer, Erc721Transfer::decode(&data).unwrap());
+
+        // dirty padding and an offset out of the call
+        let mut dirty = data.clone();
+        *dirty.last_mut().unwrap() = 1;
+        assert!(Erc721Transfer::decode(&dirty).is_err());
+        let mut dirty = data;
+        dirty[4 + 3 * 32 + 31] = 0xc0;
+        assert!(Erc721Transfer::decode(&dirty).is_err());
+    }
+}
@@ -6,21 +6,44 @@
 #![warn(unused_extern_crates, dead_code)]
 #![forbid(unsafe_code)]
 
+pub mod abi;
+
 pub mod address;
 pub use self::address::*;
 
 pub mod amount;
 pub use self::amount::*;
 
+pub mod eip712;
+pub use self::eip712::*;
+
+pub mod ens;
+pub use self::ens::*;
+
+pub mod erc1155;
+pub use self::erc1155::*;
+
+pub mod erc20;
+pub use self::erc20::*;
+
+pub mod erc721;
+pub use self::erc721::*;
+
 pub mod format;
 pub use self::format::*;
 
+pub mod message;
+pub use self::message::*;
+
 pub mod network;
 pub use self::network::*;
 
 pub mod public_key;
 pub use self::public_key::*;
 
+pub mod safe;
+pub use self::safe::*;
+
 pub mod transaction;
 pub use self::transaction::*;
 
@@ -0,0 +1,147 @@
+//!
+//! Message
+//!
+//! This module implements the `personal_sign` signed message of EIP-191
+//! (version 0x45): the message is framed with "\x19Ethereum Signed Message:\n"
+//! and its decimal length, hashed with Keccak-256 and signed with a 65 byte
+//! r || s || v signature, where v is the recovery id plus 27. The signer of
+//! a signature is the address of the key recovered from it.
+//!
+
+use crate::{EthereumAddress, EthereumFormat, EthereumPublicKey};
+use anychain_core::{
+    crypto::{keccak256, sign_deterministic},
+    libsecp256k1::{self, Message, RecoveryId, Signature},
+    no_std::*,
+    Address, AddressError, MessageSigner, MessageVerifier,
+};
+
+/// Returns the payload `personal_sign` hashes for 'message': the message
+/// preceded by "\x19Ethereum Signed Message:\n" and its decimal length
+pub fn personal_message(message: &[u8]) -> Vec<u8> {
+    let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
+    [prefix.as_bytes(), message].concat()
+}
+
+/// Returns the hash signed by `personal_sign` for 'message'
+pub fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    keccak256(&personal_message(message))
+}
+
+/// Splits the 65 byte r || s || v 'signature' into its compact signature and
+/// its recovery id, accepting both the 27/28 and the 0/1 encodings of v
+fn parse_signature(signature: &[u8]) -> Result<(Signature, RecoveryId), AddressError> {
+    let invalid = || AddressError::Message("invalid message signature".into());
+
+    if signature.len() != 65 {
+        return Err(invalid());
+    }
+    let recid = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => return Err(invalid()),
+    };
+    let recid = RecoveryId::parse(recid).map_err(|_| invalid())?;
+    let rs = Signature::parse_standard_slice(&signature[..64]).map_err(|_| invalid())?;
+    Ok((rs, recid))
+}
+
+/// Returns the address of the key that made the 65 byte r || s || v
+/// 'signature' of 'hash', such as a `personal_sign` or an EIP-712 digest
+pub fn recover_signer(hash: &[u8; 32], signature: &[u8]) -> Result<EthereumAddress, AddressError> {
+    let (rs, recid) = parse_signature(signature)?;
+    let public_key = libsecp256k1::recover(&Message::parse(hash), &rs, &recid)
+        .map_err(|error| AddressError::Message(format!("{}", error)))?;
+    EthereumAddress::from_public_key(
+        &EthereumPublicKey::from_secp256k1_public_key(public_key),
+        &EthereumFormat::Standard,
+    )
+}
+
+/// Returns the address of the key that made the `personal_sign` 'signature'
+/// of 'message'
+pub fn recover_message_signer(
+    message: &[u8],
+    signature: &[u8],
+) -> Result<EthereumAddress, AddressError> {
+    recover_signer(&personal_message_hash(message), signature)
+}
+
+impl MessageSigner for EthereumAddress {
+    /// Returns the 65 byte `personal_sign` signature of 'message' by
+    /// 'secret_key'
+    fn sign_message(
+        secret_key: &Self::SecretKey,
+        _format: &Self::Format,
+        message: &[u8],
+    ) -> Result<Vec<u8>, AddressError> {
+        let hash = Message::parse(&personal_message_hash(message));
+        let (signature, recid) = sign_deterministic(&hash, secret_key);
+        Ok([&signature.serialize()[..], &[recid.serialize() + 27]].concat())
+    }
+}
+
+impl MessageVerifier for EthereumAddress {
+    /// Returns true if 'signature' is the `personal_sign` signature of
+    /// 'message' by the key of this address. Both the 27/28 and the 0/1
+    /// encodings of v are accepted.
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<bool, AddressError> {
+        let (rs, recid) = parse_signature(signature)?;
+        let hash = Message::parse(&personal_message_hash(message));
+        match libsecp256k1::recover(&hash, &rs, &recid) {
+            Ok(public_key) => Ok(Self::from_public_key(
+                &EthereumPublicKey::from_secp256k1_public_key(public_key),
+                &EthereumFormat::Standard,
+            )? == *self),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::{hex, libsecp256k1::SecretKey};
+
+    #[test]
+    fn test_personal_sign() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let address =
+            EthereumAddress::from_secret_key(&secret_key, &EthereumFormat::Standard).unwrap();
+        let signature =
+            EthereumAddress::sign_message(&secret_key, &EthereumFormat::Standard, b"hello")
+                .unwrap();
+        assert_eq!(65, signature.len());
+        assert!(address.verify_message(b"hello", &signature).unwrap());
+        assert!(!address.verify_message(b"hello!", &signature).unwrap());
+
+        // v may also be the bare recovery id
+        let mut signature = signature;
+        signature[64] -= 27;
+        assert!(address.verify_message(b"hello", &signature).unwrap());
+        assert_eq!(
+            address,
+            recover_message_signer(b"hello", &signature).unwrap()
+        );
+        assert_ne!(
+            address,
+            recover_message_signer(b"hello!", &signature).unwrap()
+        );
+        signature[64] = 29;
+        assert!(address.verify_message(b"hello", &signature).is_err());
+        assert!(recover_message_signer(b"hello", &signature).is_err());
+    }
+
+    #[test]
+    fn test_personal_message_hash() {
+        // the hashMessage example of ethers.js
+        assert_eq!(
+            b"\x19Ethereum Signed Message:\n11Hello World".to_vec(),
+            personal_message(b"Hello World")
+        );
+        assert_eq!(
+            "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2",
+            hex::encode(personal_message_hash(b"Hello World"))
+        );
+    }
+}
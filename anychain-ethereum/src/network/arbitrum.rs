@@ -5,7 +5,7 @@ use crate::no_std::{fmt, FromStr};
 use serde::Serialize;
 
 /// Represents an ARB mainnet
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct Arbitrum;
 
 impl Network for Arbitrum {
@@ -1,5 +1,6 @@
 use crate::address::EthereumAddress;
 use crate::format::EthereumFormat;
+use anychain_core::crypto::secp256k1_verify;
 use anychain_core::{hex, libsecp256k1, Address, AddressError, PublicKey, PublicKeyError};
 use core::{fmt, fmt::Display, str::FromStr};
 
@@ -21,6 +22,13 @@ impl PublicKey for EthereumPublicKey {
     fn to_address(&self, _format: &Self::Format) -> Result<Self::Address, AddressError> {
         Self::Address::from_public_key(self, _format)
     }
+
+    /// Returns true if 'signature', the 64 byte r || s with an optional
+    /// recovery id, is a signature of the 32 byte 'message_hash' by this
+    /// public key.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError> {
+        secp256k1_verify(&self.0, message_hash, signature)
+    }
 }
 
 impl EthereumPublicKey {
@@ -29,8 +37,9 @@ impl EthereumPublicKey {
         Self(public_key)
     }
 
-    pub fn from_slice(sl: &[u8]) -> Self {
-        Self(libsecp256k1::PublicKey::parse_slice(sl, None).unwrap())
+    /// Returns a public key given its compressed, uncompressed or raw 64-byte encoding
+    pub fn from_slice(sl: &[u8]) -> Result<Self, PublicKeyError> {
+        Ok(Self(libsecp256k1::PublicKey::parse_slice(sl, None)?))
     }
 
     /// Returns the secp256k1 public key of the public key
@@ -196,8 +205,9 @@ mod tests {
             72, 21, 35, 233, 194, 163, 225, 28,
         ];
 
-        let pk = EthereumPublicKey::from_slice(&raw_pk);
-        let pk1 = EthereumPublicKey::from_slice(&raw_pk1);
+        let pk = EthereumPublicKey::from_slice(&raw_pk).unwrap();
+        let pk1 = EthereumPublicKey::from_slice(&raw_pk1).unwrap();
+        assert!(EthereumPublicKey::from_slice(&raw_pk[..63]).is_err());
 
         let addr = pk.to_address(&EthereumFormat::Standard).unwrap();
         let addr1 = pk1.to_address(&EthereumFormat::Standard).unwrap();
@@ -207,4 +217,18 @@ mod tests {
         println!("address for {:?} is {}", raw_pk1, addr1);
         println!();
     }
+
+    #[test]
+    fn test_verify() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let public_key = EthereumPublicKey::from_secret_key(&secret_key);
+        let other = EthereumPublicKey::from_secret_key(&SecretKey::parse(&[2u8; 32]).unwrap());
+        let hash = anychain_core::crypto::keccak256(b"anychain");
+        let message = libsecp256k1::Message::parse(&hash);
+        let signature = libsecp256k1::sign(&message, &secret_key).0.serialize();
+
+        assert!(public_key.verify(&hash, &signature).unwrap());
+        assert!(!other.verify(&hash, &signature).unwrap());
+        assert!(public_key.verify(&hash, &signature[..32]).is_err());
+    }
 }
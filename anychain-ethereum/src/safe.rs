@@ -0,0 +1,205 @@
+//!
+//! Safe
+//!
+//! This module computes the hash of the transactions of Safe (formerly Gnosis
+//! Safe) multisig accounts, the EIP-712 hash of a `SafeTx` struct in the
+//! domain of the chain id and the address of the Safe, which each owner signs
+//! offline. Safe accepts these signatures in the r || s || v format where v is
+//! the recovery id plus 27, sorted by owner in `execTransaction`.
+//!
+
+use crate::address::EthereumAddress;
+use crate::eip712::{Eip712Domain, Eip712TypedData, Eip712Types, Eip712Value};
+use anychain_core::{libsecp256k1::SecretKey, no_std::*, AddressError};
+use core::str::FromStr;
+use ethereum_types::U256;
+
+/// Represents the operation of a Safe transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SafeOperation {
+    /// A call to the receiver
+    #[default]
+    Call = 0,
+    /// A delegate call to the receiver, running its code as the Safe
+    DelegateCall = 1,
+}
+
+/// Represents a transaction of a Safe, calling 'to' with 'value' wei and
+/// 'data'. The gas fields refund the executor 'base_gas' plus the gas used
+/// by the call at 'gas_price' in 'gas_token' to 'refund_receiver', the zero
+/// address standing for ether and for the executor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SafeTransaction {
+    pub to: EthereumAddress,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub operation: SafeOperation,
+    pub safe_tx_gas: U256,
+    pub base_gas: U256,
+    pub gas_price: U256,
+    pub gas_token: EthereumAddress,
+    pub refund_receiver: EthereumAddress,
+    /// The nonce of the Safe
+    pub nonce: U256,
+}
+
+impl SafeTransaction {
+    /// Returns the call of 'to' with 'value' wei and 'data' of nonce 'nonce',
+    /// without a refund
+    pub fn new(to: EthereumAddress, value: U256, data: Vec<u8>, nonce: U256) -> Self {
+        let zero = EthereumAddress::from_str(&"00".repeat(20)).unwrap();
+        Self {
+            to,
+            value,
+            data,
+            operation: SafeOperation::Call,
+            safe_tx_gas: U256::zero(),
+            base_gas: U256::zero(),
+            gas_price: U256::zero(),
+            gas_token: zero.clone(),
+            refund_receiver: zero,
+            nonce,
+        }
+    }
+
+    /// Returns the typed data of the transaction of the Safe at 'safe' on the
+    /// chain 'chain_id'
+    pub fn typed_data(&self, chain_id: U256, safe: &EthereumAddress) -> Eip712TypedData {
+        let mut types = Eip712Types::new();
+        types.insert(
+            "SafeTx",
+            &[
+                ("to", "address"),
+                ("value", "uint256"),
+                ("data", "bytes"),
+                ("operation", "uint8"),
+                ("safeTxGas", "uint256"),
+                ("baseGas", "uint256"),
+                ("gasPrice", "uint256"),
+                ("gasToken", "address"),
+                ("refundReceiver", "address"),
+                ("nonce", "uint256"),
+            ],
+        );
+        let operation = U256::from(self.operation as u8);
+        let message = [
+            ("to", Eip712Value::Address(self.to.clone())),
+            ("value", Eip712Value::Uint(self.value)),
+            ("data", Eip712Value::Bytes(self.data.clone())),
+            ("operation", Eip712Value::Uint(operation)),
+            ("safeTxGas", Eip712Value::Uint(self.safe_tx_gas)),
+            ("baseGas", Eip712Value::Uint(self.base_gas)),
+            ("gasPrice", Eip712Value::Uint(self.gas_price)),
+            ("gasToken", Eip712Value::Address(self.gas_token.clone())),
+            (
+                "refundReceiver",
+                Eip712Value::Address(self.refund_receiver.clone()),
+            ),
+            ("nonce", Eip712Value::Uint(self.nonce)),
+        ];
+
+        Eip712TypedData {
+            domain: Eip712Domain {
+                chain_id: Some(chain_id),
+                verifying_contract: Some(safe.clone()),
+                ..Default::default()
+            },
+            types,
+            primary_type: "SafeTx".into(),
+            message: Eip712Value::Struct(
+                message
+                    .into_iter()
+                    .map(|(name, value)| (name.into(), value))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the Safe transaction hash of the transaction of the Safe at
+    /// 'safe' on the chain 'chain_id', which its owners sign
+    pub fn hash(&self, chain_id: U256, safe: &EthereumAddress) -> Result<[u8; 32], AddressError> {
+        self.typed_data(chain_id, safe).signing_hash()
+    }
+
+    /// Returns the 65 byte r || s || v signature by 'secret_key' of the
+    /// transaction of the Safe at 'safe' on the chain 'chain_id'
+    pub fn sign(
+        &self,
+        chain_id: U256,
+        safe: &EthereumAddress,
+        secret_key: &SecretKey,
+    ) -> Result<Vec<u8>, AddressError> {
+        self.typed_data(chain_id, safe).sign(secret_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{encode_address, encode_uint};
+    use crate::message::recover_signer;
+    use crate::public_key::EthereumPublicKey;
+    use crate::EthereumFormat;
+    use anychain_core::{crypto::keccak256, hex, PublicKey};
+
+    #[test]
+    fn test_safe_transaction() {
+        let safe = EthereumAddress::from_str("0x3535353535353535353535353535353535353535").unwrap();
+        let to = EthereumAddress::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F").unwrap();
+        let mut transaction = SafeTransaction::new(
+            to.clone(),
+            U256::from(1000),
+            vec![0xca, 0xfe],
+            U256::from(3),
+        );
+        transaction.operation = SafeOperation::DelegateCall;
+        let typed_data = transaction.typed_data(U256::from(5), &safe);
+
+        // the SAFE_TX_TYPEHASH and DOMAIN_SEPARATOR_TYPEHASH of the Safe
+        // contracts
+        assert_eq!(
+            "bb8310d486368db6bd6f849402fdd73ad53d316b5a4b2644ad6efe0f941286d8",
+            hex::encode(typed_data.types.type_hash("SafeTx").unwrap())
+        );
+        let domain_separator = keccak256(
+            &[
+                &hex::decode("47e79534a245952e8b16893a336b85a3d9ea9fa8c573f3d803afb92a79469218")
+                    .unwrap()[..],
+                &encode_uint(U256::from(5)),
+                &encode_address(&safe).unwrap(),
+            ]
+            .concat(),
+        );
+        assert_eq!(domain_separator, typed_data.domain.separator().unwrap());
+
+        // the hash of getTransactionHash, computed as the Safe contracts do
+        let zero = [0u8; 32];
+        let safe_tx_hash = keccak256(
+            &[
+                &typed_data.types.type_hash("SafeTx").unwrap()[..],
+                &encode_address(&to).unwrap(),
+                &encode_uint(U256::from(1000)),
+                &keccak256(&[0xca, 0xfe]),
+                &encode_uint(U256::one()),
+                &zero,
+                &zero,
+                &zero,
+                &zero,
+                &zero,
+                &encode_uint(U256::from(3)),
+            ]
+            .concat(),
+        );
+        let hash = keccak256(&[&[0x19, 0x01], &domain_separator[..], &safe_tx_hash].concat());
+        assert_eq!(hash, transaction.hash(U256::from(5), &safe).unwrap());
+
+        let secret_key = SecretKey::parse(&[0x46; 32]).unwrap();
+        let signature = transaction.sign(U256::from(5), &safe, &secret_key).unwrap();
+        assert_eq!(
+            EthereumPublicKey::from_secret_key(&secret_key)
+                .to_address(&EthereumFormat::Standard)
+                .unwrap(),
+            recover_signer(&hash, &signature).unwrap()
+        );
+    }
+}
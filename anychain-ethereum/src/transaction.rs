@@ -5,14 +5,17 @@ use crate::network::EthereumNetwork;
 use crate::public_key::EthereumPublicKey;
 use anychain_core::no_std::*;
 use anychain_core::utilities::crypto::keccak256;
-use anychain_core::{hex, libsecp256k1, PublicKey, Transaction, TransactionError, TransactionId};
+use anychain_core::{
+    hex, libsecp256k1, AddressError, MultiSignTransaction, PublicKey, SigningPayload, Transaction,
+    TransactionBuilder, TransactionError, TransactionId,
+};
 #[cfg(not(feature = "std"))]
 use core::convert::TryInto;
 use core::{fmt, marker::PhantomData, str::FromStr};
 use ethabi::ethereum_types::H160;
 use ethabi::{Function, Param, ParamType, StateMutability, Token};
 use ethereum_types::U256;
-use rlp::{decode_list, RlpStream};
+use rlp::{Rlp, RlpStream};
 #[cfg(feature = "std")]
 use std::convert::TryInto;
 
@@ -40,7 +43,38 @@ fn pad_zeros(v: &mut Vec<u8>, to_len: usize) {
     }
 }
 
-pub fn encode_transfer(func_name: &str, address: &EthereumAddress, amount: U256) -> Vec<u8> {
+/// Returns the RLP field of 'receiver', empty for a contract creation
+fn encode_receiver(receiver: &Option<EthereumAddress>) -> Result<Vec<u8>, TransactionError> {
+    match receiver {
+        Some(receiver) => Ok(hex::decode(&receiver.to_string()[2..])?),
+        None => Ok(vec![]),
+    }
+}
+
+/// Returns the receiver of an RLP field, `None` if it is empty
+fn decode_receiver(bytes: &[u8]) -> Result<Option<EthereumAddress>, TransactionError> {
+    match bytes {
+        [] => Ok(None),
+        bytes => Ok(Some(EthereumAddress::from_str(&hex::encode(bytes))?)),
+    }
+}
+
+/// Returns the big-endian integer of an RLP field, failing if it exceeds 256 bits
+fn to_u256(bytes: &[u8]) -> Result<U256, TransactionError> {
+    match bytes.len() {
+        0..=32 => Ok(U256::from(bytes)),
+        length => Err(TransactionError::Message(format!(
+            "integer field of {} bytes exceeds 256 bits",
+            length
+        ))),
+    }
+}
+
+pub fn encode_transfer(
+    func_name: &str,
+    address: &EthereumAddress,
+    amount: U256,
+) -> Result<Vec<u8>, TransactionError> {
     #[allow(deprecated)]
     let func = Function {
         name: func_name.to_string(),
@@ -61,19 +95,27 @@ pub fn encode_transfer(func_name: &str, address: &EthereumAddress, amount: U256)
         state_mutability: StateMutability::Payable,
     };
 
+    let address = address
+        .to_bytes()
+        .map_err(|error| AddressError::InvalidAddress(error.to_string()))?;
+    if address.len() != 20 {
+        return Err(AddressError::InvalidByteLength(address.len()).into());
+    }
     let tokens = vec![
-        Token::Address(H160::from_slice(&address.to_bytes().unwrap())),
+        Token::Address(H160::from_slice(&address)),
         Token::Uint(amount),
     ];
 
-    func.encode_input(&tokens).unwrap()
+    func.encode_input(&tokens)
+        .map_err(|error| TransactionError::Crate("ethabi", format!("{:?}", error)))
 }
 
 /// Represents the parameters for an Ethereum transaction
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EthereumTransactionParameters {
-    /// The address of the receiver
-    pub receiver: EthereumAddress,
+    /// The address of the receiver, or `None` for a contract creation, whose
+    /// data is the init code of the contract
+    pub receiver: Option<EthereumAddress>,
     /// The amount (in wei)
     pub amount: EthereumAmount,
     /// The transaction gas limit
@@ -84,6 +126,200 @@ pub struct EthereumTransactionParameters {
     pub nonce: U256,
     /// The transaction data
     pub data: Vec<u8>,
+    /// The type of the transaction, which selects its fee model and encoding
+    pub transaction_type: EthereumTransactionType,
+}
+
+/// Represents the type of an Ethereum transaction, with the fields it adds to
+/// those of legacy transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum EthereumTransactionType {
+    /// A legacy transaction paying 'gas_price' per gas, replay protected by
+    /// EIP-155
+    #[default]
+    Legacy,
+    /// An EIP-1559 dynamic fee transaction (type 2), paying at most
+    /// 'gas_price' per gas, of which at most 'max_priority_fee_per_gas' goes
+    /// to the block producer
+    Eip1559 {
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+    },
+    /// An EIP-4844 blob transaction (type 3), a dynamic fee transaction also
+    /// paying at most 'max_fee_per_blob_gas' per blob gas for the blobs of
+    /// 'blob_versioned_hashes'. The blobs, their commitments and their proofs
+    /// travel in a sidecar the signature does not cover.
+    Eip4844 {
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+        max_fee_per_blob_gas: U256,
+        blob_versioned_hashes: Vec<[u8; 32]>,
+    },
+    /// An EIP-7702 set code transaction (type 4), a dynamic fee transaction
+    /// also setting the code of each signer of 'authorization_list' to a
+    /// delegation to the contract it authorizes
+    Eip7702 {
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+        authorization_list: Vec<Authorization>,
+    },
+}
+
+/// The version byte of the versioned hashes of KZG blob commitments
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+impl EthereumTransactionType {
+    /// Returns the type byte prefixing the envelope of typed transactions
+    /// (EIP-2718)
+    pub fn type_byte(&self) -> Option<u8> {
+        match self {
+            EthereumTransactionType::Legacy => None,
+            EthereumTransactionType::Eip1559 { .. } => Some(0x02),
+            EthereumTransactionType::Eip4844 { .. } => Some(0x03),
+            EthereumTransactionType::Eip7702 { .. } => Some(0x04),
+        }
+    }
+}
+
+/// The magic byte prefixing the signing payload of EIP-7702 authorizations
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// Represents an EIP-7702 authorization, by which the signer, its authority,
+/// delegates the code of its account to the contract at 'address'. A chain id
+/// of 0 makes the authorization valid on every chain.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: EthereumAddress,
+    /// The nonce of the authority when the authorization applies
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Authorization {
+    /// Returns the unsigned authorization of the authority of nonce 'nonce' to
+    /// the contract at 'address' on the chain of id 'chain_id'
+    pub fn new(chain_id: U256, address: EthereumAddress, nonce: u64) -> Self {
+        Self {
+            chain_id,
+            address,
+            nonce,
+            y_parity: 0,
+            r: U256::zero(),
+            s: U256::zero(),
+        }
+    }
+
+    /// Returns the hash the authority signs,
+    /// keccak256(0x05 || rlp([chain_id, address, nonce]))
+    pub fn signing_hash(&self) -> Result<[u8; 32], TransactionError> {
+        let mut rlp = RlpStream::new_list(3);
+        rlp.append(&self.chain_id);
+        rlp.append(&hex::decode(&self.address.to_string()[2..])?);
+        rlp.append(&self.nonce);
+        Ok(keccak256(
+            &[&[AUTHORIZATION_MAGIC], &rlp.out()[..]].concat(),
+        ))
+    }
+
+    /// Inserts the signature {r,s} of recovery id 'recid' of the signing hash
+    pub fn insert_signature(&mut self, rs: &[u8], recid: u8) -> Result<(), TransactionError> {
+        if rs.len() != 64 || recid > 1 {
+            return Err(TransactionError::Message(format!(
+                "invalid authorization signature of length {} and recovery id {}",
+                rs.len(),
+                recid
+            )));
+        }
+        self.y_parity = recid;
+        self.r = U256::from_big_endian(&rs[..32]);
+        self.s = U256::from_big_endian(&rs[32..]);
+        Ok(())
+    }
+
+    /// Returns the address of the authority, recovered from the signature
+    pub fn authority(&self) -> Result<EthereumAddress, TransactionError> {
+        let mut rs = [0u8; 64];
+        self.r.to_big_endian(&mut rs[..32]);
+        self.s.to_big_endian(&mut rs[32..]);
+        let message = libsecp256k1::Message::parse_slice(&self.signing_hash()?)?;
+        let public_key = libsecp256k1::recover(
+            &message,
+            &libsecp256k1::Signature::parse_standard_slice(&rs)?,
+            &libsecp256k1::RecoveryId::parse(self.y_parity)?,
+        )?;
+        Ok(EthereumPublicKey::from_secp256k1_public_key(public_key)
+            .to_address(&EthereumFormat::Standard)?)
+    }
+}
+
+/// Represents an entry of the access list of a typed transaction (EIP-2930),
+/// an address and the storage slots the transaction will access
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessListItem {
+    pub address: EthereumAddress,
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+/// Appends the RLP list of 'access_list' to 'rlp'
+fn append_access_list(
+    rlp: &mut RlpStream,
+    access_list: &[AccessListItem],
+) -> Result<(), TransactionError> {
+    rlp.begin_list(access_list.len());
+    for item in access_list {
+        rlp.begin_list(2);
+        rlp.append(&hex::decode(&item.address.to_string()[2..])?);
+        rlp.begin_list(item.storage_keys.len());
+        for key in &item.storage_keys {
+            rlp.append(&key.as_slice());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the authorization list of the RLP list 'rlp'
+fn decode_authorization_list(rlp: &Rlp) -> Result<Vec<Authorization>, TransactionError> {
+    rlp.iter()
+        .map(|item| {
+            if item.item_count()? != 6 {
+                return Err(TransactionError::InvalidRlpLength(item.item_count()?));
+            }
+            Ok(Authorization {
+                chain_id: to_u256(item.at(0)?.data()?)?,
+                address: EthereumAddress::from_str(&hex::encode(item.at(1)?.data()?))?,
+                nonce: item.val_at(2)?,
+                y_parity: item.val_at(3)?,
+                r: to_u256(item.at(4)?.data()?)?,
+                s: to_u256(item.at(5)?.data()?)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the 32 byte hashes of the RLP list 'rlp'
+fn decode_hashes(rlp: &Rlp) -> Result<Vec<[u8; 32]>, TransactionError> {
+    rlp.iter()
+        .map(|hash| {
+            hash.data()?
+                .try_into()
+                .map_err(|_| TransactionError::Message("invalid 32 byte hash".into()))
+        })
+        .collect()
+}
+
+/// Returns the access list of the RLP list 'rlp'
+fn decode_access_list(rlp: &Rlp) -> Result<Vec<AccessListItem>, TransactionError> {
+    rlp.iter()
+        .map(|item| {
+            Ok(AccessListItem {
+                address: EthereumAddress::from_str(&hex::encode(item.at(0)?.data()?))?,
+                storage_keys: decode_hashes(&item.at(1)?)?,
+            })
+        })
+        .collect()
 }
 
 /// Represents an Ethereum transaction signature
@@ -132,6 +368,39 @@ impl<N: EthereumNetwork> Transaction for EthereumTransaction<N> {
 
     /// Returns an unsigned transaction given the transaction parameters.
     fn new(parameters: &Self::TransactionParameters) -> Result<Self, TransactionError> {
+        if parameters.receiver.is_none()
+            && matches!(
+                parameters.transaction_type,
+                EthereumTransactionType::Eip4844 { .. } | EthereumTransactionType::Eip7702 { .. }
+            )
+        {
+            return Err(TransactionError::Message(
+                "blob and set code transactions cannot create contracts".into(),
+            ));
+        }
+        if let EthereumTransactionType::Eip4844 {
+            blob_versioned_hashes,
+            ..
+        } = &parameters.transaction_type
+        {
+            if blob_versioned_hashes.is_empty()
+                || blob_versioned_hashes
+                    .iter()
+                    .any(|hash| hash[0] != VERSIONED_HASH_VERSION_KZG)
+            {
+                return Err(TransactionError::Message(
+                    "invalid blob versioned hashes".into(),
+                ));
+            }
+        }
+        if let EthereumTransactionType::Eip7702 {
+            authorization_list, ..
+        } = &parameters.transaction_type
+        {
+            if authorization_list.is_empty() {
+                return Err(TransactionError::Message("empty authorization list".into()));
+            }
+        }
         Ok(Self {
             sender: None,
             parameters: parameters.clone(),
@@ -142,108 +411,54 @@ impl<N: EthereumNetwork> Transaction for EthereumTransaction<N> {
 
     /// Returns a signed transaction given the {r,s,recid}.
     fn sign(&mut self, rs: Vec<u8>, recid: u8) -> Result<Vec<u8>, TransactionError> {
-        let message = libsecp256k1::Message::parse_slice(&self.to_transaction_id()?.txid)?;
-        let recovery_id = libsecp256k1::RecoveryId::parse(recid)?;
-
-        let public_key = EthereumPublicKey::from_secp256k1_public_key(libsecp256k1::recover(
-            &message,
-            &libsecp256k1::Signature::parse_standard_slice(rs.as_slice())?,
-            &recovery_id,
-        )?);
-        self.sender = Some(public_key.to_address(&EthereumFormat::Standard)?);
-        self.signature =
-            Some(EthereumTransactionSignature {
-                v: (u32::from(recid) + N::CHAIN_ID * 2 + 35)
-                    .to_be_bytes()
-                    .to_vec(), // EIP155
-                r: rs[..32].to_vec(),
-                s: rs[32..64].to_vec(),
-            });
-        self.to_bytes()
+        if rs.len() != 64 {
+            return Err(TransactionError::Message(format!(
+                "invalid signature length {}",
+                rs.len()
+            )));
+        }
+        let v = match self.parameters.transaction_type {
+            // EIP-155
+            EthereumTransactionType::Legacy => Self::eip155_offset()? + u64::from(recid),
+            _ => u64::from(recid),
+        };
+        self.insert_signature(v, &rs[..32], &rs[32..])
     }
 
     /// Returns a transaction given the transaction bytes.
     /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-155.md
     fn from_bytes(transaction: &[u8]) -> Result<Self, TransactionError> {
-        let list: Vec<Vec<u8>> = decode_list(transaction);
+        // EIP-2718: typed transactions start with a type byte below 0x80,
+        // legacy ones with an RLP list header
+        if let [type_byte @ 0x00..=0x7f, typed @ ..] = transaction {
+            return Self::from_typed_bytes(*type_byte, typed);
+        }
+        let list: Vec<Vec<u8>> = Rlp::new(transaction).as_list()?;
         if list.len() != 9 {
             return Err(TransactionError::InvalidRlpLength(list.len()));
         }
 
-        let parameters =
-            EthereumTransactionParameters {
-                receiver: EthereumAddress::from_str(&hex::encode(&list[3]))?,
-                amount: match list[4].is_empty() {
-                    true => EthereumAmount::from_u256(U256::zero()),
-                    false => EthereumAmount::from_u256(U256::from(list[4].as_slice())),
-                },
-                gas: match list[2].is_empty() {
-                    true => U256::zero(),
-                    false => U256::from(list[2].as_slice()),
-                },
-                gas_price: match list[1].is_empty() {
-                    true => EthereumAmount::from_u256(U256::zero()),
-                    false => EthereumAmount::from_u256(U256::from(list[1].as_slice())),
-                },
-                nonce: match list[0].is_empty() {
-                    true => U256::zero(),
-                    false => U256::from(list[0].as_slice()),
-                },
-                data: list[5].clone(),
-            };
+        let parameters = EthereumTransactionParameters {
+            receiver: decode_receiver(&list[3])?,
+            amount: EthereumAmount::from_u256(to_u256(&list[4])?),
+            gas: to_u256(&list[2])?,
+            gas_price: EthereumAmount::from_u256(to_u256(&list[1])?),
+            nonce: to_u256(&list[0])?,
+            data: list[5].clone(),
+            transaction_type: EthereumTransactionType::Legacy,
+        };
 
-        match list[7].is_empty() && list[8].is_empty() {
-            true => {
-                // Raw transaction
-                Ok(Self {
-                    sender: None,
-                    parameters,
-                    signature: None,
-                    _network: PhantomData,
-                })
-            }
-            false => {
-                // Signed transaction
-                let mut v = list[6].clone();
-                pad_zeros(&mut v, 4);
-                let v: [u8; 4] = v.try_into().unwrap();
-                let v = u32::from_be_bytes(v);
-                let recovery_id =
-                    libsecp256k1::RecoveryId::parse((v - N::CHAIN_ID * 2 - 35) as u8)?;
-                let mut r = list[7].clone();
-                pad_zeros(&mut r, 32);
-                let mut s = list[8].clone();
-                pad_zeros(&mut s, 32);
-                let signature = [r.clone(), s.clone()].concat();
-                let raw_transaction =
-                    Self {
-                        sender: None,
-                        parameters: parameters.clone(),
-                        signature: None,
-                        _network: PhantomData,
-                    };
-                let message =
-                    libsecp256k1::Message::parse_slice(&raw_transaction.to_transaction_id()?.txid)?;
-                let public_key = EthereumPublicKey::from_secp256k1_public_key(
-                    libsecp256k1::recover(
-                        &message,
-                        &libsecp256k1::Signature::parse_standard_slice(signature.as_slice())?,
-                        &recovery_id,
-                    )?,
-                );
-
-                Ok(Self {
-                    sender: Some(public_key.to_address(&EthereumFormat::Standard)?),
-                    parameters,
-                    signature: Some(EthereumTransactionSignature {
-                        v: v.to_be_bytes().to_vec(),
-                        r,
-                        s,
-                    }),
-                    _network: PhantomData,
-                })
-            }
+        let mut transaction = Self::new(&parameters)?;
+        if !(list[7].is_empty() && list[8].is_empty()) {
+            // Signed transaction
+            let mut v = list[6].clone();
+            pad_zeros(&mut v, 8);
+            let v: [u8; 8] = v
+                .try_into()
+                .map_err(|v: Vec<u8>| TransactionError::InvalidRlpLength(v.len()))?;
+            transaction.insert_signature(u64::from_be_bytes(v), &list[7], &list[8])?;
         }
+        Ok(transaction)
     }
 
     /// Returns the transaction in bytes.
@@ -258,7 +473,7 @@ impl<N: EthereumNetwork> Transaction for EthereumTransaction<N> {
             transaction_rlp.append(&parameters.nonce);
             transaction_rlp.append(&parameters.gas_price.0);
             transaction_rlp.append(&parameters.gas);
-            transaction_rlp.append(&hex::decode(&parameters.receiver.to_string()[2..])?);
+            transaction_rlp.append(&encode_receiver(&parameters.receiver)?);
             transaction_rlp.append(&parameters.amount.0);
             transaction_rlp.append(&parameters.data);
             Ok(())
@@ -299,11 +514,96 @@ impl<N: EthereumNetwork> Transaction for EthereumTransaction<N> {
             Ok(transaction_rlp)
         }
 
-        match &self.signature {
-            Some(signature) => Ok(signed_transaction(&self.parameters, signature)?
+        // Returns the envelope of a typed transaction, signed or not: its type
+        // byte followed by the RLP list of its fields.
+        // https://eips.ethereum.org/EIPS/eip-1559
+        // https://eips.ethereum.org/EIPS/eip-4844
+        // https://eips.ethereum.org/EIPS/eip-7702
+        fn typed_transaction<N: EthereumNetwork>(
+            type_byte: u8,
+            parameters: &EthereumTransactionParameters,
+            signature: Option<&EthereumTransactionSignature>,
+        ) -> Result<Vec<u8>, TransactionError> {
+            let (max_priority_fee_per_gas, access_list) = match &parameters.transaction_type {
+                EthereumTransactionType::Eip1559 {
+                    max_priority_fee_per_gas,
+                    access_list,
+                }
+                | EthereumTransactionType::Eip4844 {
+                    max_priority_fee_per_gas,
+                    access_list,
+                    ..
+                }
+                | EthereumTransactionType::Eip7702 {
+                    max_priority_fee_per_gas,
+                    access_list,
+                    ..
+                } => (max_priority_fee_per_gas, access_list),
+                EthereumTransactionType::Legacy => {
+                    return Err(TransactionError::Message(
+                        "legacy transactions have no type".into(),
+                    ))
+                }
+            };
+            let mut transaction_rlp = RlpStream::new();
+            transaction_rlp.begin_unbounded_list();
+            transaction_rlp.append(&N::CHAIN_ID);
+            transaction_rlp.append(&parameters.nonce);
+            transaction_rlp.append(max_priority_fee_per_gas);
+            transaction_rlp.append(&parameters.gas_price.0);
+            transaction_rlp.append(&parameters.gas);
+            transaction_rlp.append(&encode_receiver(&parameters.receiver)?);
+            transaction_rlp.append(&parameters.amount.0);
+            transaction_rlp.append(&parameters.data);
+            append_access_list(&mut transaction_rlp, access_list)?;
+            if let EthereumTransactionType::Eip4844 {
+                max_fee_per_blob_gas,
+                blob_versioned_hashes,
+                ..
+            } = &parameters.transaction_type
+            {
+                transaction_rlp.append(max_fee_per_blob_gas);
+                transaction_rlp.begin_list(blob_versioned_hashes.len());
+                for hash in blob_versioned_hashes {
+                    transaction_rlp.append(&hash.as_slice());
+                }
+            }
+            if let EthereumTransactionType::Eip7702 {
+                authorization_list, ..
+            } = &parameters.transaction_type
+            {
+                transaction_rlp.begin_list(authorization_list.len());
+                for authorization in authorization_list {
+                    transaction_rlp.begin_list(6);
+                    transaction_rlp.append(&authorization.chain_id);
+                    transaction_rlp.append(&hex::decode(&authorization.address.to_string()[2..])?);
+                    transaction_rlp.append(&authorization.nonce);
+                    transaction_rlp.append(&authorization.y_parity);
+                    transaction_rlp.append(&authorization.r);
+                    transaction_rlp.append(&authorization.s);
+                }
+            }
+            if let Some(signature) = signature {
+                // the y parity of the signature
+                transaction_rlp.append(&trim_leading_zeros(&signature.v));
+                transaction_rlp.append(&trim_leading_zeros(&signature.r));
+                transaction_rlp.append(&trim_leading_zeros(&signature.s));
+            }
+            transaction_rlp.finalize_unbounded_list();
+            Ok([&[type_byte], &transaction_rlp.out()[..]].concat())
+        }
+
+        match (
+            self.parameters.transaction_type.type_byte(),
+            &self.signature,
+        ) {
+            (Some(type_byte), signature) => {
+                typed_transaction::<N>(type_byte, &self.parameters, signature.as_ref())
+            }
+            (None, Some(signature)) => Ok(signed_transaction(&self.parameters, signature)?
                 .out()
                 .to_vec()),
-            None => Ok(raw_transaction::<N>(&self.parameters)?.out().to_vec()),
+            (None, None) => Ok(raw_transaction::<N>(&self.parameters)?.out().to_vec()),
         }
     }
 
@@ -316,15 +616,238 @@ impl<N: EthereumNetwork> Transaction for EthereumTransaction<N> {
     }
 }
 
+impl<N: EthereumNetwork> MultiSignTransaction for EthereumTransaction<N> {
+    /// Returns the hash of the raw transaction, the only payload
+    fn signing_payloads(&self) -> Result<Vec<SigningPayload>, TransactionError> {
+        let raw = Self::new(&self.parameters)?;
+        Ok(vec![SigningPayload {
+            index: 0,
+            digest: raw.to_transaction_id()?.txid,
+        }])
+    }
+
+    /// Inserts the signature {r,s}, recovering its recovery id from
+    /// 'public_key'
+    fn apply_signature(
+        &mut self,
+        index: usize,
+        signature: Vec<u8>,
+        public_key: &Self::PublicKey,
+    ) -> Result<(), TransactionError> {
+        if index != 0 {
+            return Err(TransactionError::Message(format!(
+                "invalid signature index {}",
+                index
+            )));
+        }
+        let digest = Self::new(&self.parameters)?.to_transaction_id()?.txid;
+        let message = libsecp256k1::Message::parse_slice(&digest)?;
+        let parsed = libsecp256k1::Signature::parse_standard_slice(signature.get(..64).ok_or(
+            TransactionError::Message(format!("invalid signature length {}", signature.len())),
+        )?)?;
+        let recid = (0..2u8)
+            .find(|recid| {
+                let recovery_id = libsecp256k1::RecoveryId::parse(*recid).unwrap();
+                matches!(
+                    libsecp256k1::recover(&message, &parsed, &recovery_id),
+                    Ok(key) if key == public_key.to_secp256k1_public_key()
+                )
+            })
+            .ok_or(TransactionError::Message(
+                "the signature does not verify under the public key".into(),
+            ))?;
+        self.sign(signature[..64].to_vec(), recid)?;
+        Ok(())
+    }
+}
+
 impl<N: EthereumNetwork> EthereumTransaction<N> {
-    pub fn get_from(&self) -> EthereumAddress {
-        self.sender.clone().unwrap()
+    /// Inserts the signature {v,r,s} and returns the signed transaction. For
+    /// legacy transactions 'v' is the recovery id plus 35 plus twice the chain
+    /// id (EIP-155), or plus 27 for transactions without replay protection,
+    /// and for typed transactions the recovery id, the y parity of the
+    /// signature. The sender is recovered from the signature of the raw
+    /// transaction.
+    pub fn insert_signature(
+        &mut self,
+        v: u64,
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<Vec<u8>, TransactionError> {
+        let recid = self.recovery_id(v)?;
+        let recovery_id = libsecp256k1::RecoveryId::parse(recid)?;
+        if r.len() > 32 || s.len() > 32 {
+            return Err(TransactionError::Message(format!(
+                "invalid signature component lengths {} and {}",
+                r.len(),
+                s.len()
+            )));
+        }
+        let mut r = r.to_vec();
+        pad_zeros(&mut r, 32);
+        let mut s = s.to_vec();
+        pad_zeros(&mut s, 32);
+
+        let digest = match (&self.parameters.transaction_type, v) {
+            (EthereumTransactionType::Legacy, 27 | 28) => self.unprotected_signing_hash()?,
+            _ => Self::new(&self.parameters)?.to_transaction_id()?.txid,
+        };
+        let message = libsecp256k1::Message::parse_slice(&digest)?;
+        let signature = libsecp256k1::Signature::parse_standard_slice(&[&r[..], &s].concat())?;
+        let public_key = EthereumPublicKey::from_secp256k1_public_key(libsecp256k1::recover(
+            &message,
+            &signature,
+            &recovery_id,
+        )?);
+        self.sender = Some(public_key.to_address(&EthereumFormat::Standard)?);
+        self.signature = Some(EthereumTransactionSignature {
+            v: v.to_be_bytes().to_vec(),
+            r,
+            s,
+        });
+        self.to_bytes()
     }
 
-    pub fn get_to(&self) -> EthereumAddress {
+    /// Returns 35 plus twice the chain id, which EIP-155 adds to the recovery
+    /// id in the field 'v' of legacy signatures
+    fn eip155_offset() -> Result<u64, TransactionError> {
+        let chain_id = u64::from(N::CHAIN_ID);
+        chain_id
+            .checked_mul(2)
+            .and_then(|offset| offset.checked_add(35))
+            .ok_or(TransactionError::InvalidChainId(chain_id))
+    }
+
+    /// Returns the recovery id of the signature of field 'v'
+    fn recovery_id(&self, v: u64) -> Result<u8, TransactionError> {
+        let recid = match self.parameters.transaction_type {
+            EthereumTransactionType::Legacy if v == 27 || v == 28 => v - 27,
+            // the chain id of the error is the one 'v' was signed for
+            EthereumTransactionType::Legacy => v
+                .checked_sub(Self::eip155_offset()?)
+                .filter(|recid| *recid < 2)
+                .ok_or(TransactionError::InvalidChainId(v.saturating_sub(35) / 2))?,
+            _ if v > 1 => return Err(TransactionError::Message(format!("invalid y parity {}", v))),
+            _ => v,
+        };
+        Ok(recid as u8)
+    }
+
+    /// Returns the hash signed by legacy transactions without replay
+    /// protection, that of the RLP list of their first 6 fields
+    fn unprotected_signing_hash(&self) -> Result<Vec<u8>, TransactionError> {
+        let parameters = &self.parameters;
+        let mut transaction_rlp = RlpStream::new_list(6);
+        transaction_rlp.append(&parameters.nonce);
+        transaction_rlp.append(&parameters.gas_price.0);
+        transaction_rlp.append(&parameters.gas);
+        transaction_rlp.append(&encode_receiver(&parameters.receiver)?);
+        transaction_rlp.append(&parameters.amount.0);
+        transaction_rlp.append(&parameters.data);
+        Ok(keccak256(&transaction_rlp.out()).to_vec())
+    }
+
+    /// Returns the sender of the signed raw transaction 'transaction', legacy
+    /// or typed, recovered from its signature
+    pub fn recover_sender(transaction: &[u8]) -> Result<EthereumAddress, TransactionError> {
+        Self::from_bytes(transaction)?.get_from()
+    }
+
+    /// Returns a typed transaction given its type byte and the RLP list of its
+    /// fields
+    fn from_typed_bytes(type_byte: u8, transaction: &[u8]) -> Result<Self, TransactionError> {
+        // the number of the fields of the unsigned transaction
+        let unsigned = match type_byte {
+            0x02 => 9,
+            0x03 => 11,
+            0x04 => 10,
+            _ => {
+                return Err(TransactionError::Message(format!(
+                    "unsupported transaction type {}",
+                    type_byte
+                )))
+            }
+        };
+        let rlp = Rlp::new(transaction);
+        let count = rlp.item_count()?;
+        if count != unsigned && count != unsigned + 3 {
+            return Err(TransactionError::InvalidRlpLength(count));
+        }
+        let field =
+            |index: usize| -> Result<&[u8], TransactionError> { Ok(rlp.at(index)?.data()?) };
+
+        let chain_id = to_u256(field(0)?)?;
+        if chain_id != U256::from(N::CHAIN_ID) {
+            return Err(TransactionError::InvalidChainId(
+                u64::try_from(chain_id).unwrap_or(u64::MAX),
+            ));
+        }
+        let max_priority_fee_per_gas = to_u256(field(2)?)?;
+        let access_list = decode_access_list(&rlp.at(8)?)?;
+        let transaction_type = match type_byte {
+            0x02 => EthereumTransactionType::Eip1559 {
+                max_priority_fee_per_gas,
+                access_list,
+            },
+            0x03 => EthereumTransactionType::Eip4844 {
+                max_priority_fee_per_gas,
+                access_list,
+                max_fee_per_blob_gas: to_u256(field(9)?)?,
+                blob_versioned_hashes: decode_hashes(&rlp.at(10)?)?,
+            },
+            _ => EthereumTransactionType::Eip7702 {
+                max_priority_fee_per_gas,
+                access_list,
+                authorization_list: decode_authorization_list(&rlp.at(9)?)?,
+            },
+        };
+        let parameters = EthereumTransactionParameters {
+            receiver: decode_receiver(field(5)?)?,
+            amount: EthereumAmount::from_u256(to_u256(field(6)?)?),
+            gas: to_u256(field(4)?)?,
+            gas_price: EthereumAmount::from_u256(to_u256(field(3)?)?),
+            nonce: to_u256(field(1)?)?,
+            data: field(7)?.to_vec(),
+            transaction_type,
+        };
+
+        let mut transaction = Self::new(&parameters)?;
+        if count > unsigned {
+            let v = match field(unsigned)? {
+                [] => 0,
+                [parity] => u64::from(*parity),
+                v => return Err(TransactionError::InvalidRlpLength(v.len())),
+            };
+            transaction.insert_signature(v, field(unsigned + 1)?, field(unsigned + 2)?)?;
+        }
+        Ok(transaction)
+    }
+
+    /// Returns the sender of a signed transaction
+    pub fn get_from(&self) -> Result<EthereumAddress, TransactionError> {
+        self.sender
+            .clone()
+            .ok_or(TransactionError::MissingSignature)
+    }
+
+    /// Returns the receiver, `None` for a contract creation
+    pub fn get_to(&self) -> Option<EthereumAddress> {
         self.parameters.receiver.clone()
     }
 
+    /// Returns the address of the contract a signed contract creation
+    /// creates, that of its sender and its nonce, and `None` for other
+    /// transactions
+    pub fn get_contract_address(&self) -> Result<Option<EthereumAddress>, TransactionError> {
+        match self.parameters.receiver {
+            Some(_) => Ok(None),
+            None => Ok(Some(EthereumAddress::create(
+                &self.get_from()?,
+                self.parameters.nonce,
+            )?)),
+        }
+    }
+
     pub fn get_amount(&self) -> EthereumAmount {
         self.parameters.amount
     }
@@ -345,18 +868,40 @@ impl<N: EthereumNetwork> EthereumTransaction<N> {
         self.parameters.data.clone()
     }
 
-    pub fn get_r(&self) -> String {
-        hex::encode(self.signature.clone().unwrap().r)
+    pub fn get_r(&self) -> Result<String, TransactionError> {
+        Ok(hex::encode(&self.get_signature()?.r))
+    }
+
+    pub fn get_s(&self) -> Result<String, TransactionError> {
+        Ok(hex::encode(&self.get_signature()?.s))
     }
 
-    pub fn get_s(&self) -> String {
-        hex::encode(self.signature.clone().unwrap().s)
+    pub fn get_v(&self) -> Result<u64, TransactionError> {
+        let v = &self.get_signature()?.v;
+        let v: [u8; 8] = v[..]
+            .try_into()
+            .map_err(|_| TransactionError::InvalidRlpLength(v.len()))?;
+        Ok(u64::from_be_bytes(v))
     }
 
-    pub fn get_v(&self) -> u32 {
-        let v = self.signature.clone().unwrap().v;
-        let v: [u8; 4] = v.try_into().unwrap();
-        u32::from_be_bytes(v)
+    /// Returns the recovery id of the signature, the y parity of its point R
+    pub fn get_recovery_id(&self) -> Result<u8, TransactionError> {
+        self.recovery_id(self.get_v()?)
+    }
+
+    /// Returns true unless the transaction is a signed legacy transaction
+    /// without EIP-155 replay protection
+    pub fn is_replay_protected(&self) -> Result<bool, TransactionError> {
+        Ok(!matches!(
+            (&self.parameters.transaction_type, self.get_v()?),
+            (EthereumTransactionType::Legacy, 27 | 28)
+        ))
+    }
+
+    fn get_signature(&self) -> Result<&EthereumTransactionSignature, TransactionError> {
+        self.signature
+            .as_ref()
+            .ok_or(TransactionError::MissingSignature)
     }
 
     pub fn get_chain_id(&self) -> u32 {
@@ -368,11 +913,7 @@ impl<N: EthereumNetwork> FromStr for EthereumTransaction<N> {
     type Err = TransactionError;
 
     fn from_str(tx: &str) -> Result<Self, Self::Err> {
-        let tx = match &tx[..2] {
-            "0x" => &tx[2..],
-            _ => tx,
-        };
-        Self::from_bytes(&hex::decode(tx)?)
+        Self::from_bytes(&hex::decode(tx.strip_prefix("0x").unwrap_or(tx))?)
     }
 }
 
@@ -381,10 +922,573 @@ impl<N: EthereumNetwork> fmt::Display for EthereumTransaction<N> {
         write!(
             f,
             "0x{}",
-            &hex::encode(match self.to_bytes() {
+            hex::encode(match self.to_bytes() {
                 Ok(transaction) => transaction,
                 _ => return Err(fmt::Error),
             })
         )
     }
 }
+
+/// Builds Ethereum transfers of ether, to a single recipient
+#[derive(Debug, Clone)]
+pub struct EthereumTransactionBuilder<N: EthereumNetwork> {
+    sender: Option<EthereumAddress>,
+    recipient: Option<(EthereumAddress, EthereumAmount)>,
+    fee: Option<(U256, EthereumAmount)>,
+    nonce: U256,
+    data: Vec<u8>,
+    transaction_type: EthereumTransactionType,
+    _network: PhantomData<N>,
+}
+
+impl<N: EthereumNetwork> EthereumTransactionBuilder<N> {
+    /// Returns a builder of the transaction of the sender with 'nonce'
+    pub fn new(nonce: U256) -> Self {
+        Self {
+            sender: None,
+            recipient: None,
+            fee: None,
+            nonce,
+            data: vec![],
+            transaction_type: EthereumTransactionType::Legacy,
+            _network: PhantomData,
+        }
+    }
+
+    /// Returns the builder with the call data 'data'
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Returns the builder of transactions of type 'transaction_type', whose
+    /// fee gas price is the max fee per gas of dynamic fee transactions
+    pub fn with_transaction_type(mut self, transaction_type: EthereumTransactionType) -> Self {
+        self.transaction_type = transaction_type;
+        self
+    }
+}
+
+impl<N: EthereumNetwork> TransactionBuilder for EthereumTransactionBuilder<N> {
+    type Transaction = EthereumTransaction<N>;
+    type Amount = EthereumAmount;
+    /// The gas limit and the gas price
+    type Fee = (U256, EthereumAmount);
+
+    /// Sets the sender, which the nonce of the builder must belong to
+    fn set_sender(&mut self, sender: EthereumAddress) -> &mut Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    fn add_recipient(
+        &mut self,
+        recipient: EthereumAddress,
+        amount: EthereumAmount,
+    ) -> Result<&mut Self, TransactionError> {
+        if self.recipient.is_some() {
+            return Err(TransactionError::Message(
+                "ethereum transactions pay a single recipient".into(),
+            ));
+        }
+        self.recipient = Some((recipient, amount));
+        Ok(self)
+    }
+
+    fn set_fee(&mut self, fee: (U256, EthereumAmount)) -> &mut Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    fn build(&self) -> Result<EthereumTransaction<N>, TransactionError> {
+        if self.sender.is_none() {
+            return Err(TransactionError::InvalidInputs("sender".into()));
+        }
+        let (receiver, amount) = self
+            .recipient
+            .clone()
+            .ok_or(TransactionError::InvalidInputs("recipient".into()))?;
+        let (gas, gas_price) = self
+            .fee
+            .ok_or(TransactionError::InvalidInputs("fee".into()))?;
+        EthereumTransaction::new(&EthereumTransactionParameters {
+            receiver: Some(receiver),
+            amount,
+            gas,
+            gas_price,
+            nonce: self.nonce,
+            data: self.data.clone(),
+            transaction_type: self.transaction_type.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ethereum, Goerli};
+    use anychain_core::{Network, NetworkError};
+
+    /// A network with the largest chain id, whose EIP-155 v exceeds a u32
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct MaxChain;
+
+    impl Network for MaxChain {
+        const NAME: &'static str = "max chain";
+    }
+
+    impl EthereumNetwork for MaxChain {
+        const CHAIN_ID: u32 = u32::MAX;
+        const NETWORK_ID: u32 = u32::MAX;
+    }
+
+    impl FromStr for MaxChain {
+        type Err = NetworkError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                Self::NAME => Ok(Self),
+                _ => Err(NetworkError::InvalidNetwork(s.into())),
+            }
+        }
+    }
+
+    impl fmt::Display for MaxChain {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", Self::NAME)
+        }
+    }
+
+    fn parameters() -> EthereumTransactionParameters {
+        EthereumTransactionParameters {
+            receiver: Some(
+                EthereumAddress::from_str("0x3535353535353535353535353535353535353535").unwrap(),
+            ),
+            amount: EthereumAmount::from_u256(U256::from(1_000_000_000_000_000_000u64)),
+            gas: U256::from(21_000),
+            gas_price: EthereumAmount::from_u256(U256::from(20_000_000_000u64)),
+            nonce: U256::from(9),
+            data: vec![],
+            transaction_type: EthereumTransactionType::Legacy,
+        }
+    }
+
+    #[test]
+    fn test_multi_sign() {
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let public_key = EthereumPublicKey::from_secret_key(&secret_key);
+
+        let payloads = transaction.signing_payloads().unwrap();
+        assert_eq!(1, payloads.len());
+        let signed = transaction
+            .sign_with(|payload| {
+                let message = libsecp256k1::Message::parse_slice(&payload.digest)?;
+                let (signature, _) = libsecp256k1::sign(&message, &secret_key);
+                Ok((signature.serialize().to_vec(), public_key.clone()))
+            })
+            .unwrap();
+        // the EIP-155 example transaction
+        assert_eq!(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+            hex::encode(signed)
+        );
+        assert_eq!(
+            public_key.to_address(&EthereumFormat::Standard).unwrap(),
+            transaction.get_from().unwrap()
+        );
+        assert_eq!(payloads, transaction.signing_payloads().unwrap());
+    }
+
+    #[test]
+    fn test_insert_signature() {
+        // the EIP-155 example transaction, signed by the key 0x4646..46
+        let signed = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let r = hex::decode("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276")
+            .unwrap();
+        let s = hex::decode("67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83")
+            .unwrap();
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        assert_eq!(
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53",
+            hex::encode(transaction.to_transaction_id().unwrap().txid)
+        );
+        assert_eq!(
+            signed,
+            hex::encode(transaction.insert_signature(37, &r, &s).unwrap())
+        );
+        assert_eq!(37, transaction.get_v().unwrap());
+        assert_eq!(
+            "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F",
+            transaction.get_from().unwrap().to_string()
+        );
+        assert_eq!(
+            transaction,
+            EthereumTransaction::<Ethereum>::from_str(signed).unwrap()
+        );
+
+        // a v of another chain and an oversized component
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        assert!(matches!(
+            transaction.insert_signature(39, &r, &s),
+            Err(TransactionError::InvalidChainId(2))
+        ));
+        assert!(transaction
+            .insert_signature(37, &[&[1u8][..], &r].concat(), &s)
+            .is_err());
+        assert!(transaction.sign(r, 0).is_err());
+    }
+
+    #[test]
+    fn test_eip1559() {
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        let parameters = EthereumTransactionParameters {
+            amount: EthereumAmount::from_u256(U256::zero()),
+            gas_price: EthereumAmount::from_u256(U256::from(2)),
+            nonce: U256::zero(),
+            transaction_type: EthereumTransactionType::Eip1559 {
+                max_priority_fee_per_gas: U256::one(),
+                access_list: vec![AccessListItem {
+                    address: parameters().receiver.unwrap(),
+                    storage_keys: vec![key],
+                }],
+            },
+            ..parameters()
+        };
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters).unwrap();
+        let address = "35".repeat(20);
+        assert_eq!(
+            format!(
+                "02f8580180010282520894{}8080f838f794{}e1a0{}",
+                address,
+                address,
+                hex::encode(key)
+            ),
+            hex::encode(transaction.to_bytes().unwrap())
+        );
+        assert_eq!(
+            transaction,
+            EthereumTransaction::from_bytes(&transaction.to_bytes().unwrap()).unwrap()
+        );
+
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let public_key = EthereumPublicKey::from_secret_key(&secret_key);
+        let digest = transaction.signing_payloads().unwrap()[0].digest.clone();
+        let signed = transaction
+            .sign_with(|payload| {
+                let message = libsecp256k1::Message::parse_slice(&payload.digest)?;
+                let (signature, _) = libsecp256k1::sign(&message, &secret_key);
+                Ok((signature.serialize().to_vec(), public_key.clone()))
+            })
+            .unwrap();
+        assert_eq!(0x02, signed[0]);
+        assert!(transaction.get_v().unwrap() < 2);
+        assert_eq!(
+            public_key.to_address(&EthereumFormat::Standard).unwrap(),
+            transaction.get_from().unwrap()
+        );
+        // the txid commits to the signature, unlike the signing hash
+        assert_ne!(digest, transaction.to_transaction_id().unwrap().txid);
+        assert_eq!(
+            transaction,
+            EthereumTransaction::from_bytes(&signed).unwrap()
+        );
+        assert!(transaction
+            .clone()
+            .insert_signature(27, &[0x11; 32], &[0x22; 32])
+            .is_err());
+        assert!(matches!(
+            EthereumTransaction::<Goerli>::from_bytes(&signed),
+            Err(TransactionError::InvalidChainId(1))
+        ));
+    }
+
+    #[test]
+    fn test_eip4844() {
+        let mut hash = [0u8; 32];
+        hash[0] = VERSIONED_HASH_VERSION_KZG;
+        let parameters = EthereumTransactionParameters {
+            amount: EthereumAmount::from_u256(U256::zero()),
+            gas_price: EthereumAmount::from_u256(U256::from(2)),
+            nonce: U256::zero(),
+            transaction_type: EthereumTransactionType::Eip4844 {
+                max_priority_fee_per_gas: U256::one(),
+                access_list: vec![],
+                max_fee_per_blob_gas: U256::from(3),
+                blob_versioned_hashes: vec![hash],
+            },
+            ..parameters()
+        };
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters).unwrap();
+        assert_eq!(
+            format!(
+                "03f8420180010282520894{}8080c003e1a0{}",
+                "35".repeat(20),
+                hex::encode(hash)
+            ),
+            hex::encode(transaction.to_bytes().unwrap())
+        );
+
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let public_key = EthereumPublicKey::from_secret_key(&secret_key);
+        let signed = transaction
+            .sign_with(|payload| {
+                let message = libsecp256k1::Message::parse_slice(&payload.digest)?;
+                let (signature, _) = libsecp256k1::sign(&message, &secret_key);
+                Ok((signature.serialize().to_vec(), public_key.clone()))
+            })
+            .unwrap();
+        assert_eq!(0x03, signed[0]);
+        assert_eq!(
+            transaction,
+            EthereumTransaction::from_bytes(&signed).unwrap()
+        );
+        assert_eq!(
+            public_key.to_address(&EthereumFormat::Standard).unwrap(),
+            transaction.get_from().unwrap()
+        );
+
+        // blob transactions carry at least one blob of a known version
+        for hashes in [vec![], vec![[0u8; 32]]] {
+            let parameters = EthereumTransactionParameters {
+                transaction_type: EthereumTransactionType::Eip4844 {
+                    max_priority_fee_per_gas: U256::one(),
+                    access_list: vec![],
+                    max_fee_per_blob_gas: U256::from(3),
+                    blob_versioned_hashes: hashes,
+                },
+                ..parameters.clone()
+            };
+            assert!(EthereumTransaction::<Ethereum>::new(&parameters).is_err());
+        }
+        assert!(
+            EthereumTransaction::<Ethereum>::from_bytes(&[&[0x05], &signed[1..]].concat()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_eip7702() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let public_key = EthereumPublicKey::from_secret_key(&secret_key);
+        let sign = |digest: &[u8]| {
+            let message = libsecp256k1::Message::parse_slice(digest).unwrap();
+            let (signature, recid) = libsecp256k1::sign(&message, &secret_key);
+            (signature.serialize(), recid.serialize())
+        };
+
+        let mut authorization = Authorization::new(U256::one(), parameters().receiver.unwrap(), 0);
+        let hash = authorization.signing_hash().unwrap();
+        let preimage = hex::decode(format!("05d70194{}80", "35".repeat(20))).unwrap();
+        assert_eq!(keccak256(&preimage), hash);
+        let (rs, recid) = sign(&hash);
+        assert!(authorization.insert_signature(&rs, 2).is_err());
+        authorization.insert_signature(&rs, recid).unwrap();
+        assert_eq!(
+            public_key.to_address(&EthereumFormat::Standard).unwrap(),
+            authorization.authority().unwrap()
+        );
+
+        let parameters = EthereumTransactionParameters {
+            transaction_type: EthereumTransactionType::Eip7702 {
+                max_priority_fee_per_gas: U256::one(),
+                access_list: vec![],
+                authorization_list: vec![authorization],
+            },
+            ..parameters()
+        };
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters).unwrap();
+        let (rs, recid) = sign(&transaction.signing_payloads().unwrap()[0].digest);
+        let signed = transaction.sign(rs.to_vec(), recid).unwrap();
+        assert_eq!(0x04, signed[0]);
+        assert_eq!(
+            transaction,
+            EthereumTransaction::from_bytes(&signed).unwrap()
+        );
+
+        let parameters = EthereumTransactionParameters {
+            transaction_type: EthereumTransactionType::Eip7702 {
+                max_priority_fee_per_gas: U256::one(),
+                access_list: vec![],
+                authorization_list: vec![],
+            },
+            ..parameters
+        };
+        assert!(EthereumTransaction::<Ethereum>::new(&parameters).is_err());
+    }
+
+    #[test]
+    fn test_malformed() {
+        let transaction = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        assert!(matches!(
+            transaction.get_from(),
+            Err(TransactionError::MissingSignature)
+        ));
+        assert!(transaction.get_v().is_err());
+
+        let encode = |nonce: &[u8], v: &[u8]| {
+            let mut rlp = RlpStream::new_list(9);
+            rlp.append(&nonce);
+            rlp.append(&20_000_000_000u64);
+            rlp.append(&21_000u64);
+            rlp.append(&[0x35u8; 20].as_slice());
+            rlp.append(&0u8);
+            rlp.append_empty_data();
+            rlp.append(&v);
+            rlp.append(&[0x11u8; 32].as_slice());
+            rlp.append(&[0x22u8; 32].as_slice());
+            rlp.out().to_vec()
+        };
+        assert!(EthereumTransaction::<Ethereum>::from_bytes(&encode(&[0x01; 33], &[])).is_err());
+        // v below the EIP-155 offset of the chain
+        assert!(matches!(
+            EthereumTransaction::<Ethereum>::from_bytes(&encode(&[0x09], &[0x01])),
+            Err(TransactionError::InvalidChainId(_))
+        ));
+        assert!(EthereumTransaction::<Ethereum>::from_bytes(&[0xf8, 0x01]).is_err());
+        for invalid in ["", "0", "0x", "é0"] {
+            assert!(EthereumTransaction::<Ethereum>::from_str(invalid).is_err());
+        }
+
+        let data = encode_transfer(
+            "transfer",
+            &parameters().receiver.unwrap(),
+            U256::from(1_000_000u64),
+        )
+        .unwrap();
+        assert_eq!("a9059cbb", hex::encode(&data[..4]));
+    }
+
+    #[test]
+    fn test_contract_creation() {
+        let mut parameters = parameters();
+        parameters.receiver = None;
+        parameters.amount = EthereumAmount::from_u256(U256::zero());
+        // init code returning an empty contract
+        parameters.data = hex::decode("600080f3").unwrap();
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters).unwrap();
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let message =
+            libsecp256k1::Message::parse_slice(&transaction.to_transaction_id().unwrap().txid)
+                .unwrap();
+        let (signature, recid) = libsecp256k1::sign(&message, &secret_key);
+        let signed = transaction
+            .sign(signature.serialize().to_vec(), recid.serialize())
+            .unwrap();
+        // the empty receiver field follows the gas limit
+        assert_eq!("825208808084600080f3", hex::encode(&signed[9..19]));
+
+        let transaction = EthereumTransaction::<Ethereum>::from_bytes(&signed).unwrap();
+        let sender = transaction.get_from().unwrap();
+        assert_eq!(None, transaction.get_to());
+        assert_eq!(
+            Some(EthereumAddress::create(&sender, U256::from(9)).unwrap()),
+            transaction.get_contract_address().unwrap()
+        );
+        assert_eq!(
+            None,
+            EthereumTransaction::<Ethereum>::new(&self::parameters())
+                .unwrap()
+                .get_contract_address()
+                .unwrap()
+        );
+
+        parameters.transaction_type = EthereumTransactionType::Eip7702 {
+            max_priority_fee_per_gas: U256::one(),
+            access_list: vec![],
+            authorization_list: vec![Authorization::new(U256::one(), sender, 0)],
+        };
+        assert!(EthereumTransaction::<Ethereum>::new(&parameters).is_err());
+    }
+
+    #[test]
+    fn test_recover_sender() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[0x46; 32]).unwrap();
+        let sender = EthereumPublicKey::from_secret_key(&secret_key)
+            .to_address(&EthereumFormat::Standard)
+            .unwrap();
+        let sign = |digest: &[u8]| {
+            let message = libsecp256k1::Message::parse_slice(digest).unwrap();
+            let (signature, recid) = libsecp256k1::sign(&message, &secret_key);
+            (signature.serialize(), recid.serialize())
+        };
+
+        // the EIP-155 example transaction
+        let signed = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let signed = hex::decode(signed).unwrap();
+        assert_eq!(
+            sender,
+            EthereumTransaction::<Ethereum>::recover_sender(&signed).unwrap()
+        );
+        let transaction = EthereumTransaction::<Ethereum>::from_bytes(&signed).unwrap();
+        assert_eq!(0, transaction.get_recovery_id().unwrap());
+        assert!(transaction.is_replay_protected().unwrap());
+        // another chain and an unsigned transaction
+        assert!(EthereumTransaction::<Goerli>::recover_sender(&signed).is_err());
+        let unsigned = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        assert!(
+            EthereumTransaction::<Ethereum>::recover_sender(&unsigned.to_bytes().unwrap()).is_err()
+        );
+
+        // the v of the largest chain id does not fit in 32 bits
+        let mut transaction = EthereumTransaction::<MaxChain>::new(&parameters()).unwrap();
+        let (rs, recid) = sign(&transaction.to_transaction_id().unwrap().txid);
+        let signed = transaction.sign(rs.to_vec(), recid).unwrap();
+        let transaction = EthereumTransaction::<MaxChain>::from_bytes(&signed).unwrap();
+        assert_eq!(
+            u64::from(u32::MAX) * 2 + 35 + u64::from(recid),
+            transaction.get_v().unwrap()
+        );
+        assert_eq!(sender, transaction.get_from().unwrap());
+        assert!(matches!(
+            EthereumTransaction::<Ethereum>::recover_sender(&signed),
+            Err(TransactionError::InvalidChainId(chain_id)) if chain_id == u64::from(u32::MAX)
+        ));
+
+        // a legacy transaction without replay protection, whose v is 27 or 28
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters()).unwrap();
+        let (rs, recid) = sign(&transaction.unprotected_signing_hash().unwrap());
+        let signed = transaction
+            .insert_signature(27 + u64::from(recid), &rs[..32], &rs[32..])
+            .unwrap();
+        let transaction = EthereumTransaction::<Ethereum>::from_bytes(&signed).unwrap();
+        assert_eq!(sender, transaction.get_from().unwrap());
+        assert_eq!(recid, transaction.get_recovery_id().unwrap());
+        assert!(!transaction.is_replay_protected().unwrap());
+
+        // a typed transaction
+        let mut parameters = parameters();
+        parameters.transaction_type = EthereumTransactionType::Eip1559 {
+            max_priority_fee_per_gas: U256::one(),
+            access_list: vec![],
+        };
+        let mut transaction = EthereumTransaction::<Ethereum>::new(&parameters).unwrap();
+        let (rs, recid) = sign(&transaction.to_transaction_id().unwrap().txid);
+        let signed = transaction.sign(rs.to_vec(), recid).unwrap();
+        assert_eq!(
+            sender,
+            EthereumTransaction::<Ethereum>::recover_sender(&signed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let parameters = parameters();
+        let mut builder = EthereumTransactionBuilder::<Ethereum>::new(parameters.nonce);
+        builder
+            .set_sender(
+                EthereumAddress::from_str("0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F").unwrap(),
+            )
+            .add_recipient(parameters.receiver.clone().unwrap(), parameters.amount)
+            .unwrap()
+            .set_fee((parameters.gas, parameters.gas_price));
+        assert_eq!(
+            EthereumTransaction::new(&parameters).unwrap(),
+            builder.build().unwrap()
+        );
+        assert!(builder
+            .add_recipient(parameters.receiver.unwrap(), parameters.amount)
+            .is_err());
+    }
+}
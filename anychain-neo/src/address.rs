@@ -1,7 +1,7 @@
 use crate::{NeoFormat, NeoPublicKey};
 use anychain_core::{
     crypto::{checksum, hash160},
-    Address, AddressError, PublicKey,
+    Address, AddressError, AnyNetwork, PublicKey,
 };
 use base58::{FromBase58, ToBase58};
 use std::{fmt::Display, str::FromStr};
@@ -13,6 +13,7 @@ impl Address for NeoAddress {
     type Format = NeoFormat;
     type SecretKey = p256::SecretKey;
     type PublicKey = NeoPublicKey;
+    type Network = AnyNetwork;
 
     fn from_secret_key(
         secret_key: &Self::SecretKey,
@@ -47,13 +48,30 @@ impl Address for NeoAddress {
 
         Ok(Self(res.to_base58()))
     }
+
+    fn network(&self) -> AnyNetwork {
+        AnyNetwork
+    }
+
+    fn format(&self) -> NeoFormat {
+        NeoFormat::Standard
+    }
 }
 
 impl NeoAddress {
-    pub fn to_script_hash(&self) -> Vec<u8> {
-        let bytes = self.0.as_str().from_base58().unwrap();
+    /// Returns the 20 byte script hash of this address, failing if it is not
+    /// the base58 encoding of 25 bytes
+    pub fn to_script_hash(&self) -> Result<Vec<u8>, AddressError> {
+        let bytes = self
+            .0
+            .as_str()
+            .from_base58()
+            .map_err(|_| AddressError::InvalidAddress(self.0.clone()))?;
+        if bytes.len() != 25 {
+            return Err(AddressError::InvalidByteLength(bytes.len()));
+        }
         // strip the version byte and the checksum
-        bytes[1..21].to_vec()
+        Ok(bytes[1..21].to_vec())
     }
 }
 
@@ -61,7 +79,12 @@ impl FromStr for NeoAddress {
     type Err = AddressError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = s.from_base58()?;
+        let bytes = s
+            .from_base58()
+            .map_err(|_| AddressError::InvalidAddress(s.to_string()))?;
+        if bytes.len() != 25 {
+            return Err(AddressError::InvalidByteLength(bytes.len()));
+        }
         let checksum_provided = bytes[21..].to_vec();
         let checksum_gen = checksum(&bytes[..21])[..4].to_vec();
 
@@ -100,7 +123,7 @@ mod tests {
         assert!(result.is_ok());
 
         let address = result.unwrap();
-        assert_eq!(address.to_script_hash().len(), 20);
+        assert_eq!(address.to_script_hash().unwrap().len(), 20);
     }
 
     #[test]
@@ -117,7 +140,7 @@ mod tests {
         assert!(result.is_ok());
 
         let address = result.unwrap();
-        assert_eq!(address.to_script_hash().len(), 20);
+        assert_eq!(address.to_script_hash().unwrap().len(), 20);
     }
 
     #[test]
@@ -129,6 +152,12 @@ mod tests {
 
         let parsed_address = address.unwrap();
         assert_eq!(parsed_address.to_string(), address_str);
+        assert_eq!(parsed_address.network(), AnyNetwork);
+
+        assert!(NeoAddress::from_str("NVEqR4e73afGKpVBz").is_err());
+        assert!(NeoAddress("NVEqR4e73afGKpVBz".to_string())
+            .to_script_hash()
+            .is_err());
     }
 
     #[test]
@@ -1,5 +1,6 @@
 use crate::{NeoAddress, NeoFormat};
-use anychain_core::{hex, Address, AddressError, PublicKey, PublicKeyError};
+use anychain_core::{Address, AddressError, PublicKey, PublicKeyError};
+use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
 use std::{fmt::Display, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +19,19 @@ impl PublicKey for NeoPublicKey {
     fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError> {
         Self::Address::from_public_key(self, format)
     }
+
+    /// Returns true if 'signature', the 64 byte r || s, is a P-256 ECDSA
+    /// signature of the 32 byte 'message_hash' by this public key.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError> {
+        if message_hash.len() != 32 {
+            return Err(PublicKeyError::InvalidByteLength(message_hash.len()));
+        }
+        let signature = Signature::from_slice(signature)
+            .map_err(|_| PublicKeyError::InvalidByteLength(signature.len()))?;
+        Ok(VerifyingKey::from(&self.0)
+            .verify_prehash(message_hash, &signature)
+            .is_ok())
+    }
 }
 
 impl NeoPublicKey {
@@ -31,7 +45,8 @@ impl FromStr for NeoPublicKey {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let bin = hex::decode(s)?;
-        let public_key = p256::PublicKey::from_sec1_bytes(&bin).unwrap();
+        let public_key = p256::PublicKey::from_sec1_bytes(&bin)
+            .map_err(|error| PublicKeyError::Crate("p256", format!("{:?}", error)))?;
         Ok(NeoPublicKey(public_key))
     }
 }
@@ -65,5 +80,6 @@ mod test {
 
         let pb = NeoPublicKey::from_str(public_key);
         assert!(pb.is_ok());
+        assert!(NeoPublicKey::from_str(&public_key[..64]).is_err());
     }
 }
@@ -1,5 +1,5 @@
 use crate::{NeoAddress, NeoFormat, NeoPublicKey};
-use anychain_core::{crypto::sha256, hex, Transaction, TransactionError, TransactionId};
+use anychain_core::{crypto::sha256, Transaction, TransactionError, TransactionId};
 use std::fmt::{Display, Error};
 
 #[derive(Clone)]
@@ -16,28 +16,34 @@ pub struct TxOut {
 }
 
 impl TxIn {
-    fn serialize(&self) -> Vec<u8> {
+    fn serialize(&self) -> Result<Vec<u8>, TransactionError> {
         if self.prev_hash.len() != 32 {
-            panic!("Invalid prev_hash length");
+            return Err(TransactionError::Message(format!(
+                "Invalid prev_hash length {}",
+                self.prev_hash.len()
+            )));
         }
         let prevhash = self.prev_hash.clone();
         let index = self.index.to_le_bytes().to_vec();
-        [prevhash, index].concat()
+        Ok([prevhash, index].concat())
     }
 }
 
 impl TxOut {
-    fn serialize(&self) -> Vec<u8> {
+    fn serialize(&self) -> Result<Vec<u8>, TransactionError> {
         if self.asset_id.len() != 32 {
-            panic!("Invalid prev_hash length");
+            return Err(TransactionError::Message(format!(
+                "Invalid asset_id length {}",
+                self.asset_id.len()
+            )));
         }
         let mut asset_id = self.asset_id.clone();
         asset_id.reverse();
         let value = self.value.to_le_bytes().to_vec();
         let address = NeoAddress(self.address.clone());
-        let address = address.to_script_hash();
+        let address = address.to_script_hash()?;
 
-        [asset_id, value, address].concat()
+        Ok([asset_id, value, address].concat())
     }
 }
 
@@ -48,7 +54,7 @@ pub struct NeoTransactionParameters {
 }
 
 impl NeoTransactionParameters {
-    fn serialize(&self) -> Vec<u8> {
+    fn serialize(&self) -> Result<Vec<u8>, TransactionError> {
         let mut ret = vec![0u8; 0];
         ret.push(0x80); // contract type byte
         ret.push(0x00); // version byte
@@ -58,16 +64,16 @@ impl NeoTransactionParameters {
         ret.push(self.txins.len() as u8);
 
         for txin in &self.txins {
-            ret.extend(txin.serialize());
+            ret.extend(txin.serialize()?);
         }
 
         ret.push(self.txouts.len() as u8);
 
         for txout in &self.txouts {
-            ret.extend(txout.serialize());
+            ret.extend(txout.serialize()?);
         }
 
-        ret
+        Ok(ret)
     }
 }
 
@@ -106,8 +112,8 @@ pub struct NeoTransactionId {
 }
 
 impl Display for NeoTransactionId {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(&self.txid))
     }
 }
 
@@ -128,7 +134,7 @@ impl Transaction for NeoTransaction {
     }
 
     fn sign(&mut self, rs_pk_s: Vec<u8>, _recid: u8) -> Result<Vec<u8>, TransactionError> {
-        if rs_pk_s.len() % 97 != 0 {
+        if !rs_pk_s.len().is_multiple_of(97) {
             return Err(TransactionError::Message(format!(
                 "Invalid signauture-public-key tuple length {}",
                 rs_pk_s.len()
@@ -162,7 +168,7 @@ impl Transaction for NeoTransaction {
     }
 
     fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
-        let mut stream = self.params.serialize();
+        let mut stream = self.params.serialize()?;
         if let Some(sigs) = &self.signatures {
             stream.push(sigs.len() as u8);
             for sig in sigs {
@@ -173,7 +179,9 @@ impl Transaction for NeoTransaction {
     }
 
     fn from_bytes(_tx: &[u8]) -> Result<Self, TransactionError> {
-        todo!()
+        Err(TransactionError::Message(
+            "parsing Neo transactions is not supported".into(),
+        ))
     }
 
     fn to_transaction_id(&self) -> Result<Self::TransactionId, TransactionError> {
@@ -189,7 +197,7 @@ impl Display for NeoTransaction {
         write!(
             f,
             "{}",
-            &hex::encode(match self.to_bytes() {
+            hex::encode(match self.to_bytes() {
                 Ok(transaction) => transaction,
                 _ => return Err(Error),
             })
@@ -4,7 +4,7 @@ use std::{fmt::Display, str::FromStr};
 use crate::{RippleFormat, RipplePublicKey};
 use anychain_core::{
     crypto::{checksum, hash160},
-    libsecp256k1, Address, AddressError, PublicKey,
+    libsecp256k1, Address, AddressError, AnyNetwork, PublicKey,
 };
 
 // fn map_gen(from: &str, to: &str) {
@@ -20,7 +20,7 @@ use anychain_core::{
 //     println!("{:?}", table);
 // }
 
-/// You can run this test function to generate the 2 maps below
+// You can run this test function to generate the 2 maps below
 // #[test]
 // fn gen_map() {
 //     let ripple_alphabet: &str = "rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
@@ -50,26 +50,24 @@ static XRP_2_BTC_BS58_MAP: [i8; 128] = [
     49, 51, 116, 117, 118, 65, 120, 121, 122, -1, -1, -1, -1, -1,
 ];
 
+/// Map the string 's' with 'map', failing on characters outside the alphabet
+fn map_bs58(s: &str, map: &[i8; 128]) -> Result<String, AddressError> {
+    s.bytes()
+        .map(|b| match map.get(b as usize) {
+            Some(c) if *c >= 0 => Ok(*c as u8 as char),
+            _ => Err(AddressError::InvalidAddress(s.to_string())),
+        })
+        .collect()
+}
+
 /// Map the string in bitcoin base58 format to ripple base58 format
 fn to_xrp_bs58(s: &str) -> Result<String, AddressError> {
-    let to: Vec<u8> = s
-        .as_bytes()
-        .iter()
-        .map(|b| BTC_2_XRP_BS58_MAP[*b as usize] as u8)
-        .collect();
-
-    Ok(String::from_utf8(to)?)
+    map_bs58(s, &BTC_2_XRP_BS58_MAP)
 }
 
 /// Map the string in ripple base58 format to bitcoin base58 format
 fn to_btc_bs58(s: &str) -> Result<String, AddressError> {
-    let to: Vec<u8> = s
-        .as_bytes()
-        .iter()
-        .map(|b| XRP_2_BTC_BS58_MAP[*b as usize] as u8)
-        .collect();
-
-    Ok(String::from_utf8(to)?)
+    map_bs58(s, &XRP_2_BTC_BS58_MAP)
 }
 
 /// Represents a Ripple address
@@ -79,6 +77,7 @@ pub struct RippleAddress(String);
 impl Address for RippleAddress {
     type SecretKey = libsecp256k1::SecretKey;
     type PublicKey = RipplePublicKey;
+    type Network = AnyNetwork;
     type Format = RippleFormat;
 
     fn from_secret_key(
@@ -100,13 +99,23 @@ impl Address for RippleAddress {
 
         Ok(RippleAddress(to_xrp_bs58(&data.to_base58())?))
     }
+
+    fn network(&self) -> AnyNetwork {
+        AnyNetwork
+    }
+
+    fn format(&self) -> RippleFormat {
+        RippleFormat::Standard
+    }
 }
 
 impl FromStr for RippleAddress {
     type Err = AddressError;
     fn from_str(addr: &str) -> Result<Self, Self::Err> {
         let s = to_btc_bs58(addr)?;
-        let data = s.from_base58()?;
+        let data = s
+            .from_base58()
+            .map_err(|_| AddressError::InvalidAddress(addr.to_string()))?;
         if data.len() != 25 {
             return Err(AddressError::InvalidByteLength(s.len()));
         }
@@ -146,7 +155,9 @@ impl RippleAddress {
     pub fn to_hash160(&self) -> Result<[u8; 20], AddressError> {
         let _ = Self::from_str(&self.0)?;
         let btc_bs58 = to_btc_bs58(&self.0)?;
-        let bytes = btc_bs58.from_base58()?;
+        let bytes = btc_bs58
+            .from_base58()
+            .map_err(|_| AddressError::InvalidAddress(self.0.clone()))?;
 
         let mut ret = [0u8; 20];
 
@@ -161,7 +172,7 @@ impl RippleAddress {
 mod tests {
     use std::str::FromStr;
 
-    use super::{libsecp256k1, Address, RippleAddress, RippleFormat};
+    use super::{libsecp256k1, Address, AnyNetwork, RippleAddress, RippleFormat};
 
     #[test]
     fn test_from_secret_key() {
@@ -187,6 +198,15 @@ mod tests {
         addrs.iter().for_each(|&addr| {
             let addr = RippleAddress::from_str(addr).unwrap();
             println!("address = {}", addr);
+            assert_eq!(AnyNetwork, addr.network());
         });
+
+        // characters outside the ripple alphabet
+        for addr in [
+            "r0J6HEKFe8T2mkZQqzuGbFEmE8SKtadxd8",
+            "rJ6HEKFe8T2mkZQqzuGbFEmE8SKtadxd8é",
+        ] {
+            assert!(RippleAddress::from_str(addr).is_err());
+        }
     }
 }
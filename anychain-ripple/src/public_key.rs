@@ -1,5 +1,6 @@
 use crate::{RippleAddress, RippleFormat};
-use anychain_core::{hex, libsecp256k1, Address, AddressError, PublicKey, PublicKeyError};
+use anychain_core::crypto::secp256k1_verify;
+use anychain_core::{libsecp256k1, Address, AddressError, PublicKey, PublicKeyError};
 use core::{fmt, str::FromStr};
 
 /// Represents a Ripple public key
@@ -28,6 +29,13 @@ impl PublicKey for RipplePublicKey {
     fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError> {
         Self::Address::from_public_key(self, format)
     }
+
+    /// Returns true if 'signature', the 64 byte r || s with an optional
+    /// recovery id, is a signature of the 32 byte 'message_hash' by this
+    /// public key.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError> {
+        secp256k1_verify(&self.public_key, message_hash, signature)
+    }
 }
 
 impl RipplePublicKey {
@@ -4,7 +4,6 @@ use std::{fmt, str::FromStr};
 use crate::{RippleAddress, RippleFormat, RipplePublicKey};
 use anychain_core::{
     crypto::{hash160, sha512},
-    hex,
     libsecp256k1::Signature,
     no_std::io::Read,
     Transaction, TransactionError, TransactionId,
@@ -105,7 +104,8 @@ impl FromStr for RippleTransaction {
 
 impl fmt::Display for RippleTransaction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(self.to_bytes().unwrap()))
+        let bytes = self.to_bytes().map_err(|_| fmt::Error)?;
+        write!(f, "{}", hex::encode(bytes))
     }
 }
 
@@ -378,7 +378,10 @@ impl RippleTransaction {
     }
 
     pub fn set_public_key(&mut self, pk: Vec<u8>) -> Result<(), TransactionError> {
-        self.params.public_key = pk.try_into().unwrap();
+        let len = pk.len();
+        self.params.public_key = pk
+            .try_into()
+            .map_err(|_| TransactionError::Message(format!("invalid public key length {}", len)))?;
         Ok(())
     }
 }
@@ -1,8 +1,14 @@
 use crate::TronAddress;
-use anychain_core::{ethereum_types::U256, utilities::crypto::keccak256};
+use anychain_core::{utilities::crypto::keccak256, AddressError, TransactionError};
+use ethabi::ethereum_types::U256;
 use ethabi::{encode, Token};
 use std::str::FromStr;
 
+/// The selector of `transfer(address,uint256)`
+pub const TRC20_TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// The selector of `transferFrom(address,address,uint256)`
+pub const TRC20_TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
 /// Represents a parameter that's fed to a
 /// function of an on-chain contract
 pub struct Param {
@@ -51,25 +57,56 @@ pub fn contract_function_call(function_name: &str, params: &[Param]) -> Vec<u8>
     data
 }
 
-pub fn trc20_transfer(address: &str, amount: &str) -> Vec<u8> {
-    let address = TronAddress::from_str(address).unwrap();
-    let amount = U256::from_dec_str(amount).unwrap();
+/// Returns the address of the argument word 'word', whose 20 address bytes
+/// follow 12 zeros, as encoded by TronWeb, or 11 zeros and the 0x41 prefix,
+/// as encoded by this crate
+pub fn decode_address(word: &[u8]) -> Result<TronAddress, AddressError> {
+    if word.len() != 32 || word[..11].iter().any(|byte| *byte != 0) {
+        return Err(AddressError::InvalidAddress(hex::encode(word)));
+    }
+    let mut address = word[11..].to_vec();
+    address[0] = 0x41;
+    match word[11] {
+        0 | 0x41 => TronAddress::try_from(address),
+        _ => Err(AddressError::InvalidAddress(hex::encode(word))),
+    }
+}
 
-    contract_function_call("transfer", &[Param::from(&address), Param::from(amount)])
+/// Returns the calldata of the TRC-20 `transfer` of 'amount' tokens to
+/// 'address', failing on invalid addresses and amounts
+pub fn trc20_transfer(address: &str, amount: &str) -> Result<Vec<u8>, TransactionError> {
+    let (address, amount) = parse_address_amount(address, amount)?;
+    Ok(contract_function_call(
+        "transfer",
+        &[Param::from(&address), Param::from(amount)],
+    ))
 }
 
-pub fn trc20_approve(address: &str, amount: &str) -> Vec<u8> {
-    let address = TronAddress::from_str(address).unwrap();
-    let amount = U256::from_dec_str(amount).unwrap();
+/// Returns the calldata of the TRC-20 `approve` of 'amount' tokens to
+/// 'address', failing on invalid addresses and amounts
+pub fn trc20_approve(address: &str, amount: &str) -> Result<Vec<u8>, TransactionError> {
+    let (address, amount) = parse_address_amount(address, amount)?;
+    Ok(contract_function_call(
+        "approve",
+        &[Param::from(&address), Param::from(amount)],
+    ))
+}
 
-    contract_function_call("approve", &[Param::from(&address), Param::from(amount)])
+fn parse_address_amount(
+    address: &str,
+    amount: &str,
+) -> Result<(TronAddress, U256), TransactionError> {
+    let address = TronAddress::from_str(address)?;
+    let amount = U256::from_dec_str(amount)
+        .map_err(|e| TransactionError::Message(format!("invalid amount {}: {:?}", amount, e)))?;
+    Ok((address, amount))
 }
 
 #[cfg(test)]
 mod test_mod {
     use std::str::FromStr;
 
-    use super::{contract_function_call, Param};
+    use super::{contract_function_call, trc20_approve, trc20_transfer, Param};
     use crate::TronAddress;
     use ethabi::ethereum_types::U256;
 
@@ -85,7 +122,17 @@ mod test_mod {
             "a9059cbb000000000000000000000041436d74fc1577266b7\
              290b85801145d9c5287e19400000000000000000000000000\
              0000000000000000000001158e460913d00000",
-            hex::encode(call_data)
-        )
+            hex::encode(&call_data)
+        );
+        assert_eq!(
+            call_data,
+            trc20_transfer("TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvXr", "20000000000000000000").unwrap()
+        );
+        assert_eq!(
+            "095ea7b3",
+            hex::encode(&trc20_approve("TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvXr", "1").unwrap()[..4])
+        );
+        assert!(trc20_transfer("TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvX", "1").is_err());
+        assert!(trc20_approve("TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvXr", "-1").is_err());
     }
 }
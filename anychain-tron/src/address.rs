@@ -1,11 +1,11 @@
 use crate::{TronFormat, TronPublicKey};
-use anychain_core::{libsecp256k1, Address, AddressError, PublicKey};
-use base58::{FromBase58, ToBase58};
+use anychain_core::{
+    crypto::keccak256, libsecp256k1, Address, AddressError, AnyNetwork, PublicKey,
+};
 use ethabi::Token;
 use hex::FromHex;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
-use sha3::Keccak256;
 use std::fmt;
 use std::str::FromStr;
 
@@ -18,6 +18,7 @@ impl Address for TronAddress {
     type SecretKey = libsecp256k1::SecretKey;
     type Format = TronFormat;
     type PublicKey = TronPublicKey;
+    type Network = AnyNetwork;
 
     fn from_secret_key(
         secret_key: &Self::SecretKey,
@@ -30,15 +31,20 @@ impl Address for TronAddress {
         public_key: &Self::PublicKey,
         _format: &Self::Format,
     ) -> Result<Self, AddressError> {
-        let mut hasher = Keccak256::new();
-
-        hasher.update(&public_key.to_secp256k1_public_key().serialize()[1..]);
-        let digest = hasher.finalize();
+        let digest = keccak256(&public_key.to_secp256k1_public_key().serialize()[1..]);
         let mut raw = [ADDRESS_TYPE_PREFIX; 21];
         raw[1..21].copy_from_slice(&digest[digest.len() - 20..]);
 
         Ok(TronAddress(raw))
     }
+
+    fn network(&self) -> AnyNetwork {
+        AnyNetwork
+    }
+
+    fn format(&self) -> TronFormat {
+        TronFormat::Standard
+    }
 }
 
 impl TronAddress {
@@ -146,7 +152,7 @@ impl FromStr for TronAddress {
     {
         if s.len() == 34 {
             b58decode_check(s).and_then(TronAddress::try_from)
-        } else if s.len() == 42 && s[..2] == hex::encode([ADDRESS_TYPE_PREFIX]) {
+        } else if s.len() == 42 && s.starts_with(&hex::encode([ADDRESS_TYPE_PREFIX])) {
             Vec::from_hex(s)
                 .map_err(|_| AddressError::InvalidAddress("InvalidAddress".to_string()))
                 .and_then(TronAddress::try_from)
@@ -157,7 +163,6 @@ impl FromStr for TronAddress {
         } else if s == "_" || s == "0x0" || s == "/0" {
             "410000000000000000000000000000000000000000".parse()
         } else {
-            eprintln!("len={} prefix={:x}", s.len(), s.as_bytes()[0]);
             Err(AddressError::InvalidAddress("Invalid length".to_string()))
         }
     }
@@ -182,13 +187,13 @@ pub fn b58encode_check<T: AsRef<[u8]>>(raw: T) -> String {
 
     let mut raw = raw.as_ref().to_owned();
     raw.extend(&digest[..4]);
-    raw.to_base58()
+    bs58::encode(raw).into_string()
 }
 
 /// Base58check decode.
 pub fn b58decode_check(s: &str) -> Result<Vec<u8>, AddressError> {
-    let mut result = s
-        .from_base58()
+    let mut result = bs58::decode(s)
+        .into_vec()
         .map_err(|_| AddressError::InvalidAddress("".to_string()))?;
 
     let check = result.split_off(result.len() - 4);
@@ -237,7 +242,13 @@ mod tests {
         assert_eq!(
             addr.as_bytes().encode_hex::<String>(),
             "4196a3bace5adacf637eb7cc79d5787f4247da4bbe"
-        )
+        );
+
+        assert_eq!(AnyNetwork, addr.network());
+        assert_eq!(TronFormat::Standard, addr.format());
+
+        assert!(TronAddress::from_str("").is_err());
+        assert!(TronAddress::from_str(&format!("é{}", "0".repeat(40))).is_err());
     }
 
     #[test]
@@ -4,9 +4,13 @@ pub use address::*;
 pub mod format;
 pub use format::*;
 
+pub mod message;
+pub use message::*;
+
 pub mod public_key;
 pub use public_key::*;
 
+#[allow(renamed_and_removed_lints)]
 pub mod protocol;
 
 pub mod transaction;
@@ -0,0 +1,81 @@
+//!
+//! Message
+//!
+//! This module implements the signed message of TIP-191, as signed by
+//! `signMessageV2` of TronWeb: the message is framed with
+//! "\x19TRON Signed Message:\n" and its decimal length, hashed with
+//! Keccak-256 and signed with a 65 byte r || s || v signature, where v is the
+//! recovery id plus 27.
+//!
+
+use crate::{TronAddress, TronFormat, TronPublicKey};
+use anychain_core::{
+    crypto::{keccak256, sign_deterministic},
+    libsecp256k1::{self, Message, RecoveryId, Signature},
+    Address, AddressError, MessageSigner, MessageVerifier,
+};
+
+/// Returns the hash signed by `signMessageV2` for 'message'
+pub fn tron_message_hash(message: &[u8]) -> [u8; 32] {
+    let prefix = format!("\x19TRON Signed Message:\n{}", message.len());
+    keccak256(&[prefix.as_bytes(), message].concat())
+}
+
+impl MessageSigner for TronAddress {
+    /// Returns the 65 byte TIP-191 signature of 'message' by 'secret_key'
+    fn sign_message(
+        secret_key: &Self::SecretKey,
+        _format: &Self::Format,
+        message: &[u8],
+    ) -> Result<Vec<u8>, AddressError> {
+        let hash = Message::parse(&tron_message_hash(message));
+        let (signature, recid) = sign_deterministic(&hash, secret_key);
+        Ok([&signature.serialize()[..], &[recid.serialize() + 27]].concat())
+    }
+}
+
+impl MessageVerifier for TronAddress {
+    /// Returns true if 'signature' is the TIP-191 signature of 'message' by
+    /// the key of this address. Both the 27/28 and the 0/1 encodings of v are
+    /// accepted.
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<bool, AddressError> {
+        let invalid = || AddressError::Message("invalid message signature".into());
+
+        if signature.len() != 65 {
+            return Err(invalid());
+        }
+        let recid = match signature[64] {
+            v @ (0 | 1) => v,
+            v @ (27 | 28) => v - 27,
+            _ => return Err(invalid()),
+        };
+        let recid = RecoveryId::parse(recid).map_err(|_| invalid())?;
+        let rs = Signature::parse_standard_slice(&signature[..64]).map_err(|_| invalid())?;
+
+        let hash = Message::parse(&tron_message_hash(message));
+        match libsecp256k1::recover(&hash, &rs, &recid) {
+            Ok(public_key) => Ok(Self::from_public_key(
+                &TronPublicKey::from_secp256k1_public_key(public_key),
+                &TronFormat::Standard,
+            )? == *self),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anychain_core::libsecp256k1::SecretKey;
+
+    #[test]
+    fn test_sign_message() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let address = TronAddress::from_secret_key(&secret_key, &TronFormat::Standard).unwrap();
+        let signature =
+            TronAddress::sign_message(&secret_key, &TronFormat::Standard, b"hello").unwrap();
+        assert!(address.verify_message(b"hello", &signature).unwrap());
+        assert!(!address.verify_message(b"hello!", &signature).unwrap());
+        assert!(address.verify_message(b"hello", &signature[..64]).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{TronAddress, TronFormat};
+use anychain_core::crypto::secp256k1_verify;
 use anychain_core::{libsecp256k1, Address, AddressError, PublicKey, PublicKeyError};
 use core::{fmt, fmt::Display, str::FromStr};
 
@@ -17,6 +18,13 @@ impl PublicKey for TronPublicKey {
     fn to_address(&self, format: &Self::Format) -> Result<Self::Address, AddressError> {
         TronAddress::from_public_key(self, format)
     }
+
+    /// Returns true if 'signature', the 64 byte r || s with an optional
+    /// recovery id, is a signature of the 32 byte 'message_hash' by this
+    /// public key.
+    fn verify(&self, message_hash: &[u8], signature: &[u8]) -> Result<bool, PublicKeyError> {
+        secp256k1_verify(&self.0, message_hash, signature)
+    }
 }
 
 impl TronPublicKey {
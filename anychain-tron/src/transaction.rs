@@ -23,9 +23,20 @@ pub struct TronTransactionParameters {
 }
 
 impl TronTransactionParameters {
-    pub fn set_ref_block(&mut self, number: i64, hash: &str) {
+    /// Sets the reference block to the block of height 'number' and hex
+    /// encoded 32 byte id 'hash', failing on malformed ids
+    pub fn set_ref_block(&mut self, number: i64, hash: &str) -> Result<(), TransactionError> {
+        let hash = hex::decode(hash)
+            .map_err(|e| TransactionError::Message(format!("invalid block id {}: {}", hash, e)))?;
+        if hash.len() != 32 {
+            return Err(TransactionError::Message(format!(
+                "invalid block id length {}",
+                hash.len()
+            )));
+        }
         self.ref_block_bytes = vec![((number & 0xff00) >> 8) as u8, (number & 0xff) as u8];
-        self.ref_block_hash = hex::decode(hash).unwrap()[8..16].to_owned();
+        self.ref_block_hash = hash[8..16].to_owned();
+        Ok(())
     }
 
     pub fn set_contract(&mut self, ct: Contract) {
@@ -40,11 +51,19 @@ impl TronTransactionParameters {
         self.expiration = time;
     }
 
+    /// Sets the most sun the energy of a smart contract call may burn. Calls
+    /// with a fee limit of 0 can only spend the energy of the owner.
     pub fn set_fee_limit(&mut self, fee: i64) {
         self.fee_limit = fee;
     }
 
     pub fn to_transaction_raw(&self) -> Result<TransactionRaw, TransactionError> {
+        if self.fee_limit < 0 {
+            return Err(TransactionError::Message(format!(
+                "negative fee limit {}",
+                self.fee_limit
+            )));
+        }
         let mut raw = TransactionRaw::new();
         let mut timestamp = self.timestamp;
         // if timestamp equals 0, means the tx is new
@@ -109,7 +128,7 @@ impl TransactionId for TronTransactionId {}
 
 impl fmt::Display for TronTransactionId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &hex::encode(&self.txid))
+        write!(f, "{}", hex::encode(&self.txid))
     }
 }
 
@@ -141,14 +160,49 @@ impl Transaction for TronTransaction {
         })
     }
 
+    /// Attaches the 64 byte signature {r,s} and its recovery id, and returns
+    /// the signed transaction
     fn sign(&mut self, signature: Vec<u8>, recid: u8) -> Result<Vec<u8>, TransactionError> {
+        if signature.len() != 64 || recid > 3 {
+            return Err(TransactionError::Message(format!(
+                "invalid signature of length {} and recovery id {}",
+                signature.len(),
+                recid
+            )));
+        }
         self.signature = Some(TronTransactionSignature::new(&signature, recid));
         self.to_bytes()
     }
 
+    /// Returns a transaction given its raw data, or given the signed
+    /// transaction wrapping its raw data and its signature
     fn from_bytes(transaction: &[u8]) -> Result<Self, TransactionError> {
-        let raw = TransactionRaw::parse_from_bytes(transaction)
-            .map_err(|e| TransactionError::Crate("protobuf", e.to_string()))?;
+        // the fields of signed transactions but their raw data are unknown to
+        // raw data, so signed transactions parse as raw data without contracts
+        let (raw, signature) = match TransactionRaw::parse_from_bytes(transaction) {
+            Ok(raw) if !raw.contract.is_empty() => (raw, None),
+            _ => {
+                let signed = TransactionProto::parse_from_bytes(transaction)
+                    .map_err(|e| TransactionError::Crate("protobuf", e.to_string()))?;
+                let signature = match &signed.signature[..] {
+                    [signature] if signature.len() == 65 => {
+                        TronTransactionSignature(signature.clone())
+                    }
+                    _ => return Err(TransactionError::MissingSignature),
+                };
+                let raw = signed
+                    .raw_data
+                    .into_option()
+                    .ok_or_else(|| TransactionError::Message("missing raw data".to_string()))?;
+                (raw, Some(signature))
+            }
+        };
+        if raw.contract.len() != 1 {
+            return Err(TransactionError::Message(format!(
+                "expected a contract, found {}",
+                raw.contract.len()
+            )));
+        }
         let param = TronTransactionParameters {
             timestamp: raw.timestamp,
             expiration: raw.expiration - raw.timestamp,
@@ -162,7 +216,7 @@ impl Transaction for TronTransaction {
 
         Ok(Self {
             data: param,
-            signature: None,
+            signature,
         })
     }
 
@@ -198,6 +252,7 @@ impl Transaction for TronTransaction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethabi::ethereum_types::U256;
 
     pub fn build_trx_transaction() -> TronTransaction {
         let addr_from = "TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvXr";
@@ -206,10 +261,12 @@ mod tests {
         let ct = trx::build_transfer_contract(addr_from, addr_to, amount).unwrap();
         let mut param = TronTransactionParameters::default();
         param.set_timestamp(trx::timestamp_millis());
-        param.set_ref_block(
-            26661399,
-            "000000000196d21784deb05dee04c69ed112b8e078e74019f9a0b1df6adc414e",
-        );
+        param
+            .set_ref_block(
+                26661399,
+                "000000000196d21784deb05dee04c69ed112b8e078e74019f9a0b1df6adc414e",
+            )
+            .unwrap();
         param.set_contract(ct);
 
         TronTransaction::new(&param).unwrap()
@@ -235,7 +292,9 @@ mod tests {
         let ct = trx::build_transfer_contract(from_addr, to_addr, amount).unwrap();
         let mut param = TronTransactionParameters::default();
         param.set_timestamp(trx::timestamp_millis());
-        param.set_ref_block(block_height, block_hash);
+        assert!(param.set_ref_block(block_height, &block_hash[2..]).is_err());
+        assert!(param.set_ref_block(block_height, "zz").is_err());
+        param.set_ref_block(block_height, block_hash).unwrap();
         param.set_contract(ct);
         let transaction = TronTransaction::new(&param).unwrap();
 
@@ -258,6 +317,68 @@ mod tests {
         assert_eq!(txid, transaction.to_transaction_id().unwrap().to_string());
     }
 
+    #[test]
+    pub fn test_signed_transaction() {
+        let raw = "0a0218902208f87d110a81d815b9409994dbfaac305a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541fa3146ab779ce02392d11209f524ee75d4088a45121541436d74fc1577266b7290b85801145d9c5287e19418c0843d70b9bfd7faac30900180ade204";
+        let mut transaction = TronTransaction::from_bytes(&hex::decode(raw).unwrap()).unwrap();
+        assert!(transaction.sign(vec![1; 63], 0).is_err());
+        let signed = transaction.sign(vec![1; 64], 1).unwrap();
+
+        let decoded = TronTransaction::from_bytes(&signed).unwrap();
+        assert_eq!(transaction, decoded);
+        assert_eq!(
+            transaction.to_transaction_id().unwrap(),
+            decoded.to_transaction_id().unwrap()
+        );
+
+        let transfer = trx::parse_transfer_contract(&decoded.data.contract).unwrap();
+        assert_eq!(
+            "41fa3146ab779ce02392d11209f524ee75d4088a45",
+            hex::encode(&transfer.owner_address)
+        );
+        assert_eq!(1_000_000, transfer.amount);
+    }
+
+    #[test]
+    pub fn test_trc20_transfer() {
+        let owner = "TG7jQ7eGsns6nmQNfcKNgZKyKBFkx7CvXr";
+        let usdt = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+        let recipient = "TFk5LfscQv8hYM11mZYmi3ZcnRfFc4LLap";
+        let ct = trx::build_trc20_transfer_contract(owner, usdt, recipient, "1000000").unwrap();
+        let mut param = TronTransactionParameters::default();
+        param.set_timestamp(1_700_000_000_000);
+        param
+            .set_ref_block(
+                26661399,
+                "000000000196d21784deb05dee04c69ed112b8e078e74019f9a0b1df6adc414e",
+            )
+            .unwrap();
+        param.set_contract(ct);
+        param.set_fee_limit(30_000_000);
+        let mut transaction = TronTransaction::new(&param).unwrap();
+        let signed = transaction.sign(vec![1; 64], 0).unwrap();
+
+        let decoded = TronTransaction::from_bytes(&signed).unwrap();
+        assert_eq!(30_000_000, decoded.data.fee_limit);
+        let transfer = trx::parse_trc20_transfer(&decoded.data.contract).unwrap();
+        assert_eq!(
+            trx::Trc20Transfer {
+                from: owner.parse().unwrap(),
+                to: recipient.parse().unwrap(),
+                token: usdt.parse().unwrap(),
+                amount: U256::from(1_000_000u64),
+            },
+            transfer
+        );
+        // a TRX transfer is no TRC-20 transfer
+        let ct = trx::build_transfer_contract(owner, recipient, "1").unwrap();
+        assert!(trx::parse_trc20_transfer(&ct).is_err());
+        assert!(trx::build_trc20_transfer_contract(owner, usdt, recipient, "-1").is_err());
+
+        param.set_fee_limit(-1);
+        assert!(TronTransaction::new(&param).unwrap().to_bytes().is_err());
+    }
+
     #[test]
     pub fn test_raw() {
         let raw = "0a025aa722088cb23bfcb18ea03c40facee394ad305a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541fa3146ab779ce02392d11209f524ee75d4088a45121541436d74fc1577266b7290b85801145d9c5287e19418c0843d709afadf94ad30900180ade204";
@@ -15,7 +15,7 @@ use crate::{
 };
 use anychain_core::Error;
 use chrono::Utc;
-use core::panic;
+use ethabi::ethereum_types::U256;
 use protobuf::{well_known_types::any::Any, EnumOrUnknown, Message};
 use std::str::FromStr;
 
@@ -50,11 +50,11 @@ impl_contract_pb_ext_for!(UnfreezeBalanceV2Contract);
 impl_contract_pb_ext_for!(DelegateResourceContract);
 impl_contract_pb_ext_for!(UnDelegateResourceContract);
 
-fn to_resource_code(r: u8) -> ResourceCode {
+fn to_resource_code(r: u8) -> Result<ResourceCode, Error> {
     match r {
-        0 => ResourceCode::BANDWIDTH,
-        1 => ResourceCode::ENERGY,
-        _ => panic!("Undefined resource"),
+        0 => Ok(ResourceCode::BANDWIDTH),
+        1 => Ok(ResourceCode::ENERGY),
+        _ => Err(Error::RuntimeError(format!("undefined resource {}", r))),
     }
 }
 
@@ -74,6 +74,72 @@ pub fn build_contract(ct: &impl ContractPbExt) -> Result<Contract, Error> {
     Ok(contract)
 }
 
+/// Returns the contract of type 'contract_type' that 'contract' wraps,
+/// failing on contracts of other types
+pub fn parse_contract<T: ContractPbExt>(
+    contract: &Contract,
+    contract_type: ContractType,
+) -> Result<T, Error> {
+    if contract.type_.enum_value() != Ok(contract_type) {
+        return Err(Error::RuntimeError(format!(
+            "expected a {:?}, found {:?}",
+            contract_type, contract.type_
+        )));
+    }
+    let parameter = contract
+        .parameter
+        .as_ref()
+        .ok_or_else(|| Error::RuntimeError("missing contract parameter".to_string()))?;
+    T::parse_from_bytes(&parameter.value).map_err(|e| Error::RuntimeError(e.to_string()))
+}
+
+/// Returns the TRX transfer that 'contract' wraps
+pub fn parse_transfer_contract(contract: &Contract) -> Result<TransferContract, Error> {
+    parse_contract(contract, ContractType::TransferContract)
+}
+
+/// Represents a TRC-20 transfer of 'amount' tokens of the contract 'token'
+/// from 'from' to 'to'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trc20Transfer {
+    pub from: TronAddress,
+    pub to: TronAddress,
+    pub token: TronAddress,
+    pub amount: U256,
+}
+
+/// Returns the TRC-20 transfer that 'contract' wraps, a `transfer` or a
+/// `transferFrom` call of a TriggerSmartContract, failing on other contracts
+/// and calls
+pub fn parse_trc20_transfer(contract: &Contract) -> Result<Trc20Transfer, Error> {
+    let trigger: TriggerSmartContract =
+        parse_contract(contract, ContractType::TriggerSmartContract)?;
+    let data = &trigger.data;
+    let invalid = || Error::RuntimeError(format!("not a TRC-20 transfer: {}", hex::encode(data)));
+
+    if data.len() < 4 || !(data.len() - 4).is_multiple_of(32) {
+        return Err(invalid());
+    }
+    let words: Vec<&[u8]> = data[4..].chunks(32).collect();
+    let (from, to, amount) = match (&data[..4], &words[..]) {
+        (selector, [to, amount]) if selector == abi::TRC20_TRANSFER => (
+            TronAddress::try_from(&trigger.owner_address)?,
+            abi::decode_address(to)?,
+            amount,
+        ),
+        (selector, [from, to, amount]) if selector == abi::TRC20_TRANSFER_FROM => {
+            (abi::decode_address(from)?, abi::decode_address(to)?, amount)
+        }
+        _ => return Err(invalid()),
+    };
+    Ok(Trc20Transfer {
+        from,
+        to,
+        token: TronAddress::try_from(&trigger.contract_address)?,
+        amount: U256::from_big_endian(amount),
+    })
+}
+
 pub fn build_trigger_contract(
     owner: &str,
     contract: &str,
@@ -88,22 +154,28 @@ pub fn build_trigger_contract(
     build_contract(&ts_contract)
 }
 
+/// Returns the TriggerSmartContract of the TRC-20 `transfer` of 'amount'
+/// tokens of the contract 'contract' from 'owner' to 'recipient', failing on
+/// invalid addresses and amounts
 pub fn build_trc20_transfer_contract(
     owner: &str,
     contract: &str,
     recipient: &str,
     amount: &str,
 ) -> Result<Contract, Error> {
-    build_trigger_contract(owner, contract, abi::trc20_transfer(recipient, amount))
+    build_trigger_contract(owner, contract, abi::trc20_transfer(recipient, amount)?)
 }
 
+/// Returns the TriggerSmartContract of the TRC-20 `approve` of 'amount'
+/// tokens of the contract 'contract' by 'owner' to 'recipient', failing on
+/// invalid addresses and amounts
 pub fn build_trc20_approve_contract(
     owner: &str,
     contract: &str,
     recipient: &str,
     amount: &str,
 ) -> Result<Contract, Error> {
-    build_trigger_contract(owner, contract, abi::trc20_approve(recipient, amount))
+    build_trigger_contract(owner, contract, abi::trc20_approve(recipient, amount)?)
 }
 
 pub fn build_transfer_contract(
@@ -142,7 +214,7 @@ pub fn build_freeze_balance_v2_contract(
 
     fb_v2_contract.owner_address = TronAddress::from_str(owner)?.as_bytes().to_vec();
     fb_v2_contract.frozen_balance = freeze_balance.parse::<i64>()?;
-    fb_v2_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource));
+    fb_v2_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource)?);
 
     build_contract(&fb_v2_contract)
 }
@@ -156,7 +228,7 @@ pub fn build_unfreeze_balance_v2_contract(
 
     ub_v2_contract.owner_address = TronAddress::from_str(owner)?.as_bytes().to_vec();
     ub_v2_contract.unfreeze_balance = unfreeze_balance.parse::<i64>()?;
-    ub_v2_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource));
+    ub_v2_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource)?);
 
     build_contract(&ub_v2_contract)
 }
@@ -173,7 +245,7 @@ pub fn build_delegate_resource_contract(
     dr_contract.owner_address = TronAddress::from_str(owner)?.as_bytes().to_vec();
     dr_contract.receiver_address = TronAddress::from_str(recipient)?.as_bytes().to_vec();
     dr_contract.balance = amount.parse::<i64>()?;
-    dr_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource));
+    dr_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource)?);
     dr_contract.lock = lock;
 
     build_contract(&dr_contract)
@@ -190,7 +262,7 @@ pub fn build_undelegate_resource_contract(
     ur_contract.owner_address = TronAddress::from_str(owner)?.as_bytes().to_vec();
     ur_contract.receiver_address = TronAddress::from_str(recipient)?.as_bytes().to_vec();
     ur_contract.balance = amount.parse::<i64>()?;
-    ur_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource));
+    ur_contract.resource = EnumOrUnknown::<ResourceCode>::new(to_resource_code(resource)?);
 
     build_contract(&ur_contract)
 }
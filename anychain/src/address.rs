@@ -0,0 +1,119 @@
+use crate::Chain;
+use anychain_bitcoin::BitcoinAddress;
+use anychain_core::{no_std::*, Address, AddressError, MessageVerifier};
+use anychain_ethereum::EthereumAddress;
+use core::{fmt, str::FromStr};
+
+macro_rules! define_address {
+    (bitcoin: [$($btc:ident),* $(,)?], ethereum: [$($eth:ident),* $(,)?] $(,)?) => {
+        /// An address of any network supported by the facade
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum AnyAddress {
+            $($btc(BitcoinAddress<anychain_bitcoin::$btc>),)*
+            $($eth(EthereumAddress),)*
+        }
+
+        impl AnyAddress {
+            /// Returns the address 'address' of network 'chain'
+            pub fn parse(chain: Chain, address: &str) -> Result<Self, AddressError> {
+                Ok(match chain {
+                    $(Chain::$btc => AnyAddress::$btc(BitcoinAddress::from_str(address)?),)*
+                    $(Chain::$eth => AnyAddress::$eth(EthereumAddress::from_str(address)?),)*
+                })
+            }
+
+            /// Returns the network of the address
+            pub fn chain(&self) -> Chain {
+                match self {
+                    $(AnyAddress::$btc(_) => Chain::$btc,)*
+                    $(AnyAddress::$eth(_) => Chain::$eth,)*
+                }
+            }
+
+            /// Returns the name of the format of the address, e.g. "p2wpkh"
+            pub fn format(&self) -> String {
+                match self {
+                    $(AnyAddress::$btc(address) => Address::format(address).to_string(),)*
+                    $(AnyAddress::$eth(address) => address.format().to_string(),)*
+                }
+            }
+
+            /// Returns true if 'signature' is a signature of 'message' by the key
+            /// of the address, in the signed message format of its chain
+            pub fn verify_message(
+                &self,
+                message: &[u8],
+                signature: &[u8],
+            ) -> Result<bool, AddressError> {
+                match self {
+                    $(AnyAddress::$btc(address) => address.verify_message(message, signature),)*
+                    $(AnyAddress::$eth(address) => address.verify_message(message, signature),)*
+                }
+            }
+        }
+
+        impl fmt::Display for AnyAddress {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    $(AnyAddress::$btc(address) => write!(f, "{}", address),)*
+                    $(AnyAddress::$eth(address) => write!(f, "{}", address),)*
+                }
+            }
+        }
+    };
+}
+
+with_chains!(define_address);
+
+impl AnyAddress {
+    /// Returns true if 'address' is a valid address of network 'chain'
+    pub fn is_valid(chain: Chain, address: &str) -> bool {
+        Self::parse(chain, address).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_address() {
+        let address =
+            AnyAddress::parse(Chain::Bitcoin, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+                .unwrap();
+        assert_eq!(Chain::Bitcoin, address.chain());
+        assert_eq!(
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            address.to_string()
+        );
+
+        // the same address is valid on every EVM network
+        let evm = "0x3535353535353535353535353535353535353535";
+        assert_eq!(
+            Chain::Polygon,
+            AnyAddress::parse(Chain::Polygon, evm).unwrap().chain()
+        );
+        assert!(AnyAddress::is_valid(Chain::Ethereum, evm));
+
+        // a mainnet address is not valid on testnet
+        assert!(!AnyAddress::is_valid(
+            Chain::BitcoinTestnet,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        ));
+        assert!(!AnyAddress::is_valid(Chain::Ethereum, "0x35"));
+    }
+
+    #[test]
+    fn test_verify_message() {
+        use anychain_core::{libsecp256k1::SecretKey, MessageSigner};
+
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let format = anychain_ethereum::EthereumFormat::Standard;
+        let address = EthereumAddress::from_secret_key(&secret_key, &format).unwrap();
+        let signature = EthereumAddress::sign_message(&secret_key, &format, b"hello").unwrap();
+
+        let address = AnyAddress::parse(Chain::Arbitrum, &address.to_string()).unwrap();
+        assert!(address.verify_message(b"hello", &signature).unwrap());
+        assert!(!address.verify_message(b"bye", &signature).unwrap());
+    }
+}
@@ -0,0 +1,97 @@
+use anychain_bitcoin::BitcoinAmount;
+use anychain_core::{no_std::*, Amount, Network, NetworkError};
+use anychain_ethereum::EthereumAmount;
+use core::{fmt, str::FromStr};
+
+/// The chain crate implementing the addresses and transactions of a network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    Bitcoin,
+    Ethereum,
+}
+
+macro_rules! define_chain {
+    (bitcoin: [$($btc:ident),* $(,)?], ethereum: [$($eth:ident),* $(,)?] $(,)?) => {
+        /// Identifies a network supported by the facade
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum Chain {
+            $($btc,)*
+            $($eth,)*
+        }
+
+        impl Chain {
+            /// Every network supported by the facade
+            pub const ALL: &'static [Chain] = &[$(Chain::$btc,)* $(Chain::$eth,)*];
+
+            /// Returns the name of the network, e.g. "bitcoin testnet"
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Chain::$btc => <anychain_bitcoin::$btc as Network>::NAME,)*
+                    $(Chain::$eth => <anychain_ethereum::$eth as Network>::NAME,)*
+                }
+            }
+
+            /// Returns the chain crate of the network
+            pub fn family(&self) -> Family {
+                match self {
+                    $(Chain::$btc => Family::Bitcoin,)*
+                    $(Chain::$eth => Family::Ethereum,)*
+                }
+            }
+        }
+    };
+}
+
+with_chains!(define_chain);
+
+impl Chain {
+    /// Returns the number of decimal places of the main unit of the network
+    /// in its smallest unit
+    pub fn decimals(&self) -> u32 {
+        match self.family() {
+            Family::Bitcoin => BitcoinAmount::DECIMALS,
+            Family::Ethereum => EthereumAmount::DECIMALS,
+        }
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = NetworkError;
+
+    /// Returns the network of a name, e.g. "bitcoin testnet"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Chain::ALL
+            .iter()
+            .find(|chain| chain.name() == s)
+            .copied()
+            .ok_or_else(|| NetworkError::InvalidNetwork(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        for chain in Chain::ALL {
+            assert_eq!(*chain, Chain::from_str(&chain.to_string()).unwrap());
+        }
+        assert_eq!(
+            Chain::BitcoinTestnet,
+            Chain::from_str("bitcoin testnet").unwrap()
+        );
+        assert_eq!(Family::Ethereum, Chain::Polygon.family());
+        assert_eq!(
+            (8, 18),
+            (Chain::Litecoin.decimals(), Chain::Base.decimals())
+        );
+        assert!(Chain::from_str("bitcoin mainnet").is_err());
+    }
+}
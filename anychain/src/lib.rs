@@ -0,0 +1,64 @@
+//! # Anychain
+//!
+//! A facade over the chain crates for wallets handling many currencies, which
+//! parse, validate and serialize the addresses and transactions of a chain
+//! chosen at runtime instead of being generic over every network type.
+//!
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(unused_extern_crates, dead_code)]
+#![forbid(unsafe_code)]
+
+pub use anychain_bitcoin as bitcoin;
+pub use anychain_core as model;
+pub use anychain_ethereum as ethereum;
+
+/// Invokes '$macro' with the networks of the facade, grouped by chain crate
+macro_rules! with_chains {
+    ($macro:ident) => {
+        $macro! {
+            bitcoin: [
+                Bitcoin,
+                BitcoinTestnet,
+                Signet,
+                Testnet4,
+                BitcoinCash,
+                BitcoinCashTestnet,
+                Litecoin,
+                LitecoinTestnet,
+                Dogecoin,
+                DogecoinTestnet,
+            ],
+            ethereum: [
+                Ethereum,
+                Goerli,
+                EthereumClassic,
+                Kotti,
+                Polygon,
+                Mumbai,
+                Avalanche,
+                AvalancheTestnet,
+                Arbitrum,
+                ArbitrumGoerli,
+                Optimism,
+                OptimismGoerli,
+                Base,
+                BaseGoerli,
+                HuobiEco,
+                HuobiEcoTestnet,
+                BinanceSmartChain,
+                BinanceSmartChainTestnet,
+                Okex,
+                OkexTestnet,
+            ],
+        }
+    };
+}
+
+pub mod address;
+pub use self::address::*;
+
+pub mod chain;
+pub use self::chain::*;
+
+pub mod transaction;
+pub use self::transaction::*;
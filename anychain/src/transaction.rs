@@ -0,0 +1,83 @@
+use crate::Chain;
+use anychain_bitcoin::BitcoinTransaction;
+use anychain_core::{hex, no_std::*, Transaction, TransactionError};
+use anychain_ethereum::EthereumTransaction;
+use core::fmt;
+
+macro_rules! define_transaction {
+    (bitcoin: [$($btc:ident),* $(,)?], ethereum: [$($eth:ident),* $(,)?] $(,)?) => {
+        /// A transaction of any network supported by the facade
+        #[derive(Debug, Clone)]
+        pub enum AnyTransaction {
+            $($btc(BitcoinTransaction<anychain_bitcoin::$btc>),)*
+            $($eth(EthereumTransaction<anychain_ethereum::$eth>),)*
+        }
+
+        impl AnyTransaction {
+            /// Returns the transaction of network 'chain' serialized as 'transaction'
+            pub fn from_bytes(chain: Chain, transaction: &[u8]) -> Result<Self, TransactionError> {
+                Ok(match chain {
+                    $(Chain::$btc => AnyTransaction::$btc(BitcoinTransaction::from_bytes(transaction)?),)*
+                    $(Chain::$eth => AnyTransaction::$eth(EthereumTransaction::from_bytes(transaction)?),)*
+                })
+            }
+
+            /// Returns the network of the transaction
+            pub fn chain(&self) -> Chain {
+                match self {
+                    $(AnyTransaction::$btc(_) => Chain::$btc,)*
+                    $(AnyTransaction::$eth(_) => Chain::$eth,)*
+                }
+            }
+
+            /// Returns the serialized transaction
+            pub fn to_bytes(&self) -> Result<Vec<u8>, TransactionError> {
+                match self {
+                    $(AnyTransaction::$btc(transaction) => transaction.to_bytes(),)*
+                    $(AnyTransaction::$eth(transaction) => transaction.to_bytes(),)*
+                }
+            }
+
+            /// Returns the transaction id in the form shown by explorers of the network
+            pub fn transaction_id(&self) -> Result<String, TransactionError> {
+                Ok(match self {
+                    $(AnyTransaction::$btc(transaction) => transaction.to_transaction_id()?.to_string(),)*
+                    $(AnyTransaction::$eth(transaction) => transaction.to_transaction_id()?.to_string(),)*
+                })
+            }
+        }
+    };
+}
+
+with_chains!(define_transaction);
+
+impl fmt::Display for AnyTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.to_bytes().map_err(|_| fmt::Error)?;
+        write!(f, "{}", hex::encode(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the EIP-155 example transaction
+    const ETHEREUM: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    #[test]
+    fn test_any_transaction() {
+        let bytes = hex::decode(ETHEREUM).unwrap();
+        let transaction = AnyTransaction::from_bytes(Chain::Ethereum, &bytes).unwrap();
+        assert_eq!(Chain::Ethereum, transaction.chain());
+        assert_eq!(ETHEREUM, transaction.to_string());
+        assert_eq!(
+            "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788",
+            transaction.transaction_id().unwrap()
+        );
+
+        // the signature commits to chain id 1
+        assert!(AnyTransaction::from_bytes(Chain::Polygon, &bytes).is_err());
+        assert!(AnyTransaction::from_bytes(Chain::Bitcoin, &bytes[..4]).is_err());
+    }
+}